@@ -0,0 +1,91 @@
+//! Allocation slack: bytes a heap allocator rounds a struct up to, beyond its size.
+
+use crate::types::AllocSlack;
+use serde::{Deserialize, Serialize};
+
+/// Size-class model of a 64-bit heap allocator.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum AllocatorModel {
+    /// 8, 16, then 16-byte steps to 128, then four classes per power of two.
+    #[default]
+    Jemalloc,
+    /// 8-byte steps to 64, then four classes per power of two.
+    Mimalloc,
+    /// ptmalloc chunks: the size plus an 8-byte header, rounded up to 16, at least 32 bytes.
+    Glibc,
+}
+
+impl AllocatorModel {
+    pub fn name(self) -> &'static str {
+        match self {
+            AllocatorModel::Jemalloc => "jemalloc",
+            AllocatorModel::Mimalloc => "mimalloc",
+            AllocatorModel::Glibc => "glibc",
+        }
+    }
+
+    /// Bytes consumed by a single allocation of `size` bytes.
+    pub fn size_class(self, size: u64) -> u64 {
+        let size = size.max(1);
+        match self {
+            AllocatorModel::Jemalloc => match size {
+                ..=8 => 8,
+                9..=128 => size.next_multiple_of(16),
+                _ => quarter_power_class(size),
+            },
+            AllocatorModel::Mimalloc => match size {
+                ..=64 => size.next_multiple_of(8),
+                _ => quarter_power_class(size),
+            },
+            AllocatorModel::Glibc => size.saturating_add(8).next_multiple_of(16).max(32),
+        }
+    }
+
+    pub fn slack(self, size: u64) -> AllocSlack {
+        let size_class = self.size_class(size);
+        AllocSlack { size_class, slack_bytes: size_class - size.min(size_class) }
+    }
+}
+
+/// Round up to one of four evenly spaced classes between consecutive powers of two
+/// (e.g. 160, 192, 224, 256 above 128).
+fn quarter_power_class(size: u64) -> u64 {
+    let group = 63 - (size - 1).leading_zeros();
+    let step = 1u64 << group.saturating_sub(2);
+    size.checked_next_multiple_of(step).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jemalloc_classes() {
+        let classes: Vec<u64> = [1, 9, 17, 64, 65, 72, 129, 200, 257, 1000]
+            .iter()
+            .map(|&s| AllocatorModel::Jemalloc.size_class(s))
+            .collect();
+        assert_eq!(classes, vec![8, 16, 32, 64, 80, 80, 160, 224, 320, 1024]);
+    }
+
+    #[test]
+    fn mimalloc_and_glibc_classes() {
+        assert_eq!(AllocatorModel::Mimalloc.size_class(24), 24);
+        assert_eq!(AllocatorModel::Mimalloc.size_class(65), 80);
+        assert_eq!(AllocatorModel::Mimalloc.size_class(130), 160);
+        assert_eq!(AllocatorModel::Glibc.size_class(1), 32);
+        assert_eq!(AllocatorModel::Glibc.size_class(24), 32);
+        assert_eq!(AllocatorModel::Glibc.size_class(72), 80);
+    }
+
+    #[test]
+    fn slack_is_class_minus_size() {
+        let slack = AllocatorModel::Jemalloc.slack(129);
+        assert_eq!(slack.size_class, 160);
+        assert_eq!(slack.slack_bytes, 31);
+        assert_eq!(AllocatorModel::Jemalloc.slack(128).slack_bytes, 0);
+    }
+}
@@ -0,0 +1,185 @@
+//! Padding attribution for C++ base class subobjects.
+
+use super::analyze_layout;
+use crate::types::{BaseAttribution, MemberLayout, MemberRole, StructLayout};
+
+/// Attribute padding to each base class subobject of `layout` and detect whether the derived
+/// class reused a base's tail padding for its own members (allowed by the Itanium C++ ABI for
+/// non-POD bases).
+///
+/// Bases without a known offset, size, or layout are skipped.
+pub fn analyze_bases(layout: &StructLayout) -> Vec<BaseAttribution> {
+    let mut bases = Vec::new();
+
+    for member in &layout.members {
+        let Some(base) = member.base_layout.as_deref() else {
+            continue;
+        };
+        let (Some(offset), Some(size)) = (member.offset, member.size) else {
+            continue;
+        };
+
+        let mut base = base.clone();
+        // Padding totals don't depend on the cache line size.
+        analyze_layout(&mut base, 64);
+
+        // The base's own virtual bases live with the most-derived object, not in this subobject.
+        let data_size = base
+            .members
+            .iter()
+            .filter(|m| m.role != Some(MemberRole::VirtualBase))
+            .filter_map(|m| Some(m.offset?.saturating_add(m.size?)))
+            .max()
+            .unwrap_or(0)
+            .min(size);
+
+        let tail_start = offset.saturating_add(data_size);
+        let tail_end = offset.saturating_add(size);
+        let tail_reused_by: Vec<String> = layout
+            .members
+            .iter()
+            .filter(|m| !std::ptr::eq(*m, member))
+            .filter(|m| m.offset.is_some_and(|o| o >= tail_start && o < tail_end))
+            .map(|m| m.name.clone())
+            .collect();
+
+        bases.push(BaseAttribution {
+            name: member.name.clone(),
+            type_name: member.type_name.clone(),
+            offset,
+            size,
+            data_size,
+            padding_bytes: base.metrics.padding_bytes,
+            tail_padding: size - data_size,
+            tail_padding_reused: !tail_reused_by.is_empty(),
+            is_virtual: member.role == Some(MemberRole::VirtualBase),
+            tail_reused_by,
+        });
+    }
+
+    bases
+}
+
+/// The complete-object view of `layout`: every `<base: X>` member whose layout is known is
+/// replaced by that base's own members (recursively, vptrs included), shifted to their absolute
+/// offsets and named `X::member`. Metrics are left for `analyze_layout` to recompute.
+///
+/// Bases without a known offset or layout (e.g. virtual bases) keep their single row.
+pub fn flatten_bases(layout: &StructLayout) -> StructLayout {
+    let mut flat = layout.clone();
+    flat.members = Vec::with_capacity(layout.members.len());
+    flatten_into(&mut flat.members, layout, None, 0);
+    flat.members.sort_by_key(|m| (m.offset.is_none(), m.offset, m.bit_offset));
+    flat
+}
+
+fn flatten_into(
+    out: &mut Vec<MemberLayout>,
+    layout: &StructLayout,
+    owner: Option<&str>,
+    shift: u64,
+) {
+    for member in &layout.members {
+        if let (Some(base), Some(offset)) = (member.base_layout.as_deref(), member.offset) {
+            flatten_into(out, base, Some(&member.type_name), shift.saturating_add(offset));
+            continue;
+        }
+        let mut member = member.clone();
+        member.offset = member.offset.map(|o| o.saturating_add(shift));
+        if let Some(owner) = owner {
+            member.name = format!("{}::{}", owner, member.name);
+        }
+        out.push(member);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    fn base() -> StructLayout {
+        // struct Base { long a; char b; };  -> 16 bytes, 7 bytes tail padding
+        let mut base = StructLayout::new("Base".to_string(), 16, Some(8));
+        base.members = vec![
+            MemberLayout::new("a".to_string(), "long".to_string(), Some(0), Some(8)),
+            MemberLayout::new("b".to_string(), "char".to_string(), Some(8), Some(1)),
+        ];
+        base
+    }
+
+    fn derived(c_offset: u64) -> StructLayout {
+        let mut base_member =
+            MemberLayout::new("<base: Base>".to_string(), "Base".to_string(), Some(0), Some(16));
+        base_member.base_layout = Some(Box::new(base()));
+
+        let mut layout = StructLayout::new("Derived".to_string(), 24, Some(8));
+        layout.members = vec![
+            base_member,
+            MemberLayout::new("c".to_string(), "char".to_string(), Some(c_offset), Some(1)),
+        ];
+        layout
+    }
+
+    #[test]
+    fn reports_base_padding_and_tail_reuse() {
+        let bases = analyze_bases(&derived(9));
+        assert_eq!(bases.len(), 1);
+        let b = &bases[0];
+        assert_eq!(b.size, 16);
+        assert_eq!(b.data_size, 9);
+        assert_eq!(b.tail_padding, 7);
+        assert_eq!(b.padding_bytes, 7);
+        assert!(b.tail_padding_reused);
+        assert_eq!(b.tail_reused_by, vec!["c"]);
+    }
+
+    #[test]
+    fn member_after_base_does_not_reuse_tail() {
+        let bases = analyze_bases(&derived(16));
+        assert!(!bases[0].tail_padding_reused);
+        assert!(bases[0].tail_reused_by.is_empty());
+    }
+
+    #[test]
+    fn flattening_inlines_nested_bases_at_absolute_offsets() {
+        // struct Mid : Base { int m; };  struct Leaf : Mid { char c; };
+        let mut base_member =
+            MemberLayout::new("<base: Base>".to_string(), "Base".to_string(), Some(0), Some(16));
+        base_member.base_layout = Some(Box::new(base()));
+        let mut mid = StructLayout::new("Mid".to_string(), 24, Some(8));
+        mid.members = vec![
+            base_member,
+            MemberLayout::new("m".to_string(), "int".to_string(), Some(16), Some(4)),
+        ];
+        let mut mid_member =
+            MemberLayout::new("<base: Mid>".to_string(), "Mid".to_string(), Some(8), Some(24));
+        mid_member.base_layout = Some(Box::new(mid));
+        let mut leaf = StructLayout::new("Leaf".to_string(), 40, Some(8));
+        leaf.members = vec![
+            MemberLayout::new("_vptr$Leaf".to_string(), "**fn".to_string(), Some(0), Some(8)),
+            mid_member,
+            MemberLayout::new("c".to_string(), "char".to_string(), Some(32), Some(1)),
+        ];
+
+        let flat = flatten_bases(&leaf);
+        let rows: Vec<_> =
+            flat.members.iter().map(|m| (m.name.as_str(), m.offset.unwrap())).collect();
+        assert_eq!(
+            rows,
+            vec![("_vptr$Leaf", 0), ("Base::a", 8), ("Base::b", 16), ("Mid::m", 24), ("c", 32)]
+        );
+        assert!(flat.members.iter().all(|m| m.base_layout.is_none()));
+
+        let mut flat = flat;
+        analyze_layout(&mut flat, 64);
+        assert_eq!(flat.metrics.padding_bytes, 7 + 4 + 7);
+    }
+
+    #[test]
+    fn members_without_base_layout_are_ignored() {
+        let mut layout = derived(16);
+        layout.members[0].base_layout = None;
+        assert!(analyze_bases(&layout).is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+//! Bit-granular layout for structs made of bitfields, such as hardware register maps.
+
+use crate::types::{BitGap, BitLayout, BitRange, StructLayout};
+
+/// True if every member of `layout` is a bitfield.
+pub fn is_bitfield_only(layout: &StructLayout) -> bool {
+    !layout.members.is_empty() && layout.members.iter().all(|m| m.bit_size.is_some())
+}
+
+/// The bit range of each member (whole bytes for non-bitfield members) and the runs of bits
+/// no member covers. `None` if any member's position or size is unknown.
+pub fn analyze_bits(layout: &StructLayout) -> Option<BitLayout> {
+    let total_bits = layout.size.checked_mul(8)?;
+
+    let mut fields = Vec::with_capacity(layout.members.len());
+    for member in &layout.members {
+        let offset = member.offset?.checked_mul(8)?;
+        let (start_bit, bits) = match member.bit_size {
+            Some(bits) => (offset.checked_add(member.bit_offset?)?, bits),
+            None => (offset, member.size?.checked_mul(8)?),
+        };
+        fields.push(BitRange { name: member.name.clone(), start_bit, bits });
+    }
+    fields.sort_by_key(|f| (f.start_bit, f.bits));
+
+    let mut gaps = Vec::new();
+    let mut used_bits = 0;
+    let mut covered = 0u64;
+    for field in fields.iter().filter(|f| f.bits > 0) {
+        let start = field.start_bit.min(total_bits);
+        let end = field.start_bit.saturating_add(field.bits).min(total_bits);
+        if start > covered {
+            gaps.push(BitGap { start_bit: covered, bits: start - covered });
+        }
+        used_bits += end.saturating_sub(start.max(covered));
+        covered = covered.max(end);
+    }
+    if covered < total_bits {
+        gaps.push(BitGap { start_bit: covered, bits: total_bits - covered });
+    }
+
+    Some(BitLayout { total_bits, used_bits, fields, gaps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    fn bitfield(name: &str, bit_offset: u64, bit_size: u64) -> MemberLayout {
+        let mut member = MemberLayout::new(name.to_string(), "u32".to_string(), Some(0), Some(4));
+        member.bit_offset = Some(bit_offset);
+        member.bit_size = Some(bit_size);
+        member
+    }
+
+    #[test]
+    fn reports_bit_ranges_and_gaps() {
+        // struct Reg { u32 en:1; u32 :3; u32 mode:2; u32 :26; };
+        let mut layout = StructLayout::new("Reg".to_string(), 4, Some(4));
+        layout.members = vec![bitfield("mode", 4, 2), bitfield("en", 0, 1)];
+        assert!(is_bitfield_only(&layout));
+
+        let bits = analyze_bits(&layout).unwrap();
+        assert_eq!(bits.total_bits, 32);
+        assert_eq!(bits.used_bits, 3);
+        assert_eq!(bits.fields[0], BitRange { name: "en".to_string(), start_bit: 0, bits: 1 });
+        assert_eq!(bits.fields[1].start_bit, 4);
+        assert_eq!(
+            bits.gaps,
+            vec![BitGap { start_bit: 1, bits: 3 }, BitGap { start_bit: 6, bits: 26 }]
+        );
+    }
+
+    #[test]
+    fn plain_members_count_whole_bytes() {
+        let mut layout = StructLayout::new("Mixed".to_string(), 8, Some(4));
+        layout.members = vec![
+            MemberLayout::new("tag".to_string(), "u8".to_string(), Some(0), Some(1)),
+            MemberLayout::new("value".to_string(), "u32".to_string(), Some(4), Some(4)),
+        ];
+        assert!(!is_bitfield_only(&layout));
+
+        let bits = analyze_bits(&layout).unwrap();
+        assert_eq!(bits.used_bits, 40);
+        assert_eq!(bits.gaps, vec![BitGap { start_bit: 8, bits: 24 }]);
+
+        layout.members[1].offset = None;
+        assert!(analyze_bits(&layout).is_none());
+    }
+}
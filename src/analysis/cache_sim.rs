@@ -0,0 +1,246 @@
+//! Cache line simulation for a sequential pass over a declared member access sequence.
+
+use super::optimize::{OptimizedLayout, OptimizedMember, optimize_layout_with_priority};
+use crate::types::StructLayout;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashSet};
+
+/// Result of simulating one pass over a member access sequence.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessSimulation {
+    /// Member names in the order they are accessed.
+    pub sequence: Vec<String>,
+    /// Distinct cache lines touched with the current layout.
+    pub original_lines_touched: u64,
+    /// Distinct cache lines touched with the padding-minimizing suggestion.
+    pub optimized_lines_touched: u64,
+    /// Distinct cache lines touched with the access-locality ordering.
+    pub locality_lines_touched: u64,
+    /// Size of the struct with the access-locality ordering.
+    pub locality_size: u64,
+    /// Member ordering that packs the accessed members into as few cache lines as possible.
+    pub locality_members: Vec<OptimizedMember>,
+    /// Bytes the pass loads with the current layout against the bytes it uses; `None` when no
+    /// member of the sequence has a known offset and size.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub over_fetch: Option<OverFetch>,
+    /// Names from the sequence that don't match any member with a known offset and size.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unknown_members: Vec<String>,
+    /// True if the sequence came from static access analysis rather than the config.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub from_static_analysis: bool,
+}
+
+/// Whole cache lines a read of some members loads, against the bytes it uses.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+pub struct OverFetch {
+    /// Bytes of the members read; members sharing storage (bitfields) count once.
+    pub bytes_used: u64,
+    /// Bytes of every touched line, whole.
+    pub bytes_loaded: u64,
+    /// Distinct cache lines the read touches.
+    pub lines_touched: u64,
+    /// Fewest lines the read could touch: its members packed together from a line boundary.
+    pub min_lines_touched: u64,
+    /// `bytes_loaded` per byte used; 1.0 when nothing is wasted.
+    pub ratio: f64,
+}
+
+/// Over-fetch of one read of the members named in `sequence` with `layout` as it is. `None`
+/// when none of them has a known offset and size.
+///
+/// # Panics
+/// Panics if `cache_line_size` is 0.
+pub fn over_fetch(
+    layout: &StructLayout,
+    sequence: &[String],
+    cache_line_size: u32,
+) -> Option<OverFetch> {
+    assert!(cache_line_size > 0, "cache_line_size must be > 0");
+    let line = cache_line_size as u64;
+    let members: Vec<(&str, u64, u64)> =
+        layout.members.iter().filter_map(|m| Some((m.name.as_str(), m.offset?, m.size?))).collect();
+
+    let read: BTreeSet<(u64, u64)> = sequence
+        .iter()
+        .filter_map(|name| members.iter().find(|(n, _, _)| n == name))
+        .map(|&(_, offset, size)| (offset, size))
+        .collect();
+    let bytes_used: u64 = read.iter().map(|&(_, size)| size).sum();
+    if bytes_used == 0 {
+        return None;
+    }
+    let lines_touched = lines_touched(&members, sequence, line);
+    let bytes_loaded = lines_touched * line;
+    Some(OverFetch {
+        bytes_used,
+        bytes_loaded,
+        lines_touched,
+        min_lines_touched: bytes_used.div_ceil(line),
+        ratio: bytes_loaded as f64 / bytes_used as f64,
+    })
+}
+
+/// Simulate cache line touches for one pass over `sequence` with the current layout, the
+/// padding-optimized layout, and a reorder that packs the accessed members first.
+///
+/// # Panics
+/// Panics if `cache_line_size` is 0.
+pub fn simulate_access(
+    layout: &StructLayout,
+    optimized: &OptimizedLayout,
+    sequence: &[String],
+    cache_line_size: u32,
+    max_align: u64,
+) -> AccessSimulation {
+    assert!(cache_line_size > 0, "cache_line_size must be > 0");
+    let line = cache_line_size as u64;
+
+    let original: Vec<(&str, u64, u64)> =
+        layout.members.iter().filter_map(|m| Some((m.name.as_str(), m.offset?, m.size?))).collect();
+
+    let unknown_members: Vec<String> = sequence
+        .iter()
+        .filter(|name| !original.iter().any(|(n, _, _)| n == name))
+        .cloned()
+        .collect();
+
+    let priority: HashSet<&str> = sequence.iter().map(String::as_str).collect();
+    let locality = optimize_layout_with_priority(layout, max_align, &priority);
+
+    AccessSimulation {
+        sequence: sequence.to_vec(),
+        original_lines_touched: lines_touched(&original, sequence, line),
+        optimized_lines_touched: lines_touched(
+            &placements(&optimized.optimized_members),
+            sequence,
+            line,
+        ),
+        locality_lines_touched: lines_touched(
+            &placements(&locality.optimized_members),
+            sequence,
+            line,
+        ),
+        locality_size: locality.optimized_size,
+        locality_members: locality.optimized_members,
+        over_fetch: over_fetch(layout, sequence, cache_line_size),
+        unknown_members,
+        from_static_analysis: false,
+    }
+}
+
+/// Members with a nonzero static popularity, most popular first (ties in offset order).
+/// Used as the access sequence when no access pattern is configured for the struct.
+pub fn static_access_sequence(layout: &StructLayout) -> Vec<String> {
+    let mut popular: Vec<(u32, u64, &str)> = layout
+        .members
+        .iter()
+        .filter_map(|m| {
+            let popularity = m.static_popularity.filter(|&p| p > 0)?;
+            Some((popularity, m.offset.unwrap_or(u64::MAX), m.name.as_str()))
+        })
+        .collect();
+    popular.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    popular.into_iter().map(|(_, _, name)| name.to_string()).collect()
+}
+
+fn placements(members: &[OptimizedMember]) -> Vec<(&str, u64, u64)> {
+    members.iter().map(|m| (m.name.as_str(), m.offset, m.size)).collect()
+}
+
+/// Count distinct cache lines covered by the members named in `sequence`.
+fn lines_touched(members: &[(&str, u64, u64)], sequence: &[String], line: u64) -> u64 {
+    let mut lines: BTreeSet<u64> = BTreeSet::new();
+    for name in sequence {
+        let Some(&(_, offset, size)) = members.iter().find(|(n, _, _)| n == name) else {
+            continue;
+        };
+        if size == 0 {
+            continue;
+        }
+        let Some(last_byte) = offset.checked_add(size - 1) else {
+            continue;
+        };
+        lines.extend(offset / line..=last_byte / line);
+    }
+    lines.len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::optimize_layout;
+    use crate::types::MemberLayout;
+
+    fn hot_cold_layout() -> StructLayout {
+        // hot_a at 0, 120 bytes of cold data, hot_b at 128: a pass over the hot
+        // members touches two 64-byte lines although both fit in one.
+        let mut layout = StructLayout::new("HotCold".to_string(), 136, Some(8));
+        layout.members = vec![
+            MemberLayout::new("hot_a".to_string(), "u64".to_string(), Some(0), Some(8)),
+            MemberLayout::new("cold".to_string(), "[u8; 120]".to_string(), Some(8), Some(120)),
+            MemberLayout::new("hot_b".to_string(), "u64".to_string(), Some(128), Some(8)),
+        ];
+        layout
+    }
+
+    #[test]
+    fn locality_ordering_reduces_lines_touched() {
+        let layout = hot_cold_layout();
+        let optimized = optimize_layout(&layout, 8);
+        let sequence = vec!["hot_a".to_string(), "hot_b".to_string()];
+
+        let sim = simulate_access(&layout, &optimized, &sequence, 64, 8);
+
+        assert_eq!(sim.original_lines_touched, 2);
+        assert_eq!(sim.locality_lines_touched, 1);
+        assert_eq!(sim.locality_members[0].name, "hot_a");
+        assert_eq!(sim.locality_members[1].name, "hot_b");
+        assert_eq!(sim.locality_size, 136);
+        assert!(sim.unknown_members.is_empty());
+    }
+
+    #[test]
+    fn over_fetch_compares_lines_loaded_with_bytes_used() {
+        let layout = hot_cold_layout();
+        let sequence = vec!["hot_a".to_string(), "hot_b".to_string(), "missing".to_string()];
+
+        let fetch = over_fetch(&layout, &sequence, 64).expect("known members");
+        assert_eq!((fetch.bytes_used, fetch.lines_touched, fetch.min_lines_touched), (16, 2, 1));
+        assert_eq!(fetch.ratio, 8.0);
+        assert!(over_fetch(&layout, &["missing".to_string()], 64).is_none());
+
+        let optimized = optimize_layout(&layout, 8);
+        let sim = simulate_access(&layout, &optimized, &sequence, 64, 8);
+        assert_eq!(sim.over_fetch, Some(fetch));
+    }
+
+    #[test]
+    fn static_sequence_orders_by_popularity() {
+        let mut layout = hot_cold_layout();
+        layout.members[0].static_popularity = Some(2);
+        layout.members[1].static_popularity = Some(0);
+        layout.members[2].static_popularity = Some(5);
+
+        assert_eq!(static_access_sequence(&layout), vec!["hot_b", "hot_a"]);
+    }
+
+    #[test]
+    fn unknown_members_are_reported() {
+        let layout = hot_cold_layout();
+        let optimized = optimize_layout(&layout, 8);
+        let sequence = vec!["hot_a".to_string(), "missing".to_string()];
+
+        let sim = simulate_access(&layout, &optimized, &sequence, 64, 8);
+
+        assert_eq!(sim.unknown_members, vec!["missing"]);
+        assert_eq!(sim.original_lines_touched, 1);
+    }
+
+    #[test]
+    fn member_spanning_lines_counts_each_line() {
+        let members = [("big", 60, 8)];
+        assert_eq!(lines_touched(&members, &["big".to_string()], 64), 2);
+    }
+}
@@ -0,0 +1,138 @@
+//! Aggregate cache line utilization: how full each struct's final cache line is.
+
+use crate::types::StructLayout;
+use serde::Serialize;
+
+/// Number of buckets the final cache line is split into.
+const HEATMAP_BUCKETS: u64 = 8;
+
+/// Distribution of final-line usage across all analyzed structs.
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheLineHeatmap {
+    pub cache_line_size: u32,
+    pub total_structs: usize,
+    pub buckets: Vec<HeatmapBucket>,
+    /// Multi-line structs whose final line holds at most one bucket's worth of bytes;
+    /// trimming `overflow_bytes` would save a whole cache line. Sorted by overflow, smallest
+    /// first.
+    pub just_over_the_line: Vec<JustOverTheLine>,
+}
+
+/// Structs using between `min_bytes` and `max_bytes` (inclusive) of their final cache line.
+#[derive(Debug, Clone, Serialize)]
+pub struct HeatmapBucket {
+    pub min_bytes: u64,
+    pub max_bytes: u64,
+    pub structs: usize,
+    /// Of `structs`, how many span more than one cache line.
+    pub multi_line_structs: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JustOverTheLine {
+    pub name: String,
+    pub size: u64,
+    pub cache_lines_spanned: u32,
+    pub overflow_bytes: u64,
+}
+
+/// Bytes of the last cache line a struct of `size` bytes occupies (0 for empty structs).
+///
+/// # Panics
+/// Panics if `cache_line_size` is 0.
+pub fn final_line_bytes(size: u64, cache_line_size: u32) -> u64 {
+    assert!(cache_line_size > 0, "cache_line_size must be > 0");
+    let line = cache_line_size as u64;
+    match size % line {
+        0 if size > 0 => line,
+        rem => rem,
+    }
+}
+
+/// Bucket `layouts` by final-line usage. Empty structs are not counted.
+///
+/// # Panics
+/// Panics if `cache_line_size` is 0.
+pub fn cache_line_heatmap(layouts: &[StructLayout], cache_line_size: u32) -> CacheLineHeatmap {
+    assert!(cache_line_size > 0, "cache_line_size must be > 0");
+    let line = cache_line_size as u64;
+    let width = line.div_ceil(HEATMAP_BUCKETS);
+
+    let mut buckets: Vec<HeatmapBucket> = (0..line.div_ceil(width))
+        .map(|i| HeatmapBucket {
+            min_bytes: i * width + 1,
+            max_bytes: ((i + 1) * width).min(line),
+            structs: 0,
+            multi_line_structs: 0,
+        })
+        .collect();
+    let mut just_over_the_line = Vec::new();
+    let mut total_structs = 0;
+
+    for layout in layouts.iter().filter(|l| l.size > 0) {
+        total_structs += 1;
+        let used = final_line_bytes(layout.size, cache_line_size);
+        let multi_line = layout.size > line;
+
+        let bucket = &mut buckets[((used - 1) / width) as usize];
+        bucket.structs += 1;
+        if multi_line {
+            bucket.multi_line_structs += 1;
+            if used <= width {
+                just_over_the_line.push(JustOverTheLine {
+                    name: layout.name.clone(),
+                    size: layout.size,
+                    cache_lines_spanned: layout.size.div_ceil(line).min(u32::MAX as u64) as u32,
+                    overflow_bytes: used,
+                });
+            }
+        }
+    }
+
+    // Stable sort keeps the caller's order among equal overflows.
+    just_over_the_line.sort_by_key(|s| s.overflow_bytes);
+
+    CacheLineHeatmap { cache_line_size, total_structs, buckets, just_over_the_line }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sized(name: &str, size: u64) -> StructLayout {
+        StructLayout::new(name.to_string(), size, Some(8))
+    }
+
+    #[test]
+    fn final_line_bytes_handles_exact_multiples() {
+        assert_eq!(final_line_bytes(64, 64), 64);
+        assert_eq!(final_line_bytes(72, 64), 8);
+        assert_eq!(final_line_bytes(0, 64), 0);
+    }
+
+    #[test]
+    fn heatmap_buckets_and_flags_just_over_the_line() {
+        let layouts =
+            [sized("Small", 8), sized("Over", 72), sized("WayOver", 65), sized("Full", 128)];
+        let heatmap = cache_line_heatmap(&layouts, 64);
+
+        assert_eq!(heatmap.buckets.len(), 8);
+        assert_eq!(heatmap.total_structs, 4);
+        assert_eq!(heatmap.buckets[0].structs, 3);
+        assert_eq!(heatmap.buckets[0].multi_line_structs, 2);
+        assert_eq!(heatmap.buckets[7].structs, 1);
+
+        let names: Vec<&str> = heatmap.just_over_the_line.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["WayOver", "Over"]);
+        assert_eq!(heatmap.just_over_the_line[0].overflow_bytes, 1);
+        assert_eq!(heatmap.just_over_the_line[0].cache_lines_spanned, 2);
+    }
+
+    #[test]
+    fn heatmap_handles_small_cache_lines() {
+        let heatmap = cache_line_heatmap(&[sized("A", 5)], 4);
+        assert_eq!(heatmap.buckets.len(), 4);
+        assert_eq!(heatmap.buckets[0].max_bytes, 1);
+        assert_eq!(heatmap.just_over_the_line.len(), 1);
+    }
+}
@@ -1,6 +1,9 @@
+use crate::error::{Error, Result};
 use crate::types::{
     AtomicMember, CacheLineSpanningWarning, FalseSharingAnalysis, FalseSharingWarning, StructLayout,
 };
+use globset::{GlobBuilder, GlobMatcher};
+use regex_automata::meta::Regex;
 use std::collections::BTreeMap;
 
 const ATOMIC_PATTERNS: &[&str] = &[
@@ -49,8 +52,92 @@ const ATOMIC_PATTERNS: &[&str] = &[
     "arc_swap::ArcSwapAny",
 ];
 
-fn is_atomic_type_by_name(type_name: &str) -> bool {
-    ATOMIC_PATTERNS.iter().any(|pattern| type_name.contains(pattern))
+/// Type-name patterns that mark a member or global as an atomic or a lock built on one, for
+/// false-sharing detection. Types the debug info marks atomic (`_Atomic`) always count. The
+/// default is the built-in set of standard and well-known crate types; in-house lock types can
+/// be added by substring or regular expression. Groups of members known to be safe together can
+/// be exempted from pair warnings.
+#[derive(Debug, Clone)]
+pub struct AtomicPatterns {
+    builtin: bool,
+    substrings: Vec<String>,
+    regexes: Vec<Regex>,
+    /// Struct-name globs and the members of each that may share a cache line.
+    ignored: Vec<(GlobMatcher, Vec<String>)>,
+}
+
+impl Default for AtomicPatterns {
+    fn default() -> Self {
+        Self { builtin: true, substrings: Vec::new(), regexes: Vec::new(), ignored: Vec::new() }
+    }
+}
+
+impl AtomicPatterns {
+    /// No patterns, not even the built-in ones, so only added patterns match.
+    pub fn empty() -> Self {
+        Self { builtin: false, ..Self::default() }
+    }
+
+    /// Also match type names containing `pattern`, e.g. `my_rt::SpinLock`.
+    pub fn with_substring(mut self, pattern: impl Into<String>) -> Self {
+        self.substrings.push(pattern.into());
+        self
+    }
+
+    /// Also match type names matching `pattern` (unanchored).
+    pub fn with_regex(mut self, pattern: &str) -> Result<Self> {
+        self.regexes.push(crate::dwarf::compile_regex(pattern)?);
+        Ok(self)
+    }
+
+    /// Let any two of `members` share a cache line in structs matching the glob
+    /// `struct_pattern`, for atomics that are always modified together (e.g. under one lock).
+    pub fn with_ignored_members(
+        mut self,
+        struct_pattern: &str,
+        members: Vec<String>,
+    ) -> Result<Self> {
+        let matcher = GlobBuilder::new(struct_pattern)
+            .literal_separator(false)
+            .build()
+            .map_err(|e| Error::InvalidPattern(format!("'{}': {}", struct_pattern, e)))?
+            .compile_matcher();
+        self.ignored.push((matcher, members));
+        Ok(self)
+    }
+
+    /// Whether `a` and `b` of struct `struct_name` belong to one ignored group.
+    fn ignores_pair(&self, struct_name: &str, a: &str, b: &str) -> bool {
+        self.ignored.iter().any(|(matcher, members)| {
+            matcher.is_match(struct_name)
+                && members.iter().any(|m| m == a)
+                && members.iter().any(|m| m == b)
+        })
+    }
+
+    /// Whether a (qualified) type name names an atomic or a lock.
+    pub fn matches(&self, type_name: &str) -> bool {
+        (self.builtin && ATOMIC_PATTERNS.iter().any(|pattern| type_name.contains(pattern)))
+            || self.substrings.iter().any(|pattern| type_name.contains(pattern.as_str()))
+            || self.regexes.iter().any(|re| re.is_match(type_name))
+    }
+
+    /// Whether a DWARF type name, which carries no namespace, names an atomic or a lock type.
+    /// The built-in set also takes Rust's `AtomicUsize`/`AtomicPtr<T>` and C++'s
+    /// `atomic<T>`/`atomic_flag` here.
+    pub(crate) fn matches_die_name(&self, name: &str) -> bool {
+        let builtin_atomic = self.builtin && {
+            let rust_atomic = name
+                .strip_prefix("Atomic")
+                .and_then(|rest| rest.chars().next())
+                .is_some_and(|c| c.is_ascii_uppercase());
+            rust_atomic
+                || name.starts_with("atomic<")
+                || name.starts_with("__atomic_base<")
+                || name == "atomic_flag"
+        };
+        builtin_atomic || self.matches(name)
+    }
 }
 
 /// Analyzes a struct layout for potential false sharing issues.
@@ -58,6 +145,18 @@ fn is_atomic_type_by_name(type_name: &str) -> bool {
 /// # Panics
 /// Panics if `cache_line_size` is 0.
 pub fn analyze_false_sharing(layout: &StructLayout, cache_line_size: u32) -> FalseSharingAnalysis {
+    analyze_false_sharing_with_patterns(layout, cache_line_size, &AtomicPatterns::default())
+}
+
+/// [`analyze_false_sharing`], treating members whose type matches `patterns` as atomics.
+///
+/// # Panics
+/// Panics if `cache_line_size` is 0.
+pub fn analyze_false_sharing_with_patterns(
+    layout: &StructLayout,
+    cache_line_size: u32,
+    patterns: &AtomicPatterns,
+) -> FalseSharingAnalysis {
     assert!(cache_line_size > 0, "cache_line_size must be > 0");
     let cache_line_size_u64 = cache_line_size as u64;
 
@@ -65,7 +164,7 @@ pub fn analyze_false_sharing(layout: &StructLayout, cache_line_size: u32) -> Fal
         .members
         .iter()
         // Use DWARF-detected is_atomic flag OR fall back to string pattern matching
-        .filter(|m| m.is_atomic || is_atomic_type_by_name(&m.type_name))
+        .filter(|m| m.is_atomic || patterns.matches(&m.type_name))
         .filter_map(|m| {
             let offset = m.offset?;
             let size = m.size?;
@@ -145,6 +244,9 @@ pub fn analyze_false_sharing(layout: &StructLayout, cache_line_size: u32) -> Fal
                     continue;
                 }
                 seen_pairs.insert(pair_key);
+                if patterns.ignores_pair(&layout.name, &first.name, &second.name) {
+                    continue;
+                }
 
                 // gap_bytes = second.offset - (first.offset + first.size)
                 // Negative = overlap, Zero = adjacent, Positive = gap
@@ -633,4 +735,80 @@ mod tests {
         assert!(analysis.warnings.is_empty());
         assert!(analysis.spanning_warnings.is_empty());
     }
+
+    #[test]
+    fn atomic_die_names_without_namespaces() {
+        let patterns = AtomicPatterns::default();
+        assert!(patterns.matches_die_name("AtomicUsize"));
+        assert!(patterns.matches_die_name("AtomicPtr<u8>"));
+        assert!(patterns.matches_die_name("atomic<int>"));
+        assert!(patterns.matches_die_name("atomic_flag"));
+        assert!(patterns.matches_die_name("std::sync::Mutex<u32>"));
+        assert!(!patterns.matches_die_name("Atomicity"));
+        assert!(!patterns.matches_die_name("atomic_count"));
+    }
+
+    #[test]
+    fn custom_patterns_extend_or_replace_builtin() {
+        let layout = make_layout_with_members(vec![
+            MemberLayout::new("lock".to_string(), "my_rt::SpinLock".to_string(), Some(0), Some(4)),
+            MemberLayout::new("gate".to_string(), "my_rt::Gate<u8>".to_string(), Some(8), Some(4)),
+            MemberLayout::new(
+                "count".to_string(),
+                "std::sync::atomic::AtomicU64".to_string(),
+                Some(16),
+                Some(8),
+            ),
+        ]);
+        let names = |patterns: &AtomicPatterns| -> Vec<String> {
+            analyze_false_sharing_with_patterns(&layout, 64, patterns)
+                .atomic_members
+                .into_iter()
+                .map(|m| m.name)
+                .collect()
+        };
+
+        assert_eq!(names(&AtomicPatterns::default()), vec!["count"]);
+        let extended = AtomicPatterns::default()
+            .with_substring("my_rt::SpinLock")
+            .with_regex(r"^my_rt::Gate<")
+            .unwrap();
+        assert_eq!(names(&extended), vec!["lock", "gate", "count"]);
+        let replaced = AtomicPatterns::empty().with_substring("SpinLock");
+        assert_eq!(names(&replaced), vec!["lock"]);
+        assert!(!replaced.matches_die_name("AtomicUsize"));
+        assert!(AtomicPatterns::empty().with_regex("(").is_err());
+    }
+
+    #[test]
+    fn ignored_member_groups_skip_pair_warnings() {
+        let atomic = |name: &str, offset| {
+            MemberLayout::new(
+                name.to_string(),
+                "std::sync::atomic::AtomicU64".to_string(),
+                Some(offset),
+                Some(8),
+            )
+        };
+        let layout =
+            make_layout_with_members(vec![atomic("a", 0), atomic("b", 8), atomic("c", 16)]);
+        let pairs = |patterns: &AtomicPatterns| -> Vec<(String, String)> {
+            analyze_false_sharing_with_patterns(&layout, 64, patterns)
+                .warnings
+                .into_iter()
+                .map(|w| (w.member_a, w.member_b))
+                .collect()
+        };
+        let pair = |a: &str, b: &str| (a.to_string(), b.to_string());
+
+        let ignored = AtomicPatterns::default()
+            .with_ignored_members("Test*", vec!["a".to_string(), "b".to_string()])
+            .unwrap();
+        assert_eq!(pairs(&ignored), vec![pair("a", "c"), pair("b", "c")]);
+        let other_struct = AtomicPatterns::default()
+            .with_ignored_members("Other", vec!["a".to_string(), "b".to_string()])
+            .unwrap();
+        assert_eq!(pairs(&other_struct).len(), 3);
+        assert!(AtomicPatterns::default().with_ignored_members("[", Vec::new()).is_err());
+    }
 }
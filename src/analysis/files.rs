@@ -0,0 +1,320 @@
+use crate::names::{namespace_of, namespace_prefix, template_of};
+use crate::owners::CodeOwners;
+use crate::types::StructLayout;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Padding totals for the structs declared in one source file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileSummary {
+    /// Declaring file as recorded in debug info; `None` for structs without a location.
+    pub file: Option<String>,
+    pub structs: usize,
+    pub total_size: u64,
+    pub padding_bytes: u64,
+    pub worst_struct: Option<WorstStruct>,
+}
+
+/// Padding totals for the files one CODEOWNERS owner is responsible for.
+#[derive(Debug, Clone, Serialize)]
+pub struct OwnerSummary {
+    /// Owner as written in CODEOWNERS; `None` collects unowned files.
+    pub owner: Option<String>,
+    pub files: usize,
+    pub structs: usize,
+    pub total_size: u64,
+    pub padding_bytes: u64,
+    pub worst_struct: Option<WorstStruct>,
+}
+
+/// Padding totals for the structs in one namespace, crate, or Go package.
+#[derive(Debug, Clone, Serialize)]
+pub struct NamespaceSummary {
+    /// Namespace from the debug info, or else from a qualified struct name (Go packages).
+    /// Structs outside any namespace (C structs) fall back to the declaring file's directory,
+    /// with a trailing `/`; `None` collects the rest.
+    pub namespace: Option<String>,
+    pub structs: usize,
+    pub total_size: u64,
+    pub padding_bytes: u64,
+    pub worst_struct: Option<WorstStruct>,
+}
+
+/// Size and padding of the instantiations of one template, which `--collapse-templates` lists
+/// in place of the instantiations themselves.
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateSummary {
+    /// The template, with its arguments left out (`Vector<>`, `main.Pair[]`).
+    pub template: String,
+    pub instantiations: usize,
+    pub min_size: u64,
+    pub max_size: u64,
+    pub total_size: u64,
+    pub padding_bytes: u64,
+    pub worst_struct: Option<WorstStruct>,
+}
+
+/// The struct with the most padding in a group. Groups without padding have none.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct WorstStruct {
+    pub name: String,
+    pub padding_bytes: u64,
+}
+
+impl WorstStruct {
+    /// Keep whichever of `current` and `candidate` has more padding; the earlier one on ties.
+    fn pick(current: &mut Option<WorstStruct>, candidate: &WorstStruct) {
+        if candidate.padding_bytes > current.as_ref().map_or(0, |w| w.padding_bytes) {
+            *current = Some(candidate.clone());
+        }
+    }
+}
+
+/// Group layouts by declaring file, most padding first (ties by file name).
+pub fn summarize_by_file(layouts: &[StructLayout]) -> Vec<FileSummary> {
+    let mut files: BTreeMap<Option<&str>, FileSummary> = BTreeMap::new();
+    for layout in layouts {
+        let file = layout.source_location.as_ref().map(|l| l.file.as_str());
+        let summary = files.entry(file).or_insert_with(|| FileSummary {
+            file: file.map(str::to_string),
+            structs: 0,
+            total_size: 0,
+            padding_bytes: 0,
+            worst_struct: None,
+        });
+        summary.structs += 1;
+        summary.total_size += layout.size;
+        summary.padding_bytes += layout.metrics.padding_bytes;
+        WorstStruct::pick(
+            &mut summary.worst_struct,
+            &WorstStruct { name: layout.name.clone(), padding_bytes: layout.metrics.padding_bytes },
+        );
+    }
+    let mut files: Vec<FileSummary> = files.into_values().collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.padding_bytes));
+    files
+}
+
+/// Group layouts by namespace, most padding first (ties by namespace). With `depth`, only the
+/// first that many segments of each namespace count, so `depth: Some(1)` groups by crate or
+/// top-level namespace.
+pub fn summarize_by_namespace(
+    layouts: &[StructLayout],
+    depth: Option<usize>,
+) -> Vec<NamespaceSummary> {
+    let mut groups: BTreeMap<Option<String>, NamespaceSummary> = BTreeMap::new();
+    for layout in layouts {
+        let namespace = match layout.namespace.as_deref().or_else(|| namespace_of(&layout.name)) {
+            Some(namespace) => {
+                Some(depth.map_or(namespace, |d| namespace_prefix(namespace, d)).to_string())
+            }
+            None => layout.source_location.as_ref().and_then(|l| {
+                let (dir, _) = l.file.rsplit_once('/')?;
+                Some(format!("{}/", dir))
+            }),
+        };
+        let summary = groups.entry(namespace.clone()).or_insert_with(|| NamespaceSummary {
+            namespace,
+            structs: 0,
+            total_size: 0,
+            padding_bytes: 0,
+            worst_struct: None,
+        });
+        summary.structs += 1;
+        summary.total_size += layout.size;
+        summary.padding_bytes += layout.metrics.padding_bytes;
+        WorstStruct::pick(
+            &mut summary.worst_struct,
+            &WorstStruct { name: layout.name.clone(), padding_bytes: layout.metrics.padding_bytes },
+        );
+    }
+    let mut namespaces: Vec<NamespaceSummary> = groups.into_values().collect();
+    namespaces.sort_by_key(|n| std::cmp::Reverse(n.padding_bytes));
+    namespaces
+}
+
+/// Take the instantiations of every template instantiated more than once out of `layouts`, and
+/// summarize them per template, most padding first (ties by template). Structs that are not
+/// instantiations, or are the only instantiation of their template, stay in `layouts`.
+pub fn collapse_templates(layouts: &mut Vec<StructLayout>) -> Vec<TemplateSummary> {
+    let templates: Vec<Option<String>> = layouts.iter().map(|l| template_of(&l.name)).collect();
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for template in templates.iter().flatten() {
+        *counts.entry(template).or_default() += 1;
+    }
+
+    let mut groups: BTreeMap<String, TemplateSummary> = BTreeMap::new();
+    let mut kept = Vec::with_capacity(layouts.len());
+    for (layout, template) in layouts.drain(..).zip(&templates) {
+        let Some(template) = template.as_deref().filter(|t| counts[t] > 1) else {
+            kept.push(layout);
+            continue;
+        };
+        let summary = groups.entry(template.to_string()).or_insert_with(|| TemplateSummary {
+            template: template.to_string(),
+            instantiations: 0,
+            min_size: layout.size,
+            max_size: layout.size,
+            total_size: 0,
+            padding_bytes: 0,
+            worst_struct: None,
+        });
+        summary.instantiations += 1;
+        summary.min_size = summary.min_size.min(layout.size);
+        summary.max_size = summary.max_size.max(layout.size);
+        summary.total_size += layout.size;
+        summary.padding_bytes += layout.metrics.padding_bytes;
+        WorstStruct::pick(
+            &mut summary.worst_struct,
+            &WorstStruct { name: layout.name.clone(), padding_bytes: layout.metrics.padding_bytes },
+        );
+    }
+    *layouts = kept;
+
+    let mut templates: Vec<TemplateSummary> = groups.into_values().collect();
+    templates.sort_by_key(|t| std::cmp::Reverse(t.padding_bytes));
+    templates
+}
+
+/// Roll file summaries up to their CODEOWNERS owners, most padding first (ties by owner).
+/// A file with several owners counts toward each of them.
+pub fn summarize_by_owner(files: &[FileSummary], owners: &CodeOwners) -> Vec<OwnerSummary> {
+    let mut by_owner: BTreeMap<Option<&str>, OwnerSummary> = BTreeMap::new();
+    for file in files {
+        let file_owners = file.file.as_deref().map_or(&[][..], |f| owners.owners_of(f));
+        let keys: Vec<Option<&str>> = if file_owners.is_empty() {
+            vec![None]
+        } else {
+            file_owners.iter().map(|o| Some(o.as_str())).collect()
+        };
+        for key in keys {
+            let summary = by_owner.entry(key).or_insert_with(|| OwnerSummary {
+                owner: key.map(str::to_string),
+                files: 0,
+                structs: 0,
+                total_size: 0,
+                padding_bytes: 0,
+                worst_struct: None,
+            });
+            summary.files += 1;
+            summary.structs += file.structs;
+            summary.total_size += file.total_size;
+            summary.padding_bytes += file.padding_bytes;
+            if let Some(worst) = &file.worst_struct {
+                WorstStruct::pick(&mut summary.worst_struct, worst);
+            }
+        }
+    }
+    let mut owners: Vec<OwnerSummary> = by_owner.into_values().collect();
+    owners.sort_by_key(|o| std::cmp::Reverse(o.padding_bytes));
+    owners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SourceLocation;
+    use std::path::Path;
+
+    fn layout(name: &str, file: Option<&str>, size: u64, padding: u64) -> StructLayout {
+        let mut l = StructLayout::new(name.to_string(), size, Some(8));
+        l.source_location = file.map(|f| SourceLocation { file: f.to_string(), line: 1 });
+        l.metrics.padding_bytes = padding;
+        l
+    }
+
+    #[test]
+    fn files_and_owners_roll_up_padding() {
+        let layouts = [
+            layout("A", Some("src/net/a.h"), 16, 4),
+            layout("B", Some("src/net/a.h"), 24, 7),
+            layout("C", Some("src/db.c"), 8, 0),
+            layout("D", Some("vendor/z.c"), 32, 12),
+            layout("E", None, 8, 1),
+        ];
+        let files = summarize_by_file(&layouts);
+        let order: Vec<Option<&str>> = files.iter().map(|f| f.file.as_deref()).collect();
+        assert_eq!(order, vec![Some("vendor/z.c"), Some("src/net/a.h"), None, Some("src/db.c")]);
+        assert_eq!(files[1].structs, 2);
+        assert_eq!(files[1].padding_bytes, 11);
+        assert_eq!(files[1].worst_struct.as_ref().unwrap().name, "B");
+        assert_eq!(files[3].worst_struct, None);
+
+        let codeowners =
+            CodeOwners::parse("src/ @acme/core\nsrc/net/ @acme/net @acme/core\n", Path::new(""))
+                .unwrap();
+        let owners = summarize_by_owner(&files, &codeowners);
+        let rows: Vec<(Option<&str>, usize, u64)> =
+            owners.iter().map(|o| (o.owner.as_deref(), o.files, o.padding_bytes)).collect();
+        assert_eq!(
+            rows,
+            vec![(None, 2, 13), (Some("@acme/core"), 2, 11), (Some("@acme/net"), 1, 11)]
+        );
+        assert_eq!(owners[0].worst_struct.as_ref().unwrap().name, "D");
+    }
+
+    #[test]
+    fn namespaces_roll_up_padding() {
+        let layouts = [
+            layout("app::net::Conn", None, 16, 4),
+            layout("app::net::tcp::Socket", None, 24, 7),
+            layout("app::db::Row", None, 8, 0),
+            layout("github.com/acme/shop.Order", None, 32, 12),
+            layout("point", Some("src/geo/point.h"), 8, 1),
+            layout("anon", None, 8, 2),
+        ];
+        let rows = |depth| -> Vec<(Option<String>, usize, u64)> {
+            summarize_by_namespace(&layouts, depth)
+                .into_iter()
+                .map(|n| (n.namespace, n.structs, n.padding_bytes))
+                .collect()
+        };
+        let ns = |s: &str| Some(s.to_string());
+
+        assert_eq!(
+            rows(None),
+            vec![
+                (ns("github.com/acme/shop"), 1, 12),
+                (ns("app::net::tcp"), 1, 7),
+                (ns("app::net"), 1, 4),
+                (None, 1, 2),
+                (ns("src/geo/"), 1, 1),
+                (ns("app::db"), 1, 0),
+            ]
+        );
+        assert_eq!(
+            rows(Some(1)),
+            vec![
+                (ns("github.com"), 1, 12),
+                (ns("app"), 3, 11),
+                (None, 1, 2),
+                (ns("src/geo/"), 1, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn template_instantiations_collapse() {
+        let mut layouts = vec![
+            layout("Vector<int>", None, 16, 4),
+            layout("Order", None, 8, 0),
+            layout("Vector<Foo>", None, 32, 12),
+            layout("Vector<char>", None, 24, 0),
+            layout("Single<int>", None, 8, 3),
+        ];
+        let templates = collapse_templates(&mut layouts);
+
+        let kept: Vec<&str> = layouts.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(kept, vec!["Order", "Single<int>"]);
+        assert_eq!(templates.len(), 1);
+        let vector = &templates[0];
+        assert_eq!(vector.template, "Vector<>");
+        assert_eq!(vector.instantiations, 3);
+        assert_eq!((vector.min_size, vector.max_size, vector.total_size), (16, 32, 72));
+        assert_eq!(vector.padding_bytes, 16);
+        assert_eq!(
+            vector.worst_struct,
+            Some(WorstStruct { name: "Vector<Foo>".to_string(), padding_bytes: 12 })
+        );
+    }
+}
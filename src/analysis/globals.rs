@@ -0,0 +1,178 @@
+use crate::loader::{DataSection, DataSymbol};
+use crate::types::{GlobalFalseSharingWarning, SharedGlobal};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Thread-shared globals that gained or lost a cache-line neighbour between two builds.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GlobalFalseSharingDiff {
+    /// Pairs sharing a line in the new build but not the old one.
+    pub introduced: Vec<GlobalFalseSharingWarning>,
+    /// Pairs that shared a line in the old build only.
+    pub resolved: Vec<GlobalFalseSharingWarning>,
+}
+
+impl GlobalFalseSharingDiff {
+    pub fn is_empty(&self) -> bool {
+        self.introduced.is_empty() && self.resolved.is_empty()
+    }
+}
+
+/// Match globals from the debug info to data symbols by address, taking each one's size and
+/// section from its symbol. A global the symbol table does not list (it was stripped) keeps the
+/// size of its type and is placed by the writable `sections` range holding its address. Other
+/// globals (thread-locals, constants) are dropped. The result is sorted by address.
+pub fn place_globals(
+    globals: &[SharedGlobal],
+    symbols: &[DataSymbol],
+    sections: &[DataSection],
+) -> Vec<SharedGlobal> {
+    let by_address: HashMap<u64, &DataSymbol> = symbols.iter().map(|s| (s.address, s)).collect();
+    let section_of = |address: u64| {
+        sections.iter().find(|s| address >= s.address && address - s.address < s.size)
+    };
+    let mut seen = HashSet::new();
+    let mut placed: Vec<SharedGlobal> = globals
+        .iter()
+        .filter_map(|g| {
+            let (size, section) = match by_address.get(&g.address) {
+                Some(symbol) => (symbol.size, symbol.section.clone()),
+                None => (g.size, section_of(g.address)?.name.clone()),
+            };
+            // Units that each describe the same definition (C++ inline variables) repeat it.
+            seen.insert(g.address).then(|| SharedGlobal {
+                size,
+                section: Some(section),
+                ..g.clone()
+            })
+        })
+        .collect();
+    placed.sort_by_key(|g| g.address);
+    placed
+}
+
+/// Pairs of placed globals whose byte ranges touch a common cache line, in address order.
+///
+/// # Panics
+/// Panics if `cache_line_size` is 0.
+pub fn analyze_global_false_sharing(
+    placed: &[SharedGlobal],
+    cache_line_size: u32,
+) -> Vec<GlobalFalseSharingWarning> {
+    assert!(cache_line_size > 0, "cache_line_size must be > 0");
+    let line = cache_line_size as u64;
+
+    let mut sorted: Vec<&SharedGlobal> = placed.iter().filter(|g| g.size > 0).collect();
+    sorted.sort_by_key(|g| g.address);
+
+    let mut warnings = Vec::new();
+    for (i, first) in sorted.iter().enumerate() {
+        let last_line = first.address.saturating_add(first.size - 1) / line;
+        // Later globals start at or after `first`, so the first one past its last line ends
+        // the scan.
+        for second in sorted[i + 1..].iter().take_while(|g| g.address / line <= last_line) {
+            warnings.push(GlobalFalseSharingWarning {
+                first: (*first).clone(),
+                second: (*second).clone(),
+                cache_line_address: second.address / line * line,
+                binary: None,
+            });
+        }
+    }
+    warnings
+}
+
+/// Compare the shared-line pairs of two builds by variable names.
+pub fn diff_global_false_sharing(
+    old: &[GlobalFalseSharingWarning],
+    new: &[GlobalFalseSharingWarning],
+) -> GlobalFalseSharingDiff {
+    fn key(w: &GlobalFalseSharingWarning) -> (&str, &str) {
+        let (a, b) = (w.first.name.as_str(), w.second.name.as_str());
+        if a <= b { (a, b) } else { (b, a) }
+    }
+    let old_keys: HashSet<(&str, &str)> = old.iter().map(key).collect();
+    let new_keys: HashSet<(&str, &str)> = new.iter().map(key).collect();
+    GlobalFalseSharingDiff {
+        introduced: new.iter().filter(|w| !old_keys.contains(&key(w))).cloned().collect(),
+        resolved: old.iter().filter(|w| !new_keys.contains(&key(w))).cloned().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn global(name: &str, address: u64, size: u64) -> SharedGlobal {
+        SharedGlobal {
+            name: name.to_string(),
+            type_name: "_Atomic long".to_string(),
+            address,
+            size,
+            section: Some(".bss".to_string()),
+        }
+    }
+
+    fn pairs(warnings: &[GlobalFalseSharingWarning]) -> Vec<(&str, &str)> {
+        warnings.iter().map(|w| (w.first.name.as_str(), w.second.name.as_str())).collect()
+    }
+
+    #[test]
+    fn globals_sharing_a_line_are_paired() {
+        let placed = [
+            global("c", 0x1040, 8),
+            global("a", 0x1000, 8),
+            global("b", 0x1038, 16), // straddles into c's line
+            global("d", 0x10c0, 4),
+        ];
+        let warnings = analyze_global_false_sharing(&placed, 64);
+        assert_eq!(pairs(&warnings), vec![("a", "b"), ("b", "c")]);
+        assert_eq!(warnings[0].cache_line_address, 0x1000);
+        assert_eq!(warnings[1].cache_line_address, 0x1040);
+    }
+
+    #[test]
+    fn placement_uses_symbol_sizes_and_sections() {
+        let symbols = [DataSymbol {
+            name: "requests".to_string(),
+            address: 0x4018,
+            size: 8,
+            section: ".bss".to_string(),
+        }];
+        let mut from_dwarf = global("requests", 0x4018, 0);
+        from_dwarf.section = None;
+        let placed =
+            place_globals(&[from_dwarf.clone(), from_dwarf, global("tls", 0x10, 4)], &symbols, &[]);
+        assert_eq!(placed, vec![global("requests", 0x4018, 8)]);
+    }
+
+    #[test]
+    fn unlisted_globals_are_placed_by_section_range() {
+        let sections = [
+            DataSection { name: ".data".to_string(), address: 0x3000, size: 0x20 },
+            DataSection { name: ".bss".to_string(), address: 0x4000, size: 0x100 },
+        ];
+        let mut stripped = global("stripped", 0x4040, 8);
+        stripped.section = None;
+        let placed = place_globals(
+            &[stripped, global("rodata", 0x2000, 8), global("past_data", 0x3020, 8)],
+            &[],
+            &sections,
+        );
+        assert_eq!(placed, vec![global("stripped", 0x4040, 8)]);
+    }
+
+    #[test]
+    fn diff_matches_pairs_by_name() {
+        let old = analyze_global_false_sharing(&[global("a", 0, 8), global("b", 8, 8)], 64);
+        // The pair swapped places and gained a neighbour.
+        let new = analyze_global_false_sharing(
+            &[global("b", 0x40, 8), global("a", 0x48, 8), global("c", 0x50, 8)],
+            64,
+        );
+        let diff = diff_global_false_sharing(&old, &new);
+        assert_eq!(pairs(&diff.introduced), vec![("b", "c"), ("a", "c")]);
+        assert!(diff.resolved.is_empty());
+        assert!(diff_global_false_sharing(&new, &[]).introduced.is_empty());
+    }
+}
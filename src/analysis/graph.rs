@@ -0,0 +1,227 @@
+use crate::names::embedded_type;
+use crate::types::StructLayout;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Which structs embed which, by value: a pointer to a struct is not an edge, an array of them
+/// is one edge counting every element.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContainmentGraph {
+    /// Structs with at least one edge, by name.
+    pub nodes: Vec<GraphNode>,
+    /// Edges by container, then embedded struct.
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub name: String,
+    pub size: u64,
+    pub padding_bytes: u64,
+    pub padding_percent: f64,
+    #[serde(skip_serializing_if = "crate::output::skip_false")]
+    pub partial: bool,
+    /// Copies of this struct held by one instance of every other struct in the graph, through
+    /// any depth of nesting: 6 when `A` holds two `B`s and each `B` holds two of it.
+    pub embedded_copies: u64,
+    /// Padding bytes times embedded copies: what this struct's holes cost its containers.
+    pub embedded_padding_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphEdge {
+    /// The containing struct.
+    pub from: String,
+    /// The embedded struct.
+    pub to: String,
+    /// Members of `from` holding `to`, in declaration order.
+    pub members: Vec<String>,
+    /// Copies of `to` in one `from`, array elements included.
+    pub count: u64,
+}
+
+/// Containment graph of `layouts`. When a name appears more than once (the same struct from
+/// several binaries), the first layout stands for it.
+pub fn containment_graph(layouts: &[StructLayout]) -> ContainmentGraph {
+    let mut by_name: HashMap<&str, &StructLayout> = HashMap::new();
+    for layout in layouts {
+        by_name.entry(layout.name.as_str()).or_insert(layout);
+    }
+
+    let mut edges: BTreeMap<(&str, &str), GraphEdge> = BTreeMap::new();
+    for layout in by_name.values() {
+        for member in &layout.members {
+            let Some(inner) = embedded_type(&member.type_name).and_then(|t| by_name.get(t)) else {
+                continue;
+            };
+            if inner.name == layout.name {
+                continue;
+            }
+            // The member size over the struct size counts array elements, nested arrays too.
+            let count = match member.size {
+                Some(size) if inner.size > 0 && size >= inner.size => size / inner.size,
+                _ => 1,
+            };
+            let edge =
+                edges.entry((layout.name.as_str(), inner.name.as_str())).or_insert_with(|| {
+                    GraphEdge {
+                        from: layout.name.clone(),
+                        to: inner.name.clone(),
+                        members: Vec::new(),
+                        count: 0,
+                    }
+                });
+            edge.members.push(member.name.clone());
+            edge.count += count;
+        }
+    }
+
+    let mut containers: HashMap<&str, Vec<&GraphEdge>> = HashMap::new();
+    let mut connected = BTreeSet::new();
+    for edge in edges.values() {
+        containers.entry(edge.to.as_str()).or_default().push(edge);
+        connected.insert(edge.from.as_str());
+        connected.insert(edge.to.as_str());
+    }
+    let mut copies = HashMap::new();
+    let nodes = connected
+        .into_iter()
+        .map(|name| {
+            let layout = by_name[name];
+            let embedded_copies =
+                embedded_copies(name, &containers, &mut copies, &mut HashSet::new());
+            GraphNode {
+                name: name.to_string(),
+                size: layout.size,
+                padding_bytes: layout.metrics.padding_bytes,
+                padding_percent: layout.metrics.padding_percentage,
+                partial: layout.metrics.partial,
+                embedded_copies,
+                embedded_padding_bytes: layout
+                    .metrics
+                    .padding_bytes
+                    .saturating_mul(embedded_copies),
+            }
+        })
+        .collect();
+
+    ContainmentGraph { nodes, edges: edges.into_values().collect() }
+}
+
+/// Copies of `name` in every container, each container counting once for itself and once per
+/// copy of it held elsewhere. Containment cannot cycle in one program, but names merged from
+/// several binaries can; a cycle contributes nothing.
+fn embedded_copies<'a>(
+    name: &'a str,
+    containers: &HashMap<&'a str, Vec<&'a GraphEdge>>,
+    memo: &mut HashMap<&'a str, u64>,
+    visiting: &mut HashSet<&'a str>,
+) -> u64 {
+    if let Some(&copies) = memo.get(name) {
+        return copies;
+    }
+    if !visiting.insert(name) {
+        return 0;
+    }
+    let mut copies = 0u64;
+    for edge in containers.get(name).into_iter().flatten() {
+        let outer = embedded_copies(&edge.from, containers, memo, visiting);
+        copies = copies.saturating_add(edge.count.saturating_mul(outer.saturating_add(1)));
+    }
+    visiting.remove(name);
+    memo.insert(name, copies);
+    copies
+}
+
+impl ContainmentGraph {
+    /// Keep the nodes matching `keep` and every struct they embed or are embedded in, at any
+    /// depth, with the edges between those.
+    pub fn retain_connected(&mut self, keep: impl Fn(&str) -> bool) {
+        let start: Vec<&str> =
+            self.nodes.iter().map(|n| n.name.as_str()).filter(|n| keep(n)).collect();
+        let mut kept: HashSet<String> = HashSet::new();
+        for downward in [true, false] {
+            let mut stack = start.clone();
+            let mut seen: HashSet<&str> = HashSet::new();
+            while let Some(name) = stack.pop() {
+                if !seen.insert(name) {
+                    continue;
+                }
+                kept.insert(name.to_string());
+                for edge in &self.edges {
+                    let (near, far) =
+                        if downward { (&edge.from, &edge.to) } else { (&edge.to, &edge.from) };
+                    if near == name {
+                        stack.push(far);
+                    }
+                }
+            }
+        }
+        self.nodes.retain(|n| kept.contains(&n.name));
+        self.edges.retain(|e| kept.contains(&e.from) && kept.contains(&e.to));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    fn layout(name: &str, size: u64, padding: u64, members: &[(&str, &str, u64)]) -> StructLayout {
+        let mut layout = StructLayout::new(name.to_string(), size, Some(8));
+        layout.metrics.padding_bytes = padding;
+        let mut offset = 0;
+        for &(member, type_name, member_size) in members {
+            layout.members.push(MemberLayout::new(
+                member.to_string(),
+                type_name.to_string(),
+                Some(offset),
+                Some(member_size),
+            ));
+            offset += member_size;
+        }
+        layout
+    }
+
+    fn layouts() -> Vec<StructLayout> {
+        vec![
+            layout("Leaf", 16, 7, &[("flag", "char", 1), ("value", "double", 8)]),
+            layout(
+                "Pair",
+                40,
+                0,
+                &[("left", "Leaf", 16), ("right", "const Leaf", 16), ("next", "*Pair", 8)],
+            ),
+            layout("Table", 160, 0, &[("rows", "[Pair; 4]", 160)]),
+            layout("Lonely", 8, 0, &[("x", "long", 8)]),
+        ]
+    }
+
+    #[test]
+    fn edges_count_members_and_array_elements() {
+        let graph = containment_graph(&layouts());
+        let names: Vec<_> = graph.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["Leaf", "Pair", "Table"]);
+        assert_eq!(graph.edges.len(), 2);
+        assert_eq!(graph.edges[0].from, "Pair");
+        assert_eq!(graph.edges[0].members, ["left", "right"]);
+        assert_eq!(graph.edges[0].count, 2);
+        assert_eq!((graph.edges[1].from.as_str(), graph.edges[1].count), ("Table", 4));
+
+        // Two per Pair, eight more per Table.
+        let leaf = &graph.nodes[0];
+        assert_eq!((leaf.embedded_copies, leaf.embedded_padding_bytes), (10, 70));
+        assert_eq!(graph.nodes[2].embedded_copies, 0);
+    }
+
+    #[test]
+    fn retain_connected_follows_both_directions() {
+        let mut with_leaf = layouts();
+        with_leaf.push(layout("Other", 16, 0, &[("leaf", "Leaf", 16)]));
+        let mut graph = containment_graph(&with_leaf);
+        graph.retain_connected(|name| name == "Pair");
+        let names: Vec<_> = graph.nodes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["Leaf", "Pair", "Table"]);
+        assert!(graph.edges.iter().all(|e| e.from != "Other"));
+    }
+}
@@ -0,0 +1,336 @@
+//! Member orderings that keep related fields adjacent, traded off against struct size.
+
+use super::optimize::{OptimizedMember, SortableUnit, align_up, placed_members, prepare_units};
+use crate::types::StructLayout;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// Proposals per annealing run. Each run weighs size against grouping differently.
+pub const LOCALITY_ITERATIONS: usize = 2000;
+
+/// Weight of size (against grouping) in each annealing run's objective.
+const SIZE_WEIGHTS: [f64; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+
+/// Orderings of one struct that no other ordering found beats on both size and grouping.
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalityFront {
+    /// Largest size an ordering may have.
+    pub size_cap: u64,
+    /// Member groups kept adjacent.
+    pub groups: Vec<Vec<String>>,
+    /// `groups` were derived from shared name prefixes, not configured.
+    pub groups_from_prefixes: bool,
+    /// Smallest first; each ordering groups better than the one before it.
+    pub orderings: Vec<LocalityOrdering>,
+}
+
+/// One candidate member order.
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalityOrdering {
+    pub size: u64,
+    /// Share of adjacent pairs achievable within groups that the order achieves: 1.0 when
+    /// every group is contiguous.
+    pub grouping_score: f64,
+    pub members: Vec<OptimizedMember>,
+}
+
+/// Groups of members whose names share a prefix before the first `_`, such as `rx_bytes` and
+/// `rx_packets`. Prefixes shorter than two characters (`m_`) and groups of one are ignored.
+pub fn prefix_groups(layout: &StructLayout) -> Vec<Vec<String>> {
+    let mut groups: indexmap::IndexMap<&str, Vec<String>> = indexmap::IndexMap::new();
+    for member in &layout.members {
+        let Some((prefix, _)) = member.name.split_once('_') else {
+            continue;
+        };
+        if prefix.len() >= 2 {
+            groups.entry(prefix).or_default().push(member.name.clone());
+        }
+    }
+    groups.into_values().filter(|g| g.len() > 1).collect()
+}
+
+/// Search member orders of `layout` no larger than `size_cap` (the current size by default)
+/// for ones that keep each of `groups` together, with a seeded simulated annealing so the
+/// result is the same on every run. Returns `None` when no group has two members to place.
+pub fn locality_front(
+    layout: &StructLayout,
+    max_align: u64,
+    groups: &[Vec<String>],
+    size_cap: Option<u64>,
+) -> Option<LocalityFront> {
+    let prepared = prepare_units(layout, max_align, &HashSet::new());
+    let mut units = prepared.units;
+    units.sort_by_key(|u| u.members.first().map_or(0, |m| m.offset));
+
+    let group_of: Vec<Option<usize>> = units
+        .iter()
+        .map(|u| u.members.iter().find_map(|m| groups.iter().position(|g| g.contains(&m.name))))
+        .collect();
+    let max_pairs = (0..groups.len())
+        .map(|g| group_of.iter().filter(|&&u| u == Some(g)).count().saturating_sub(1))
+        .sum::<usize>();
+    if max_pairs == 0 {
+        return None;
+    }
+
+    let search = Annealer {
+        units: &units,
+        group_of: &group_of,
+        struct_alignment: prepared.struct_alignment,
+        size_cap: size_cap.unwrap_or(layout.size),
+    };
+    let mut front = ParetoFront::default();
+
+    // Start points: declaration order, the smallest greedy order, and groups gathered in
+    // declaration order.
+    let declared: Vec<usize> = (0..units.len()).collect();
+    let mut greedy = declared.clone();
+    greedy.sort_by(|&a, &b| {
+        (units[b].alignment, units[b].total_size).cmp(&(units[a].alignment, units[a].total_size))
+    });
+    let mut grouped = declared.clone();
+    grouped.sort_by_key(|&i| group_of[i].map_or(usize::MAX, |g| g));
+    for start in [&declared, &greedy, &grouped] {
+        search.offer(start, &mut front);
+    }
+
+    let mut rng = SplitMix64(0x5eed);
+    for (run, &weight) in SIZE_WEIGHTS.iter().enumerate() {
+        let start = if run % 2 == 0 { &grouped } else { &greedy };
+        search.anneal(start.clone(), weight, max_pairs, &mut rng, &mut front);
+    }
+
+    let mut orderings: Vec<LocalityOrdering> = front
+        .points
+        .into_iter()
+        .map(|point| {
+            let order = point.order.iter().map(|&i| units[i].clone()).collect();
+            let (members, size) = placed_members(order, prepared.struct_alignment);
+            LocalityOrdering {
+                size,
+                grouping_score: point.pairs as f64 / max_pairs as f64,
+                members,
+            }
+        })
+        .collect();
+    orderings.sort_by_key(|o| o.size);
+
+    Some(LocalityFront {
+        size_cap: search.size_cap,
+        groups: groups.to_vec(),
+        groups_from_prefixes: false,
+        orderings,
+    })
+}
+
+struct Annealer<'a> {
+    units: &'a [SortableUnit],
+    group_of: &'a [Option<usize>],
+    struct_alignment: u64,
+    size_cap: u64,
+}
+
+impl Annealer<'_> {
+    /// Size of `order` with tail padding, and how many neighbouring units share a group.
+    fn evaluate(&self, order: &[usize]) -> (u64, usize) {
+        let mut offset: u64 = 0;
+        for &i in order {
+            let unit = &self.units[i];
+            offset = align_up(offset, unit.alignment).saturating_add(unit.total_size);
+        }
+        let pairs = order
+            .windows(2)
+            .filter(|w| self.group_of[w[0]].is_some() && self.group_of[w[0]] == self.group_of[w[1]])
+            .count();
+        (align_up(offset, self.struct_alignment), pairs)
+    }
+
+    fn offer(&self, order: &[usize], front: &mut ParetoFront) -> (u64, usize) {
+        let (size, pairs) = self.evaluate(order);
+        if size <= self.size_cap {
+            front.insert(size, pairs, order);
+        }
+        (size, pairs)
+    }
+
+    /// Lower is better: a `weight`ed mix of size above the cap's headroom and missing pairs.
+    /// Orders over the cap always cost more than any order within it.
+    fn energy(&self, size: u64, pairs: usize, weight: f64, max_pairs: usize) -> f64 {
+        let scale = (self.size_cap + self.struct_alignment) as f64;
+        let grouping = 1.0 - pairs as f64 / max_pairs as f64;
+        let energy = weight * size as f64 / scale + (1.0 - weight) * grouping;
+        if size > self.size_cap { energy + 1.0 } else { energy }
+    }
+
+    fn anneal(
+        &self,
+        mut order: Vec<usize>,
+        weight: f64,
+        max_pairs: usize,
+        rng: &mut SplitMix64,
+        front: &mut ParetoFront,
+    ) {
+        let n = order.len();
+        if n < 2 {
+            return;
+        }
+        let (size, pairs) = self.evaluate(&order);
+        let mut energy = self.energy(size, pairs, weight, max_pairs);
+        const START_TEMPERATURE: f64 = 0.5;
+        const END_TEMPERATURE: f64 = 0.001;
+
+        for step in 0..LOCALITY_ITERATIONS {
+            let progress = step as f64 / LOCALITY_ITERATIONS as f64;
+            let temperature =
+                START_TEMPERATURE * (END_TEMPERATURE / START_TEMPERATURE).powf(progress);
+
+            let from = rng.below(n);
+            let to = (from + 1 + rng.below(n - 1)) % n;
+            let swap = rng.next() & 1 == 0;
+            let mut candidate = order.clone();
+            if swap {
+                candidate.swap(from, to);
+            } else {
+                let unit = candidate.remove(from);
+                candidate.insert(to, unit);
+            }
+
+            let (size, pairs) = self.offer(&candidate, front);
+            let candidate_energy = self.energy(size, pairs, weight, max_pairs);
+            let delta = candidate_energy - energy;
+            if delta <= 0.0 || rng.unit() < (-delta / temperature).exp() {
+                order = candidate;
+                energy = candidate_energy;
+            }
+        }
+    }
+}
+
+/// Orders not beaten on both size and grouping by another order seen so far.
+#[derive(Default)]
+struct ParetoFront {
+    points: Vec<FrontPoint>,
+}
+
+struct FrontPoint {
+    size: u64,
+    pairs: usize,
+    order: Vec<usize>,
+}
+
+impl ParetoFront {
+    /// Keeps the first order seen for each (size, pairs) outcome.
+    fn insert(&mut self, size: u64, pairs: usize, order: &[usize]) {
+        if self.points.iter().any(|p| p.size <= size && p.pairs >= pairs) {
+            return;
+        }
+        self.points.retain(|p| !(size <= p.size && pairs >= p.pairs));
+        self.points.push(FrontPoint { size, pairs, order: order.to_vec() });
+    }
+}
+
+/// Small deterministic generator; suggestions must not change between runs.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    fn layout(fields: &[(&str, u64, u64)], size: u64) -> StructLayout {
+        let mut layout = StructLayout::new("Conn".to_string(), size, Some(8));
+        layout.members = fields
+            .iter()
+            .map(|&(name, offset, size)| {
+                MemberLayout::new(
+                    name.to_string(),
+                    format!("u{}", size * 8),
+                    Some(offset),
+                    Some(size),
+                )
+            })
+            .collect();
+        layout
+    }
+
+    fn names(ordering: &LocalityOrdering) -> Vec<&str> {
+        ordering.members.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    #[test]
+    fn prefix_groups_need_two_members_and_a_real_prefix() {
+        let layout = layout(
+            &[
+                ("rx_bytes", 0, 8),
+                ("m_id", 8, 4),
+                ("tx_bytes", 16, 8),
+                ("rx_errors", 24, 1),
+                ("m_x", 28, 4),
+            ],
+            32,
+        );
+        assert_eq!(
+            prefix_groups(&layout),
+            vec![vec!["rx_bytes".to_string(), "rx_errors".to_string()]]
+        );
+    }
+
+    #[test]
+    fn front_trades_size_for_grouping() {
+        // Each flag next to its counter costs padding; packing the flags together is smaller
+        // but splits a group.
+        let layout = layout(
+            &[
+                ("rx_bytes", 0, 8),
+                ("rx_flag", 8, 1),
+                ("tx_bytes", 16, 8),
+                ("tx_flag", 24, 1),
+                ("cx_bytes", 32, 8),
+                ("cx_flag", 40, 1),
+            ],
+            48,
+        );
+        let groups = prefix_groups(&layout);
+        let front = locality_front(&layout, 8, &groups, None).unwrap();
+
+        assert_eq!(front.size_cap, 48);
+        let points: Vec<(u64, f64)> =
+            front.orderings.iter().map(|o| (o.size, o.grouping_score)).collect();
+        assert_eq!(points, vec![(32, 2.0 / 3.0), (40, 1.0)]);
+        let grouped = names(&front.orderings[1]);
+        for pair in [["rx_bytes", "rx_flag"], ["tx_bytes", "tx_flag"], ["cx_bytes", "cx_flag"]] {
+            let at = |name| grouped.iter().position(|&n| n == name).unwrap();
+            assert_eq!(at(pair[0]).abs_diff(at(pair[1])), 1);
+        }
+
+        // A tighter cap drops the padded, fully grouped order.
+        let capped = locality_front(&layout, 8, &groups, Some(32)).unwrap();
+        assert_eq!(capped.orderings.len(), 1);
+        assert_eq!(capped.orderings[0].size, 32);
+    }
+
+    #[test]
+    fn no_front_without_groups() {
+        let layout = layout(&[("a", 0, 8), ("b", 8, 1)], 16);
+        assert!(locality_front(&layout, 8, &[], None).is_none());
+        assert!(locality_front(&layout, 8, &[vec!["a".to_string()]], None).is_none());
+    }
+}
@@ -1,7 +1,52 @@
+mod alloc;
+mod bases;
+mod bits;
+mod cache_sim;
+mod cache_util;
 mod false_sharing;
+mod files;
+mod globals;
+mod graph;
+mod locality;
 mod optimize;
+mod pack;
 mod padding;
+mod summary;
+mod type_sizes;
 
-pub use false_sharing::analyze_false_sharing;
-pub use optimize::{OptimizedLayout, OptimizedMember, optimize_layout};
+pub use alloc::AllocatorModel;
+pub use bases::{analyze_bases, flatten_bases};
+pub use bits::{analyze_bits, is_bitfield_only};
+pub use cache_sim::{
+    AccessSimulation, OverFetch, over_fetch, simulate_access, static_access_sequence,
+};
+pub use cache_util::{
+    CacheLineHeatmap, HeatmapBucket, JustOverTheLine, cache_line_heatmap, final_line_bytes,
+};
+pub use false_sharing::{
+    AtomicPatterns, analyze_false_sharing, analyze_false_sharing_with_patterns,
+};
+pub use files::{
+    FileSummary, NamespaceSummary, OwnerSummary, TemplateSummary, WorstStruct, collapse_templates,
+    summarize_by_file, summarize_by_namespace, summarize_by_owner,
+};
+pub use globals::{
+    GlobalFalseSharingDiff, analyze_global_false_sharing, diff_global_false_sharing, place_globals,
+};
+pub use graph::{ContainmentGraph, GraphEdge, GraphNode, containment_graph};
+pub use locality::{
+    LOCALITY_ITERATIONS, LocalityFront, LocalityOrdering, locality_front, prefix_groups,
+};
+pub use optimize::{
+    AlignmentSource, EXHAUSTIVE_MAX_UNITS, EXHAUSTIVE_TIME_LIMIT, MemberPlacement,
+    OptimizeStrategy, OptimizedLayout, OptimizedMember, StrategyOutcome, optimize_layout,
+    optimize_layout_with_priority, optimize_layout_with_strategy, strategy_disagreement,
+};
+pub(crate) use pack::implied_alignment;
+pub use pack::{PackedRegion, detect_pack, packed_regions};
 pub use padding::analyze_layout;
+pub use summary::{
+    BinarySummary, PaddingBucket, SUMMARY_TOP_OFFENDERS, SummaryOffender, count_static_instances,
+    summarize_binary,
+};
+pub use type_sizes::{TypeSizeConflict, TypeSizes};
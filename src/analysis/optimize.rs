@@ -1,8 +1,12 @@
 //! Field reordering optimization for struct layouts.
 
-use crate::types::{MemberLayout, StructLayout};
+use super::cache_sim::AccessSimulation;
+use super::locality::LocalityFront;
+use super::pack::detect_pack;
+use crate::types::{MemberLayout, StructLayout, StructUsage, TypeTag};
 use serde::Serialize;
 use std::collections::HashSet;
+use std::time::{Duration, Instant};
 
 /// Result of optimizing a struct layout.
 #[derive(Debug, Clone, Serialize)]
@@ -20,6 +24,97 @@ pub struct OptimizedLayout {
     pub skipped_members: Vec<String>,
     /// True if layout contains bitfields that were kept together.
     pub has_bitfields: bool,
+    /// Strategy that produced `optimized_members` (after any fallback).
+    pub strategy: OptimizeStrategy,
+    /// The exhaustive search hit [`EXHAUSTIVE_TIME_LIMIT`]: the order is the best found by
+    /// then (greedy, if nothing better turned up), not proven optimal.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub search_timed_out: bool,
+    /// Optimized size under each strategy, when they disagree.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub strategy_disagreement: Vec<StrategyOutcome>,
+    /// Cache line simulation for a configured member access sequence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_simulation: Option<AccessSimulation>,
+    /// Orders that keep related members together, from `suggest --locality`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locality: Option<LocalityFront>,
+    /// Pack value the original offsets imply (`#pragma pack(N)` or a packed attribute); the
+    /// suggestion then gives no member more alignment than N.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pack: Option<u64>,
+    /// Listed in the config's `frozen_layouts`: an ABI or wire format that must not be
+    /// reordered, whatever it would save.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub frozen: bool,
+    /// What refers to the struct, copied from its layout.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<StructUsage>,
+}
+
+impl OptimizedLayout {
+    /// The proposed member order: names with their new offsets.
+    pub fn optimized_order(&self) -> Vec<MemberPlacement> {
+        self.optimized_members
+            .iter()
+            .map(|m| MemberPlacement {
+                name: m.name.clone(),
+                offset: m.offset,
+                bit_offset: m.bit_offset,
+            })
+            .collect()
+    }
+}
+
+/// Where a member lands in an optimized layout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MemberPlacement {
+    pub name: String,
+    pub offset: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bit_offset: Option<u64>,
+}
+
+/// Largest number of units (members, with each bitfield group counted once) that
+/// [`OptimizeStrategy::ExhaustiveSmall`] searches; larger structs fall back to greedy.
+pub const EXHAUSTIVE_MAX_UNITS: usize = 12;
+
+/// Time [`OptimizeStrategy::ExhaustiveSmall`] spends searching one struct before settling for
+/// the best order found so far.
+pub const EXHAUSTIVE_TIME_LIMIT: Duration = Duration::from_millis(100);
+
+/// How members are reordered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum OptimizeStrategy {
+    /// Sort by alignment, then size (largest first)
+    #[default]
+    Greedy,
+    /// Keep declaration order and move later members into holes, like `pahole --reorganize`
+    Pahole,
+    /// Search every order for structs with at most 12 members (time-boxed); greedy otherwise
+    ExhaustiveSmall,
+}
+
+impl OptimizeStrategy {
+    pub const ALL: [OptimizeStrategy; 3] =
+        [OptimizeStrategy::Greedy, OptimizeStrategy::Pahole, OptimizeStrategy::ExhaustiveSmall];
+
+    /// Name as accepted by `--strategy`.
+    pub fn name(self) -> &'static str {
+        match self {
+            OptimizeStrategy::Greedy => "greedy",
+            OptimizeStrategy::Pahole => "pahole",
+            OptimizeStrategy::ExhaustiveSmall => "exhaustive-small",
+        }
+    }
+}
+
+/// Optimized size a strategy reached for one struct.
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyOutcome {
+    pub strategy: OptimizeStrategy,
+    pub optimized_size: u64,
 }
 
 /// Member with computed offset and alignment.
@@ -30,10 +125,46 @@ pub struct OptimizedMember {
     pub offset: u64,
     pub size: u64,
     pub alignment: u64,
+    /// Why the member needs `alignment`, so a suggestion built on a wrong guess can be
+    /// corrected with an `align` in the config's `type_sizes`.
+    #[serde(rename = "alignment_requirement_source")]
+    pub alignment_source: AlignmentSource,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bit_offset: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bit_size: Option<u64>,
+    /// Tag C needs before `type_name` (see [`MemberLayout::type_tag`]).
+    #[serde(skip)]
+    pub type_tag: Option<TypeTag>,
+}
+
+/// Where an [`OptimizedMember`]'s alignment requirement comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlignmentSource {
+    /// `DW_AT_alignment` on the member or its type (alignas, aligned attributes,
+    /// #[repr(align)]).
+    Explicit,
+    /// The type's natural alignment as the debug info describes it.
+    Natural,
+    /// An `align` declared for the type in the config's `type_sizes`.
+    TypeSizes,
+    /// Guessed from the member's size, capped at the target's maximum alignment.
+    Size,
+    /// Lowered to the pack of the struct's packed region.
+    Packed,
+}
+
+impl AlignmentSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlignmentSource::Explicit => "explicit",
+            AlignmentSource::Natural => "natural",
+            AlignmentSource::TypeSizes => "type_sizes",
+            AlignmentSource::Size => "size",
+            AlignmentSource::Packed => "packed",
+        }
+    }
 }
 
 /// Infer alignment from size using standard C ABI rules.
@@ -50,7 +181,7 @@ pub fn infer_alignment(size: u64, max_align: u64) -> u64 {
 /// Align value up to alignment boundary.
 /// Returns value unchanged if alignment <= 1.
 /// For values near u64::MAX where alignment would overflow, returns u64::MAX.
-fn align_up(value: u64, alignment: u64) -> u64 {
+pub(super) fn align_up(value: u64, alignment: u64) -> u64 {
     if alignment <= 1 {
         return value;
     }
@@ -66,10 +197,11 @@ fn align_up(value: u64, alignment: u64) -> u64 {
 
 /// A sortable unit for optimization - either a single member or a bitfield group.
 #[derive(Clone)]
-struct SortableUnit {
-    members: Vec<OptimizedMember>,
-    total_size: u64,
-    alignment: u64,
+pub(super) struct SortableUnit {
+    pub(super) members: Vec<OptimizedMember>,
+    pub(super) total_size: u64,
+    pub(super) alignment: u64,
+    prioritized: bool,
 }
 
 /// Group bitfield members that share storage units.
@@ -118,22 +250,153 @@ fn find_bitfield_groups(members: &[MemberLayout]) -> Vec<Vec<usize>> {
 /// Optimize a struct layout by reordering fields to minimize padding.
 /// Uses greedy bin-packing: sort by alignment desc, then size desc.
 pub fn optimize_layout(layout: &StructLayout, max_align: u64) -> OptimizedLayout {
+    optimize_layout_with_priority(layout, max_align, &HashSet::new())
+}
+
+/// Like [`optimize_layout`], but members named in `priority` are packed first so they
+/// occupy as few cache lines as possible. Within each group the greedy ordering applies.
+/// A bitfield group is prioritized if any of its members is.
+pub fn optimize_layout_with_priority(
+    layout: &StructLayout,
+    max_align: u64,
+    priority: &HashSet<&str>,
+) -> OptimizedLayout {
+    optimize_layout_with_strategy(layout, max_align, priority, OptimizeStrategy::Greedy)
+}
+
+/// Like [`optimize_layout_with_priority`], with a choice of reordering strategy. The returned
+/// layout records the strategy actually used, which is greedy when `ExhaustiveSmall` is asked
+/// for a struct with more than [`EXHAUSTIVE_MAX_UNITS`] units, or when its search runs out of
+/// time without beating greedy.
+pub fn optimize_layout_with_strategy(
+    layout: &StructLayout,
+    max_align: u64,
+    priority: &HashSet<&str>,
+    strategy: OptimizeStrategy,
+) -> OptimizedLayout {
+    let PreparedUnits {
+        struct_alignment,
+        pack,
+        original_members,
+        skipped_members,
+        has_bitfields,
+        units,
+    } = prepare_units(layout, max_align, priority);
+
+    let mut search_timed_out = false;
+    let (order, strategy) = match strategy {
+        OptimizeStrategy::Pahole => (pahole_order(units), OptimizeStrategy::Pahole),
+        OptimizeStrategy::ExhaustiveSmall if units.len() <= EXHAUSTIVE_MAX_UNITS => {
+            let deadline = Instant::now() + EXHAUSTIVE_TIME_LIMIT;
+            let search = exhaustive_order(units, struct_alignment, deadline);
+            search_timed_out = search.timed_out;
+            let strategy = if search.timed_out && !search.improved {
+                OptimizeStrategy::Greedy
+            } else {
+                OptimizeStrategy::ExhaustiveSmall
+            };
+            (search.order, strategy)
+        }
+        _ => (greedy_order(units), OptimizeStrategy::Greedy),
+    };
+
+    let (optimized_members, optimized_size) = placed_members(order, struct_alignment);
+
+    let savings_bytes = layout.size.saturating_sub(optimized_size);
+    let savings_percent =
+        if layout.size > 0 { (savings_bytes as f64 / layout.size as f64) * 100.0 } else { 0.0 };
+
+    OptimizedLayout {
+        name: layout.name.clone(),
+        original_size: layout.size,
+        optimized_size,
+        savings_bytes,
+        savings_percent,
+        struct_alignment,
+        original_members,
+        optimized_members,
+        skipped_members,
+        has_bitfields,
+        strategy,
+        search_timed_out,
+        strategy_disagreement: Vec::new(),
+        access_simulation: None,
+        locality: None,
+        pack,
+        frozen: false,
+        usage: layout.usage,
+    }
+}
+
+/// Members of a struct grouped into the units that get reordered.
+pub(super) struct PreparedUnits {
+    pub(super) struct_alignment: u64,
+    pub(super) pack: Option<u64>,
+    pub(super) original_members: Vec<OptimizedMember>,
+    pub(super) skipped_members: Vec<String>,
+    pub(super) has_bitfields: bool,
+    pub(super) units: Vec<SortableUnit>,
+}
+
+/// Split `layout` into sortable units: one per member, with the members of each bitfield
+/// storage unit kept together. Members without a size or offset, and
+/// zero-size members, are skipped.
+pub(super) fn prepare_units(
+    layout: &StructLayout,
+    max_align: u64,
+    priority: &HashSet<&str>,
+) -> PreparedUnits {
     let max_align = max_align.max(1);
+    // A packed struct gives no member more alignment than the pack, so reordering must not
+    // count on it either.
+    let pack = detect_pack(layout);
+    let pack_cap = pack.unwrap_or(u64::MAX);
+    // Alignment recorded in the debug info is exact and may exceed `max_align` (alignas,
+    // #[repr(align)], SIMD); only alignments guessed from sizes are capped.
+    let member_alignment = |member: &MemberLayout, size: u64| {
+        // Alignment declared in the config's `type_sizes` beats the debug info and a guess.
+        let (alignment, source) = match (member.assumed.and_then(|a| a.align), member.alignment) {
+            (Some(align), _) => (align.min(max_align), AlignmentSource::TypeSizes),
+            (None, Some(align)) if member.explicit_alignment => (align, AlignmentSource::Explicit),
+            (None, Some(align)) => (align, AlignmentSource::Natural),
+            (None, None) => (infer_alignment(size, max_align), AlignmentSource::Size),
+        };
+        if alignment > pack_cap { (pack_cap, AlignmentSource::Packed) } else { (alignment, source) }
+    };
     // If struct alignment is known, use it; otherwise infer from member alignments.
     // Exclude ZSTs (size=0) since they don't affect struct alignment.
     let inferred_alignment = layout
         .members
         .iter()
-        .filter_map(|m| m.size)
-        .filter(|&s| s > 0)
-        .map(|s| infer_alignment(s, max_align))
+        .filter_map(|m| Some((m, m.size?)))
+        .filter(|&(_, s)| s > 0)
+        .map(|(m, s)| member_alignment(m, s).0)
         .max()
         .unwrap_or(1);
 
-    let struct_alignment = layout.alignment.unwrap_or(inferred_alignment).min(max_align);
+    let struct_alignment = layout.alignment.unwrap_or(inferred_alignment.min(max_align));
+    // An over-aligned member raises the struct's alignment even when the struct declares none.
+    let struct_alignment = layout
+        .members
+        .iter()
+        .filter_map(|m| m.alignment)
+        .fold(struct_alignment, u64::max)
+        .min(pack_cap);
+
+    // Members flattened out of one unnamed struct or union move as a block, bitfields included.
+    let mut aggregate_groups: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (idx, member) in layout.members.iter().enumerate() {
+        let Some(key) = member.aggregate.as_deref() else { continue };
+        match aggregate_groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, group)) => group.push(idx),
+            None => aggregate_groups.push((key, vec![idx])),
+        }
+    }
 
     // Find bitfield groups
-    let bitfield_groups = find_bitfield_groups(&layout.members);
+    let mut bitfield_groups = find_bitfield_groups(&layout.members);
+    bitfield_groups
+        .retain(|group| group.iter().all(|&idx| layout.members[idx].aggregate.is_none()));
     let bitfield_indices: HashSet<usize> = bitfield_groups.iter().flatten().copied().collect();
     let has_bitfields = !bitfield_groups.is_empty();
 
@@ -156,7 +419,7 @@ pub fn optimize_layout(layout: &StructLayout, max_align: u64) -> OptimizedLayout
             continue;
         }
 
-        let alignment = infer_alignment(size, max_align);
+        let (alignment, alignment_source) = member_alignment(member, size);
 
         original_members.push(OptimizedMember {
             name: member.name.clone(),
@@ -164,8 +427,10 @@ pub fn optimize_layout(layout: &StructLayout, max_align: u64) -> OptimizedLayout
             offset,
             size,
             alignment,
+            alignment_source,
             bit_offset: member.bit_offset,
             bit_size: member.bit_size,
+            type_tag: member.type_tag,
         });
     }
 
@@ -213,7 +478,34 @@ pub fn optimize_layout(layout: &StructLayout, max_align: u64) -> OptimizedLayout
             converted_bitfield_indices.insert(*idx);
         }
 
-        units.push(SortableUnit { members: group_members, total_size, alignment });
+        let prioritized = group_members.iter().any(|m| priority.contains(m.name.as_str()));
+        units.push(SortableUnit { members: group_members, total_size, alignment, prioritized });
+    }
+
+    for (_, group) in &aggregate_groups {
+        let group_members: Vec<OptimizedMember> = group
+            .iter()
+            .filter_map(|&idx| original_members.iter().find(|m| m.name == layout.members[idx].name))
+            .cloned()
+            .collect();
+        processed_indices.extend(group.iter().copied());
+        let (Some(start), Some(end)) = (
+            group_members.iter().map(|m| m.offset).min(),
+            group_members.iter().map(|m| m.offset.saturating_add(m.size)).max(),
+        ) else {
+            continue;
+        };
+        // The aggregate's own size keeps its tail padding, which no member extent covers.
+        let extent = layout.members[group[0]].aggregate_extent;
+        let total_size = extent.map_or(0, |e| e.size).max(end - start);
+        let alignment = group_members
+            .iter()
+            .map(|m| m.alignment)
+            .chain(extent.and_then(|e| e.alignment))
+            .max()
+            .unwrap_or(1);
+        let prioritized = group_members.iter().any(|m| priority.contains(m.name.as_str()));
+        units.push(SortableUnit { members: group_members, total_size, alignment, prioritized });
     }
 
     // Verify all bitfield indices are accounted for (either converted or in skipped_members).
@@ -246,61 +538,243 @@ pub fn optimize_layout(layout: &StructLayout, max_align: u64) -> OptimizedLayout
                 members: vec![opt_member.clone()],
                 total_size: opt_member.size,
                 alignment: opt_member.alignment,
+                prioritized: priority.contains(opt_member.name.as_str()),
             });
         }
     }
 
-    // Sort: largest alignment first, then largest size
-    units.sort_by(|a, b| {
-        b.alignment.cmp(&a.alignment).then_with(|| b.total_size.cmp(&a.total_size))
-    });
-
-    // Place members greedily
-    let mut optimized_members: Vec<OptimizedMember> = Vec::new();
-    let mut current_offset: u64 = 0;
-
-    for unit in units {
-        // Align to unit's alignment requirement
-        let aligned_offset = align_up(current_offset, unit.alignment);
+    PreparedUnits {
+        struct_alignment,
+        pack,
+        original_members,
+        skipped_members,
+        has_bitfields,
+        units,
+    }
+}
 
+/// Members of `order` at their new offsets, and the struct size including tail padding.
+pub(super) fn placed_members(
+    order: Vec<SortableUnit>,
+    struct_alignment: u64,
+) -> (Vec<OptimizedMember>, u64) {
+    let (offsets, current_offset) = place_units(&order);
+    let mut members: Vec<OptimizedMember> = Vec::new();
+    for (unit, aligned_offset) in order.into_iter().zip(offsets) {
+        // Members of one unit keep their distances: bitfields share storage, and the fields of
+        // a flattened struct stay where the struct put them.
+        let start = unit.members.iter().map(|m| m.offset).min().unwrap_or(0);
         for mut member in unit.members {
-            member.offset = aligned_offset;
+            member.offset = aligned_offset + (member.offset - start);
             // Clear bit_offset after reordering - the original value was relative to
             // the original layout and is no longer valid. Keep bit_size for reference.
             if member.bit_size.is_some() {
                 member.bit_offset = None;
             }
-            optimized_members.push(member);
+            members.push(member);
         }
+    }
 
+    // Add tail padding to reach struct alignment
+    (members, align_up(current_offset, struct_alignment))
+}
+
+/// Sort: prioritized units first, then largest alignment, then largest size.
+fn greedy_order(mut units: Vec<SortableUnit>) -> Vec<SortableUnit> {
+    units.sort_by(|a, b| {
+        b.prioritized
+            .cmp(&a.prioritized)
+            .then_with(|| b.alignment.cmp(&a.alignment))
+            .then_with(|| b.total_size.cmp(&a.total_size))
+    });
+    units
+}
+
+/// Offset of each unit when placed in order, and the end offset (before tail padding).
+pub(super) fn place_units(units: &[SortableUnit]) -> (Vec<u64>, u64) {
+    let mut offsets = Vec::with_capacity(units.len());
+    let mut current_offset: u64 = 0;
+    for unit in units {
+        let aligned_offset = align_up(current_offset, unit.alignment);
+        offsets.push(aligned_offset);
         // Use saturating_add to prevent overflow near u64::MAX
         current_offset = aligned_offset.saturating_add(unit.total_size);
     }
+    (offsets, current_offset)
+}
 
-    // Add tail padding to reach struct alignment
-    let optimized_size = align_up(current_offset, struct_alignment);
+/// Keep declaration order (prioritized units first) and fill each hole with the last later
+/// unit that fits in it, as `pahole --reorganize` does. Moving a unit into a hole never pushes
+/// anything later, so each move can only shrink the struct.
+fn pahole_order(mut units: Vec<SortableUnit>) -> Vec<SortableUnit> {
+    units.sort_by_key(|u| u.members.first().map_or(0, |m| m.offset));
+    units.sort_by_key(|u| !u.prioritized);
 
-    let savings_bytes = layout.size.saturating_sub(optimized_size);
-    let savings_percent =
-        if layout.size > 0 { (savings_bytes as f64 / layout.size as f64) * 100.0 } else { 0.0 };
+    // Each unit moves at most once per hole ahead of it; the cap only guards against cycles.
+    for _ in 0..units.len() * units.len() {
+        let mut moved = false;
+        let (offsets, _) = place_units(&units);
+        for i in 1..units.len() {
+            let hole_start = offsets[i - 1].saturating_add(units[i - 1].total_size);
+            if offsets[i] <= hole_start {
+                continue;
+            }
+            let fits = |u: &SortableUnit| {
+                align_up(hole_start, u.alignment).saturating_add(u.total_size) <= offsets[i]
+            };
+            if let Some(j) = (i + 1..units.len()).rev().find(|&j| fits(&units[j])) {
+                let unit = units.remove(j);
+                units.insert(i, unit);
+                moved = true;
+                break;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    units
+}
 
-    OptimizedLayout {
-        name: layout.name.clone(),
-        original_size: layout.size,
-        optimized_size,
-        savings_bytes,
-        savings_percent,
+/// Outcome of [`exhaustive_order`].
+struct ExhaustiveOutcome {
+    order: Vec<SortableUnit>,
+    /// The search found an order smaller than greedy's.
+    improved: bool,
+    /// The search stopped at its deadline, so `order` may not be optimal.
+    timed_out: bool,
+}
+
+/// Try every order of up to [`EXHAUSTIVE_MAX_UNITS`] units and keep the smallest, by
+/// branch-and-bound. The search starts from the greedy order, so it only departs from it for a
+/// strict improvement, and gives up at `deadline` with the best order found by then.
+fn exhaustive_order(
+    units: Vec<SortableUnit>,
+    struct_alignment: u64,
+    deadline: Instant,
+) -> ExhaustiveOutcome {
+    let units = greedy_order(units);
+    let (_, greedy_end) = place_units(&units);
+
+    let mut search = ExhaustiveSearch {
+        units: &units,
         struct_alignment,
-        original_members,
-        optimized_members,
-        skipped_members,
-        has_bitfields,
+        remaining_size: units.iter().map(|u| u.total_size).sum(),
+        remaining_prioritized: units.iter().filter(|u| u.prioritized).count(),
+        used: vec![false; units.len()],
+        order: Vec::with_capacity(units.len()),
+        best_size: align_up(greedy_end, struct_alignment),
+        best_order: None,
+        deadline,
+        nodes: 0,
+        timed_out: false,
+    };
+    search.run(0);
+
+    let timed_out = search.timed_out;
+    match search.best_order {
+        Some(best) => ExhaustiveOutcome {
+            order: best.into_iter().map(|i| units[i].clone()).collect(),
+            improved: true,
+            timed_out,
+        },
+        None => ExhaustiveOutcome { order: units, improved: false, timed_out },
     }
 }
 
+struct ExhaustiveSearch<'a> {
+    units: &'a [SortableUnit],
+    struct_alignment: u64,
+    remaining_size: u64,
+    remaining_prioritized: usize,
+    used: Vec<bool>,
+    order: Vec<usize>,
+    best_size: u64,
+    best_order: Option<Vec<usize>>,
+    deadline: Instant,
+    /// Search nodes visited; the clock is read every [`Self::CLOCK_INTERVAL`] of them.
+    nodes: u64,
+    timed_out: bool,
+}
+
+impl ExhaustiveSearch<'_> {
+    const CLOCK_INTERVAL: u64 = 1024;
+
+    fn run(&mut self, offset: u64) {
+        if self.timed_out {
+            return;
+        }
+        if self.nodes % Self::CLOCK_INTERVAL == 0 && Instant::now() >= self.deadline {
+            self.timed_out = true;
+            return;
+        }
+        self.nodes += 1;
+        if self.order.len() == self.units.len() {
+            let size = align_up(offset, self.struct_alignment);
+            if size < self.best_size {
+                self.best_size = size;
+                self.best_order = Some(self.order.clone());
+            }
+            return;
+        }
+        // No order of the remaining units can beat this bound.
+        if align_up(offset.saturating_add(self.remaining_size), self.struct_alignment)
+            >= self.best_size
+        {
+            return;
+        }
+
+        let mut tried: Vec<(u64, u64)> = Vec::new();
+        for i in 0..self.units.len() {
+            let unit = &self.units[i];
+            // Prioritized units stay ahead of the rest, as in the greedy order.
+            if self.used[i] || (self.remaining_prioritized > 0 && !unit.prioritized) {
+                continue;
+            }
+            // Units with the same shape are interchangeable; trying one is enough.
+            let shape = (unit.total_size, unit.alignment);
+            if tried.contains(&shape) {
+                continue;
+            }
+            tried.push(shape);
+
+            self.used[i] = true;
+            self.order.push(i);
+            self.remaining_size -= unit.total_size;
+            self.remaining_prioritized -= unit.prioritized as usize;
+
+            let start = align_up(offset, unit.alignment);
+            self.run(start.saturating_add(unit.total_size));
+
+            self.remaining_prioritized += unit.prioritized as usize;
+            self.remaining_size += unit.total_size;
+            self.order.pop();
+            self.used[i] = false;
+        }
+    }
+}
+
+/// Optimized size under each strategy, or an empty list when they all agree.
+/// `exhaustive-small` is only included for structs small enough to search.
+pub fn strategy_disagreement(layout: &StructLayout, max_align: u64) -> Vec<StrategyOutcome> {
+    let no_priority = HashSet::new();
+    let outcomes: Vec<StrategyOutcome> = OptimizeStrategy::ALL
+        .iter()
+        .map(|&s| (s, optimize_layout_with_strategy(layout, max_align, &no_priority, s)))
+        .filter(|(requested, o)| o.strategy == *requested)
+        .map(|(_, o)| StrategyOutcome { strategy: o.strategy, optimized_size: o.optimized_size })
+        .collect();
+
+    if outcomes.windows(2).all(|w| w[0].optimized_size == w[1].optimized_size) {
+        return Vec::new();
+    }
+    outcomes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{AggregateExtent, DeclaredSize};
 
     #[test]
     fn test_infer_alignment() {
@@ -340,6 +814,58 @@ mod tests {
         assert_eq!(align_up(u64::MAX, 8), u64::MAX);
     }
 
+    #[test]
+    fn test_flattened_aggregate_moves_as_one_block() {
+        // struct { char a; struct { char lo; long hi; }; char c; } = 32 bytes
+        let mut layout = StructLayout::new("Test".to_string(), 32, Some(8));
+        let field = |name: &str, offset, size| {
+            let mut m =
+                MemberLayout::new(name.to_string(), "x".to_string(), Some(offset), Some(size));
+            m.aggregate = Some("<anonymous>@8".to_string());
+            m
+        };
+        layout.members = vec![
+            MemberLayout::new("a".to_string(), "char".to_string(), Some(0), Some(1)),
+            field("lo", 8, 1),
+            field("hi", 16, 8),
+            MemberLayout::new("c".to_string(), "char".to_string(), Some(24), Some(1)),
+        ];
+
+        let result = optimize_layout(&layout, 8);
+
+        // `lo` keeps its padding to `hi`: the inner struct's layout is not ours to change.
+        let offsets: Vec<(&str, u64)> =
+            result.optimized_members.iter().map(|m| (m.name.as_str(), m.offset)).collect();
+        assert_eq!(offsets, vec![("lo", 0), ("hi", 8), ("a", 16), ("c", 17)]);
+        assert_eq!(result.optimized_size, 24);
+    }
+
+    #[test]
+    fn test_flattened_aggregate_keeps_its_tail_padding() {
+        // struct { struct { long hi; short lo; }; short a; char b; } = 24 bytes: the inner
+        // struct is 16 bytes, 6 of them tail padding nothing else may use.
+        let mut layout = StructLayout::new("Test".to_string(), 24, Some(8));
+        let field = |name: &str, offset, size| {
+            let mut m =
+                MemberLayout::new(name.to_string(), "x".to_string(), Some(offset), Some(size));
+            m.aggregate = Some("<anonymous>@0".to_string());
+            m.aggregate_extent = Some(AggregateExtent { size: 16, alignment: Some(8) });
+            m
+        };
+        layout.members = vec![
+            field("hi", 0, 8),
+            field("lo", 8, 2),
+            MemberLayout::new("a".to_string(), "short".to_string(), Some(16), Some(2)),
+            MemberLayout::new("b".to_string(), "char".to_string(), Some(18), Some(1)),
+        ];
+
+        let result = optimize_layout(&layout, 8);
+
+        let a = result.optimized_members.iter().find(|m| m.name == "a").unwrap();
+        assert!(a.offset >= 16, "`a` placed inside the inner struct at {}", a.offset);
+        assert_eq!(result.optimized_size, 24);
+    }
+
     #[test]
     fn test_optimize_padded_struct() {
         // struct { char a; int b; char c; } = 12 bytes with padding
@@ -378,6 +904,195 @@ mod tests {
         assert_eq!(result.savings_bytes, 0);
     }
 
+    #[test]
+    fn test_dwarf_alignment_beats_size_inference() {
+        // struct { char a; alignas(32) int b; char c; } with no struct alignment recorded:
+        // a size-based guess would align `b` to 4 and promise a 12-byte struct.
+        let mut layout = StructLayout::new("Test".to_string(), 64, None);
+        layout.members = vec![
+            MemberLayout::new("a".to_string(), "char".to_string(), Some(0), Some(1)),
+            MemberLayout::new("b".to_string(), "int".to_string(), Some(32), Some(4)),
+            MemberLayout::new("c".to_string(), "char".to_string(), Some(36), Some(1)),
+        ];
+        layout.members[1].alignment = Some(32);
+
+        let result = optimize_layout(&layout, 8);
+
+        assert_eq!(result.struct_alignment, 32);
+        assert_eq!(result.optimized_members[0].name, "b");
+        assert_eq!(result.optimized_members[0].alignment, 32);
+        assert_eq!(result.optimized_size, 32);
+        assert_eq!(result.savings_bytes, 32);
+    }
+
+    #[test]
+    fn test_priority_members_placed_first() {
+        let mut layout = StructLayout::new("Test".to_string(), 24, Some(8));
+        layout.members = vec![
+            MemberLayout::new("big".to_string(), "u64".to_string(), Some(0), Some(8)),
+            MemberLayout::new("other".to_string(), "u64".to_string(), Some(8), Some(8)),
+            MemberLayout::new("hot".to_string(), "u8".to_string(), Some(16), Some(1)),
+        ];
+
+        let priority: HashSet<&str> = ["hot"].into_iter().collect();
+        let result = optimize_layout_with_priority(&layout, 8, &priority);
+
+        assert_eq!(result.optimized_members[0].name, "hot");
+        assert_eq!(result.optimized_members[0].offset, 0);
+        assert_eq!(result.optimized_members[1].offset, 8);
+    }
+
+    fn members(layout: &mut StructLayout, fields: &[(&str, u64, u64)]) {
+        layout.members = fields
+            .iter()
+            .map(|&(name, offset, size)| {
+                MemberLayout::new(
+                    name.to_string(),
+                    format!("u{}", size * 8),
+                    Some(offset),
+                    Some(size),
+                )
+            })
+            .collect();
+    }
+
+    fn names(members: &[OptimizedMember]) -> Vec<&str> {
+        members.iter().map(|m| m.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_alignment_source_explains_each_requirement() {
+        let sources = |layout: &StructLayout| {
+            let result = optimize_layout(layout, 8);
+            let mut sources: Vec<(String, u64, AlignmentSource)> = result
+                .optimized_members
+                .iter()
+                .map(|m| (m.name.clone(), m.alignment, m.alignment_source))
+                .collect();
+            sources.sort_by(|a, b| a.0.cmp(&b.0));
+            sources
+        };
+
+        let mut layout = StructLayout::new("Sources".to_string(), 48, Some(16));
+        members(&mut layout, &[("v", 0, 16), ("n", 16, 4), ("g", 24, 8), ("t", 32, 2)]);
+        layout.members[0].alignment = Some(16);
+        layout.members[0].explicit_alignment = true;
+        layout.members[1].alignment = Some(4);
+        layout.members[3].assumed = Some(DeclaredSize { size: 2, align: Some(2) });
+        assert_eq!(
+            sources(&layout),
+            vec![
+                ("g".to_string(), 8, AlignmentSource::Size),
+                ("n".to_string(), 4, AlignmentSource::Natural),
+                ("t".to_string(), 2, AlignmentSource::TypeSizes),
+                ("v".to_string(), 16, AlignmentSource::Explicit),
+            ]
+        );
+
+        // #pragma pack(2): char, int at 2
+        let mut packed = StructLayout::new("Packed".to_string(), 6, Some(2));
+        members(&mut packed, &[("a", 0, 1), ("b", 2, 4)]);
+        packed.members[0].alignment = Some(1);
+        packed.members[1].alignment = Some(4);
+        assert_eq!(
+            sources(&packed),
+            vec![
+                ("a".to_string(), 1, AlignmentSource::Natural),
+                ("b".to_string(), 2, AlignmentSource::Packed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pahole_fills_holes_in_declaration_order() {
+        let mut layout = StructLayout::new("Test".to_string(), 24, Some(8));
+        members(&mut layout, &[("a", 0, 1), ("b", 8, 8), ("c", 16, 1), ("d", 20, 4)]);
+
+        let result =
+            optimize_layout_with_strategy(&layout, 8, &HashSet::new(), OptimizeStrategy::Pahole);
+        assert_eq!(result.strategy, OptimizeStrategy::Pahole);
+        assert_eq!(result.optimized_size, 16);
+        assert_eq!(names(&result.optimized_members), vec!["a", "c", "d", "b"]);
+    }
+
+    #[test]
+    fn test_exhaustive_beats_greedy_and_disagreement_is_reported() {
+        // A 12-byte member is 8-aligned, so greedy puts the other 8-aligned member next and
+        // leaves a hole the 4-byte member could have filled.
+        let mut layout = StructLayout::new("Test".to_string(), 32, Some(8));
+        members(&mut layout, &[("a", 0, 12), ("c", 16, 8), ("b", 24, 4)]);
+
+        assert_eq!(optimize_layout(&layout, 8).optimized_size, 32);
+        let exhaustive = optimize_layout_with_strategy(
+            &layout,
+            8,
+            &HashSet::new(),
+            OptimizeStrategy::ExhaustiveSmall,
+        );
+        assert_eq!(exhaustive.strategy, OptimizeStrategy::ExhaustiveSmall);
+        assert_eq!(exhaustive.optimized_size, 24);
+        assert_eq!(names(&exhaustive.optimized_members), vec!["a", "b", "c"]);
+
+        let sizes: Vec<(OptimizeStrategy, u64)> = strategy_disagreement(&layout, 8)
+            .iter()
+            .map(|o| (o.strategy, o.optimized_size))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![
+                (OptimizeStrategy::Greedy, 32),
+                (OptimizeStrategy::Pahole, 24),
+                (OptimizeStrategy::ExhaustiveSmall, 24)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_exhaustive_search_respects_deadline() {
+        let unit = |name: &str, size: u64, alignment: u64| SortableUnit {
+            members: vec![OptimizedMember {
+                name: name.to_string(),
+                type_name: "t".to_string(),
+                offset: 0,
+                size,
+                alignment,
+                alignment_source: AlignmentSource::Natural,
+                bit_offset: None,
+                bit_size: None,
+                type_tag: None,
+            }],
+            total_size: size,
+            alignment,
+            prioritized: false,
+        };
+        let units = || vec![unit("a", 12, 8), unit("c", 8, 8), unit("b", 4, 4)];
+
+        let done = exhaustive_order(units(), 8, Instant::now() + Duration::from_secs(60));
+        assert!(done.improved && !done.timed_out);
+        let expired = exhaustive_order(units(), 8, Instant::now());
+        assert!(!expired.improved && expired.timed_out);
+        let names: Vec<&str> = expired.order.iter().map(|u| u.members[0].name.as_str()).collect();
+        assert_eq!(names, vec!["a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_exhaustive_falls_back_to_greedy_for_large_structs() {
+        let mut layout = StructLayout::new("Test".to_string(), 13, Some(1));
+        let fields: Vec<(String, u64)> = (0..13).map(|i| (format!("f{}", i), i)).collect();
+        let fields: Vec<(&str, u64, u64)> =
+            fields.iter().map(|(n, o)| (n.as_str(), *o, 1)).collect();
+        members(&mut layout, &fields);
+
+        let result = optimize_layout_with_strategy(
+            &layout,
+            8,
+            &HashSet::new(),
+            OptimizeStrategy::ExhaustiveSmall,
+        );
+        assert_eq!(result.strategy, OptimizeStrategy::Greedy);
+        assert!(strategy_disagreement(&layout, 8).is_empty());
+    }
+
     #[test]
     fn test_skipped_members() {
         let mut layout = StructLayout::new("Test".to_string(), 16, Some(8));
@@ -0,0 +1,148 @@
+//! Packed layouts (`#pragma pack`, `__attribute__((packed))`, `#[repr(packed)]`): structs whose
+//! member offsets are tighter than the members' natural alignment. Reordering such a struct
+//! cannot rely on alignment the pragma takes away, so suggestions cap alignments at the pack.
+
+use crate::types::StructLayout;
+use serde::Serialize;
+
+/// The alignment a layout actually honours: the largest power of two up to `natural` that
+/// every placed member (`(offset, alignment)`) and the size respect. Equal to `natural` for
+/// unpacked layouts.
+pub(crate) fn implied_alignment(
+    natural: u64,
+    size: Option<u64>,
+    placements: impl Iterator<Item = (u64, u64)> + Clone,
+) -> u64 {
+    let mut pack = natural.max(1);
+    while pack > 1 {
+        let fits = |offset: u64, alignment: u64| offset % alignment.clamp(1, pack) == 0;
+        if size.is_none_or(|size| fits(size, pack))
+            && placements.clone().all(|(offset, alignment)| fits(offset, alignment))
+        {
+            break;
+        }
+        pack /= 2;
+    }
+    pack
+}
+
+/// The pack value a layout's offsets imply, when it is below the alignment its members would
+/// naturally get. Only members with a known alignment and offset count; bitfields are skipped.
+pub fn detect_pack(layout: &StructLayout) -> Option<u64> {
+    let placements = layout.members.iter().filter_map(|m| {
+        if m.bit_size.is_some() || m.size.is_none_or(|size| size == 0) {
+            return None;
+        }
+        Some((m.offset?, m.assumed.and_then(|a| a.align).or(m.alignment)?))
+    });
+    let natural = placements.clone().map(|(_, alignment)| alignment).max()?;
+    if !natural.is_power_of_two() {
+        return None;
+    }
+    let pack = implied_alignment(natural, Some(layout.size), placements);
+    (pack < natural).then_some(pack)
+}
+
+/// Consecutively declared structs in one file sharing a pack value, such as the structs
+/// between a `#pragma pack(push, N)` and its `pop`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackedRegion {
+    pub file: String,
+    pub first_line: u64,
+    pub last_line: u64,
+    pub pack: u64,
+    /// Struct names in declaration order.
+    pub structs: Vec<String>,
+}
+
+/// Group packed structs with a declaration site into regions. An unpacked struct declared
+/// between two packed ones, or a different pack value, starts a new region.
+pub fn packed_regions(layouts: &[StructLayout]) -> Vec<PackedRegion> {
+    let mut located: Vec<(&str, u64, &str, Option<u64>)> = layouts
+        .iter()
+        .filter_map(|l| {
+            let location = l.source_location.as_ref()?;
+            Some((location.file.as_str(), location.line, l.name.as_str(), detect_pack(l)))
+        })
+        .collect();
+    located.sort();
+    located.dedup_by(|a, b| (a.0, a.1, a.2) == (b.0, b.1, b.2));
+
+    let mut regions: Vec<PackedRegion> = Vec::new();
+    let mut open = false;
+    for (file, line, name, pack) in located {
+        let Some(pack) = pack else {
+            open = false;
+            continue;
+        };
+        match regions.last_mut() {
+            Some(region) if open && region.file == file && region.pack == pack => {
+                region.last_line = line;
+                region.structs.push(name.to_string());
+            }
+            _ => regions.push(PackedRegion {
+                file: file.to_string(),
+                first_line: line,
+                last_line: line,
+                pack,
+                structs: vec![name.to_string()],
+            }),
+        }
+        open = true;
+    }
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{MemberLayout, SourceLocation};
+
+    /// `(name, offset, size, alignment)` members declared at `file:line`.
+    fn layout(name: &str, size: u64, members: &[(&str, u64, u64, u64)], line: u64) -> StructLayout {
+        let mut layout = StructLayout::new(name.to_string(), size, None);
+        layout.source_location = Some(SourceLocation { file: "wire.h".to_string(), line });
+        layout.members = members
+            .iter()
+            .map(|&(name, offset, size, alignment)| {
+                let mut m =
+                    MemberLayout::new(name.to_string(), "t".to_string(), Some(offset), Some(size));
+                m.alignment = Some(alignment);
+                m
+            })
+            .collect();
+        layout
+    }
+
+    #[test]
+    fn detects_the_pack_value_offsets_imply() {
+        // #pragma pack(1): char, int at 1
+        let packed1 = layout("P1", 5, &[("a", 0, 1, 1), ("b", 1, 4, 4)], 1);
+        assert_eq!(detect_pack(&packed1), Some(1));
+        // #pragma pack(2): char, int at 2, long at 6
+        let packed2 = layout("P2", 14, &[("a", 0, 1, 1), ("b", 2, 4, 4), ("c", 6, 8, 8)], 1);
+        assert_eq!(detect_pack(&packed2), Some(2));
+        // packed only in its size: int, char with no tail padding
+        let tail = layout("T", 5, &[("a", 0, 4, 4), ("b", 4, 1, 1)], 1);
+        assert_eq!(detect_pack(&tail), Some(1));
+        let natural = layout("N", 8, &[("a", 0, 1, 1), ("b", 4, 4, 4)], 1);
+        assert_eq!(detect_pack(&natural), None);
+    }
+
+    #[test]
+    fn consecutive_packed_structs_form_a_region() {
+        let layouts = vec![
+            layout("Header", 5, &[("a", 0, 1, 1), ("b", 1, 4, 4)], 10),
+            layout("Body", 3, &[("a", 0, 1, 1), ("b", 1, 2, 2)], 20),
+            layout("Natural", 8, &[("a", 0, 1, 1), ("b", 4, 4, 4)], 30),
+            layout("Trailer", 5, &[("a", 0, 1, 1), ("b", 1, 4, 4)], 40),
+        ];
+
+        let regions = packed_regions(&layouts);
+
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].structs, vec!["Header", "Body"]);
+        assert_eq!((regions[0].first_line, regions[0].last_line, regions[0].pack), (10, 20, 1));
+        assert_eq!(regions[1].structs, vec!["Trailer"]);
+    }
+}
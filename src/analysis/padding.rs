@@ -14,15 +14,11 @@ pub fn analyze_layout(layout: &mut StructLayout, cache_line_size: u32) {
     }
 
     let mut spans = Vec::new();
-    let mut partial = false;
+    let mut partial_members = Vec::new();
 
     for member in &layout.members {
-        let Some(member_offset) = member.offset else {
-            partial = true;
-            continue;
-        };
-        let Some(member_size) = member.size else {
-            partial = true;
+        let (Some(member_offset), Some(member_size)) = (member.offset, member.size) else {
+            partial_members.push(member.name.clone());
             continue;
         };
         if member_size == 0 {
@@ -36,6 +32,8 @@ pub fn analyze_layout(layout: &mut StructLayout, cache_line_size: u32) {
         });
     }
 
+    let partial = !partial_members.is_empty();
+    let stride = layout.size.next_multiple_of(layout.alignment.unwrap_or(1).max(1));
     spans.sort_by_key(|s| (s.start, s.end));
 
     let mut padding_holes = Vec::new();
@@ -60,7 +58,15 @@ pub fn analyze_layout(layout: &mut StructLayout, cache_line_size: u32) {
             cache_line_density: 0.0,
             padding_holes,
             partial,
+            partial_members,
             false_sharing: None,
+            bases: Vec::new(),
+            alloc_slack: None,
+            bits: None,
+            stride,
+            stride_waste: 0,
+            static_instances: None,
+            weighted_waste_bytes: None,
         };
         return;
     }
@@ -133,7 +139,15 @@ pub fn analyze_layout(layout: &mut StructLayout, cache_line_size: u32) {
         cache_line_density,
         padding_holes,
         partial,
+        partial_members,
         false_sharing: None,
+        bases: Vec::new(),
+        alloc_slack: None,
+        bits: None,
+        stride,
+        stride_waste: if partial { 0 } else { stride.saturating_sub(useful_size) },
+        static_instances: None,
+        weighted_waste_bytes: None,
     };
 }
 
@@ -164,6 +178,24 @@ mod tests {
         assert!(layout.metrics.padding_holes.is_empty());
     }
 
+    #[test]
+    fn stride_rounds_size_up_to_alignment() {
+        let member = MemberLayout::new("v".to_string(), "u64".to_string(), Some(0), Some(8));
+        let mut layout = StructLayout::new("Slot".to_string(), 64, Some(64));
+        layout.members = vec![member.clone()];
+        analyze_layout(&mut layout, 64);
+        assert_eq!(layout.metrics.stride, 64);
+        assert_eq!(layout.metrics.stride_waste, 56);
+        assert_eq!(layout.metrics.stride_factor(), Some(8.0));
+
+        // A declared alignment beyond the size (possible in hand-written DWARF) widens the stride.
+        let mut layout = StructLayout::new("Odd".to_string(), 8, Some(16));
+        layout.members = vec![member];
+        analyze_layout(&mut layout, 64);
+        assert_eq!(layout.metrics.stride, 16);
+        assert_eq!(layout.metrics.stride_waste, 8);
+    }
+
     #[test]
     fn test_zero_size_layout_no_spans() {
         // Layout with size=0 and no members
@@ -190,6 +222,7 @@ mod tests {
         analyze_layout(&mut layout, 64);
 
         assert!(layout.metrics.partial);
+        assert_eq!(layout.metrics.partial_members, vec!["b"]);
         // With partial=true, no padding holes should be reported
         assert!(layout.metrics.padding_holes.is_empty());
     }
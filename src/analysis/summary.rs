@@ -0,0 +1,194 @@
+use crate::names::embedded_type;
+use crate::types::{SharedGlobal, StructLayout};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// How many structs a [`BinarySummary`] lists as its worst offenders.
+pub const SUMMARY_TOP_OFFENDERS: usize = 10;
+
+/// Upper bounds (exclusive) of the padding percentage buckets after the padding-free one; the
+/// last bucket takes everything from 50% up.
+const BUCKETS: [(&str, f64); 4] =
+    [("0-10%", 10.0), ("10-25%", 25.0), ("25-50%", 50.0), ("50%+", f64::INFINITY)];
+
+/// Binary-wide padding totals: one set of numbers to track from build to build.
+#[derive(Debug, Clone, Serialize)]
+pub struct BinarySummary {
+    /// Structs counted in the totals. Partial layouts are left out, as their padding is not
+    /// measured.
+    pub structs: usize,
+    pub partial_structs: usize,
+    pub total_bytes: u64,
+    pub padding_bytes: u64,
+    pub padding_percent: f64,
+    /// Global and static variables of a counted struct type, with each array element counted.
+    pub static_instances: u64,
+    /// Padding summed over every static instance: bytes the binary's data actually wastes.
+    pub static_waste_bytes: u64,
+    /// Structs per padding percentage range, padding-free first.
+    pub histogram: Vec<PaddingBucket>,
+    /// The structs with the most padding bytes, worst first (ties by name).
+    pub top_offenders: Vec<SummaryOffender>,
+}
+
+/// Structs whose padding percentage falls in one range.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PaddingBucket {
+    pub range: &'static str,
+    pub structs: usize,
+    pub padding_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryOffender {
+    pub name: String,
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub binary: Option<String>,
+    pub size: u64,
+    pub padding_bytes: u64,
+    pub padding_percent: f64,
+    pub static_instances: u64,
+    pub static_waste_bytes: u64,
+}
+
+/// Static instances of each struct in `layouts`, by name: every global or static variable of
+/// the struct's type, or an array of it, counts once per element. Pointers count for nothing.
+/// `globals` should come from [`place_globals`](super::place_globals), so each one is sized by
+/// its symbol and counted once.
+pub fn count_static_instances(
+    globals: &[SharedGlobal],
+    layouts: &[StructLayout],
+) -> HashMap<String, u64> {
+    let mut sizes: HashMap<&str, u64> = HashMap::new();
+    for layout in layouts.iter().filter(|l| l.size > 0) {
+        sizes.entry(layout.name.as_str()).or_insert(layout.size);
+    }
+    let mut instances = HashMap::new();
+    for global in globals {
+        let Some((name, size)) =
+            embedded_type(&global.type_name).and_then(|t| sizes.get_key_value(t))
+        else {
+            continue;
+        };
+        *instances.entry(name.to_string()).or_insert(0) += (global.size / size).max(1);
+    }
+    instances
+}
+
+/// Totals over `layouts`, weighted by the `static_instances` in their metrics.
+pub fn summarize_binary(layouts: &[StructLayout]) -> BinarySummary {
+    let counted: Vec<&StructLayout> = layouts.iter().filter(|l| !l.metrics.partial).collect();
+    let total_bytes: u64 = counted.iter().map(|l| l.size).sum();
+    let padding_bytes: u64 = counted.iter().map(|l| l.metrics.padding_bytes).sum();
+
+    let mut histogram: Vec<PaddingBucket> = std::iter::once("0%")
+        .chain(BUCKETS.iter().map(|(range, _)| *range))
+        .map(|range| PaddingBucket { range, structs: 0, padding_bytes: 0 })
+        .collect();
+    let mut offenders = Vec::new();
+    let (mut static_instances, mut static_waste_bytes) = (0, 0);
+    for layout in &counted {
+        let percent = layout.metrics.padding_percentage;
+        let bucket = if layout.metrics.padding_bytes == 0 {
+            0
+        } else {
+            1 + BUCKETS.iter().position(|(_, below)| percent < *below).unwrap_or(BUCKETS.len() - 1)
+        };
+        histogram[bucket].structs += 1;
+        histogram[bucket].padding_bytes += layout.metrics.padding_bytes;
+
+        let count = layout.metrics.static_instances.unwrap_or(0);
+        let waste = layout.metrics.weighted_waste_bytes.unwrap_or(0);
+        static_instances += count;
+        static_waste_bytes += waste;
+        if layout.metrics.padding_bytes > 0 {
+            offenders.push(SummaryOffender {
+                name: layout.name.clone(),
+                binary: layout.binary.clone(),
+                size: layout.size,
+                padding_bytes: layout.metrics.padding_bytes,
+                padding_percent: percent,
+                static_instances: count,
+                static_waste_bytes: waste,
+            });
+        }
+    }
+    offenders.sort_by(|a, b| b.padding_bytes.cmp(&a.padding_bytes).then(a.name.cmp(&b.name)));
+    offenders.truncate(SUMMARY_TOP_OFFENDERS);
+
+    BinarySummary {
+        structs: counted.len(),
+        partial_structs: layouts.len() - counted.len(),
+        total_bytes,
+        padding_bytes,
+        padding_percent: if total_bytes > 0 {
+            padding_bytes as f64 / total_bytes as f64 * 100.0
+        } else {
+            0.0
+        },
+        static_instances,
+        static_waste_bytes,
+        histogram,
+        top_offenders: offenders,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GlobalVariable;
+
+    fn layout(name: &str, size: u64, padding: u64) -> StructLayout {
+        let mut l = StructLayout::new(name.to_string(), size, Some(8));
+        l.metrics.padding_bytes = padding;
+        l.metrics.padding_percentage = padding as f64 / size as f64 * 100.0;
+        l
+    }
+
+    fn global(name: &str, type_name: &str, size: u64) -> SharedGlobal {
+        let global =
+            GlobalVariable { name: name.into(), type_name: type_name.into(), address: 0, size };
+        global.into()
+    }
+
+    #[test]
+    fn summary_weights_padding_by_static_instances() {
+        let mut partial = layout("Partial", 16, 0);
+        partial.metrics.partial = true;
+        let mut layouts = vec![
+            layout("Tight", 16, 0),
+            layout("Loose", 24, 7),
+            layout("Half", 16, 8),
+            layout("Bit", 100, 3),
+            partial,
+        ];
+        let globals = [
+            global("one", "Loose", 24),
+            global("table", "const [Loose; 4]", 96),
+            global("ptr", "*Half", 8),
+            global("half", "Half", 16),
+            global("other", "u64", 8),
+        ];
+        let instances = count_static_instances(&globals, &layouts);
+        assert_eq!(instances.get("Loose"), Some(&5));
+        assert_eq!(instances.get("Half"), Some(&1));
+
+        for layout in &mut layouts {
+            let count = instances.get(&layout.name).copied().unwrap_or(0);
+            layout.metrics.static_instances = Some(count);
+            layout.metrics.weighted_waste_bytes = Some(count * layout.metrics.padding_bytes);
+        }
+        let summary = summarize_binary(&layouts);
+        assert_eq!((summary.structs, summary.partial_structs), (4, 1));
+        assert_eq!((summary.total_bytes, summary.padding_bytes), (156, 18));
+        assert_eq!((summary.static_instances, summary.static_waste_bytes), (6, 5 * 7 + 8));
+        let histogram: Vec<(&str, usize)> =
+            summary.histogram.iter().map(|b| (b.range, b.structs)).collect();
+        assert_eq!(
+            histogram,
+            vec![("0%", 1), ("0-10%", 1), ("10-25%", 0), ("25-50%", 1), ("50%+", 1)]
+        );
+        let offenders: Vec<&str> = summary.top_offenders.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(offenders, vec!["Half", "Loose", "Bit"]);
+    }
+}
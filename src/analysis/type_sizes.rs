@@ -0,0 +1,149 @@
+//! Sizes declared in the config for types the debug info only declares: pimpl targets, and
+//! types from units built without full debug info (`-fno-standalone-debug`,
+//! `-flimit-debug-info`). Members of such types have no size, which leaves their struct's
+//! layout partial.
+
+use crate::types::{DeclaredSize, StructLayout};
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+/// The config's `type_sizes` map, from type name (as shown in member types) to its layout.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(transparent)]
+pub struct TypeSizes(IndexMap<String, DeclaredSize>);
+
+/// A declared size that contradicts the debug info, which wins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeSizeConflict {
+    pub struct_name: String,
+    pub member: String,
+    pub type_name: String,
+    pub declared: u64,
+    pub actual: u64,
+}
+
+impl TypeSizes {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Check that alignments are powers of two and sizes are multiples of them.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, declared) in &self.0 {
+            let Some(align) = declared.align else { continue };
+            if !align.is_power_of_two() {
+                return Err(format!(
+                    "Invalid type_sizes entry '{}': align {} is not a power of two",
+                    name, align
+                ));
+            }
+            if declared.size % align != 0 {
+                return Err(format!(
+                    "Invalid type_sizes entry '{}': size {} is not a multiple of align {}",
+                    name, declared.size, align
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Fill in the size of members whose type is declared here and has no size in the debug
+    /// info, marking each with the assumption. Arrays of a declared type and cv-qualified
+    /// spellings match too. Members whose debug info does give a size keep it, and take the
+    /// declared alignment only when the sizes agree; disagreements are returned.
+    pub fn apply(&self, layouts: &mut [StructLayout]) -> Vec<TypeSizeConflict> {
+        let mut conflicts = Vec::new();
+        if self.0.is_empty() {
+            return conflicts;
+        }
+        for layout in layouts {
+            for member in &mut layout.members {
+                let Some(declared) = self.lookup(&member.type_name) else { continue };
+                match member.size {
+                    None => {
+                        member.size = Some(declared.size);
+                        member.assumed = Some(declared);
+                    }
+                    Some(actual) if actual == declared.size => {
+                        member.assumed = declared.align.is_some().then_some(declared);
+                    }
+                    Some(actual) => conflicts.push(TypeSizeConflict {
+                        struct_name: layout.name.clone(),
+                        member: member.name.clone(),
+                        type_name: member.type_name.clone(),
+                        declared: declared.size,
+                        actual,
+                    }),
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// The declared layout of `type_name`, looking through `const`/`volatile` and, for
+    /// `[T; N]`, scaling `T`'s size by `N`.
+    fn lookup(&self, type_name: &str) -> Option<DeclaredSize> {
+        let mut name = type_name;
+        while let Some(rest) =
+            name.strip_prefix("const ").or_else(|| name.strip_prefix("volatile "))
+        {
+            name = rest;
+        }
+        if let Some(declared) = self.0.get(name) {
+            return Some(*declared);
+        }
+        let (element, count) = name.strip_prefix('[')?.strip_suffix(']')?.rsplit_once("; ")?;
+        let element = self.lookup(element)?;
+        let size = element.size.checked_mul(count.parse().ok()?)?;
+        Some(DeclaredSize { size, align: element.align })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    fn sizes(yaml: &str) -> TypeSizes {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn fills_missing_sizes_and_flags_them() {
+        let mut layout = StructLayout::new("Widget".to_string(), 48, Some(8));
+        layout.members = vec![
+            MemberLayout::new("impl_".to_string(), "Impl".to_string(), Some(0), None),
+            MemberLayout::new("pair".to_string(), "[const Impl; 2]".to_string(), Some(8), None),
+            MemberLayout::new("id".to_string(), "int".to_string(), Some(40), Some(4)),
+        ];
+        let mut layouts = [layout];
+        let conflicts = sizes("Impl: { size: 16, align: 8 }\n").apply(&mut layouts);
+        assert!(conflicts.is_empty());
+        let members = &layouts[0].members;
+        assert_eq!(members[0].size, Some(16));
+        assert_eq!(members[0].assumed, Some(DeclaredSize { size: 16, align: Some(8) }));
+        assert_eq!(members[1].size, Some(32));
+        assert_eq!(members[2].assumed, None);
+    }
+
+    #[test]
+    fn debug_info_sizes_win() {
+        let mut layout = StructLayout::new("Widget".to_string(), 8, Some(8));
+        layout.members =
+            vec![MemberLayout::new("impl_".to_string(), "Impl".to_string(), Some(0), Some(8))];
+        let mut layouts = [layout];
+        let conflicts = sizes("Impl: { size: 16 }\n").apply(&mut layouts);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!((conflicts[0].declared, conflicts[0].actual), (16, 8));
+        assert_eq!(layouts[0].members[0].size, Some(8));
+        assert_eq!(layouts[0].members[0].assumed, None);
+    }
+
+    #[test]
+    fn validation_rejects_bad_alignments() {
+        assert!(sizes("A: { size: 12, align: 4 }\n").validate().is_ok());
+        assert!(sizes("A: { size: 12, align: 3 }\n").validate().is_err());
+        assert!(sizes("A: { size: 12, align: 8 }\n").validate().is_err());
+        assert!(serde_yaml::from_str::<TypeSizes>("A: { size: 4, alignment: 4 }\n").is_err());
+    }
+}
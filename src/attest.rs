@@ -0,0 +1,243 @@
+//! Build provenance for `check`: an [in-toto] statement naming the checked binaries by SHA-256
+//! digest, with the tool version, config digest, and results digest as its predicate. A
+//! signing command can wrap it in a [DSSE] envelope, so the attestation can travel with the
+//! build's other supply-chain evidence.
+//!
+//! [in-toto]: https://github.com/in-toto/attestation/blob/main/spec/v1/statement.md
+//! [DSSE]: https://github.com/secure-systems-lab/dsse/blob/master/envelope.md
+
+use crate::error::{Error, Result};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::Serialize;
+use sha2::{Digest as _, Sha256};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+pub const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+pub const PREDICATE_TYPE: &str = "https://github.com/avifenesh/audit-struct/check/v1";
+/// DSSE payload type of an in-toto statement.
+pub const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// SHA-256 digest, lowercase hex, keyed by algorithm as in-toto expects.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Digest {
+    pub sha256: String,
+}
+
+impl Digest {
+    pub fn of(data: &[u8]) -> Self {
+        let hash = Sha256::digest(data);
+        Self { sha256: hash.iter().map(|b| format!("{:02x}", b)).collect() }
+    }
+}
+
+/// A checked binary.
+#[derive(Debug, Clone, Serialize)]
+pub struct Subject {
+    pub name: String,
+    pub digest: Digest,
+    /// `gnu_build_id` (hex) when the binary carries a build-id note.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl Subject {
+    /// Digest a binary's contents under `name`.
+    pub fn new(name: impl Into<String>, data: &[u8]) -> Self {
+        let mut annotations = BTreeMap::new();
+        if let Ok(object) = object::File::parse(data)
+            && let Ok(Some(build_id)) = object::Object::build_id(&object)
+        {
+            let hex = build_id.iter().map(|b| format!("{:02x}", b)).collect();
+            annotations.insert("gnu_build_id".to_string(), hex);
+        }
+        Self { name: name.into(), digest: Digest::of(data), annotations }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Tool {
+    pub name: &'static str,
+    pub version: &'static str,
+}
+
+/// The config a check ran against. `source` is its path, `stdin`, or the environment variable
+/// it came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigDigest {
+    pub source: String,
+    pub digest: Digest,
+}
+
+/// Outcome of the check. `digest` covers the JSON report exactly as `check -o json` prints it.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResults {
+    pub digest: Digest,
+    pub violations: usize,
+    pub failing: usize,
+    pub passed: bool,
+}
+
+impl CheckResults {
+    pub fn new(report: &[u8], violations: usize, failing: usize) -> Self {
+        Self { digest: Digest::of(report), violations, failing, passed: failing == 0 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckPredicate {
+    pub tool: Tool,
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub config: Option<ConfigDigest>,
+    pub results: CheckResults,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub statement_type: &'static str,
+    pub subject: Vec<Subject>,
+    #[serde(rename = "predicateType")]
+    pub predicate_type: &'static str,
+    pub predicate: CheckPredicate,
+}
+
+impl Statement {
+    pub fn new(results: CheckResults) -> Self {
+        Self {
+            statement_type: STATEMENT_TYPE,
+            subject: Vec::new(),
+            predicate_type: PREDICATE_TYPE,
+            predicate: CheckPredicate {
+                tool: Tool { name: env!("CARGO_PKG_NAME"), version: env!("CARGO_PKG_VERSION") },
+                config: None,
+                results,
+            },
+        }
+    }
+
+    pub fn with_subjects(mut self, subjects: Vec<Subject>) -> Self {
+        self.subject.extend(subjects);
+        self
+    }
+
+    pub fn with_config(mut self, source: impl Into<String>, text: &[u8]) -> Self {
+        self.predicate.config =
+            Some(ConfigDigest { source: source.into(), digest: Digest::of(text) });
+        self
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("statement serializes")
+    }
+
+    /// Sign the statement with `command` (split on whitespace), which reads the DSSE
+    /// pre-authentication encoding on stdin and writes the signature to stdout.
+    pub fn sign(&self, command: &str) -> Result<Envelope> {
+        let payload = serde_json::to_vec(self).expect("statement serializes");
+        let sig = run_signer(command, &pae(PAYLOAD_TYPE, &payload))?;
+        Ok(Envelope {
+            payload_type: PAYLOAD_TYPE,
+            payload: BASE64.encode(&payload),
+            signatures: vec![Signature { sig: BASE64.encode(sig) }],
+        })
+    }
+}
+
+/// A DSSE envelope holding a signed statement.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    pub payload_type: &'static str,
+    /// The statement, base64-encoded.
+    pub payload: String,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Signature {
+    /// The signing command's output, base64-encoded.
+    pub sig: String,
+}
+
+/// DSSE pre-authentication encoding: what the signature actually covers.
+fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut out =
+        format!("DSSEv1 {} {} {} ", payload_type.len(), payload_type, payload.len()).into_bytes();
+    out.extend_from_slice(payload);
+    out
+}
+
+fn run_signer(command: &str, input: &[u8]) -> Result<Vec<u8>> {
+    let mut words = command.split_whitespace();
+    let program =
+        words.next().ok_or_else(|| Error::Attestation("signing command is empty".to_string()))?;
+    let mut child = Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            Error::Attestation(format!("cannot run signing command {}: {}", program, e))
+        })?;
+
+    // Feed stdin from another thread so a signer that writes before reading everything
+    // cannot deadlock against us.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    writer.join().expect("stdin writer panicked")?;
+
+    if !output.status.success() {
+        return Err(Error::Attestation(format!("signing command {} {}", program, output.status)));
+    }
+    if output.stdout.is_empty() {
+        return Err(Error::Attestation(format!("signing command {} wrote no signature", program)));
+    }
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statement_names_subjects_and_digests() {
+        let statement = Statement::new(CheckResults::new(b"{}\n", 2, 1))
+            .with_subjects(vec![Subject::new("app", b"abc")])
+            .with_config(".layout-audit.yaml", b"budgets: {}\n");
+        let json: serde_json::Value = serde_json::from_str(&statement.to_json()).unwrap();
+
+        assert_eq!(json["_type"], STATEMENT_TYPE);
+        assert_eq!(json["subject"][0]["name"], "app");
+        assert_eq!(
+            json["subject"][0]["digest"]["sha256"],
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(json["predicate"]["config"]["source"], ".layout-audit.yaml");
+        assert_eq!(json["predicate"]["results"]["passed"], false);
+        assert_eq!(json["predicate"]["tool"]["name"], "layout-audit");
+    }
+
+    #[test]
+    fn pae_matches_the_dsse_spec() {
+        assert_eq!(
+            pae("http://example.com/HelloWorld", b"hello world"),
+            b"DSSEv1 29 http://example.com/HelloWorld 11 hello world"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sign_wraps_the_signer_output() {
+        let envelope = Statement::new(CheckResults::new(b"", 0, 0)).sign("cat").unwrap();
+        assert_eq!(envelope.payload_type, PAYLOAD_TYPE);
+        let payload = BASE64.decode(&envelope.payload).unwrap();
+        let sig = BASE64.decode(&envelope.signatures[0].sig).unwrap();
+        assert_eq!(sig, pae(PAYLOAD_TYPE, &payload));
+        assert!(Statement::new(CheckResults::new(b"", 0, 0)).sign("false").is_err());
+    }
+}
@@ -1,5 +1,9 @@
+use crate::analysis::{AllocatorModel, OptimizeStrategy};
+use crate::lock::DEFAULT_LOCKFILE;
+use crate::output::{CodeLanguage, Column, JsonStyle, Severity};
 use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "layout-audit")]
@@ -20,23 +24,50 @@ pub struct Cli {
 
 #[derive(Subcommand)]
 pub enum Commands {
-    /// Analyze and display struct layouts from a binary
+    /// Analyze and display struct layouts from one or more binaries
     Inspect {
-        /// Path to the binary file to analyze
-        #[arg(value_name = "BINARY")]
-        binary: PathBuf,
+        /// Binaries to analyze; a directory stands for the object files directly inside it.
+        /// With several binaries, each struct records which one it came from
+        #[arg(value_name = "BINARY", required = true, num_args = 1..)]
+        binaries: Vec<PathBuf>,
 
         /// Filter structs by name (substring match)
         #[arg(short, long)]
         filter: Option<String>,
 
-        /// Output format (table, json, sarif)
+        /// Filter structs by name with a regular expression (combines with --filter)
+        #[arg(long, value_name = "REGEX")]
+        filter_regex: Option<String>,
+
+        /// Only include structs with a member whose name or type matches this regular
+        /// expression (e.g. `Mutex`)
+        #[arg(long, value_name = "REGEX")]
+        member_filter: Option<String>,
+
+        /// Output format (table, json, sarif, csv, jsonl)
         #[arg(short, long, value_enum, default_value = "table")]
         output: OutputFormat,
 
-        /// Sort structs by field
-        #[arg(short, long, value_enum, default_value = "name")]
-        sort_by: SortField,
+        /// With `-o csv`, write one row per member instead of one per struct
+        #[arg(long)]
+        csv_members: bool,
+
+        /// Maximum SARIF results; lower-priority findings are replaced by one summary result
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        sarif_max_results: Option<usize>,
+
+        /// Preset sorting, thresholds, and detail for an audience; flags given explicitly
+        /// override it
+        #[arg(long, value_enum, value_name = "PROFILE")]
+        report_profile: Option<ReportProfile>,
+
+        /// Sort structs by field [default: name]
+        #[arg(short, long, value_enum)]
+        sort_by: Option<SortField>,
 
         /// Show only the top N structs (by sort order)
         #[arg(short = 'n', long)]
@@ -50,6 +81,11 @@ pub enum Commands {
         #[arg(long)]
         no_color: bool,
 
+        /// Write table output straight to the terminal instead of through $PAGER (default
+        /// `less`), which is used when stdout is a terminal
+        #[arg(long)]
+        no_pager: bool,
+
         /// Cache line size in bytes (must be > 0)
         #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
         cache_line: u32,
@@ -58,30 +94,382 @@ pub enum Commands {
         #[arg(long)]
         pretty: bool,
 
+        /// JSON field policy: `compact` leaves out absent, empty, and false fields; `explicit`
+        /// writes every field (absent ones as null) plus a `schema_version`, for typed consumers
+        #[arg(long, value_enum, value_name = "STYLE", default_value = "compact")]
+        json_style: JsonStyle,
+
         /// Warn about potential false sharing between atomic members on the same cache line
         #[arg(long)]
         warn_false_sharing: bool,
 
+        /// Warn about thread-shared globals (atomics, or types holding one) placed on the same
+        /// cache line
+        #[arg(long)]
+        global_false_sharing: bool,
+
+        /// YAML file of extra atomic and lock type patterns for false-sharing checks (`names`,
+        /// `regex`, and `replace`, like the config's `atomic_patterns` section)
+        #[arg(long, value_name = "PATH")]
+        atomic_patterns_file: Option<PathBuf>,
+
+        /// Exit with an error if any analyzed struct has a partial layout (members missing an
+        /// offset or size)
+        #[arg(long)]
+        fail_on_partial: bool,
+
+        /// Count partial layouts in the table's totals line (excluded by default)
+        #[arg(long)]
+        include_partial: bool,
+
+        /// Add an aggregate heatmap of how full each struct's final cache line is, listing
+        /// structs just over a line boundary
+        #[arg(long)]
+        cache_heatmap: bool,
+
+        /// Add padding totals per source file (structs, bytes, worst struct)
+        #[arg(long)]
+        by_file: bool,
+
+        /// Also roll the per-file totals up to the owners listed in this CODEOWNERS file
+        #[arg(long, value_name = "PATH", requires = "by_file")]
+        codeowners: Option<PathBuf>,
+
+        /// Add padding totals per namespace, crate, or Go package, taken from struct names
+        /// (unqualified C structs are grouped by their file's directory)
+        #[arg(long)]
+        by_namespace: bool,
+
+        /// Group --by-namespace totals by only the first N namespace segments (1 = crate or
+        /// top-level namespace)
+        #[arg(long, value_name = "N", requires = "by_namespace")]
+        namespace_depth: Option<usize>,
+
+        /// List each template instantiated more than once (`Vector<int>`, `Vector<Foo>`, ...) as
+        /// one row with its instantiation count, size range, and worst padding, instead of
+        /// listing every instantiation
+        #[arg(long)]
+        collapse_templates: bool,
+
+        /// Add binary-wide totals: structs, padding bytes, padding weighted by static
+        /// instances, a histogram of padding percentages, and the 10 worst offenders
+        #[arg(long)]
+        summary: bool,
+
+        /// Draw a byte grid under each struct in table output, one character per byte, with
+        /// cache line boundaries marked
+        #[arg(long)]
+        layout_map: bool,
+
+        /// List one row per struct with these columns, in this order, instead of a member table
+        /// per struct (default: the config's `columns`)
+        #[arg(long, value_enum, value_delimiter = ',', value_name = "COLUMNS")]
+        columns: Option<Vec<Column>>,
+
+        /// List C++ base class members inline at their offsets in the complete object instead
+        /// of as one `<base: X>` row, so padding and cache lines reflect the full layout
+        #[arg(long)]
+        flatten_bases: bool,
+
+        /// Report bit ranges and unused bits for every struct (always shown for structs made
+        /// only of bitfields)
+        #[arg(long)]
+        bits: bool,
+
+        /// Split table output into pages of N structs, each headed by its struct range
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        page_size: Option<usize>,
+
+        /// Report how much of its allocator size class each struct leaves unused
+        #[arg(long, value_enum, value_name = "MODEL")]
+        allocator: Option<AllocatorModel>,
+
+        /// Annotate members with how many functions' variable locations reference them
+        /// (slower: scans every subprogram)
+        #[arg(long)]
+        static_access: bool,
+
+        /// Count the functions, types, and globals that refer to each struct, and their sum
+        /// as its impact (slower: scans every DIE)
+        #[arg(long)]
+        usage: bool,
+
+        /// Count global and static instances of each struct (array elements included) from
+        /// the symbol table, and weight its padding by them (`weighted_waste_bytes`)
+        #[arg(long)]
+        static_instances: bool,
+
+        /// Skip (with a warning) any compilation unit that takes longer than this to scan
+        #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+        unit_timeout: Option<Duration>,
+
+        /// Give up once analysis has run this long and exit with status 3
+        #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+        budget_time: Option<Duration>,
+
+        /// When --budget-time runs out, report the structs read so far before exiting
+        #[arg(long, requires = "budget_time")]
+        emit_partial_results: bool,
+
+        /// Print how long each compilation unit took to scan, slowest first, to stderr
+        #[arg(long)]
+        stats: bool,
+
         /// Include Go runtime internal types (filtered by default)
         #[arg(long)]
         include_go_runtime: bool,
+
+        /// Only analyze structs reachable from exported function signatures (the ABI surface)
+        #[arg(long)]
+        exported_only: bool,
+
+        /// Re-apply the canonical order (name, size, fingerprint) before sorting, so ties
+        /// resolve identically in every output format and run
+        #[arg(long)]
+        sort_stable: bool,
+
+        /// Append the results to a SQLite database (requires the `sqlite` feature)
+        #[arg(long, value_name = "PATH")]
+        export_sqlite: Option<PathBuf>,
+
+        /// Write a bug-report bundle (.tar.zst) with the DWARF sections, config, command line,
+        /// and JSON output
+        #[arg(long, value_name = "PATH")]
+        repro_bundle: Option<PathBuf>,
+
+        /// Scrub names from the bundle's DWARF string tables (and leave out the config and
+        /// JSON output, which repeat them)
+        #[arg(long, requires = "repro_bundle")]
+        redact_strings: bool,
+
+        /// Config file whose `analyze` section limits which structs are analyzed
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Write a Makefile-style depfile listing the files read during analysis
+        #[arg(long, value_name = "PATH")]
+        emit_depfile: Option<PathBuf>,
+
+        /// Target name for the depfile rule (default: depfile path without extension)
+        #[arg(long, value_name = "TARGET", requires = "emit_depfile")]
+        depfile_target: Option<String>,
     },
 
-    /// Compare struct layouts between two binaries
+    /// Compare struct layouts between two binaries, or a snapshot and a binary
     Diff {
-        /// Path to the old (baseline) binary
+        /// Old and new binaries (OLD NEW), or only NEW when `--baseline` is given
+        #[arg(required = true, num_args = 1..=2, value_names = ["OLD", "NEW"])]
+        binaries: Vec<PathBuf>,
+
+        /// Snapshot written by `layout-audit snapshot` to use as the old side
+        #[arg(long, value_name = "JSON")]
+        baseline: Option<PathBuf>,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Filter structs by name with a regular expression (combines with --filter)
+        #[arg(long, value_name = "REGEX")]
+        filter_regex: Option<String>,
+
+        /// Only include structs with a member whose name or type matches this regular
+        /// expression (e.g. `Mutex`)
+        #[arg(long, value_name = "REGEX")]
+        member_filter: Option<String>,
+
+        /// Output format (table, json, sarif)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Maximum SARIF results; lower-priority findings are replaced by one summary result
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        sarif_max_results: Option<usize>,
+
+        /// Cache line size in bytes (must be > 0)
+        #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
+        cache_line: u32,
+
+        /// Exit with error code 1 if any regressions found (size or padding increased)
+        #[arg(long)]
+        fail_on_regression: bool,
+
+        /// Exit with error code 1 on regressions, or on `new_struct_defaults` violations of at
+        /// least this severity (error, warn, info); implies --fail-on-regression
+        #[arg(long, value_enum, value_name = "SEVERITY")]
+        fail_on: Option<Severity>,
+
+        /// Preset detail for an audience: `ci` lists regressions only and fails on them, `exec`
+        /// prints totals and the 10 structs that grew most
+        #[arg(long, value_enum, value_name = "PROFILE")]
+        report_profile: Option<ReportProfile>,
+
+        /// Also compare which thread-shared globals share a cache line; pairs new in NEW count
+        /// as regressions
+        #[arg(long, conflicts_with = "baseline")]
+        global_false_sharing: bool,
+
+        /// YAML file of extra atomic and lock type patterns for false-sharing checks (`names`,
+        /// `regex`, and `replace`, like the config's `atomic_patterns` section)
+        #[arg(long, value_name = "PATH")]
+        atomic_patterns_file: Option<PathBuf>,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+
+        /// Only analyze structs reachable from exported function signatures (the ABI surface)
+        #[arg(long)]
+        exported_only: bool,
+
+        /// Re-apply the canonical order (name, size, fingerprint) before sorting, so ties
+        /// resolve identically in every output format and run
+        #[arg(long)]
+        sort_stable: bool,
+
+        /// Config file whose `analyze` section limits which structs are analyzed
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Write a Makefile-style depfile listing the files read during analysis
+        #[arg(long, value_name = "PATH")]
+        emit_depfile: Option<PathBuf>,
+
+        /// Target name for the depfile rule (default: depfile path without extension)
+        #[arg(long, value_name = "TARGET", requires = "emit_depfile")]
+        depfile_target: Option<String>,
+    },
+
+    /// Save struct layouts as a JSON snapshot that `diff --baseline` can compare against
+    Snapshot {
+        /// Path to the binary file
+        binary: PathBuf,
+
+        /// Write the snapshot to this file instead of stdout
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+
+        /// Only record structs reachable from exported function signatures (the ABI surface)
+        #[arg(long)]
+        exported_only: bool,
+
+        /// Config file whose `analyze` section limits which structs are recorded
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Pretty-print the JSON
+        #[arg(long)]
+        pretty: bool,
+
+        /// Write a Makefile-style depfile listing the files read during analysis
+        #[arg(long, value_name = "PATH")]
+        emit_depfile: Option<PathBuf>,
+
+        /// Target name for the depfile rule (default: depfile path without extension)
+        #[arg(long, value_name = "TARGET", requires = "emit_depfile")]
+        depfile_target: Option<String>,
+    },
+
+    /// Compare debug and release builds of the same code, attributing layout differences to
+    /// build configuration instead of reporting them as regressions
+    CompareBuilds {
+        /// Path to the debug binary
+        #[arg(value_name = "DEBUG")]
+        debug: PathBuf,
+
+        /// Path to the release binary
+        #[arg(value_name = "RELEASE")]
+        release: PathBuf,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Output format (table, json, sarif)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Maximum SARIF results; lower-priority findings are replaced by one summary result
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        sarif_max_results: Option<usize>,
+
+        /// Cache line size in bytes (must be > 0)
+        #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
+        cache_line: u32,
+
+        /// Exit with error code 1 if a struct differs without a build-configuration explanation
+        /// (same members and types, different layout)
+        #[arg(long)]
+        fail_on_unexplained: bool,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+
+        /// Only analyze structs reachable from exported function signatures (the ABI surface)
+        #[arg(long)]
+        exported_only: bool,
+
+        /// Re-apply the canonical order (name, size, fingerprint) before sorting, so ties
+        /// resolve identically in every output format and run
+        #[arg(long)]
+        sort_stable: bool,
+
+        /// Config file whose `analyze` section limits which structs are analyzed
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Write a Makefile-style depfile listing the files read during analysis
+        #[arg(long, value_name = "PATH")]
+        emit_depfile: Option<PathBuf>,
+
+        /// Target name for the depfile rule (default: depfile path without extension)
+        #[arg(long, value_name = "TARGET", requires = "emit_depfile")]
+        depfile_target: Option<String>,
+    },
+
+    /// Compare builds of the same program for two target architectures, naming what makes
+    /// each struct's size, alignment, or padding differ (pointer width, `long double`,
+    /// alignment rules)
+    CompareTargets {
+        /// Path to the binary built for the first target
         #[arg(value_name = "OLD")]
         old: PathBuf,
 
-        /// Path to the new binary
+        /// Path to the binary built for the second target
         #[arg(value_name = "NEW")]
         new: PathBuf,
 
+        /// Config file whose `analyze` section limits which structs are compared and whose
+        /// `type_sizes` fill in opaque types
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
         /// Filter structs by name (substring match)
         #[arg(short, long)]
         filter: Option<String>,
 
-        /// Output format (table, json, sarif)
+        /// Output format (table, json)
         #[arg(short, long, value_enum, default_value = "table")]
         output: OutputFormat,
 
@@ -89,9 +477,13 @@ pub enum Commands {
         #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
         cache_line: u32,
 
-        /// Exit with error code 1 if any regressions found (size or padding increased)
+        /// Pretty-print JSON output
         #[arg(long)]
-        fail_on_regression: bool,
+        pretty: bool,
+
+        /// Disable colored output
+        #[arg(long)]
+        no_color: bool,
 
         /// Include Go runtime internal types (filtered by default)
         #[arg(long)]
@@ -100,18 +492,83 @@ pub enum Commands {
 
     /// Check struct layouts against budget constraints
     Check {
-        /// Path to the binary file to analyze
-        #[arg(value_name = "BINARY")]
-        binary: PathBuf,
+        /// Binaries to check; a directory stands for the object files directly inside it
+        #[arg(
+            value_name = "BINARY",
+            required_unless_present_any = ["lint_config", "validate_config_only"],
+            num_args = 1..
+        )]
+        binaries: Vec<PathBuf>,
 
-        /// Path to config file (.layout-audit.yaml)
+        /// Path to config file (.layout-audit.yaml), or `-` to read it from stdin; its
+        /// `analyze` section limits which structs are checked. Unless `-`, inline YAML in
+        /// LAYOUT_AUDIT_BUDGETS takes its place
         #[arg(short, long, default_value = ".layout-audit.yaml")]
         config: PathBuf,
 
+        /// Access-frequency profile (YAML/JSON map of struct name to score) used by budgets
+        /// with `applies_to: hot`
+        #[arg(long, value_name = "PATH")]
+        profile: Option<PathBuf>,
+
+        /// Older build of the binary; structs absent from it are held to the config's
+        /// `new_struct_defaults`
+        #[arg(long, value_name = "BINARY")]
+        previous: Option<PathBuf>,
+
+        /// YAML file of extra atomic and lock type patterns for false-sharing checks (`names`,
+        /// `regex`, and `replace`, like the config's `atomic_patterns` section)
+        #[arg(long, value_name = "PATH")]
+        atomic_patterns_file: Option<PathBuf>,
+
+        /// List the `layout-audit: allow(...)` markers found next to analyzed structs instead of
+        /// checking budgets
+        #[arg(long)]
+        list_suppressions: bool,
+
+        /// Lint the config's budget names instead of checking binaries: glob patterns that
+        /// overlap, budgets every name of which an earlier pattern already takes, and exact
+        /// names a glob also matches
+        #[arg(long, conflicts_with_all = ["list_suppressions", "accept"])]
+        lint_config: bool,
+
+        /// Validate the config instead of checking binaries: YAML structure, unknown keys
+        /// (which parsing otherwise ignores), value ranges, and pattern syntax, each reported
+        /// at its line and column
+        #[arg(long, conflicts_with_all = ["list_suppressions", "accept", "lint_config"])]
+        validate_config_only: bool,
+
+        /// Lockfile of accepted struct sizes; when it exists, structs that grew past their
+        /// recorded size fail the check
+        #[arg(long, value_name = "PATH", default_value = DEFAULT_LOCKFILE)]
+        lockfile: PathBuf,
+
+        /// Write the current struct sizes to the lockfile instead of comparing against it
+        #[arg(long)]
+        accept: bool,
+
+        /// How budget names are matched to struct names: `exact`, or `canonical` to ignore
+        /// module and package qualifiers (`my_app::Order` also matches `main.Order` and `Order`)
+        #[arg(long, value_enum, value_name = "MODE", default_value = "exact")]
+        budget_match: BudgetMatch,
+
+        /// Lowest budget severity that fails the check (error, warn, info); violations below
+        /// it are reported without failing
+        #[arg(long, value_enum, value_name = "SEVERITY", default_value = "error")]
+        fail_on: Severity,
+
         /// Output format (table, json, sarif)
         #[arg(short, long, value_enum, default_value = "table")]
         output: OutputFormat,
 
+        /// Maximum SARIF results; lower-priority findings are replaced by one summary result
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        sarif_max_results: Option<usize>,
+
         /// Cache line size in bytes (must be > 0)
         #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
         cache_line: u32,
@@ -119,6 +576,43 @@ pub enum Commands {
         /// Include Go runtime internal types (filtered by default)
         #[arg(long)]
         include_go_runtime: bool,
+
+        /// Only analyze structs reachable from exported function signatures (the ABI surface)
+        #[arg(long)]
+        exported_only: bool,
+
+        /// Re-apply the canonical order (name, size, fingerprint) before sorting, so ties
+        /// resolve identically in every output format and run
+        #[arg(long)]
+        sort_stable: bool,
+
+        /// Give up once analysis has run this long and exit with status 3, without updating
+        /// the lockfile or writing an attestation
+        #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+        budget_time: Option<Duration>,
+
+        /// When --budget-time runs out, report violations among the structs read so far
+        /// before exiting
+        #[arg(long, requires = "budget_time")]
+        emit_partial_results: bool,
+
+        /// Write a Makefile-style depfile listing the files read during analysis
+        #[arg(long, value_name = "PATH")]
+        emit_depfile: Option<PathBuf>,
+
+        /// Target name for the depfile rule (default: depfile path without extension)
+        #[arg(long, value_name = "TARGET", requires = "emit_depfile")]
+        depfile_target: Option<String>,
+
+        /// Write an in-toto attestation of the check (digests of the binaries, config, and
+        /// JSON results, plus the tool version) for build provenance
+        #[arg(long, value_name = "PATH")]
+        attestation: Option<PathBuf>,
+
+        /// Command that signs the attestation, making it a DSSE envelope: it reads the bytes
+        /// to sign on stdin and writes the signature to stdout (split on whitespace, no shell)
+        #[arg(long, value_name = "COMMAND", requires = "attestation")]
+        attestation_sign: Option<String>,
     },
 
     /// Suggest optimal field ordering to minimize padding
@@ -131,10 +625,27 @@ pub enum Commands {
         #[arg(short, long)]
         filter: Option<String>,
 
+        /// Filter structs by name with a regular expression (combines with --filter)
+        #[arg(long, value_name = "REGEX")]
+        filter_regex: Option<String>,
+
+        /// Only include structs with a member whose name or type matches this regular
+        /// expression (e.g. `Mutex`)
+        #[arg(long, value_name = "REGEX")]
+        member_filter: Option<String>,
+
         /// Output format (table, json, sarif)
         #[arg(short, long, value_enum, default_value = "table")]
         output: OutputFormat,
 
+        /// Maximum SARIF results; lower-priority findings are replaced by one summary result
+        #[arg(
+            long,
+            value_name = "N",
+            value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+        )]
+        sarif_max_results: Option<usize>,
+
         /// Show only structs with at least N bytes of potential savings
         #[arg(long)]
         min_savings: Option<u64>,
@@ -155,6 +666,39 @@ pub enum Commands {
         #[arg(long)]
         sort_by_savings: bool,
 
+        /// Reordering strategy; suggestions note when the strategies reach different sizes
+        #[arg(long, value_enum, default_value = "greedy")]
+        strategy: OptimizeStrategy,
+
+        /// Estimate member popularity from variable locations in every subprogram and use it as
+        /// the access pattern for structs without one in the config
+        #[arg(long)]
+        static_access: bool,
+
+        /// Count the functions, types, and globals that refer to each struct, and their sum
+        /// as its impact (slower: scans every DIE)
+        #[arg(long)]
+        usage: bool,
+
+        /// Also search orders that keep related members adjacent (the config's `field_groups`,
+        /// or members sharing a name prefix) and list the size/grouping trade-offs found
+        #[arg(long)]
+        locality: bool,
+
+        /// Largest size a --locality order may have (default: the current size)
+        #[arg(long, value_name = "BYTES", requires = "locality")]
+        size_cap: Option<u64>,
+
+        /// Config file with `access_patterns` used to simulate cache line touches; its `analyze`
+        /// section limits which structs are analyzed
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Print the suggested layouts as struct definitions in this language instead of a
+        /// report, with padding spelled out as fields
+        #[arg(long, value_name = "LANG", value_enum, conflicts_with_all = ["output", "pretty"])]
+        emit_code: Option<CodeLanguage>,
+
         /// Disable colored output
         #[arg(long)]
         no_color: bool,
@@ -162,24 +706,619 @@ pub enum Commands {
         /// Include Go runtime internal types (filtered by default)
         #[arg(long)]
         include_go_runtime: bool,
+
+        /// Only analyze structs reachable from exported function signatures (the ABI surface)
+        #[arg(long)]
+        exported_only: bool,
+
+        /// Re-apply the canonical order (name, size, fingerprint) before sorting, so ties
+        /// resolve identically in every output format and run
+        #[arg(long)]
+        sort_stable: bool,
+
+        /// Write a Makefile-style depfile listing the files read during analysis
+        #[arg(long, value_name = "PATH")]
+        emit_depfile: Option<PathBuf>,
+
+        /// Target name for the depfile rule (default: depfile path without extension)
+        #[arg(long, value_name = "TARGET", requires = "emit_depfile")]
+        depfile_target: Option<String>,
     },
-}
 
-#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
-pub enum OutputFormat {
-    Table,
-    Json,
-    Sarif,
-}
+    /// Attribute sampled memory accesses (from `perf record -d` / `perf mem record`, or a list
+    /// of addresses) to the struct members of global and static variables
+    Annotate {
+        /// Path to the profiled binary
+        #[arg(value_name = "BINARY")]
+        binary: PathBuf,
 
-#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
-pub enum SortField {
-    /// Sort by struct name (alphabetical)
-    Name,
-    /// Sort by total size (largest first)
-    Size,
-    /// Sort by padding bytes (most padding first)
-    Padding,
-    /// Sort by padding percentage (worst efficiency first)
-    PaddingPct,
+        /// perf data file, or a text file with one `ADDRESS[,COUNT]` per line
+        #[arg(value_name = "SAMPLES")]
+        samples: PathBuf,
+
+        /// Address the binary was loaded at minus its link-time address (hex with 0x, or
+        /// decimal). Default: from the perf mappings, or 0 for text input
+        #[arg(long, value_name = "BIAS", value_parser = parse_address)]
+        load_bias: Option<u64>,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Output format (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+
+        /// Disable colored output
+        #[arg(long)]
+        no_color: bool,
+
+        /// Write the sampled members of each struct, hottest first, as a `hot_fields` config
+        /// section that `suggest --config` packs first
+        #[arg(long, value_name = "PATH")]
+        emit_config: Option<PathBuf>,
+    },
+
+    /// Name the member of a struct holding a byte offset, following nested structs and array
+    /// elements, or the padding hole it falls in
+    Locate {
+        /// Path to the binary file to analyze
+        #[arg(value_name = "BINARY")]
+        binary: PathBuf,
+
+        /// Struct name, qualified or unqualified (`Foo`, `ns::Foo`)
+        #[arg(value_name = "TYPE")]
+        type_name: String,
+
+        /// Byte offset within the struct (hex with 0x, or decimal)
+        #[arg(value_name = "OFFSET", value_parser = parse_address)]
+        offset: u64,
+
+        /// Output format (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+
+        /// Disable colored output
+        #[arg(long)]
+        no_color: bool,
+    },
+
+    /// Cross-check DWARF layouts against rustc's `-Zprint-type-sizes` output, failing on any
+    /// disagreement in size, alignment, or field offsets
+    Verify {
+        /// Path to the binary file to analyze
+        #[arg(value_name = "BINARY")]
+        binary: PathBuf,
+
+        /// Output of `cargo rustc -- -Zprint-type-sizes` for the same build (`-` for stdin)
+        #[arg(long, value_name = "PATH")]
+        rustc_sizes: PathBuf,
+
+        /// Filter types by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Output format (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+
+        /// Disable colored output
+        #[arg(long)]
+        no_color: bool,
+    },
+
+    /// Cross-check a `.proto` or FlatBuffers schema against the structs generated from it:
+    /// fields missing from either side
+    SchemaCheck {
+        /// Schema file (`.proto` or `.fbs`)
+        #[arg(value_name = "SCHEMA")]
+        schema: PathBuf,
+
+        /// Path to the binary file to analyze
+        #[arg(value_name = "BINARY")]
+        binary: PathBuf,
+
+        /// Config file; its `schema_structs` map names the struct generated for a message
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Filter messages by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Output format (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+
+        /// Disable colored output
+        #[arg(long)]
+        no_color: bool,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+    },
+
+    /// Flag structs whose layout differs between analyses of one binary, or between builds of
+    /// identical source: nondeterministic code generation, or configuration the build
+    /// environment decided
+    VerifyDeterminism {
+        /// One binary to analyze repeatedly, or several builds of the same source to compare
+        /// with the first
+        #[arg(value_name = "BINARY", required = true, num_args = 1..)]
+        binaries: Vec<PathBuf>,
+
+        /// How many times to analyze a single binary
+        #[arg(long, default_value = "2", value_parser = clap::value_parser!(u32).range(2..))]
+        runs: u32,
+
+        /// Config file whose `analyze` section limits which structs are compared
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Output format (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+
+        /// Disable colored output
+        #[arg(long)]
+        no_color: bool,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+    },
+
+    /// Estimate fleet-wide memory lost to padding from live instance counts, what the top
+    /// suggestions would recover, and how the waste is trending across snapshots
+    Forecast {
+        /// Path to the binary file to analyze
+        #[arg(value_name = "BINARY")]
+        binary: PathBuf,
+
+        /// YAML or JSON map of struct name to live instance count across the fleet
+        #[arg(long, value_name = "PATH")]
+        instances: PathBuf,
+
+        /// Snapshots of earlier builds, oldest first, for the growth trend (repeatable)
+        #[arg(long, value_name = "SNAPSHOT")]
+        history: Vec<PathBuf>,
+
+        /// Number of suggestions, most recoverable bytes first, to project the impact of
+        #[arg(long, value_name = "N", default_value = "10")]
+        top: usize,
+
+        /// Snapshots past the current build to extend the trend over
+        #[arg(long, value_name = "SNAPSHOTS", default_value = "4")]
+        horizon: usize,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Config file: `budgets` flag structs already over budget, and `analyze` and
+        /// `type_sizes` apply as for suggest
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Maximum alignment to assume for types (typically 8 on 64-bit)
+        #[arg(long, default_value = "8", value_parser = clap::value_parser!(u64).range(1..))]
+        max_align: u64,
+
+        /// Cache line size in bytes (must be > 0)
+        #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
+        cache_line: u32,
+
+        /// Output format (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+
+        /// Disable colored output
+        #[arg(long)]
+        no_color: bool,
+    },
+
+    /// Recommend cache line alignment and padding for structs shared across threads: those
+    /// the config's `thread_owners` gives several owners, or that no owner claims and that hold
+    /// atomics or locks
+    Placement {
+        /// Path to the binary file to analyze
+        #[arg(value_name = "BINARY")]
+        binary: PathBuf,
+
+        /// Config file with `thread_owners` (owner name to struct globs); `atomic_patterns`,
+        /// `analyze`, and `type_sizes` apply as for inspect
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Language the wrapper suggestions are written in
+        #[arg(long, value_enum, default_value = "rust")]
+        lang: CodeLanguage,
+
+        /// Bytes to align and pad shared structs to (must be > 0); 128 matches crossbeam's
+        /// CachePadded on x86_64 and aarch64
+        #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
+        cache_line: u32,
+
+        /// Output format (table, json)
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+
+        /// Disable colored output
+        #[arg(long)]
+        no_color: bool,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+    },
+
+    /// Generate a test file whose assertions stop compiling when a struct's size or member
+    /// offsets change
+    EmitAssertions {
+        /// Path to the binary file to analyze
+        #[arg(value_name = "BINARY")]
+        binary: PathBuf,
+
+        /// Language of the generated file
+        #[arg(long, value_enum, default_value = "go")]
+        lang: AssertionLang,
+
+        /// Go package whose structs are asserted on, as its import path appears in type names
+        /// (`main`, `github.com/acme/shop`)
+        #[arg(long, value_name = "IMPORT_PATH", default_value = "main")]
+        go_package: String,
+
+        /// Package clause of the generated file (default: last segment of --go-package)
+        #[arg(long, value_name = "NAME")]
+        package_name: Option<String>,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Config file; its `analyze` section limits which structs are asserted on
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Write the assertions to this file instead of stdout (e.g. `layout_test.go`)
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+
+        /// Write a Makefile-style depfile listing the files read during analysis
+        #[arg(long, value_name = "PATH")]
+        emit_depfile: Option<PathBuf>,
+
+        /// Target name for the depfile rule (default: depfile path without extension)
+        #[arg(long, value_name = "TARGET", requires = "emit_depfile")]
+        depfile_target: Option<String>,
+    },
+
+    /// Generate a C header reconstructing struct definitions from the debug info, with explicit
+    /// padding fields and `_Static_assert`s on sizes and member offsets
+    EmitHeader {
+        /// Path to the binary file to analyze
+        #[arg(value_name = "BINARY")]
+        binary: PathBuf,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Config file; its `analyze` section limits which structs are defined
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Write the header to this file instead of stdout (e.g. `layout.h`)
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+
+        /// Include guard macro (default: from the --out file name, e.g. `LAYOUT_H`)
+        #[arg(long, value_name = "MACRO")]
+        guard: Option<String>,
+
+        /// Write a Makefile-style depfile listing the files read during analysis
+        #[arg(long, value_name = "PATH")]
+        emit_depfile: Option<PathBuf>,
+
+        /// Target name for the depfile rule (default: depfile path without extension)
+        #[arg(long, value_name = "TARGET", requires = "emit_depfile")]
+        depfile_target: Option<String>,
+    },
+
+    /// Re-run inspect or check whenever a binary is rebuilt, printing only what changed since
+    /// the previous run
+    Watch {
+        /// Binary to watch, or a cargo target directory (with --bin)
+        #[arg(value_name = "PATH")]
+        path: PathBuf,
+
+        /// Executable to pick from a target directory: whichever of `<dir>/debug/<NAME>`,
+        /// `<dir>/release/<NAME>`, and `<dir>/<NAME>` was built last
+        #[arg(long, value_name = "NAME")]
+        bin: Option<String>,
+
+        /// What to re-run: `inspect` reports layout changes, `check` reports new and resolved
+        /// budget violations
+        #[arg(long, value_enum, default_value = "inspect")]
+        run: WatchAction,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Config file; its `analyze` section limits which structs are analyzed, and `--run check`
+        /// reads budgets from it (default: .layout-audit.yaml)
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Milliseconds without further writes before re-running, so a linker's partial writes
+        /// are not analyzed
+        #[arg(long, value_name = "MS", default_value = "300")]
+        debounce: u64,
+
+        /// Cache line size in bytes (must be > 0)
+        #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
+        cache_line: u32,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+    },
+
+    /// Explore struct layouts interactively: fuzzy search, byte maps, suggested orders, and a
+    /// false-sharing overlay (requires the `tui` feature)
+    Tui {
+        /// Path to the binary file to analyze
+        #[arg(value_name = "BINARY")]
+        binary: PathBuf,
+
+        /// Config file; `analyze`, `type_sizes`, and `atomic_patterns` apply as for inspect
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Cache line size in bytes (must be > 0)
+        #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
+        cache_line: u32,
+
+        /// Maximum alignment the suggest view assumes for types (typically 8 on 64-bit)
+        #[arg(long, default_value = "8", value_parser = clap::value_parser!(u64).range(1..))]
+        max_align: u64,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+    },
+
+    /// Report binary-wide padding totals: the numbers inspect's --summary adds, alone
+    Summary {
+        /// Binaries to analyze; a directory stands for the object files directly inside it
+        #[arg(value_name = "BINARY", required = true, num_args = 1..)]
+        binaries: Vec<PathBuf>,
+
+        /// Config file; `analyze` and `type_sizes` apply as for inspect
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// Cache line size in bytes (must be > 0)
+        #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
+        cache_line: u32,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+
+        /// Disable colored output
+        #[arg(long)]
+        no_color: bool,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+    },
+
+    /// Graph which structs embed which by value, to see where a badly packed type is
+    /// multiplied
+    Graph {
+        /// Binaries to analyze; a directory stands for the object files directly inside it
+        #[arg(value_name = "BINARY", required = true, num_args = 1..)]
+        binaries: Vec<PathBuf>,
+
+        /// Config file; `analyze` and `type_sizes` apply as for inspect
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Keep structs whose name contains this, with everything they embed or are embedded
+        /// in
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Graph format
+        #[arg(long, value_enum, default_value = "dot")]
+        format: GraphFormat,
+
+        /// Pretty-print JSON output
+        #[arg(long)]
+        pretty: bool,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+    },
+
+    /// Write structs, members, padding holes, and metrics to a database for SQL queries across
+    /// runs
+    Export {
+        /// Database format to write
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+
+        /// Database to create, or to append this run to
+        #[arg(value_name = "OUT")]
+        out: PathBuf,
+
+        /// Binaries to analyze; a directory stands for the object files directly inside it
+        #[arg(value_name = "BINARY", required = true, num_args = 1..)]
+        binaries: Vec<PathBuf>,
+
+        /// Config file; `analyze`, `type_sizes`, and `atomic_patterns` apply as for inspect
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+
+        /// Filter structs by name (substring match)
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Cache line size in bytes (must be > 0)
+        #[arg(long, default_value = "64", value_parser = clap::value_parser!(u32).range(1..))]
+        cache_line: u32,
+
+        /// Include Go runtime internal types (filtered by default)
+        #[arg(long)]
+        include_go_runtime: bool,
+    },
+}
+
+/// Preset report settings for one audience, selected with `--report-profile`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ReportProfile {
+    /// Full detail: every struct, with layout maps and false-sharing warnings
+    Dev,
+    /// Violations and regressions: only structs with padding, worst first, without color, and
+    /// failing on partial layouts
+    Ci,
+    /// The 10 worst offenders as one summary row each, plus totals over every struct
+    Exec,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum AssertionLang {
+    /// A `_test.go` file of `unsafe.Sizeof` / `unsafe.Offsetof` constant assertions
+    Go,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// A SQLite database (needs the `sqlite` feature)
+    Sqlite,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT, for `dot -Tsvg`
+    Dot,
+    /// Nodes and edges as JSON
+    Json,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum WatchAction {
+    Inspect,
+    Check,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Sarif,
+    /// Comma-separated values, one row per struct (inspect only)
+    Csv,
+    /// One JSON object per line, each struct printed as soon as it is analyzed (inspect only)
+    Jsonl,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum BudgetMatch {
+    /// Budget names must equal the struct name as it appears in the debug info
+    Exact,
+    /// Compare unqualified names, so budgets match across C, C++, Rust, and Go spellings
+    Canonical,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum SortField {
+    /// Sort by struct name (alphabetical)
+    Name,
+    /// Sort by total size (largest first)
+    Size,
+    /// Sort by padding bytes (most padding first)
+    Padding,
+    /// Sort by padding percentage (worst efficiency first)
+    PaddingPct,
+    /// Sort by bytes used in the final cache line (multi-line structs nearest a line boundary
+    /// first)
+    FinalLine,
+    /// Sort by padding bytes times usage impact (padding felt in the most places first);
+    /// implies --usage
+    Impact,
+    /// Sort by padding bytes times static instances (padding stored most often first);
+    /// implies --static-instances
+    WeightedWaste,
+}
+
+/// Parse a positive number of seconds, e.g. `30` or `2.5`.
+fn parse_seconds(s: &str) -> Result<Duration, String> {
+    let secs: f64 = s.parse().map_err(|_| format!("invalid number of seconds: {}", s))?;
+    if !secs.is_finite() || secs <= 0.0 {
+        return Err("must be greater than 0".to_string());
+    }
+    Duration::try_from_secs_f64(secs).map_err(|e| e.to_string())
+}
+
+/// Parse an address: hexadecimal with a `0x` prefix, or decimal.
+fn parse_address(s: &str) -> Result<u64, String> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => s.parse(),
+    }
+    .map_err(|_| format!("invalid address: {}", s))
 }
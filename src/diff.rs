@@ -1,4 +1,6 @@
-use crate::types::{SourceLocation, StructLayout};
+use crate::analysis::implied_alignment;
+use crate::names::embedded_type;
+use crate::types::{MemberLayout, SourceLocation, StructLayout};
 use serde::Serialize;
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -51,17 +53,34 @@ pub struct MemberChange {
     pub kind: MemberChangeKind,
     pub name: String,
     pub details: String,
+    /// Declaration of the member in the new build, or in the old one when it was removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_location: Option<SourceLocation>,
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
 pub enum MemberChangeKind {
     Added,
     Removed,
+    /// Removed under one name and added under another at the same offset, with the same size
+    /// and type. `name` is the new name; `details` reads `old -> new`.
+    Renamed,
     OffsetChanged,
     SizeChanged,
     TypeChanged,
 }
 
+impl StructSummary {
+    fn of(s: &StructLayout) -> Self {
+        Self {
+            name: s.name.clone(),
+            size: s.size,
+            padding_bytes: s.metrics.padding_bytes,
+            source_location: s.source_location.clone(),
+        }
+    }
+}
+
 impl DiffResult {
     pub fn has_changes(&self) -> bool {
         !self.added.is_empty() || !self.removed.is_empty() || !self.changed.is_empty()
@@ -73,6 +92,26 @@ impl DiffResult {
 }
 
 pub fn diff_layouts(old: &[StructLayout], new: &[StructLayout]) -> DiffResult {
+    let Matched { added, removed, mut changed, unchanged_count } =
+        match_layouts(old, new, diff_struct);
+    changed.sort_by(change_order);
+    DiffResult { added, removed, changed, unchanged_count }
+}
+
+/// Structs matched between two sets of layouts, with `compare`'s result for each matched pair
+/// that differs.
+struct Matched<T> {
+    added: Vec<StructSummary>,
+    removed: Vec<StructSummary>,
+    changed: Vec<T>,
+    unchanged_count: usize,
+}
+
+fn match_layouts<T>(
+    old: &[StructLayout],
+    new: &[StructLayout],
+    compare: impl Fn(&StructLayout, &StructLayout) -> Option<T>,
+) -> Matched<T> {
     let mut added = Vec::new();
     let mut removed = Vec::new();
     let mut changed = Vec::new();
@@ -101,68 +140,329 @@ pub fn diff_layouts(old: &[StructLayout], new: &[StructLayout]) -> DiffResult {
         let new_group = new_by_name.get(&name).map(Vec::as_slice).unwrap_or(&[]);
 
         if old_group.is_empty() {
-            for s in new_group {
-                added.push(StructSummary {
-                    name: name.clone(),
-                    size: s.size,
-                    padding_bytes: s.metrics.padding_bytes,
-                    source_location: s.source_location.clone(),
-                });
-            }
+            added.extend(new_group.iter().map(|s| StructSummary::of(s)));
             continue;
         }
 
         if new_group.is_empty() {
-            for s in old_group {
-                removed.push(StructSummary {
-                    name: name.clone(),
-                    size: s.size,
-                    padding_bytes: s.metrics.padding_bytes,
-                    source_location: s.source_location.clone(),
-                });
-            }
+            removed.extend(old_group.iter().map(|s| StructSummary::of(s)));
             continue;
         }
 
         let (pairs, old_unmatched, new_unmatched) = match_structs(old_group, new_group);
 
         for (old_s, new_s) in pairs {
-            if let Some(change) = diff_struct(old_s, new_s) {
+            if let Some(change) = compare(old_s, new_s) {
                 changed.push(change);
             } else {
                 unchanged_count += 1;
             }
         }
 
-        for s in old_unmatched {
-            removed.push(StructSummary {
-                name: name.clone(),
-                size: s.size,
-                padding_bytes: s.metrics.padding_bytes,
-                source_location: s.source_location.clone(),
-            });
+        removed.extend(old_unmatched.into_iter().map(StructSummary::of));
+        added.extend(new_unmatched.into_iter().map(StructSummary::of));
+    }
+
+    added.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.size.cmp(&b.size)));
+    removed.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.size.cmp(&b.size)));
+
+    Matched { added, removed, changed, unchanged_count }
+}
+
+fn change_order(a: &StructChange, b: &StructChange) -> std::cmp::Ordering {
+    a.name
+        .cmp(&b.name)
+        .then_with(|| a.old_size.cmp(&b.old_size))
+        .then_with(|| a.new_size.cmp(&b.new_size))
+}
+
+/// Why a struct differs between a debug and a release build of the same source.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildDifferenceKind {
+    /// Members exist in only one build (e.g. fields under `cfg(debug_assertions)` or `#ifndef
+    /// NDEBUG`).
+    ConditionalMembers,
+    /// Same members, but some member types differ in size or name (conditional fields nested
+    /// in a member's type).
+    MemberTypes,
+    /// Same members with the same types, laid out differently. Build configuration doesn't
+    /// explain this, so it is reported apart from the expected differences.
+    Unexplained,
+}
+
+impl BuildDifferenceKind {
+    /// Whether the difference is expected from building the same source twice with different
+    /// settings, as opposed to something worth investigating.
+    pub fn is_build_config(self) -> bool {
+        !matches!(self, BuildDifferenceKind::Unexplained)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildDifference {
+    pub kind: BuildDifferenceKind,
+    #[serde(flatten)]
+    pub change: StructChange,
+}
+
+/// Structs matched between a debug and a release build. In `differences`, member changes read
+/// debug -> release: `removed` members are debug-only, `added` members are release-only.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildComparison {
+    pub debug_only: Vec<StructSummary>,
+    pub release_only: Vec<StructSummary>,
+    pub differences: Vec<BuildDifference>,
+    pub unchanged_count: usize,
+}
+
+impl BuildComparison {
+    pub fn has_differences(&self) -> bool {
+        !self.debug_only.is_empty() || !self.release_only.is_empty() || !self.differences.is_empty()
+    }
+
+    pub fn has_unexplained(&self) -> bool {
+        self.differences.iter().any(|d| !d.kind.is_build_config())
+    }
+}
+
+/// Match structs between a debug and a release build and attribute each layout difference
+/// to build configuration where the member lists explain it.
+pub fn compare_builds(debug: &[StructLayout], release: &[StructLayout]) -> BuildComparison {
+    let diff = diff_layouts(debug, release);
+
+    let differences = diff
+        .changed
+        .into_iter()
+        .map(|change| BuildDifference { kind: classify_build_difference(&change), change })
+        .collect();
+
+    BuildComparison {
+        debug_only: diff.removed,
+        release_only: diff.added,
+        differences,
+        unchanged_count: diff.unchanged_count,
+    }
+}
+
+/// One run or build compared with the first of a `verify-determinism` check, which should have
+/// given identical layouts. Member changes read baseline -> this run.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeterminismRun {
+    /// The binary this run analyzed, with the run number when one binary was analyzed
+    /// repeatedly.
+    pub label: String,
+    pub only_in_baseline: Vec<StructSummary>,
+    pub only_in_run: Vec<StructSummary>,
+    /// `conditional_members` and `member_types` point at configuration the build environment
+    /// decided (feature detection, defines); `unexplained` at nondeterministic code generation.
+    pub differences: Vec<BuildDifference>,
+    pub unchanged_count: usize,
+}
+
+impl DeterminismRun {
+    pub fn new(label: String, baseline: &[StructLayout], run: &[StructLayout]) -> Self {
+        let comparison = compare_builds(baseline, run);
+        Self {
+            label,
+            only_in_baseline: comparison.debug_only,
+            only_in_run: comparison.release_only,
+            differences: comparison.differences,
+            unchanged_count: comparison.unchanged_count,
         }
+    }
 
-        for s in new_unmatched {
-            added.push(StructSummary {
-                name: name.clone(),
-                size: s.size,
-                padding_bytes: s.metrics.padding_bytes,
-                source_location: s.source_location.clone(),
-            });
+    /// Structs that differ from the baseline, or exist on only one side.
+    pub fn differing(&self) -> usize {
+        self.only_in_baseline.len() + self.only_in_run.len() + self.differences.len()
+    }
+}
+
+/// Runs or builds that should agree, each compared with the first.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeterminismReport {
+    pub baseline: String,
+    pub runs: Vec<DeterminismRun>,
+}
+
+impl DeterminismReport {
+    pub fn is_deterministic(&self) -> bool {
+        self.runs.iter().all(|r| r.differing() == 0)
+    }
+}
+
+/// What makes a struct's layout differ between builds for two target architectures.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetDifferenceCause {
+    /// A pointer, or an integer as wide as one (`long`, `size_t`, `usize`), changed size.
+    PointerWidth,
+    /// `long double` changed size: x87's 80 bits padded to 12 or 16 bytes, IEEE quad, or a
+    /// plain double.
+    LongDouble,
+    /// Another member type changed size, such as a nested struct that differs itself.
+    MemberTypes,
+    /// Members kept their sizes but moved, or the struct's size or alignment changed around
+    /// them: the targets' alignment rules differ (i386 aligns `double` and `long long` to 4).
+    Alignment,
+    /// Members exist for only one target (architecture `#ifdef`s, `cfg(target_arch)`).
+    ConditionalMembers,
+}
+
+impl TargetDifferenceCause {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TargetDifferenceCause::PointerWidth => "pointer width",
+            TargetDifferenceCause::LongDouble => "long double",
+            TargetDifferenceCause::MemberTypes => "member types",
+            TargetDifferenceCause::Alignment => "alignment rules",
+            TargetDifferenceCause::ConditionalMembers => "conditional members",
         }
     }
+}
 
-    added.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.size.cmp(&b.size)));
-    removed.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.size.cmp(&b.size)));
-    changed.sort_by(|a, b| {
-        a.name
-            .cmp(&b.name)
-            .then_with(|| a.old_size.cmp(&b.old_size))
-            .then_with(|| a.new_size.cmp(&b.new_size))
-    });
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetDifference {
+    pub causes: Vec<TargetDifferenceCause>,
+    /// Alignment each layout honours, when every member's alignment is known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_alignment: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_alignment: Option<u64>,
+    #[serde(flatten)]
+    pub change: StructChange,
+}
 
-    DiffResult { added, removed, changed, unchanged_count }
+/// Structs matched between builds of one program for two targets. Member changes read
+/// old -> new target.
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetComparison {
+    pub old_target: String,
+    pub new_target: String,
+    pub only_in_old: Vec<StructSummary>,
+    pub only_in_new: Vec<StructSummary>,
+    pub differences: Vec<TargetDifference>,
+    pub unchanged_count: usize,
+}
+
+/// Match structs between builds for two targets and name what makes each differing layout
+/// differ.
+pub fn compare_targets(
+    old_target: String,
+    old: &[StructLayout],
+    new_target: String,
+    new: &[StructLayout],
+) -> TargetComparison {
+    let Matched { added, removed, mut changed, unchanged_count } =
+        match_layouts(old, new, |old, new| {
+            let change = diff_struct(old, new)?;
+            Some(TargetDifference {
+                causes: target_causes(old, new),
+                old_alignment: honoured_alignment(old),
+                new_alignment: honoured_alignment(new),
+                change,
+            })
+        });
+    changed.sort_by(|a, b| change_order(&a.change, &b.change));
+
+    TargetComparison {
+        old_target,
+        new_target,
+        only_in_old: removed,
+        only_in_new: added,
+        differences: changed,
+        unchanged_count,
+    }
+}
+
+/// Integer types as wide as a pointer on the common data models (ILP32, LP64), by the names
+/// C, C++, Rust, and Go give them.
+const POINTER_SIZED: [&str; 13] = [
+    "long",
+    "long int",
+    "unsigned long",
+    "long unsigned int",
+    "size_t",
+    "ssize_t",
+    "intptr_t",
+    "uintptr_t",
+    "ptrdiff_t",
+    "usize",
+    "isize",
+    "uintptr",
+    "int",
+];
+
+fn size_cause(type_name: &str) -> TargetDifferenceCause {
+    match embedded_type(type_name) {
+        None => TargetDifferenceCause::PointerWidth,
+        Some(t) if POINTER_SIZED.contains(&t) => TargetDifferenceCause::PointerWidth,
+        Some("long double") => TargetDifferenceCause::LongDouble,
+        Some(_) => TargetDifferenceCause::MemberTypes,
+    }
+}
+
+/// Causes in declaration order of the old layout: a member whose size changed explains every
+/// later member moving, while a member that moved with nothing before it changing points at
+/// different alignment rules.
+fn target_causes(old: &StructLayout, new: &StructLayout) -> Vec<TargetDifferenceCause> {
+    let new_members: BTreeMap<&str, &MemberLayout> =
+        new.members.iter().map(|m| (m.name.as_str(), m)).collect();
+    let old_names: BTreeSet<&str> = old.members.iter().map(|m| m.name.as_str()).collect();
+
+    let mut causes = BTreeSet::new();
+    if old.members.len() != new.members.len()
+        || new.members.iter().any(|m| !old_names.contains(m.name.as_str()))
+    {
+        causes.insert(TargetDifferenceCause::ConditionalMembers);
+    }
+    // With members missing on either side, moved offsets say nothing about alignment.
+    let mut explained = !causes.is_empty();
+    for member in &old.members {
+        let Some(other) = new_members.get(member.name.as_str()) else { continue };
+        if member.size != other.size {
+            causes.insert(size_cause(&member.type_name));
+            explained = true;
+        } else if member.offset != other.offset && !explained {
+            causes.insert(TargetDifferenceCause::Alignment);
+            explained = true;
+        }
+    }
+    // Same members at the same offsets: the struct's size changed with its alignment.
+    if causes.is_empty() {
+        causes.insert(TargetDifferenceCause::Alignment);
+    }
+    causes.into_iter().collect()
+}
+
+/// The alignment `layout` honours: its declared one, else the largest its members'
+/// alignments, offsets, and size allow. `None` when a member's alignment is unknown.
+fn honoured_alignment(layout: &StructLayout) -> Option<u64> {
+    if layout.alignment.is_some() {
+        return layout.alignment;
+    }
+    let placed: Vec<(u64, u64)> = layout
+        .members
+        .iter()
+        .filter(|m| m.bit_size.is_none() && m.size.is_some_and(|s| s > 0))
+        .map(|m| Some((m.offset?, m.alignment?)))
+        .collect::<Option<_>>()?;
+    let natural = placed.iter().map(|&(_, alignment)| alignment).max().unwrap_or(1);
+    Some(implied_alignment(natural, Some(layout.size), placed.into_iter()))
+}
+
+fn classify_build_difference(change: &StructChange) -> BuildDifferenceKind {
+    let has = |kinds: &[MemberChangeKind]| {
+        change.member_changes.iter().any(|mc| kinds.contains(&mc.kind))
+    };
+
+    if has(&[MemberChangeKind::Added, MemberChangeKind::Removed, MemberChangeKind::Renamed]) {
+        BuildDifferenceKind::ConditionalMembers
+    } else if has(&[MemberChangeKind::SizeChanged, MemberChangeKind::TypeChanged]) {
+        BuildDifferenceKind::MemberTypes
+    } else {
+        BuildDifferenceKind::Unexplained
+    }
 }
 
 fn location_key(s: &StructLayout) -> Option<(&str, u64)> {
@@ -307,12 +607,37 @@ fn kind_rank(kind: &MemberChangeKind) -> u8 {
     match kind {
         MemberChangeKind::Removed => 0,
         MemberChangeKind::Added => 1,
-        MemberChangeKind::TypeChanged => 2,
-        MemberChangeKind::SizeChanged => 3,
-        MemberChangeKind::OffsetChanged => 4,
+        MemberChangeKind::Renamed => 2,
+        MemberChangeKind::TypeChanged => 3,
+        MemberChangeKind::SizeChanged => 4,
+        MemberChangeKind::OffsetChanged => 5,
     }
 }
 
+/// Members only in `old` paired with members only in `new` at the same known offset, with the
+/// same size, type, and bit range: most likely one member renamed. Where several could pair (a
+/// union), names pair in sorted order.
+fn renamed_members<'a>(
+    old: &BTreeMap<&'a str, &MemberLayout>,
+    new: &BTreeMap<&'a str, &MemberLayout>,
+) -> Vec<(&'a str, &'a str)> {
+    let same_place = |a: &MemberLayout, b: &MemberLayout| {
+        a.offset.is_some()
+            && (a.offset, a.size, &a.type_name, a.bit_offset, a.bit_size)
+                == (b.offset, b.size, &b.type_name, b.bit_offset, b.bit_size)
+    };
+    let mut added: Vec<(&str, &MemberLayout)> =
+        new.iter().filter(|(name, _)| !old.contains_key(*name)).map(|(n, m)| (*n, *m)).collect();
+
+    let mut renames = Vec::new();
+    for (old_name, old_member) in old.iter().filter(|(name, _)| !new.contains_key(*name)) {
+        if let Some(index) = added.iter().position(|(_, m)| same_place(old_member, m)) {
+            renames.push((*old_name, added.remove(index).0));
+        }
+    }
+    renames
+}
+
 fn diff_struct(old: &StructLayout, new: &StructLayout) -> Option<StructChange> {
     // Use saturating signed subtraction to handle large u64 values safely.
     let size_delta =
@@ -326,23 +651,36 @@ fn diff_struct(old: &StructLayout, new: &StructLayout) -> Option<StructChange> {
     let old_members: BTreeMap<&str, _> = old.members.iter().map(|m| (m.name.as_str(), m)).collect();
     let new_members: BTreeMap<&str, _> = new.members.iter().map(|m| (m.name.as_str(), m)).collect();
 
+    let renames = renamed_members(&old_members, &new_members);
+    let renamed_to: BTreeMap<&str, &str> = renames.iter().map(|&(old, new)| (new, old)).collect();
+
     for (name, old_member) in &old_members {
-        if !new_members.contains_key(name) {
+        if !new_members.contains_key(name) && !renames.iter().any(|&(old, _)| old == *name) {
             member_changes.push(MemberChange {
                 kind: MemberChangeKind::Removed,
                 name: name.to_string(),
                 details: format!("offset {:?}, size {:?}", old_member.offset, old_member.size),
+                source_location: old_member.source_location.clone(),
             });
         }
     }
 
     for (name, new_member) in &new_members {
         match old_members.get(name) {
+            None if renamed_to.contains_key(name) => {
+                member_changes.push(MemberChange {
+                    kind: MemberChangeKind::Renamed,
+                    name: name.to_string(),
+                    details: format!("{} -> {}", renamed_to[name], name),
+                    source_location: new_member.source_location.clone(),
+                });
+            }
             None => {
                 member_changes.push(MemberChange {
                     kind: MemberChangeKind::Added,
                     name: name.to_string(),
                     details: format!("offset {:?}, size {:?}", new_member.offset, new_member.size),
+                    source_location: new_member.source_location.clone(),
                 });
             }
             Some(old_member) => {
@@ -351,6 +689,7 @@ fn diff_struct(old: &StructLayout, new: &StructLayout) -> Option<StructChange> {
                         kind: MemberChangeKind::OffsetChanged,
                         name: name.to_string(),
                         details: format!("{:?} -> {:?}", old_member.offset, new_member.offset),
+                        source_location: new_member.source_location.clone(),
                     });
                 }
                 if old_member.size != new_member.size {
@@ -358,6 +697,7 @@ fn diff_struct(old: &StructLayout, new: &StructLayout) -> Option<StructChange> {
                         kind: MemberChangeKind::SizeChanged,
                         name: name.to_string(),
                         details: format!("{:?} -> {:?}", old_member.size, new_member.size),
+                        source_location: new_member.source_location.clone(),
                     });
                 }
                 if old_member.type_name != new_member.type_name {
@@ -365,6 +705,7 @@ fn diff_struct(old: &StructLayout, new: &StructLayout) -> Option<StructChange> {
                         kind: MemberChangeKind::TypeChanged,
                         name: name.to_string(),
                         details: format!("{} -> {}", old_member.type_name, new_member.type_name),
+                        source_location: new_member.source_location.clone(),
                     });
                 }
             }
@@ -531,6 +872,103 @@ mod tests {
         assert!(diff.has_regressions());
     }
 
+    #[test]
+    fn renames_replace_removal_and_addition_pairs() {
+        let member = |name: &str, ty: &str, offset, size| {
+            MemberLayout::new(name.to_string(), ty.to_string(), Some(offset), Some(size))
+        };
+        let old = layout(
+            "R",
+            16,
+            0,
+            vec![
+                member("count", "u32", 0, 4),
+                member("flags", "u32", 4, 4),
+                member("id", "u64", 8, 8),
+            ],
+        );
+        let new = layout(
+            "R",
+            16,
+            0,
+            vec![
+                member("len", "u32", 0, 4),
+                member("mode", "i32", 4, 4),
+                member("id", "u64", 8, 8),
+            ],
+        );
+
+        let diff = diff_layouts(&[old], &[new]);
+        let changes: Vec<_> = diff.changed[0]
+            .member_changes
+            .iter()
+            .map(|c| (&c.kind, c.name.as_str(), c.details.as_str()))
+            .collect();
+        // `flags` -> `mode` changed type too, so it stays a removal and an addition.
+        assert_eq!(
+            changes,
+            [
+                (&MemberChangeKind::Removed, "flags", "offset Some(4), size Some(4)"),
+                (&MemberChangeKind::Added, "mode", "offset Some(4), size Some(4)"),
+                (&MemberChangeKind::Renamed, "len", "count -> len"),
+            ]
+        );
+    }
+
+    #[test]
+    fn member_changes_point_at_member_declarations() {
+        let located = |name: &str, offset, file: &str, line| {
+            let mut m =
+                MemberLayout::new(name.to_string(), "u32".to_string(), Some(offset), Some(4));
+            m.source_location = Some(SourceLocation { file: file.to_string(), line });
+            m
+        };
+        let old = layout("Z", 8, 0, vec![located("a", 0, "old.h", 3), located("b", 4, "old.h", 4)]);
+        let new = layout("Z", 8, 0, vec![located("a", 4, "new.h", 7)]);
+
+        let diff = diff_layouts(&[old], &[new]);
+        let lines: Vec<_> = diff.changed[0]
+            .member_changes
+            .iter()
+            .map(|c| {
+                (c.name.as_str(), c.source_location.as_ref().map(|l| (l.file.as_str(), l.line)))
+            })
+            .collect();
+        // A removed member is only declared in the old build.
+        assert_eq!(lines, [("b", Some(("old.h", 4))), ("a", Some(("new.h", 7)))]);
+    }
+
+    #[test]
+    fn compare_builds_attributes_differences() {
+        let member = |name: &str, ty: &str, offset, size| {
+            MemberLayout::new(name.to_string(), ty.to_string(), Some(offset), Some(size))
+        };
+        let debug = [
+            layout("Gated", 16, 0, vec![member("a", "u64", 0, 8), member("dbg", "u64", 8, 8)]),
+            layout("Nested", 16, 0, vec![member("inner", "Inner", 0, 16)]),
+            layout("Moved", 8, 0, vec![member("a", "u32", 0, 4), member("b", "u32", 4, 4)]),
+            layout("DebugOnly", 8, 0, Vec::new()),
+        ];
+        let release = [
+            layout("Gated", 8, 0, vec![member("a", "u64", 0, 8)]),
+            layout("Nested", 8, 0, vec![member("inner", "Inner", 0, 8)]),
+            layout("Moved", 8, 0, vec![member("a", "u32", 4, 4), member("b", "u32", 0, 4)]),
+        ];
+
+        let cmp = compare_builds(&debug, &release);
+        let kind_of = |name: &str| {
+            cmp.differences.iter().find(|d| d.change.name == name).map(|d| d.kind).unwrap()
+        };
+
+        assert_eq!(kind_of("Gated"), BuildDifferenceKind::ConditionalMembers);
+        assert_eq!(kind_of("Nested"), BuildDifferenceKind::MemberTypes);
+        assert_eq!(kind_of("Moved"), BuildDifferenceKind::Unexplained);
+        assert_eq!(cmp.debug_only.len(), 1);
+        assert!(cmp.release_only.is_empty());
+        assert!(cmp.has_differences());
+        assert!(cmp.has_unexplained());
+    }
+
     #[test]
     fn member_similarity_location_penalty() {
         let mut a = layout_with_loc("T", "a.c", 1);
@@ -573,4 +1011,82 @@ mod tests {
         assert!(old_unmatched.is_empty());
         assert!(new_unmatched.is_empty());
     }
+
+    #[test]
+    fn determinism_runs_count_every_difference() {
+        let member =
+            |offset| MemberLayout::new("a".to_string(), "u32".to_string(), Some(offset), Some(4));
+        let baseline = vec![layout("A", 8, 4, vec![member(0)]), layout("Gone", 8, 0, Vec::new())];
+        let same = DeterminismRun::new("run 2".to_string(), &baseline, &baseline);
+        assert_eq!((same.differing(), same.unchanged_count), (0, 2));
+
+        let moved = vec![layout("A", 8, 4, vec![member(4)])];
+        let run = DeterminismRun::new("run 3".to_string(), &baseline, &moved);
+        assert_eq!(run.differing(), 2);
+        assert_eq!(run.differences[0].kind, BuildDifferenceKind::Unexplained);
+        assert_eq!(run.only_in_baseline[0].name, "Gone");
+
+        let report = DeterminismReport { baseline: "run 1".to_string(), runs: vec![same] };
+        assert!(report.is_deterministic());
+        let report = DeterminismReport { runs: vec![run], ..report };
+        assert!(!report.is_deterministic());
+    }
+
+    #[test]
+    fn target_comparison_names_the_cause_of_each_difference() {
+        let member = |name: &str, type_name: &str, offset: u64, size: u64| {
+            let mut m = MemberLayout::new(
+                name.to_string(),
+                type_name.to_string(),
+                Some(offset),
+                Some(size),
+            );
+            m.alignment = Some(size.min(8));
+            m
+        };
+        let unaligned = |name, size, members| {
+            let mut s = layout(name, size, 0, members);
+            s.alignment = None;
+            s
+        };
+        // x86_64 against i386.
+        let old = vec![
+            unaligned("Node", 16, vec![member("tag", "char", 0, 1), member("next", "*Node", 8, 8)]),
+            unaligned(
+                "Sample",
+                16,
+                vec![member("count", "int", 0, 4), member("value", "double", 8, 8)],
+            ),
+            unaligned("Wide", 16, vec![member("value", "long double", 0, 16)]),
+            unaligned("Same", 4, vec![member("count", "int", 0, 4)]),
+        ];
+        let new = vec![
+            unaligned("Node", 8, vec![member("tag", "char", 0, 1), member("next", "*Node", 4, 4)]),
+            unaligned(
+                "Sample",
+                12,
+                vec![member("count", "int", 0, 4), member("value", "double", 4, 8)],
+            ),
+            unaligned("Wide", 12, vec![member("value", "long double", 0, 12)]),
+            unaligned("Same", 4, vec![member("count", "int", 0, 4)]),
+        ];
+
+        let comparison = compare_targets("x86_64".to_string(), &old, "i386".to_string(), &new);
+        assert_eq!(comparison.unchanged_count, 1);
+        let causes: Vec<(&str, &[TargetDifferenceCause])> = comparison
+            .differences
+            .iter()
+            .map(|d| (d.change.name.as_str(), d.causes.as_slice()))
+            .collect();
+        assert_eq!(
+            causes,
+            vec![
+                ("Node", &[TargetDifferenceCause::PointerWidth][..]),
+                ("Sample", &[TargetDifferenceCause::Alignment][..]),
+                ("Wide", &[TargetDifferenceCause::LongDouble][..]),
+            ]
+        );
+        let sample = &comparison.differences[1];
+        assert_eq!((sample.old_alignment, sample.new_alignment), (Some(8), Some(4)));
+    }
 }
@@ -1,11 +1,25 @@
+use crate::analysis::AtomicPatterns;
 use crate::error::{Error, Result};
 use crate::loader::{DwarfSlice, LoadedDwarf};
-use crate::types::{MemberLayout, SourceLocation, StructLayout};
-use gimli::{AttributeValue, DebuggingInformationEntry, Dwarf, Unit};
-
-use super::TypeResolver;
-use super::expr::{evaluate_member_offset, try_simple_offset};
-use super::{debug_info_ref_to_unit_offset, read_u64_from_attr};
+use crate::locate::{FieldLocation, resolve_field};
+use crate::target::TargetMismatch;
+use crate::types::{
+    AggregateExtent, EnumLayout, GlobalVariable, MemberLayout, MemberRole, SharedGlobal,
+    SourceLocation, StaticMember, StructFingerprint, StructLayout, StructUsage, VariantLayout,
+};
+use gimli::{
+    AttributeValue, DebuggingInformationEntry, Dwarf, DwoId, Unit, UnitHeader, UnitOffset,
+};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+use super::expr::{accessed_ranges, evaluate_member_offset, static_address, try_simple_offset};
+use super::{ScanStats, StructScope, TypeInfo, TypeResolver, UnitStats};
+use super::{
+    debug_info_ref_to_unit_offset, read_alignment, read_u64_from_attr, whole_byte_data_bit_offset,
+};
 
 /// Prefixes for Go runtime internal types that should be filtered.
 /// Grouped by category for maintainability.
@@ -93,102 +107,1137 @@ pub fn is_go_internal_type(name: &str) -> bool {
         b't' => {
             name.starts_with("type:") || name.starts_with("type..") || name.starts_with("type.*[")
         }
-        b'u' => name.starts_with("unsafe."),
-        b'h' => name.starts_with("hash<") || name.starts_with("hmap") || name.starts_with("hchan"),
-        b'b' => name.starts_with("bucket<"),
-        b'w' => name.starts_with("waitq<"),
-        b'e' => name.starts_with("eface"),
-        b'f' => name.starts_with("funcval"),
-        b'n' => name.starts_with("noalg."),
-        b'[' => name.starts_with("[]") || name.starts_with("[]*"),
-        _ => false,
-    };
+        b'u' => name.starts_with("unsafe."),
+        b'h' => name.starts_with("hash<") || name.starts_with("hmap") || name.starts_with("hchan"),
+        b'b' => name.starts_with("bucket<"),
+        b'w' => name.starts_with("waitq<"),
+        b'e' => name.starts_with("eface"),
+        b'f' => name.starts_with("funcval"),
+        b'n' => name.starts_with("noalg."),
+        b'[' => name.starts_with("[]") || name.starts_with("[]*"),
+        _ => false,
+    };
+
+    // Middle dot check as fallback (Go uses · for unexported identifiers)
+    matches_prefix || name.contains('\u{00B7}')
+}
+
+/// Returns the list of Go internal type prefixes (for testing).
+#[cfg(test)]
+pub(crate) fn go_internal_prefixes() -> &'static [&'static str] {
+    GO_INTERNAL_PREFIXES
+}
+
+#[derive(Clone)]
+pub struct DwarfContext<'a> {
+    dwarf: &'a Dwarf<DwarfSlice<'a>>,
+    split_units: &'a HashMap<DwoId, Dwarf<DwarfSlice<'a>>>,
+    endian: gimli::RunTimeEndian,
+    target_mismatches: &'a [TargetMismatch],
+    scope: StructScope,
+    unit_timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    atomic_patterns: AtomicPatterns,
+}
+
+/// A struct definition by name, size, and source location (file and line).
+type DefinitionKey = (String, u64, Option<(String, u64)>);
+
+/// Structs reachable from exported symbols, from [`DwarfContext::exported_structs`].
+#[derive(Debug, Default)]
+pub struct ExportedStructs {
+    definitions: HashSet<DefinitionKey>,
+    /// Structs the ABI reaches only as a declaration (an opaque type), by name.
+    declarations: HashSet<String>,
+}
+
+impl ExportedStructs {
+    /// Whether `layout` is exported: the same definition is reachable, or a declaration of its
+    /// name is. A private struct sharing only its name with an exported one is not.
+    pub fn contains(&self, layout: &StructLayout) -> bool {
+        if self.declarations.contains(&layout.name) {
+            return true;
+        }
+        let source = layout.source_location.as_ref().map(|l| (l.file.clone(), l.line));
+        self.definitions.contains(&(layout.name.clone(), layout.size, source))
+    }
+}
+
+/// Iterator returned by [`DwarfContext::iter_structs`].
+pub struct StructIter<'c, 'a, 'f> {
+    context: &'c DwarfContext<'a>,
+    units: gimli::DebugInfoUnitHeadersIter<DwarfSlice<'a>>,
+    filter: Option<&'f str>,
+    include_go_runtime: bool,
+    pending: std::vec::IntoIter<StructLayout>,
+    done: bool,
+    /// Fingerprint digests of structs yielded so far, when deduplicating.
+    seen: Option<HashSet<[u8; 32]>>,
+    units_read: usize,
+    skipped: Vec<UnitStats>,
+    out_of_time: bool,
+}
+
+impl StructIter<'_, '_, '_> {
+    /// Skip definitions identical to one already yielded, as [`DwarfContext::find_structs`]
+    /// does. Each distinct struct costs a 32-byte SHA-256 of its whole fingerprint (name, size,
+    /// source and members), so memory grows with the number of distinct structs, not their
+    /// size, and a collision between distinct layouts is not a practical concern.
+    pub fn deduplicated(mut self) -> Self {
+        self.seen = Some(HashSet::new());
+        self
+    }
+
+    /// Whether `layout` repeats a struct already yielded (always false without deduplication).
+    fn is_repeat(&mut self, layout: &StructLayout) -> bool {
+        let Some(seen) = &mut self.seen else { return false };
+        !seen.insert(layout.fingerprint().digest())
+    }
+
+    /// Units dropped so far by the unit timeout, in `.debug_info` order.
+    pub fn skipped_units(&self) -> &[UnitStats] {
+        &self.skipped
+    }
+
+    /// Units read so far, skipped ones included.
+    pub fn units_read(&self) -> usize {
+        self.units_read
+    }
+
+    /// True once iteration stopped at the context's deadline (see
+    /// [`DwarfContext::with_deadline`]); units after it were never read.
+    pub fn out_of_time(&self) -> bool {
+        self.out_of_time
+    }
+
+    /// Parse the next unit into `pending`. Returns `Ok(false)` once units run out or the
+    /// deadline passes.
+    fn load_next_unit(&mut self) -> Result<bool> {
+        let Some(header) = self
+            .units
+            .next()
+            .map_err(|e| Error::Dwarf(format!("Failed to read unit header: {}", e)))?
+        else {
+            return Ok(false);
+        };
+        if self.context.past_deadline() {
+            self.out_of_time = true;
+            return Ok(false);
+        }
+
+        let mut structs = Vec::new();
+        let stats =
+            self.context.scan_unit(header, self.filter, self.include_go_runtime, &mut structs)?;
+        if stats.skipped {
+            if self.context.past_deadline() {
+                self.out_of_time = true;
+                return Ok(false);
+            }
+            self.skipped.push(stats);
+        }
+        self.units_read += 1;
+        self.pending = structs.into_iter();
+        Ok(true)
+    }
+}
+
+impl Iterator for StructIter<'_, '_, '_> {
+    type Item = Result<StructLayout>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(layout) = self.pending.next() {
+                if self.is_repeat(&layout) {
+                    continue;
+                }
+                return Some(Ok(layout));
+            }
+            if self.done {
+                return None;
+            }
+            match self.load_next_unit() {
+                Ok(true) => {}
+                Ok(false) => self.done = true,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+impl std::iter::FusedIterator for StructIter<'_, '_, '_> {}
+
+impl<'a> DwarfContext<'a> {
+    pub fn new(loaded: &'a LoadedDwarf<'a>) -> Self {
+        Self {
+            dwarf: &loaded.dwarf,
+            split_units: &loaded.split_units,
+            endian: loaded.endian,
+            target_mismatches: &loaded.target_mismatches,
+            scope: StructScope::default(),
+            unit_timeout: None,
+            deadline: None,
+            atomic_patterns: AtomicPatterns::default(),
+        }
+    }
+
+    /// Parse the unit at `header`. A skeleton unit (DWARF fission) is replaced by its split unit
+    /// when the loader found it, together with a context reading from the file holding it.
+    fn unit(
+        &self,
+        header: UnitHeader<DwarfSlice<'a>>,
+    ) -> Result<(Cow<'_, Self>, Unit<DwarfSlice<'a>>)> {
+        let parse_error = |e: gimli::Error| Error::Dwarf(format!("Failed to parse unit: {}", e));
+        let unit = self.dwarf.unit(header).map_err(parse_error)?;
+        let Some(split) = unit.dwo_id.and_then(|id| self.split_units.get(&id)) else {
+            return Ok((Cow::Borrowed(self), unit));
+        };
+
+        let mut split_units = split.units();
+        while let Some(header) = split_units.next().map_err(parse_error)? {
+            let mut split_unit = split.unit(header).map_err(parse_error)?;
+            if split_unit.dwo_id == unit.dwo_id {
+                split_unit.copy_relocated_attributes(&unit);
+                // DW_AT_decl_file in a split unit indexes the file table at the start of
+                // `.debug_line.dwo`.
+                if split_unit.line_program.is_none() {
+                    split_unit.line_program = split
+                        .debug_line
+                        .program(
+                            gimli::DebugLineOffset(0),
+                            split_unit.header.address_size(),
+                            split_unit.comp_dir,
+                            split_unit.name,
+                        )
+                        .ok();
+                }
+                return Ok((Cow::Owned(Self { dwarf: split, ..self.clone() }), split_unit));
+            }
+        }
+        Ok((Cow::Borrowed(self), unit))
+    }
+
+    /// Ways the binary's DWARF disagrees with its object file headers.
+    pub fn target_mismatches(&self) -> &[TargetMismatch] {
+        self.target_mismatches
+    }
+
+    /// Restrict `find_structs` to names admitted by `scope`. Out-of-scope structs are skipped
+    /// before their members are read.
+    pub fn with_scope(mut self, scope: StructScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Give up on any compilation unit that takes longer than `timeout` to scan in
+    /// `find_structs`; its structs are dropped and the unit is marked `skipped` in the
+    /// [`ScanStats`]. The deadline is checked between DIEs, so a unit can overrun it by the
+    /// time one struct takes to read.
+    pub fn with_unit_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.unit_timeout = timeout;
+        self
+    }
+
+    /// Stop `find_structs` at `deadline`: the unit being read is dropped, later units are not
+    /// read, and [`ScanStats::out_of_time`] is set. Checked like the unit timeout.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Treat globals whose types match `patterns` (or hold such a type) as thread-shared in
+    /// [`DwarfContext::shared_globals`].
+    pub fn with_atomic_patterns(mut self, patterns: AtomicPatterns) -> Self {
+        self.atomic_patterns = patterns;
+        self
+    }
+
+    /// Find all structs in the binary.
+    ///
+    /// - `filter`: Optional substring filter for struct names
+    /// - `include_go_runtime`: If false, Go runtime internal types are filtered out
+    ///
+    /// Only structs inside the context's scope (see [`DwarfContext::with_scope`]) are returned;
+    /// the scope can also match names by regex and require a matching member.
+    ///
+    /// Results are returned in canonical order (see [`StructLayout::canonical_cmp`]).
+    pub fn find_structs(
+        &self,
+        filter: Option<&str>,
+        include_go_runtime: bool,
+    ) -> Result<Vec<StructLayout>> {
+        self.find_structs_with_stats(filter, include_go_runtime).map(|(structs, _)| structs)
+    }
+
+    /// Like [`DwarfContext::find_structs`], also returning how long each unit took and which
+    /// units were skipped by the unit timeout.
+    pub fn find_structs_with_stats(
+        &self,
+        filter: Option<&str>,
+        include_go_runtime: bool,
+    ) -> Result<(Vec<StructLayout>, ScanStats)> {
+        let mut structs = Vec::new();
+        let mut stats = ScanStats::default();
+        let mut units = self.dwarf.units();
+
+        while let Some(header) =
+            units.next().map_err(|e| Error::Dwarf(format!("Failed to read unit header: {}", e)))?
+        {
+            if self.past_deadline() {
+                stats.out_of_time = true;
+                break;
+            }
+            let unit = self.scan_unit(header, filter, include_go_runtime, &mut structs)?;
+            if unit.skipped && self.past_deadline() {
+                stats.out_of_time = true;
+                break;
+            }
+            stats.units.push(unit);
+        }
+
+        // DWARF can contain duplicate identical type entries (e.g., across units or due to
+        // language/compiler quirks). Deduplicate exact duplicates to avoid double-counting in
+        // `check` and unstable matching in `diff`.
+        // Use enumerated index as tiebreaker for stable deduplication (Rust's sort_by is unstable).
+        let mut with_fp: Vec<(StructFingerprint, usize, StructLayout)> =
+            structs.into_iter().enumerate().map(|(i, s)| (s.fingerprint(), i, s)).collect();
+        with_fp.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        with_fp.dedup_by(|a, b| a.0 == b.0);
+
+        Ok((with_fp.into_iter().map(|(_, _, s)| s).collect(), stats))
+    }
+
+    /// Whether the context's deadline has passed.
+    fn past_deadline(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+
+    /// Append the structs of the unit at `header` to `structs`. A unit that runs past the unit
+    /// timeout or the deadline contributes none and comes back marked skipped.
+    fn scan_unit(
+        &self,
+        header: UnitHeader<DwarfSlice<'a>>,
+        filter: Option<&str>,
+        include_go_runtime: bool,
+        structs: &mut Vec<StructLayout>,
+    ) -> Result<UnitStats> {
+        let start = Instant::now();
+        let offset = match header.offset() {
+            gimli::UnitSectionOffset::DebugInfoOffset(o) => o.0 as u64,
+            gimli::UnitSectionOffset::DebugTypesOffset(o) => o.0 as u64,
+        };
+        let (context, unit) = self.unit(header)?;
+
+        let before = structs.len();
+        let deadline = match (self.unit_timeout.map(|t| start + t), self.deadline) {
+            (Some(unit), Some(scan)) => Some(unit.min(scan)),
+            (unit, scan) => unit.or(scan),
+        };
+        let mut unsupported_forms = BTreeMap::new();
+        let completed = context.process_unit(
+            &unit,
+            filter,
+            include_go_runtime,
+            deadline,
+            structs,
+            &mut unsupported_forms,
+        )?;
+        if !completed {
+            structs.truncate(before);
+        }
+
+        Ok(UnitStats {
+            offset,
+            name: unit.name.map(|n| n.to_string_lossy().into_owned()),
+            elapsed: start.elapsed(),
+            structs: structs.len() - before,
+            skipped: !completed,
+            version: unit.header.version(),
+            producer: context.producer(&unit),
+            split: matches!(context, Cow::Owned(_)),
+            unsupported_forms,
+        })
+    }
+
+    /// Iterate over structs one compilation unit at a time, without collecting the whole
+    /// binary first. Only the current unit's structs are buffered, so consumers can stop early
+    /// (e.g. after finding one struct by name) or process huge binaries in constant memory.
+    ///
+    /// Unlike [`DwarfContext::find_structs`], results come in `.debug_info` order and identical
+    /// definitions repeated across units are not deduplicated. Units that exceed the unit
+    /// timeout yield nothing and are listed by [`StructIter::skipped_units`]; iteration ends
+    /// at the context's deadline, setting [`StructIter::out_of_time`], or after the first
+    /// error. Call [`StructIter::deduplicated`] to drop repeated definitions.
+    pub fn iter_structs<'c, 'f>(
+        &'c self,
+        filter: Option<&'f str>,
+        include_go_runtime: bool,
+    ) -> StructIter<'c, 'a, 'f> {
+        StructIter {
+            context: self,
+            units: self.dwarf.units(),
+            filter,
+            include_go_runtime,
+            pending: Vec::new().into_iter(),
+            done: false,
+            seen: None,
+            units_read: 0,
+            skipped: Vec::new(),
+            out_of_time: false,
+        }
+    }
+
+    /// Call `visit` with each distinct struct as its unit is parsed, until it returns
+    /// `ControlFlow::Break`. Like [`DwarfContext::iter_structs`] with deduplication, for
+    /// consumers that prefer a callback; memory is the largest unit plus a 32-byte digest per
+    /// distinct struct.
+    pub fn visit_structs(
+        &self,
+        filter: Option<&str>,
+        include_go_runtime: bool,
+        mut visit: impl FnMut(StructLayout) -> ControlFlow<()>,
+    ) -> Result<()> {
+        for layout in self.iter_structs(filter, include_go_runtime).deduplicated() {
+            if visit(layout?).is_break() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// The member of the struct named `type_name` holding the byte at `byte_offset`, followed
+    /// into nested structs and array elements, or the padding hole the byte falls in. Parses
+    /// every struct in scope; see [`resolve_field`] for resolving against layouts already found.
+    pub fn resolve_address_field(
+        &self,
+        type_name: &str,
+        byte_offset: u64,
+    ) -> Result<FieldLocation> {
+        let layouts = self.find_structs(None, true)?;
+        resolve_field(&layouts, type_name, byte_offset)
+    }
+
+    /// Structs reachable from the ABI surface: the return and parameter types of exported
+    /// functions and the types of exported variables, followed through pointers, typedefs,
+    /// qualifiers, arrays, members, and base classes, across units too (as LTO and partial
+    /// units refer to types in other units).
+    ///
+    /// `exports` holds symbol names as they appear in the dynamic symbol table; they are
+    /// matched against DW_AT_linkage_name, falling back to DW_AT_name for C symbols.
+    pub fn exported_structs(&self, exports: &HashSet<String>) -> Result<ExportedStructs> {
+        let mut headers = Vec::new();
+        let mut units = self.dwarf.units();
+        while let Some(header) =
+            units.next().map_err(|e| Error::Dwarf(format!("Failed to read unit header: {}", e)))?
+        {
+            headers.push(header);
+        }
+
+        // Every unit is read for its exported symbols, so each is parsed once and kept for
+        // references into it from other units.
+        let mut parsed = Vec::with_capacity(headers.len());
+        let mut pending: Vec<(usize, UnitOffset)> = Vec::new();
+        for (index, header) in headers.iter().enumerate() {
+            let (context, unit) = self.unit(*header)?;
+            let split = matches!(context, Cow::Owned(_));
+            for attr in context.exported_type_refs(&unit, exports)? {
+                pending.extend(resolve_type_ref(&headers, index, split, &unit, attr));
+            }
+            parsed.push((context, unit));
+        }
+
+        let mut exported = ExportedStructs::default();
+        let mut visited: HashSet<(usize, UnitOffset)> = HashSet::new();
+        while let Some((index, offset)) = pending.pop() {
+            if !visited.insert((index, offset)) {
+                continue;
+            }
+            let (context, unit) = &parsed[index];
+            let split = matches!(context, Cow::Owned(_));
+            let entry = unit
+                .entry(offset)
+                .map_err(|e| Error::Dwarf(format!("Failed to get type entry: {}", e)))?;
+
+            let mut refs: Vec<AttributeValue<DwarfSlice<'a>>> = Vec::new();
+            match entry.tag() {
+                gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type => {
+                    if let Some(name) = context.get_die_name(unit, &entry)? {
+                        context.add_exported(unit, &entry, name, &mut exported)?;
+                    }
+                    refs.extend(context.child_type_attrs(unit, offset)?);
+                }
+                // Unions and function pointer types carry no layout of their own here,
+                // but structs reachable through them are still part of the ABI.
+                gimli::DW_TAG_union_type | gimli::DW_TAG_subroutine_type => {
+                    refs.extend(context.child_type_attrs(unit, offset)?);
+                }
+                _ => {}
+            }
+            refs.extend(entry.attr_value(gimli::DW_AT_type).ok().flatten());
+            pending.extend(
+                refs.into_iter()
+                    .filter_map(|attr| resolve_type_ref(&headers, index, split, unit, attr)),
+            );
+        }
+
+        Ok(exported)
+    }
+
+    /// Global and static variables at fixed addresses whose types hold an atomic or a lock,
+    /// directly or through typedefs, qualifiers, arrays, members, and base classes (not
+    /// pointers). Sizes come from the types; sections are left for [`place_globals`].
+    ///
+    /// [`place_globals`]: crate::analysis::place_globals
+    pub fn shared_globals(&self) -> Result<Vec<SharedGlobal>> {
+        Ok(self.globals(true)?.into_iter().map(SharedGlobal::from).collect())
+    }
+
+    /// Every global and static variable at a fixed address, with its type and size.
+    pub fn global_variables(&self) -> Result<Vec<GlobalVariable>> {
+        self.globals(false)
+    }
+
+    fn globals(&self, shared_only: bool) -> Result<Vec<GlobalVariable>> {
+        let mut globals = Vec::new();
+        let mut units = self.dwarf.units();
+
+        while let Some(header) =
+            units.next().map_err(|e| Error::Dwarf(format!("Failed to read unit header: {}", e)))?
+        {
+            let (context, unit) = self.unit(header)?;
+
+            context.collect_globals(&unit, shared_only, &mut globals)?;
+        }
+
+        Ok(globals)
+    }
+
+    fn collect_globals(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        shared_only: bool,
+        globals: &mut Vec<GlobalVariable>,
+    ) -> Result<()> {
+        let mut type_resolver = TypeResolver::new(self.dwarf, unit, unit.header.address_size());
+        let mut shared_types: HashMap<UnitOffset, bool> = HashMap::new();
+        let mut entries = unit.entries();
+
+        while let Some((_, entry)) =
+            entries.next_dfs().map_err(|e| Error::Dwarf(format!("Failed to read DIE: {}", e)))?
+        {
+            if entry.tag() != gimli::DW_TAG_variable {
+                continue;
+            }
+            let Some(AttributeValue::Exprloc(expr)) =
+                entry.attr_value(gimli::DW_AT_location).ok().flatten()
+            else {
+                continue;
+            };
+            let Some(address) =
+                static_address(expr, unit.encoding(), |index| self.dwarf.address(unit, index).ok())
+            else {
+                continue;
+            };
+
+            // Out-of-class definitions of C++ static members name their declaration instead.
+            let declaration =
+                unit_ref(unit, entry.attr_value(gimli::DW_AT_specification).ok().flatten())
+                    .map(|offset| unit.entry(offset))
+                    .transpose()
+                    .map_err(|e| Error::Dwarf(format!("Failed to get declaration: {}", e)))?;
+            let described = declaration.as_ref().unwrap_or(entry);
+            let Some(type_offset) = type_ref(unit, entry).or_else(|| type_ref(unit, described))
+            else {
+                continue;
+            };
+            if shared_only && !self.type_holds_atomic(unit, type_offset, &mut shared_types, 0)? {
+                continue;
+            }
+            let Some(name) = self.get_die_name(unit, described)? else {
+                continue;
+            };
+            let TypeInfo { name: type_name, size, .. } = type_resolver.resolve_type(type_offset)?;
+            globals.push(GlobalVariable { name, type_name, address, size: size.unwrap_or(0) });
+        }
+
+        Ok(())
+    }
+
+    /// Whether a value of this type contains an atomic, memoized per unit.
+    fn type_holds_atomic(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        offset: UnitOffset,
+        memo: &mut HashMap<UnitOffset, bool>,
+        depth: usize,
+    ) -> Result<bool> {
+        if let Some(&known) = memo.get(&offset) {
+            return Ok(known);
+        }
+        if depth > 16 {
+            return Ok(false);
+        }
+        // Provisionally false, so recursive types terminate.
+        memo.insert(offset, false);
+
+        let entry = unit
+            .entry(offset)
+            .map_err(|e| Error::Dwarf(format!("Failed to get type entry: {}", e)))?;
+        let named_atomic = self
+            .get_die_name(unit, &entry)?
+            .is_some_and(|n| self.atomic_patterns.matches_die_name(&n));
+        let holds = match entry.tag() {
+            gimli::DW_TAG_atomic_type => true,
+            _ if named_atomic => true,
+            gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type | gimli::DW_TAG_union_type => {
+                let mut holds = false;
+                for child in self.child_type_refs(unit, offset)? {
+                    if self.type_holds_atomic(unit, child, memo, depth + 1)? {
+                        holds = true;
+                        break;
+                    }
+                }
+                holds
+            }
+            gimli::DW_TAG_typedef
+            | gimli::DW_TAG_const_type
+            | gimli::DW_TAG_volatile_type
+            | gimli::DW_TAG_array_type => match type_ref(unit, &entry) {
+                Some(inner) => self.type_holds_atomic(unit, inner, memo, depth + 1)?,
+                None => false,
+            },
+            _ => false,
+        };
+        memo.insert(offset, holds);
+        Ok(holds)
+    }
+
+    /// Approximate how many functions touch each member of `layouts`, from the location
+    /// descriptions of struct-typed (or pointer-to-struct) variables and parameters: a
+    /// `DW_OP_plus_uconst` offset or a live `DW_OP_piece` marks the members it covers as used
+    /// by the enclosing subprogram (inlined bodies count toward their caller).
+    ///
+    /// Sets `static_popularity` on every member of every layout, to 0 when no function was
+    /// seen using it. Layouts are matched by struct name.
+    pub fn annotate_static_popularity(&self, layouts: &mut [StructLayout]) -> Result<()> {
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, layout) in layouts.iter_mut().enumerate() {
+            by_name.entry(layout.name.clone()).or_default().push(i);
+            for member in &mut layout.members {
+                member.static_popularity = Some(0);
+            }
+        }
+
+        let mut units = self.dwarf.units();
+        while let Some(header) =
+            units.next().map_err(|e| Error::Dwarf(format!("Failed to read unit header: {}", e)))?
+        {
+            let (context, unit) = self.unit(header)?;
+
+            context.collect_static_accesses(&unit, layouts, &by_name)?;
+        }
+
+        Ok(())
+    }
+
+    fn collect_static_accesses(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        layouts: &mut [StructLayout],
+        by_name: &HashMap<String, Vec<usize>>,
+    ) -> Result<()> {
+        let mut struct_names: HashMap<UnitOffset, Option<String>> = HashMap::new();
+        // Depth of the innermost enclosing subprogram, and the (layout, member) pairs it touches.
+        let mut functions: Vec<(isize, HashSet<(usize, usize)>)> = Vec::new();
+        let mut depth: isize = 0;
+        let mut entries = unit.entries();
+
+        while let Some((delta, entry)) =
+            entries.next_dfs().map_err(|e| Error::Dwarf(format!("Failed to read DIE: {}", e)))?
+        {
+            depth += delta;
+            while functions.last().is_some_and(|(d, _)| *d >= depth) {
+                if let Some((_, touched)) = functions.pop() {
+                    count_touched(layouts, touched);
+                }
+            }
+
+            match entry.tag() {
+                gimli::DW_TAG_subprogram => functions.push((depth, HashSet::new())),
+                gimli::DW_TAG_variable | gimli::DW_TAG_formal_parameter => {
+                    let Some((_, touched)) = functions.last_mut() else {
+                        continue;
+                    };
+                    let Some(location) = entry.attr_value(gimli::DW_AT_location).ok().flatten()
+                    else {
+                        continue;
+                    };
+                    let type_offset = type_ref(unit, entry).or_else(|| {
+                        let origin =
+                            unit_ref(unit, entry.attr_value(gimli::DW_AT_abstract_origin).ok()?)?;
+                        type_ref(unit, &unit.entry(origin).ok()?)
+                    });
+                    let Some(type_offset) = type_offset else {
+                        continue;
+                    };
+                    let name = match struct_names.get(&type_offset) {
+                        Some(name) => name.clone(),
+                        None => {
+                            let name = self.struct_behind(unit, type_offset, false)?;
+                            struct_names.insert(type_offset, name.clone());
+                            name
+                        }
+                    };
+                    let Some(indices) = name.as_ref().and_then(|n| by_name.get(n)) else {
+                        continue;
+                    };
+
+                    for range in self.location_ranges(unit, location)? {
+                        for &li in indices {
+                            for (mi, member) in layouts[li].members.iter().enumerate() {
+                                let Some(start) = member.offset else {
+                                    continue;
+                                };
+                                let end = member
+                                    .end_offset()
+                                    .unwrap_or(start)
+                                    .max(start.saturating_add(1));
+                                if start < range.end && range.start < end {
+                                    touched.insert((li, mi));
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (_, touched) in functions {
+            count_touched(layouts, touched);
+        }
+        Ok(())
+    }
+
+    /// Name of the struct a variable of this type accesses: the struct itself or the target of
+    /// one pointer or reference, through typedefs and qualifiers, and with `arrays` also the
+    /// element type of arrays.
+    fn struct_behind(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        mut offset: UnitOffset,
+        arrays: bool,
+    ) -> Result<Option<String>> {
+        let mut pointers = 0;
+        for _ in 0..16 {
+            let entry = unit
+                .entry(offset)
+                .map_err(|e| Error::Dwarf(format!("Failed to get type entry: {}", e)))?;
+            match entry.tag() {
+                gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type => {
+                    return self.get_die_name(unit, &entry);
+                }
+                gimli::DW_TAG_pointer_type
+                | gimli::DW_TAG_reference_type
+                | gimli::DW_TAG_rvalue_reference_type => {
+                    pointers += 1;
+                    if pointers > 1 {
+                        return Ok(None);
+                    }
+                }
+                gimli::DW_TAG_typedef
+                | gimli::DW_TAG_const_type
+                | gimli::DW_TAG_volatile_type
+                | gimli::DW_TAG_restrict_type
+                | gimli::DW_TAG_atomic_type => {}
+                gimli::DW_TAG_array_type if arrays => {}
+                _ => return Ok(None),
+            }
+            let Some(next) = type_ref(unit, &entry) else {
+                return Ok(None);
+            };
+            offset = next;
+        }
+        Ok(None)
+    }
+
+    /// Count the distinct functions, types, and global variables referring to each struct in
+    /// `layouts` (see [`StructUsage`]) into its `usage`. Layouts are matched by struct name.
+    /// Functions and variables are told apart by linkage name, or by name without one.
+    pub fn annotate_usage(&self, layouts: &mut [StructLayout]) -> Result<()> {
+        let mut users: HashMap<String, Users> =
+            layouts.iter().map(|l| (l.name.clone(), Users::default())).collect();
+
+        let mut units = self.dwarf.units();
+        while let Some(header) =
+            units.next().map_err(|e| Error::Dwarf(format!("Failed to read unit header: {}", e)))?
+        {
+            let (context, unit) = self.unit(header)?;
+
+            context.collect_users(&unit, &mut users)?;
+        }
+
+        for layout in layouts {
+            layout.usage = users.get(&layout.name).map(Users::usage);
+        }
+        Ok(())
+    }
+
+    fn collect_users(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        users: &mut HashMap<String, Users>,
+    ) -> Result<()> {
+        let mut struct_names: HashMap<UnitOffset, Option<String>> = HashMap::new();
+        // Innermost enclosing function or type definition, by depth.
+        let mut scopes: Vec<(isize, Referrer)> = Vec::new();
+        let mut depth: isize = 0;
+        let mut entries = unit.entries();
+
+        while let Some((delta, entry)) =
+            entries.next_dfs().map_err(|e| Error::Dwarf(format!("Failed to read DIE: {}", e)))?
+        {
+            depth += delta;
+            while scopes.last().is_some_and(|(d, _)| *d >= depth) {
+                scopes.pop();
+            }
+            let enclosing = scopes.last().map(|(_, r)| r);
+            if matches!(enclosing, Some(Referrer::Ignored)) {
+                continue;
+            }
+            let declaration = matches!(
+                entry.attr_value(gimli::DW_AT_declaration),
+                Ok(Some(AttributeValue::Flag(true)))
+            );
+
+            let referrer = match entry.tag() {
+                gimli::DW_TAG_structure_type
+                | gimli::DW_TAG_class_type
+                | gimli::DW_TAG_union_type => {
+                    let name = self.get_die_name(unit, entry)?.filter(|_| !declaration);
+                    scopes.push((depth, name.map_or(Referrer::Ignored, Referrer::Type)));
+                    continue;
+                }
+                // Declarations are counted at their definitions.
+                gimli::DW_TAG_subprogram if declaration => {
+                    scopes.push((depth, Referrer::Ignored));
+                    continue;
+                }
+                gimli::DW_TAG_subprogram => {
+                    let function = Referrer::Function(self.symbol_key(unit, entry)?);
+                    scopes.push((depth, function.clone()));
+                    function
+                }
+                gimli::DW_TAG_member | gimli::DW_TAG_inheritance => match enclosing {
+                    Some(Referrer::Type(name)) if !is_static_member(entry) => {
+                        Referrer::Type(name.clone())
+                    }
+                    _ => continue,
+                },
+                gimli::DW_TAG_formal_parameter | gimli::DW_TAG_variable => match enclosing {
+                    Some(function @ Referrer::Function(_)) => function.clone(),
+                    None if entry.tag() == gimli::DW_TAG_variable && !declaration => {
+                        Referrer::Variable(self.symbol_key(unit, entry)?)
+                    }
+                    _ => continue,
+                },
+                _ => continue,
+            };
+
+            // Inlined and out-of-line instances take their type from the abstract origin.
+            let type_offset = type_ref(unit, entry).or_else(|| {
+                let origin = unit_ref(unit, entry.attr_value(gimli::DW_AT_abstract_origin).ok()?)?;
+                type_ref(unit, &unit.entry(origin).ok()?)
+            });
+            let Some(type_offset) = type_offset else {
+                continue;
+            };
+            let name = match struct_names.get(&type_offset) {
+                Some(name) => name.clone(),
+                None => {
+                    let name = self.struct_behind(unit, type_offset, true)?;
+                    struct_names.insert(type_offset, name.clone());
+                    name
+                }
+            };
+            let Some((name, users)) = name.and_then(|n| users.get_mut(&n).map(|u| (n, u))) else {
+                continue;
+            };
+            match referrer {
+                Referrer::Function(key) => users.functions.insert(key),
+                // A struct pointing at its own kind (a list node) is not another user.
+                Referrer::Type(owner) if owner != name => users.types.insert(owner),
+                Referrer::Variable(key) => users.variables.insert(key),
+                Referrer::Type(_) | Referrer::Ignored => false,
+            };
+        }
+        Ok(())
+    }
+
+    /// What tells a function or variable apart from others in the binary: its linkage name, or
+    /// its name, read through `DW_AT_specification` and `DW_AT_abstract_origin` links to the
+    /// declaration. Anonymous ones are told apart by DIE offset.
+    fn symbol_key(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+    ) -> Result<String> {
+        let mut name = None;
+        let mut current = entry.clone();
+        for _ in 0..4 {
+            if let Some(linkage_name) = self.get_linkage_name(unit, &current)? {
+                return Ok(linkage_name);
+            }
+            if name.is_none() {
+                name = self.get_die_name(unit, &current)?;
+            }
+            let origin = [gimli::DW_AT_specification, gimli::DW_AT_abstract_origin]
+                .into_iter()
+                .find_map(|at| unit_ref(unit, current.attr_value(at).ok().flatten()));
+            let Some(origin) = origin else {
+                break;
+            };
+            current = unit
+                .entry(origin)
+                .map_err(|e| Error::Dwarf(format!("Failed to get origin entry: {}", e)))?;
+        }
+        Ok(name.unwrap_or_else(|| {
+            let offset = entry.offset().to_unit_section_offset(unit);
+            format!("<anonymous {:?}>", offset)
+        }))
+    }
+
+    /// Byte ranges referenced by a DW_AT_location, whether an inline expression or a
+    /// location list. Unreadable location lists contribute nothing.
+    fn location_ranges(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        location: AttributeValue<DwarfSlice<'a>>,
+    ) -> Result<Vec<std::ops::Range<u64>>> {
+        let encoding = unit.encoding();
+        if let AttributeValue::Exprloc(expr) = location {
+            return Ok(accessed_ranges(expr, encoding));
+        }
+
+        let mut ranges = Vec::new();
+        let Ok(Some(mut locations)) = self.dwarf.attr_locations(unit, location) else {
+            return Ok(ranges);
+        };
+        while let Ok(Some(entry)) = locations.next() {
+            ranges.extend(accessed_ranges(entry.data, encoding));
+        }
+        Ok(ranges)
+    }
+
+    /// DW_AT_type values of the exported functions and variables of `unit`, and of the
+    /// parameters of those functions.
+    fn exported_type_refs(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        exports: &HashSet<String>,
+    ) -> Result<Vec<AttributeValue<DwarfSlice<'a>>>> {
+        let mut refs = Vec::new();
+        let mut entries = unit.entries();
+
+        while let Some((_, entry)) =
+            entries.next_dfs().map_err(|e| Error::Dwarf(format!("Failed to read DIE: {}", e)))?
+        {
+            let tag = entry.tag();
+            if !matches!(tag, gimli::DW_TAG_subprogram | gimli::DW_TAG_variable)
+                || !self.is_exported(unit, entry, exports)?
+            {
+                continue;
+            }
+            refs.extend(entry.attr_value(gimli::DW_AT_type).ok().flatten());
+            if tag == gimli::DW_TAG_subprogram {
+                refs.extend(self.child_type_attrs(unit, entry.offset())?);
+            }
+        }
+
+        Ok(refs)
+    }
+
+    /// Record the struct at `entry`: a definition by name, size, and source location, a
+    /// declaration (an opaque type, whose layout lives in another unit) by name alone.
+    fn add_exported(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+        name: String,
+        exported: &mut ExportedStructs,
+    ) -> Result<()> {
+        let size = read_u64_from_attr(entry.attr_value(gimli::DW_AT_byte_size).ok().flatten());
+        let declaration = matches!(
+            entry.attr_value(gimli::DW_AT_declaration),
+            Ok(Some(AttributeValue::Flag(true)))
+        );
+        match size {
+            Some(size) if !declaration => {
+                let source = self.get_source_location(unit, entry)?.map(|l| (l.file, l.line));
+                exported.definitions.insert((name, size, source));
+            }
+            _ => {
+                exported.declarations.insert(name);
+            }
+        }
+        Ok(())
+    }
 
-    // Middle dot check as fallback (Go uses · for unexported identifiers)
-    matches_prefix || name.contains('\u{00B7}')
-}
+    /// Whether a subprogram or variable DIE is an external definition whose symbol is exported.
+    /// Out-of-line C++ definitions carry their name and DW_AT_external on the in-class
+    /// declaration, so DW_AT_specification / DW_AT_abstract_origin links are followed.
+    fn is_exported(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+        exports: &HashSet<String>,
+    ) -> Result<bool> {
+        let mut external = false;
+        let mut linkage_name = None;
+        let mut name = None;
+        let mut current = entry.clone();
+
+        for _ in 0..4 {
+            external |= matches!(
+                current.attr_value(gimli::DW_AT_external),
+                Ok(Some(AttributeValue::Flag(true)))
+            );
+            if linkage_name.is_none() {
+                linkage_name = self.get_linkage_name(unit, &current)?;
+            }
+            if name.is_none() {
+                name = self.get_die_name(unit, &current)?;
+            }
 
-/// Returns the list of Go internal type prefixes (for testing).
-#[cfg(test)]
-pub(crate) fn go_internal_prefixes() -> &'static [&'static str] {
-    GO_INTERNAL_PREFIXES
-}
+            let origin = [gimli::DW_AT_specification, gimli::DW_AT_abstract_origin]
+                .into_iter()
+                .find_map(|at| unit_ref(unit, current.attr_value(at).ok().flatten()));
+            let Some(origin) = origin else {
+                break;
+            };
+            current = unit
+                .entry(origin)
+                .map_err(|e| Error::Dwarf(format!("Failed to get origin entry: {}", e)))?;
+        }
 
-pub struct DwarfContext<'a> {
-    dwarf: &'a Dwarf<DwarfSlice<'a>>,
-    address_size: u8,
-    endian: gimli::RunTimeEndian,
-}
+        Ok(external && linkage_name.or(name).is_some_and(|sym| exports.contains(&sym)))
+    }
 
-impl<'a> DwarfContext<'a> {
-    pub fn new(loaded: &'a LoadedDwarf<'a>) -> Self {
-        Self { dwarf: &loaded.dwarf, address_size: loaded.address_size, endian: loaded.endian }
+    /// DW_AT_type targets in `unit` of the direct children of a DIE (members, base classes,
+    /// parameters).
+    fn child_type_refs(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        offset: UnitOffset,
+    ) -> Result<Vec<UnitOffset>> {
+        let attrs = self.child_type_attrs(unit, offset)?;
+        Ok(attrs.into_iter().filter_map(|attr| unit_ref(unit, Some(attr))).collect())
     }
 
-    /// Find all structs in the binary.
-    ///
-    /// - `filter`: Optional substring filter for struct names
-    /// - `include_go_runtime`: If false, Go runtime internal types are filtered out
-    pub fn find_structs(
+    /// DW_AT_type values of the direct children of a DIE, wherever they point.
+    fn child_type_attrs(
         &self,
-        filter: Option<&str>,
-        include_go_runtime: bool,
-    ) -> Result<Vec<StructLayout>> {
-        let mut structs = Vec::new();
-        let mut units = self.dwarf.units();
+        unit: &Unit<DwarfSlice<'a>>,
+        offset: UnitOffset,
+    ) -> Result<Vec<AttributeValue<DwarfSlice<'a>>>> {
+        let mut refs = Vec::new();
+        let mut tree = unit
+            .entries_tree(Some(offset))
+            .map_err(|e| Error::Dwarf(format!("Failed to create entries tree: {}", e)))?;
+        let root =
+            tree.root().map_err(|e| Error::Dwarf(format!("Failed to get tree root: {}", e)))?;
 
-        while let Some(header) =
-            units.next().map_err(|e| Error::Dwarf(format!("Failed to read unit header: {}", e)))?
+        let mut children = root.children();
+        while let Some(child) = children
+            .next()
+            .map_err(|e| Error::Dwarf(format!("Failed to iterate children: {}", e)))?
         {
-            let unit = self
-                .dwarf
-                .unit(header)
-                .map_err(|e| Error::Dwarf(format!("Failed to parse unit: {}", e)))?;
-
-            self.process_unit(&unit, filter, include_go_runtime, &mut structs)?;
+            let entry = child.entry();
+            if matches!(
+                entry.tag(),
+                gimli::DW_TAG_member | gimli::DW_TAG_inheritance | gimli::DW_TAG_formal_parameter
+            ) {
+                refs.extend(entry.attr_value(gimli::DW_AT_type).ok().flatten());
+            }
         }
 
-        // DWARF can contain duplicate identical type entries (e.g., across units or due to
-        // language/compiler quirks). Deduplicate exact duplicates to avoid double-counting in
-        // `check` and unstable matching in `diff`.
-        // Use enumerated index as tiebreaker for stable deduplication (Rust's sort_by is unstable).
-        let mut with_fp: Vec<(StructFingerprint, usize, StructLayout)> =
-            structs.into_iter().enumerate().map(|(i, s)| (struct_fingerprint(&s), i, s)).collect();
-        with_fp.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
-        with_fp.dedup_by(|a, b| a.0 == b.0);
-
-        Ok(with_fp.into_iter().map(|(_, _, s)| s).collect())
+        Ok(refs)
     }
 
+    /// Parse the structs of `unit` into `structs`, adding attribute forms the parser skipped
+    /// to `unsupported_forms`. Returns `Ok(false)` if `deadline` passed first.
     fn process_unit(
         &self,
         unit: &Unit<DwarfSlice<'a>>,
         filter: Option<&str>,
         include_go_runtime: bool,
+        deadline: Option<Instant>,
         structs: &mut Vec<StructLayout>,
-    ) -> Result<()> {
-        let mut type_resolver = TypeResolver::new(self.dwarf, unit, self.address_size);
+        unsupported_forms: &mut BTreeMap<String, usize>,
+    ) -> Result<bool> {
+        let mut type_resolver = TypeResolver::new(self.dwarf, unit, unit.header.address_size());
+        let completed = self.process_unit_entries(
+            unit,
+            filter,
+            include_go_runtime,
+            deadline,
+            structs,
+            &mut type_resolver,
+        );
+        for (form, count) in type_resolver.take_unsupported_forms() {
+            *unsupported_forms.entry(form).or_default() += count;
+        }
+        completed
+    }
+
+    fn process_unit_entries(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        filter: Option<&str>,
+        include_go_runtime: bool,
+        deadline: Option<Instant>,
+        structs: &mut Vec<StructLayout>,
+        type_resolver: &mut TypeResolver<'a, '_>,
+    ) -> Result<bool> {
         let mut entries = unit.entries();
+        // Enclosing namespaces, innermost last, with the depth of their DIE.
+        let mut namespaces: Vec<(isize, String)> = Vec::new();
+        let mut depth: isize = 0;
 
-        while let Some((_, entry)) =
+        while let Some((delta, entry)) =
             entries.next_dfs().map_err(|e| Error::Dwarf(format!("Failed to read DIE: {}", e)))?
         {
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return Ok(false);
+            }
+            depth += delta;
+            while namespaces.last().is_some_and(|(d, _)| *d >= depth) {
+                namespaces.pop();
+            }
+            if entry.tag() == gimli::DW_TAG_namespace {
+                let name = self.get_die_name(unit, entry)?;
+                namespaces
+                    .push((depth, name.unwrap_or_else(|| "(anonymous namespace)".to_string())));
+                continue;
+            }
             if !matches!(entry.tag(), gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type) {
                 continue;
             }
 
-            if let Some(layout) = self.process_struct_entry(
-                unit,
-                entry,
-                filter,
-                include_go_runtime,
-                &mut type_resolver,
-            )? {
+            if let Some(mut layout) =
+                self.process_struct_entry(unit, entry, filter, include_go_runtime, type_resolver)?
+            {
+                if !namespaces.is_empty() {
+                    let path: Vec<&str> = namespaces.iter().map(|(_, n)| n.as_str()).collect();
+                    layout.namespace = Some(path.join("::"));
+                }
                 structs.push(layout);
             }
         }
 
-        Ok(())
+        Ok(true)
     }
 
     fn process_struct_entry(
@@ -218,19 +1267,123 @@ impl<'a> DwarfContext<'a> {
             return Ok(None);
         }
 
-        if filter.is_some_and(|f| !name.contains(f)) {
+        if filter.is_some_and(|f| !name.contains(f)) || !self.scope.contains(&name) {
             return Ok(None);
         }
 
-        let alignment = read_u64_from_attr(entry.attr_value(gimli::DW_AT_alignment).ok().flatten());
+        let alignment = read_alignment(entry);
 
         let mut layout = StructLayout::new(name, size, alignment);
         layout.source_location = self.get_source_location(unit, entry)?;
         layout.members = self.extract_members(unit, entry, type_resolver)?;
+        layout.static_members = self.extract_static_members(unit, entry, type_resolver)?;
+        layout.enum_layout = self.extract_enum_layout(unit, entry, type_resolver)?;
+
+        let variant_members =
+            layout.enum_layout.iter().flat_map(|e| &e.variants).flat_map(|v| &v.members);
+        if !self.scope.admits_members(layout.members.iter().chain(variant_members)) {
+            return Ok(None);
+        }
 
         Ok(Some(layout))
     }
 
+    /// Variants of a Rust enum, from the struct's DW_TAG_variant_part: the part's DW_AT_discr
+    /// names the discriminant member, and each DW_TAG_variant holds one member whose type is
+    /// the variant's payload struct. Returns `None` for ordinary structs.
+    fn extract_enum_layout(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        struct_entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+        type_resolver: &mut TypeResolver<'a, '_>,
+    ) -> Result<Option<EnumLayout>> {
+        let mut tree = unit
+            .entries_tree(Some(struct_entry.offset()))
+            .map_err(|e| Error::Dwarf(format!("Failed to create entries tree: {}", e)))?;
+        let root =
+            tree.root().map_err(|e| Error::Dwarf(format!("Failed to get tree root: {}", e)))?;
+
+        let mut children = root.children();
+        while let Some(child) = children
+            .next()
+            .map_err(|e| Error::Dwarf(format!("Failed to iterate children: {}", e)))?
+        {
+            if child.entry().tag() != gimli::DW_TAG_variant_part {
+                continue;
+            }
+            let discr = unit_ref(unit, child.entry().attr_value(gimli::DW_AT_discr).ok().flatten());
+
+            let mut discriminant = None;
+            let mut variants = Vec::new();
+            let mut parts = child.children();
+            while let Some(part) = parts
+                .next()
+                .map_err(|e| Error::Dwarf(format!("Failed to iterate variants: {}", e)))?
+            {
+                let entry = part.entry().clone();
+                match entry.tag() {
+                    gimli::DW_TAG_member if discr.is_none_or(|d| d == entry.offset()) => {
+                        discriminant = self.process_member(unit, &entry, type_resolver)?;
+                        if let Some(d) = discriminant.as_mut()
+                            && d.name == "<anonymous>"
+                        {
+                            d.name = "<discriminant>".to_string();
+                        }
+                    }
+                    gimli::DW_TAG_variant => {
+                        let value = read_discr_value(entry.attr_value(gimli::DW_AT_discr_value));
+                        let mut fields = part.children();
+                        while let Some(field) = fields.next().map_err(|e| {
+                            Error::Dwarf(format!("Failed to iterate variant: {}", e))
+                        })? {
+                            if field.entry().tag() == gimli::DW_TAG_member {
+                                variants.push(self.process_variant(
+                                    unit,
+                                    field.entry(),
+                                    value,
+                                    type_resolver,
+                                )?);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            return Ok(Some(EnumLayout::new(discriminant, variants)));
+        }
+
+        Ok(None)
+    }
+
+    /// One enum variant from the member inside its DW_TAG_variant. Payload field offsets are
+    /// made relative to the enum.
+    fn process_variant(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+        discriminant_value: Option<i128>,
+        type_resolver: &mut TypeResolver<'a, '_>,
+    ) -> Result<VariantLayout> {
+        let name = self.get_die_name(unit, entry)?.unwrap_or_else(|| "<anonymous>".to_string());
+        let base = self.get_member_offset(unit, entry, type_resolver)?.unwrap_or(0);
+
+        let mut members = Vec::new();
+        if let Some(offset) = type_ref(unit, entry) {
+            let payload = unit
+                .entry(offset)
+                .map_err(|e| Error::Dwarf(format!("Failed to get variant entry: {}", e)))?;
+            if matches!(payload.tag(), gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type) {
+                members = self.extract_members(unit, &payload, type_resolver)?;
+            }
+        }
+        for member in &mut members {
+            member.offset = member.offset.map(|o| o.saturating_add(base));
+        }
+        let payload_size = members.iter().filter_map(|m| m.size).sum();
+
+        Ok(VariantLayout { name, discriminant_value, payload_size, members })
+    }
+
     fn extract_members(
         &self,
         unit: &Unit<DwarfSlice<'a>>,
@@ -245,6 +1398,9 @@ impl<'a> DwarfContext<'a> {
         let root =
             tree.root().map_err(|e| Error::Dwarf(format!("Failed to get tree root: {}", e)))?;
 
+        // GCC points DW_AT_containing_type of a dynamic class at the class holding its vptr.
+        let mut dynamic =
+            struct_entry.attr_value(gimli::DW_AT_containing_type).ok().flatten().is_some();
         let mut children = root.children();
         while let Some(child) = children
             .next()
@@ -252,9 +1408,13 @@ impl<'a> DwarfContext<'a> {
         {
             let entry = child.entry();
             match entry.tag() {
+                gimli::DW_TAG_member if is_static_member(entry) => {}
                 gimli::DW_TAG_member => {
                     if let Some(member) = self.process_member(unit, entry, type_resolver)? {
-                        members.push(member);
+                        match self.anonymous_aggregate(unit, entry, &member, type_resolver)? {
+                            Some(fields) => members.extend(fields),
+                            None => members.push(member),
+                        }
                     }
                 }
                 gimli::DW_TAG_inheritance => {
@@ -262,22 +1422,111 @@ impl<'a> DwarfContext<'a> {
                         members.push(member);
                     }
                 }
+                gimli::DW_TAG_subprogram => dynamic |= is_virtual(entry),
                 _ => {}
             }
         }
 
+        let size =
+            read_u64_from_attr(struct_entry.attr_value(gimli::DW_AT_byte_size).ok().flatten());
+        annotate_cxx_layout(&mut members, size, dynamic, u64::from(unit.header.address_size()));
         members.sort_by_key(|m| m.offset.unwrap_or(u64::MAX));
         Ok(members)
     }
 
-    /// Resolve type information from a DW_AT_type attribute.
-    /// Returns (type_name, size, is_atomic) or a default for unknown types.
+    /// The fields of a member whose type is an unnamed struct or union, placed in the parent:
+    /// `u.field` for `union { .. } u;`, plain `field` for a C11 anonymous member. Padding and
+    /// false sharing then see the real fields rather than one opaque blob. A typedef'd type is
+    /// named and stays whole.
+    fn anonymous_aggregate(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+        member: &MemberLayout,
+        type_resolver: &mut TypeResolver<'a, '_>,
+    ) -> Result<Option<Vec<MemberLayout>>> {
+        let (Some(base), Some(offset)) = (member.offset, type_ref(unit, entry)) else {
+            return Ok(None);
+        };
+        let aggregate = unit
+            .entry(offset)
+            .map_err(|e| Error::Dwarf(format!("Failed to get member type entry: {}", e)))?;
+        if !matches!(aggregate.tag(), gimli::DW_TAG_structure_type | gimli::DW_TAG_union_type)
+            || self.get_die_name(unit, &aggregate)?.is_some()
+        {
+            return Ok(None);
+        }
+
+        let mut fields = self.extract_members(unit, &aggregate, type_resolver)?;
+        if fields.is_empty() {
+            return Ok(None);
+        }
+        let named = entry.attr_value(gimli::DW_AT_name).ok().flatten().is_some();
+        let key = if named { member.name.clone() } else { format!("<anonymous>@{}", base) };
+        // GCC gives union members no DW_AT_data_member_location: they all start at zero.
+        let union = aggregate.tag() == gimli::DW_TAG_union_type;
+        for field in &mut fields {
+            if union && field.offset.is_none() {
+                field.offset = Some(0);
+            }
+            field.offset = field.offset.map(|o| o.saturating_add(base));
+            if named {
+                field.name = format!("{}.{}", member.name, field.name);
+            }
+            field.aggregate = Some(key.clone());
+            field.aggregate_extent =
+                member.size.map(|size| AggregateExtent { size, alignment: member.alignment });
+        }
+        Ok(Some(fields))
+    }
+
+    /// Static data members: DW_TAG_member entries marked static (DWARF 4) and DW_TAG_variable
+    /// children (DWARF 5).
+    fn extract_static_members(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        struct_entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+        type_resolver: &mut TypeResolver<'a, '_>,
+    ) -> Result<Vec<StaticMember>> {
+        let mut statics = Vec::new();
+        let mut tree = unit
+            .entries_tree(Some(struct_entry.offset()))
+            .map_err(|e| Error::Dwarf(format!("Failed to create entries tree: {}", e)))?;
+
+        let root =
+            tree.root().map_err(|e| Error::Dwarf(format!("Failed to get tree root: {}", e)))?;
+
+        let mut children = root.children();
+        while let Some(child) = children
+            .next()
+            .map_err(|e| Error::Dwarf(format!("Failed to iterate children: {}", e)))?
+        {
+            let entry = child.entry();
+            let is_static = match entry.tag() {
+                gimli::DW_TAG_member => is_static_member(entry),
+                gimli::DW_TAG_variable => true,
+                _ => false,
+            };
+            if !is_static {
+                continue;
+            }
+            let name = self.get_die_name(unit, entry)?.unwrap_or_else(|| "<anonymous>".to_string());
+            let TypeInfo { name: type_name, size, .. } =
+                self.resolve_type_attr(unit, entry, type_resolver)?;
+            let source_location = self.get_source_location(unit, entry)?;
+            statics.push(StaticMember { name, type_name, size, source_location });
+        }
+
+        Ok(statics)
+    }
+
+    /// Resolve type information from a DW_AT_type attribute, or a default for unknown types.
     fn resolve_type_attr(
         &self,
         unit: &Unit<DwarfSlice<'a>>,
         entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
         type_resolver: &mut TypeResolver<'a, '_>,
-    ) -> Result<(String, Option<u64>, bool)> {
+    ) -> Result<TypeInfo> {
         match entry.attr_value(gimli::DW_AT_type) {
             Ok(Some(AttributeValue::UnitRef(type_offset))) => {
                 type_resolver.resolve_type(type_offset)
@@ -289,10 +1538,18 @@ impl<'a> DwarfContext<'a> {
                 {
                     type_resolver.resolve_type(unit_offset)
                 } else {
-                    Ok(("unknown".to_string(), None, false))
+                    type_resolver.note_unsupported(
+                        gimli::DW_AT_type,
+                        &AttributeValue::DebugInfoRef(debug_info_offset),
+                    );
+                    Ok(TypeInfo::unknown())
                 }
             }
-            _ => Ok(("unknown".to_string(), None, false)),
+            Ok(Some(value)) => {
+                type_resolver.note_unsupported(gimli::DW_AT_type, &value);
+                Ok(TypeInfo::unknown())
+            }
+            _ => Ok(TypeInfo::unknown()),
         }
     }
 
@@ -302,11 +1559,68 @@ impl<'a> DwarfContext<'a> {
         entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
         type_resolver: &mut TypeResolver<'a, '_>,
     ) -> Result<Option<MemberLayout>> {
-        let offset = self.get_member_offset(unit, entry)?;
-        let (type_name, size, is_atomic) = self.resolve_type_attr(unit, entry, type_resolver)?;
+        let TypeInfo { name: type_name, size, is_atomic, alignment, explicit_alignment, tag } =
+            self.resolve_type_attr(unit, entry, type_resolver)?;
+
+        // A virtual base's DW_AT_data_member_location reads the offset out of the vtable at
+        // run time; `annotate_cxx_layout` infers it where the ABI pins it down.
+        let (name, offset, role) = if is_virtual(entry) {
+            (format!("<virtual base: {}>", type_name), None, Some(MemberRole::VirtualBase))
+        } else {
+            let offset = self.get_member_offset(unit, entry, type_resolver)?;
+            (format!("<base: {}>", type_name), offset, None)
+        };
+        let mut member = MemberLayout::new(name, type_name, offset, size).with_atomic(is_atomic);
+        member.alignment = alignment;
+        member.explicit_alignment = explicit_alignment;
+        member.type_tag = tag;
+        member.role = role;
+        member.base_layout = self.base_layout(unit, entry, type_resolver)?.map(Box::new);
+        Ok(Some(member))
+    }
+
+    /// Build the layout of the class an inheritance entry refers to, so analysis can
+    /// attribute the base subobject's padding. Follows typedefs and cv-qualifiers.
+    fn base_layout(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+        type_resolver: &mut TypeResolver<'a, '_>,
+    ) -> Result<Option<StructLayout>> {
+        let mut next = type_ref(unit, entry);
+
+        for _ in 0..8 {
+            let Some(offset) = next else {
+                return Ok(None);
+            };
+            let base = unit
+                .entry(offset)
+                .map_err(|e| Error::Dwarf(format!("Failed to get base entry: {}", e)))?;
+
+            match base.tag() {
+                gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type => {
+                    let Some(size) =
+                        read_u64_from_attr(base.attr_value(gimli::DW_AT_byte_size).ok().flatten())
+                    else {
+                        return Ok(None);
+                    };
+                    let name = self
+                        .get_die_name(unit, &base)?
+                        .unwrap_or_else(|| "<anonymous>".to_string());
+                    let alignment = read_alignment(&base);
+
+                    let mut layout = StructLayout::new(name, size, alignment);
+                    layout.members = self.extract_members(unit, &base, type_resolver)?;
+                    return Ok(Some(layout));
+                }
+                gimli::DW_TAG_typedef | gimli::DW_TAG_const_type | gimli::DW_TAG_volatile_type => {
+                    next = type_ref(unit, &base)
+                }
+                _ => return Ok(None),
+            }
+        }
 
-        let name = format!("<base: {}>", type_name);
-        Ok(Some(MemberLayout::new(name, type_name, offset, size).with_atomic(is_atomic)))
+        Ok(None)
     }
 
     fn process_member(
@@ -316,11 +1630,29 @@ impl<'a> DwarfContext<'a> {
         type_resolver: &mut TypeResolver<'a, '_>,
     ) -> Result<Option<MemberLayout>> {
         let name = self.get_die_name(unit, entry)?.unwrap_or_else(|| "<anonymous>".to_string());
-        let (type_name, size, is_atomic) = self.resolve_type_attr(unit, entry, type_resolver)?;
+        let TypeInfo { name: type_name, size, is_atomic, alignment, explicit_alignment, tag } =
+            self.resolve_type_attr(unit, entry, type_resolver)?;
 
-        let offset = self.get_member_offset(unit, entry)?;
+        let offset = self
+            .get_member_offset(unit, entry, type_resolver)?
+            .or_else(|| whole_byte_data_bit_offset(entry));
 
         let mut member = MemberLayout::new(name, type_name, offset, size).with_atomic(is_atomic);
+        // `alignas` on the member itself beats its type's alignment.
+        let own_alignment = read_alignment(entry);
+        member.alignment = own_alignment.or(alignment);
+        member.explicit_alignment = own_alignment.is_some() || explicit_alignment;
+        member.type_tag = tag;
+        member.source_location = self.get_source_location(unit, entry)?;
+        // GCC names the vtable pointer `_vptr.Class`, Clang `_vptr$Class`.
+        if member.name.starts_with("_vptr")
+            && matches!(
+                entry.attr_value(gimli::DW_AT_artificial),
+                Ok(Some(AttributeValue::Flag(true)))
+            )
+        {
+            member.role = Some(MemberRole::VtablePointer);
+        }
 
         let bit_size = read_u64_from_attr(entry.attr_value(gimli::DW_AT_bit_size).ok().flatten());
         let dwarf5_data_bit_offset =
@@ -382,6 +1714,7 @@ impl<'a> DwarfContext<'a> {
         &self,
         unit: &Unit<DwarfSlice<'a>>,
         entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+        type_resolver: &TypeResolver<'a, '_>,
     ) -> Result<Option<u64>> {
         match entry.attr_value(gimli::DW_AT_data_member_location) {
             Ok(Some(AttributeValue::Udata(offset))) => Ok(Some(offset)),
@@ -399,7 +1732,11 @@ impl<'a> DwarfContext<'a> {
                 evaluate_member_offset(expr, unit.encoding())
             }
             Ok(None) => Ok(None), // Missing offset - don't assume 0 (bitfields, packed structs)
-            _ => Ok(None),
+            Ok(Some(value)) => {
+                type_resolver.note_unsupported(gimli::DW_AT_data_member_location, &value);
+                Ok(None)
+            }
+            Err(_) => Ok(None),
         }
     }
 
@@ -421,6 +1758,33 @@ impl<'a> DwarfContext<'a> {
         }
     }
 
+    /// The unit's DW_AT_producer, e.g. `GNU C17 13.2.0 -g -O2`.
+    fn producer(&self, unit: &Unit<DwarfSlice<'a>>) -> Option<String> {
+        let mut entries = unit.entries();
+        let (_, root) = entries.next_dfs().ok()??;
+        let attr = root.attr_value(gimli::DW_AT_producer).ok()??;
+        let producer = self.dwarf.attr_string(unit, attr).ok()?;
+        Some(producer.to_string_lossy().into_owned())
+    }
+
+    fn get_linkage_name(
+        &self,
+        unit: &Unit<DwarfSlice<'a>>,
+        entry: &DebuggingInformationEntry<DwarfSlice<'a>>,
+    ) -> Result<Option<String>> {
+        let attr = [gimli::DW_AT_linkage_name, gimli::DW_AT_MIPS_linkage_name]
+            .into_iter()
+            .find_map(|at| entry.attr_value(at).ok().flatten());
+        let Some(attr) = attr else {
+            return Ok(None);
+        };
+        let name = self
+            .dwarf
+            .attr_string(unit, attr)
+            .map_err(|e| Error::Dwarf(format!("Failed to read linkage name: {}", e)))?;
+        Ok(Some(name.to_string_lossy().into_owned()))
+    }
+
     fn get_source_location(
         &self,
         unit: &Unit<DwarfSlice<'a>>,
@@ -476,51 +1840,194 @@ impl<'a> DwarfContext<'a> {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct StructFingerprint {
-    name: String,
-    size: u64,
-    alignment: Option<u64>,
-    source: Option<(String, u64)>,
-    members: Vec<MemberFingerprint>,
+/// Fill in the C++ layout the debug info leaves implicit, following the Itanium ABI:
+/// - an empty base sharing its byte with another member was folded away by the empty base
+///   optimization, so it takes no space;
+/// - a class's only virtual base sits after the non-virtual part, aligned, when the class has
+///   no other virtual bases (nested ones included) to order it against;
+/// - a dynamic class with nothing at offset 0 keeps its vtable pointer there.
+fn annotate_cxx_layout(
+    members: &mut Vec<MemberLayout>,
+    struct_size: Option<u64>,
+    dynamic: bool,
+    pointer_size: u64,
+) {
+    let occupied: Vec<(u64, u64)> = members
+        .iter()
+        .filter(|m| !is_empty_base(m))
+        .filter_map(|m| Some((m.offset?, m.end_offset()?)))
+        .collect();
+    for member in members.iter_mut() {
+        if is_empty_base(member)
+            && let Some(offset) = member.offset
+            && occupied.iter().any(|&(start, end)| start <= offset && offset < end)
+        {
+            member.size = Some(0);
+            member.role = Some(MemberRole::EmptyBase);
+        }
+    }
+
+    let is_virtual_base = |m: &MemberLayout| m.role == Some(MemberRole::VirtualBase);
+    let virtual_bases: Vec<usize> =
+        members.iter().enumerate().filter(|(_, m)| is_virtual_base(m)).map(|(i, _)| i).collect();
+    let nested = members
+        .iter()
+        .filter_map(|m| m.base_layout.as_deref())
+        .any(|b| b.members.iter().any(is_virtual_base));
+    if let [index] = virtual_bases[..]
+        && !nested
+        && let Some(struct_size) = struct_size
+        && let Some(size) = members[index].size.filter(|_| !is_empty_base(&members[index]))
+    {
+        let others = members.iter().enumerate().filter(|&(i, _)| i != index);
+        let ends: Option<Vec<u64>> = others.clone().map(|(_, m)| m.end_offset()).collect();
+        let non_virtual_align = others.filter_map(|(_, m)| m.alignment).max().unwrap_or(1);
+        let non_virtual_size =
+            ends.map(|e| e.into_iter().max().unwrap_or(0).next_multiple_of(non_virtual_align));
+        let align = members[index].alignment.unwrap_or(1);
+        if let Some(offset) = non_virtual_size.map(|n| n.next_multiple_of(align))
+            && offset.checked_add(size).is_some_and(|end| end <= struct_size)
+        {
+            members[index].offset = Some(offset);
+        }
+    }
+
+    let holds_offset_zero = members.iter().any(|m| m.offset == Some(0) && m.size != Some(0));
+    let dynamic = dynamic || !virtual_bases.is_empty();
+    if dynamic && !holds_offset_zero && struct_size.is_some_and(|s| s >= pointer_size) {
+        let mut vptr = MemberLayout::new(
+            "<vptr>".to_string(),
+            "*vtable".to_string(),
+            Some(0),
+            Some(pointer_size),
+        );
+        vptr.alignment = Some(pointer_size);
+        vptr.role = Some(MemberRole::VtablePointer);
+        members.insert(0, vptr);
+    }
+}
+
+/// A non-virtual base class with no data of its own.
+fn is_empty_base(member: &MemberLayout) -> bool {
+    member.role != Some(MemberRole::VirtualBase)
+        && member
+            .base_layout
+            .as_deref()
+            .is_some_and(|b| b.members.iter().all(|m| m.size == Some(0)))
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct MemberFingerprint {
-    name: String,
-    type_name: String,
-    offset: Option<u64>,
-    size: Option<u64>,
-    bit_offset: Option<u64>,
-    bit_size: Option<u64>,
-    is_atomic: bool,
+/// Whether a DW_TAG_inheritance or DW_TAG_subprogram carries a non-none DW_AT_virtuality.
+fn is_virtual(entry: &DebuggingInformationEntry<DwarfSlice<'_>>) -> bool {
+    matches!(
+        entry.attr_value(gimli::DW_AT_virtuality),
+        Ok(Some(AttributeValue::Virtuality(v))) if v != gimli::DW_VIRTUALITY_none
+    )
 }
 
-fn struct_fingerprint(s: &StructLayout) -> StructFingerprint {
-    StructFingerprint {
-        name: s.name.clone(),
-        size: s.size,
-        alignment: s.alignment,
-        source: s.source_location.as_ref().map(|l| (l.file.clone(), l.line)),
-        members: s
-            .members
-            .iter()
-            .map(|m| MemberFingerprint {
-                name: m.name.clone(),
-                type_name: m.type_name.clone(),
-                offset: m.offset,
-                size: m.size,
-                bit_offset: m.bit_offset,
-                bit_size: m.bit_size,
-                is_atomic: m.is_atomic,
-            })
-            .collect(),
+/// Who refers to one struct, for [`DwarfContext::annotate_usage`].
+#[derive(Default)]
+struct Users {
+    functions: HashSet<String>,
+    types: HashSet<String>,
+    variables: HashSet<String>,
+}
+
+impl Users {
+    fn usage(&self) -> StructUsage {
+        StructUsage::new(self.functions.len(), self.types.len(), self.variables.len())
+    }
+}
+
+/// A function, type, or global variable referring to a struct, or a declaration whose
+/// contents are counted at the definition instead.
+#[derive(Clone)]
+enum Referrer {
+    Function(String),
+    Type(String),
+    Variable(String),
+    Ignored,
+}
+
+/// Credit one function with each member it touched.
+fn count_touched(layouts: &mut [StructLayout], touched: HashSet<(usize, usize)>) {
+    for (li, mi) in touched {
+        let member = &mut layouts[li].members[mi];
+        member.static_popularity = Some(member.static_popularity.unwrap_or(0) + 1);
+    }
+}
+
+/// Resolve a DW_AT_type-style reference to an offset within `unit`.
+fn type_ref(
+    unit: &Unit<DwarfSlice<'_>>,
+    entry: &DebuggingInformationEntry<DwarfSlice<'_>>,
+) -> Option<UnitOffset> {
+    unit_ref(unit, entry.attr_value(gimli::DW_AT_type).ok().flatten())
+}
+
+/// Whether a DW_TAG_member is a static data member, which DWARF 4 producers emit as an
+/// external declaration (with DW_AT_const_value for `constexpr`) rather than a DW_TAG_variable.
+fn is_static_member(entry: &DebuggingInformationEntry<DwarfSlice<'_>>) -> bool {
+    let flag = |at| matches!(entry.attr_value(at), Ok(Some(AttributeValue::Flag(true))));
+    let has = |at| matches!(entry.attr_value(at), Ok(Some(_)));
+    flag(gimli::DW_AT_external)
+        || flag(gimli::DW_AT_declaration)
+        || (has(gimli::DW_AT_const_value)
+            && !has(gimli::DW_AT_data_member_location)
+            && !has(gimli::DW_AT_data_bit_offset))
+}
+
+/// DW_AT_discr_value, which is signed when the discriminant type is.
+fn read_discr_value(attr: gimli::Result<Option<AttributeValue<DwarfSlice<'_>>>>) -> Option<i128> {
+    match attr.ok().flatten()? {
+        AttributeValue::Sdata(v) => Some(i128::from(v)),
+        other => read_u64_from_attr(Some(other)).map(i128::from),
+    }
+}
+
+fn unit_ref(
+    unit: &Unit<DwarfSlice<'_>>,
+    attr: Option<AttributeValue<DwarfSlice<'_>>>,
+) -> Option<UnitOffset> {
+    match attr? {
+        AttributeValue::UnitRef(offset) => Some(offset),
+        AttributeValue::DebugInfoRef(offset) => debug_info_ref_to_unit_offset(offset, &unit.header),
+        _ => None,
+    }
+}
+
+/// Where a type reference from `unit`, the unit at `index` of `headers`, points: the index
+/// of the unit holding the target and the target's offset in it. References into other units
+/// are resolved in the main file only; a `split` unit (DWARF fission) stays within itself.
+fn resolve_type_ref(
+    headers: &[UnitHeader<DwarfSlice<'_>>],
+    index: usize,
+    split: bool,
+    unit: &Unit<DwarfSlice<'_>>,
+    attr: AttributeValue<DwarfSlice<'_>>,
+) -> Option<(usize, UnitOffset)> {
+    let AttributeValue::DebugInfoRef(offset) = attr else {
+        return unit_ref(unit, Some(attr)).map(|o| (index, o));
+    };
+    if let Some(within) = debug_info_ref_to_unit_offset(offset, &unit.header)
+        && within.0 < unit.header.length_including_self()
+    {
+        return Some((index, within));
+    }
+    if split {
+        return None;
     }
+    // Headers are in section order, so the target's unit is the last one starting at or
+    // before it.
+    let target = headers
+        .partition_point(|h| h.offset().as_debug_info_offset().is_some_and(|s| s.0 <= offset.0))
+        .checked_sub(1)?;
+    debug_info_ref_to_unit_offset(offset, &headers[target]).map(|o| (target, o))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::write_elf;
 
     #[test]
     fn test_is_go_internal_type() {
@@ -649,4 +2156,174 @@ mod tests {
         // Type with middle dot but also matching prefix
         assert!(is_go_internal_type("runtime\u{00B7}internal"));
     }
+
+    #[test]
+    fn annotate_cxx_layout_synthesizes_vptr_and_folds_empty_bases() {
+        let mut empty =
+            MemberLayout::new("<base: Tag>".to_string(), "Tag".to_string(), Some(8), Some(1));
+        empty.base_layout = Some(Box::new(StructLayout::new("Tag".to_string(), 1, Some(1))));
+        let id = MemberLayout::new("id".to_string(), "u32".to_string(), Some(8), Some(4));
+        let mut members = vec![empty, id];
+
+        annotate_cxx_layout(&mut members, Some(16), true, 8);
+
+        assert_eq!(members[0].name, "<vptr>");
+        assert_eq!(members[0].role, Some(MemberRole::VtablePointer));
+        assert_eq!((members[1].size, members[1].role), (Some(0), Some(MemberRole::EmptyBase)));
+
+        // Nothing is added to a class that is not dynamic.
+        let mut plain =
+            vec![MemberLayout::new("x".to_string(), "u32".to_string(), Some(4), Some(4))];
+        annotate_cxx_layout(&mut plain, Some(8), false, 8);
+        assert_eq!(plain.len(), 1);
+    }
+
+    #[test]
+    fn members_placed_only_by_data_bit_offset() {
+        use gimli::write::{AttributeValue, DwarfUnit, UnitEntryId};
+
+        // The encoding some Clang versions use: every member, bitfield or not, is placed by
+        // DW_AT_data_bit_offset alone.
+        let encoding =
+            gimli::Encoding { format: gimli::Format::Dwarf32, version: 5, address_size: 8 };
+        let mut dwarf = DwarfUnit::new(encoding);
+        let root = dwarf.unit.root();
+        let mut add = |parent: UnitEntryId, tag, attrs: Vec<(gimli::DwAt, AttributeValue)>| {
+            let id = dwarf.unit.add(parent, tag);
+            for (name, value) in attrs {
+                dwarf.unit.get_mut(id).set(name, value);
+            }
+            id
+        };
+        let base = |name: &str, size| {
+            vec![
+                (gimli::DW_AT_name, AttributeValue::String(name.as_bytes().to_vec())),
+                (gimli::DW_AT_byte_size, AttributeValue::Udata(size)),
+                (gimli::DW_AT_encoding, AttributeValue::Encoding(gimli::DW_ATE_signed)),
+            ]
+        };
+        let char_type = add(root, gimli::DW_TAG_base_type, base("char", 1));
+        let long_type = add(root, gimli::DW_TAG_base_type, base("long", 8));
+        let int_type = add(root, gimli::DW_TAG_base_type, base("int", 4));
+        let record = add(
+            root,
+            gimli::DW_TAG_structure_type,
+            vec![
+                (gimli::DW_AT_name, AttributeValue::String(b"BitPlaced".to_vec())),
+                (gimli::DW_AT_byte_size, AttributeValue::Udata(24)),
+            ],
+        );
+        let member = |name: &str, ty, bits: Option<u64>, offset_bits| {
+            let mut attrs = vec![
+                (gimli::DW_AT_name, AttributeValue::String(name.as_bytes().to_vec())),
+                (gimli::DW_AT_type, AttributeValue::UnitRef(ty)),
+                (gimli::DW_AT_data_bit_offset, AttributeValue::Udata(offset_bits)),
+            ];
+            attrs.extend(bits.map(|b| (gimli::DW_AT_bit_size, AttributeValue::Udata(b))));
+            attrs
+        };
+        add(record, gimli::DW_TAG_member, member("tag", char_type, None, 0));
+        add(record, gimli::DW_TAG_member, member("value", long_type, None, 64));
+        add(record, gimli::DW_TAG_member, member("flag", int_type, Some(3), 130));
+
+        let file = write_elf(|sections| dwarf.write(sections));
+        let binary = crate::loader::BinaryData::load(file.path()).unwrap();
+        let loaded = binary.load_dwarf().unwrap();
+        let mut layouts = DwarfContext::new(&loaded).find_structs(None, false).unwrap();
+        let layout = &mut layouts[0];
+
+        let placed: Vec<(Option<u64>, Option<u64>)> =
+            layout.members.iter().map(|m| (m.offset, m.bit_offset)).collect();
+        assert_eq!(placed, vec![(Some(0), None), (Some(8), None), (Some(16), Some(2))]);
+        crate::analysis::analyze_layout(layout, 64);
+        assert!(!layout.metrics.partial);
+        assert_eq!(layout.metrics.padding_bytes, 7 + 4);
+    }
+
+    #[test]
+    fn deduplicated_keeps_distinct_layouts_with_one_name() {
+        use gimli::write::{AttributeValue, DwarfUnit};
+
+        let encoding =
+            gimli::Encoding { format: gimli::Format::Dwarf32, version: 5, address_size: 8 };
+        let mut dwarf = DwarfUnit::new(encoding);
+        let root = dwarf.unit.root();
+        let int_type = dwarf.unit.add(root, gimli::DW_TAG_base_type);
+        let entry = dwarf.unit.get_mut(int_type);
+        entry.set(gimli::DW_AT_name, AttributeValue::String(b"int".to_vec()));
+        entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(4));
+        entry.set(gimli::DW_AT_encoding, AttributeValue::Encoding(gimli::DW_ATE_signed));
+        // Two identical `Twin`s and a third that differs only in its member's name.
+        for member_name in ["a", "a", "b"] {
+            let record = dwarf.unit.add(root, gimli::DW_TAG_structure_type);
+            let entry = dwarf.unit.get_mut(record);
+            entry.set(gimli::DW_AT_name, AttributeValue::String(b"Twin".to_vec()));
+            entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(4));
+            let member = dwarf.unit.add(record, gimli::DW_TAG_member);
+            let entry = dwarf.unit.get_mut(member);
+            entry.set(gimli::DW_AT_name, AttributeValue::String(member_name.as_bytes().to_vec()));
+            entry.set(gimli::DW_AT_type, AttributeValue::UnitRef(int_type));
+            entry.set(gimli::DW_AT_data_member_location, AttributeValue::Udata(0));
+        }
+
+        let file = write_elf(|sections| dwarf.write(sections));
+        let binary = crate::loader::BinaryData::load(file.path()).unwrap();
+        let loaded = binary.load_dwarf().unwrap();
+        let context = DwarfContext::new(&loaded);
+
+        let members: Vec<String> = context
+            .iter_structs(None, false)
+            .deduplicated()
+            .map(|layout| layout.unwrap().members[0].name.clone())
+            .collect();
+        assert_eq!(members, ["a", "b"]);
+        assert_eq!(context.find_structs(None, false).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn exported_structs_follow_references_across_units() {
+        use gimli::write::{AttributeValue, Dwarf, LineProgram, Reference, Unit, UnitId};
+
+        let encoding =
+            gimli::Encoding { format: gimli::Format::Dwarf32, version: 5, address_size: 8 };
+        let mut dwarf = Dwarf::new();
+        let add_struct = |dwarf: &mut Dwarf, size| {
+            let unit_id = dwarf.units.add(Unit::new(encoding, LineProgram::none()));
+            let unit = dwarf.units.get_mut(unit_id);
+            let record = unit.add(unit.root(), gimli::DW_TAG_structure_type);
+            let entry = unit.get_mut(record);
+            entry.set(gimli::DW_AT_name, AttributeValue::String(b"Shared".to_vec()));
+            entry.set(gimli::DW_AT_byte_size, AttributeValue::Udata(size));
+            (unit_id, record)
+        };
+        // The exported function's return type lives in another unit, as after LTO; a private
+        // struct of the same name and a different size lives in a third.
+        let (public_unit, public): (UnitId, _) = add_struct(&mut dwarf, 8);
+        add_struct(&mut dwarf, 4);
+        let api_unit = dwarf.units.add(Unit::new(encoding, LineProgram::none()));
+        let unit = dwarf.units.get_mut(api_unit);
+        let function = unit.add(unit.root(), gimli::DW_TAG_subprogram);
+        let entry = unit.get_mut(function);
+        entry.set(gimli::DW_AT_name, AttributeValue::String(b"api".to_vec()));
+        entry.set(gimli::DW_AT_external, AttributeValue::Flag(true));
+        entry.set(
+            gimli::DW_AT_type,
+            AttributeValue::DebugInfoRef(Reference::Entry(public_unit, public)),
+        );
+
+        let file = write_elf(|sections| dwarf.write(sections));
+        let binary = crate::loader::BinaryData::load(file.path()).unwrap();
+        let loaded = binary.load_dwarf().unwrap();
+        let context = DwarfContext::new(&loaded);
+        let exports = HashSet::from(["api".to_string()]);
+        let exported = context.exported_structs(&exports).unwrap();
+
+        let sizes: Vec<(u64, bool)> = context
+            .find_structs(None, false)
+            .unwrap()
+            .iter()
+            .map(|layout| (layout.size, exported.contains(layout)))
+            .collect();
+        assert_eq!(sizes, [(4, false), (8, true)]);
+    }
 }
@@ -80,6 +80,65 @@ pub fn try_simple_offset(
     Some(value)
 }
 
+/// Byte ranges of a struct-typed variable that a location description refers to: the target of
+/// each `DW_OP_plus_uconst` (one byte, the start of a member), and every `DW_OP_piece` whose
+/// value lives in a register or memory. Pieces that are optimized out or only hold a constant
+/// don't count. Malformed trailing ops end the scan.
+pub fn accessed_ranges(
+    expr: Expression<DwarfSlice<'_>>,
+    encoding: gimli::Encoding,
+) -> Vec<std::ops::Range<u64>> {
+    let mut ranges = Vec::new();
+    let mut ops = expr.operations(encoding);
+    let mut piece_start_bits: u64 = 0;
+    let mut piece_has_location = false;
+
+    while let Ok(Some(op)) = ops.next() {
+        match op {
+            Operation::PlusConstant { value } => {
+                ranges.push(value..value.saturating_add(1));
+                piece_has_location = true;
+            }
+            Operation::Piece { size_in_bits, bit_offset } => {
+                let start = bit_offset.unwrap_or(piece_start_bits);
+                let end = start.saturating_add(size_in_bits);
+                if piece_has_location && size_in_bits > 0 {
+                    ranges.push(start / 8..end.div_ceil(8));
+                }
+                piece_start_bits = end;
+                piece_has_location = false;
+            }
+            Operation::UnsignedConstant { .. }
+            | Operation::SignedConstant { .. }
+            | Operation::StackValue
+            | Operation::ImplicitValue { .. } => {}
+            _ => piece_has_location = true,
+        }
+    }
+
+    ranges
+}
+
+/// The fixed address a variable's location expression names: a lone `DW_OP_addr`, or a lone
+/// `DW_OP_addrx` resolved through `indexed`. Thread-local offsets, registers, and pieces are
+/// not static addresses.
+pub fn static_address(
+    expr: Expression<DwarfSlice<'_>>,
+    encoding: gimli::Encoding,
+    indexed: impl FnOnce(gimli::DebugAddrIndex<usize>) -> Option<u64>,
+) -> Option<u64> {
+    let mut ops = expr.operations(encoding);
+    let first = ops.next().ok()??;
+    if !matches!(ops.next(), Ok(None)) {
+        return None;
+    }
+    match first {
+        Operation::Address { address } => Some(address),
+        Operation::AddressIndex { index } => indexed(index),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,6 +188,39 @@ mod tests {
         assert_eq!(try_simple_offset(expr(&bytes), encoding(8)), None);
     }
 
+    #[test]
+    fn accessed_ranges_reads_plus_uconst_and_pieces() {
+        let mut bytes = vec![0x91, 0x70, 0x06, 0x23]; // DW_OP_fbreg -16; DW_OP_deref; plus_uconst
+        bytes.extend_from_slice(&uleb(24));
+        assert_eq!(accessed_ranges(expr(&bytes), encoding(8)), vec![24..25]);
+
+        // DW_OP_reg0; DW_OP_piece 8; DW_OP_piece 4 (optimized out); DW_OP_reg1; DW_OP_piece 4
+        let bytes = vec![0x50, 0x93, 0x08, 0x93, 0x04, 0x51, 0x93, 0x04];
+        assert_eq!(accessed_ranges(expr(&bytes), encoding(8)), vec![0..8, 12..16]);
+
+        // DW_OP_lit0; DW_OP_stack_value; DW_OP_piece 8: a constant, not an access
+        let bytes = vec![0x30, 0x9f, 0x93, 0x08];
+        assert!(accessed_ranges(expr(&bytes), encoding(8)).is_empty());
+    }
+
+    #[test]
+    fn static_address_needs_a_lone_addr() {
+        let mut bytes = vec![0x03]; // DW_OP_addr
+        bytes.extend_from_slice(&0x4018u64.to_le_bytes());
+        assert_eq!(static_address(expr(&bytes), encoding(8), |_| None), Some(0x4018));
+
+        // DW_OP_const8u 0; DW_OP_GNU_push_tls_address: a thread-local
+        let mut bytes = vec![0x0e];
+        bytes.extend_from_slice(&0u64.to_le_bytes());
+        bytes.push(0xe0);
+        assert_eq!(static_address(expr(&bytes), encoding(8), |_| Some(1)), None);
+
+        // DW_OP_addrx 2
+        let bytes = vec![0xa1, 0x02];
+        let found = static_address(expr(&bytes), encoding(8), |i| Some(0x1000 + i.0 as u64));
+        assert_eq!(found, Some(0x1002));
+    }
+
     #[test]
     fn evaluate_member_offset_returns_value() {
         let bytes = vec![0x23, 0x05]; // DW_OP_plus_uconst 5
@@ -1,9 +1,15 @@
 mod context;
 mod expr;
+mod scope;
+mod stats;
 mod types;
 
-pub use context::{DwarfContext, is_go_internal_type};
-pub use types::TypeResolver;
+pub use context::{DwarfContext, ExportedStructs, StructIter, is_go_internal_type};
+pub use scope::StructScope;
+pub(crate) use scope::compile_regex;
+pub use stats::{DwarfFeatures, ScanStats, UnitStats};
+pub(crate) use types::read_alignment;
+pub use types::{TypeInfo, TypeResolver};
 
 use crate::loader::DwarfSlice;
 use gimli::{AttributeValue, DebugInfoOffset, UnitHeader, UnitOffset};
@@ -25,6 +31,33 @@ pub(crate) fn read_u64_from_attr(attr: Option<AttributeValue<DwarfSlice<'_>>>) -
     }
 }
 
+/// Byte offset of a member that only has `DW_AT_data_bit_offset`, as some Clang versions emit
+/// even for members that are not bitfields. `None` for bitfields, which are placed from their
+/// storage unit instead, and for offsets that are not a whole number of bytes.
+pub(crate) fn whole_byte_data_bit_offset(
+    entry: &gimli::DebuggingInformationEntry<DwarfSlice<'_>>,
+) -> Option<u64> {
+    if entry.attr_value(gimli::DW_AT_bit_size).ok().flatten().is_some() {
+        return None;
+    }
+    let bits = read_u64_from_attr(entry.attr_value(gimli::DW_AT_data_bit_offset).ok().flatten())?;
+    (bits % 8 == 0).then_some(bits / 8)
+}
+
+/// The form of an attribute value the parser does not read, for [`UnitStats`] diagnostics.
+pub(crate) fn unsupported_form_label(value: &AttributeValue<DwarfSlice<'_>>) -> &'static str {
+    match value {
+        AttributeValue::DebugTypesRef(_) => "DW_FORM_ref_sig8",
+        AttributeValue::DebugInfoRef(_) => "DW_FORM_ref_addr (other unit)",
+        AttributeValue::DebugInfoRefSup(_) => "DW_FORM_ref_sup",
+        AttributeValue::LocationListsRef(_) => "DW_FORM_sec_offset (location list)",
+        AttributeValue::DebugLocListsIndex(_) => "DW_FORM_loclistx",
+        AttributeValue::Block(_) => "DW_FORM_block",
+        AttributeValue::Sdata(_) => "DW_FORM_sdata (negative)",
+        _ => "other",
+    }
+}
+
 /// Convert a DebugInfoRef (section offset) to a UnitOffset (unit-relative offset).
 /// Returns None if the reference is invalid (cross-unit or corrupted DWARF).
 /// Used by both types.rs and context.rs for consistent cross-unit reference handling.
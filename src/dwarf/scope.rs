@@ -0,0 +1,136 @@
+//! Name-based allowlist/denylist limiting which structs are analyzed.
+
+use crate::error::{Error, Result};
+use crate::types::MemberLayout;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use regex_automata::meta::Regex;
+
+/// Glob patterns over struct names. `*` also matches `::` and `.`, so `my_company::*` covers
+/// nested namespaces and `main.*` covers a Go package. Regular expressions can narrow it
+/// further, by struct name and by the members a struct must have.
+#[derive(Debug, Clone, Default)]
+pub struct StructScope {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    name_pattern: Option<Regex>,
+    member_pattern: Option<Regex>,
+}
+
+impl StructScope {
+    /// An empty `include` list admits every name; `exclude` always wins over `include`.
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        Ok(Self { include: compile(include)?, exclude: compile(exclude)?, ..Self::default() })
+    }
+
+    /// Also require struct names to match `pattern` (unanchored, so `^Conn` or `Conn$` as
+    /// needed).
+    pub fn with_name_regex(mut self, pattern: &str) -> Result<Self> {
+        self.name_pattern = Some(compile_regex(pattern)?);
+        Ok(self)
+    }
+
+    /// Also require a member whose name or type matches `pattern`, e.g. `Mutex` for every
+    /// struct holding a lock.
+    pub fn with_member_regex(mut self, pattern: &str) -> Result<Self> {
+        self.member_pattern = Some(compile_regex(pattern)?);
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none()
+            && self.exclude.is_none()
+            && self.name_pattern.is_none()
+            && self.member_pattern.is_none()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.include.as_ref().is_none_or(|set| set.is_match(name))
+            && !self.exclude.as_ref().is_some_and(|set| set.is_match(name))
+            && self.name_pattern.as_ref().is_none_or(|re| re.is_match(name))
+    }
+
+    /// Whether a struct with these members passes the member pattern, if any.
+    pub fn admits_members<'m>(&self, mut members: impl Iterator<Item = &'m MemberLayout>) -> bool {
+        let Some(re) = &self.member_pattern else { return true };
+        members.any(|m| re.is_match(m.name.as_str()) || re.is_match(m.type_name.as_str()))
+    }
+}
+
+pub(crate) fn compile_regex(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| {
+        let reason = e.syntax_error().map_or_else(|| e.to_string(), |se| se.to_string());
+        Error::InvalidRegex(format!("'{}': {}", pattern, reason))
+    })
+}
+
+fn compile(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(false)
+            .build()
+            .map_err(|e| Error::InvalidPattern(format!("'{}': {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    let set = builder.build().map_err(|e| Error::InvalidPattern(e.to_string()))?;
+    Ok(Some(set))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(include: &[&str], exclude: &[&str]) -> StructScope {
+        let owned = |p: &[&str]| p.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        StructScope::new(&owned(include), &owned(exclude)).unwrap()
+    }
+
+    #[test]
+    fn empty_scope_admits_everything() {
+        let s = scope(&[], &[]);
+        assert!(s.is_empty());
+        assert!(s.contains("anything"));
+    }
+
+    #[test]
+    fn include_and_exclude_patterns() {
+        let s = scope(&["my_company::*", "main.*"], &["*::testing::*"]);
+        assert!(s.contains("my_company::net::Socket"));
+        assert!(s.contains("main.Config"));
+        assert!(!s.contains("my_company::testing::Fake"));
+        assert!(!s.contains("std::string"));
+    }
+
+    #[test]
+    fn name_and_member_patterns() {
+        let s = scope(&["app::*"], &[])
+            .with_name_regex("Conn(ection)?$")
+            .unwrap()
+            .with_member_regex("Mutex")
+            .unwrap();
+        assert!(!s.is_empty());
+        assert!(s.contains("app::Connection"));
+        assert!(!s.contains("app::ConnectionPool"));
+        assert!(!s.contains("lib::Conn"));
+
+        let member = |name: &str, type_name: &str| {
+            MemberLayout::new(name.to_string(), type_name.to_string(), Some(0), Some(8))
+        };
+        let locked = [member("id", "u64"), member("state", "std::sync::Mutex<State>")];
+        assert!(s.admits_members(locked.iter()));
+        assert!(!s.admits_members([member("id", "u64")].iter()));
+        assert!(StructScope::default().admits_members(std::iter::empty()));
+
+        assert!(StructScope::default().with_member_regex("(").is_err());
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        let err = StructScope::new(&["[".to_string()], &[]).unwrap_err();
+        assert!(err.to_string().contains("'['"));
+    }
+}
@@ -0,0 +1,219 @@
+use crate::loader::LoadedDwarf;
+use crate::target::Target;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Per-unit timing collected by [`DwarfContext::find_structs_with_stats`].
+///
+/// [`DwarfContext::find_structs_with_stats`]: super::DwarfContext::find_structs_with_stats
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanStats {
+    /// Units in `.debug_info` order.
+    pub units: Vec<UnitStats>,
+    /// True if the scan stopped at the context's deadline; `units` lists those read before it.
+    #[serde(skip_serializing_if = "crate::output::skip_false")]
+    pub out_of_time: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnitStats {
+    /// Offset of the unit header in `.debug_info`.
+    pub offset: u64,
+    /// The unit's `DW_AT_name`, usually its primary source file.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub name: Option<String>,
+    #[serde(serialize_with = "serialize_millis", rename = "elapsed_ms")]
+    pub elapsed: Duration,
+    /// Structs the unit contributed before deduplication (0 if skipped).
+    pub structs: usize,
+    /// True if the unit ran past the unit timeout and its structs were dropped.
+    #[serde(skip_serializing_if = "crate::output::skip_false")]
+    pub skipped: bool,
+    /// DWARF version of the unit header.
+    pub version: u16,
+    /// The unit's `DW_AT_producer`: compiler, version, and (with GCC) its flags.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub producer: Option<String>,
+    /// True for a skeleton unit read from its split unit (DWARF fission).
+    #[serde(skip_serializing_if = "crate::output::skip_false")]
+    pub split: bool,
+    /// Attribute values in a form the parser does not read, keyed `DW_AT_x: form`. The
+    /// members or types holding them come out partial or unknown.
+    #[serde(skip_serializing_if = "crate::output::skip_empty_map")]
+    pub unsupported_forms: BTreeMap<String, usize>,
+}
+
+impl UnitStats {
+    /// Name for messages: the unit name, or its offset for unnamed units.
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} (0x{:x})", name, self.offset),
+            None => format!("unit at 0x{:x}", self.offset),
+        }
+    }
+}
+
+impl ScanStats {
+    pub fn total_elapsed(&self) -> Duration {
+        self.units.iter().map(|u| u.elapsed).sum()
+    }
+
+    pub fn skipped(&self) -> impl Iterator<Item = &UnitStats> {
+        self.units.iter().filter(|u| u.skipped)
+    }
+
+    /// Up to `n` units, slowest first.
+    pub fn slowest(&self, n: usize) -> Vec<&UnitStats> {
+        let mut units: Vec<&UnitStats> = self.units.iter().collect();
+        units.sort_by(|a, b| b.elapsed.cmp(&a.elapsed).then_with(|| a.offset.cmp(&b.offset)));
+        units.truncate(n);
+        units
+    }
+}
+
+/// DWARF features one binary's debug info uses, summed over its units, with what the loader
+/// found outside `.debug_info`. Explains partial results and which compiler flags avoid them.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DwarfFeatures {
+    /// The target the object file headers describe.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub target: Option<Target>,
+    /// Units per DWARF version.
+    pub versions: BTreeMap<u16, usize>,
+    /// Units per `DW_AT_producer`.
+    pub producers: BTreeMap<String, usize>,
+    /// Skeleton units read from `.dwo` files or a `.dwp` package.
+    pub split_units: usize,
+    /// `.dwo` files skeleton units name but the loader could not find; their structs are
+    /// missing.
+    pub missing_dwo: Vec<String>,
+    /// Sections stored compressed (`SHF_COMPRESSED` or `.zdebug_*`).
+    pub compressed_sections: Vec<String>,
+    /// Attribute values the parser skipped, keyed `DW_AT_x: form`.
+    pub unsupported_forms: BTreeMap<String, usize>,
+}
+
+impl DwarfFeatures {
+    pub fn new(loaded: &LoadedDwarf<'_>, stats: &ScanStats) -> Self {
+        Self {
+            target: Some(loaded.target.clone()),
+            missing_dwo: loaded.missing_dwo.clone(),
+            compressed_sections: loaded.compressed_sections.clone(),
+            ..Self::from_units(&stats.units)
+        }
+    }
+
+    fn from_units(units: &[UnitStats]) -> Self {
+        let mut features = Self::default();
+        for unit in units {
+            *features.versions.entry(unit.version).or_default() += 1;
+            if let Some(producer) = &unit.producer {
+                *features.producers.entry(producer.clone()).or_default() += 1;
+            }
+            features.split_units += usize::from(unit.split);
+            for (form, count) in &unit.unsupported_forms {
+                *features.unsupported_forms.entry(form.clone()).or_default() += count;
+            }
+        }
+        features
+    }
+
+    /// Compiler or build changes that would make the results complete.
+    pub fn hints(&self) -> Vec<String> {
+        let mut hints = Vec::new();
+        if !self.missing_dwo.is_empty() {
+            hints.push(format!(
+                "{} .dwo file(s) not found: keep them where they were built, package them with \
+                 `dwp` into <binary>.dwp, or build without -gsplit-dwarf",
+                self.missing_dwo.len()
+            ));
+        }
+        if self.unsupported_forms.keys().any(|form| form.ends_with("DW_FORM_ref_sig8")) {
+            hints.push(
+                "types in type units (.debug_types or DW_UT_type) are not followed: build \
+                 without -fdebug-types-section"
+                    .to_string(),
+            );
+        }
+        let reduced: usize = self
+            .producers
+            .iter()
+            .filter(|(producer, _)| {
+                producer.split_whitespace().any(|flag| {
+                    matches!(
+                        flag,
+                        "-g1" | "-gmlt" | "-gline-tables-only" | "-gline-directives-only"
+                    )
+                })
+            })
+            .map(|(_, count)| count)
+            .sum();
+        if reduced > 0 {
+            hints.push(format!(
+                "{} unit(s) built with line tables only (-g1 or -gline-tables-only) carry no \
+                 struct types: build them with -g",
+                reduced
+            ));
+        }
+        hints
+    }
+}
+
+fn serialize_millis<S: serde::Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_f64(d.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(offset: u64, ms: u64, skipped: bool) -> UnitStats {
+        UnitStats {
+            offset,
+            name: None,
+            elapsed: Duration::from_millis(ms),
+            structs: 0,
+            skipped,
+            version: 5,
+            producer: None,
+            split: false,
+            unsupported_forms: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn slowest_orders_by_elapsed() {
+        let stats = ScanStats {
+            units: vec![unit(0, 5, false), unit(16, 50, true), unit(32, 5, false)],
+            out_of_time: false,
+        };
+        let offsets: Vec<u64> = stats.slowest(2).iter().map(|u| u.offset).collect();
+        assert_eq!(offsets, vec![16, 0]);
+        assert_eq!(stats.total_elapsed(), Duration::from_millis(60));
+        assert_eq!(stats.skipped().count(), 1);
+        assert_eq!(stats.units[1].label(), "unit at 0x10");
+    }
+
+    #[test]
+    fn hints_name_the_flags_to_change() {
+        let mut reduced = unit(0, 1, false);
+        reduced.producer = Some("GNU C17 13.2.0 -g1 -O2".to_string());
+        let mut type_units = unit(16, 1, false);
+        type_units.version = 4;
+        type_units.unsupported_forms.insert("DW_AT_type: DW_FORM_ref_sig8".to_string(), 3);
+
+        let features = DwarfFeatures::from_units(&[reduced, type_units, unit(32, 1, false)]);
+        assert_eq!(features.versions, BTreeMap::from([(4, 1), (5, 2)]));
+        assert_eq!(features.unsupported_forms["DW_AT_type: DW_FORM_ref_sig8"], 3);
+        let hints = features.hints();
+        assert_eq!(hints.len(), 2, "{:?}", hints);
+        assert!(hints[0].contains("-fdebug-types-section"));
+        assert!(hints[1].starts_with("1 unit(s)"));
+
+        let clean = DwarfFeatures::from_units(&[unit(0, 1, false)]);
+        assert!(clean.hints().is_empty());
+        let missing = DwarfFeatures { missing_dwo: vec!["a.dwo".to_string()], ..clean };
+        assert!(missing.hints()[0].contains("-gsplit-dwarf"));
+    }
+}
@@ -1,18 +1,58 @@
+use crate::analysis::implied_alignment;
 use crate::error::{Error, Result};
 use crate::loader::DwarfSlice;
+use crate::types::TypeTag;
 use gimli::{AttributeValue, Dwarf, Unit, UnitOffset};
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+use super::{
+    debug_info_ref_to_unit_offset, read_u64_from_attr, unsupported_form_label,
+    whole_byte_data_bit_offset,
+};
+
+/// Result of resolving a type.
+#[derive(Debug, Clone)]
+pub struct TypeInfo {
+    pub name: String,
+    pub size: Option<u64>,
+    pub is_atomic: bool,
+    /// Alignment the debug info establishes: `DW_AT_alignment`, else the type's natural
+    /// alignment (scalars by size, vectors by their full size, aggregates from their members,
+    /// lowered to what a packed aggregate's offsets allow). `None` for unsized or unknown
+    /// types, whose alignment must be inferred from the size.
+    pub alignment: Option<u64>,
+    /// True if `alignment` is a `DW_AT_alignment` somewhere along the type chain.
+    pub explicit_alignment: bool,
+    /// Tag of the struct, union, or enum `name` is built on, unless a typedef names it.
+    pub tag: Option<TypeTag>,
+}
+
+impl TypeInfo {
+    fn new(name: String, size: Option<u64>, is_atomic: bool) -> Self {
+        Self { name, size, is_atomic, alignment: None, explicit_alignment: false, tag: None }
+    }
 
-use super::{debug_info_ref_to_unit_offset, read_u64_from_attr};
+    fn aligned(mut self, alignment: Option<u64>) -> Self {
+        self.alignment = alignment;
+        self
+    }
 
-/// Result of resolving a type: (type_name, size, is_atomic)
-pub type TypeInfo = (String, Option<u64>, bool);
+    /// Placeholder for a missing or unreadable type reference.
+    pub fn unknown() -> Self {
+        Self::new("unknown".to_string(), None, false)
+    }
+}
 
 pub struct TypeResolver<'a, 'b> {
     dwarf: &'b Dwarf<DwarfSlice<'a>>,
     unit: &'b Unit<DwarfSlice<'a>>,
     address_size: u8,
     cache: HashMap<UnitOffset, TypeInfo>,
+    /// Natural alignment of aggregate types; `None` while one is being computed.
+    aggregate_alignment: HashMap<UnitOffset, Option<u64>>,
+    /// Attribute values skipped for their form, keyed `DW_AT_x: form`.
+    unsupported_forms: RefCell<BTreeMap<String, usize>>,
 }
 
 impl<'a, 'b> TypeResolver<'a, 'b> {
@@ -21,7 +61,29 @@ impl<'a, 'b> TypeResolver<'a, 'b> {
         unit: &'b Unit<DwarfSlice<'a>>,
         address_size: u8,
     ) -> Self {
-        Self { dwarf, unit, address_size, cache: HashMap::new() }
+        Self {
+            dwarf,
+            unit,
+            address_size,
+            cache: HashMap::new(),
+            aggregate_alignment: HashMap::new(),
+            unsupported_forms: RefCell::default(),
+        }
+    }
+
+    /// Record an `attr` value skipped because the parser does not read its form.
+    pub(crate) fn note_unsupported(
+        &self,
+        attr: gimli::DwAt,
+        value: &AttributeValue<DwarfSlice<'a>>,
+    ) {
+        let key = format!("{}: {}", attr, unsupported_form_label(value));
+        *self.unsupported_forms.borrow_mut().entry(key).or_default() += 1;
+    }
+
+    /// The forms [`TypeResolver::note_unsupported`] recorded so far, clearing them.
+    pub(crate) fn take_unsupported_forms(&self) -> BTreeMap<String, usize> {
+        self.unsupported_forms.take()
     }
 
     pub fn resolve_type(&mut self, offset: UnitOffset) -> Result<TypeInfo> {
@@ -41,7 +103,7 @@ impl<'a, 'b> TypeResolver<'a, 'b> {
         is_atomic: bool,
     ) -> Result<TypeInfo> {
         if depth > 20 {
-            return Ok(("...".to_string(), None, is_atomic));
+            return Ok(TypeInfo::new("...".to_string(), None, is_atomic));
         }
 
         let entry = self
@@ -49,35 +111,49 @@ impl<'a, 'b> TypeResolver<'a, 'b> {
             .entry(offset)
             .map_err(|e| Error::Dwarf(format!("Failed to get type entry: {}", e)))?;
 
+        let mut info = self.resolve_entry(&entry, depth, is_atomic)?;
+        // An explicit alignment (alignas, aligned attributes, #[repr(align)]) on this entry
+        // overrides whatever the type it refers to carries.
+        if let Some(alignment) = read_alignment(&entry) {
+            info.alignment = Some(alignment);
+            info.explicit_alignment = true;
+        }
+        Ok(info)
+    }
+
+    fn resolve_entry(
+        &mut self,
+        entry: &gimli::DebuggingInformationEntry<DwarfSlice<'a>>,
+        depth: usize,
+        is_atomic: bool,
+    ) -> Result<TypeInfo> {
         let tag = entry.tag();
 
         match tag {
             gimli::DW_TAG_base_type => {
-                let name = self.get_type_name(&entry)?.unwrap_or_else(|| "?".to_string());
-                let size = self.get_byte_size(&entry)?;
-                Ok((name, size, is_atomic))
+                let name = self.get_type_name(entry)?.unwrap_or_else(|| "?".to_string());
+                let size = self.get_byte_size(entry)?;
+                Ok(TypeInfo::new(name, size, is_atomic).aligned(size.and_then(scalar_alignment)))
             }
 
             gimli::DW_TAG_pointer_type => {
-                let pointee = if let Some(type_offset) = self.get_type_ref(&entry)? {
-                    let (pointee_name, _, _) =
-                        self.resolve_type_inner(type_offset, depth + 1, false)?;
-                    pointee_name
+                let (pointee, tag) = if let Some(type_offset) = self.get_type_ref(entry)? {
+                    let pointee = self.resolve_type_inner(type_offset, depth + 1, false)?;
+                    (pointee.name, pointee.tag)
                 } else {
-                    "void".to_string()
+                    ("void".to_string(), None)
                 };
-                Ok((format!("*{}", pointee), Some(self.address_size as u64), is_atomic))
+                Ok(TypeInfo { tag, ..self.pointer_sized(format!("*{}", pointee), is_atomic) })
             }
 
             gimli::DW_TAG_reference_type => {
-                let referee = if let Some(type_offset) = self.get_type_ref(&entry)? {
-                    let (referee_name, _, _) =
-                        self.resolve_type_inner(type_offset, depth + 1, false)?;
-                    referee_name
+                let (referee, tag) = if let Some(type_offset) = self.get_type_ref(entry)? {
+                    let referee = self.resolve_type_inner(type_offset, depth + 1, false)?;
+                    (referee.name, referee.tag)
                 } else {
-                    "void".to_string()
+                    ("void".to_string(), None)
                 };
-                Ok((format!("&{}", referee), Some(self.address_size as u64), is_atomic))
+                Ok(TypeInfo { tag, ..self.pointer_sized(format!("&{}", referee), is_atomic) })
             }
 
             gimli::DW_TAG_const_type
@@ -89,87 +165,209 @@ impl<'a, 'b> TypeResolver<'a, 'b> {
                     gimli::DW_TAG_volatile_type => "volatile ",
                     _ => "restrict ", // DW_TAG_restrict_type
                 };
-                if let Some(type_offset) = self.get_type_ref(&entry)? {
-                    let (inner_name, size, inner_atomic) =
-                        self.resolve_type_inner(type_offset, depth + 1, is_atomic)?;
-                    Ok((format!("{}{}", prefix, inner_name), size, inner_atomic))
+                if let Some(type_offset) = self.get_type_ref(entry)? {
+                    let inner = self.resolve_type_inner(type_offset, depth + 1, is_atomic)?;
+                    Ok(TypeInfo { name: format!("{}{}", prefix, inner.name), ..inner })
                 } else {
-                    Ok((format!("{}void", prefix), None, is_atomic))
+                    Ok(TypeInfo::new(format!("{}void", prefix), None, is_atomic))
                 }
             }
 
             gimli::DW_TAG_atomic_type => {
                 // Mark as atomic and propagate through the type chain
-                if let Some(type_offset) = self.get_type_ref(&entry)? {
-                    let (inner_name, size, _) =
-                        self.resolve_type_inner(type_offset, depth + 1, true)?;
-                    Ok((format!("_Atomic {}", inner_name), size, true))
+                if let Some(type_offset) = self.get_type_ref(entry)? {
+                    let inner = self.resolve_type_inner(type_offset, depth + 1, true)?;
+                    Ok(TypeInfo {
+                        name: format!("_Atomic {}", inner.name),
+                        is_atomic: true,
+                        ..inner
+                    })
                 } else {
-                    Ok(("_Atomic void".to_string(), None, true))
+                    Ok(TypeInfo::new("_Atomic void".to_string(), None, true))
                 }
             }
 
             gimli::DW_TAG_typedef => {
-                let name = self.get_type_name(&entry)?;
-                if let Some(type_offset) = self.get_type_ref(&entry)? {
-                    let (_, size, inner_atomic) =
-                        self.resolve_type_inner(type_offset, depth + 1, is_atomic)?;
+                let name = self.get_type_name(entry)?.unwrap_or_else(|| "typedef".to_string());
+                if let Some(type_offset) = self.get_type_ref(entry)? {
+                    let inner = self.resolve_type_inner(type_offset, depth + 1, is_atomic)?;
                     // Propagate atomic flag through typedefs
-                    Ok((
-                        name.unwrap_or_else(|| "typedef".to_string()),
-                        size,
-                        inner_atomic || is_atomic,
-                    ))
+                    Ok(TypeInfo {
+                        name,
+                        is_atomic: inner.is_atomic || is_atomic,
+                        tag: None,
+                        ..inner
+                    })
                 } else {
-                    Ok((name.unwrap_or_else(|| "typedef".to_string()), None, is_atomic))
+                    Ok(TypeInfo::new(name, None, is_atomic))
                 }
             }
 
             gimli::DW_TAG_array_type => {
-                let element_type = if let Some(type_offset) = self.get_type_ref(&entry)? {
+                let element_type = if let Some(type_offset) = self.get_type_ref(entry)? {
                     self.resolve_type_inner(type_offset, depth + 1, is_atomic)?
                 } else {
-                    ("?".to_string(), None, is_atomic)
+                    TypeInfo::new("?".to_string(), None, is_atomic)
                 };
 
-                let count = self.get_array_count(&entry)?;
-                let size = match (element_type.1, count) {
+                let count = self.get_array_count(entry)?;
+                let size = match (element_type.size, count) {
                     // Use checked_mul to prevent overflow for very large arrays.
                     // Fall back to DW_AT_byte_size if multiplication overflows.
                     (Some(elem_size), Some(c)) => elem_size
                         .checked_mul(c)
-                        .or_else(|| self.get_byte_size(&entry).ok().flatten()),
-                    _ => self.get_byte_size(&entry)?,
+                        .or_else(|| self.get_byte_size(entry).ok().flatten()),
+                    _ => self.get_byte_size(entry)?,
                 };
+                // SIMD vectors (GCC/Clang `vector_size`) are aligned to their full size.
+                let vector_alignment = size.filter(|s| is_vector(entry) && s.is_power_of_two());
 
                 let count_str = count.map(|c| c.to_string()).unwrap_or_else(|| "?".to_string());
-                Ok((format!("[{}; {}]", element_type.0, count_str), size, element_type.2))
+                Ok(TypeInfo {
+                    name: format!("[{}; {}]", element_type.name, count_str),
+                    size,
+                    is_atomic: element_type.is_atomic,
+                    alignment: vector_alignment.or(element_type.alignment),
+                    explicit_alignment: vector_alignment.is_none()
+                        && element_type.explicit_alignment,
+                    tag: element_type.tag,
+                })
             }
 
             gimli::DW_TAG_structure_type | gimli::DW_TAG_class_type | gimli::DW_TAG_union_type => {
-                let name = self.get_type_name(&entry)?.unwrap_or_else(|| "<anonymous>".to_string());
-                let size = self.get_byte_size(&entry)?;
-                Ok((name, size, is_atomic))
+                let name = self.get_type_name(entry)?.unwrap_or_else(|| "<anonymous>".to_string());
+                let size = self.get_byte_size(entry)?;
+                let alignment = match size {
+                    Some(size) => self.aggregate_alignment(entry, size)?,
+                    None => None,
+                };
+                let tag =
+                    if tag == gimli::DW_TAG_union_type { TypeTag::Union } else { TypeTag::Struct };
+                Ok(TypeInfo { tag: Some(tag), ..TypeInfo::new(name, size, is_atomic) }
+                    .aligned(alignment))
             }
 
             gimli::DW_TAG_enumeration_type => {
-                let name = self.get_type_name(&entry)?.unwrap_or_else(|| "enum".to_string());
-                let size = self.get_byte_size(&entry)?;
-                Ok((name, size, is_atomic))
+                let name = self.get_type_name(entry)?.unwrap_or_else(|| "enum".to_string());
+                let size = self.get_byte_size(entry)?;
+                Ok(TypeInfo { tag: Some(TypeTag::Enum), ..TypeInfo::new(name, size, is_atomic) }
+                    .aligned(size.and_then(scalar_alignment)))
             }
 
             gimli::DW_TAG_subroutine_type => {
-                Ok(("fn(...)".to_string(), Some(self.address_size as u64), is_atomic))
+                Ok(self.pointer_sized("fn(...)".to_string(), is_atomic))
             }
 
+            gimli::DW_TAG_ptr_to_member_type => self.member_pointer(entry, depth, is_atomic),
+
             _ => {
-                let name = self.get_type_name(&entry)?.unwrap_or_else(|| format!("?<{:?}>", tag));
-                let size = self.get_byte_size(&entry)?;
-                Ok((name, size, is_atomic))
+                let name = self.get_type_name(entry)?.unwrap_or_else(|| format!("?<{:?}>", tag));
+                let size = self.get_byte_size(entry)?;
+                Ok(TypeInfo::new(name, size, is_atomic))
             }
         }
     }
 
+    /// A C++ pointer to member, named `Class::*pointee`. Without a DW_AT_byte_size, the
+    /// Itanium ABI size applies: a data member pointer is one offset, a member function pointer
+    /// a function pointer plus a `this` adjustment.
+    fn member_pointer(
+        &mut self,
+        entry: &gimli::DebuggingInformationEntry<DwarfSlice<'a>>,
+        depth: usize,
+        is_atomic: bool,
+    ) -> Result<TypeInfo> {
+        let class = match self.get_unit_ref(entry, gimli::DW_AT_containing_type)? {
+            Some(offset) => self.resolve_type_inner(offset, depth + 1, false)?.name,
+            None => "?".to_string(),
+        };
+        let (pointee, is_function) = match self.get_type_ref(entry)? {
+            Some(offset) => {
+                let tag = self
+                    .unit
+                    .entry(offset)
+                    .map_err(|e| Error::Dwarf(format!("Failed to get type entry: {}", e)))?
+                    .tag();
+                let pointee = self.resolve_type_inner(offset, depth + 1, false)?.name;
+                (pointee, tag == gimli::DW_TAG_subroutine_type)
+            }
+            None => ("?".to_string(), false),
+        };
+
+        let word = self.address_size as u64;
+        let size = match self.get_byte_size(entry)? {
+            Some(size) => size,
+            None if is_function => 2 * word,
+            None => word,
+        };
+        let name = format!("{}::*{}", class, pointee);
+        Ok(TypeInfo::new(name, Some(size), is_atomic).aligned(scalar_alignment(word)))
+    }
+
+    fn pointer_sized(&self, name: String, is_atomic: bool) -> TypeInfo {
+        let size = self.address_size as u64;
+        TypeInfo::new(name, Some(size), is_atomic).aligned(scalar_alignment(size))
+    }
+
+    /// Natural alignment of a struct, class, or union: the largest of its data members',
+    /// lowered to what its offsets and `size` allow when it is packed. `None` if a member's
+    /// alignment is unknown.
+    fn aggregate_alignment(
+        &mut self,
+        entry: &gimli::DebuggingInformationEntry<DwarfSlice<'a>>,
+        size: u64,
+    ) -> Result<Option<u64>> {
+        let offset = entry.offset();
+        if let Some(&cached) = self.aggregate_alignment.get(&offset) {
+            return Ok(cached);
+        }
+        // Placeholder: an aggregate cannot contain itself by value, so reaching it again
+        // (through a pointer member's name) needs no alignment.
+        self.aggregate_alignment.insert(offset, None);
+
+        let mut members = Vec::new();
+        let mut tree = self
+            .unit
+            .entries_tree(Some(offset))
+            .map_err(|e| Error::Dwarf(format!("Failed to create tree: {}", e)))?;
+        let root = tree.root().map_err(|e| Error::Dwarf(format!("Failed to get root: {}", e)))?;
+        let mut children = root.children();
+        while let Some(child) =
+            children.next().map_err(|e| Error::Dwarf(format!("Failed to iterate: {}", e)))?
+        {
+            let child = child.entry();
+            if !matches!(child.tag(), gimli::DW_TAG_member | gimli::DW_TAG_inheritance)
+                || is_declaration(child)
+            {
+                continue;
+            }
+            let bitfield = child.attr_value(gimli::DW_AT_bit_size).ok().flatten().is_some();
+            let member_offset = read_u64_from_attr(
+                child.attr_value(gimli::DW_AT_data_member_location).ok().flatten(),
+            )
+            .or_else(|| whole_byte_data_bit_offset(child));
+            let type_alignment = match self.get_type_ref(child)? {
+                Some(type_offset) => self.resolve_type(type_offset)?.alignment,
+                None => None,
+            };
+            members.push((
+                member_offset.filter(|_| !bitfield),
+                read_alignment(child).or(type_alignment),
+            ));
+        }
+
+        let alignment = if members.iter().any(|&(_, alignment)| alignment.is_none()) {
+            None
+        } else {
+            let natural = members.iter().filter_map(|&(_, a)| a).max().unwrap_or(1);
+            let placed =
+                members.iter().filter_map(|&(offset, alignment)| Some((offset?, alignment?)));
+            Some(implied_alignment(natural, Some(size), placed))
+        };
+        self.aggregate_alignment.insert(offset, alignment);
+        Ok(alignment)
+    }
+
     fn get_type_name(
         &self,
         entry: &gimli::DebuggingInformationEntry<DwarfSlice<'a>>,
@@ -199,11 +397,28 @@ impl<'a, 'b> TypeResolver<'a, 'b> {
         &self,
         entry: &gimli::DebuggingInformationEntry<DwarfSlice<'a>>,
     ) -> Result<Option<UnitOffset>> {
-        match entry.attr_value(gimli::DW_AT_type) {
+        self.get_unit_ref(entry, gimli::DW_AT_type)
+    }
+
+    /// The in-unit entry a reference attribute such as DW_AT_type points at.
+    fn get_unit_ref(
+        &self,
+        entry: &gimli::DebuggingInformationEntry<DwarfSlice<'a>>,
+        attr: gimli::DwAt,
+    ) -> Result<Option<UnitOffset>> {
+        match entry.attr_value(attr) {
             Ok(Some(AttributeValue::UnitRef(offset))) => Ok(Some(offset)),
-            Ok(Some(AttributeValue::DebugInfoRef(debug_info_offset))) => {
+            Ok(Some(value @ AttributeValue::DebugInfoRef(debug_info_offset))) => {
                 // Use shared helper for cross-unit reference conversion.
-                Ok(debug_info_ref_to_unit_offset(debug_info_offset, &self.unit.header))
+                let offset = debug_info_ref_to_unit_offset(debug_info_offset, &self.unit.header);
+                if offset.is_none() {
+                    self.note_unsupported(attr, &value);
+                }
+                Ok(offset)
+            }
+            Ok(Some(value)) => {
+                self.note_unsupported(attr, &value);
+                Ok(None)
             }
             _ => Ok(None),
         }
@@ -253,32 +468,36 @@ impl<'a, 'b> TypeResolver<'a, 'b> {
     }
 }
 
+/// `DW_AT_alignment` of a type or member entry, if recorded.
+pub(crate) fn read_alignment(
+    entry: &gimli::DebuggingInformationEntry<DwarfSlice<'_>>,
+) -> Option<u64> {
+    read_u64_from_attr(entry.attr_value(gimli::DW_AT_alignment).ok().flatten()).filter(|&a| a > 0)
+}
+
+/// Natural alignment of a scalar of `size` bytes: the largest power of two dividing it
+/// (`long double`'s 12 bytes on i386 align to 4).
+fn scalar_alignment(size: u64) -> Option<u64> {
+    (size > 0).then(|| size & size.wrapping_neg())
+}
+
+/// Whether a member entry only declares a static member.
+fn is_declaration(entry: &gimli::DebuggingInformationEntry<DwarfSlice<'_>>) -> bool {
+    let flag = |at| matches!(entry.attr_value(at), Ok(Some(AttributeValue::Flag(true))));
+    flag(gimli::DW_AT_external) || flag(gimli::DW_AT_declaration)
+}
+
+/// Whether an array type is a SIMD vector (`DW_AT_GNU_vector`).
+fn is_vector(entry: &gimli::DebuggingInformationEntry<DwarfSlice<'_>>) -> bool {
+    matches!(entry.attr_value(gimli::DW_AT_GNU_vector), Ok(Some(AttributeValue::Flag(true))))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::loader::BinaryData;
+    use crate::test_support::find_fixture_path;
     use gimli::DwTag;
-    use std::path::{Path, PathBuf};
-
-    fn find_fixture_path(name: &str) -> Option<PathBuf> {
-        let base = Path::new("tests/fixtures/bin");
-        let dsym_path = base.join(format!("{}.dSYM/Contents/Resources/DWARF/{}", name, name));
-        if dsym_path.exists() {
-            return Some(dsym_path);
-        }
-
-        let exe_path = base.join(format!("{}.exe", name));
-        if exe_path.exists() {
-            return Some(exe_path);
-        }
-
-        let direct_path = base.join(name);
-        if direct_path.exists() {
-            return Some(direct_path);
-        }
-
-        None
-    }
 
     fn find_type_offset(unit: &Unit<DwarfSlice<'_>>, tag: DwTag) -> Option<UnitOffset> {
         let mut entries = unit.entries();
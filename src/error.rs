@@ -11,11 +11,89 @@ pub enum Error {
     #[error("No debug information found. Compile with -g flag to include DWARF debug info.")]
     NoDebugInfo,
 
+    #[error(
+        "Split DWARF not found: {0}. Keep the .dwo files where they were built or put a .dwp package next to the binary."
+    )]
+    MissingSplitDwarf(String),
+
     #[error("Unsupported binary format. Supported: ELF, Mach-O, PE.")]
     UnsupportedFormat,
 
     #[error("DWARF parsing error: {0}")]
     Dwarf(String),
+
+    #[error("Invalid glob pattern {0}")]
+    InvalidPattern(String),
+
+    #[error("Invalid regular expression {0}")]
+    InvalidRegex(String),
+
+    #[error("Repro bundle error: {0}")]
+    Repro(String),
+
+    #[error("Lockfile error: {0}")]
+    Lockfile(String),
+
+    #[error("CODEOWNERS error: {0}")]
+    Codeowners(String),
+
+    #[error("Snapshot error: {0}")]
+    Snapshot(String),
+
+    #[error("Invalid ignore entry: {0}")]
+    Ignore(String),
+
+    #[error("Sample file error: {0}")]
+    Samples(String),
+
+    #[error("rustc type sizes error: {0}")]
+    RustcSizes(String),
+
+    #[error("Schema error: {0}")]
+    Schema(String),
+
+    #[error("Cannot locate offset: {0}")]
+    Locate(String),
+
+    #[error("No binaries found in directory: {}", .0.display())]
+    NoBinaries(std::path::PathBuf),
+
+    #[error("Attestation error: {0}")]
+    Attestation(String),
+
+    #[error("Global false sharing needs the old binary; snapshots do not record globals")]
+    SnapshotGlobals,
+
+    #[cfg(feature = "sqlite")]
+    #[error("SQLite export error: {0}")]
+    Sqlite(String),
+
+    /// `source`, failing while doing what `message` describes.
+    #[error("{message}")]
+    Context {
+        message: String,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    pub(crate) fn context(self, message: impl Into<String>) -> Self {
+        Error::Context { message: message.into(), source: Box::new(self) }
+    }
+}
+
+impl From<gimli::Error> for Error {
+    fn from(e: gimli::Error) -> Self {
+        Error::Dwarf(e.to_string())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Sqlite(e.to_string())
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
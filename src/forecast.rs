@@ -0,0 +1,224 @@
+//! Fleet-wide memory waste: each struct's padding and the bytes its suggested reordering would
+//! free, multiplied by how many instances are live across the fleet, with a growth trend from
+//! earlier snapshots.
+
+use crate::analysis::OptimizedLayout;
+use crate::types::StructLayout;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Waste of one struct with a known instance count.
+#[derive(Debug, Clone, Serialize)]
+pub struct StructForecast {
+    pub name: String,
+    pub instances: u64,
+    pub size: u64,
+    pub padding_bytes: u64,
+    pub optimized_size: u64,
+    /// Padding bytes across all instances.
+    pub waste_bytes: u64,
+    /// Bytes the suggested reordering frees across all instances.
+    pub recoverable_bytes: u64,
+    /// The struct already breaks its budget.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub over_budget: bool,
+}
+
+/// Fleet waste recorded by one snapshot, or by the current binary.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendPoint {
+    pub label: String,
+    pub waste_bytes: u64,
+}
+
+/// How fleet waste moved across snapshots, extended `horizon` snapshots ahead.
+#[derive(Debug, Clone, Serialize)]
+pub struct WasteTrend {
+    /// Oldest first; the last point is the current binary.
+    pub points: Vec<TrendPoint>,
+    /// Average change in waste from one snapshot to the next.
+    pub bytes_per_snapshot: i64,
+    pub horizon: usize,
+    pub projected_waste_bytes: u64,
+    /// Projected waste if the top suggestions are applied now.
+    pub projected_waste_after_top_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Forecast {
+    /// Structs with an instance count, most recoverable bytes first.
+    pub structs: Vec<StructForecast>,
+    pub total_waste_bytes: u64,
+    pub total_recoverable_bytes: u64,
+    /// Number of suggestions `top_recoverable_bytes` assumes are applied.
+    pub top: usize,
+    pub top_recoverable_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trend: Option<WasteTrend>,
+    /// Counted struct names the binary does not define.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unmatched: Vec<String>,
+}
+
+impl Forecast {
+    /// Weigh analyzed `layouts` and their `suggestions` (in the same order) by `instances`.
+    /// Structs named in `over_budget` are flagged; the `top` entries with the most recoverable
+    /// bytes make up the projected impact.
+    pub fn new(
+        layouts: &[StructLayout],
+        suggestions: &[OptimizedLayout],
+        instances: &HashMap<String, u64>,
+        over_budget: &HashSet<String>,
+        top: usize,
+    ) -> Self {
+        let mut seen = HashSet::new();
+        let mut structs: Vec<StructForecast> = layouts
+            .iter()
+            .zip(suggestions)
+            .filter(|(layout, _)| seen.insert(layout.name.as_str()))
+            .filter_map(|(layout, suggestion)| {
+                let count = *instances.get(&layout.name)?;
+                Some(StructForecast {
+                    name: layout.name.clone(),
+                    instances: count,
+                    size: layout.size,
+                    padding_bytes: layout.metrics.padding_bytes,
+                    optimized_size: suggestion.optimized_size,
+                    waste_bytes: layout.metrics.padding_bytes.saturating_mul(count),
+                    recoverable_bytes: suggestion.savings_bytes.saturating_mul(count),
+                    over_budget: over_budget.contains(&layout.name),
+                })
+            })
+            .collect();
+        structs.sort_by(|a, b| {
+            b.recoverable_bytes
+                .cmp(&a.recoverable_bytes)
+                .then_with(|| b.waste_bytes.cmp(&a.waste_bytes))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let mut unmatched: Vec<String> =
+            instances.keys().filter(|name| !seen.contains(name.as_str())).cloned().collect();
+        unmatched.sort();
+
+        Self {
+            total_waste_bytes: structs.iter().map(|s| s.waste_bytes).sum(),
+            total_recoverable_bytes: structs.iter().map(|s| s.recoverable_bytes).sum(),
+            top,
+            top_recoverable_bytes: structs.iter().take(top).map(|s| s.recoverable_bytes).sum(),
+            structs,
+            trend: None,
+            unmatched,
+        }
+    }
+
+    /// Add a trend from analyzed snapshot layouts, oldest first, extended `horizon` snapshots
+    /// past the current binary. A struct missing from a snapshot adds nothing to its waste.
+    pub fn with_trend(
+        mut self,
+        history: &[(String, Vec<StructLayout>)],
+        instances: &HashMap<String, u64>,
+        horizon: usize,
+    ) -> Self {
+        if history.is_empty() {
+            return self;
+        }
+        let mut points: Vec<TrendPoint> = history
+            .iter()
+            .map(|(label, layouts)| TrendPoint {
+                label: label.clone(),
+                waste_bytes: fleet_waste(layouts, instances),
+            })
+            .collect();
+        points
+            .push(TrendPoint { label: "current".to_string(), waste_bytes: self.total_waste_bytes });
+
+        let first = points[0].waste_bytes as i128;
+        let last = self.total_waste_bytes as i128;
+        let bytes_per_snapshot = ((last - first) / (points.len() as i128 - 1)) as i64;
+        let projected = (last + bytes_per_snapshot as i128 * horizon as i128).max(0) as u64;
+
+        self.trend = Some(WasteTrend {
+            points,
+            bytes_per_snapshot,
+            horizon,
+            projected_waste_bytes: projected,
+            projected_waste_after_top_bytes: projected.saturating_sub(self.top_recoverable_bytes),
+        });
+        self
+    }
+}
+
+/// Padding bytes across all counted instances of `layouts`, each struct name counted once.
+fn fleet_waste(layouts: &[StructLayout], instances: &HashMap<String, u64>) -> u64 {
+    let mut seen = HashSet::new();
+    layouts
+        .iter()
+        .filter(|l| seen.insert(l.name.as_str()))
+        .filter_map(|l| Some(l.metrics.padding_bytes.saturating_mul(*instances.get(&l.name)?)))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::optimize_layout;
+    use crate::types::MemberLayout;
+
+    fn padded(name: &str, tail: u64) -> StructLayout {
+        // char, (3 padding), int, char, (3 + tail padding)
+        let mut layout = StructLayout::new(name.to_string(), 12 + tail, Some(4));
+        layout.members = vec![
+            MemberLayout::new("a".to_string(), "char".to_string(), Some(0), Some(1)),
+            MemberLayout::new("b".to_string(), "int".to_string(), Some(4), Some(4)),
+            MemberLayout::new("c".to_string(), "char".to_string(), Some(8), Some(1)),
+        ];
+        layout.metrics.padding_bytes = 6 + tail;
+        layout
+    }
+
+    fn counts(pairs: &[(&str, u64)]) -> HashMap<String, u64> {
+        pairs.iter().map(|&(name, count)| (name.to_string(), count)).collect()
+    }
+
+    #[test]
+    fn weighs_waste_by_instances_and_ranks_by_recoverable_bytes() {
+        let layouts = vec![padded("Rare", 0), padded("Common", 0), padded("Uncounted", 0)];
+        let suggestions: Vec<_> = layouts.iter().map(|l| optimize_layout(l, 8)).collect();
+        let instances = counts(&[("Rare", 10), ("Common", 1000), ("Gone", 5)]);
+        let over_budget: HashSet<String> = ["Rare".to_string()].into_iter().collect();
+
+        let forecast = Forecast::new(&layouts, &suggestions, &instances, &over_budget, 1);
+
+        let names: Vec<&str> = forecast.structs.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Common", "Rare"]);
+        assert_eq!(forecast.structs[0].waste_bytes, 6000);
+        assert_eq!(forecast.structs[0].recoverable_bytes, 4000);
+        assert!(forecast.structs[1].over_budget);
+        assert_eq!(forecast.total_waste_bytes, 6060);
+        assert_eq!(forecast.total_recoverable_bytes, 4040);
+        assert_eq!(forecast.top_recoverable_bytes, 4000);
+        assert_eq!(forecast.unmatched, vec!["Gone".to_string()]);
+    }
+
+    #[test]
+    fn trend_projects_average_growth() {
+        let instances = counts(&[("Conn", 100)]);
+        let current = vec![padded("Conn", 8)];
+        let suggestions: Vec<_> = current.iter().map(|l| optimize_layout(l, 8)).collect();
+        let history = vec![
+            ("v1.json".to_string(), vec![padded("Conn", 0)]),
+            ("v2.json".to_string(), vec![padded("Conn", 4)]),
+        ];
+
+        let forecast = Forecast::new(&current, &suggestions, &instances, &HashSet::new(), 5)
+            .with_trend(&history, &instances, 2);
+
+        let trend = forecast.trend.unwrap();
+        let waste: Vec<u64> = trend.points.iter().map(|p| p.waste_bytes).collect();
+        assert_eq!(waste, vec![600, 1000, 1400]);
+        assert_eq!(trend.bytes_per_snapshot, 400);
+        assert_eq!(trend.projected_waste_bytes, 2200);
+        assert_eq!(trend.projected_waste_after_top_bytes, 2200 - forecast.top_recoverable_bytes);
+    }
+}
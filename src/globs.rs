@@ -0,0 +1,284 @@
+//! Set comparisons between glob patterns, for linting configs without a binary: whether two
+//! patterns match a common name, and whether earlier patterns take every name a later one
+//! matches. `*` matches `::` and `.`, as it does in budget and scope patterns.
+
+use crate::error::{Error, Result};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// Alternatives a `{a,b}` pattern may expand into before it is rejected.
+const MAX_ALTERNATIVES: usize = 256;
+
+/// Product states explored before a comparison is rejected as too complex.
+const MAX_STATES: usize = 100_000;
+
+/// A short name matched by both `a` and `b`, if any.
+pub fn overlap_example(a: &str, b: &str) -> Result<Option<String>> {
+    let (a, b) = (Pattern::parse(a)?, Pattern::parse(b)?);
+    search(&a, &b, |a, b| a && b)
+}
+
+/// Whether every name `pattern` matches is also matched by one of `earlier`.
+pub fn is_shadowed(pattern: &str, earlier: &[&str]) -> Result<bool> {
+    let pattern = Pattern::parse(pattern)?;
+    let mut union = Pattern { alternatives: Vec::new() };
+    for other in earlier {
+        union.alternatives.extend(Pattern::parse(other)?.alternatives);
+    }
+    Ok(search(&pattern, &union, |p, earlier| p && !earlier)?.is_none())
+}
+
+#[derive(Debug, Clone)]
+enum Token {
+    /// Any run of characters, possibly empty.
+    Star,
+    /// One character from the set.
+    One(CharSet),
+}
+
+#[derive(Debug, Clone)]
+struct CharSet {
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl CharSet {
+    fn literal(c: char) -> Self {
+        Self { ranges: vec![(c, c)], negated: false }
+    }
+
+    fn any() -> Self {
+        Self { ranges: Vec::new(), negated: true }
+    }
+
+    fn contains(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&c)) != self.negated
+    }
+}
+
+/// A glob as the token sequences of its `{a,b}` alternatives.
+#[derive(Debug)]
+struct Pattern {
+    alternatives: Vec<Vec<Token>>,
+}
+
+/// Positions reached in each alternative: (alternative, tokens matched).
+type States = BTreeSet<(usize, usize)>;
+
+impl Pattern {
+    fn parse(pattern: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::InvalidPattern(format!("'{}': {}", pattern, reason));
+        let mut alternatives: Vec<Vec<Token>> = vec![Vec::new()];
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            let tokens = match c {
+                '*' => {
+                    while chars.next_if_eq(&'*').is_some() {}
+                    vec![vec![Token::Star]]
+                }
+                '?' => vec![vec![Token::One(CharSet::any())]],
+                '\\' => {
+                    let escaped = chars.next().ok_or_else(|| invalid("dangling escape"))?;
+                    vec![vec![Token::One(CharSet::literal(escaped))]]
+                }
+                '[' => vec![vec![Token::One(
+                    parse_class(&mut chars).ok_or_else(|| invalid("unclosed character class"))?,
+                )]],
+                '{' => {
+                    let mut branches = Vec::new();
+                    let mut branch = String::new();
+                    loop {
+                        match chars.next().ok_or_else(|| invalid("unclosed alternation"))? {
+                            '}' => break,
+                            ',' => branches.push(std::mem::take(&mut branch)),
+                            '{' => return Err(invalid("nested alternation")),
+                            '\\' => {
+                                branch.push('\\');
+                                branch.extend(chars.next());
+                            }
+                            c => branch.push(c),
+                        }
+                    }
+                    branches.push(branch);
+                    let mut tokens = Vec::new();
+                    for branch in &branches {
+                        tokens.extend(Pattern::parse(branch)?.alternatives);
+                    }
+                    tokens
+                }
+                c => vec![vec![Token::One(CharSet::literal(c))]],
+            };
+            if alternatives.len() * tokens.len() > MAX_ALTERNATIVES {
+                return Err(invalid("too many alternatives"));
+            }
+            alternatives = alternatives
+                .iter()
+                .flat_map(|prefix| {
+                    tokens.iter().map(move |suffix| {
+                        prefix.iter().chain(suffix).cloned().collect::<Vec<Token>>()
+                    })
+                })
+                .collect();
+        }
+        Ok(Self { alternatives })
+    }
+
+    fn start(&self) -> States {
+        self.closure((0..self.alternatives.len()).map(|alt| (alt, 0)).collect())
+    }
+
+    /// `states` plus the positions past any `*` they sit on, which may match nothing.
+    fn closure(&self, mut states: States) -> States {
+        let mut pending: Vec<(usize, usize)> = states.iter().copied().collect();
+        while let Some((alt, pos)) = pending.pop() {
+            if matches!(self.alternatives[alt].get(pos), Some(Token::Star))
+                && states.insert((alt, pos + 1))
+            {
+                pending.push((alt, pos + 1));
+            }
+        }
+        states
+    }
+
+    fn step(&self, states: &States, c: char) -> States {
+        let next = states
+            .iter()
+            .filter_map(|&(alt, pos)| match self.alternatives[alt].get(pos)? {
+                Token::Star => Some((alt, pos)),
+                Token::One(set) => set.contains(c).then_some((alt, pos + 1)),
+            })
+            .collect();
+        self.closure(next)
+    }
+
+    fn accepts(&self, states: &States) -> bool {
+        states.iter().any(|&(alt, pos)| pos == self.alternatives[alt].len())
+    }
+
+    fn char_sets(&self) -> impl Iterator<Item = &CharSet> {
+        self.alternatives.iter().flatten().filter_map(|token| match token {
+            Token::One(set) => Some(set),
+            Token::Star => None,
+        })
+    }
+}
+
+/// Read a `[...]` class after its opening bracket. `!` or `^` negates it, a leading `]` is
+/// literal, and `-` between two characters makes a range.
+fn parse_class(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Option<CharSet> {
+    let negated = chars.next_if(|&c| c == '!' || c == '^').is_some();
+    let mut ranges = Vec::new();
+    let mut first = true;
+    loop {
+        let c = chars.next()?;
+        if c == ']' && !first {
+            return Some(CharSet { ranges, negated });
+        }
+        first = false;
+        let lo = if c == '\\' { chars.next()? } else { c };
+        let hi = match chars.peek() {
+            Some('-') => {
+                chars.next();
+                match chars.peek() {
+                    Some(']') | None => {
+                        ranges.push(('-', '-'));
+                        lo
+                    }
+                    Some(_) => chars.next()?,
+                }
+            }
+            _ => lo,
+        };
+        ranges.push((lo.min(hi), lo.max(hi)));
+    }
+}
+
+/// One character from each run of characters every set treats alike, preferring ones that
+/// read well in an example name.
+fn representatives(a: &Pattern, b: &Pattern) -> Vec<char> {
+    let mut starts: BTreeSet<u32> = BTreeSet::from([0]);
+    for set in a.char_sets().chain(b.char_sets()) {
+        for &(lo, hi) in &set.ranges {
+            starts.insert(lo as u32);
+            starts.insert(hi as u32 + 1);
+        }
+    }
+    let starts: Vec<u32> = starts.into_iter().collect();
+    const PREFERRED: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
+    starts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, &lo)| {
+            let hi = starts.get(i + 1).map_or(char::MAX as u32, |next| next - 1);
+            PREFERRED
+                .chars()
+                .find(|&c| (lo..=hi).contains(&(c as u32)))
+                .or_else(|| (lo..=hi).find_map(char::from_u32))
+        })
+        .collect()
+}
+
+/// The shortest name (first in representative order) leading to states of `a` and `b` whose
+/// acceptance satisfies `goal`.
+fn search(a: &Pattern, b: &Pattern, goal: impl Fn(bool, bool) -> bool) -> Result<Option<String>> {
+    let alphabet = representatives(a, b);
+    let start = (a.start(), b.start());
+    let mut seen: HashMap<(States, States), Option<(usize, char)>> = HashMap::new();
+    let mut order: Vec<(States, States)> = Vec::new();
+    let mut queue = VecDeque::new();
+    seen.insert(start.clone(), None);
+    order.push(start);
+    queue.push_back(0);
+
+    while let Some(index) = queue.pop_front() {
+        let (sa, sb) = order[index].clone();
+        if goal(a.accepts(&sa), b.accepts(&sb)) {
+            let mut name = Vec::new();
+            let mut at = index;
+            while let Some((parent, c)) = seen[&order[at]] {
+                name.push(c);
+                at = parent;
+            }
+            return Ok(Some(name.into_iter().rev().collect()));
+        }
+        if sa.is_empty() {
+            continue;
+        }
+        if order.len() > MAX_STATES {
+            return Err(Error::InvalidPattern("patterns too complex to compare".to_string()));
+        }
+        for &c in &alphabet {
+            let next = (a.step(&sa, c), b.step(&sb, c));
+            if !seen.contains_key(&next) {
+                seen.insert(next.clone(), Some((index, c)));
+                order.push(next);
+                queue.push_back(order.len() - 1);
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlaps_come_with_an_example() {
+        assert_eq!(overlap_example("*Padding", "No*").unwrap().as_deref(), Some("NoPadding"));
+        assert_eq!(overlap_example("net::*", "*::Conn").unwrap().as_deref(), Some("net::Conn"));
+        assert_eq!(overlap_example("Foo*", "Bar*").unwrap(), None);
+        assert_eq!(overlap_example("Foo[0-9]", "Foo[!0-9]").unwrap(), None);
+        assert_eq!(overlap_example("{Rx,Tx}Queue", "T?Queue").unwrap().as_deref(), Some("TxQueue"));
+        assert!(overlap_example("Foo[", "Foo").is_err());
+    }
+
+    #[test]
+    fn shadowing_needs_every_name_covered() {
+        assert!(is_shadowed("Foo*Bar", &["Foo*"]).unwrap());
+        assert!(is_shadowed("{A,B}x", &["Ax", "B?"]).unwrap());
+        assert!(is_shadowed("Foo", &["F*"]).unwrap());
+        assert!(!is_shadowed("Foo?", &["Foo[a-z]"]).unwrap());
+        assert!(!is_shadowed("*Padding", &["No*"]).unwrap());
+        assert!(!is_shadowed("Foo*", &[]).unwrap());
+    }
+}
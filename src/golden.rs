@@ -0,0 +1,353 @@
+//! Golden layout snapshots for downstream tests (the `test-support` feature). Struct layouts
+//! render as normalized text a project can keep next to its tests, so a layout change shows
+//! up as a readable diff:
+//!
+//! ```no_run
+//! use layout_audit::golden::{Golden, assert_golden};
+//!
+//! let text = Golden::new().render_binary("target/debug/myapp".as_ref(), &["Packet"]).unwrap();
+//! assert_golden("tests/golden/packet.txt", &text);
+//! ```
+//!
+//! Source paths are reduced to file names, and sizes that depend on the target's pointer width
+//! (pointers, references, `size_t`, `long`, and structs holding them) print as `ptr` or `?`,
+//! as do the offsets of such members and of those after them, so one golden file holds on
+//! targets that differ only in pointer width. Other ABI differences, such as 4-byte aligned
+//! `double` on 32-bit x86, are not normalized.
+
+use crate::analysis::analyze_layout;
+use crate::dwarf::DwarfContext;
+use crate::error::{Error, Result};
+use crate::loader::BinaryData;
+use crate::types::{MemberLayout, StructLayout};
+use globset::{GlobBuilder, GlobMatcher};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Environment variable that makes [`assert_golden`] write the golden file instead of
+/// comparing against it.
+pub const UPDATE_ENV: &str = "LAYOUT_AUDIT_UPDATE_GOLDEN";
+
+/// Type names as wide as a pointer on common targets (`long` differs between LP64 and LLP64).
+const POINTER_WIDTH_TYPES: &[&str] = &[
+    "usize",
+    "isize",
+    "uintptr",
+    "size_t",
+    "ssize_t",
+    "ptrdiff_t",
+    "intptr_t",
+    "uintptr_t",
+    "long",
+    "long int",
+    "unsigned long",
+    "long unsigned int",
+    "unsigned long int",
+];
+
+/// Renders layouts as golden text.
+#[derive(Debug, Clone)]
+pub struct Golden {
+    /// Further type names whose size depends on the target, such as Go's `int`.
+    abi_types: Vec<GlobMatcher>,
+    source_lines: bool,
+}
+
+impl Default for Golden {
+    fn default() -> Self {
+        Self { abi_types: Vec::new(), source_lines: true }
+    }
+}
+
+impl Golden {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also treat member types matching `pattern` (a glob where `*` matches `::`) as sized by
+    /// the target, e.g. `int` for Go or `alloc::vec::Vec<*` for a struct not rendered itself.
+    pub fn with_abi_dependent_type(mut self, pattern: &str) -> Result<Self> {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(false)
+            .build()
+            .map_err(|e| Error::InvalidPattern(format!("'{}': {}", pattern, e)))?;
+        self.abi_types.push(glob.compile_matcher());
+        Ok(self)
+    }
+
+    /// Whether struct headers carry declaration lines after the file name (the default).
+    pub fn with_source_lines(mut self, source_lines: bool) -> Self {
+        self.source_lines = source_lines;
+        self
+    }
+
+    /// The structs named in `names` (all structs when empty) from the binary at `path`,
+    /// rendered. Fails when a named struct is missing.
+    pub fn render_binary(&self, path: &Path, names: &[&str]) -> Result<String> {
+        let binary = BinaryData::load(path)?;
+        let loaded = binary.load_dwarf()?;
+        let mut layouts = DwarfContext::new(&loaded).find_structs(None, false)?;
+        if !names.is_empty() {
+            layouts.retain(|l| names.contains(&l.name.as_str()));
+            if let Some(missing) = names.iter().find(|n| !layouts.iter().any(|l| l.name == **n)) {
+                return Err(Error::Snapshot(format!(
+                    "struct '{}' not found in {}",
+                    missing,
+                    path.display()
+                )));
+            }
+        }
+        Ok(self.render(&layouts))
+    }
+
+    /// `layouts` as normalized text, sorted by name. Identical definitions from several
+    /// compilation units appear once.
+    pub fn render(&self, layouts: &[StructLayout]) -> String {
+        let dependent = self.abi_dependent_structs(layouts);
+        let mut blocks: Vec<(String, String)> = layouts
+            .iter()
+            .map(|layout| {
+                let mut layout = layout.clone();
+                analyze_layout(&mut layout, 64);
+                (layout.name.clone(), self.render_struct(&layout, &dependent))
+            })
+            .collect();
+        blocks.sort();
+        blocks.dedup();
+        blocks.into_iter().map(|(_, block)| block).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Names of the structs among `layouts` whose size depends on the target, directly or
+    /// through a member struct.
+    fn abi_dependent_structs<'a>(&self, layouts: &'a [StructLayout]) -> HashSet<&'a str> {
+        let mut dependent = HashSet::new();
+        loop {
+            let before = dependent.len();
+            for layout in layouts {
+                if layout.members.iter().any(|m| self.is_abi_dependent(&m.type_name, &dependent)) {
+                    dependent.insert(layout.name.as_str());
+                }
+            }
+            if dependent.len() == before {
+                return dependent;
+            }
+        }
+    }
+
+    fn is_abi_dependent(&self, type_name: &str, dependent: &HashSet<&str>) -> bool {
+        let base = base_type(type_name);
+        is_pointer_width(base)
+            || is_fat_pointer(base)
+            || is_member_pointer(base)
+            || dependent.contains(base)
+            || self.abi_types.iter().any(|glob| glob.is_match(base))
+    }
+
+    fn render_struct(&self, layout: &StructLayout, dependent: &HashSet<&str>) -> String {
+        let portable = !dependent.contains(layout.name.as_str());
+        let known = |value: u64| if portable { value.to_string() } else { "?".to_string() };
+        let mut out = format!("struct {}: size {}", layout.name, known(layout.size));
+        if let Some(alignment) = layout.alignment {
+            write!(out, ", align {}", known(alignment)).unwrap();
+        }
+        write!(out, ", padding {}", known(layout.metrics.padding_bytes)).unwrap();
+        if let Some(location) = &layout.source_location {
+            let file = location.file.rsplit(['/', '\\']).next().unwrap_or(&location.file);
+            match self.source_lines {
+                true => write!(out, " ({}:{})", file, location.line).unwrap(),
+                false => write!(out, " ({})", file).unwrap(),
+            }
+        }
+        out.push('\n');
+
+        let mut after_abi_member = false;
+        for member in &layout.members {
+            let abi = self.is_abi_dependent(&member.type_name, dependent);
+            out.push_str(&render_member(member, abi, after_abi_member));
+            out.push('\n');
+            after_abi_member |= abi;
+        }
+        out
+    }
+}
+
+/// Thin pointers and references, and the built-in pointer-width integer names.
+fn is_pointer_width(type_name: &str) -> bool {
+    (type_name.starts_with(['*', '&']) && !is_fat_pointer(type_name))
+        || POINTER_WIDTH_TYPES.contains(&type_name)
+}
+
+/// Rust pointers to slices, `str`, and trait objects, which carry a length or vtable too.
+fn is_fat_pointer(type_name: &str) -> bool {
+    let Some(pointee) = type_name.strip_prefix(['*', '&']) else {
+        return false;
+    };
+    let pointee = pointee.trim_start_matches("mut ").trim_start_matches("const ");
+    pointee.starts_with('[') || pointee == "str" || pointee.starts_with("dyn ")
+}
+
+/// C++ pointers to members (`Class::*type`), an offset or a function pointer plus adjustment.
+fn is_member_pointer(type_name: &str) -> bool {
+    type_name.contains("::*")
+}
+
+/// `  @offset name: type (size)`. A size is `ptr` for a pointer-width member and `?` for other
+/// target-dependent ones; the offsets of such members (which align by the target) and of
+/// members after them are `?` unless 0. `-` marks what the debug info leaves out.
+fn render_member(member: &MemberLayout, abi: bool, after_abi_member: bool) -> String {
+    let offset = match member.offset {
+        Some(0) => "0".to_string(),
+        Some(_) if abi || after_abi_member => "?".to_string(),
+        Some(offset) => offset.to_string(),
+        None => "-".to_string(),
+    };
+    let size = match (member.bit_size, member.size) {
+        (Some(bits), _) => {
+            let bit_offset = member.bit_offset.map_or("-".to_string(), |b| b.to_string());
+            format!("bits {}+{}", bit_offset, bits)
+        }
+        (None, Some(_)) if abi && is_pointer_width(strip_qualifiers(&member.type_name)) => {
+            "ptr".to_string()
+        }
+        (None, Some(_)) if abi => "?".to_string(),
+        (None, Some(size)) => size.to_string(),
+        (None, None) => "-".to_string(),
+    };
+    format!("  @{} {}: {} ({})", offset, member.name, member.type_name, size)
+}
+
+/// `type_name` without leading qualifiers and tags such as `const` or `struct`.
+fn strip_qualifiers(mut type_name: &str) -> &str {
+    const PREFIXES: &[&str] =
+        &["const ", "volatile ", "restrict ", "_Atomic ", "struct ", "union "];
+    while let Some(rest) = PREFIXES.iter().find_map(|prefix| type_name.trim().strip_prefix(prefix))
+    {
+        type_name = rest;
+    }
+    type_name.trim()
+}
+
+/// The type a member's storage is made of, without qualifiers or array extents.
+fn base_type(mut type_name: &str) -> &str {
+    loop {
+        type_name = strip_qualifiers(type_name);
+        match type_name.strip_prefix('[').and_then(|rest| rest.rsplit_once(';')) {
+            Some((element, _)) => type_name = element,
+            None => return type_name,
+        }
+    }
+}
+
+/// Compare `actual` with the golden file at `path`, panicking with the first differing line
+/// and the full new text on a mismatch. With [`UPDATE_ENV`] set (to anything but `0`), write
+/// `actual` to the file instead.
+#[track_caller]
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_ENV).is_some_and(|value| value != "0") {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)
+                .unwrap_or_else(|e| panic!("Failed to create {}: {}", parent.display(), e));
+        }
+        std::fs::write(path, actual)
+            .unwrap_or_else(|e| panic!("Failed to write golden file {}: {}", path.display(), e));
+        return;
+    }
+    let expected = std::fs::read_to_string(path).unwrap_or_else(|e| {
+        panic!(
+            "Failed to read golden file {}: {} (set {}=1 to create it)",
+            path.display(),
+            e,
+            UPDATE_ENV
+        )
+    });
+    let expected = expected.replace("\r\n", "\n");
+    if expected == actual {
+        return;
+    }
+    let (line, (want, got)) = expected
+        .lines()
+        .chain(std::iter::repeat(""))
+        .zip(actual.lines().chain(std::iter::repeat("")))
+        .enumerate()
+        .find(|(_, (want, got))| want != got)
+        .unwrap_or((0, ("", "")));
+    panic!(
+        "Layout differs from golden file {} at line {}:\n  expected: {}\n  actual:   {}\n\n\
+         Full output:\n{}\n(set {}=1 to update the file)",
+        path.display(),
+        line + 1,
+        want,
+        got,
+        actual,
+        UPDATE_ENV
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SourceLocation;
+
+    fn layout(name: &str, size: u64, members: &[(&str, &str, u64, u64)]) -> StructLayout {
+        let mut layout = StructLayout::new(name.to_string(), size, Some(8));
+        layout.source_location =
+            Some(SourceLocation { file: "/home/ci/src/net/packet.h".to_string(), line: 12 });
+        layout.members = members
+            .iter()
+            .map(|&(name, type_name, offset, size)| {
+                MemberLayout::new(name.to_string(), type_name.to_string(), Some(offset), Some(size))
+            })
+            .collect();
+        layout
+    }
+
+    #[test]
+    fn pointer_width_parts_are_redacted() {
+        let layouts = vec![
+            layout(
+                "Packet",
+                24,
+                &[("len", "u32", 0, 4), ("data", "*u8", 8, 8), ("id", "u32", 16, 4)],
+            ),
+            layout("Header", 8, &[("kind", "u8", 0, 1), ("len", "u32", 4, 4)]),
+            layout("Frame", 32, &[("header", "Header", 0, 8), ("packet", "Packet", 8, 24)]),
+        ];
+
+        let text = Golden::new().render(&layouts);
+
+        assert_eq!(
+            text,
+            "struct Frame: size ?, align ?, padding ? (packet.h:12)\n  @0 header: Header (8)\n  \
+             @? packet: Packet (?)\n\nstruct Header: size 8, align 8, padding 3 (packet.h:12)\n  \
+             @0 kind: u8 (1)\n  @4 len: u32 (4)\n\nstruct Packet: size ?, align ?, padding ? \
+             (packet.h:12)\n  @0 len: u32 (4)\n  @? data: *u8 (ptr)\n  @? id: u32 (4)\n"
+        );
+    }
+
+    #[test]
+    fn extra_abi_types_and_base_types() {
+        let golden = Golden::new().with_abi_dependent_type("int").unwrap();
+        let layouts =
+            vec![layout("GoStruct", 16, &[("n", "int", 0, 8), ("m", "[const long; 2]", 8, 16)])];
+        let text = golden.with_source_lines(false).render(&layouts);
+        assert!(text.starts_with("struct GoStruct: size ?, align ?, padding ? (packet.h)\n"));
+        assert!(text.contains("@0 n: int (?)\n  @? m: [const long; 2] (?)"));
+        assert!(is_pointer_width("*const u8") && is_pointer_width("&mut Foo"));
+        assert!(
+            is_fat_pointer("&[u8]") && is_fat_pointer("*mut dyn Any") && is_fat_pointer("&str")
+        );
+        assert!(Golden::new().with_abi_dependent_type("[").is_err());
+    }
+
+    #[test]
+    fn assert_golden_accepts_matching_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("layout.txt");
+        std::fs::write(&path, "struct A: size 1\r\n").unwrap();
+        assert_golden(&path, "struct A: size 1\n");
+        let mismatch = std::panic::catch_unwind(|| assert_golden(&path, "struct A: size 2\n"));
+        assert!(mismatch.is_err());
+    }
+}
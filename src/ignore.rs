@@ -0,0 +1,169 @@
+//! The config's `ignore` list: struct-name globs whose budget violations `check` reports as
+//! suppressed instead of failing on, each with an optional reason and expiry date.
+//!
+//! An entry stops applying once its `expires` date has passed, so a temporary exception turns
+//! back into a failure instead of lingering.
+
+use crate::error::{Error, Result};
+use globset::{GlobBuilder, GlobMatcher};
+use serde::Deserialize;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One `ignore` entry as written in the config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IgnoreEntry {
+    /// Struct-name glob, matched like `analyze` patterns.
+    pub pattern: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Last day (`YYYY-MM-DD`) the entry applies.
+    #[serde(default)]
+    pub expires: Option<String>,
+}
+
+/// Compiled `ignore` entries.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreList {
+    entries: Vec<(IgnoreEntry, GlobMatcher, Option<Date>)>,
+}
+
+impl IgnoreList {
+    pub fn new(entries: &[IgnoreEntry]) -> Result<Self> {
+        let entries = entries
+            .iter()
+            .map(|entry| {
+                let matcher = GlobBuilder::new(&entry.pattern)
+                    .literal_separator(false)
+                    .build()
+                    .map_err(|e| Error::InvalidPattern(format!("'{}': {}", entry.pattern, e)))?
+                    .compile_matcher();
+                let expires = entry.expires.as_deref().map(Date::parse).transpose()?;
+                Ok((entry.clone(), matcher, expires))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { entries })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The first entry matching `struct_name` that has not expired by `today`.
+    pub fn find(&self, struct_name: &str, today: Date) -> Option<&IgnoreEntry> {
+        self.entries
+            .iter()
+            .find(|(_, matcher, expires)| {
+                matcher.is_match(struct_name) && expires.is_none_or(|date| today <= date)
+            })
+            .map(|(entry, _, _)| entry)
+    }
+
+    /// Entries whose expiry date is before `today`.
+    pub fn expired(&self, today: Date) -> impl Iterator<Item = &IgnoreEntry> {
+        self.entries
+            .iter()
+            .filter(move |(_, _, expires)| expires.is_some_and(|date| date < today))
+            .map(|(entry, _, _)| entry)
+    }
+}
+
+/// A calendar date (UTC), as used for `expires`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    year: i64,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    /// Parse `YYYY-MM-DD`.
+    pub fn parse(text: &str) -> Result<Self> {
+        let invalid = || Error::Ignore(format!("expires '{}' is not a YYYY-MM-DD date", text));
+        let mut parts = text.trim().splitn(3, '-');
+        let mut next = || parts.next().and_then(|p| p.parse::<i64>().ok()).ok_or_else(invalid);
+        let (year, month, day) = (next()?, next()?, next()?);
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month as u32).into() {
+            return Err(invalid());
+        }
+        Ok(Self { year, month: month as u32, day: day as u32 })
+    }
+
+    pub fn today() -> Self {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        Self::from_days(secs as i64 / 86_400)
+    }
+
+    /// The date `days` days after 1970-01-01 (Howard Hinnant's `civil_from_days`).
+    fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Self { year, month, day }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pattern: &str, expires: Option<&str>) -> IgnoreEntry {
+        IgnoreEntry {
+            pattern: pattern.to_string(),
+            reason: None,
+            expires: expires.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn dates_parse_and_convert() {
+        assert_eq!(Date::parse("2024-02-29").unwrap().to_string(), "2024-02-29");
+        assert!(Date::parse("2023-02-29").is_err());
+        assert!(Date::parse("2024-13-01").is_err());
+        assert!(Date::parse("next week").is_err());
+        assert_eq!(Date::from_days(0).to_string(), "1970-01-01");
+        assert_eq!(Date::from_days(19_782).to_string(), "2024-02-29");
+    }
+
+    #[test]
+    fn expired_entries_stop_matching() {
+        let list =
+            IgnoreList::new(&[entry("legacy::*", Some("2025-06-30")), entry("Legacy*", None)])
+                .unwrap();
+        let before = Date::parse("2025-06-30").unwrap();
+        let after = Date::parse("2025-07-01").unwrap();
+        assert_eq!(list.find("legacy::ns::Old", before).unwrap().pattern, "legacy::*");
+        assert!(list.find("legacy::ns::Old", after).is_none());
+        assert!(list.find("LegacyBuffer", after).is_some());
+        assert_eq!(list.expired(after).count(), 1);
+        assert_eq!(list.expired(before).count(), 0);
+    }
+
+    #[test]
+    fn invalid_entries_are_rejected() {
+        assert!(IgnoreList::new(&[entry("[", None)]).is_err());
+        assert!(IgnoreList::new(&[entry("A", Some("2025-6-31"))]).is_err());
+    }
+}
@@ -1,25 +1,112 @@
 pub mod analysis;
+pub mod attest;
 pub mod cli;
 pub mod diff;
 pub mod dwarf;
 pub mod error;
+pub mod forecast;
+pub mod globs;
+#[cfg(feature = "test-support")]
+pub mod golden;
+pub mod ignore;
 pub mod loader;
+pub mod locate;
+pub mod lock;
+pub mod names;
 pub mod output;
+pub mod owners;
+pub mod pager;
+pub mod pipeline;
+pub mod placement;
+pub mod repro;
+pub mod rustc_sizes;
+pub mod samples;
+pub mod schema;
+pub mod snapshot;
+pub mod suppress;
+pub mod target;
+#[cfg(test)]
+mod test_support;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod types;
+pub mod yaml;
 
 pub use analysis::{
-    OptimizedLayout, OptimizedMember, analyze_false_sharing, analyze_layout, optimize_layout,
+    AccessSimulation, AlignmentSource, AllocatorModel, AtomicPatterns, BinarySummary,
+    CacheLineHeatmap, ContainmentGraph, FileSummary, GlobalFalseSharingDiff, GraphEdge, GraphNode,
+    HeatmapBucket, JustOverTheLine, LocalityFront, LocalityOrdering, NamespaceSummary,
+    OptimizeStrategy, OptimizedLayout, OptimizedMember, OverFetch, OwnerSummary, PackedRegion,
+    PaddingBucket, SUMMARY_TOP_OFFENDERS, StrategyOutcome, SummaryOffender, TemplateSummary,
+    TypeSizeConflict, TypeSizes, WorstStruct, analyze_bases, analyze_bits, analyze_false_sharing,
+    analyze_false_sharing_with_patterns, analyze_global_false_sharing, analyze_layout,
+    cache_line_heatmap, collapse_templates, containment_graph, count_static_instances, detect_pack,
+    diff_global_false_sharing, final_line_bytes, flatten_bases, is_bitfield_only, locality_front,
+    optimize_layout, optimize_layout_with_priority, optimize_layout_with_strategy, over_fetch,
+    packed_regions, place_globals, prefix_groups, simulate_access, static_access_sequence,
+    strategy_disagreement, summarize_binary, summarize_by_file, summarize_by_namespace,
+    summarize_by_owner,
+};
+pub use cli::{
+    AssertionLang, BudgetMatch, Cli, Commands, ExportFormat, GraphFormat, OutputFormat,
+    ReportProfile, SortField, WatchAction,
+};
+pub use diff::{
+    BuildComparison, BuildDifferenceKind, DeterminismReport, DeterminismRun, DiffResult,
+    MemberChangeKind, StructChange, TargetComparison, TargetDifference, TargetDifferenceCause,
+    compare_builds, compare_targets, diff_layouts,
+};
+pub use dwarf::{
+    DwarfContext, DwarfFeatures, ExportedStructs, ScanStats, StructIter, StructScope, UnitStats,
+    is_go_internal_type,
 };
-pub use cli::{Cli, Commands, OutputFormat, SortField};
-pub use diff::{DiffResult, diff_layouts};
-pub use dwarf::DwarfContext;
 pub use error::{Error, Result};
-pub use loader::{BinaryData, LoadedDwarf};
+pub use forecast::{Forecast, StructForecast, TrendPoint, WasteTrend};
+pub use globs::{is_shadowed, overlap_example};
+pub use ignore::{Date, IgnoreEntry, IgnoreList};
+pub use loader::{BinaryData, DataSection, DataSymbol, LoadedDwarf};
+pub use locate::{FieldLocation, FieldStep, resolve_field};
+pub use lock::{DEFAULT_LOCKFILE, LockedSizeChange, SizeLock};
+pub use names::{canonical_name, embedded_type, namespace_of, namespace_prefix, near_misses};
 pub use output::{
-    CheckViolation, CheckViolationKind, JsonFormatter, SarifFormatter, SuggestJsonFormatter,
-    SuggestTableFormatter, TableFormatter,
+    AnnotateJsonFormatter, AnnotateTableFormatter, CheckViolation, CheckViolationKind,
+    CodeFormatter, CodeLanguage, Column, CsvFormatter, DebugInfo, DepfileFormatter,
+    DeterminismJsonFormatter, DeterminismTableFormatter, ForecastJsonFormatter,
+    ForecastTableFormatter, GoAssertionFormatter, GraphDotFormatter, GraphJsonFormatter,
+    HeaderFormatter, IgnoredViolation, JSON_SCHEMA_VERSION, JsonFormatter, JsonStyle,
+    LocateJsonFormatter, LocateTableFormatter, PlacementJsonFormatter, PlacementTableFormatter,
+    SarifFormatter, SchemaJsonFormatter, SchemaTableFormatter, Severity, SuggestJsonFormatter,
+    SuggestTableFormatter, TableFormatter, TargetsJsonFormatter, TargetsTableFormatter,
+    VerifyJsonFormatter, VerifyTableFormatter,
+};
+#[cfg(feature = "sqlite")]
+pub use output::{SQLITE_SCHEMA_VERSION, SqliteExporter, SqliteRun};
+pub use owners::CodeOwners;
+pub use pipeline::{
+    BinaryScan, DiffBaseline, DiffOptions, DiffReport, InspectOptions, InspectReport, Notice, Scan,
+    ScanOptions, StreamReport, stream_inspect,
+};
+pub use placement::{Placement, PlacementAdvice, SharedBy, ThreadOwners};
+pub use repro::{REPRO_DEBUG_OBJECT, ReproBundle, extract_debug_object};
+pub use rustc_sizes::{
+    LayoutMismatch, MismatchKind, RustcField, RustcLayout, RustcVariant, VerifyReport,
+    verify_layouts,
+};
+pub use samples::{
+    AccessSample, FieldAccessReport, Mapping, MemberAccess, SampleSet, StructAccess,
+    attribute_accesses,
+};
+pub use schema::{
+    SchemaFormat, SchemaMessage, SchemaMismatch, SchemaMismatchKind, SchemaReport, check_schema,
 };
+pub use snapshot::{Snapshot, SnapshotMember, SnapshotStruct};
+pub use suppress::{ALLOW_ALL, SourceSuppression, SuppressionScanner};
+#[cfg(feature = "tui")]
+pub use tui::{Explorer, fuzzy_score};
 pub use types::{
-    AtomicMember, CacheLineSpanningWarning, FalseSharingAnalysis, FalseSharingWarning,
-    LayoutMetrics, MemberLayout, PaddingHole, SourceLocation, StructLayout,
+    AggregateExtent, AllocSlack, AtomicMember, BaseAttribution, BitGap, BitLayout, BitRange,
+    CacheLineSpanningWarning, DeclaredSize, EnumLayout, FalseSharingAnalysis, FalseSharingWarning,
+    GlobalFalseSharingWarning, GlobalVariable, LayoutMetrics, MemberLayout, MemberRole,
+    PaddingHole, SharedGlobal, SourceLocation, StaticMember, StructLayout, StructUsage,
+    VariantLayout,
 };
@@ -1,15 +1,58 @@
 use crate::error::{Error, Result};
-use gimli::{Dwarf, EndianSlice, RunTimeEndian, SectionId};
+use crate::target::{Target, TargetMismatch, debug_info_endian};
+use gimli::{Dwarf, DwarfPackage, DwoId, EndianSlice, RunTimeEndian, Section, SectionId};
 use memmap2::Mmap;
-use object::{Object, ObjectSection};
+use object::{Object, ObjectSection, ObjectSegment, ObjectSymbol};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::OnceLock;
 
 pub struct BinaryData {
     pub mmap: Mmap,
+    path: PathBuf,
+    /// Separate debug file found for a stripped binary (build-id, `.gnu_debuglink`, or dSYM).
+    debug: Option<DebugFile>,
+    /// Supplementary file named by `.gnu_debugaltlink` or `.debug_sup` (e.g. from `dwz`).
+    sup: Option<DebugFile>,
+    /// DWARF package (`<binary>.dwp`) holding split units.
+    dwp: Option<DebugFile>,
+    /// `.dwo` files read by `load_dwarf`.
+    dwo_files: OnceLock<Vec<PathBuf>>,
+}
+
+/// A file holding debug info for the binary, separate from the binary itself.
+struct DebugFile {
+    path: PathBuf,
+    mmap: Mmap,
+}
+
+impl DebugFile {
+    fn open(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        // SAFETY: The file is opened read-only and the map is kept alive with the path.
+        let mmap = unsafe { Mmap::map(&file).ok()? };
+        Some(Self { path: path.to_path_buf(), mmap })
+    }
+}
+
+/// A data object from the symbol table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataSymbol {
+    pub name: String,
+    pub address: u64,
+    pub size: u64,
+    pub section: String,
+}
+
+/// A writable data section, used to place globals the symbol table does not list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataSection {
+    pub name: String,
+    pub address: u64,
+    pub size: u64,
 }
 
 pub type DwarfSlice<'a> = EndianSlice<'a, RunTimeEndian>;
@@ -17,51 +60,58 @@ pub type DwarfSlice<'a> = EndianSlice<'a, RunTimeEndian>;
 /// Storage for decompressed DWARF sections.
 /// Pinned to ensure stable addresses for slices pointing into the data.
 pub struct DecompressedSections {
-    sections: HashMap<&'static str, Vec<u8>>,
+    sections: Vec<Vec<u8>>,
+    /// Names of the sections decompressed so far.
+    names: Vec<String>,
 }
 
 impl DecompressedSections {
     fn new() -> Pin<Box<Self>> {
-        Box::pin(Self { sections: HashMap::new() })
+        Box::pin(Self { sections: Vec::new(), names: Vec::new() })
     }
 
-    fn insert(self: &mut Pin<Box<Self>>, name: &'static str, data: Vec<u8>) {
-        // SAFETY: We only modify the HashMap contents, not the Box location.
-        // The HashMap's heap allocations (Vec<u8>) have stable addresses.
-        unsafe { self.as_mut().get_unchecked_mut() }.sections.insert(name, data);
-    }
-
-    fn get(&self, name: &str) -> Option<&[u8]> {
-        self.sections.get(name).map(|v| v.as_slice())
+    /// Keep `data`, decompressed from section `name`, alive as long as `self`, returning a
+    /// slice into it.
+    ///
+    /// The slice must not be used after `self` is dropped; `LoadedDwarf` holds both.
+    fn keep<'d>(self: &mut Pin<Box<Self>>, name: &str, data: Vec<u8>) -> &'d [u8] {
+        // SAFETY: The Vec's heap buffer does not move when the Vec itself is moved into
+        // `sections`, and it is never modified or dropped before `self`.
+        let slice = unsafe { std::slice::from_raw_parts(data.as_ptr(), data.len()) };
+        // SAFETY: We only modify the Vec contents, not the Box location.
+        let this = unsafe { self.as_mut().get_unchecked_mut() };
+        this.sections.push(data);
+        this.names.push(name.to_string());
+        slice
     }
 }
 
 pub struct LoadedDwarf<'a> {
     pub dwarf: Dwarf<DwarfSlice<'a>>,
+    /// Split compilation units (DWARF fission) by DWO id, each read from its `.dwo` file or
+    /// its contribution to the `.dwp` package. Skeleton units in `dwarf` point here.
+    pub split_units: HashMap<DwoId, Dwarf<DwarfSlice<'a>>>,
     pub address_size: u8,
+    /// Byte order the DWARF is read in: the file header's, unless `.debug_info` disagrees.
     pub endian: RunTimeEndian,
+    /// The target the object file headers describe.
+    pub target: Target,
+    /// Ways the DWARF disagrees with `target`.
+    pub target_mismatches: Vec<TargetMismatch>,
+    /// `.dwo` files named by skeleton units that were not found; their structs are missing.
+    pub missing_dwo: Vec<String>,
+    /// Names of the DWARF sections that were stored compressed, sorted and deduplicated.
+    pub compressed_sections: Vec<String>,
     /// Pinned storage for decompressed sections. The Dwarf object holds slices
     /// pointing into this data, so it must remain at a stable address.
     /// Named with underscore prefix to indicate intentional non-use (kept for lifetime).
     _decompressed_sections: Pin<Box<DecompressedSections>>,
+    /// Mappings of the `.dwo` files that `split_units` point into.
+    _dwo_mappings: Vec<Mmap>,
 }
 
-/// Standard DWARF section names that we need to load.
-const DEBUG_SECTIONS: &[&str] = &[
-    "abbrev",
-    "addr",
-    "aranges",
-    "info",
-    "line",
-    "line_str",
-    "loc",
-    "loclists",
-    "ranges",
-    "rnglists",
-    "str",
-    "str_offsets",
-    "types",
-];
+/// Directories searched for separate debug files, as GDB does.
+const DEBUG_ROOTS: &[&str] = &["/usr/lib/debug"];
 
 impl BinaryData {
     pub fn load(path: &Path) -> Result<Self> {
@@ -69,11 +119,210 @@ impl BinaryData {
         // SAFETY: The file is opened read-only and we keep the mmap alive
         // for the lifetime of BinaryData.
         let mmap = unsafe { Mmap::map(&file)? };
-        Ok(Self { mmap })
+        let mut binary = Self {
+            mmap,
+            path: path.to_path_buf(),
+            debug: None,
+            sup: None,
+            dwp: None,
+            dwo_files: OnceLock::new(),
+        };
+        binary.find_external_debug_info();
+        Ok(binary)
     }
 
-    pub fn load_dwarf(&self) -> Result<LoadedDwarf<'_>> {
+    /// Look for debug info kept outside the binary: a separate debug file when the binary has
+    /// none of its own, the supplementary file it refers to, and a `.dwp` package.
+    fn find_external_debug_info(&mut self) {
+        let Ok(object) = object::File::parse(&*self.mmap) else { return };
+        if !has_debug_info(&object) {
+            self.debug = find_debug_file(&self.path, &object, DEBUG_ROOTS);
+        }
+
+        let (debug_path, debug_data) = match &self.debug {
+            Some(debug) => (debug.path.as_path(), &*debug.mmap),
+            None => (self.path.as_path(), &*self.mmap),
+        };
+        if let Ok(debug_object) = object::File::parse(debug_data) {
+            self.sup = find_sup_file(debug_path, &debug_object, DEBUG_ROOTS);
+        }
+
+        let mut dwp = self.path.clone().into_os_string();
+        dwp.push(".dwp");
+        self.dwp = DebugFile::open(Path::new(&dwp));
+    }
+
+    /// The bytes DWARF is read from: the separate debug file if one was found, else the binary.
+    pub fn debug_data(&self) -> &[u8] {
+        match &self.debug {
+            Some(debug) => &debug.mmap,
+            None => &self.mmap,
+        }
+    }
+
+    /// Object files directly inside `dir` (executables, shared libraries, relocatable
+    /// objects), sorted by path. Archives and files of other kinds are skipped.
+    pub fn find_in_dir(dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut found = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            // Unreadable and empty files are not binaries either.
+            let Ok(binary) = Self::load(&path) else { continue };
+            match object::FileKind::parse(&*binary.mmap) {
+                Ok(object::FileKind::Archive) | Err(_) => {}
+                Ok(_) => found.push(path),
+            }
+        }
+        found.sort();
+        Ok(found)
+    }
+
+    /// Files read to produce the analysis (the binary plus any external debug files).
+    /// Used to emit dependency files for build systems. `.dwo` files are included once
+    /// `load_dwarf` has read them.
+    pub fn input_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.path.clone()];
+        files.extend(
+            [&self.debug, &self.sup, &self.dwp].into_iter().flatten().map(|f| f.path.clone()),
+        );
+        files.extend(self.dwo_files.get().into_iter().flatten().cloned());
+        files
+    }
+
+    /// Whether `other` holds the same build: the same file, an identical copy, or a binary with
+    /// the same GNU build-id or Mach-O UUID. Such binaries have the same debug info.
+    pub fn is_same_build(&self, other: &BinaryData) -> bool {
+        if self.path.canonicalize().is_ok_and(|p| other.path.canonicalize().is_ok_and(|o| p == o))
+            || *self.mmap == *other.mmap
+        {
+            return true;
+        }
+        let (Ok(this), Ok(that)) =
+            (object::File::parse(&*self.mmap), object::File::parse(&*other.mmap))
+        else {
+            return false;
+        };
+        match (this.build_id(), that.build_id()) {
+            (Ok(Some(a)), Ok(Some(b))) => return a == b,
+            (Ok(Some(_)), _) | (_, Ok(Some(_))) => return false,
+            _ => {}
+        }
+        matches!((this.mach_uuid(), that.mach_uuid()), (Ok(Some(a)), Ok(Some(b))) if a == b)
+    }
+
+    /// The link-time address of a file offset in the binary, from the segment holding it.
+    pub fn link_address(&self, file_offset: u64) -> Option<u64> {
+        let object = object::File::parse(&*self.mmap).ok()?;
+        object.segments().find_map(|segment| {
+            let (start, size) = segment.file_range();
+            (start..start + size)
+                .contains(&file_offset)
+                .then(|| segment.address() + (file_offset - start))
+        })
+    }
+
+    /// Names of symbols exported from the dynamic symbol table (or the export trie/table on
+    /// Mach-O and PE). Mach-O's leading underscore is stripped so names match DWARF.
+    pub fn exported_symbols(&self) -> Result<HashSet<String>> {
+        let object = object::File::parse(&*self.mmap)?;
+        let strip_underscore = object.format() == object::BinaryFormat::MachO;
+
+        Ok(object
+            .exports()?
+            .iter()
+            .map(|export| {
+                let name = String::from_utf8_lossy(export.name());
+                match name.strip_prefix('_') {
+                    Some(stripped) if strip_underscore => stripped.to_string(),
+                    _ => name.into_owned(),
+                }
+            })
+            .collect())
+    }
+
+    /// Defined data objects in writable sections (`.data`, `.bss`, and the like). Thread-local
+    /// sections are left out: each thread gets its own copy. Aliases at one address are
+    /// reported once.
+    pub fn data_symbols(&self) -> Result<Vec<DataSymbol>> {
+        self.symbols_in(false)
+    }
+
+    /// Like [`data_symbols`], plus objects in read-only data (`.rodata`): every object the
+    /// binary stores.
+    ///
+    /// [`data_symbols`]: Self::data_symbols
+    pub fn stored_symbols(&self) -> Result<Vec<DataSymbol>> {
+        self.symbols_in(true)
+    }
+
+    fn symbols_in(&self, read_only: bool) -> Result<Vec<DataSymbol>> {
+        let object = object::File::parse(&*self.mmap)?;
+        let strip_underscore = object.format() == object::BinaryFormat::MachO;
+
+        let mut symbols: Vec<DataSymbol> = Vec::new();
+        for symbol in object.symbols() {
+            if symbol.kind() != object::SymbolKind::Data || symbol.size() == 0 {
+                continue;
+            }
+            let Some(index) = symbol.section_index() else { continue };
+            let section = object.section_by_index(index)?;
+            if !is_data_section(section.kind(), read_only) {
+                continue;
+            }
+            let name = symbol.name().unwrap_or_default();
+            let name = match name.strip_prefix('_') {
+                Some(stripped) if strip_underscore => stripped,
+                _ => name,
+            };
+            symbols.push(DataSymbol {
+                name: name.to_string(),
+                address: symbol.address(),
+                size: symbol.size(),
+                section: section.name().unwrap_or_default().to_string(),
+            });
+        }
+        symbols.sort_by(|a, b| a.address.cmp(&b.address).then_with(|| a.name.cmp(&b.name)));
+        symbols.dedup_by_key(|s| s.address);
+        Ok(symbols)
+    }
+
+    /// Writable data sections with an address, the same kinds [`data_symbols`] draws from.
+    /// These survive stripping the symbol table.
+    ///
+    /// [`data_symbols`]: Self::data_symbols
+    pub fn data_sections(&self) -> Result<Vec<DataSection>> {
+        self.sections_in(false)
+    }
+
+    /// Like [`data_sections`], plus read-only data sections; the kinds [`stored_symbols`]
+    /// draws from.
+    ///
+    /// [`data_sections`]: Self::data_sections
+    /// [`stored_symbols`]: Self::stored_symbols
+    pub fn stored_sections(&self) -> Result<Vec<DataSection>> {
+        self.sections_in(true)
+    }
+
+    fn sections_in(&self, read_only: bool) -> Result<Vec<DataSection>> {
         let object = object::File::parse(&*self.mmap)?;
+        let mut sections: Vec<DataSection> = object
+            .sections()
+            .filter(|s| is_data_section(s.kind(), read_only) && s.address() != 0 && s.size() != 0)
+            .map(|s| DataSection {
+                name: s.name().unwrap_or_default().to_string(),
+                address: s.address(),
+                size: s.size(),
+            })
+            .collect();
+        sections.sort_by_key(|s| s.address);
+        Ok(sections)
+    }
+
+    pub fn load_dwarf(&self) -> Result<LoadedDwarf<'_>> {
+        let object = object::File::parse(self.debug_data())?;
 
         if !matches!(
             object.format(),
@@ -82,95 +331,401 @@ impl BinaryData {
             return Err(Error::UnsupportedFormat);
         }
 
-        let endian =
-            if object.is_little_endian() { RunTimeEndian::Little } else { RunTimeEndian::Big };
+        let target = Target::of(&object);
+        let mut target_mismatches = Vec::new();
+        let mut endian = RunTimeEndian::from(target.endian);
 
         // Create pinned storage for decompressed sections
         let mut decompressed_sections = DecompressedSections::new();
 
-        // Pre-decompress any compressed sections and store them
-        for &base_name in DEBUG_SECTIONS {
-            let debug_name = format!(".debug_{}", base_name);
-            let zdebug_name = format!(".zdebug_{}", base_name);
-
-            // Try .debug_* first, then .zdebug_*
-            for name in [&debug_name, &zdebug_name] {
-                if let Some(section) = object.section_by_name(name) {
-                    if let Ok(Cow::Owned(vec)) = section.uncompressed_data() {
-                        // Leak the string to get a 'static lifetime - this is fine since
-                        // these are a fixed set of section names used for the program lifetime
-                        let static_name: &'static str = match name.as_str() {
-                            n if n == debug_name => leak_section_name(&debug_name),
-                            _ => leak_section_name(&zdebug_name),
-                        };
-                        decompressed_sections.insert(static_name, vec);
-                    }
-                }
-            }
+        let mut dwarf = load_sections(&object, endian, false, &mut decompressed_sections)?;
+        let dwarf_endian = debug_info_endian(dwarf.debug_info.reader().slice(), target.endian);
+        if dwarf_endian != target.endian {
+            target_mismatches
+                .push(TargetMismatch::Endian { target: target.endian, dwarf: dwarf_endian });
+            endian = dwarf_endian.into();
+            dwarf = load_sections(&object, endian, false, &mut decompressed_sections)?;
+        }
+        target_mismatches.extend(address_size_mismatches(&dwarf, target.address_size));
+        if let Some(sup) = &self.sup {
+            let sup_object = object::File::parse(&*sup.mmap)?;
+            dwarf.set_sup(load_sections(&sup_object, endian, false, &mut decompressed_sections)?);
         }
 
-        // Create a raw pointer to the pinned storage for use in the closure.
-        // SAFETY: The Pin<Box<DecompressedSections>> ensures the data won't move.
-        // We only read from it in the closure, and the LoadedDwarf keeps it alive.
-        let decompressed_ptr = &*decompressed_sections as *const DecompressedSections;
+        let mut units = dwarf.units();
+        if units.next().map_err(|e| Error::Dwarf(e.to_string()))?.is_none() {
+            return Err(Error::NoDebugInfo);
+        }
 
-        let load_section = |id: SectionId| -> std::result::Result<DwarfSlice<'_>, gimli::Error> {
-            let section_name = id.name();
-            let zdebug_name = section_name.replace(".debug_", ".zdebug_");
+        let dwp = match &self.dwp {
+            Some(dwp) => {
+                let dwp_object = object::File::parse(&*dwp.mmap)?;
+                let mut load = |id: SectionId| -> Result<_> {
+                    let data = match id.dwo_name() {
+                        Some(name) => section_data(&dwp_object, name, &mut decompressed_sections)?,
+                        None => None,
+                    };
+                    Ok(EndianSlice::new(data.unwrap_or(&[]), endian))
+                };
+                Some(DwarfPackage::load(&mut load, EndianSlice::new(&[], endian))?)
+            }
+            None => None,
+        };
 
-            let try_load = |name: &str| -> Option<&[u8]> {
-                // SAFETY: decompressed_ptr points to pinned data that outlives this closure
-                let decompressed = unsafe { &*decompressed_ptr };
-                if let Some(slice) = decompressed.get(name) {
-                    return Some(slice);
+        let mut split_units = HashMap::new();
+        let mut dwo_mappings = Vec::new();
+        let mut dwo_files = Vec::new();
+        let mut missing = Vec::new();
+        let mut units = dwarf.units();
+        while let Some(header) = units.next().map_err(|e| Error::Dwarf(e.to_string()))? {
+            let unit = dwarf.unit(header).map_err(|e| Error::Dwarf(e.to_string()))?;
+            let Some(dwo_id) = unit.dwo_id else { continue };
+            if let Some(dwp) = &dwp {
+                if let Some(split) =
+                    dwp.find_cu(dwo_id, &dwarf).map_err(|e| Error::Dwarf(e.to_string()))?
+                {
+                    split_units.insert(dwo_id, split);
+                    continue;
                 }
+            }
 
-                // Fall back to borrowing directly from mmap for uncompressed sections
-                object.section_by_name(name).and_then(|s| s.uncompressed_data().ok()).and_then(
-                    |data| match data {
-                        Cow::Borrowed(b) => Some(b),
-                        Cow::Owned(_) => None,
-                    },
-                )
+            let Some(dwo_name) = unit
+                .dwo_name()
+                .ok()
+                .flatten()
+                .and_then(|name| dwarf.attr_string(&unit, name).ok())
+                .map(|name| name.to_string_lossy().into_owned())
+            else {
+                continue;
             };
+            let comp_dir = unit.comp_dir.map(|dir| dir.to_string_lossy().into_owned());
+            let Some(dwo) = find_dwo_file(&self.path, comp_dir.as_deref(), &dwo_name) else {
+                missing.push(dwo_name);
+                continue;
+            };
+            // SAFETY: The mapping is moved into `LoadedDwarf` alongside the `Dwarf` borrowing
+            // it; moving an `Mmap` does not move the mapped memory.
+            let data: &[u8] =
+                unsafe { std::slice::from_raw_parts(dwo.mmap.as_ptr(), dwo.mmap.len()) };
+            let dwo_object = object::File::parse(data)?;
+            let mut split = load_sections(&dwo_object, endian, true, &mut decompressed_sections)?;
+            split.make_dwo(&dwarf);
+            split_units.insert(dwo_id, split);
+            dwo_files.push(dwo.path);
+            dwo_mappings.push(dwo.mmap);
+        }
 
-            let slice = try_load(section_name).or_else(|| try_load(&zdebug_name)).unwrap_or(&[]);
-
-            Ok(EndianSlice::new(slice, endian))
-        };
-
-        let dwarf = Dwarf::load(load_section).map_err(|e| Error::Dwarf(e.to_string()))?;
-
-        let mut units = dwarf.units();
-        if units.next().map_err(|e| Error::Dwarf(e.to_string()))?.is_none() {
-            return Err(Error::NoDebugInfo);
+        if split_units.is_empty() && !missing.is_empty() {
+            return Err(Error::MissingSplitDwarf(missing.join(", ")));
         }
+        let _ = self.dwo_files.set(dwo_files);
+        let mut compressed_sections = decompressed_sections.names.clone();
+        compressed_sections.sort();
+        compressed_sections.dedup();
 
         Ok(LoadedDwarf {
             dwarf,
-            address_size: if object.is_64() { 8 } else { 4 },
+            split_units,
+            address_size: target.address_size,
             endian,
+            target,
+            target_mismatches,
+            missing_dwo: missing,
+            compressed_sections,
             _decompressed_sections: decompressed_sections,
+            _dwo_mappings: dwo_mappings,
+        })
+    }
+}
+
+/// Sections holding data objects: writable ones, and read-only ones too with `read_only`.
+fn is_data_section(kind: object::SectionKind, read_only: bool) -> bool {
+    match kind {
+        object::SectionKind::Data | object::SectionKind::UninitializedData => true,
+        object::SectionKind::ReadOnlyData | object::SectionKind::ReadOnlyDataWithRel => read_only,
+        _ => false,
+    }
+}
+
+/// Units whose header address size differs from `address_size`, one entry per size found.
+fn address_size_mismatches(dwarf: &Dwarf<DwarfSlice<'_>>, address_size: u8) -> Vec<TargetMismatch> {
+    let mut sizes: std::collections::BTreeMap<u8, usize> = std::collections::BTreeMap::new();
+    let mut units = dwarf.units();
+    while let Ok(Some(header)) = units.next() {
+        if header.address_size() != address_size {
+            *sizes.entry(header.address_size()).or_default() += 1;
+        }
+    }
+    sizes
+        .into_iter()
+        .map(|(size, units)| TargetMismatch::AddressSize {
+            target: address_size,
+            dwarf: size,
+            units,
         })
+        .collect()
+}
+
+/// Load every DWARF section of `object`, borrowing from the file's data where possible.
+/// `dwo` selects the `.dwo` section names used by split DWARF files.
+fn load_sections<'d>(
+    object: &object::File<'d>,
+    endian: RunTimeEndian,
+    dwo: bool,
+    decompressed: &mut Pin<Box<DecompressedSections>>,
+) -> Result<Dwarf<DwarfSlice<'d>>> {
+    let mut dwarf = Dwarf::load(|id: SectionId| -> Result<_> {
+        let name = if dwo { id.dwo_name() } else { Some(id.name()) };
+        let data = match name {
+            Some(name) => section_data(object, name, decompressed)?,
+            None => None,
+        };
+        Ok(EndianSlice::new(data.unwrap_or(&[]), endian))
+    })?;
+    if dwo {
+        dwarf.file_type = gimli::DwarfFileType::Dwo;
+    }
+    Ok(dwarf)
+}
+
+/// Contents of the section `name` (or its `.zdebug_` variant). Sections compressed with
+/// zlib or zstd (`SHF_COMPRESSED`, or GNU-style `.zdebug_*`) are decompressed into
+/// `decompressed`; a section that fails to decompress is an error rather than treated as absent.
+fn section_data<'d>(
+    object: &object::File<'d>,
+    name: &str,
+    decompressed: &mut Pin<Box<DecompressedSections>>,
+) -> Result<Option<&'d [u8]>> {
+    let zdebug_name = name.replacen(".debug_", ".zdebug_", 1);
+    for name in [name, zdebug_name.as_str()] {
+        let Some(section) = object.section_by_name(name) else { continue };
+        let data = section
+            .uncompressed_data()
+            .map_err(|e| Error::Dwarf(format!("Failed to decompress {}: {}", name, e)))?;
+        return Ok(Some(match data {
+            Cow::Borrowed(data) => data,
+            Cow::Owned(data) => decompressed.keep(name, data),
+        }));
+    }
+    Ok(None)
+}
+
+fn has_debug_info(object: &object::File<'_>) -> bool {
+    [".debug_info", ".zdebug_info"]
+        .into_iter()
+        .any(|name| object.section_by_name(name).is_some_and(|s| s.size() > 0))
+}
+
+/// The separate debug file for a stripped binary, tried in GDB's order: the dSYM bundle next to
+/// a Mach-O binary, then the build-id path, then the `.gnu_debuglink` name next to the binary,
+/// in its `.debug` directory, and under each debug root. Candidates whose build id, UUID, or
+/// CRC does not match the binary are skipped.
+fn find_debug_file(path: &Path, object: &object::File<'_>, roots: &[&str]) -> Option<DebugFile> {
+    let usable = |file: &DebugFile| {
+        object::File::parse(&*file.mmap).is_ok_and(|debug| has_debug_info(&debug))
+    };
+
+    if let (Some(name), Ok(Some(uuid))) = (path.file_name(), object.mach_uuid()) {
+        let mut bundle = path.as_os_str().to_owned();
+        bundle.push(".dSYM");
+        let candidate = Path::new(&bundle).join("Contents/Resources/DWARF").join(name);
+        if let Some(file) = DebugFile::open(&candidate) {
+            let same = object::File::parse(&*file.mmap)
+                .is_ok_and(|debug| debug.mach_uuid().ok().flatten().is_none_or(|u| u == uuid));
+            if same && usable(&file) {
+                return Some(file);
+            }
+        }
+    }
+
+    if let Ok(Some(build_id)) = object.build_id() {
+        for root in roots {
+            let Some(file) = DebugFile::open(&build_id_path(Path::new(root), build_id)) else {
+                continue;
+            };
+            let same = object::File::parse(&*file.mmap)
+                .is_ok_and(|debug| debug.build_id().ok().flatten() == Some(build_id));
+            if same && usable(&file) {
+                return Some(file);
+            }
+        }
+    }
+
+    if let Ok(Some((name, crc))) = object.gnu_debuglink() {
+        let name = Path::new(std::str::from_utf8(name).ok()?);
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let absolute = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        let mut candidates = vec![dir.join(name), dir.join(".debug").join(name)];
+        for root in roots {
+            let relative = absolute.strip_prefix("/").unwrap_or(&absolute);
+            candidates.push(Path::new(root).join(relative).join(name));
+        }
+        for candidate in candidates {
+            if candidate == path {
+                continue;
+            }
+            let Some(file) = DebugFile::open(&candidate) else { continue };
+            if crc32(&file.mmap) == crc && usable(&file) {
+                return Some(file);
+            }
+        }
+    }
+
+    None
+}
+
+/// The supplementary file named by `.gnu_debugaltlink` (checked by build id, or found by it
+/// under a debug root) or by a DWARF 5 `.debug_sup` section. Relative names are resolved
+/// against the directory of the file naming them.
+fn find_sup_file(
+    debug_path: &Path,
+    object: &object::File<'_>,
+    roots: &[&str],
+) -> Option<DebugFile> {
+    let dir = debug_path.parent().unwrap_or(Path::new(""));
+
+    if let Ok(Some((name, build_id))) = object.gnu_debugaltlink() {
+        let mut candidates = Vec::new();
+        if let Ok(name) = std::str::from_utf8(name) {
+            candidates.push(dir.join(name));
+        }
+        candidates.extend(roots.iter().map(|root| build_id_path(Path::new(root), build_id)));
+        return candidates.into_iter().filter_map(|path| DebugFile::open(&path)).find(|file| {
+            object::File::parse(&*file.mmap)
+                .is_ok_and(|sup| sup.build_id().ok().flatten() == Some(build_id))
+        });
+    }
+
+    // .debug_sup: version (u16), is_supplementary (u8), NUL-terminated file name, checksum.
+    let data = object.section_by_name(".debug_sup")?.uncompressed_data().ok()?;
+    let (&is_supplementary, rest) = data.get(2..)?.split_first()?;
+    if is_supplementary != 0 {
+        return None;
+    }
+    let name = rest.split(|&b| b == 0).next().filter(|name| !name.is_empty())?;
+    DebugFile::open(&dir.join(std::str::from_utf8(name).ok()?))
+}
+
+/// A split unit's `.dwo` file: `dwo_name` under the unit's compilation directory, else next to
+/// the binary (for builds moved after compiling).
+fn find_dwo_file(binary: &Path, comp_dir: Option<&str>, dwo_name: &str) -> Option<DebugFile> {
+    let name = Path::new(dwo_name);
+    let mut candidates = Vec::new();
+    if let Some(dir) = comp_dir {
+        candidates.push(Path::new(dir).join(name));
+    } else {
+        candidates.push(name.to_path_buf());
     }
+    if let Some(file_name) = name.file_name() {
+        candidates.push(binary.parent().unwrap_or(Path::new("")).join(file_name));
+    }
+    candidates.iter().find_map(|path| DebugFile::open(path))
+}
+
+/// `<root>/.build-id/xx/yyyy.debug` for build id `xxyyyy`.
+fn build_id_path(root: &Path, build_id: &[u8]) -> PathBuf {
+    let hex: String = build_id.iter().map(|b| format!("{:02x}", b)).collect();
+    let (head, tail) = hex.split_at(hex.len().min(2));
+    root.join(".build-id").join(head).join(format!("{}.debug", tail))
 }
 
-/// Leak a section name string to get a 'static lifetime.
-/// This is acceptable because we only call this for a fixed set of ~26 section names.
-fn leak_section_name(name: &str) -> &'static str {
-    // Use a simple cache to avoid leaking duplicates
-    use std::sync::OnceLock;
-    static CACHE: OnceLock<std::sync::Mutex<HashMap<String, &'static str>>> = OnceLock::new();
+/// CRC-32 (IEEE), as stored in `.gnu_debuglink`.
+fn crc32(data: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0u32; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    !data.iter().fold(!0u32, |crc, &b| TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::write_object;
+
+    #[test]
+    fn crc32_matches_zlib() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn corrupt_compressed_section_is_an_error() {
+        use object::write::Object as WriteObject;
+        use object::{Architecture, BinaryFormat, Endianness, SectionFlags, SectionKind, elf};
 
-    let cache = CACHE.get_or_init(|| std::sync::Mutex::new(HashMap::new()));
-    // Recover from poisoned lock - cache is just an optimization, safe to continue
-    let mut guard = cache.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = WriteObject::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+        let id = out.add_section(Vec::new(), b".debug_info".to_vec(), SectionKind::Debug);
+        // Elf64_Chdr claiming 64 bytes of zlib data, followed by bytes that are not zlib.
+        let mut data = Vec::new();
+        data.extend_from_slice(&elf::ELFCOMPRESS_ZLIB.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&64u64.to_le_bytes());
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.extend_from_slice(b"not zlib data");
+        out.set_section_data(id, data, 1);
+        out.section_mut(id).flags = SectionFlags::Elf { sh_flags: elf::SHF_COMPRESSED.into() };
 
-    if let Some(&cached) = guard.get(name) {
-        return cached;
+        let file = write_object(out);
+        let binary = BinaryData::load(file.path()).unwrap();
+        match binary.load_dwarf() {
+            Err(Error::Dwarf(msg)) => assert!(msg.contains("decompress .debug_info"), "{}", msg),
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("corrupt section loaded"),
+        }
     }
 
-    let leaked: &'static str = Box::leak(name.to_string().into_boxed_str());
-    guard.insert(name.to_string(), leaked);
-    leaked
+    #[test]
+    fn dwarf_that_disagrees_with_the_header_is_reported() {
+        use crate::target::Endian;
+        use object::write::Object as WriteObject;
+        use object::{Architecture, BinaryFormat, Endianness, SectionKind};
+
+        // A big-endian ARM image whose one DWARF 4 unit is little-endian with 2-byte addresses.
+        let mut out = WriteObject::new(BinaryFormat::Elf, Architecture::Arm, Endianness::Big);
+        let mut unit = Vec::new();
+        unit.extend_from_slice(&8u32.to_le_bytes());
+        unit.extend_from_slice(&4u16.to_le_bytes());
+        unit.extend_from_slice(&0u32.to_le_bytes());
+        // Address size, then a childless DW_TAG_compile_unit.
+        unit.extend_from_slice(&[2, 1]);
+        let id = out.add_section(Vec::new(), b".debug_info".to_vec(), SectionKind::Debug);
+        out.set_section_data(id, unit, 1);
+        let id = out.add_section(Vec::new(), b".debug_abbrev".to_vec(), SectionKind::Debug);
+        out.set_section_data(id, vec![1, gimli::DW_TAG_compile_unit.0 as u8, 0, 0, 0, 0], 1);
+
+        let file = write_object(out);
+        let binary = BinaryData::load(file.path()).unwrap();
+        let loaded = binary.load_dwarf().unwrap();
+
+        assert_eq!(loaded.target.triple, "armeb-unknown-none");
+        assert_eq!(loaded.endian, RunTimeEndian::Little);
+        assert_eq!(
+            loaded.target_mismatches,
+            vec![
+                TargetMismatch::Endian { target: Endian::Big, dwarf: Endian::Little },
+                TargetMismatch::AddressSize { target: 4, dwarf: 2, units: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn build_id_path_splits_first_byte() {
+        assert_eq!(
+            build_id_path(Path::new("/usr/lib/debug"), &[0xab, 0xcd, 0x01]),
+            Path::new("/usr/lib/debug/.build-id/ab/cd01.debug")
+        );
+    }
 }
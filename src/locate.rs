@@ -0,0 +1,253 @@
+//! Which member of a struct holds a given byte: "what field is at offset 0x1c of Foo", for
+//! debugging memory corruption from a faulting address or a watchpoint hit.
+
+use crate::error::{Error, Result};
+use crate::names::{canonical_name, embedded_type};
+use crate::types::{PaddingHole, StructLayout};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Struct nesting followed at most; only malformed debug info nests deeper.
+const MAX_DEPTH: usize = 64;
+
+/// Where a byte offset falls within a struct.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldLocation {
+    /// The struct the offset was given for.
+    pub type_name: String,
+    pub type_size: u64,
+    pub offset: u64,
+    /// Members holding the byte, from the outer struct inward through nested structs and
+    /// array elements. Empty when the byte is padding of the outer struct itself.
+    pub path: Vec<FieldStep>,
+    /// The padding hole holding the byte, when no member of the innermost struct does.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub padding: Option<PaddingHole>,
+}
+
+/// One member on the way to a byte.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct FieldStep {
+    /// The struct this member belongs to.
+    pub parent: String,
+    pub name: String,
+    pub type_name: String,
+    /// Offset from the start of the outer struct.
+    pub offset: u64,
+    pub size: u64,
+    /// Element holding the byte, when the member is an array.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub index: Option<u64>,
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub bit_offset: Option<u64>,
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub bit_size: Option<u64>,
+}
+
+impl FieldLocation {
+    /// The member path as an expression: `inner.items[2].x`, or `<padding>` for padding of the
+    /// outer struct.
+    pub fn field_path(&self) -> String {
+        let mut path = String::new();
+        for step in &self.path {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&step.name);
+            if let Some(index) = step.index {
+                path.push_str(&format!("[{}]", index));
+            }
+        }
+        if path.is_empty() { "<padding>".to_string() } else { path }
+    }
+}
+
+/// Resolve `offset` within the struct named `type_name` (its full name, or an unqualified name
+/// only one struct has) among `layouts`. Members of struct type and arrays of structs are
+/// followed inward; where members overlap (unions, bitfields sharing storage), the first one
+/// declared whose bytes, or bits, include the offset is reported.
+pub fn resolve_field(
+    layouts: &[StructLayout],
+    type_name: &str,
+    offset: u64,
+) -> Result<FieldLocation> {
+    let by_name: HashMap<&str, &StructLayout> =
+        layouts.iter().map(|l| (l.name.as_str(), l)).collect();
+    let root = match by_name.get(type_name) {
+        Some(layout) => *layout,
+        None => {
+            let mut matches = layouts.iter().filter(|l| canonical_name(&l.name) == type_name);
+            let first = matches.next();
+            match (first, matches.find(|l| first.is_some_and(|f| f.name != l.name))) {
+                (Some(layout), None) => layout,
+                (Some(_), Some(_)) => {
+                    return Err(Error::Locate(format!(
+                        "'{}' names several structs; give the qualified name",
+                        type_name
+                    )));
+                }
+                (None, _) => {
+                    return Err(Error::Locate(format!(
+                        "no struct named '{}' in the debug info",
+                        type_name
+                    )));
+                }
+            }
+        }
+    };
+    if offset >= root.size {
+        return Err(Error::Locate(format!(
+            "offset {:#x} is past the end of {} ({} bytes)",
+            offset, root.name, root.size
+        )));
+    }
+
+    let mut location = FieldLocation {
+        type_name: root.name.clone(),
+        type_size: root.size,
+        offset,
+        path: Vec::new(),
+        padding: None,
+    };
+    let mut layout = root;
+    let mut base = 0;
+    let mut relative = offset;
+    for _ in 0..MAX_DEPTH {
+        let Some(member) = layout.members.iter().find(|m| {
+            let (Some(start), Some(size)) = (m.offset, m.size) else { return false };
+            if !(start..start.saturating_add(size)).contains(&relative) {
+                return false;
+            }
+            match (m.bit_offset, m.bit_size) {
+                (Some(bit_offset), Some(bit_size)) => {
+                    let byte_bit = (relative - start) * 8;
+                    bit_offset < byte_bit + 8 && byte_bit < bit_offset + bit_size
+                }
+                _ => true,
+            }
+        }) else {
+            location.padding = Some(hole(layout, base, relative));
+            break;
+        };
+        let (Some(start), Some(size)) = (member.offset, member.size) else { break };
+
+        let mut within = relative - start;
+        let mut element_size = size;
+        let index = array_len(&member.type_name).filter(|&n| n > 0 && size >= n).map(|n| {
+            element_size = size / n;
+            let index = within / element_size;
+            within %= element_size;
+            index
+        });
+        location.path.push(FieldStep {
+            parent: layout.name.clone(),
+            name: member.name.clone(),
+            type_name: member.type_name.clone(),
+            offset: base + start,
+            size,
+            index,
+            bit_offset: member.bit_offset,
+            bit_size: member.bit_size,
+        });
+
+        let inner = embedded_type(&member.type_name)
+            .and_then(|t| by_name.get(t))
+            .filter(|inner| inner.size == element_size && member.bit_size.is_none());
+        let Some(inner) = inner else { break };
+        base += start + index.unwrap_or(0) * element_size;
+        relative = within;
+        layout = inner;
+    }
+    Ok(location)
+}
+
+/// The hole of `layout` holding `relative`, as offsets from the outer struct.
+fn hole(layout: &StructLayout, base: u64, relative: u64) -> PaddingHole {
+    let before = layout
+        .members
+        .iter()
+        .filter_map(|m| Some((m, m.end_offset()?)))
+        .filter(|&(_, end)| end <= relative)
+        .max_by_key(|&(_, end)| end);
+    let start = before.map_or(0, |(_, end)| end);
+    let end = layout
+        .members
+        .iter()
+        .filter_map(|m| m.offset)
+        .filter(|&o| o > relative)
+        .min()
+        .unwrap_or(layout.size);
+    PaddingHole {
+        offset: base + start,
+        size: end - start,
+        after_member: before.map(|(m, _)| m.name.clone()),
+    }
+}
+
+/// Element count of an array type (`[int; 4]`), of its outer dimension when nested.
+fn array_len(type_name: &str) -> Option<u64> {
+    let inner = type_name.strip_prefix('[')?.strip_suffix(']')?;
+    inner.rsplit_once("; ")?.1.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    fn member(name: &str, type_name: &str, offset: u64, size: u64) -> MemberLayout {
+        MemberLayout::new(name.to_string(), type_name.to_string(), Some(offset), Some(size))
+    }
+
+    fn layouts() -> Vec<StructLayout> {
+        let mut point = StructLayout::new("geo::Point".to_string(), 8, Some(4));
+        point.members = vec![member("x", "int", 0, 4), member("y", "int", 4, 4)];
+        let mut shape = StructLayout::new("Shape".to_string(), 40, Some(8));
+        shape.members = vec![
+            member("kind", "char", 0, 1),
+            member("points", "[geo::Point; 3]", 4, 24),
+            member("area", "double", 32, 8),
+        ];
+        vec![point, shape]
+    }
+
+    #[test]
+    fn offsets_resolve_through_arrays_of_structs() {
+        let location = resolve_field(&layouts(), "Shape", 0x18).unwrap();
+        assert_eq!(location.field_path(), "points[2].y");
+        let last = location.path.last().unwrap();
+        assert_eq!((last.parent.as_str(), last.offset, last.size), ("geo::Point", 24, 4));
+        assert!(location.padding.is_none());
+
+        // Unqualified names work when only one struct has them.
+        let location = resolve_field(&layouts(), "Point", 3).unwrap();
+        assert_eq!(location.field_path(), "x");
+    }
+
+    #[test]
+    fn padding_and_bad_offsets() {
+        let location = resolve_field(&layouts(), "Shape", 2).unwrap();
+        assert_eq!(location.field_path(), "<padding>");
+        let hole = location.padding.unwrap();
+        assert_eq!((hole.offset, hole.size, hole.after_member.as_deref()), (1, 3, Some("kind")));
+
+        let err = resolve_field(&layouts(), "Shape", 40).unwrap_err();
+        assert!(err.to_string().contains("offset 0x28 is past the end of Shape (40 bytes)"));
+        assert!(resolve_field(&layouts(), "Missing", 0).is_err());
+    }
+
+    #[test]
+    fn bitfields_resolve_by_bit_range() {
+        let bit = |name: &str, bit_offset, bit_size| {
+            let mut m = member(name, "unsigned int", 0, 4);
+            m.bit_offset = Some(bit_offset);
+            m.bit_size = Some(bit_size);
+            m
+        };
+        let mut flags = StructLayout::new("Flags".to_string(), 4, Some(4));
+        flags.members = vec![bit("low", 0, 4), bit("high", 12, 20)];
+        let layouts = [flags];
+        assert_eq!(resolve_field(&layouts, "Flags", 0).unwrap().field_path(), "low");
+        assert_eq!(resolve_field(&layouts, "Flags", 2).unwrap().field_path(), "high");
+    }
+}
@@ -0,0 +1,138 @@
+//! `layout-audit.lock`: accepted struct sizes that `check` does not let grow.
+//!
+//! The file is YAML sorted by struct name, so accepting a change produces a small, reviewable
+//! diff. `check --accept` rewrites it with the current sizes; every other `check` run fails for
+//! structs that grew past their recorded size.
+
+use crate::error::{Error, Result};
+use crate::types::StructLayout;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Lockfile `check` uses when no other path is given.
+pub const DEFAULT_LOCKFILE: &str = "layout-audit.lock";
+
+const LOCK_VERSION: u32 = 1;
+
+const HEADER: &str = "# Accepted struct sizes in bytes, written by `layout-audit check --accept`.\n\
+                      # Structs may shrink freely; growing past these sizes fails the check.\n";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeLock {
+    pub version: u32,
+    /// Struct name -> accepted size in bytes.
+    #[serde(default)]
+    pub sizes: BTreeMap<String, u64>,
+}
+
+/// A struct whose size differs from the lock.
+#[derive(Debug, Clone, Copy)]
+pub struct LockedSizeChange<'a> {
+    pub layout: &'a StructLayout,
+    pub locked: u64,
+}
+
+impl LockedSizeChange<'_> {
+    pub fn delta(&self) -> i128 {
+        i128::from(self.layout.size) - i128::from(self.locked)
+    }
+}
+
+impl SizeLock {
+    /// Lock the current sizes. Structs sharing a name record the largest size.
+    pub fn from_layouts(layouts: &[StructLayout]) -> Self {
+        let mut sizes: BTreeMap<String, u64> = BTreeMap::new();
+        for layout in layouts {
+            let size = sizes.entry(layout.name.clone()).or_default();
+            *size = (*size).max(layout.size);
+        }
+        Self { version: LOCK_VERSION, sizes }
+    }
+
+    /// Read a lockfile; `Ok(None)` if it does not exist.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text)
+                .map(Some)
+                .map_err(|e| Error::Lockfile(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let lock: SizeLock =
+            serde_yaml::from_str(text).map_err(|e| Error::Lockfile(e.to_string()))?;
+        if lock.version != LOCK_VERSION {
+            return Err(Error::Lockfile(format!(
+                "unsupported lockfile version {} (expected {})",
+                lock.version, LOCK_VERSION
+            )));
+        }
+        Ok(lock)
+    }
+
+    pub fn render(&self) -> String {
+        // Serializing a map of strings and integers cannot fail.
+        let body = serde_yaml::to_string(self).unwrap_or_default();
+        format!("{}{}", HEADER, body)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.render())?;
+        Ok(())
+    }
+
+    pub fn locked_size(&self, name: &str) -> Option<u64> {
+        self.sizes.get(name).copied()
+    }
+
+    /// Layouts whose size differs from their locked size, in `layouts` order. Structs missing
+    /// from the lock are not reported.
+    pub fn changes<'a>(&self, layouts: &'a [StructLayout]) -> Vec<LockedSizeChange<'a>> {
+        layouts
+            .iter()
+            .filter_map(|layout| {
+                let locked = self.locked_size(&layout.name)?;
+                (locked != layout.size).then_some(LockedSizeChange { layout, locked })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(name: &str, size: u64) -> StructLayout {
+        StructLayout::new(name.to_string(), size, None)
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let lock =
+            SizeLock::from_layouts(&[layout("b::Node", 16), layout("A", 8), layout("A", 24)]);
+        assert_eq!(lock.locked_size("A"), Some(24));
+
+        let text = lock.render();
+        assert!(text.starts_with('#'));
+        assert!(text.find("A:").unwrap() < text.find("b::Node").unwrap());
+        assert_eq!(SizeLock::parse(&text).unwrap(), lock);
+    }
+
+    #[test]
+    fn reports_size_changes() {
+        let lock = SizeLock::from_layouts(&[layout("Grew", 8), layout("Shrank", 32)]);
+        let now = [layout("Grew", 16), layout("Shrank", 24), layout("New", 64)];
+        let deltas: Vec<(&str, i128)> =
+            lock.changes(&now).iter().map(|c| (c.layout.name.as_str(), c.delta())).collect();
+        assert_eq!(deltas, vec![("Grew", 8), ("Shrank", -8)]);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        assert!(SizeLock::parse("version: 2\nsizes: {}\n").is_err());
+        assert!(SizeLock::parse("not a lock").is_err());
+    }
+}
@@ -1,365 +1,2347 @@
 use anyhow::{Context, Result, bail};
 use clap::Parser;
+use layout_audit::attest::{CheckResults, Statement, Subject};
+use layout_audit::pager::Pager;
+use layout_audit::pipeline::{
+    self, BinaryScan, DiffBaseline, DiffOptions, InspectOptions, Notice, ScanOptions,
+    expand_binary_paths, record_binary,
+};
+use layout_audit::yaml::{self, PathSegment};
 use layout_audit::{
-    BinaryData, CheckViolation, CheckViolationKind, Cli, Commands, DwarfContext, JsonFormatter,
-    OutputFormat, SarifFormatter, SortField, SuggestJsonFormatter, SuggestTableFormatter,
-    TableFormatter, analyze_false_sharing, analyze_layout, diff_layouts, optimize_layout,
+    ALLOW_ALL, AllocatorModel, AnnotateJsonFormatter, AnnotateTableFormatter, AssertionLang,
+    AtomicPatterns, BinaryData, BudgetMatch, CheckViolation, CheckViolationKind, Cli,
+    CodeFormatter, CodeLanguage, CodeOwners, Column, Commands, CsvFormatter, Date, DebugInfo,
+    DepfileFormatter, DeterminismJsonFormatter, DeterminismReport, DeterminismRun,
+    DeterminismTableFormatter, DwarfContext, DwarfFeatures, ExportFormat, FieldAccessReport,
+    Forecast, ForecastJsonFormatter, ForecastTableFormatter, GlobalFalseSharingDiff,
+    GoAssertionFormatter, GraphDotFormatter, GraphFormat, GraphJsonFormatter, HeaderFormatter,
+    IgnoreEntry, IgnoreList, IgnoredViolation, JsonFormatter, JsonStyle, LocateJsonFormatter,
+    LocateTableFormatter, MemberChangeKind, MemberLayout, OptimizeStrategy, OptimizedLayout,
+    OutputFormat, Placement, PlacementJsonFormatter, PlacementTableFormatter, REPRO_DEBUG_OBJECT,
+    ReportProfile, ReproBundle, RustcLayout, SampleSet, SarifFormatter, ScanStats,
+    SchemaJsonFormatter, SchemaMessage, SchemaTableFormatter, Severity, SizeLock, Snapshot,
+    SortField, SourceSuppression, StructChange, StructLayout, StructScope, SuggestJsonFormatter,
+    SuggestTableFormatter, SuppressionScanner, TableFormatter, TargetsJsonFormatter,
+    TargetsTableFormatter, ThreadOwners, TypeSizes, VerifyJsonFormatter, VerifyTableFormatter,
+    WatchAction, analyze_false_sharing_with_patterns, analyze_layout, attribute_accesses,
+    canonical_name, check_schema, compare_builds, compare_targets, containment_graph, diff_layouts,
+    embedded_type, extract_debug_object, is_shadowed, locality_front, near_misses, optimize_layout,
+    optimize_layout_with_strategy, over_fetch, overlap_example, packed_regions, prefix_groups,
+    simulate_access, static_access_sequence, strategy_disagreement, verify_layouts,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+#[path = "test_support/fixtures.rs"]
+mod fixtures;
 
 /// Configuration for the inspect command
 struct InspectConfig<'a> {
-    binary_path: &'a Path,
+    binary_paths: &'a [PathBuf],
     filter: Option<&'a str>,
+    filter_regex: Option<&'a str>,
+    member_filter: Option<&'a str>,
     output_format: OutputFormat,
+    /// With CSV output, one row per member instead of per struct.
+    csv_members: bool,
+    sarif_max_results: Option<usize>,
     sort_by: SortField,
     top: Option<usize>,
     min_padding: Option<u64>,
     no_color: bool,
+    no_pager: bool,
+    cache_line_size: u32,
+    pretty: bool,
+    json_style: JsonStyle,
+    warn_false_sharing: bool,
+    global_false_sharing: bool,
+    atomic_patterns_file: Option<&'a Path>,
+    fail_on_partial: bool,
+    include_partial: bool,
+    cache_heatmap: bool,
+    by_file: bool,
+    by_namespace: bool,
+    namespace_depth: Option<usize>,
+    collapse_templates: bool,
+    summary: bool,
+    codeowners: Option<&'a Path>,
+    layout_map: bool,
+    columns: Option<&'a [Column]>,
+    summary_only: bool,
+    flatten_bases: bool,
+    bits: bool,
+    page_size: Option<usize>,
+    allocator: Option<AllocatorModel>,
+    static_access: bool,
+    usage: bool,
+    static_instances: bool,
+    unit_timeout: Option<std::time::Duration>,
+    time_budget: Option<TimeBudget>,
+    stats: bool,
+    include_go_runtime: bool,
+    exported_only: bool,
+    sort_stable: bool,
+    export_sqlite: Option<&'a Path>,
+    repro_bundle: Option<&'a Path>,
+    redact_strings: bool,
+    config_path: Option<&'a Path>,
+    depfile: Option<DepfileConfig<'a>>,
+}
+
+/// Configuration for the diff command
+struct DiffConfig<'a> {
+    old: DiffBaseline<'a>,
+    new_path: &'a Path,
+    filter: Option<&'a str>,
+    filter_regex: Option<&'a str>,
+    member_filter: Option<&'a str>,
+    output_format: OutputFormat,
+    sarif_max_results: Option<usize>,
+    cache_line_size: u32,
+    fail_on_regression: bool,
+    /// Lowest `new_struct_defaults` severity that counts as a failure.
+    fail_on: Severity,
+    profile: Option<ReportProfile>,
+    global_false_sharing: bool,
+    atomic_patterns_file: Option<&'a Path>,
+    include_go_runtime: bool,
+    exported_only: bool,
+    sort_stable: bool,
+    config_path: Option<&'a Path>,
+    depfile: Option<DepfileConfig<'a>>,
+}
+
+/// Configuration for the snapshot command
+struct SnapshotConfig<'a> {
+    binary_path: &'a Path,
+    output: Option<&'a Path>,
+    filter: Option<&'a str>,
+    include_go_runtime: bool,
+    exported_only: bool,
+    config_path: Option<&'a Path>,
+    pretty: bool,
+    depfile: Option<DepfileConfig<'a>>,
+}
+
+/// Configuration for the compare-builds command
+struct CompareBuildsConfig<'a> {
+    debug_path: &'a Path,
+    release_path: &'a Path,
+    filter: Option<&'a str>,
+    output_format: OutputFormat,
+    sarif_max_results: Option<usize>,
+    cache_line_size: u32,
+    include_go_runtime: bool,
+    exported_only: bool,
+    sort_stable: bool,
+    config_path: Option<&'a Path>,
+    depfile: Option<DepfileConfig<'a>>,
+}
+
+/// Configuration for the compare-targets command
+struct CompareTargetsConfig<'a> {
+    old_path: &'a Path,
+    new_path: &'a Path,
+    config_path: Option<&'a Path>,
+    filter: Option<&'a str>,
+    output_format: OutputFormat,
+    cache_line_size: u32,
+    pretty: bool,
+    no_color: bool,
+    include_go_runtime: bool,
+}
+
+/// Configuration for the check command
+struct CheckConfig<'a> {
+    binary_paths: &'a [PathBuf],
+    config_path: &'a Path,
+    profile_path: Option<&'a Path>,
+    previous_path: Option<&'a Path>,
+    atomic_patterns_file: Option<&'a Path>,
+    list_suppressions: bool,
+    lint_config: bool,
+    validate_config_only: bool,
+    lockfile_path: &'a Path,
+    accept: bool,
+    budget_match: BudgetMatch,
+    output_format: OutputFormat,
+    sarif_max_results: Option<usize>,
+    /// Lowest budget severity that fails the check.
+    fail_on: Severity,
+    cache_line_size: u32,
+    include_go_runtime: bool,
+    exported_only: bool,
+    sort_stable: bool,
+    time_budget: Option<TimeBudget>,
+    depfile: Option<DepfileConfig<'a>>,
+    attestation: Option<AttestationConfig<'a>>,
+}
+
+/// Configuration for the suggest command
+struct SuggestConfig<'a> {
+    binary_path: &'a Path,
+    filter: Option<&'a str>,
+    filter_regex: Option<&'a str>,
+    member_filter: Option<&'a str>,
+    output_format: OutputFormat,
+    sarif_max_results: Option<usize>,
+    min_savings: Option<u64>,
+    cache_line_size: u32,
+    pretty: bool,
+    max_align: u64,
+    sort_by_savings: bool,
+    strategy: OptimizeStrategy,
+    static_access: bool,
+    usage: bool,
+    locality: bool,
+    size_cap: Option<u64>,
+    no_color: bool,
+    include_go_runtime: bool,
+    exported_only: bool,
+    sort_stable: bool,
+    config_path: Option<&'a Path>,
+    emit_code: Option<CodeLanguage>,
+    depfile: Option<DepfileConfig<'a>>,
+}
+
+/// Configuration for the verify command
+struct SchemaCheckConfig<'a> {
+    schema_path: &'a Path,
+    binary_path: &'a Path,
+    config_path: Option<&'a Path>,
+    filter: Option<&'a str>,
+    output_format: OutputFormat,
+    pretty: bool,
+    no_color: bool,
+    include_go_runtime: bool,
+}
+
+struct VerifyConfig<'a> {
+    binary_path: &'a Path,
+    rustc_sizes_path: &'a Path,
+    filter: Option<&'a str>,
+    output_format: OutputFormat,
+    pretty: bool,
+    no_color: bool,
+}
+
+/// Configuration for the verify-determinism command
+struct VerifyDeterminismConfig<'a> {
+    binary_paths: &'a [PathBuf],
+    runs: u32,
+    config_path: Option<&'a Path>,
+    filter: Option<&'a str>,
+    output_format: OutputFormat,
+    pretty: bool,
+    no_color: bool,
+    include_go_runtime: bool,
+}
+
+/// Configuration for the forecast command
+struct ForecastConfig<'a> {
+    binary_path: &'a Path,
+    instances_path: &'a Path,
+    history: &'a [PathBuf],
+    top: usize,
+    horizon: usize,
+    filter: Option<&'a str>,
+    config_path: Option<&'a Path>,
+    max_align: u64,
+    cache_line_size: u32,
+    output_format: OutputFormat,
+    pretty: bool,
+    no_color: bool,
+}
+
+/// Configuration for the placement command
+struct PlacementConfig<'a> {
+    binary_path: &'a Path,
+    config_path: Option<&'a Path>,
+    filter: Option<&'a str>,
+    lang: CodeLanguage,
+    cache_line_size: u32,
+    output_format: OutputFormat,
+    pretty: bool,
+    no_color: bool,
+    include_go_runtime: bool,
+}
+
+/// Configuration for the annotate command
+struct AnnotateConfig<'a> {
+    binary_path: &'a Path,
+    samples_path: &'a Path,
+    load_bias: Option<u64>,
+    filter: Option<&'a str>,
+    output_format: OutputFormat,
+    pretty: bool,
+    no_color: bool,
+    emit_config: Option<&'a Path>,
+}
+
+/// Configuration for the locate command
+struct LocateConfig<'a> {
+    binary_path: &'a Path,
+    type_name: &'a str,
+    offset: u64,
+    output_format: OutputFormat,
+    pretty: bool,
+    no_color: bool,
+}
+
+/// Configuration for the emit-assertions command
+struct EmitAssertionsConfig<'a> {
+    binary_path: &'a Path,
+    lang: AssertionLang,
+    go_package: &'a str,
+    package_name: Option<&'a str>,
+    filter: Option<&'a str>,
+    config_path: Option<&'a Path>,
+    out: Option<&'a Path>,
+    depfile: Option<DepfileConfig<'a>>,
+}
+
+/// Configuration for the emit-header command
+struct EmitHeaderConfig<'a> {
+    binary_path: &'a Path,
+    filter: Option<&'a str>,
+    config_path: Option<&'a Path>,
+    out: Option<&'a Path>,
+    guard: Option<&'a str>,
+    depfile: Option<DepfileConfig<'a>>,
+}
+
+/// Configuration for the watch command
+/// Configuration for the tui command
+#[cfg_attr(not(feature = "tui"), allow(dead_code))]
+struct TuiConfig<'a> {
+    binary_path: &'a Path,
+    config_path: Option<&'a Path>,
+    filter: Option<&'a str>,
+    cache_line_size: u32,
+    max_align: u64,
+    include_go_runtime: bool,
+}
+
+/// Configuration for the summary command
+struct SummaryConfig<'a> {
+    binary_paths: &'a [PathBuf],
+    config_path: Option<&'a Path>,
+    filter: Option<&'a str>,
+    output_format: OutputFormat,
     cache_line_size: u32,
     pretty: bool,
+    no_color: bool,
+    include_go_runtime: bool,
+}
+
+/// Configuration for the graph command
+struct GraphConfig<'a> {
+    binary_paths: &'a [PathBuf],
+    config_path: Option<&'a Path>,
+    filter: Option<&'a str>,
+    format: GraphFormat,
+    pretty: bool,
+    include_go_runtime: bool,
+}
+
+struct ExportConfig<'a> {
+    format: ExportFormat,
+    out: &'a Path,
+    binary_paths: &'a [PathBuf],
+    config_path: Option<&'a Path>,
+    filter: Option<&'a str>,
+    cache_line_size: u32,
+    include_go_runtime: bool,
+}
+
+struct WatchConfig<'a> {
+    path: &'a Path,
+    bin: Option<&'a str>,
+    action: WatchAction,
+    filter: Option<&'a str>,
+    config_path: Option<&'a Path>,
+    debounce: Duration,
+    cache_line_size: u32,
+    include_go_runtime: bool,
+}
+
+/// Structs an `exec` report lists by name.
+const EXEC_TOP_OFFENDERS: usize = 10;
+
+/// What a `--report-profile` presets for inspect. Flags given explicitly take precedence:
+/// values replace the profile's, switches add to it.
+struct InspectProfile {
+    sort_by: SortField,
+    top: Option<usize>,
+    min_padding: Option<u64>,
+    no_color: bool,
+    layout_map: bool,
     warn_false_sharing: bool,
+    fail_on_partial: bool,
+    summary_only: bool,
+}
+
+impl InspectProfile {
+    fn new(profile: Option<ReportProfile>) -> Self {
+        let defaults = Self {
+            sort_by: SortField::Name,
+            top: None,
+            min_padding: None,
+            no_color: false,
+            layout_map: false,
+            warn_false_sharing: false,
+            fail_on_partial: false,
+            summary_only: false,
+        };
+        match profile {
+            None => defaults,
+            Some(ReportProfile::Dev) => {
+                Self { layout_map: true, warn_false_sharing: true, ..defaults }
+            }
+            Some(ReportProfile::Ci) => Self {
+                sort_by: SortField::Padding,
+                min_padding: Some(1),
+                no_color: true,
+                fail_on_partial: true,
+                ..defaults
+            },
+            Some(ReportProfile::Exec) => Self {
+                sort_by: SortField::Padding,
+                top: Some(EXEC_TOP_OFFENDERS),
+                summary_only: true,
+                ..defaults
+            },
+        }
+    }
+}
+
+/// Where to write a Makefile-style depfile, and the rule target to use
+#[derive(Clone, Copy)]
+struct DepfileConfig<'a> {
+    path: &'a Path,
+    target: Option<&'a str>,
+}
+
+impl<'a> DepfileConfig<'a> {
+    fn from_args(path: Option<&'a Path>, target: Option<&'a str>) -> Option<Self> {
+        path.map(|path| Self { path, target })
+    }
+
+    fn write(&self, inputs: &[PathBuf]) -> Result<()> {
+        let target = self
+            .target
+            .map(str::to_string)
+            .unwrap_or_else(|| DepfileFormatter::default_target(self.path));
+        let content = DepfileFormatter::new(target).format(inputs);
+        std::fs::write(self.path, content)
+            .with_context(|| format!("Failed to write depfile: {}", self.path.display()))
+    }
+}
+
+/// Exit status when analysis runs past `--budget-time`.
+const EXIT_OUT_OF_TIME: i32 = 3;
+
+/// `--budget-time`: when analysis has to be done by, and whether to report what it got
+/// through if it is not.
+#[derive(Clone, Copy)]
+struct TimeBudget {
+    deadline: Instant,
+    limit: Duration,
+    emit_partial: bool,
+}
+
+impl TimeBudget {
+    fn from_args(limit: Option<Duration>, emit_partial: bool) -> Option<Self> {
+        limit.map(|limit| Self { deadline: Instant::now() + limit, limit, emit_partial })
+    }
+
+    fn expired(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+}
+
+/// Analysis ran past `--budget-time`; the process exits with [`EXIT_OUT_OF_TIME`].
+#[derive(Debug, Clone, Copy)]
+struct OutOfTime(Duration);
+
+impl std::fmt::Display for OutOfTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Analysis exceeded --budget-time of {:.1}s", self.0.as_secs_f64())
+    }
+}
+
+impl std::error::Error for OutOfTime {}
+
+impl From<TimeBudget> for OutOfTime {
+    fn from(budget: TimeBudget) -> Self {
+        Self(budget.limit)
+    }
+}
+
+/// Where to write a check's provenance attestation, and the command that signs it
+#[derive(Clone, Copy)]
+struct AttestationConfig<'a> {
+    path: &'a Path,
+    sign: Option<&'a str>,
+}
+
+impl<'a> AttestationConfig<'a> {
+    fn from_args(path: Option<&'a Path>, sign: Option<&'a str>) -> Option<Self> {
+        path.map(|path| Self { path, sign })
+    }
+
+    fn write(&self, statement: &Statement) -> Result<()> {
+        let content = match self.sign {
+            Some(command) => serde_json::to_string_pretty(&statement.sign(command)?)?,
+            None => statement.to_json(),
+        };
+        std::fs::write(self.path, content + "\n")
+            .with_context(|| format!("Failed to write attestation: {}", self.path.display()))
+    }
+}
+
+/// Parse struct layouts, keeping only those reachable from exported symbols when
+/// `exported_only` is set. With `sort_stable`, the result is put back into canonical order
+/// so later (stable) sorts break ties the same way regardless of how layouts were gathered.
+fn find_layouts(
+    binary: &BinaryData,
+    dwarf: &DwarfContext<'_>,
+    filter: Option<&str>,
+    include_go_runtime: bool,
+    exported_only: bool,
+    sort_stable: bool,
+) -> Result<Vec<StructLayout>> {
+    find_layouts_with_stats(binary, dwarf, filter, include_go_runtime, exported_only, sort_stable)
+        .map(|(layouts, _)| layouts)
+}
+
+/// Fill in member sizes the config declares in `type_sizes`, warning where the debug info
+/// disagrees with a declaration.
+fn apply_type_sizes(type_sizes: &TypeSizes, layouts: &mut [StructLayout]) {
+    for conflict in type_sizes.apply(layouts) {
+        print_notice(&Notice::TypeSizeConflict(conflict));
+    }
+}
+
+fn print_notice(notice: &Notice) {
+    eprintln!("{}: {}", if notice.is_note() { "Note" } else { "Warning" }, notice);
+}
+
+/// [`find_layouts`], also returning per-unit scan timing. Warns about units dropped by the
+/// context's unit timeout.
+fn find_layouts_with_stats(
+    binary: &BinaryData,
+    dwarf: &DwarfContext<'_>,
+    filter: Option<&str>,
     include_go_runtime: bool,
+    exported_only: bool,
+    sort_stable: bool,
+) -> Result<(Vec<StructLayout>, ScanStats)> {
+    let options = ScanOptions { filter, include_go_runtime, exported_only, sort_stable };
+    let scan = pipeline::find_layouts(binary, dwarf, &options)?;
+    scan.notices.iter().for_each(print_notice);
+    Ok((scan.layouts, scan.stats))
+}
+
+/// Number of units listed by `--stats`.
+const STATS_TOP_UNITS: usize = 10;
+
+fn print_scan_stats(stats: &ScanStats, features: &DwarfFeatures) {
+    eprintln!(
+        "Scanned {} compilation units in {:.1} ms; slowest:",
+        stats.units.len(),
+        stats.total_elapsed().as_secs_f64() * 1000.0
+    );
+    for unit in stats.slowest(STATS_TOP_UNITS) {
+        eprintln!(
+            "  {:>10.1} ms  {:>5} structs  {}{}",
+            unit.elapsed.as_secs_f64() * 1000.0,
+            unit.structs,
+            unit.label(),
+            if unit.skipped { "  [skipped]" } else { "" }
+        );
+    }
+
+    if let Some(target) = &features.target {
+        eprintln!(
+            "Target: {} ({}-byte addresses, {}-endian)",
+            target.triple,
+            target.address_size,
+            target.endian.as_str()
+        );
+    }
+    let versions: Vec<String> =
+        features.versions.iter().map(|(v, n)| format!("v{} ({} units)", v, n)).collect();
+    eprintln!("DWARF versions: {}", versions.join(", "));
+    for (producer, count) in &features.producers {
+        eprintln!("  producer: {} ({} units)", producer, count);
+    }
+    if features.split_units > 0 {
+        eprintln!("Split DWARF: {} units read from .dwo/.dwp", features.split_units);
+    }
+    if !features.compressed_sections.is_empty() {
+        eprintln!("Compressed sections: {}", features.compressed_sections.join(", "));
+    }
+    for dwo in &features.missing_dwo {
+        eprintln!("Missing split DWARF: {}", dwo);
+    }
+    if !features.unsupported_forms.is_empty() {
+        eprintln!("Unsupported attribute forms (results may be partial):");
+        for (form, count) in &features.unsupported_forms {
+            eprintln!("  {:>6}  {}", count, form);
+        }
+    }
+    for hint in features.hints() {
+        eprintln!("hint: {}", hint);
+    }
 }
 
 fn run_cli(cli: Cli) -> Result<()> {
     match cli.command {
         Commands::Inspect {
-            binary,
+            binaries,
             filter,
+            filter_regex,
+            member_filter,
             output,
+            csv_members,
+            sarif_max_results,
+            report_profile,
             sort_by,
             top,
             min_padding,
             no_color,
+            no_pager,
             cache_line,
             pretty,
+            json_style,
             warn_false_sharing,
+            global_false_sharing,
+            atomic_patterns_file,
+            fail_on_partial,
+            include_partial,
+            cache_heatmap,
+            by_file,
+            by_namespace,
+            namespace_depth,
+            collapse_templates,
+            summary,
+            codeowners,
+            layout_map,
+            columns,
+            flatten_bases,
+            bits,
+            page_size,
+            allocator,
+            static_access,
+            usage,
+            static_instances,
+            unit_timeout,
+            budget_time,
+            emit_partial_results,
+            stats,
             include_go_runtime,
+            exported_only,
+            sort_stable,
+            export_sqlite,
+            repro_bundle,
+            redact_strings,
+            config,
+            emit_depfile,
+            depfile_target,
         } => {
+            let profile = InspectProfile::new(report_profile);
             let config = InspectConfig {
-                binary_path: &binary,
+                binary_paths: &binaries,
                 filter: filter.as_deref(),
+                filter_regex: filter_regex.as_deref(),
+                member_filter: member_filter.as_deref(),
                 output_format: output,
-                sort_by,
-                top,
-                min_padding,
-                no_color,
+                csv_members,
+                sarif_max_results,
+                sort_by: sort_by.unwrap_or(profile.sort_by),
+                top: top.or(profile.top),
+                min_padding: min_padding.or(profile.min_padding),
+                no_color: no_color || profile.no_color,
+                no_pager,
                 cache_line_size: cache_line,
                 pretty,
-                warn_false_sharing,
+                json_style,
+                warn_false_sharing: warn_false_sharing || profile.warn_false_sharing,
+                global_false_sharing,
+                atomic_patterns_file: atomic_patterns_file.as_deref(),
+                fail_on_partial: fail_on_partial || profile.fail_on_partial,
+                include_partial,
+                cache_heatmap,
+                by_file,
+                by_namespace,
+                namespace_depth,
+                collapse_templates,
+                summary,
+                codeowners: codeowners.as_deref(),
+                layout_map: layout_map || profile.layout_map,
+                columns: columns.as_deref(),
+                summary_only: profile.summary_only,
+                flatten_bases,
+                bits,
+                page_size,
+                allocator,
+                static_access,
+                usage,
+                static_instances,
+                unit_timeout,
+                time_budget: TimeBudget::from_args(budget_time, emit_partial_results),
+                stats,
                 include_go_runtime,
+                exported_only,
+                sort_stable,
+                export_sqlite: export_sqlite.as_deref(),
+                repro_bundle: repro_bundle.as_deref(),
+                redact_strings,
+                config_path: config.as_deref(),
+                depfile: DepfileConfig::from_args(
+                    emit_depfile.as_deref(),
+                    depfile_target.as_deref(),
+                ),
             };
             run_inspect(&config)?;
         }
         Commands::Diff {
-            old,
-            new,
+            binaries,
+            baseline,
             filter,
+            filter_regex,
+            member_filter,
             output,
+            sarif_max_results,
             cache_line,
             fail_on_regression,
+            fail_on,
+            report_profile,
+            global_false_sharing,
+            atomic_patterns_file,
             include_go_runtime,
+            exported_only,
+            sort_stable,
+            config,
+            emit_depfile,
+            depfile_target,
         } => {
-            let has_regression = run_diff(
-                &old,
-                &new,
-                filter.as_deref(),
-                output,
-                cache_line,
+            let fail_on_regression = fail_on_regression
+                || fail_on.is_some()
+                || report_profile == Some(ReportProfile::Ci);
+            let (old, new_path) = match (baseline.as_deref(), binaries.as_slice()) {
+                (Some(snapshot), [new]) => (DiffBaseline::Snapshot(snapshot), new),
+                (None, [old, new]) => (DiffBaseline::Binary(old), new),
+                (Some(_), _) => bail!("--baseline takes the place of OLD; pass only NEW"),
+                (None, _) => bail!("diff needs OLD and NEW binaries, or --baseline and NEW"),
+            };
+            let has_regression = run_diff(&DiffConfig {
+                old,
+                new_path,
+                filter: filter.as_deref(),
+                filter_regex: filter_regex.as_deref(),
+                member_filter: member_filter.as_deref(),
+                output_format: output,
+                sarif_max_results,
+                cache_line_size: cache_line,
                 fail_on_regression,
+                fail_on: fail_on.unwrap_or_default(),
+                profile: report_profile,
+                global_false_sharing,
+                atomic_patterns_file: atomic_patterns_file.as_deref(),
                 include_go_runtime,
-            )?;
+                exported_only,
+                sort_stable,
+                config_path: config.as_deref(),
+                depfile: DepfileConfig::from_args(
+                    emit_depfile.as_deref(),
+                    depfile_target.as_deref(),
+                ),
+            })?;
             if fail_on_regression && has_regression {
                 std::process::exit(1);
             }
         }
-        Commands::Check { binary, config, output, cache_line, include_go_runtime } => {
-            run_check(&binary, &config, output, cache_line, include_go_runtime)?;
-        }
-        Commands::Suggest {
+        Commands::Snapshot {
             binary,
-            filter,
             output,
-            min_savings,
-            cache_line,
-            pretty,
-            max_align,
-            sort_by_savings,
-            no_color,
+            filter,
             include_go_runtime,
+            exported_only,
+            config,
+            pretty,
+            emit_depfile,
+            depfile_target,
         } => {
-            run_suggest(
-                &binary,
-                filter.as_deref(),
-                output,
-                min_savings,
-                cache_line,
-                pretty,
-                max_align,
-                sort_by_savings,
-                no_color,
+            run_snapshot(&SnapshotConfig {
+                binary_path: &binary,
+                output: output.as_deref(),
+                filter: filter.as_deref(),
                 include_go_runtime,
-            )?;
+                exported_only,
+                config_path: config.as_deref(),
+                pretty,
+                depfile: DepfileConfig::from_args(
+                    emit_depfile.as_deref(),
+                    depfile_target.as_deref(),
+                ),
+            })?;
         }
-    }
-
-    Ok(())
-}
+        Commands::CompareBuilds {
+            debug,
+            release,
+            filter,
+            output,
+            sarif_max_results,
+            cache_line,
+            fail_on_unexplained,
+            include_go_runtime,
+            exported_only,
+            sort_stable,
+            config,
+            emit_depfile,
+            depfile_target,
+        } => {
+            let has_unexplained = run_compare_builds(&CompareBuildsConfig {
+                debug_path: &debug,
+                release_path: &release,
+                filter: filter.as_deref(),
+                output_format: output,
+                sarif_max_results,
+                cache_line_size: cache_line,
+                include_go_runtime,
+                exported_only,
+                sort_stable,
+                config_path: config.as_deref(),
+                depfile: DepfileConfig::from_args(
+                    emit_depfile.as_deref(),
+                    depfile_target.as_deref(),
+                ),
+            })?;
+            if fail_on_unexplained && has_unexplained {
+                std::process::exit(1);
+            }
+        }
+        Commands::CompareTargets {
+            old,
+            new,
+            config,
+            filter,
+            output,
+            cache_line,
+            pretty,
+            no_color,
+            include_go_runtime,
+        } => {
+            run_compare_targets(&CompareTargetsConfig {
+                old_path: &old,
+                new_path: &new,
+                config_path: config.as_deref(),
+                filter: filter.as_deref(),
+                output_format: output,
+                cache_line_size: cache_line,
+                pretty,
+                no_color,
+                include_go_runtime,
+            })?;
+        }
+        Commands::Check {
+            binaries,
+            config,
+            profile,
+            previous,
+            atomic_patterns_file,
+            list_suppressions,
+            lint_config,
+            validate_config_only,
+            lockfile,
+            accept,
+            budget_match,
+            fail_on,
+            output,
+            sarif_max_results,
+            cache_line,
+            include_go_runtime,
+            exported_only,
+            sort_stable,
+            budget_time,
+            emit_partial_results,
+            emit_depfile,
+            depfile_target,
+            attestation,
+            attestation_sign,
+        } => {
+            run_check(&CheckConfig {
+                binary_paths: &binaries,
+                config_path: &config,
+                profile_path: profile.as_deref(),
+                previous_path: previous.as_deref(),
+                atomic_patterns_file: atomic_patterns_file.as_deref(),
+                list_suppressions,
+                lint_config,
+                validate_config_only,
+                lockfile_path: &lockfile,
+                accept,
+                budget_match,
+                fail_on,
+                output_format: output,
+                sarif_max_results,
+                cache_line_size: cache_line,
+                include_go_runtime,
+                exported_only,
+                sort_stable,
+                time_budget: TimeBudget::from_args(budget_time, emit_partial_results),
+                depfile: DepfileConfig::from_args(
+                    emit_depfile.as_deref(),
+                    depfile_target.as_deref(),
+                ),
+                attestation: AttestationConfig::from_args(
+                    attestation.as_deref(),
+                    attestation_sign.as_deref(),
+                ),
+            })?;
+        }
+        Commands::Suggest {
+            binary,
+            filter,
+            filter_regex,
+            member_filter,
+            output,
+            sarif_max_results,
+            min_savings,
+            cache_line,
+            pretty,
+            max_align,
+            sort_by_savings,
+            strategy,
+            static_access,
+            usage,
+            locality,
+            size_cap,
+            no_color,
+            include_go_runtime,
+            exported_only,
+            sort_stable,
+            config,
+            emit_code,
+            emit_depfile,
+            depfile_target,
+        } => {
+            run_suggest(&SuggestConfig {
+                binary_path: &binary,
+                filter: filter.as_deref(),
+                filter_regex: filter_regex.as_deref(),
+                member_filter: member_filter.as_deref(),
+                output_format: output,
+                sarif_max_results,
+                min_savings,
+                cache_line_size: cache_line,
+                pretty,
+                max_align,
+                sort_by_savings,
+                strategy,
+                static_access,
+                usage,
+                locality,
+                size_cap,
+                no_color,
+                include_go_runtime,
+                exported_only,
+                sort_stable,
+                config_path: config.as_deref(),
+                emit_code,
+                depfile: DepfileConfig::from_args(
+                    emit_depfile.as_deref(),
+                    depfile_target.as_deref(),
+                ),
+            })?;
+        }
+        Commands::Annotate {
+            binary,
+            samples,
+            load_bias,
+            filter,
+            output,
+            pretty,
+            no_color,
+            emit_config,
+        } => {
+            run_annotate(&AnnotateConfig {
+                binary_path: &binary,
+                samples_path: &samples,
+                load_bias,
+                filter: filter.as_deref(),
+                output_format: output,
+                pretty,
+                no_color,
+                emit_config: emit_config.as_deref(),
+            })?;
+        }
+        Commands::Locate { binary, type_name, offset, output, pretty, no_color } => {
+            run_locate(&LocateConfig {
+                binary_path: &binary,
+                type_name: &type_name,
+                offset,
+                output_format: output,
+                pretty,
+                no_color,
+            })?;
+        }
+        Commands::Verify { binary, rustc_sizes, filter, output, pretty, no_color } => {
+            run_verify(&VerifyConfig {
+                binary_path: &binary,
+                rustc_sizes_path: &rustc_sizes,
+                filter: filter.as_deref(),
+                output_format: output,
+                pretty,
+                no_color,
+            })?;
+        }
+        Commands::SchemaCheck {
+            schema,
+            binary,
+            config,
+            filter,
+            output,
+            pretty,
+            no_color,
+            include_go_runtime,
+        } => {
+            run_schema_check(&SchemaCheckConfig {
+                schema_path: &schema,
+                binary_path: &binary,
+                config_path: config.as_deref(),
+                filter: filter.as_deref(),
+                output_format: output,
+                pretty,
+                no_color,
+                include_go_runtime,
+            })?;
+        }
+        Commands::VerifyDeterminism {
+            binaries,
+            runs,
+            config,
+            filter,
+            output,
+            pretty,
+            no_color,
+            include_go_runtime,
+        } => {
+            run_verify_determinism(&VerifyDeterminismConfig {
+                binary_paths: &binaries,
+                runs,
+                config_path: config.as_deref(),
+                filter: filter.as_deref(),
+                output_format: output,
+                pretty,
+                no_color,
+                include_go_runtime,
+            })?;
+        }
+        Commands::Forecast {
+            binary,
+            instances,
+            history,
+            top,
+            horizon,
+            filter,
+            config,
+            max_align,
+            cache_line,
+            output,
+            pretty,
+            no_color,
+        } => {
+            run_forecast(&ForecastConfig {
+                binary_path: &binary,
+                instances_path: &instances,
+                history: &history,
+                top,
+                horizon,
+                filter: filter.as_deref(),
+                config_path: config.as_deref(),
+                max_align,
+                cache_line_size: cache_line,
+                output_format: output,
+                pretty,
+                no_color,
+            })?;
+        }
+        Commands::Placement {
+            binary,
+            config,
+            filter,
+            lang,
+            cache_line,
+            output,
+            pretty,
+            no_color,
+            include_go_runtime,
+        } => {
+            run_placement(&PlacementConfig {
+                binary_path: &binary,
+                config_path: config.as_deref(),
+                filter: filter.as_deref(),
+                lang,
+                cache_line_size: cache_line,
+                output_format: output,
+                pretty,
+                no_color,
+                include_go_runtime,
+            })?;
+        }
+        Commands::EmitAssertions {
+            binary,
+            lang,
+            go_package,
+            package_name,
+            filter,
+            config,
+            out,
+            emit_depfile,
+            depfile_target,
+        } => {
+            run_emit_assertions(&EmitAssertionsConfig {
+                binary_path: &binary,
+                lang,
+                go_package: &go_package,
+                package_name: package_name.as_deref(),
+                filter: filter.as_deref(),
+                config_path: config.as_deref(),
+                out: out.as_deref(),
+                depfile: DepfileConfig::from_args(
+                    emit_depfile.as_deref(),
+                    depfile_target.as_deref(),
+                ),
+            })?;
+        }
+        Commands::EmitHeader {
+            binary,
+            filter,
+            config,
+            out,
+            guard,
+            emit_depfile,
+            depfile_target,
+        } => {
+            run_emit_header(&EmitHeaderConfig {
+                binary_path: &binary,
+                filter: filter.as_deref(),
+                config_path: config.as_deref(),
+                out: out.as_deref(),
+                guard: guard.as_deref(),
+                depfile: DepfileConfig::from_args(
+                    emit_depfile.as_deref(),
+                    depfile_target.as_deref(),
+                ),
+            })?;
+        }
+        Commands::Watch {
+            path,
+            bin,
+            run,
+            filter,
+            config,
+            debounce,
+            cache_line,
+            include_go_runtime,
+        } => {
+            let config_path = match (&config, run) {
+                (Some(path), _) => Some(path.as_path()),
+                (None, WatchAction::Check) => Some(Path::new(DEFAULT_CONFIG)),
+                (None, WatchAction::Inspect) => None,
+            };
+            run_watch(&WatchConfig {
+                path: &path,
+                bin: bin.as_deref(),
+                action: run,
+                filter: filter.as_deref(),
+                config_path,
+                debounce: Duration::from_millis(debounce),
+                cache_line_size: cache_line,
+                include_go_runtime,
+            })?;
+        }
+        Commands::Tui { binary, config, filter, cache_line, max_align, include_go_runtime } => {
+            run_tui(&TuiConfig {
+                binary_path: &binary,
+                config_path: config.as_deref(),
+                filter: filter.as_deref(),
+                cache_line_size: cache_line,
+                max_align,
+                include_go_runtime,
+            })?;
+        }
+        Commands::Summary {
+            binaries,
+            config,
+            filter,
+            output,
+            cache_line,
+            pretty,
+            no_color,
+            include_go_runtime,
+        } => {
+            run_summary(&SummaryConfig {
+                binary_paths: &binaries,
+                config_path: config.as_deref(),
+                filter: filter.as_deref(),
+                output_format: output,
+                cache_line_size: cache_line,
+                pretty,
+                no_color,
+                include_go_runtime,
+            })?;
+        }
+        Commands::Graph { binaries, config, filter, format, pretty, include_go_runtime } => {
+            run_graph(&GraphConfig {
+                binary_paths: &binaries,
+                config_path: config.as_deref(),
+                filter: filter.as_deref(),
+                format,
+                pretty,
+                include_go_runtime,
+            })?;
+        }
+        Commands::Export {
+            format,
+            out,
+            binaries,
+            config,
+            filter,
+            cache_line,
+            include_go_runtime,
+        } => {
+            run_export(&ExportConfig {
+                format,
+                out: &out,
+                binary_paths: &binaries,
+                config_path: config.as_deref(),
+                filter: filter.as_deref(),
+                cache_line_size: cache_line,
+                include_go_runtime,
+            })?;
+        }
+    }
+
+    Ok(())
+}
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    run_cli(cli)
+    let result = run_cli(cli);
+    if let Err(e) = &result
+        && e.is::<OutOfTime>()
+    {
+        eprintln!("Error: {}", e);
+        std::process::exit(EXIT_OUT_OF_TIME);
+    }
+    result
 }
 
 fn run_inspect(config: &InspectConfig<'_>) -> Result<()> {
-    let binary = BinaryData::load(config.binary_path)
-        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
-
-    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
-
-    let dwarf = DwarfContext::new(&loaded);
-
-    let mut layouts = dwarf
-        .find_structs(config.filter, config.include_go_runtime)
-        .context("Failed to parse struct layouts")?;
+    if config.csv_members && config.output_format != OutputFormat::Csv {
+        bail!("--csv-members needs --output csv");
+    }
+    if config.csv_members && config.columns.is_some() {
+        bail!("--columns selects struct columns; it cannot be combined with --csv-members");
+    }
+    let file_config = match config.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let scope =
+        narrow_scope(file_config.analyze.scope()?, config.filter_regex, config.member_filter)?;
+    let codeowners = config
+        .codeowners
+        .map(|path| {
+            CodeOwners::load(path)
+                .with_context(|| format!("Failed to read CODEOWNERS: {}", path.display()))
+        })
+        .transpose()?;
+    let atomic_patterns = file_config.false_sharing_patterns(config.atomic_patterns_file)?;
+
+    let options = InspectOptions {
+        scan: ScanOptions {
+            filter: config.filter,
+            include_go_runtime: config.include_go_runtime,
+            exported_only: config.exported_only,
+            sort_stable: config.sort_stable,
+        },
+        scope: Some(&scope),
+        type_sizes: Some(&file_config.type_sizes),
+        unit_timeout: config.unit_timeout,
+        deadline: config.time_budget.map(|b| b.deadline),
+        cache_line_size: config.cache_line_size,
+        sort_by: config.sort_by,
+        top: config.top,
+        min_padding: config.min_padding,
+        warn_false_sharing: config.warn_false_sharing,
+        global_false_sharing: config.global_false_sharing,
+        atomic_patterns: Some(&atomic_patterns),
+        flatten_bases: config.flatten_bases,
+        bits: config.bits,
+        allocator: config.allocator,
+        static_access: config.static_access,
+        usage: config.usage,
+        static_instances: config.static_instances,
+        cache_heatmap: config.cache_heatmap,
+        by_file: config.by_file,
+        codeowners: codeowners.as_ref(),
+        by_namespace: config.by_namespace,
+        namespace_depth: config.namespace_depth,
+        collapse_templates: config.collapse_templates,
+        summary: config.summary,
+        ..InspectOptions::new(config.binary_paths)
+    };
+    if config.output_format == OutputFormat::Jsonl {
+        return run_inspect_jsonl(config, &options);
+    }
+    let report = pipeline::run_inspect(&options)?;
+    if report.scans.len() > 1 && config.repro_bundle.is_some() {
+        bail!("--repro-bundle takes a single binary, got {}", report.scans.len());
+    }
+    for scan in &report.scans {
+        scan.notices.iter().for_each(print_notice);
+        if config.stats {
+            if report.scans.len() > 1 {
+                eprintln!("{}:", scan.path.display());
+            }
+            print_scan_stats(&scan.stats, &scan.dwarf);
+        }
+    }
+    let out_of_time = config.time_budget.filter(|_| report.out_of_time);
+    if let Some(budget) = out_of_time
+        && !budget.emit_partial
+    {
+        return Err(OutOfTime::from(budget).into());
+    }
+    let done = || out_of_time.map_or(Ok(()), |b| Err(OutOfTime::from(b).into()));
+
+    if let Some(depfile) = &config.depfile {
+        let mut inputs = report.inputs.clone();
+        inputs.extend(config.config_path.filter(|p| !is_stdin(p)).map(Path::to_path_buf));
+        inputs.extend(config.codeowners.map(Path::to_path_buf));
+        inputs.extend(config.atomic_patterns_file.map(Path::to_path_buf));
+        depfile.write(&inputs)?;
+    }
 
-    if layouts.is_empty() {
+    if report.found == 0 {
         if let Some(f) = config.filter {
             eprintln!("No structs found matching filter: {}", f);
         } else {
             eprintln!("No structs found in binary");
         }
-        return Ok(());
-    }
-
-    for layout in &mut layouts {
-        analyze_layout(layout, config.cache_line_size);
-        if config.warn_false_sharing {
-            let fs_analysis = analyze_false_sharing(layout, config.cache_line_size);
-            layout.metrics.false_sharing = Some(fs_analysis);
-        }
-    }
-
-    if let Some(min) = config.min_padding {
-        layouts.retain(|l| l.metrics.padding_bytes >= min);
+        return done();
     }
-
-    if layouts.is_empty() {
+    if report.layouts.is_empty() && report.templates.as_ref().is_none_or(Vec::is_empty) {
         eprintln!("No structs match the filter criteria");
-        return Ok(());
+        return done();
     }
 
-    match config.sort_by {
-        SortField::Name => layouts.sort_by(|a, b| a.name.cmp(&b.name)),
-        SortField::Size => layouts.sort_by(|a, b| b.size.cmp(&a.size)),
-        SortField::Padding => {
-            layouts.sort_by(|a, b| b.metrics.padding_bytes.cmp(&a.metrics.padding_bytes))
-        }
-        SortField::PaddingPct => layouts.sort_by(|a, b| {
-            match (a.metrics.padding_percentage.is_nan(), b.metrics.padding_percentage.is_nan()) {
-                (true, true) => std::cmp::Ordering::Equal,
-                (true, false) => std::cmp::Ordering::Greater,
-                (false, true) => std::cmp::Ordering::Less,
-                (false, false) => b
-                    .metrics
-                    .padding_percentage
-                    .partial_cmp(&a.metrics.padding_percentage)
-                    .unwrap_or(std::cmp::Ordering::Equal),
-            }
-        }),
+    // The summary totals every matching struct, not just the --top ones listed.
+    let columns = config.columns.map(<[Column]>::to_vec).or(file_config.columns);
+    let offenders =
+        (config.summary_only && config.output_format == OutputFormat::Table).then(|| {
+            TableFormatter::new(config.no_color, config.cache_line_size)
+                .with_include_partial(config.include_partial)
+                .with_columns(columns.clone())
+                .format_offenders(report.shown(), &report.layouts)
+        });
+    let layouts = report.shown();
+
+    if let Some(path) = config.export_sqlite {
+        export_sqlite_runs(path, &report.scans, layouts, config.cache_line_size)?;
     }
 
-    if let Some(n) = config.top {
-        layouts.truncate(n);
+    let partial: Vec<&StructLayout> = layouts.iter().filter(|l| l.metrics.partial).collect();
+    if !partial.is_empty() {
+        eprintln!(
+            "Warning: {} struct(s) have partial layouts (members missing an offset or size); \
+             their padding is not measured:",
+            partial.len()
+        );
+        for layout in &partial {
+            eprintln!("  {}: {}", layout.name, layout.metrics.partial_members.join(", "));
+        }
     }
 
-    let output_str = match config.output_format {
+    match config.output_format {
         OutputFormat::Table => {
-            let formatter = TableFormatter::new(config.no_color, config.cache_line_size);
-            formatter.format(&layouts)
+            use std::io::Write;
+
+            let formatter = TableFormatter::new(config.no_color, config.cache_line_size)
+                .with_layout_map(config.layout_map)
+                .with_include_partial(config.include_partial)
+                .with_page_size(config.page_size)
+                .with_columns(columns);
+            // Streamed rather than built as one string: tens of thousands of structs would
+            // otherwise hold the whole report in memory.
+            let mut pager = if config.no_pager { None } else { Pager::spawn() };
+            let sink: Box<dyn Write> = match &mut pager {
+                Some(pager) => Box::new(pager),
+                None => Box::new(std::io::stdout().lock()),
+            };
+            let mut out = std::io::BufWriter::new(sink);
+            match &offenders {
+                Some(offenders) => out.write_all(offenders.as_bytes())?,
+                None => formatter.write_to(layouts, &mut out)?,
+            }
+            if let Some(heatmap) = &report.heatmap {
+                out.write_all(formatter.format_heatmap(heatmap).as_bytes())?;
+            }
+            if let Some(files) = &report.files {
+                out.write_all(formatter.format_file_summary(files).as_bytes())?;
+            }
+            if let Some(owners) = &report.owners {
+                out.write_all(formatter.format_owner_summary(owners).as_bytes())?;
+            }
+            if let Some(namespaces) = &report.namespaces {
+                out.write_all(formatter.format_namespace_summary(namespaces).as_bytes())?;
+            }
+            if let Some(templates) = &report.templates {
+                out.write_all(formatter.format_template_summary(templates).as_bytes())?;
+            }
+            if let Some(warnings) = &report.global_false_sharing {
+                out.write_all(formatter.format_global_false_sharing(warnings).as_bytes())?;
+            }
+            if let Some(summary) = &report.summary {
+                out.write_all(formatter.format_binary_summary(summary).as_bytes())?;
+            }
+            writeln!(out)?;
+            out.flush()?;
+            drop(out);
+            if let Some(pager) = pager {
+                pager.finish()?;
+            }
         }
         OutputFormat::Json => {
-            let formatter = JsonFormatter::new(config.pretty);
-            formatter.format(&layouts)
+            let debug_info = report
+                .scans
+                .iter()
+                .map(|scan| DebugInfo::new(scan.path.display().to_string(), scan.dwarf.clone()))
+                .collect();
+            let formatter = JsonFormatter::new(config.pretty)
+                .with_style(config.json_style)
+                .with_debug_info(debug_info)
+                .with_templates(report.templates.clone())
+                .with_summary(report.summary.clone());
+            println!(
+                "{}",
+                formatter.format_with_summaries(
+                    layouts,
+                    report.heatmap.as_ref(),
+                    report.files.as_deref(),
+                    report.owners.as_deref(),
+                    report.namespaces.as_deref(),
+                    report.global_false_sharing.as_deref(),
+                )
+            );
         }
         OutputFormat::Sarif => {
-            let formatter = SarifFormatter::new();
-            formatter.format_inspect(&layouts)
+            let formatter = SarifFormatter::new().with_max_results(config.sarif_max_results);
+            println!("{}", formatter.format_inspect(layouts));
         }
-    };
+        OutputFormat::Csv => {
+            let formatter =
+                CsvFormatter::new().with_members(config.csv_members).with_columns(columns);
+            print!("{}", formatter.format(layouts));
+        }
+        OutputFormat::Jsonl => unreachable!("jsonl output is streamed by run_inspect_jsonl"),
+    }
 
-    println!("{}", output_str);
+    if let Some(path) = config.repro_bundle {
+        let binary_path = &report.scans[0].path;
+        let binary = BinaryData::load(binary_path)
+            .with_context(|| format!("Failed to load binary: {}", binary_path.display()))?;
+        write_repro_bundle(path, &binary, config.config_path, config.redact_strings, layouts)?;
+        eprintln!("Wrote repro bundle: {}", path.display());
+    }
+
+    done()?;
+    if config.fail_on_partial && !partial.is_empty() {
+        bail!("Partial layout check failed: {} struct(s) with partial layouts", partial.len());
+    }
 
     Ok(())
 }
 
-fn run_diff(
-    old_path: &Path,
-    new_path: &Path,
-    filter: Option<&str>,
-    output_format: OutputFormat,
+/// Inspect with one JSON object per struct per line, each printed as soon as it is analyzed.
+fn run_inspect_jsonl(config: &InspectConfig<'_>, options: &InspectOptions<'_>) -> Result<()> {
+    use std::io::Write;
+
+    // These need every struct before the first can be printed.
+    let needs_all = [
+        (config.sort_by != SortField::Name, "--sort-by"),
+        (config.top.is_some(), "--top"),
+        (config.summary || config.summary_only, "--summary"),
+        (config.by_file, "--by-file"),
+        (config.by_namespace, "--by-namespace"),
+        (config.codeowners.is_some(), "--codeowners"),
+        (config.cache_heatmap, "--cache-heatmap"),
+        (config.collapse_templates, "--collapse-templates"),
+        (config.global_false_sharing, "--global-false-sharing"),
+        (config.static_instances, "--static-instances"),
+        (config.static_access, "--static-access"),
+        (config.usage, "--usage"),
+        (config.exported_only, "--exported-only"),
+        (config.sort_stable, "--sort-stable"),
+        (config.time_budget.is_some(), "--budget-time"),
+        (config.stats, "--stats"),
+        (config.export_sqlite.is_some(), "--export-sqlite"),
+        (config.repro_bundle.is_some(), "--repro-bundle"),
+        (config.depfile.is_some(), "--emit-depfile"),
+    ];
+    if let Some((_, flag)) = needs_all.iter().find(|(set, _)| *set) {
+        bail!(
+            "{} needs every struct before printing; it cannot be combined with --output jsonl",
+            flag
+        );
+    }
+
+    let formatter = JsonFormatter::new(false).with_style(config.json_style);
+    let mut partial = Vec::new();
+    let mut stdout = std::io::stdout().lock();
+    let streamed = pipeline::stream_inspect(options, |layout| {
+        if layout.metrics.partial {
+            partial.push(format!("{}: {}", layout.name, layout.metrics.partial_members.join(", ")));
+        }
+        // Stdout is line buffered, so each struct reaches the reader as it is written.
+        writeln!(stdout, "{}", formatter.format_line(layout))?;
+        Ok(())
+    });
+    let report = match streamed {
+        // The reader stopped early (`| head`); so do we.
+        Err(layout_audit::Error::Io(e)) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+            return Ok(());
+        }
+        result => result?,
+    };
+    report.notices.iter().for_each(print_notice);
+
+    if report.found == 0 {
+        match config.filter {
+            Some(f) => eprintln!("No structs found matching filter: {}", f),
+            None => eprintln!("No structs found in binary"),
+        }
+    }
+    if !partial.is_empty() {
+        eprintln!(
+            "Warning: {} struct(s) have partial layouts (members missing an offset or size); \
+             their padding is not measured:",
+            partial.len()
+        );
+        for line in &partial {
+            eprintln!("  {}", line);
+        }
+        if config.fail_on_partial {
+            bail!("Partial layout check failed: {} struct(s) with partial layouts", partial.len());
+        }
+    }
+    Ok(())
+}
+
+/// Package the debug sections, config, command line, and JSON output for a bug report.
+/// With `redact_strings`, names are scrubbed from the DWARF string tables and the config and
+/// JSON output, which would repeat them, are left out.
+fn write_repro_bundle(
+    path: &Path,
+    binary: &BinaryData,
+    config_path: Option<&Path>,
+    redact_strings: bool,
+    layouts: &[StructLayout],
+) -> Result<()> {
+    let debug_object = extract_debug_object(binary.debug_data(), redact_strings)
+        .context("Failed to extract debug sections")?;
+
+    let mut bundle = ReproBundle::new();
+    let mut omitted = Vec::new();
+    let mut extra = Vec::new();
+
+    // A config read from stdin is gone by now.
+    if let Some(config_path) = config_path.filter(|p| !is_stdin(p)) {
+        if redact_strings {
+            omitted.push("config.yaml");
+        } else {
+            let content = std::fs::read(config_path)
+                .with_context(|| format!("Failed to read config: {}", config_path.display()))?;
+            extra.push(("config.yaml", content));
+        }
+    }
+    if redact_strings {
+        omitted.push("output.json");
+    } else {
+        extra.push(("output.json", JsonFormatter::new(true).format(layouts).into_bytes()));
+    }
+
+    let manifest = serde_json::json!({
+        "tool_version": env!("CARGO_PKG_VERSION"),
+        "command_line": std::env::args().collect::<Vec<_>>(),
+        "debug_object": REPRO_DEBUG_OBJECT,
+        "redacted_strings": redact_strings,
+        "omitted": omitted,
+    });
+    bundle.add("manifest.json", serde_json::to_string_pretty(&manifest)?);
+    bundle.add(REPRO_DEBUG_OBJECT, debug_object);
+    for (name, content) in extra {
+        bundle.add(name, content);
+    }
+
+    bundle.write(path).with_context(|| format!("Failed to write repro bundle: {}", path.display()))
+}
+
+/// Export one run per binary, so each run's structs keep pointing at their binary.
+fn export_sqlite_runs(
+    path: &Path,
+    scans: &[BinaryScan],
+    layouts: &[StructLayout],
     cache_line_size: u32,
-    fail_on_regression: bool,
-    include_go_runtime: bool,
-) -> Result<bool> {
-    let old_binary = BinaryData::load(old_path)
-        .with_context(|| format!("Failed to load old binary: {}", old_path.display()))?;
-    let new_binary = BinaryData::load(new_path)
-        .with_context(|| format!("Failed to load new binary: {}", new_path.display()))?;
+) -> Result<()> {
+    for scan in scans {
+        let label = scan.path.display().to_string();
+        let run: Vec<StructLayout> = layouts
+            .iter()
+            .filter(|l| l.binary.as_ref().is_none_or(|b| *b == label))
+            .cloned()
+            .collect();
+        export_sqlite(path, &scan.path, cache_line_size, &run)?;
+    }
+    Ok(())
+}
 
-    let old_loaded = old_binary.load_dwarf().context("Failed to load DWARF from old binary")?;
-    let new_loaded = new_binary.load_dwarf().context("Failed to load DWARF from new binary")?;
+#[cfg(feature = "sqlite")]
+fn export_sqlite(
+    path: &Path,
+    binary_path: &Path,
+    cache_line_size: u32,
+    layouts: &[StructLayout],
+) -> Result<()> {
+    let mut exporter = layout_audit::SqliteExporter::open(path)
+        .with_context(|| format!("Failed to open SQLite database: {}", path.display()))?;
+    let binary = binary_path.to_string_lossy();
+    exporter
+        .export(&layout_audit::SqliteRun { binary: &binary, cache_line_size }, layouts)
+        .with_context(|| format!("Failed to export to SQLite database: {}", path.display()))?;
+    Ok(())
+}
 
-    let old_dwarf = DwarfContext::new(&old_loaded);
-    let new_dwarf = DwarfContext::new(&new_loaded);
+#[cfg(not(feature = "sqlite"))]
+fn export_sqlite(path: &Path, _: &Path, _: u32, _: &[StructLayout]) -> Result<()> {
+    bail!(
+        "Cannot export to {}: SQLite export requires building with the `sqlite` feature \
+         (cargo install layout-audit --features sqlite)",
+        path.display()
+    )
+}
 
-    let mut old_layouts = old_dwarf.find_structs(filter, include_go_runtime)?;
-    let mut new_layouts = new_dwarf.find_structs(filter, include_go_runtime)?;
+fn run_diff(config: &DiffConfig<'_>) -> Result<bool> {
+    let DiffConfig {
+        old,
+        new_path,
+        filter,
+        output_format,
+        sarif_max_results,
+        cache_line_size,
+        fail_on_regression,
+        fail_on,
+        profile,
+        global_false_sharing,
+        include_go_runtime,
+        exported_only,
+        sort_stable,
+        config_path,
+        ..
+    } = *config;
+    if matches!(output_format, OutputFormat::Csv | OutputFormat::Jsonl) {
+        bail!("diff supports table, json, and sarif output");
+    }
 
-    for layout in &mut old_layouts {
-        analyze_layout(layout, cache_line_size);
+    let file_config = match config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let scope =
+        narrow_scope(file_config.analyze.scope()?, config.filter_regex, config.member_filter)?;
+    if let Some(policy) = &file_config.new_struct_defaults {
+        policy.validate()?;
     }
-    for layout in &mut new_layouts {
-        analyze_layout(layout, cache_line_size);
+    let atomic_patterns = file_config.false_sharing_patterns(config.atomic_patterns_file)?;
+
+    let options = DiffOptions {
+        scan: ScanOptions { filter, include_go_runtime, exported_only, sort_stable },
+        scope: Some(&scope),
+        type_sizes: Some(&file_config.type_sizes),
+        cache_line_size,
+        global_false_sharing,
+        atomic_patterns: Some(&atomic_patterns),
+        ..DiffOptions::new(old, new_path)
+    };
+    let report = pipeline::run_diff(&options)?;
+    report.notices.iter().for_each(print_notice);
+    let diff = &report.diff;
+
+    if let Some(depfile) = &config.depfile {
+        let mut inputs = report.inputs.clone();
+        inputs.extend(config_path.filter(|p| !is_stdin(p)).map(Path::to_path_buf));
+        inputs.extend(config.atomic_patterns_file.map(Path::to_path_buf));
+        depfile.write(&inputs)?;
     }
 
-    let diff = diff_layouts(&old_layouts, &new_layouts);
+    let policy_violations: Vec<CheckViolation> = match &file_config.new_struct_defaults {
+        Some(policy) => {
+            let added: std::collections::HashSet<&str> =
+                diff.added.iter().map(|s| s.name.as_str()).collect();
+            report
+                .new_layouts
+                .iter()
+                .filter(|l| added.contains(l.name.as_str()))
+                .flat_map(|l| policy.violations(l))
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let global_diff = report.global_false_sharing.as_ref();
 
     match output_format {
         OutputFormat::Json => {
-            println!("{}", serde_json::to_string_pretty(&diff)?);
+            let output = DiffJsonOutput {
+                diff,
+                new_struct_policy: &policy_violations,
+                global_false_sharing: global_diff,
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
         }
         OutputFormat::Table => {
-            print_diff_table(&diff);
+            match profile {
+                Some(profile) => print!("{}", format_profiled_diff(diff, profile)),
+                None => print_diff_table(diff),
+            }
+            if let Some(global_diff) = global_diff {
+                if !diff.has_changes() {
+                    println!();
+                }
+                print!("{}", format_global_sharing_diff(global_diff));
+            }
+            if !policy_violations.is_empty() {
+                println!();
+                print_violations(&policy_violations);
+            }
         }
         OutputFormat::Sarif => {
-            let formatter = SarifFormatter::new();
-            println!("{}", formatter.format_diff(&diff, fail_on_regression));
+            let formatter = SarifFormatter::new().with_max_results(sarif_max_results);
+            println!(
+                "{}",
+                formatter.format_diff_with_policy(diff, fail_on_regression, &policy_violations)
+            );
+        }
+        OutputFormat::Csv | OutputFormat::Jsonl => {
+            unreachable!("diff rejects csv and jsonl output up front")
         }
     }
 
-    Ok(diff.has_regressions())
+    let new_global_sharing = global_diff.is_some_and(|d| !d.introduced.is_empty());
+    let failing_policy = policy_violations.iter().any(|v| v.severity >= fail_on);
+    Ok(diff.has_regressions() || failing_policy || new_global_sharing)
+}
+
+/// Diff JSON: the diff itself, plus `new_struct_policy` when added structs break the defaults.
+#[derive(serde::Serialize)]
+struct DiffJsonOutput<'a> {
+    #[serde(flatten)]
+    diff: &'a layout_audit::DiffResult,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    new_struct_policy: &'a [CheckViolation],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    global_false_sharing: Option<&'a GlobalFalseSharingDiff>,
+}
+
+fn format_global_sharing_diff(diff: &GlobalFalseSharingDiff) -> String {
+    use colored::Colorize;
+
+    if diff.is_empty() {
+        return "No changes in thread-shared globals sharing cache lines.\n".to_string();
+    }
+
+    let mut out = String::new();
+    for (title, warnings, sign) in [
+        ("Thread-shared globals now sharing a cache line:".red().bold(), &diff.introduced, "+"),
+        (
+            "Thread-shared globals no longer sharing a cache line:".green().bold(),
+            &diff.resolved,
+            "-",
+        ),
+    ] {
+        if warnings.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("{}\n", title));
+        for w in warnings {
+            out.push_str(&format!(
+                "  {} {} and {} (line {:#x})\n",
+                sign, w.first.name, w.second.name, w.cache_line_address
+            ));
+        }
+        out.push('\n');
+    }
+    out
 }
 
 fn print_diff_table(diff: &layout_audit::DiffResult) {
+    print!("{}", format_diff_table(diff));
+}
+
+fn format_diff_table(diff: &layout_audit::DiffResult) -> String {
     use colored::Colorize;
 
     if !diff.has_changes() {
-        println!("No changes detected.");
-        return;
+        return "No changes detected.\n".to_string();
     }
 
+    let mut out = String::new();
     if !diff.removed.is_empty() {
-        println!("{}", "Removed structs:".red().bold());
+        out.push_str(&format!("{}\n", "Removed structs:".red().bold()));
         for s in &diff.removed {
-            println!("  - {} ({} bytes, {} padding)", s.name, s.size, s.padding_bytes);
+            out.push_str(&format!(
+                "  - {} ({} bytes, {} padding)\n",
+                s.name, s.size, s.padding_bytes
+            ));
         }
-        println!();
+        out.push('\n');
     }
 
     if !diff.added.is_empty() {
-        println!("{}", "Added structs:".green().bold());
+        out.push_str(&format!("{}\n", "Added structs:".green().bold()));
         for s in &diff.added {
-            println!("  + {} ({} bytes, {} padding)", s.name, s.size, s.padding_bytes);
+            out.push_str(&format!(
+                "  + {} ({} bytes, {} padding)\n",
+                s.name, s.size, s.padding_bytes
+            ));
         }
-        println!();
+        out.push('\n');
     }
 
     if !diff.changed.is_empty() {
-        println!("{}", "Changed structs:".yellow().bold());
+        out.push_str(&format!("{}\n", "Changed structs:".yellow().bold()));
         for c in &diff.changed {
-            let size_indicator = match c.size_delta.cmp(&0) {
-                std::cmp::Ordering::Greater => format!("+{}", c.size_delta).red().to_string(),
-                std::cmp::Ordering::Less => format!("{}", c.size_delta).green().to_string(),
-                std::cmp::Ordering::Equal => "0".to_string(),
-            };
-            let pad_indicator = match c.padding_delta.cmp(&0) {
-                std::cmp::Ordering::Greater => format!("+{}", c.padding_delta).red().to_string(),
-                std::cmp::Ordering::Less => format!("{}", c.padding_delta).green().to_string(),
-                std::cmp::Ordering::Equal => "0".to_string(),
-            };
+            out.push_str(&format_struct_change(c));
+        }
+        out.push('\n');
+    }
 
-            println!(
-                "  ~ {} (size: {} -> {} [{}], padding: {} -> {} [{}])",
-                c.name,
-                c.old_size,
-                c.new_size,
-                size_indicator,
-                c.old_padding,
-                c.new_padding,
-                pad_indicator
-            );
+    out.push_str(&format!(
+        "Summary: {} added, {} removed, {} changed, {} unchanged\n",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len(),
+        diff.unchanged_count
+    ));
+    out
+}
 
-            for mc in &c.member_changes {
-                let prefix = match mc.kind {
-                    layout_audit::diff::MemberChangeKind::Added => "+".green(),
-                    layout_audit::diff::MemberChangeKind::Removed => "-".red(),
-                    _ => "~".yellow(),
-                };
-                println!("      {} {}: {}", prefix, mc.name, mc.details);
+/// One changed struct of a diff table, with its member changes.
+fn format_struct_change(c: &layout_audit::diff::StructChange) -> String {
+    use colored::Colorize;
+
+    let size_indicator = match c.size_delta.cmp(&0) {
+        std::cmp::Ordering::Greater => format!("+{}", c.size_delta).red().to_string(),
+        std::cmp::Ordering::Less => format!("{}", c.size_delta).green().to_string(),
+        std::cmp::Ordering::Equal => "0".to_string(),
+    };
+    let pad_indicator = match c.padding_delta.cmp(&0) {
+        std::cmp::Ordering::Greater => format!("+{}", c.padding_delta).red().to_string(),
+        std::cmp::Ordering::Less => format!("{}", c.padding_delta).green().to_string(),
+        std::cmp::Ordering::Equal => "0".to_string(),
+    };
+
+    let mut out = format!(
+        "  ~ {} (size: {} -> {} [{}], padding: {} -> {} [{}])\n",
+        c.name, c.old_size, c.new_size, size_indicator, c.old_padding, c.new_padding, pad_indicator
+    );
+    for mc in &c.member_changes {
+        let prefix = match mc.kind {
+            layout_audit::diff::MemberChangeKind::Added => "+".green(),
+            layout_audit::diff::MemberChangeKind::Removed => "-".red(),
+            _ => "~".yellow(),
+        };
+        out.push_str(&format!("      {} {}: {}\n", prefix, mc.name, mc.details));
+    }
+    out
+}
+
+/// Diff table for a `--report-profile`: `ci` lists only the structs that grew, `exec` the
+/// [`EXEC_TOP_OFFENDERS`] that grew most plus totals, and `dev` everything.
+fn format_profiled_diff(diff: &layout_audit::DiffResult, profile: ReportProfile) -> String {
+    use colored::Colorize;
+
+    let mut regressions: Vec<&layout_audit::diff::StructChange> =
+        diff.changed.iter().filter(|c| c.size_delta > 0 || c.padding_delta > 0).collect();
+    match profile {
+        ReportProfile::Dev => format_diff_table(diff),
+        ReportProfile::Ci => {
+            if regressions.is_empty() {
+                return "No regressions.\n".to_string();
+            }
+            let mut out = format!("{}\n", "Regressions:".red().bold());
+            for c in &regressions {
+                out.push_str(&format_struct_change(c));
+            }
+            out.push_str(&format!(
+                "\nSummary: {} regression(s) among {} changed struct(s)\n",
+                regressions.len(),
+                diff.changed.len()
+            ));
+            out
+        }
+        ReportProfile::Exec => {
+            regressions.sort_by_key(|c| std::cmp::Reverse((c.size_delta, c.padding_delta)));
+            let mut out = String::new();
+            if !regressions.is_empty() {
+                let shown = regressions.len().min(EXEC_TOP_OFFENDERS);
+                out.push_str(&format!(
+                    "{}\n",
+                    format!("Largest growth ({} of {}):", shown, regressions.len()).red().bold()
+                ));
+                for c in &regressions[..shown] {
+                    out.push_str(&format!(
+                        "  ~ {} (size {:+}, padding {:+})\n",
+                        c.name, c.size_delta, c.padding_delta
+                    ));
+                }
+                out.push('\n');
             }
+            let signed = |v: u64| v as i64;
+            let size_delta = diff.added.iter().map(|s| signed(s.size)).sum::<i64>()
+                - diff.removed.iter().map(|s| signed(s.size)).sum::<i64>()
+                + diff.changed.iter().map(|c| c.size_delta).sum::<i64>();
+            let padding_delta = diff.added.iter().map(|s| signed(s.padding_bytes)).sum::<i64>()
+                - diff.removed.iter().map(|s| signed(s.padding_bytes)).sum::<i64>()
+                + diff.changed.iter().map(|c| c.padding_delta).sum::<i64>();
+            out.push_str(&format!(
+                "Totals: {} added, {} removed, {} changed ({} grew), {} unchanged; \
+                 net size {:+} bytes, net padding {:+} bytes\n",
+                diff.added.len(),
+                diff.removed.len(),
+                diff.changed.len(),
+                regressions.len(),
+                diff.unchanged_count,
+                size_delta,
+                padding_delta
+            ));
+            out
+        }
+    }
+}
+
+fn run_compare_builds(config: &CompareBuildsConfig<'_>) -> Result<bool> {
+    let CompareBuildsConfig {
+        debug_path,
+        release_path,
+        filter,
+        output_format,
+        sarif_max_results,
+        cache_line_size,
+        include_go_runtime,
+        exported_only,
+        sort_stable,
+        config_path,
+        ..
+    } = *config;
+    if matches!(output_format, OutputFormat::Csv | OutputFormat::Jsonl) {
+        bail!("compare-builds supports table, json, and sarif output");
+    }
+
+    let file_config = match config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let scope = file_config.analyze.scope()?;
+
+    let debug_binary = BinaryData::load(debug_path)
+        .with_context(|| format!("Failed to load debug binary: {}", debug_path.display()))?;
+    let release_binary = BinaryData::load(release_path)
+        .with_context(|| format!("Failed to load release binary: {}", release_path.display()))?;
+
+    let debug_loaded =
+        debug_binary.load_dwarf().context("Failed to load DWARF from debug binary")?;
+    let release_loaded =
+        release_binary.load_dwarf().context("Failed to load DWARF from release binary")?;
+
+    if let Some(depfile) = &config.depfile {
+        let mut inputs = debug_binary.input_files();
+        inputs.extend(release_binary.input_files());
+        inputs.extend(config_path.filter(|p| !is_stdin(p)).map(Path::to_path_buf));
+        depfile.write(&inputs)?;
+    }
+
+    let debug_dwarf = DwarfContext::new(&debug_loaded).with_scope(scope.clone());
+    let release_dwarf = DwarfContext::new(&release_loaded).with_scope(scope);
+
+    let mut debug_layouts = find_layouts(
+        &debug_binary,
+        &debug_dwarf,
+        filter,
+        include_go_runtime,
+        exported_only,
+        sort_stable,
+    )?;
+    let mut release_layouts = find_layouts(
+        &release_binary,
+        &release_dwarf,
+        filter,
+        include_go_runtime,
+        exported_only,
+        sort_stable,
+    )?;
+
+    apply_type_sizes(&file_config.type_sizes, &mut debug_layouts);
+    apply_type_sizes(&file_config.type_sizes, &mut release_layouts);
+    for layout in debug_layouts.iter_mut().chain(release_layouts.iter_mut()) {
+        analyze_layout(layout, cache_line_size);
+    }
+
+    let comparison = compare_builds(&debug_layouts, &release_layouts);
+
+    match output_format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&comparison)?);
+        }
+        OutputFormat::Table => {
+            print_compare_builds_table(&comparison);
+        }
+        OutputFormat::Sarif => {
+            let formatter = SarifFormatter::new().with_max_results(sarif_max_results);
+            println!("{}", formatter.format_compare_builds(&comparison));
+        }
+        OutputFormat::Csv | OutputFormat::Jsonl => {
+            unreachable!("compare-builds rejects csv and jsonl output up front")
+        }
+    }
+
+    Ok(comparison.has_unexplained())
+}
+
+fn run_compare_targets(config: &CompareTargetsConfig<'_>) -> Result<()> {
+    if matches!(config.output_format, OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl)
+    {
+        bail!("compare-targets supports table and json output");
+    }
+    let file_config = match config.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let scope = file_config.analyze.scope()?;
+
+    // Each side is labelled with its path and the target the binary was built for.
+    let analyze = |path: &Path| -> Result<(String, Vec<StructLayout>)> {
+        let binary = BinaryData::load(path)
+            .with_context(|| format!("Failed to load binary: {}", path.display()))?;
+        let loaded = binary
+            .load_dwarf()
+            .with_context(|| format!("Failed to load DWARF from {}", path.display()))?;
+        let label = format!("{} ({})", path.display(), loaded.target.triple);
+        let dwarf = DwarfContext::new(&loaded).with_scope(scope.clone());
+        let mut layouts =
+            find_layouts(&binary, &dwarf, config.filter, config.include_go_runtime, false, true)?;
+        apply_type_sizes(&file_config.type_sizes, &mut layouts);
+        for layout in &mut layouts {
+            analyze_layout(layout, config.cache_line_size);
+        }
+        Ok((label, layouts))
+    };
+    let (old_target, old_layouts) = analyze(config.old_path)?;
+    let (new_target, new_layouts) = analyze(config.new_path)?;
+    let comparison = compare_targets(old_target, &old_layouts, new_target, &new_layouts);
+
+    let output = match config.output_format {
+        OutputFormat::Json => TargetsJsonFormatter::new(config.pretty).format(&comparison),
+        _ => TargetsTableFormatter::new(config.no_color).format(&comparison),
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+fn print_compare_builds_table(comparison: &layout_audit::BuildComparison) {
+    use colored::Colorize;
+
+    if !comparison.has_differences() {
+        println!("Debug and release layouts are identical.");
+        return;
+    }
+
+    let (expected, unexplained): (Vec<_>, Vec<_>) =
+        comparison.differences.iter().partition(|d| d.kind.is_build_config());
+
+    let print_difference = |d: &layout_audit::diff::BuildDifference| {
+        let c = &d.change;
+        println!(
+            "  ~ {} (debug: {} bytes, {} padding; release: {} bytes, {} padding)",
+            c.name, c.old_size, c.old_padding, c.new_size, c.new_padding
+        );
+        for mc in &c.member_changes {
+            let label = match mc.kind {
+                layout_audit::diff::MemberChangeKind::Removed => "debug only".cyan(),
+                layout_audit::diff::MemberChangeKind::Added => "release only".cyan(),
+                _ => "~".yellow(),
+            };
+            println!("      {} {}: {}", label, mc.name, mc.details);
+        }
+    };
+
+    if !expected.is_empty() {
+        println!("{}", "Build-configuration differences:".cyan().bold());
+        for d in &expected {
+            print_difference(d);
+        }
+        println!();
+    }
+
+    if !unexplained.is_empty() {
+        println!("{}", "Unexplained differences (same members, different layout):".yellow().bold());
+        for d in &unexplained {
+            print_difference(d);
+        }
+        println!();
+    }
+
+    if !comparison.debug_only.is_empty() {
+        println!("{}", "Debug-only structs:".cyan().bold());
+        for s in &comparison.debug_only {
+            println!("  - {} ({} bytes, {} padding)", s.name, s.size, s.padding_bytes);
+        }
+        println!();
+    }
+
+    if !comparison.release_only.is_empty() {
+        println!("{}", "Release-only structs:".cyan().bold());
+        for s in &comparison.release_only {
+            println!("  + {} ({} bytes, {} padding)", s.name, s.size, s.padding_bytes);
         }
         println!();
     }
 
     println!(
-        "Summary: {} added, {} removed, {} changed, {} unchanged",
-        diff.added.len(),
-        diff.removed.len(),
-        diff.changed.len(),
-        diff.unchanged_count
+        "Summary: {} build-configuration, {} unexplained, {} debug-only, {} release-only, {} \
+         identical",
+        expected.len(),
+        unexplained.len(),
+        comparison.debug_only.len(),
+        comparison.release_only.len(),
+        comparison.unchanged_count
     );
 }
 
-fn run_check(
-    binary_path: &Path,
-    config_path: &Path,
-    output_format: OutputFormat,
-    cache_line_size: u32,
-    include_go_runtime: bool,
-) -> Result<()> {
-    if !config_path.exists() {
+fn run_check(check: &CheckConfig<'_>) -> Result<()> {
+    let CheckConfig {
+        binary_paths,
+        config_path,
+        profile_path,
+        previous_path,
+        list_suppressions,
+        lint_config,
+        validate_config_only,
+        lockfile_path,
+        accept,
+        budget_match,
+        fail_on,
+        output_format,
+        sarif_max_results,
+        cache_line_size,
+        include_go_runtime,
+        exported_only,
+        sort_stable,
+        time_budget,
+        ..
+    } = *check;
+    let deadline = time_budget.map(|b| b.deadline);
+
+    if lint_config {
+        return run_lint_config(config_path, output_format);
+    }
+    if validate_config_only {
+        return run_validate_config(config_path, check.atomic_patterns_file, output_format);
+    }
+    if matches!(output_format, OutputFormat::Csv | OutputFormat::Jsonl) {
+        bail!("check supports table, json, and sarif output");
+    }
+
+    // With --accept the old lock is only read to report what changed.
+    let lock = SizeLock::load(lockfile_path)?;
+    let uses_lock = accept || lock.is_some();
+
+    let source = read_check_config(config_path, std::env::var(BUDGETS_ENV).ok())?;
+    let loaded = match &source {
+        Some(source) => Some((Config::parse(&source.text, &source.origin)?, source.is_file)),
+        None => None,
+    };
+    if loaded.is_none() && !uses_lock {
         bail!(
             "Config file not found: {}\n\nCreate a .layout-audit.yaml with budget constraints:\n\n\
             budgets:\n  MyStruct:\n    max_size: 64\n    max_padding: 8\n    max_padding_percent: 10.0\n\n\
-            Glob patterns are supported:\n  \"*Padding\":\n    max_padding_percent: 15.0",
-            config_path.display()
+            Glob patterns are supported:\n  \"*Padding\":\n    max_padding_percent: 15.0\n\n\
+            The config can also come from stdin (--config -) or the {} environment variable.",
+            config_path.display(),
+            BUDGETS_ENV
         );
     }
+    let (config, config_is_file) = loaded.unwrap_or_default();
 
-    let config_str = std::fs::read_to_string(config_path)
-        .with_context(|| format!("Failed to read config: {}", config_path.display()))?;
-
-    let config: Config = serde_yaml::from_str(&config_str)
-        .with_context(|| format!("Failed to parse config: {}", config_path.display()))?;
+    let new_struct_policy = match (&config.new_struct_defaults, previous_path) {
+        (Some(policy), Some(_)) => {
+            policy.validate()?;
+            Some(policy)
+        }
+        (Some(_), None) => {
+            eprintln!("Note: new_struct_defaults needs --previous to tell which structs are new");
+            None
+        }
+        (None, _) => None,
+    };
+    let frozen = config.frozen_layouts()?;
+    let check_frozen = !config.frozen_layouts.is_empty() && previous_path.is_some();
+    if !config.frozen_layouts.is_empty() && previous_path.is_none() {
+        eprintln!("Note: frozen_layouts needs --previous to compare against");
+    }
 
-    if config.budgets.is_empty() {
+    if config.budgets.is_empty()
+        && new_struct_policy.is_none()
+        && !check_frozen
+        && !list_suppressions
+        && !uses_lock
+    {
         eprintln!("Warning: No budget constraints defined in config file");
         return Ok(());
     }
 
     // Compile patterns (validates and separates exact matches from globs)
-    let compiled = config.compile()?;
-
-    let binary = BinaryData::load(binary_path)
-        .with_context(|| format!("Failed to load binary: {}", binary_path.display()))?;
-
-    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
-    let dwarf = DwarfContext::new(&loaded);
+    let compiled = match budget_match {
+        BudgetMatch::Exact => config.compile()?,
+        BudgetMatch::Canonical => config.compile()?.with_canonical_matching()?,
+    };
+    let scope = config.analyze.scope()?;
+    let atomic_patterns = config.false_sharing_patterns(check.atomic_patterns_file)?;
+    let ignore = IgnoreList::new(&config.ignore)?;
+    let today = Date::today();
+    for entry in ignore.expired(today) {
+        eprintln!(
+            "Warning: ignore entry '{}' expired on {}; its structs are checked again",
+            entry.pattern,
+            entry.expires.as_deref().unwrap_or_default()
+        );
+    }
 
-    let mut layouts = dwarf.find_structs(None, include_go_runtime)?;
+    let profile = match profile_path {
+        Some(path) => load_profile(path)?,
+        None if config.budgets.values().any(|b| b.applies_to == AppliesTo::Hot) => {
+            bail!("Budgets with `applies_to: hot` require an access profile (--profile)");
+        }
+        None => std::collections::HashMap::new(),
+    };
+    let is_hot = |name: &str| profile.get(name).is_some_and(|&score| score > config.hot_threshold);
+
+    let binary_paths = expand_binary_paths(binary_paths)?;
+    let mut layouts = Vec::new();
+    let mut inputs = Vec::new();
+    let mut subjects = Vec::new();
+    for path in &binary_paths {
+        let binary = BinaryData::load(path)
+            .with_context(|| format!("Failed to load binary: {}", path.display()))?;
+        if check.attestation.is_some() {
+            subjects.push(Subject::new(path.display().to_string(), &binary.mmap));
+        }
+        let loaded = binary
+            .load_dwarf()
+            .with_context(|| format!("Failed to load DWARF debug info: {}", path.display()))?;
+        inputs.extend(binary.input_files());
+
+        let dwarf = DwarfContext::new(&loaded).with_scope(scope.clone()).with_deadline(deadline);
+        let mut found =
+            find_layouts(&binary, &dwarf, None, include_go_runtime, exported_only, sort_stable)?;
+        if binary_paths.len() > 1 {
+            record_binary(&mut found, path);
+        }
+        layouts.extend(found);
+    }
+    apply_type_sizes(&config.type_sizes, &mut layouts);
     for layout in &mut layouts {
         analyze_layout(layout, cache_line_size);
     }
 
-    let layout_names: std::collections::HashSet<&str> =
-        layouts.iter().map(|l| l.name.as_str()).collect();
+    if let Some(depfile) = &check.depfile {
+        if config_is_file {
+            inputs.push(config_path.to_path_buf());
+        }
+        if lock.is_some() && !accept {
+            inputs.push(lockfile_path.to_path_buf());
+        }
+        inputs.extend(profile_path.map(Path::to_path_buf));
+        inputs.extend(previous_path.map(Path::to_path_buf));
+        inputs.extend(check.atomic_patterns_file.map(Path::to_path_buf));
+        depfile.write(&inputs)?;
+    }
+
+    let previous_layouts: Option<Vec<StructLayout>> = match previous_path {
+        Some(path) if new_struct_policy.is_some() || check_frozen => {
+            let previous = BinaryData::load(path)
+                .with_context(|| format!("Failed to load previous binary: {}", path.display()))?;
+            let loaded =
+                previous.load_dwarf().context("Failed to load DWARF from previous binary")?;
+            let dwarf = DwarfContext::new(&loaded).with_scope(scope).with_deadline(deadline);
+            let mut layouts =
+                find_layouts(&previous, &dwarf, None, include_go_runtime, exported_only, false)?;
+            apply_type_sizes(&config.type_sizes, &mut layouts);
+            for layout in &mut layouts {
+                analyze_layout(layout, cache_line_size);
+            }
+            Some(layouts)
+        }
+        _ => None,
+    };
+    // A partial scan would drop unread structs from an accepted lockfile.
+    let out_of_time = time_budget.filter(TimeBudget::expired);
+    if let Some(budget) = out_of_time
+        && (accept || !budget.emit_partial)
+    {
+        return Err(OutOfTime::from(budget).into());
+    }
 
-    // Warn about unmatched exact budget names
+    // Names present in the older build; anything else is new for `new_struct_defaults`.
+    let previous_names: Option<std::collections::HashSet<&str>> = previous_layouts
+        .as_ref()
+        .filter(|_| new_struct_policy.is_some())
+        .map(|layouts| layouts.iter().map(|l| l.name.as_str()).collect());
+
+    if list_suppressions {
+        let mut scanner = SuppressionScanner::new();
+        let suppressions: Vec<SourceSuppression> = layouts
+            .iter()
+            .filter_map(|l| scanner.find(&l.name, l.source_location.as_ref()?))
+            .collect();
+        warn_unknown_suppression_rules(&suppressions);
+        return print_suppressions(&suppressions, output_format);
+    }
+
+    let matched_keys: std::collections::HashSet<&str> =
+        layouts.iter().filter_map(|l| compiled.exact_key(&l.name)).collect();
+
+    // Warn about unmatched exact budget names, pointing at names that were probably meant
     for name in compiled.exact.keys() {
-        if !layout_names.contains(name.as_str()) {
-            eprintln!("Warning: Budget defined for '{}' but struct not found in binary", name);
+        if !matched_keys.contains(name.as_str()) {
+            let similar = near_misses(name, layouts.iter().map(|l| l.name.as_str()));
+            if similar.is_empty() {
+                eprintln!("Warning: Budget defined for '{}' but struct not found in binary", name);
+            } else {
+                let shown: Vec<&str> = similar.iter().take(MAX_NEAR_MISSES).copied().collect();
+                eprintln!(
+                    "Warning: Budget defined for '{}' but struct not found in binary \
+                     (similar: {}{})",
+                    name,
+                    shown.join(", "),
+                    if similar.len() > shown.len() { ", ..." } else { "" }
+                );
+            }
         }
     }
 
@@ -367,6 +2349,7 @@ fn run_check(
     let mut pattern_matched = vec![false; compiled.patterns.len()];
 
     let mut violations: Vec<CheckViolation> = Vec::new();
+    let mut cold_skipped = 0usize;
 
     for layout in &layouts {
         if let Some((budget, pattern_idx)) = compiled.find_budget(&layout.name) {
@@ -375,617 +2358,4791 @@ fn run_check(
                 pattern_matched[idx] = true;
             }
 
-            let source_location = layout.source_location.clone();
-            if let Some(max_size) = budget.max_size
-                && layout.size > max_size
-            {
-                violations.push(CheckViolation {
-                    struct_name: layout.name.clone(),
-                    kind: CheckViolationKind::MaxSize,
-                    message: format!(
-                        "{}: size {} exceeds budget {} (+{} bytes)",
-                        layout.name,
-                        layout.size,
-                        max_size,
-                        layout.size - max_size
-                    ),
-                    source_location: source_location.clone(),
-                });
-            }
-            if let Some(max_padding) = budget.max_padding
-                && layout.metrics.padding_bytes > max_padding
-            {
-                violations.push(CheckViolation {
-                    struct_name: layout.name.clone(),
-                    kind: CheckViolationKind::MaxPaddingBytes,
-                    message: format!(
-                        "{}: padding {} exceeds budget {} (+{} bytes)",
-                        layout.name,
-                        layout.metrics.padding_bytes,
-                        max_padding,
-                        layout.metrics.padding_bytes - max_padding
-                    ),
-                    source_location: source_location.clone(),
-                });
+            if budget.applies_to == AppliesTo::Hot && !is_hot(&layout.name) {
+                cold_skipped += 1;
+                continue;
             }
-            if let Some(max_pct) = budget.max_padding_percent {
-                const EPSILON: f64 = 1e-6;
-                if layout.metrics.padding_percentage > max_pct + EPSILON {
-                    violations.push(CheckViolation {
-                        struct_name: layout.name.clone(),
-                        kind: CheckViolationKind::MaxPaddingPercent,
-                        message: format!(
-                            "{}: padding {:.1}% exceeds budget {:.1}% (+{:.1} percentage points)",
-                            layout.name,
-                            layout.metrics.padding_percentage,
-                            max_pct,
-                            layout.metrics.padding_percentage - max_pct
-                        ),
-                        source_location: source_location.clone(),
-                    });
-                }
+
+            for member in budget.unknown_members(layout) {
+                eprintln!(
+                    "Warning: Budget for '{}' constrains member '{}', which it does not have",
+                    layout.name, member
+                );
             }
-            if let Some(max_fs) = budget.max_false_sharing_warnings {
-                let fs = analyze_false_sharing(layout, cache_line_size);
-                // Clamp to u32::MAX to prevent truncation on 64-bit platforms
-                let warning_count = fs.warnings.len().min(u32::MAX as usize) as u32;
-                if warning_count > max_fs {
-                    violations.push(CheckViolation {
-                        struct_name: layout.name.clone(),
-                        kind: CheckViolationKind::MaxFalseSharingWarnings,
-                        message: format!(
-                            "{}: {} potential false sharing issue(s) exceeds limit of {}",
-                            layout.name, warning_count, max_fs
-                        ),
-                        source_location: source_location.clone(),
-                    });
-                }
+            let read_path = config.read_path(&layout.name);
+            // Glob budgets cover structs without a declared read path by design.
+            if pattern_idx.is_none() && budget.max_over_fetch.is_some() && read_path.is_none() {
+                eprintln!(
+                    "Warning: Budget for '{}' sets max_over_fetch, but no access_patterns or \
+                     hot_fields entry declares its hot members",
+                    layout.name
+                );
             }
+            let found = budget.violations(
+                layout,
+                config.allocator,
+                &atomic_patterns,
+                cache_line_size,
+                read_path,
+            );
+            violations.extend(found.into_iter().map(|v| with_binary(v, layout)));
         }
     }
 
-    // Warn about glob patterns that matched nothing
-    for (i, matched) in pattern_matched.iter().enumerate() {
-        if !*matched {
+    if accept {
+        let accepted = SizeLock::from_layouts(&layouts);
+        let changed = match &lock {
+            Some(old) => {
+                accepted.sizes.iter().filter(|(n, s)| old.locked_size(n) != Some(**s)).count()
+            }
+            None => accepted.sizes.len(),
+        };
+        accepted
+            .save(lockfile_path)
+            .with_context(|| format!("Failed to write lockfile: {}", lockfile_path.display()))?;
+        eprintln!(
+            "Accepted sizes of {} struct(s) into {} ({} changed)",
+            accepted.sizes.len(),
+            lockfile_path.display(),
+            changed
+        );
+    } else if let Some(lock) = &lock {
+        let mut shrunk = 0usize;
+        for change in lock.changes(&layouts) {
+            if change.delta() < 0 {
+                shrunk += 1;
+                continue;
+            }
+            let violation = CheckViolation {
+                struct_name: change.layout.name.clone(),
+                kind: CheckViolationKind::LockedSize,
+                message: format!(
+                    "{}: size {} exceeds locked size {} (+{} bytes)",
+                    change.layout.name,
+                    change.layout.size,
+                    change.locked,
+                    change.delta()
+                ),
+                source_location: change.layout.source_location.clone(),
+                new_struct_policy: false,
+                severity: Severity::Error,
+            };
+            violations.push(with_binary(violation, change.layout));
+        }
+        if shrunk > 0 {
             eprintln!(
-                "Warning: Pattern '{}' did not match any structs",
-                compiled.patterns[i].original_pattern
+                "Note: {} struct(s) shrank below their locked size; run with --accept to tighten {}",
+                shrunk,
+                lockfile_path.display()
             );
         }
     }
 
+    if let (true, Some(previous)) = (check_frozen, &previous_layouts) {
+        let frozen_in = |layouts: &[StructLayout]| -> Vec<StructLayout> {
+            layouts.iter().filter(|l| frozen.is_match(&l.name)).cloned().collect()
+        };
+        let diff = diff_layouts(&frozen_in(previous), &frozen_in(&layouts));
+        violations.extend(diff.changed.iter().map(frozen_layout_violation));
+    }
+
+    // Structs matched by a budget are held to it instead of the new-struct defaults.
+    if let (Some(policy), Some(previous_names)) = (new_struct_policy, &previous_names) {
+        for layout in &layouts {
+            if !previous_names.contains(layout.name.as_str())
+                && compiled.find_budget(&layout.name).is_none()
+            {
+                let found = policy.violations(layout);
+                violations.extend(found.into_iter().map(|v| with_binary(v, layout)));
+            }
+        }
+    }
+
+    // Violations acknowledged next to the struct's declaration are reported, not enforced.
+    let (suppressions, suppressed) = remove_suppressed(&mut violations);
+    warn_unknown_suppression_rules(&suppressions);
+    if !suppressed.is_empty() {
+        eprintln!(
+            "Note: {} violation(s) suppressed in source (list them with --list-suppressions)",
+            suppressed.len()
+        );
+    }
+    let ignored = remove_ignored(&ignore, today, &mut violations);
+    if !ignored.is_empty() {
+        eprintln!("Note: {} violation(s) ignored by the config's ignore list", ignored.len());
+    }
+
+    // Warn about glob patterns that matched nothing
+    for (i, matched) in pattern_matched.iter().enumerate() {
+        if !*matched {
+            eprintln!(
+                "Warning: Pattern '{}' did not match any structs",
+                compiled.patterns[i].original_pattern
+            );
+        }
+    }
+
+    if cold_skipped > 0 {
+        eprintln!(
+            "Note: Skipped hot-only budgets for {} struct(s) at or below hot_threshold {}",
+            cold_skipped, config.hot_threshold
+        );
+    }
+
+    // Violations below --fail-on are reported but leave the exit status alone.
+    let failing = violations.iter().filter(|v| v.severity >= fail_on).count();
+    let json_report = || -> Result<String> {
+        let output = CheckJsonOutput {
+            version: env!("CARGO_PKG_VERSION"),
+            violations: &violations,
+            suppressed: &suppressed,
+            ignored: &ignored,
+            summary: CheckSummary {
+                total_violations: violations.len(),
+                failing_violations: failing,
+            },
+        };
+        Ok(serde_json::to_string_pretty(&output)? + "\n")
+    };
     match output_format {
         OutputFormat::Table => {
             if violations.is_empty() {
                 println!("All structs within budget constraints");
-                Ok(())
-            } else {
-                use colored::Colorize;
-                eprintln!("{}", "Budget violations:".red().bold());
-                for v in &violations {
-                    eprintln!("  {}", v.message);
-                }
-                bail!("Budget check failed: {} violation(s)", violations.len());
-            }
-        }
-        OutputFormat::Json => {
-            let output = CheckJsonOutput {
-                version: env!("CARGO_PKG_VERSION"),
-                violations: &violations,
-                summary: CheckSummary { total_violations: violations.len() },
-            };
-            println!("{}", serde_json::to_string_pretty(&output)?);
-            if violations.is_empty() {
-                Ok(())
             } else {
-                bail!("Budget check failed: {} violation(s)", violations.len());
+                print_violations(&violations);
             }
         }
+        OutputFormat::Json => print!("{}", json_report()?),
         OutputFormat::Sarif => {
-            let formatter = SarifFormatter::new();
-            println!("{}", formatter.format_check(&violations));
-            if violations.is_empty() {
-                Ok(())
-            } else {
-                bail!("Budget check failed: {} violation(s)", violations.len());
-            }
+            let formatter = SarifFormatter::new().with_max_results(sarif_max_results);
+            println!("{}", formatter.format_check_with_ignored(&violations, &suppressed, &ignored));
+        }
+        OutputFormat::Csv | OutputFormat::Jsonl => {
+            unreachable!("check rejects csv and jsonl output up front")
         }
     }
+
+    // A run cut short by --budget-time is not attested: its report covers only part of the
+    // binary, so it is no evidence either way.
+    if let Some(budget) = out_of_time {
+        return Err(OutOfTime::from(budget).into());
+    }
+    // Written before failing on violations, so a failed check leaves evidence too.
+    if let Some(attestation) = &check.attestation {
+        let results = CheckResults::new(json_report()?.as_bytes(), violations.len(), failing);
+        let mut statement = Statement::new(results).with_subjects(subjects);
+        if let Some(source) = &source {
+            statement = statement.with_config(&source.origin, source.text.as_bytes());
+        }
+        attestation.write(&statement)?;
+    }
+
+    if failing > 0 {
+        bail!("Budget check failed: {} violation(s)", failing);
+    }
+    if !violations.is_empty() {
+        eprintln!(
+            "Note: {} violation(s) below --fail-on {} reported without failing",
+            violations.len(),
+            fail_on
+        );
+    }
+    Ok(())
 }
 
-#[derive(serde::Serialize)]
-struct CheckJsonOutput<'a> {
-    version: &'static str,
-    violations: &'a [CheckViolation],
-    summary: CheckSummary,
+/// Name the binary in a violation's message when several binaries are checked together.
+fn with_binary(mut violation: CheckViolation, layout: &StructLayout) -> CheckViolation {
+    if let Some(binary) = &layout.binary {
+        violation.message = format!("{} (in {})", violation.message, binary);
+    }
+    violation
 }
 
-#[derive(serde::Serialize)]
-struct CheckSummary {
-    total_violations: usize,
+/// Print budget violations to stderr, with lockfile and new-struct policy violations listed
+/// separately.
+fn print_violations(violations: &[CheckViolation]) {
+    use colored::Colorize;
+
+    let (policy, budgets): (Vec<_>, Vec<_>) = violations.iter().partition(|v| v.new_struct_policy);
+    let (locked, budgets): (Vec<_>, Vec<_>) =
+        budgets.into_iter().partition(|v| matches!(v.kind, CheckViolationKind::LockedSize));
+    let (frozen, budgets): (Vec<_>, Vec<_>) =
+        budgets.into_iter().partition(|v| matches!(v.kind, CheckViolationKind::FrozenLayout));
+    if !budgets.is_empty() {
+        eprintln!("{}", "Budget violations:".red().bold());
+        for v in &budgets {
+            eprintln!("  {}", labeled(v));
+        }
+    }
+    if !locked.is_empty() {
+        eprintln!("{}", "Locked size increases:".red().bold());
+        for v in &locked {
+            eprintln!("  {}", v.message);
+        }
+    }
+    if !frozen.is_empty() {
+        eprintln!("{}", "Frozen layout changes:".red().bold());
+        for v in &frozen {
+            eprintln!("  {}", labeled(v));
+        }
+    }
+    if !policy.is_empty() {
+        eprintln!("{}", "New struct policy violations:".red().bold());
+        for v in &policy {
+            eprintln!("  {}", labeled(v));
+        }
+    }
 }
 
-#[derive(serde::Deserialize)]
-struct Config {
-    #[serde(default)]
-    budgets: indexmap::IndexMap<String, Budget>,
+/// A `frozen_layouts` struct whose layout differs from the previous build.
+fn frozen_layout_violation(change: &StructChange) -> CheckViolation {
+    let mut changes = Vec::new();
+    if change.size_delta != 0 {
+        changes.push(format!("size {} -> {}", change.old_size, change.new_size));
+    }
+    for member in &change.member_changes {
+        let what = match member.kind {
+            MemberChangeKind::Added => "added",
+            MemberChangeKind::Removed => "removed",
+            MemberChangeKind::Renamed => "renamed",
+            MemberChangeKind::OffsetChanged => "moved",
+            MemberChangeKind::SizeChanged => "resized",
+            MemberChangeKind::TypeChanged => "retyped",
+        };
+        changes.push(format!("{} {}", member.name, what));
+    }
+    CheckViolation {
+        struct_name: change.name.clone(),
+        kind: CheckViolationKind::FrozenLayout,
+        message: format!(
+            "{}: frozen layout changed since the previous build: {}",
+            change.name,
+            changes.join(", ")
+        ),
+        // The first changed member still declared points closer than the struct itself.
+        source_location: change
+            .member_changes
+            .iter()
+            .filter(|m| m.kind != MemberChangeKind::Removed)
+            .find_map(|m| m.source_location.clone())
+            .or_else(|| change.source_location.clone()),
+        new_struct_policy: false,
+        severity: Severity::Error,
+    }
 }
 
-#[derive(serde::Deserialize, Clone)]
-struct Budget {
-    max_size: Option<u64>,
-    max_padding: Option<u64>,
-    max_padding_percent: Option<f64>,
-    max_false_sharing_warnings: Option<u32>,
+/// A violation's message, tagged with its severity unless it is an error.
+fn labeled(violation: &CheckViolation) -> String {
+    match violation.severity {
+        Severity::Error => violation.message.clone(),
+        severity => format!("[{}] {}", severity, violation.message),
+    }
 }
 
-impl Budget {
-    fn validate(&self, name: &str) -> Result<()> {
-        if let Some(max_pct) = self.max_padding_percent {
-            if !max_pct.is_finite() {
-                bail!("Invalid budget for '{}': max_padding_percent must be a finite number", name);
-            }
-            if max_pct < 0.0 {
-                bail!(
-                    "Invalid budget for '{}': max_padding_percent cannot be negative (got {:.1})",
-                    name,
-                    max_pct
+/// Move violations allowed by `layout-audit: allow(...)` markers out of `violations`.
+/// Returns the markers consulted and the suppressed violations.
+fn remove_suppressed(
+    violations: &mut Vec<CheckViolation>,
+) -> (Vec<SourceSuppression>, Vec<CheckViolation>) {
+    let mut scanner = SuppressionScanner::new();
+    let mut suppressions: Vec<SourceSuppression> = Vec::new();
+    let mut suppressed: Vec<CheckViolation> = Vec::new();
+    violations.retain(|v| {
+        let Some(location) = &v.source_location else {
+            return true;
+        };
+        if !suppressions.iter().any(|s| s.struct_name == v.struct_name)
+            && let Some(found) = scanner.find(&v.struct_name, location)
+        {
+            suppressions.push(found);
+        }
+        let allowed = suppressions
+            .iter()
+            .any(|s| s.struct_name == v.struct_name && s.allows(v.kind.budget_key()));
+        if allowed {
+            suppressed.push(v.clone());
+        }
+        !allowed
+    });
+    (suppressions, suppressed)
+}
+
+/// Move violations by structs on the config's `ignore` list (and not expired by `today`) out
+/// of `violations`.
+fn remove_ignored(
+    ignore: &IgnoreList,
+    today: Date,
+    violations: &mut Vec<CheckViolation>,
+) -> Vec<IgnoredViolation> {
+    if ignore.is_empty() {
+        return Vec::new();
+    }
+    let mut ignored = Vec::new();
+    violations.retain(|v| {
+        let Some(entry) = ignore.find(&v.struct_name, today) else {
+            return true;
+        };
+        ignored.push(IgnoredViolation {
+            violation: v.clone(),
+            pattern: entry.pattern.clone(),
+            reason: entry.reason.clone(),
+            expires: entry.expires.clone(),
+        });
+        false
+    });
+    ignored
+}
+
+/// Warn about `allow(...)` rules that name no budget limit, which would never match.
+fn warn_unknown_suppression_rules(suppressions: &[SourceSuppression]) {
+    for s in suppressions {
+        for rule in &s.rules {
+            let known = rule == ALLOW_ALL
+                || CheckViolationKind::ALL.iter().any(|k| k.budget_key() == rule.as_str());
+            if !known {
+                eprintln!(
+                    "Warning: Unknown rule '{}' in layout-audit: allow for {} ({}:{})",
+                    rule, s.struct_name, s.location.file, s.location.line
                 );
             }
-            if max_pct > 100.0 {
-                bail!(
-                    "Invalid budget for '{}': max_padding_percent cannot exceed 100 (got {:.1})",
-                    name,
-                    max_pct
+        }
+    }
+}
+
+fn print_suppressions(
+    suppressions: &[SourceSuppression],
+    output_format: OutputFormat,
+) -> Result<()> {
+    match output_format {
+        OutputFormat::Table => {
+            if suppressions.is_empty() {
+                println!("No in-source suppressions found");
+            }
+            for s in suppressions {
+                println!(
+                    "{} ({}:{}): allow({})",
+                    s.struct_name,
+                    s.location.file,
+                    s.location.line,
+                    s.rules.join(", ")
                 );
             }
         }
-        if let Some(max_size) = self.max_size
-            && max_size == 0
-        {
-            bail!("Invalid budget for '{}': max_size must be greater than 0", name);
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "suppressions": suppressions,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl => {
+            bail!("--list-suppressions supports table and json output")
         }
-        Ok(())
     }
+    Ok(())
 }
 
-/// Check if a pattern string contains glob metacharacters
-fn is_glob_pattern(s: &str) -> bool {
-    s.contains('*') || s.contains('?') || s.contains('[') || s.contains('{')
-}
-
-/// Compiled budget patterns for efficient matching
-struct CompiledBudgets {
-    /// Exact name matches (O(1) lookup)
-    exact: std::collections::HashMap<String, Budget>,
-    /// Glob patterns in declaration order
-    patterns: Vec<CompiledPattern>,
+/// What `check --lint-config` found wrong with one budget name.
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum ConfigLint {
+    /// Two glob budgets match a common name; the earlier one wins it.
+    OverlappingPatterns { pattern: String, earlier: String, example: String },
+    /// Every name the glob matches is taken by earlier globs, so its budget never applies.
+    ShadowedBudget { pattern: String, earlier: Vec<String> },
+    /// An exact budget name a glob also matches; the exact budget wins.
+    ExactAlsoMatched { name: String, patterns: Vec<String> },
 }
 
-struct CompiledPattern {
-    glob: globset::GlobMatcher,
-    budget: Budget,
-    original_pattern: String,
+impl std::fmt::Display for ConfigLint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OverlappingPatterns { pattern, earlier, example } => write!(
+                f,
+                "'{}' overlaps earlier pattern '{}' (both match '{}'); '{}' wins",
+                pattern, earlier, example, earlier
+            ),
+            Self::ShadowedBudget { pattern, earlier } => write!(
+                f,
+                "'{}' never applies: every name it matches is taken by {}",
+                pattern,
+                earlier.iter().map(|p| format!("'{}'", p)).collect::<Vec<_>>().join(", ")
+            ),
+            Self::ExactAlsoMatched { name, patterns } => write!(
+                f,
+                "'{}' is also matched by {}; the exact budget wins",
+                name,
+                patterns.iter().map(|p| format!("'{}'", p)).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
 }
 
-impl Config {
-    /// Compile budget patterns for efficient matching.
-    /// Separates exact matches from glob patterns.
-    fn compile(&self) -> Result<CompiledBudgets> {
-        use globset::GlobBuilder;
+/// Budget names that resolve by declaration order or precedence rather than intent.
+fn lint_budget_names(config: &Config) -> Result<Vec<ConfigLint>> {
+    let compiled = config.compile()?;
+    let mut lints = Vec::new();
+
+    let mut globs: Vec<&str> = Vec::new();
+    for name in config.budgets.keys().filter(|name| is_glob_pattern(name)) {
+        let mut overlaps = Vec::new();
+        for &earlier in &globs {
+            if let Some(example) = overlap_example(earlier, name)? {
+                overlaps.push((earlier, example));
+            }
+        }
+        if !overlaps.is_empty() && is_shadowed(name, &globs)? {
+            lints.push(ConfigLint::ShadowedBudget {
+                pattern: name.clone(),
+                earlier: overlaps.into_iter().map(|(earlier, _)| earlier.to_string()).collect(),
+            });
+        } else {
+            lints.extend(overlaps.into_iter().map(|(earlier, example)| {
+                ConfigLint::OverlappingPatterns {
+                    pattern: name.clone(),
+                    earlier: earlier.to_string(),
+                    example,
+                }
+            }));
+        }
+        globs.push(name);
+    }
 
-        let mut exact = std::collections::HashMap::new();
-        let mut patterns = Vec::new();
+    for name in config.budgets.keys().filter(|name| !is_glob_pattern(name)) {
+        let patterns: Vec<String> = compiled
+            .patterns
+            .iter()
+            .filter(|p| p.glob.is_match(name))
+            .map(|p| p.original_pattern.clone())
+            .collect();
+        if !patterns.is_empty() {
+            lints.push(ConfigLint::ExactAlsoMatched { name: name.clone(), patterns });
+        }
+    }
+    Ok(lints)
+}
 
-        for (name, budget) in &self.budgets {
-            if name.is_empty() {
-                bail!("Empty budget pattern name is not allowed");
+/// `check --lint-config`: report ambiguous budget names without reading a binary.
+fn run_lint_config(config_path: &Path, output_format: OutputFormat) -> Result<()> {
+    let Some((config, _)) = load_check_config(config_path, std::env::var(BUDGETS_ENV).ok())? else {
+        bail!("Config file not found: {}", config_path.display());
+    };
+    let lints = lint_budget_names(&config)?;
+    match output_format {
+        OutputFormat::Table => {
+            if lints.is_empty() {
+                println!("No ambiguous budget names found");
             }
+            for lint in &lints {
+                println!("{}", lint);
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "lints": lints,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl => {
+            bail!("--lint-config supports table and json output")
+        }
+    }
+    if !lints.is_empty() {
+        bail!("Config lint failed: {} finding(s)", lints.len());
+    }
+    Ok(())
+}
 
-            budget.validate(name)?;
+/// A problem `check --validate-config-only` found, at the key it concerns when known.
+#[derive(serde::Serialize)]
+struct ConfigIssue {
+    /// Key path such as `budgets.Foo.max_paddding`; empty for document-level errors.
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    column: Option<usize>,
+    message: String,
+}
 
-            if is_glob_pattern(name) {
-                let glob = GlobBuilder::new(name)
-                    .literal_separator(false) // * matches ::
-                    .build()
-                    .with_context(|| format!("Invalid glob pattern: '{}'", name))?
-                    .compile_matcher();
+impl ConfigIssue {
+    /// An issue at `path`, located in `text`.
+    fn at(text: &str, path: &[PathSegment], message: String) -> Self {
+        let location = yaml::locate(text, path);
+        ConfigIssue {
+            path: yaml::DisplayPath(path).to_string(),
+            line: location.map(|(line, _)| line),
+            column: location.map(|(_, column)| column),
+            message,
+        }
+    }
 
-                patterns.push(CompiledPattern {
-                    glob,
-                    budget: budget.clone(),
-                    original_pattern: name.clone(),
-                });
-            } else {
-                exact.insert(name.clone(), budget.clone());
+    /// A YAML syntax or type error, which serde_yaml locates itself.
+    fn from_parse_error(error: &serde_yaml::Error) -> Self {
+        let location = error.location();
+        let mut message = error.to_string();
+        if let Some(location) = &location {
+            let suffix = format!(" at line {} column {}", location.line(), location.column());
+            if let Some(stripped) = message.strip_suffix(&suffix) {
+                message = stripped.to_string();
             }
         }
-
-        Ok(CompiledBudgets { exact, patterns })
+        // serde_yaml leads with the key path, as in `budgets.Foo.max_size: invalid type: ...`
+        let (path, message) = match message.split_once(": ") {
+            Some((path, rest)) if !path.contains(' ') => (path.to_string(), rest.to_string()),
+            _ => (String::new(), message),
+        };
+        ConfigIssue {
+            path,
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+            message,
+        }
     }
-}
 
-impl CompiledBudgets {
-    /// Find the budget for a struct name.
-    /// Returns (budget, pattern_index) where pattern_index is Some if matched by a glob.
-    fn find_budget(&self, struct_name: &str) -> Option<(&Budget, Option<usize>)> {
-        // Exact match takes priority
-        if let Some(budget) = self.exact.get(struct_name) {
-            return Some((budget, None));
+    /// `origin:line:column: path: message`, leaving out what is unknown.
+    fn render(&self, origin: &str) -> String {
+        let mut rendered = origin.to_string();
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            rendered.push_str(&format!(":{}:{}", line, column));
         }
-        // First matching glob wins
-        for (i, pattern) in self.patterns.iter().enumerate() {
-            if pattern.glob.is_match(struct_name) {
-                return Some((&pattern.budget, Some(i)));
-            }
+        if !self.path.is_empty() {
+            rendered.push_str(&format!(": {}", self.path));
         }
-        None
+        format!("{}: {}", rendered, self.message)
     }
 }
 
-#[allow(clippy::too_many_arguments)]
-fn run_suggest(
-    binary_path: &Path,
-    filter: Option<&str>,
-    output_format: OutputFormat,
-    min_savings: Option<u64>,
-    cache_line_size: u32,
-    pretty: bool,
-    max_align: u64,
-    sort_by_savings: bool,
-    no_color: bool,
-    include_go_runtime: bool,
-) -> Result<()> {
-    let binary = BinaryData::load(binary_path)
-        .with_context(|| format!("Failed to load binary: {}", binary_path.display()))?;
+/// Config sections with a fixed set of keys, for finding the ones parsing ignores.
+#[derive(Clone, Copy)]
+enum ConfigSection {
+    Root,
+    Analyze,
+    Budget,
+    MemberBudget,
+    NewStructDefaults,
+    AtomicPatterns,
+}
 
-    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
-    let dwarf = DwarfContext::new(&loaded);
+/// How a key's value holds a nested section.
+#[derive(Clone, Copy)]
+enum Nesting {
+    Direct,
+    /// A map from names to sections.
+    Map,
+}
 
-    let mut layouts =
-        dwarf.find_structs(filter, include_go_runtime).context("Failed to parse struct layouts")?;
+impl ConfigSection {
+    fn keys(self) -> &'static [&'static str] {
+        match self {
+            Self::Root => yaml::struct_fields::<Config>(),
+            Self::Analyze => yaml::struct_fields::<AnalyzeConfig>(),
+            Self::Budget => yaml::struct_fields::<Budget>(),
+            Self::MemberBudget => yaml::struct_fields::<MemberBudget>(),
+            Self::NewStructDefaults => yaml::struct_fields::<NewStructPolicy>(),
+            Self::AtomicPatterns => yaml::struct_fields::<AtomicPatternConfig>(),
+        }
+    }
 
-    if layouts.is_empty() {
-        if let Some(f) = filter {
-            eprintln!("No structs found matching filter: {}", f);
-        } else {
-            eprintln!("No structs found in binary");
+    /// The section the value under `key` holds, if any. (`ignore` and `type_sizes` entries
+    /// reject unknown keys while parsing.)
+    fn nested(self, key: &str) -> Option<(Nesting, ConfigSection)> {
+        match (self, key) {
+            (Self::Root, "analyze") => Some((Nesting::Direct, Self::Analyze)),
+            (Self::Root, "budgets") => Some((Nesting::Map, Self::Budget)),
+            (Self::Root, "new_struct_defaults") => Some((Nesting::Direct, Self::NewStructDefaults)),
+            (Self::Root, "atomic_patterns") => Some((Nesting::Direct, Self::AtomicPatterns)),
+            (Self::Budget, "members") => Some((Nesting::Map, Self::MemberBudget)),
+            _ => None,
         }
-        return Ok(());
     }
+}
 
-    // Analyze layouts first (needed for metrics)
-    for layout in &mut layouts {
-        analyze_layout(layout, cache_line_size);
+/// Paths of keys in `value` that `section` does not declare.
+fn unknown_config_keys(
+    value: &serde_yaml::Value,
+    section: ConfigSection,
+    path: &mut Vec<PathSegment>,
+    unknown: &mut Vec<(Vec<PathSegment>, ConfigSection)>,
+) {
+    let Some(mapping) = value.as_mapping() else {
+        return;
+    };
+    for (key, value) in mapping {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        path.push(PathSegment::Key(key.to_string()));
+        if !section.keys().contains(&key) {
+            unknown.push((path.clone(), section));
+        } else if let Some((nesting, nested)) = section.nested(key) {
+            match nesting {
+                Nesting::Direct => unknown_config_keys(value, nested, path, unknown),
+                Nesting::Map => {
+                    for (name, entry) in value.as_mapping().into_iter().flatten() {
+                        if let Some(name) = name.as_str() {
+                            path.push(PathSegment::Key(name.to_string()));
+                            unknown_config_keys(entry, nested, path, unknown);
+                            path.pop();
+                        }
+                    }
+                }
+            }
+        }
+        path.pop();
     }
+}
 
-    // Optimize each layout and keep source locations aligned
-    let mut suggestions_with_locations: Vec<_> = layouts
-        .iter()
-        .map(|l| (optimize_layout(l, max_align), l.source_location.clone()))
-        .collect();
+/// Every problem in config `text`: a parse error alone, or else unknown keys and invalid
+/// values and patterns.
+fn validate_config(text: &str, atomic_patterns_file: Option<&Path>) -> Vec<ConfigIssue> {
+    let config: Config = match serde_yaml::from_str(text) {
+        Ok(config) => config,
+        Err(error) => return vec![ConfigIssue::from_parse_error(&error)],
+    };
+    let key = |name: &str| PathSegment::Key(name.to_string());
+    let mut issues = Vec::new();
 
-    // Filter by minimum savings
-    if let Some(min) = min_savings {
-        suggestions_with_locations.retain(|(s, _)| s.savings_bytes >= min);
+    let mut unknown = Vec::new();
+    if let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(text) {
+        unknown_config_keys(&value, ConfigSection::Root, &mut Vec::new(), &mut unknown);
+    }
+    for (path, section) in unknown {
+        let Some(PathSegment::Key(name)) = path.last() else {
+            continue;
+        };
+        let known = section.keys();
+        let message = match near_misses(name, known.iter().copied()).first() {
+            Some(suggestion) => format!("unknown key '{}' (did you mean '{}'?)", name, suggestion),
+            None => format!("unknown key '{}', expected one of: {}", name, known.join(", ")),
+        };
+        issues.push(ConfigIssue::at(text, &path, message));
     }
 
-    if suggestions_with_locations.is_empty() {
-        eprintln!("No structs with optimization potential found");
-        return Ok(());
+    if let Err(message) = config.type_sizes.validate() {
+        issues.push(ConfigIssue::at(text, &[key("type_sizes")], message));
+    }
+    if let Err(error) = config.analyze.scope() {
+        issues.push(ConfigIssue::at(text, &[key("analyze")], format!("{:#}", error)));
+    }
+    for (name, budget) in &config.budgets {
+        if let Err(error) = compile_budget(name, budget) {
+            issues.push(ConfigIssue::at(
+                text,
+                &[key("budgets"), key(name)],
+                format!("{:#}", error),
+            ));
+        }
+    }
+    if let Some(Err(error)) = config.new_struct_defaults.as_ref().map(NewStructPolicy::validate) {
+        issues.push(ConfigIssue::at(text, &[key("new_struct_defaults")], format!("{:#}", error)));
     }
+    for (i, entry) in config.ignore.iter().enumerate() {
+        if let Err(error) = IgnoreList::new(std::slice::from_ref(entry)) {
+            let path = [key("ignore"), PathSegment::Index(i), key("pattern")];
+            issues.push(ConfigIssue::at(text, &path, error.to_string()));
+        }
+    }
+    if let Err(error) = config.atomic_patterns.compile(atomic_patterns_file) {
+        issues.push(ConfigIssue::at(text, &[key("atomic_patterns")], format!("{:#}", error)));
+    }
+    for (i, entry) in config.false_sharing_ignore.iter().enumerate() {
+        if let Err(error) =
+            AtomicPatterns::empty().with_ignored_members(&entry.struct_name, Vec::new())
+        {
+            let path = [key("false_sharing_ignore"), PathSegment::Index(i), key("struct")];
+            issues.push(ConfigIssue::at(text, &path, error.to_string()));
+        }
+    }
+    for (owner, patterns) in &config.thread_owners {
+        if let Err(error) = ThreadOwners::new([(owner, patterns)]) {
+            let path = [key("thread_owners"), key(owner)];
+            issues.push(ConfigIssue::at(text, &path, error.to_string()));
+        }
+    }
+    for (i, pattern) in config.frozen_layouts.iter().enumerate() {
+        let single = Config { frozen_layouts: vec![pattern.clone()], ..Config::default() };
+        if let Err(error) = single.frozen_layouts() {
+            let path = [key("frozen_layouts"), PathSegment::Index(i)];
+            issues.push(ConfigIssue::at(text, &path, format!("{:#}", error)));
+        }
+    }
+    issues.sort_by_key(|issue| (issue.line.is_none(), issue.line, issue.column));
+    issues
+}
 
-    // Sort by savings if requested
-    if sort_by_savings {
-        suggestions_with_locations.sort_by(|(a, _), (b, _)| b.savings_bytes.cmp(&a.savings_bytes));
+/// `check --validate-config-only`: report every config problem without reading a binary.
+fn run_validate_config(
+    config_path: &Path,
+    atomic_patterns_file: Option<&Path>,
+    output_format: OutputFormat,
+) -> Result<()> {
+    let Some(source) = read_check_config(config_path, std::env::var(BUDGETS_ENV).ok())? else {
+        bail!("Config file not found: {}", config_path.display());
+    };
+    let issues = validate_config(&source.text, atomic_patterns_file);
+    match output_format {
+        OutputFormat::Table => {
+            if issues.is_empty() {
+                println!("Config is valid: {}", source.origin);
+            }
+            for issue in &issues {
+                println!("{}", issue.render(&source.origin));
+            }
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "config": source.origin,
+                "issues": issues,
+            });
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl => {
+            bail!("--validate-config-only supports table and json output")
+        }
+    }
+    if !issues.is_empty() {
+        bail!("Config validation failed: {} issue(s)", issues.len());
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct CheckJsonOutput<'a> {
+    version: &'static str,
+    violations: &'a [CheckViolation],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    suppressed: &'a [CheckViolation],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    ignored: &'a [IgnoredViolation],
+    summary: CheckSummary,
+}
+
+#[derive(serde::Serialize)]
+struct CheckSummary {
+    total_violations: usize,
+    /// Violations at or above `--fail-on`, which fail the check.
+    failing_violations: usize,
+}
+
+#[derive(serde::Deserialize, Default)]
+struct Config {
+    /// Struct-name globs limiting what every command analyzes.
+    #[serde(default)]
+    analyze: AnalyzeConfig,
+    #[serde(default)]
+    budgets: indexmap::IndexMap<String, Budget>,
+    /// Member access sequences per struct, used by suggest to simulate cache line touches.
+    #[serde(default)]
+    access_patterns: indexmap::IndexMap<String, Vec<String>>,
+    /// Sampled members per struct, hottest first (written by `annotate --emit-config`). Suggest
+    /// packs them first and simulates them when a struct has no access pattern.
+    #[serde(default)]
+    hot_fields: indexmap::IndexMap<String, Vec<String>>,
+    /// Profile score a struct must exceed to count as hot for `applies_to: hot` budgets.
+    #[serde(default)]
+    hot_threshold: f64,
+    /// Limits for structs that are new relative to an older binary.
+    #[serde(default)]
+    new_struct_defaults: Option<NewStructPolicy>,
+    /// Size-class model used by `max_alloc_slack` budgets.
+    #[serde(default)]
+    allocator: AllocatorModel,
+    /// Sizes of types the debug info only declares, used for members of those types.
+    #[serde(default)]
+    type_sizes: TypeSizes,
+    /// Structs whose violations `check` reports as suppressed instead of failing on.
+    #[serde(default)]
+    ignore: Vec<IgnoreEntry>,
+    /// Columns of one-row-per-struct table output (`inspect --columns` overrides them).
+    #[serde(default)]
+    columns: Option<Vec<Column>>,
+    /// Groups of members per struct that `suggest --locality` keeps adjacent.
+    #[serde(default)]
+    field_groups: indexmap::IndexMap<String, Vec<Vec<String>>>,
+    /// Type names that false-sharing checks treat as atomics or locks.
+    #[serde(default)]
+    atomic_patterns: AtomicPatternConfig,
+    /// Members known to be safe on one cache line, exempt from false-sharing pair warnings.
+    #[serde(default)]
+    false_sharing_ignore: Vec<FalseSharingIgnore>,
+    /// Struct globs per owning thread, for `placement`.
+    #[serde(default)]
+    thread_owners: indexmap::IndexMap<String, Vec<String>>,
+    /// Struct globs whose layout is an ABI or wire format: suggest flags them as unsafe to
+    /// reorder, and `check --previous` fails if they change.
+    #[serde(default)]
+    frozen_layouts: Vec<String>,
+    /// Struct generated for each schema message, by qualified or bare message name, for
+    /// `schema-check` when the generator's naming is not recognized.
+    #[serde(default)]
+    schema_structs: indexmap::IndexMap<String, String>,
+}
+
+#[derive(serde::Deserialize, Clone)]
+struct Budget {
+    max_size: Option<u64>,
+    /// Smallest size the struct may shrink to, such as the wire format it mirrors.
+    min_size: Option<u64>,
+    /// Size the struct must keep, such as a ring-buffer slot padded to one cache line.
+    exact_size: Option<u64>,
+    max_padding: Option<u64>,
+    max_padding_percent: Option<f64>,
+    max_false_sharing_warnings: Option<u32>,
+    /// Most cache lines (of `--cache-line` bytes) the struct may span.
+    max_cache_lines: Option<u32>,
+    /// Most bytes a read of the struct's declared hot members (`access_patterns`, else
+    /// `hot_fields`) may load per byte it uses.
+    max_over_fetch: Option<f64>,
+    /// Bytes a struct may leave unused in its allocator size class (see `Config::allocator`).
+    max_alloc_slack: Option<u64>,
+    /// Largest allowed ratio of array stride to the bytes members occupy.
+    max_stride_factor: Option<f64>,
+    /// Most members a struct may declare (base classes count as members).
+    max_members: Option<usize>,
+    /// Largest size any one member may have, e.g. to keep big buffers out of line.
+    max_single_member_size: Option<u64>,
+    /// Placement limits for individual members, by member name.
+    #[serde(default)]
+    members: indexmap::IndexMap<String, MemberBudget>,
+    /// Exact byte offsets members must sit at, by member name.
+    #[serde(default)]
+    member_offsets: indexmap::IndexMap<String, u64>,
+    /// Type globs members may not store inline (pointers and references to them are fine).
+    #[serde(default)]
+    forbid_members_of_type: Vec<String>,
+    /// `forbid_members_of_type`, compiled by [`Budget::compiled`].
+    #[serde(skip)]
+    forbidden_types: Option<globset::GlobSet>,
+    #[serde(default)]
+    applies_to: AppliesTo,
+    /// How violations of this budget are reported; see `--fail-on`.
+    #[serde(default)]
+    severity: Severity,
+}
+
+/// Placement limits for one member of a budgeted struct.
+#[derive(serde::Deserialize, Clone, Default)]
+struct MemberBudget {
+    /// Exact byte offset the member must sit at, as in `member_offsets`.
+    offset: Option<u64>,
+    /// Largest allowed byte offset of the member.
+    max_offset: Option<u64>,
+    /// Another member whose bytes must share a single cache line with this one's.
+    same_cache_line_as: Option<String>,
+}
+
+/// Default limits applied to every struct added since an older binary (by `diff`, and by
+/// `check --previous`), so new types meet a baseline without being named in `budgets`.
+#[derive(serde::Deserialize, Clone, Default)]
+struct NewStructPolicy {
+    max_size: Option<u64>,
+    max_padding: Option<u64>,
+    max_padding_percent: Option<f64>,
+    max_cache_lines: Option<u32>,
+    #[serde(default)]
+    severity: Severity,
+}
+
+/// Which structs a budget is enforced for.
+#[derive(serde::Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum AppliesTo {
+    #[default]
+    All,
+    /// Only structs whose profile score exceeds `hot_threshold`.
+    Hot,
+}
+
+/// Atomic and lock types beyond the built-in set, from the config's `atomic_patterns` section
+/// or an `--atomic-patterns-file` of the same shape.
+#[derive(serde::Deserialize, Default)]
+struct AtomicPatternConfig {
+    /// Substrings of type names, such as `my_rt::SpinLock`.
+    #[serde(default)]
+    names: Vec<String>,
+    /// Regular expressions over type names.
+    #[serde(default)]
+    regex: Vec<String>,
+    /// Use only these patterns, dropping the built-in set.
+    #[serde(default)]
+    replace: bool,
+}
+
+impl AtomicPatternConfig {
+    fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read atomic patterns: {}", path.display()))?;
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse atomic patterns: {}", path.display()))
+    }
+
+    /// Patterns from this section plus those in `file`; either can replace the built-in set.
+    fn compile(&self, file: Option<&Path>) -> Result<AtomicPatterns> {
+        let file = file.map(Self::load).transpose()?;
+        let sections: Vec<&Self> = std::iter::once(self).chain(file.as_ref()).collect();
+        let mut patterns = if sections.iter().any(|s| s.replace) {
+            AtomicPatterns::empty()
+        } else {
+            AtomicPatterns::default()
+        };
+        for section in sections {
+            for name in &section.names {
+                patterns = patterns.with_substring(name.clone());
+            }
+            for pattern in &section.regex {
+                patterns =
+                    patterns.with_regex(pattern).context("Invalid `atomic_patterns` regex")?;
+            }
+        }
+        Ok(patterns)
+    }
+}
+
+/// Atomics of one struct that are intentionally packed together, e.g. because they are always
+/// modified under one lock.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FalseSharingIgnore {
+    /// Struct-name glob.
+    #[serde(rename = "struct")]
+    struct_name: String,
+    /// Members that may share a cache line with each other.
+    members: Vec<String>,
+}
+
+#[derive(serde::Deserialize, Default)]
+struct AnalyzeConfig {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+}
+
+impl AnalyzeConfig {
+    fn scope(&self) -> Result<StructScope> {
+        StructScope::new(&self.include, &self.exclude)
+            .context("Invalid `analyze` pattern in config")
+    }
+}
+
+/// Struct scope from the `analyze` section of an optional config file.
+fn load_scope(config_path: Option<&Path>) -> Result<StructScope> {
+    match config_path {
+        Some(path) => Config::load(path)?.analyze.scope(),
+        None => Ok(StructScope::default()),
+    }
+}
+
+/// Narrows `scope` by the `--filter-regex` and `--member-filter` command-line patterns.
+fn narrow_scope(
+    mut scope: StructScope,
+    filter_regex: Option<&str>,
+    member_filter: Option<&str>,
+) -> Result<StructScope> {
+    if let Some(pattern) = filter_regex {
+        scope = scope.with_name_regex(pattern).context("Invalid --filter-regex")?;
+    }
+    if let Some(pattern) = member_filter {
+        scope = scope.with_member_regex(pattern).context("Invalid --member-filter")?;
+    }
+    Ok(scope)
+}
+
+/// Load an access-frequency profile: a YAML or JSON map from struct name to score.
+fn load_profile(path: &Path) -> Result<std::collections::HashMap<String, f64>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read profile: {}", path.display()))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse profile: {}", path.display()))
+}
+
+/// Load fleet instance counts: a YAML or JSON map from struct name to live instances.
+fn load_instance_counts(path: &Path) -> Result<std::collections::HashMap<String, u64>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read instance counts: {}", path.display()))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse instance counts: {}", path.display()))
+}
+
+impl Budget {
+    fn validate(&self, name: &str) -> Result<()> {
+        validate_limits(
+            &format!("budget for '{}'", name),
+            self.max_size,
+            self.max_padding_percent,
+        )?;
+        if self.max_cache_lines == Some(0) {
+            bail!("Invalid budget for '{}': max_cache_lines must be greater than 0", name);
+        }
+        if self.exact_size == Some(0) {
+            bail!("Invalid budget for '{}': exact_size must be greater than 0", name);
+        }
+        if let (Some(min), Some(max)) = (self.min_size, self.max_size)
+            && min > max
+        {
+            bail!("Invalid budget for '{}': min_size {} exceeds max_size {}", name, min, max);
+        }
+        if let Some(exact) = self.exact_size
+            && (self.min_size.is_some_and(|min| exact < min)
+                || self.max_size.is_some_and(|max| exact > max))
+        {
+            bail!(
+                "Invalid budget for '{}': exact_size {} is outside its min_size/max_size range",
+                name,
+                exact
+            );
+        }
+        if let Some(ratio) = self.max_over_fetch
+            && !(ratio.is_finite() && ratio >= 1.0)
+        {
+            bail!(
+                "Invalid budget for '{}': max_over_fetch must be a number of at least 1.0 (got {})",
+                name,
+                ratio
+            );
+        }
+        for (member, limits) in &self.members {
+            let Some(offset) = limits.offset else { continue };
+            if let Some(&pinned) = self.member_offsets.get(member)
+                && pinned != offset
+            {
+                bail!(
+                    "Invalid budget for '{}': member {} is pinned to offset {} by members but {} \
+                     by member_offsets",
+                    name,
+                    member,
+                    offset,
+                    pinned
+                );
+            }
+            if let Some(max_offset) = limits.max_offset
+                && offset > max_offset
+            {
+                bail!(
+                    "Invalid budget for '{}': member {} is pinned to offset {}, past its \
+                     max_offset {}",
+                    name,
+                    member,
+                    offset,
+                    max_offset
+                );
+            }
+        }
+        if let Some(factor) = self.max_stride_factor
+            && !(factor.is_finite() && factor >= 1.0)
+        {
+            bail!(
+                "Invalid budget for '{}': max_stride_factor must be a number of at least 1.0 \
+                 (got {})",
+                name,
+                factor
+            );
+        }
+        Ok(())
+    }
+
+    /// A validated copy of this budget with its type patterns compiled.
+    fn compiled(&self, name: &str) -> Result<Budget> {
+        self.validate(name)?;
+        let mut budget = self.clone();
+        if !self.forbid_members_of_type.is_empty() {
+            let mut builder = globset::GlobSetBuilder::new();
+            for pattern in &self.forbid_members_of_type {
+                let glob = globset::GlobBuilder::new(pattern)
+                    .literal_separator(false)
+                    .build()
+                    .with_context(|| {
+                        format!(
+                            "Invalid forbid_members_of_type pattern in budget for '{}': '{}'",
+                            name, pattern
+                        )
+                    })?;
+                builder.add(glob);
+            }
+            budget.forbidden_types = Some(builder.build()?);
+        }
+        Ok(budget)
+    }
+
+    /// Violations of this budget by `layout`, which must already be analyzed.
+    fn violations(
+        &self,
+        layout: &StructLayout,
+        allocator: AllocatorModel,
+        atomic_patterns: &AtomicPatterns,
+        cache_line_size: u32,
+        read_path: Option<&[String]>,
+    ) -> Vec<CheckViolation> {
+        let source_location = layout.source_location.clone();
+        let mut violations = Vec::new();
+        if let Some(max_size) = self.max_size
+            && layout.size > max_size
+        {
+            violations.push(CheckViolation {
+                struct_name: layout.name.clone(),
+                kind: CheckViolationKind::MaxSize,
+                message: format!(
+                    "{}: size {} exceeds budget {} (+{} bytes)",
+                    layout.name,
+                    layout.size,
+                    max_size,
+                    layout.size - max_size
+                ),
+                source_location: source_location.clone(),
+                new_struct_policy: false,
+                severity: self.severity,
+            });
+        }
+        if let Some(min_size) = self.min_size
+            && layout.size < min_size
+        {
+            violations.push(CheckViolation {
+                struct_name: layout.name.clone(),
+                kind: CheckViolationKind::MinSize,
+                message: format!(
+                    "{}: size {} is below minimum {} (-{} bytes)",
+                    layout.name,
+                    layout.size,
+                    min_size,
+                    min_size - layout.size
+                ),
+                source_location: source_location.clone(),
+                new_struct_policy: false,
+                severity: self.severity,
+            });
+        }
+        if let Some(exact_size) = self.exact_size
+            && layout.size != exact_size
+        {
+            violations.push(CheckViolation {
+                struct_name: layout.name.clone(),
+                kind: CheckViolationKind::ExactSize,
+                message: format!(
+                    "{}: size {} differs from required {} ({:+} bytes)",
+                    layout.name,
+                    layout.size,
+                    exact_size,
+                    layout.size as i64 - exact_size as i64
+                ),
+                source_location: source_location.clone(),
+                new_struct_policy: false,
+                severity: self.severity,
+            });
+        }
+        if let Some(max_padding) = self.max_padding
+            && layout.metrics.padding_bytes > max_padding
+        {
+            violations.push(CheckViolation {
+                struct_name: layout.name.clone(),
+                kind: CheckViolationKind::MaxPaddingBytes,
+                message: format!(
+                    "{}: padding {} exceeds budget {} (+{} bytes)",
+                    layout.name,
+                    layout.metrics.padding_bytes,
+                    max_padding,
+                    layout.metrics.padding_bytes - max_padding
+                ),
+                source_location: source_location.clone(),
+                new_struct_policy: false,
+                severity: self.severity,
+            });
+        }
+        if let Some(max_pct) = self.max_padding_percent {
+            const EPSILON: f64 = 1e-6;
+            if layout.metrics.padding_percentage > max_pct + EPSILON {
+                violations.push(CheckViolation {
+                    struct_name: layout.name.clone(),
+                    kind: CheckViolationKind::MaxPaddingPercent,
+                    message: format!(
+                        "{}: padding {:.1}% exceeds budget {:.1}% (+{:.1} percentage points)",
+                        layout.name,
+                        layout.metrics.padding_percentage,
+                        max_pct,
+                        layout.metrics.padding_percentage - max_pct
+                    ),
+                    source_location: source_location.clone(),
+                    new_struct_policy: false,
+                    severity: self.severity,
+                });
+            }
+        }
+        if let Some(max_lines) = self.max_cache_lines
+            && layout.metrics.cache_lines_spanned > max_lines
+        {
+            violations.push(CheckViolation {
+                struct_name: layout.name.clone(),
+                kind: CheckViolationKind::MaxCacheLines,
+                message: format!(
+                    "{}: spans {} cache lines of {} bytes, exceeds budget {} (+{})",
+                    layout.name,
+                    layout.metrics.cache_lines_spanned,
+                    cache_line_size,
+                    max_lines,
+                    layout.metrics.cache_lines_spanned - max_lines
+                ),
+                source_location: source_location.clone(),
+                new_struct_policy: false,
+                severity: self.severity,
+            });
+        }
+        if let Some(max_ratio) = self.max_over_fetch
+            && let Some(fetch) =
+                read_path.and_then(|path| over_fetch(layout, path, cache_line_size))
+            && fetch.ratio > max_ratio
+        {
+            violations.push(CheckViolation {
+                struct_name: layout.name.clone(),
+                kind: CheckViolationKind::MaxOverFetch,
+                message: format!(
+                    "{}: reading its hot members loads {} bytes ({} cache lines) for {} used, \
+                     {:.1}x exceeds budget {:.1}x ({} line(s) would do)",
+                    layout.name,
+                    fetch.bytes_loaded,
+                    fetch.lines_touched,
+                    fetch.bytes_used,
+                    fetch.ratio,
+                    max_ratio,
+                    fetch.min_lines_touched
+                ),
+                source_location: source_location.clone(),
+                new_struct_policy: false,
+                severity: self.severity,
+            });
+        }
+        if let Some(max_fs) = self.max_false_sharing_warnings {
+            let fs = analyze_false_sharing_with_patterns(layout, cache_line_size, atomic_patterns);
+            // Clamp to u32::MAX to prevent truncation on 64-bit platforms
+            let warning_count = fs.warnings.len().min(u32::MAX as usize) as u32;
+            if warning_count > max_fs {
+                violations.push(CheckViolation {
+                    struct_name: layout.name.clone(),
+                    kind: CheckViolationKind::MaxFalseSharingWarnings,
+                    message: format!(
+                        "{}: {} potential false sharing issue(s) exceeds limit of {}",
+                        layout.name, warning_count, max_fs
+                    ),
+                    source_location: source_location.clone(),
+                    new_struct_policy: false,
+                    severity: self.severity,
+                });
+            }
+        }
+        if let Some(max_slack) = self.max_alloc_slack {
+            let slack = allocator.slack(layout.size);
+            if slack.slack_bytes > max_slack {
+                violations.push(CheckViolation {
+                    struct_name: layout.name.clone(),
+                    kind: CheckViolationKind::MaxAllocSlack,
+                    message: format!(
+                        "{}: {} bytes of {} allocation slack ({}-byte size class) exceeds \
+                     budget {} (+{} bytes)",
+                        layout.name,
+                        slack.slack_bytes,
+                        allocator.name(),
+                        slack.size_class,
+                        max_slack,
+                        slack.slack_bytes - max_slack
+                    ),
+                    source_location: source_location.clone(),
+                    new_struct_policy: false,
+                    severity: self.severity,
+                });
+            }
+        }
+        if let Some(max_factor) = self.max_stride_factor
+            && let Some(factor) = layout.metrics.stride_factor()
+            && factor > max_factor
+        {
+            violations.push(CheckViolation {
+                struct_name: layout.name.clone(),
+                kind: CheckViolationKind::MaxStrideFactor,
+                message: format!(
+                    "{}: array stride {} is {:.1}x its {} member bytes, budget allows {:.1}x \
+                 ({} bytes wasted per element)",
+                    layout.name,
+                    layout.metrics.stride,
+                    factor,
+                    layout.metrics.useful_size,
+                    max_factor,
+                    layout.metrics.stride_waste
+                ),
+                source_location: source_location.clone(),
+                new_struct_policy: false,
+                severity: self.severity,
+            });
+        }
+        if let Some(max_members) = self.max_members
+            && layout.members.len() > max_members
+        {
+            violations.push(CheckViolation {
+                struct_name: layout.name.clone(),
+                kind: CheckViolationKind::MaxMembers,
+                message: format!(
+                    "{}: {} members exceeds budget {} (+{})",
+                    layout.name,
+                    layout.members.len(),
+                    max_members,
+                    layout.members.len() - max_members
+                ),
+                source_location: source_location.clone(),
+                new_struct_policy: false,
+                severity: self.severity,
+            });
+        }
+        if let Some(max_member_size) = self.max_single_member_size {
+            for member in &layout.members {
+                let Some(size) = member.size.filter(|&size| size > max_member_size) else {
+                    continue;
+                };
+                violations.push(CheckViolation {
+                    struct_name: layout.name.clone(),
+                    kind: CheckViolationKind::MaxSingleMemberSize,
+                    message: format!(
+                        "{}: member {} ({}) is {} bytes, exceeds max_single_member_size {} \
+                         (+{} bytes)",
+                        layout.name,
+                        member.name,
+                        member.type_name,
+                        size,
+                        max_member_size,
+                        size - max_member_size
+                    ),
+                    source_location: member
+                        .source_location
+                        .clone()
+                        .or_else(|| source_location.clone()),
+                    new_struct_policy: false,
+                    severity: self.severity,
+                });
+            }
+        }
+        if let Some(forbidden) = &self.forbidden_types {
+            for member in &layout.members {
+                let Some(embedded) = embedded_type(&member.type_name) else { continue };
+                let Some(&index) = forbidden.matches(embedded).first() else { continue };
+                violations.push(CheckViolation {
+                    struct_name: layout.name.clone(),
+                    kind: CheckViolationKind::ForbiddenMemberType,
+                    message: format!(
+                        "{}: member {} embeds {} (forbidden by '{}')",
+                        layout.name,
+                        member.name,
+                        member.type_name,
+                        self.forbid_members_of_type[index]
+                    ),
+                    source_location: member
+                        .source_location
+                        .clone()
+                        .or_else(|| source_location.clone()),
+                    new_struct_policy: false,
+                    severity: self.severity,
+                });
+            }
+        }
+        // `members` entries pinning a member `member_offsets` also pins agree with it (see
+        // `validate`), so each member is checked once.
+        let pins =
+            self.member_offsets.iter().map(|(name, &offset)| (name, offset, "member_offsets"));
+        let member_pins = self.members.iter().filter_map(|(name, limits)| {
+            let offset = limits.offset.filter(|_| !self.member_offsets.contains_key(name))?;
+            Some((name, offset, "members"))
+        });
+        for (name, pinned, pinned_by) in pins.chain(member_pins) {
+            let Some(member) = find_member(layout, name) else { continue };
+            if member.offset == Some(pinned) {
+                continue;
+            }
+            let found = match member.offset {
+                Some(offset) => {
+                    let line = u64::from(cache_line_size.max(1));
+                    let lines = if offset / line == pinned / line {
+                        String::new()
+                    } else {
+                        format!(", cache line {} instead of {}", offset / line, pinned / line)
+                    };
+                    let distance = match offset.cmp(&pinned) {
+                        std::cmp::Ordering::Less => format!("{} bytes early", pinned - offset),
+                        _ => format!("{} bytes late", offset - pinned),
+                    };
+                    format!("is at offset {} ({}{})", offset, distance, lines)
+                }
+                None => "has no known offset".to_string(),
+            };
+            violations.push(CheckViolation {
+                struct_name: layout.name.clone(),
+                kind: CheckViolationKind::MemberOffset,
+                message: format!(
+                    "{}: member {} is pinned to offset {} by {}, but {}",
+                    layout.name, name, pinned, pinned_by, found
+                ),
+                source_location: member
+                    .source_location
+                    .clone()
+                    .or_else(|| layout.source_location.clone()),
+                new_struct_policy: false,
+                severity: self.severity,
+            });
+        }
+        for (name, limits) in &self.members {
+            let Some(member) = find_member(layout, name) else { continue };
+            let location =
+                member.source_location.clone().or_else(|| layout.source_location.clone());
+            if let Some(max_offset) = limits.max_offset
+                && let Some(offset) = member.offset
+                && offset > max_offset
+            {
+                violations.push(CheckViolation {
+                    struct_name: layout.name.clone(),
+                    kind: CheckViolationKind::MemberMaxOffset,
+                    message: format!(
+                        "{}: member {} at offset {} exceeds max_offset {} (+{} bytes)",
+                        layout.name,
+                        name,
+                        offset,
+                        max_offset,
+                        offset - max_offset
+                    ),
+                    source_location: location.clone(),
+                    new_struct_policy: false,
+                    severity: self.severity,
+                });
+            }
+            if let Some(other_name) = &limits.same_cache_line_as
+                && let Some(other) = find_member(layout, other_name)
+                && let Some(lines) = member_cache_lines(member, cache_line_size)
+                && let Some(other_lines) = member_cache_lines(other, cache_line_size)
+                && (lines.0 != lines.1 || lines != other_lines)
+            {
+                violations.push(CheckViolation {
+                    struct_name: layout.name.clone(),
+                    kind: CheckViolationKind::MemberSameCacheLine,
+                    message: format!(
+                        "{}: member {} ({}) is not on the same cache line as {} ({})",
+                        layout.name,
+                        name,
+                        describe_lines(lines),
+                        other_name,
+                        describe_lines(other_lines)
+                    ),
+                    source_location: location,
+                    new_struct_policy: false,
+                    severity: self.severity,
+                });
+            }
+        }
+        violations
+    }
+
+    /// Member names this budget constrains that `layout` does not have.
+    fn unknown_members<'a>(&'a self, layout: &StructLayout) -> Vec<&'a str> {
+        let mut unknown = Vec::new();
+        let referenced = self.members.iter().flat_map(|(name, limits)| {
+            std::iter::once(name).chain(limits.same_cache_line_as.as_ref())
+        });
+        for name in referenced.chain(self.member_offsets.keys()) {
+            if find_member(layout, name).is_none() && !unknown.contains(&name.as_str()) {
+                unknown.push(name.as_str());
+            }
+        }
+        unknown
+    }
+}
+
+fn find_member<'a>(layout: &'a StructLayout, name: &str) -> Option<&'a MemberLayout> {
+    layout.members.iter().find(|m| m.name == name)
+}
+
+/// First and last cache line holding a member's bytes, when its offset and size are known.
+fn member_cache_lines(member: &MemberLayout, cache_line_size: u32) -> Option<(u64, u64)> {
+    let start = member.offset?;
+    let end = start + member.size?.max(1) - 1;
+    let line = u64::from(cache_line_size.max(1));
+    Some((start / line, end / line))
+}
+
+fn describe_lines((first, last): (u64, u64)) -> String {
+    if first == last {
+        format!("cache line {}", first)
+    } else {
+        format!("cache lines {}-{}", first, last)
+    }
+}
+
+impl NewStructPolicy {
+    fn validate(&self) -> Result<()> {
+        validate_limits("new_struct_defaults", self.max_size, self.max_padding_percent)?;
+        if self.max_cache_lines == Some(0) {
+            bail!("Invalid new_struct_defaults: max_cache_lines must be greater than 0");
+        }
+        Ok(())
+    }
+
+    /// Violations of these limits by `layout`, which must already be analyzed.
+    fn violations(&self, layout: &StructLayout) -> Vec<CheckViolation> {
+        let violation = |kind, message| CheckViolation {
+            struct_name: layout.name.clone(),
+            kind,
+            message,
+            source_location: layout.source_location.clone(),
+            new_struct_policy: true,
+            severity: self.severity,
+        };
+        let m = &layout.metrics;
+        let mut violations = Vec::new();
+
+        if let Some(max_size) = self.max_size
+            && layout.size > max_size
+        {
+            violations.push(violation(
+                CheckViolationKind::MaxSize,
+                format!(
+                    "{}: new struct size {} exceeds default {} (+{} bytes)",
+                    layout.name,
+                    layout.size,
+                    max_size,
+                    layout.size - max_size
+                ),
+            ));
+        }
+        if let Some(max_padding) = self.max_padding
+            && m.padding_bytes > max_padding
+        {
+            violations.push(violation(
+                CheckViolationKind::MaxPaddingBytes,
+                format!(
+                    "{}: new struct padding {} exceeds default {} (+{} bytes)",
+                    layout.name,
+                    m.padding_bytes,
+                    max_padding,
+                    m.padding_bytes - max_padding
+                ),
+            ));
+        }
+        if let Some(max_pct) = self.max_padding_percent {
+            const EPSILON: f64 = 1e-6;
+            if m.padding_percentage > max_pct + EPSILON {
+                violations.push(violation(
+                    CheckViolationKind::MaxPaddingPercent,
+                    format!(
+                        "{}: new struct padding {:.1}% exceeds default {:.1}%",
+                        layout.name, m.padding_percentage, max_pct
+                    ),
+                ));
+            }
+        }
+        if let Some(max_lines) = self.max_cache_lines
+            && m.cache_lines_spanned > max_lines
+        {
+            violations.push(violation(
+                CheckViolationKind::MaxCacheLines,
+                format!(
+                    "{}: new struct spans {} cache lines, default allows {}",
+                    layout.name, m.cache_lines_spanned, max_lines
+                ),
+            ));
+        }
+
+        violations
+    }
+}
+
+/// Range checks shared by budgets and `new_struct_defaults`; `what` names the entry in errors.
+fn validate_limits(
+    what: &str,
+    max_size: Option<u64>,
+    max_padding_percent: Option<f64>,
+) -> Result<()> {
+    if let Some(max_pct) = max_padding_percent {
+        if !max_pct.is_finite() {
+            bail!("Invalid {}: max_padding_percent must be a finite number", what);
+        }
+        if max_pct < 0.0 {
+            bail!("Invalid {}: max_padding_percent cannot be negative (got {:.1})", what, max_pct);
+        }
+        if max_pct > 100.0 {
+            bail!("Invalid {}: max_padding_percent cannot exceed 100 (got {:.1})", what, max_pct);
+        }
+    }
+    if max_size == Some(0) {
+        bail!("Invalid {}: max_size must be greater than 0", what);
+    }
+    Ok(())
+}
+
+/// Check if a pattern string contains glob metacharacters
+fn is_glob_pattern(s: &str) -> bool {
+    s.contains('*') || s.contains('?') || s.contains('[') || s.contains('{')
+}
+
+/// Near-miss struct names listed for a budget that matched nothing
+const MAX_NEAR_MISSES: usize = 5;
+
+/// Compiled budget patterns for efficient matching
+struct CompiledBudgets {
+    /// Exact name matches (O(1) lookup)
+    exact: std::collections::HashMap<String, Budget>,
+    /// Canonical name -> exact budget name, with `--budget-match canonical`
+    canonical: Option<std::collections::HashMap<String, String>>,
+    /// Glob patterns in declaration order
+    patterns: Vec<CompiledPattern>,
+}
+
+struct CompiledPattern {
+    glob: globset::GlobMatcher,
+    budget: Budget,
+    original_pattern: String,
+}
+
+/// Environment variable holding inline config YAML for `check`, used instead of the file.
+const BUDGETS_ENV: &str = "LAYOUT_AUDIT_BUDGETS";
+
+/// Whether a `--config` path means "read the config from stdin".
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// `check`'s config: from stdin for `--config -`, else from `env` (the value of
+/// `LAYOUT_AUDIT_BUDGETS`) when set, else from the file if it exists. The flag tells whether
+/// it came from the file, which then counts as a build input.
+fn load_check_config(path: &Path, env: Option<String>) -> Result<Option<(Config, bool)>> {
+    let Some(source) = read_check_config(path, env)? else {
+        return Ok(None);
+    };
+    Ok(Some((Config::parse(&source.text, &source.origin)?, source.is_file)))
+}
+
+/// Unparsed config text and where it came from.
+struct ConfigSource {
+    text: String,
+    /// The file path, `stdin`, or the environment variable, for messages.
+    origin: String,
+    is_file: bool,
+}
+
+/// The text [`load_check_config`] parses, chosen the same way.
+fn read_check_config(path: &Path, env: Option<String>) -> Result<Option<ConfigSource>> {
+    if is_stdin(path) {
+        let text = read_config_text(path)?;
+        return Ok(Some(ConfigSource { text, origin: "stdin".to_string(), is_file: false }));
+    }
+    if let Some(text) = env.filter(|yaml| !yaml.trim().is_empty()) {
+        if path.exists() {
+            eprintln!("Note: using the config in {} instead of {}", BUDGETS_ENV, path.display());
+        }
+        return Ok(Some(ConfigSource { text, origin: BUDGETS_ENV.to_string(), is_file: false }));
+    }
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = read_config_text(path)?;
+    Ok(Some(ConfigSource { text, origin: path.display().to_string(), is_file: true }))
+}
+
+/// The config text at `path`, or on stdin when `path` is `-`.
+fn read_config_text(path: &Path) -> Result<String> {
+    if is_stdin(path) {
+        let mut config_str = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut config_str)
+            .context("Failed to read config from stdin")?;
+        return Ok(config_str);
+    }
+    std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config: {}", path.display()))
+}
+
+impl Config {
+    /// Load the config file at `path`, or read it from stdin when `path` is `-`.
+    fn load(path: &Path) -> Result<Self> {
+        let origin = if is_stdin(path) { "stdin".to_string() } else { path.display().to_string() };
+        Self::parse(&read_config_text(path)?, &origin)
+    }
+
+    /// Parse config YAML; `origin` names where it came from in errors.
+    fn parse(config_str: &str, origin: &str) -> Result<Self> {
+        let config: Self = serde_yaml::from_str(config_str)
+            .with_context(|| format!("Failed to parse config: {}", origin))?;
+        config.type_sizes.validate().map_err(anyhow::Error::msg)?;
+        Ok(config)
+    }
+
+    /// The members a read of `name` touches: its `access_patterns` entry, else its
+    /// `hot_fields`.
+    fn read_path(&self, name: &str) -> Option<&[String]> {
+        self.access_patterns.get(name).or_else(|| self.hot_fields.get(name)).map(Vec::as_slice)
+    }
+
+    /// Compile budget patterns for efficient matching.
+    /// Separates exact matches from glob patterns.
+    fn compile(&self) -> Result<CompiledBudgets> {
+        let mut exact = std::collections::HashMap::new();
+        let mut patterns = Vec::new();
+
+        for (name, budget) in &self.budgets {
+            match compile_budget(name, budget)? {
+                (budget, Some(glob)) => {
+                    patterns.push(CompiledPattern { glob, budget, original_pattern: name.clone() })
+                }
+                (budget, None) => {
+                    exact.insert(name.clone(), budget);
+                }
+            }
+        }
+
+        Ok(CompiledBudgets { exact, canonical: None, patterns })
+    }
+
+    /// Atomic patterns from the config and `file`, with the `false_sharing_ignore` groups.
+    fn false_sharing_patterns(&self, file: Option<&Path>) -> Result<AtomicPatterns> {
+        let mut patterns = self.atomic_patterns.compile(file)?;
+        for entry in &self.false_sharing_ignore {
+            patterns = patterns
+                .with_ignored_members(&entry.struct_name, entry.members.clone())
+                .context("Invalid `false_sharing_ignore` struct pattern")?;
+        }
+        Ok(patterns)
+    }
+
+    /// The `frozen_layouts` globs, compiled.
+    fn frozen_layouts(&self) -> Result<globset::GlobSet> {
+        let mut set = globset::GlobSetBuilder::new();
+        for pattern in &self.frozen_layouts {
+            let glob = globset::GlobBuilder::new(pattern)
+                .literal_separator(false) // * matches ::
+                .build()
+                .with_context(|| format!("Invalid frozen_layouts pattern: '{}'", pattern))?;
+            set.add(glob);
+        }
+        Ok(set.build()?)
+    }
+}
+
+/// A validated budget, with the matcher for its name when that is a glob pattern.
+fn compile_budget(name: &str, budget: &Budget) -> Result<(Budget, Option<globset::GlobMatcher>)> {
+    if name.is_empty() {
+        bail!("Empty budget pattern name is not allowed");
+    }
+
+    let budget = budget.compiled(name)?;
+
+    if !is_glob_pattern(name) {
+        return Ok((budget, None));
+    }
+    let glob = globset::GlobBuilder::new(name)
+        .literal_separator(false) // * matches ::
+        .build()
+        .with_context(|| format!("Invalid glob pattern: '{}'", name))?
+        .compile_matcher();
+    Ok((budget, Some(glob)))
+}
+
+impl CompiledBudgets {
+    /// Also match exact budget names by canonical name, ignoring language qualifiers.
+    /// Fails if two budgets canonicalize to the same name.
+    fn with_canonical_matching(mut self) -> Result<Self> {
+        let mut names: Vec<&String> = self.exact.keys().collect();
+        names.sort();
+        let mut canonical = std::collections::HashMap::new();
+        for name in names {
+            if let Some(other) = canonical.insert(canonical_name(name).to_string(), name.clone()) {
+                bail!(
+                    "Budgets '{}' and '{}' both match '{}' with --budget-match canonical",
+                    other,
+                    name,
+                    canonical_name(name)
+                );
+            }
+        }
+        self.canonical = Some(canonical);
+        Ok(self)
+    }
+
+    /// The exact (non-glob) budget name matching a struct, if any.
+    fn exact_key(&self, struct_name: &str) -> Option<&str> {
+        if let Some((key, _)) = self.exact.get_key_value(struct_name) {
+            return Some(key);
+        }
+        let canonical = self.canonical.as_ref()?;
+        canonical.get(canonical_name(struct_name)).map(String::as_str)
+    }
+
+    /// Find the budget for a struct name.
+    /// Returns (budget, pattern_index) where pattern_index is Some if matched by a glob.
+    fn find_budget(&self, struct_name: &str) -> Option<(&Budget, Option<usize>)> {
+        // Exact match takes priority, then canonical match
+        if let Some(key) = self.exact_key(struct_name) {
+            return Some((&self.exact[key], None));
+        }
+        // First matching glob wins
+        for (i, pattern) in self.patterns.iter().enumerate() {
+            if pattern.glob.is_match(struct_name) {
+                return Some((&pattern.budget, Some(i)));
+            }
+        }
+        None
+    }
+}
+
+fn run_suggest(config: &SuggestConfig<'_>) -> Result<()> {
+    let SuggestConfig {
+        binary_path,
+        filter,
+        output_format,
+        sarif_max_results,
+        min_savings,
+        cache_line_size,
+        pretty,
+        max_align,
+        sort_by_savings,
+        strategy,
+        static_access,
+        usage,
+        locality,
+        size_cap,
+        no_color,
+        include_go_runtime,
+        exported_only,
+        sort_stable,
+        config_path,
+        emit_code,
+        ..
+    } = *config;
+    if matches!(output_format, OutputFormat::Csv | OutputFormat::Jsonl) {
+        bail!("suggest supports table, json, and sarif output");
+    }
+
+    let file_config = match config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let scope =
+        narrow_scope(file_config.analyze.scope()?, config.filter_regex, config.member_filter)?;
+
+    let binary = BinaryData::load(binary_path)
+        .with_context(|| format!("Failed to load binary: {}", binary_path.display()))?;
+
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+
+    if let Some(depfile) = &config.depfile {
+        let mut inputs = binary.input_files();
+        inputs.extend(config_path.filter(|p| !is_stdin(p)).map(Path::to_path_buf));
+        depfile.write(&inputs)?;
+    }
+
+    let dwarf = DwarfContext::new(&loaded).with_scope(scope);
+    let frozen = file_config.frozen_layouts()?;
+
+    let mut layouts =
+        find_layouts(&binary, &dwarf, filter, include_go_runtime, exported_only, sort_stable)?;
+
+    if layouts.is_empty() {
+        if let Some(f) = filter {
+            eprintln!("No structs found matching filter: {}", f);
+        } else {
+            eprintln!("No structs found in binary");
+        }
+        return Ok(());
+    }
+
+    apply_type_sizes(&file_config.type_sizes, &mut layouts);
+    if static_access {
+        dwarf.annotate_static_popularity(&mut layouts)?;
+    }
+    if usage {
+        dwarf.annotate_usage(&mut layouts)?;
+    }
+
+    // Analyze layouts first (needed for metrics)
+    for layout in &mut layouts {
+        analyze_layout(layout, cache_line_size);
+    }
+
+    // Optimize each layout and keep source locations aligned
+    let mut suggestions_with_locations: Vec<_> = layouts
+        .iter()
+        .map(|l| {
+            let hot = file_config.hot_fields.get(&l.name);
+            let priority = hot.into_iter().flatten().map(String::as_str).collect();
+            let mut suggestion = optimize_layout_with_strategy(l, max_align, &priority, strategy);
+            suggestion.strategy_disagreement = strategy_disagreement(l, max_align);
+            suggestion.frozen = frozen.is_match(&l.name);
+            if let Some(sequence) = file_config.read_path(&l.name) {
+                suggestion.access_simulation =
+                    Some(simulate_access(l, &suggestion, sequence, cache_line_size, max_align));
+            } else if static_access {
+                let sequence = static_access_sequence(l);
+                if !sequence.is_empty() {
+                    let mut sim =
+                        simulate_access(l, &suggestion, &sequence, cache_line_size, max_align);
+                    sim.from_static_analysis = true;
+                    suggestion.access_simulation = Some(sim);
+                }
+            }
+            if locality {
+                let (groups, from_prefixes) = match file_config.field_groups.get(&l.name) {
+                    Some(groups) => (groups.clone(), false),
+                    None => (prefix_groups(l), true),
+                };
+                suggestion.locality =
+                    locality_front(l, max_align, &groups, size_cap).map(|mut front| {
+                        front.groups_from_prefixes = from_prefixes;
+                        front
+                    });
+            }
+            (suggestion, l.source_location.clone())
+        })
+        .collect();
+
+    // Filter by minimum savings
+    if let Some(min) = min_savings {
+        suggestions_with_locations.retain(|(s, _)| s.savings_bytes >= min);
+    }
+
+    if suggestions_with_locations.is_empty() {
+        eprintln!("No structs with optimization potential found");
+        return Ok(());
+    }
+
+    // Sort by savings if requested
+    if sort_by_savings {
+        suggestions_with_locations.sort_by_key(|(s, _)| std::cmp::Reverse(s.savings_bytes));
+    }
+
+    let (suggestions, locations): (Vec<_>, Vec<_>) = suggestions_with_locations.into_iter().unzip();
+
+    if let Some(language) = emit_code {
+        // Already-optimal structs would only be reprinted as declared; frozen ones must not be
+        // reordered at all.
+        let frozen_count = suggestions.iter().filter(|s| s.frozen && s.savings_bytes > 0).count();
+        if frozen_count > 0 {
+            eprintln!("Note: {} frozen layout(s) left out of the emitted code", frozen_count);
+        }
+        let improved: Vec<_> =
+            suggestions.into_iter().filter(|s| s.savings_bytes > 0 && !s.frozen).collect();
+        if improved.is_empty() {
+            eprintln!("No structs with optimization potential found");
+        } else {
+            print!("{}", CodeFormatter::new(language).format(&improved));
+        }
+        return Ok(());
+    }
+
+    let regions = packed_regions(&layouts);
+    let output_str = match output_format {
+        OutputFormat::Table => {
+            let formatter = SuggestTableFormatter::new(no_color);
+            formatter.format_with_regions(&suggestions, &regions)
+        }
+        OutputFormat::Json => {
+            let formatter = SuggestJsonFormatter::new(pretty);
+            formatter.format_with_regions(&suggestions, &regions)
+        }
+        OutputFormat::Sarif => {
+            let formatter = SarifFormatter::new().with_max_results(sarif_max_results);
+            formatter.format_suggest(&suggestions, &locations)
+        }
+        OutputFormat::Csv | OutputFormat::Jsonl => {
+            unreachable!("suggest rejects csv and jsonl output up front")
+        }
+    };
+
+    println!("{}", output_str);
+
+    Ok(())
+}
+
+fn run_annotate(config: &AnnotateConfig<'_>) -> Result<()> {
+    if matches!(config.output_format, OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl)
+    {
+        bail!("annotate supports table and json output");
+    }
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let samples = SampleSet::load(config.samples_path)
+        .with_context(|| format!("Failed to read samples: {}", config.samples_path.display()))?;
+    let load_bias = match config.load_bias {
+        Some(bias) => bias,
+        None if samples.mappings.is_empty() => 0,
+        None => samples
+            .load_bias(config.binary_path, |offset| binary.link_address(offset))
+            .with_context(|| {
+                format!(
+                    "No mapping of {} in the perf data; pass --load-bias",
+                    config.binary_path.display()
+                )
+            })?,
+    };
+
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+    let dwarf = DwarfContext::new(&loaded);
+    let layouts = find_layouts(&binary, &dwarf, config.filter, false, false, true)?;
+    let globals = dwarf.global_variables().context("Failed to read global variables")?;
+    let report = attribute_accesses(&samples.samples, load_bias, &globals, &layouts);
+
+    if let Some(path) = config.emit_config {
+        std::fs::write(path, hot_fields_config(&report))
+            .with_context(|| format!("Failed to write config: {}", path.display()))?;
+        eprintln!("Wrote hot fields of {} struct(s) to {}", report.structs.len(), path.display());
+    }
+
+    let output = match config.output_format {
+        OutputFormat::Json => AnnotateJsonFormatter::new(config.pretty).format(&report),
+        _ => AnnotateTableFormatter::new(config.no_color).format(&report),
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+/// A `hot_fields` config section listing each struct's sampled members, hottest first.
+fn hot_fields_config(report: &FieldAccessReport) -> String {
+    let hot_fields: indexmap::IndexMap<&str, Vec<&str>> = report
+        .structs
+        .iter()
+        .map(|s| (s.name.as_str(), s.hot_members()))
+        .filter(|(_, members)| !members.is_empty())
+        .collect();
+    #[derive(serde::Serialize)]
+    struct HotFields<'a> {
+        hot_fields: indexmap::IndexMap<&'a str, Vec<&'a str>>,
+    }
+    serde_yaml::to_string(&HotFields { hot_fields }).unwrap_or_default()
+}
+
+fn run_locate(config: &LocateConfig<'_>) -> Result<()> {
+    if matches!(config.output_format, OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl)
+    {
+        bail!("locate supports table and json output");
+    }
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+    let dwarf = DwarfContext::new(&loaded);
+    let location = dwarf.resolve_address_field(config.type_name, config.offset)?;
+
+    let output = match config.output_format {
+        OutputFormat::Json => LocateJsonFormatter::new(config.pretty).format(&location),
+        _ => LocateTableFormatter::new(config.no_color).format(&location),
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+fn run_verify(config: &VerifyConfig<'_>) -> Result<()> {
+    if matches!(config.output_format, OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl)
+    {
+        bail!("verify supports table and json output");
+    }
+
+    let path = config.rustc_sizes_path;
+    let mut expected = if is_stdin(path) {
+        let mut text = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut text)
+            .context("Failed to read rustc type sizes from stdin")?;
+        RustcLayout::parse(&text)
+    } else {
+        RustcLayout::load(path)
+    }
+    .with_context(|| format!("Failed to read rustc type sizes: {}", path.display()))?;
+    if let Some(filter) = config.filter {
+        expected.retain(|layout| layout.name.contains(filter));
+    }
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+    let dwarf = DwarfContext::new(&loaded);
+    let layouts = find_layouts(&binary, &dwarf, None, false, false, true)?;
+
+    let report = verify_layouts(&expected, &layouts);
+    let output = match config.output_format {
+        OutputFormat::Json => VerifyJsonFormatter::new(config.pretty).format(&report),
+        _ => VerifyTableFormatter::new(config.no_color).format(&report),
+    };
+    println!("{}", output);
+
+    if !report.mismatches.is_empty() {
+        bail!("{} layout mismatch(es) between rustc and the debug info", report.mismatches.len());
+    }
+    Ok(())
+}
+
+fn run_schema_check(config: &SchemaCheckConfig<'_>) -> Result<()> {
+    if matches!(config.output_format, OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl)
+    {
+        bail!("schema-check supports table and json output");
+    }
+    let file_config = match config.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+
+    let path = config.schema_path;
+    let mut messages = SchemaMessage::load(path)
+        .with_context(|| format!("Failed to read schema: {}", path.display()))?;
+    if let Some(filter) = config.filter {
+        messages.retain(|message| message.name.contains(filter));
+    }
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+    let dwarf = DwarfContext::new(&loaded);
+    let layouts = find_layouts(&binary, &dwarf, None, config.include_go_runtime, false, true)?;
+
+    let struct_names = file_config.schema_structs.into_iter().collect();
+    let report = check_schema(&messages, &layouts, &struct_names);
+    let output = match config.output_format {
+        OutputFormat::Json => SchemaJsonFormatter::new(config.pretty).format(&report),
+        _ => SchemaTableFormatter::new(config.no_color).format(&report),
+    };
+    println!("{}", output);
+
+    if !report.mismatches.is_empty() {
+        bail!("{} field mismatch(es) between the schema and the binary", report.mismatches.len());
+    }
+    Ok(())
+}
+
+fn run_verify_determinism(config: &VerifyDeterminismConfig<'_>) -> Result<()> {
+    if matches!(config.output_format, OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl)
+    {
+        bail!("verify-determinism supports table and json output");
+    }
+    let scope = load_scope(config.config_path)?;
+    // Each analysis loads the binary afresh, so nothing carries over between runs.
+    let analyze = |path: &Path| -> Result<Vec<StructLayout>> {
+        let binary = BinaryData::load(path)
+            .with_context(|| format!("Failed to load binary: {}", path.display()))?;
+        let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+        let dwarf = DwarfContext::new(&loaded).with_scope(scope.clone());
+        let mut layouts =
+            find_layouts(&binary, &dwarf, config.filter, config.include_go_runtime, false, true)?;
+        // Padding is compared, so every run is analyzed with the same cache line size.
+        for layout in &mut layouts {
+            analyze_layout(layout, 64);
+        }
+        Ok(layouts)
+    };
+
+    let analyses: Vec<(String, &Path)> = match config.binary_paths {
+        [path] => (1..=config.runs)
+            .map(|run| (format!("{} (run {})", path.display(), run), path.as_path()))
+            .collect(),
+        paths => paths.iter().map(|p| (p.display().to_string(), p.as_path())).collect(),
+    };
+    let (baseline, baseline_path) = &analyses[0];
+    let baseline_layouts = analyze(baseline_path)?;
+    let mut runs = Vec::new();
+    for (label, path) in &analyses[1..] {
+        runs.push(DeterminismRun::new(label.clone(), &baseline_layouts, &analyze(path)?));
+    }
+    let report = DeterminismReport { baseline: baseline.clone(), runs };
+
+    let output = match config.output_format {
+        OutputFormat::Json => DeterminismJsonFormatter::new(config.pretty).format(&report),
+        _ => DeterminismTableFormatter::new(config.no_color).format(&report),
+    };
+    println!("{}", output);
+
+    if !report.is_deterministic() {
+        let differing: usize = report.runs.iter().map(|r| r.differing()).sum();
+        bail!("{} struct difference(s) between runs that should be identical", differing);
+    }
+    Ok(())
+}
+
+fn run_forecast(config: &ForecastConfig<'_>) -> Result<()> {
+    if matches!(config.output_format, OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl)
+    {
+        bail!("forecast supports table and json output");
+    }
+
+    let file_config = match config.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let scope = file_config.analyze.scope()?;
+    let instances = load_instance_counts(config.instances_path)?;
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+    let dwarf = DwarfContext::new(&loaded).with_scope(scope.clone());
+    let mut layouts = find_layouts(&binary, &dwarf, config.filter, false, false, true)?;
+    apply_type_sizes(&file_config.type_sizes, &mut layouts);
+    for layout in &mut layouts {
+        analyze_layout(layout, config.cache_line_size);
+    }
+
+    let suggestions: Vec<OptimizedLayout> =
+        layouts.iter().map(|l| optimize_layout(l, config.max_align)).collect();
+    let over_budget: std::collections::HashSet<String> =
+        unsuppressed_violations(&file_config, &layouts, config.cache_line_size)?
+            .into_iter()
+            .map(|v| v.struct_name)
+            .collect();
+
+    // Snapshots are narrowed the same way as the binary, so the trend compares like with like.
+    let mut history = Vec::new();
+    for path in config.history {
+        let mut snapshot = Snapshot::load(path)
+            .with_context(|| format!("Failed to read snapshot: {}", path.display()))?
+            .into_layouts();
+        snapshot.retain(|l| {
+            config.filter.is_none_or(|f| l.name.contains(f)) && scope.contains(&l.name)
+        });
+        apply_type_sizes(&file_config.type_sizes, &mut snapshot);
+        for layout in &mut snapshot {
+            analyze_layout(layout, config.cache_line_size);
+        }
+        let label = path
+            .file_name()
+            .map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+        history.push((label, snapshot));
+    }
+
+    let forecast = Forecast::new(&layouts, &suggestions, &instances, &over_budget, config.top)
+        .with_trend(&history, &instances, config.horizon);
+    let output = match config.output_format {
+        OutputFormat::Json => ForecastJsonFormatter::new(config.pretty).format(&forecast),
+        _ => ForecastTableFormatter::new(config.no_color).format(&forecast),
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+fn run_placement(config: &PlacementConfig<'_>) -> Result<()> {
+    if matches!(config.output_format, OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl)
+    {
+        bail!("placement supports table and json output");
+    }
+
+    let file_config = match config.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let owners = ThreadOwners::new(&file_config.thread_owners)
+        .context("Invalid `thread_owners` struct pattern")?;
+    let atomic_patterns = file_config.false_sharing_patterns(None)?;
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+    let dwarf = DwarfContext::new(&loaded).with_scope(file_config.analyze.scope()?);
+    let mut layouts =
+        find_layouts(&binary, &dwarf, config.filter, config.include_go_runtime, false, true)?;
+    apply_type_sizes(&file_config.type_sizes, &mut layouts);
+
+    let placement = Placement::new(
+        &layouts,
+        &owners,
+        &atomic_patterns,
+        u64::from(config.cache_line_size),
+        config.lang,
+    );
+    let output = match config.output_format {
+        OutputFormat::Json => PlacementJsonFormatter::new(config.pretty).format(&placement),
+        _ => PlacementTableFormatter::new(config.no_color).format(&placement),
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(config: &TuiConfig<'_>) -> Result<()> {
+    use std::io::IsTerminal;
+    if !std::io::stdout().is_terminal() {
+        bail!("tui needs an interactive terminal; use inspect to write a report instead");
+    }
+
+    let file_config = match config.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let atomic_patterns = file_config.false_sharing_patterns(None)?;
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+    let dwarf = DwarfContext::new(&loaded).with_scope(file_config.analyze.scope()?);
+    let mut layouts =
+        find_layouts(&binary, &dwarf, config.filter, config.include_go_runtime, false, true)?;
+    if layouts.is_empty() {
+        bail!("No structs found in {}", config.binary_path.display());
+    }
+    apply_type_sizes(&file_config.type_sizes, &mut layouts);
+    for layout in &mut layouts {
+        analyze_layout(layout, config.cache_line_size);
+    }
+    // Most padding first, so the list opens on the structs worth a look.
+    layouts.sort_by_key(|l| std::cmp::Reverse(l.metrics.padding_bytes));
+
+    layout_audit::Explorer::new(layouts, config.cache_line_size)
+        .with_max_align(config.max_align)
+        .with_atomic_patterns(atomic_patterns)
+        .run()
+        .context("Terminal UI failed")
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(_: &TuiConfig<'_>) -> Result<()> {
+    bail!(
+        "tui requires building with the `tui` feature \
+         (cargo install layout-audit, without --no-default-features)"
+    )
+}
+
+fn run_summary(config: &SummaryConfig<'_>) -> Result<()> {
+    if matches!(config.output_format, OutputFormat::Sarif | OutputFormat::Csv | OutputFormat::Jsonl)
+    {
+        bail!("summary supports table and json output");
+    }
+    let file_config = match config.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let scope = file_config.analyze.scope()?;
+    let options = InspectOptions {
+        scan: ScanOptions {
+            filter: config.filter,
+            include_go_runtime: config.include_go_runtime,
+            ..ScanOptions::default()
+        },
+        scope: Some(&scope),
+        type_sizes: Some(&file_config.type_sizes),
+        cache_line_size: config.cache_line_size,
+        summary: true,
+        ..InspectOptions::new(config.binary_paths)
+    };
+    let report = pipeline::run_inspect(&options)?;
+    for scan in &report.scans {
+        scan.notices.iter().for_each(print_notice);
+    }
+    let summary = report.summary.expect("run_inspect summarizes when asked to");
+
+    let output = match config.output_format {
+        OutputFormat::Json => JsonFormatter::new(config.pretty).format_summary(&summary),
+        _ => TableFormatter::new(config.no_color, config.cache_line_size)
+            .format_binary_summary(&summary)
+            .trim()
+            .to_string(),
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+fn run_graph(config: &GraphConfig<'_>) -> Result<()> {
+    let file_config = match config.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let scope = file_config.analyze.scope()?;
+    // The filter picks structs to show, not structs to scan: their containers and members are
+    // needed for the edges.
+    let options = InspectOptions {
+        scan: ScanOptions {
+            include_go_runtime: config.include_go_runtime,
+            ..ScanOptions::default()
+        },
+        scope: Some(&scope),
+        type_sizes: Some(&file_config.type_sizes),
+        ..InspectOptions::new(config.binary_paths)
+    };
+    let report = pipeline::run_inspect(&options)?;
+    for scan in &report.scans {
+        scan.notices.iter().for_each(print_notice);
+    }
+
+    let mut graph = containment_graph(&report.layouts);
+    if let Some(filter) = config.filter {
+        graph.retain_connected(|name| name.contains(filter));
+    }
+    if graph.nodes.is_empty() {
+        eprintln!("No struct embeds another by value");
+    }
+    match config.format {
+        GraphFormat::Dot => print!("{}", GraphDotFormatter::new().format(&graph)),
+        GraphFormat::Json => println!("{}", GraphJsonFormatter::new(config.pretty).format(&graph)),
+    }
+    Ok(())
+}
+
+fn run_export(config: &ExportConfig<'_>) -> Result<()> {
+    let file_config = match config.config_path {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let scope = file_config.analyze.scope()?;
+    let atomic_patterns = file_config.false_sharing_patterns(None)?;
+    let options = InspectOptions {
+        scan: ScanOptions {
+            filter: config.filter,
+            include_go_runtime: config.include_go_runtime,
+            ..ScanOptions::default()
+        },
+        scope: Some(&scope),
+        type_sizes: Some(&file_config.type_sizes),
+        cache_line_size: config.cache_line_size,
+        warn_false_sharing: true,
+        atomic_patterns: Some(&atomic_patterns),
+        ..InspectOptions::new(config.binary_paths)
+    };
+    let report = pipeline::run_inspect(&options)?;
+    for scan in &report.scans {
+        scan.notices.iter().for_each(print_notice);
+    }
+
+    match config.format {
+        ExportFormat::Sqlite => {
+            export_sqlite_runs(config.out, &report.scans, &report.layouts, config.cache_line_size)?
+        }
+    }
+    eprintln!(
+        "Exported {} struct(s) from {} binary(ies) to {}",
+        report.layouts.len(),
+        report.scans.len(),
+        config.out.display()
+    );
+    Ok(())
+}
+
+fn run_emit_assertions(config: &EmitAssertionsConfig<'_>) -> Result<()> {
+    let scope = load_scope(config.config_path)?;
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+
+    if let Some(depfile) = &config.depfile {
+        let mut inputs = binary.input_files();
+        inputs.extend(config.config_path.map(Path::to_path_buf));
+        depfile.write(&inputs)?;
+    }
+
+    let dwarf = DwarfContext::new(&loaded).with_scope(scope);
+    let mut layouts = find_layouts(&binary, &dwarf, config.filter, false, false, true)?;
+    // Only the partial-layout flag is used, so the cache line size does not matter.
+    for layout in &mut layouts {
+        analyze_layout(layout, 64);
+    }
+
+    let output = match config.lang {
+        AssertionLang::Go => {
+            let asserted = GoAssertionFormatter::assertable(config.go_package, &layouts).len();
+            if asserted == 0 {
+                bail!("No structs of Go package '{}' found in binary", config.go_package);
+            }
+            let package_name = config.package_name.map_or_else(
+                || GoAssertionFormatter::default_package_name(config.go_package),
+                str::to_string,
+            );
+            eprintln!("Asserting the layout of {} struct(s)", asserted);
+            GoAssertionFormatter::new(package_name).format(config.go_package, &layouts)
+        }
+    };
+
+    match config.out {
+        Some(path) => std::fs::write(path, output)
+            .with_context(|| format!("Failed to write assertions: {}", path.display()))?,
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+fn run_emit_header(config: &EmitHeaderConfig<'_>) -> Result<()> {
+    let scope = load_scope(config.config_path)?;
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+
+    if let Some(depfile) = &config.depfile {
+        let mut inputs = binary.input_files();
+        inputs.extend(config.config_path.map(Path::to_path_buf));
+        depfile.write(&inputs)?;
+    }
+
+    let dwarf = DwarfContext::new(&loaded).with_scope(scope);
+    let mut layouts = find_layouts(&binary, &dwarf, config.filter, false, false, true)?;
+    // Only the partial-layout flag is used, so the cache line size does not matter.
+    for layout in &mut layouts {
+        analyze_layout(layout, 64);
+    }
+
+    let defined = HeaderFormatter::emittable(&layouts).len();
+    if defined == 0 {
+        bail!("No structs in the binary can be written as C definitions");
+    }
+    let guard = config.guard.map_or_else(
+        || {
+            let file_name = config.out.and_then(|p| p.file_name()).and_then(|n| n.to_str());
+            HeaderFormatter::default_guard(file_name)
+        },
+        str::to_string,
+    );
+    let source = config.binary_path.file_name().unwrap_or(config.binary_path.as_os_str());
+    eprintln!("Defining {} struct(s)", defined);
+    let output = HeaderFormatter::new(guard).with_source(source.to_string_lossy()).format(&layouts);
+
+    match config.out {
+        Some(path) => std::fs::write(path, output)
+            .with_context(|| format!("Failed to write header: {}", path.display()))?,
+        None => print!("{}", output),
+    }
+    Ok(())
+}
+
+fn run_snapshot(config: &SnapshotConfig<'_>) -> Result<()> {
+    let scope = load_scope(config.config_path)?;
+
+    let binary = BinaryData::load(config.binary_path)
+        .with_context(|| format!("Failed to load binary: {}", config.binary_path.display()))?;
+    let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+
+    if let Some(depfile) = &config.depfile {
+        let mut inputs = binary.input_files();
+        inputs.extend(config.config_path.map(Path::to_path_buf));
+        depfile.write(&inputs)?;
+    }
+
+    let dwarf = DwarfContext::new(&loaded).with_scope(scope);
+    let layouts = find_layouts(
+        &binary,
+        &dwarf,
+        config.filter,
+        config.include_go_runtime,
+        config.exported_only,
+        true,
+    )?;
+    let output = Snapshot::from_layouts(&layouts).render(config.pretty);
+
+    match config.output {
+        Some(path) => {
+            std::fs::write(path, output + "\n")
+                .with_context(|| format!("Failed to write snapshot: {}", path.display()))?;
+            eprintln!("Recorded {} struct(s) in {}", layouts.len(), path.display());
+        }
+        None => println!("{}", output),
+    }
+    Ok(())
+}
+
+/// Config file `watch --run check` reads budgets from when no `--config` is given.
+const DEFAULT_CONFIG: &str = ".layout-audit.yaml";
+
+fn run_watch(config: &WatchConfig<'_>) -> Result<()> {
+    use notify::Watcher;
+
+    let candidates = watch_candidates(config.path, config.bin)?;
+
+    // Watch the containing directories: linkers usually replace the binary rather than write
+    // it in place, and the binary may not exist yet.
+    let mut dirs: Vec<&Path> = Vec::new();
+    for candidate in &candidates {
+        let dir =
+            candidate.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        if dir.is_dir() && !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    if dirs.is_empty() {
+        bail!("Nothing to watch: no directory exists for {}", config.path.display());
+    }
+
+    let (tx, rx) = std::sync::mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to start file watcher")?;
+    for dir in &dirs {
+        watcher
+            .watch(dir, notify::RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+    }
+
+    let mut state = WatchState::default();
+    let run = |state: &mut WatchState| match newest_binary(&candidates) {
+        Some(binary) => match state.rerun(binary, config) {
+            Ok(report) => print!("{}", report),
+            // A half-written or broken build should not end the watch.
+            Err(e) => eprintln!("Error: {:#}", e),
+        },
+        None => eprintln!("Waiting for {} to be built...", config.path.display()),
+    };
+    run(&mut state);
+
+    let touches_binary = |event: &notify::Event| {
+        !event.kind.is_access() && event.paths.iter().any(|p| candidates.contains(p))
+    };
+    loop {
+        let event = rx.recv().context("File watcher stopped")?;
+        if !event.is_ok_and(|e| touches_binary(&e)) {
+            continue;
+        }
+        // Wait for the writes to settle before reading the binary.
+        while rx.recv_timeout(config.debounce).is_ok() {}
+        run(&mut state);
+    }
+}
+
+/// Files `watch` may analyze: the path itself, or the `--bin` executables in a target directory.
+fn watch_candidates(path: &Path, bin: Option<&str>) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        if bin.is_some() {
+            bail!("--bin needs a target directory, but {} is not a directory", path.display());
+        }
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let Some(bin) = bin else {
+        bail!("{} is a directory; pass --bin <NAME> to pick the executable", path.display());
+    };
+    Ok(vec![path.join("debug").join(bin), path.join("release").join(bin), path.join(bin)])
+}
+
+/// The most recently modified candidate that exists.
+fn newest_binary(candidates: &[PathBuf]) -> Option<&Path> {
+    candidates
+        .iter()
+        .filter_map(|path| Some((std::fs::metadata(path).ok()?.modified().ok()?, path)))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path.as_path())
+}
+
+/// What the previous `watch` run found, so each run reports only what changed.
+#[derive(Default)]
+struct WatchState {
+    layouts: Option<Vec<StructLayout>>,
+    violations: Option<Vec<String>>,
+}
+
+impl WatchState {
+    /// Analyze `binary_path` again and describe the changes since the previous call. The first
+    /// call summarizes the baseline instead.
+    fn rerun(&mut self, binary_path: &Path, config: &WatchConfig<'_>) -> Result<String> {
+        let file_config = match config.config_path {
+            Some(path) => Config::load(path)?,
+            None => Config::default(),
+        };
+
+        let binary = BinaryData::load(binary_path)
+            .with_context(|| format!("Failed to load binary: {}", binary_path.display()))?;
+        let loaded = binary.load_dwarf().context("Failed to load DWARF debug info")?;
+        let dwarf = DwarfContext::new(&loaded).with_scope(file_config.analyze.scope()?);
+        let mut layouts =
+            find_layouts(&binary, &dwarf, config.filter, config.include_go_runtime, false, false)?;
+        apply_type_sizes(&file_config.type_sizes, &mut layouts);
+        for layout in &mut layouts {
+            analyze_layout(layout, config.cache_line_size);
+        }
+
+        let mut out = format!("{}\n", binary_path.display());
+        match config.action {
+            WatchAction::Inspect => {
+                match &self.layouts {
+                    Some(previous) => {
+                        out.push_str(&format_diff_table(&diff_layouts(previous, &layouts)))
+                    }
+                    None => {
+                        let padding: u64 = layouts.iter().map(|l| l.metrics.padding_bytes).sum();
+                        out.push_str(&format!(
+                            "Baseline: {} structs, {} padding bytes\n",
+                            layouts.len(),
+                            padding
+                        ));
+                    }
+                }
+                self.layouts = Some(layouts);
+            }
+            WatchAction::Check => {
+                let violations = budget_violations(&file_config, &layouts, config.cache_line_size)?;
+                match &self.violations {
+                    Some(previous) => {
+                        let resolved: Vec<&String> =
+                            previous.iter().filter(|v| !violations.contains(v)).collect();
+                        let new: Vec<&String> =
+                            violations.iter().filter(|v| !previous.contains(v)).collect();
+                        if resolved.is_empty() && new.is_empty() {
+                            out.push_str("No new or resolved budget violations.\n");
+                        }
+                        for message in resolved {
+                            out.push_str(&format!("  - resolved: {}\n", message));
+                        }
+                        for message in new {
+                            out.push_str(&format!("  + new: {}\n", message));
+                        }
+                        out.push_str(&format!("{} violation(s) remaining\n", violations.len()));
+                    }
+                    None => {
+                        out.push_str(&format!("Baseline: {} violation(s)\n", violations.len()));
+                        for message in &violations {
+                            out.push_str(&format!("  {}\n", message));
+                        }
+                    }
+                }
+                self.violations = Some(violations);
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Messages of the budget violations in `layouts` that source markers do not allow. Budgets
+/// with `applies_to: hot` are skipped, since there is no access profile to consult.
+fn budget_violations(
+    config: &Config,
+    layouts: &[StructLayout],
+    cache_line_size: u32,
+) -> Result<Vec<String>> {
+    Ok(unsuppressed_violations(config, layouts, cache_line_size)?
+        .into_iter()
+        .map(|v| v.message)
+        .collect())
+}
+
+/// Budget violations of `layouts` that neither an in-source suppression nor the `ignore` list
+/// excuses. Hot-only budgets are skipped: there is no profile to apply them.
+fn unsuppressed_violations(
+    config: &Config,
+    layouts: &[StructLayout],
+    cache_line_size: u32,
+) -> Result<Vec<CheckViolation>> {
+    let compiled = config.compile()?;
+    let atomic_patterns = config.false_sharing_patterns(None)?;
+    let mut violations: Vec<CheckViolation> = Vec::new();
+    for layout in layouts {
+        if let Some((budget, _)) = compiled.find_budget(&layout.name)
+            && budget.applies_to != AppliesTo::Hot
+        {
+            violations.extend(budget.violations(
+                layout,
+                config.allocator,
+                &atomic_patterns,
+                cache_line_size,
+                config.read_path(&layout.name),
+            ));
+        }
+    }
+    remove_suppressed(&mut violations);
+    remove_ignored(&IgnoreList::new(&config.ignore)?, Date::today(), &mut violations);
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::find_fixture_path;
+    use super::*;
+    use layout_audit::attest::Digest;
+    use layout_audit::pipeline::find_global_false_sharing;
+    use layout_audit::{DEFAULT_LOCKFILE, GlobalFalseSharingWarning, analyze_false_sharing};
+    use std::path::{Path, PathBuf};
+
+    fn create_temp_config(content: &str) -> PathBuf {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let temp_dir = std::env::temp_dir();
+        let unique_id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let config_path = temp_dir.join(format!(
+            "layout-audit-main-test-{}-{}.yaml",
+            std::process::id(),
+            unique_id
+        ));
+        std::fs::write(&config_path, content).expect("Failed to write temp config");
+        config_path
+    }
+
+    #[test]
+    fn run_inspect_outputs() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let base = InspectConfig {
+            binary_paths: std::slice::from_ref(&path),
+            filter: Some("Padding"),
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Table,
+            csv_members: false,
+            sarif_max_results: None,
+            sort_by: SortField::Name,
+            top: Some(1),
+            min_padding: None,
+            no_color: true,
+            no_pager: false,
+            cache_line_size: 64,
+            pretty: true,
+            json_style: JsonStyle::Compact,
+            warn_false_sharing: true,
+            global_false_sharing: false,
+            fail_on_partial: false,
+            include_partial: false,
+            cache_heatmap: false,
+            by_file: false,
+            by_namespace: false,
+            namespace_depth: None,
+            collapse_templates: false,
+            summary: false,
+            codeowners: None,
+            layout_map: false,
+            summary_only: false,
+            columns: None,
+            flatten_bases: false,
+            bits: false,
+            page_size: None,
+            allocator: None,
+            static_access: false,
+            usage: false,
+            static_instances: false,
+            unit_timeout: None,
+            time_budget: None,
+            stats: false,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            export_sqlite: None,
+            repro_bundle: None,
+            redact_strings: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
+        };
+
+        run_inspect(&base).expect("inspect table");
+        let json_cfg = InspectConfig { output_format: OutputFormat::Json, ..base };
+        run_inspect(&json_cfg).expect("inspect json");
+        let sarif_cfg = InspectConfig { output_format: OutputFormat::Sarif, ..base };
+        run_inspect(&sarif_cfg).expect("inspect sarif");
+        let strict_cfg =
+            InspectConfig { fail_on_partial: true, include_partial: true, top: None, ..base };
+        run_inspect(&strict_cfg).expect("inspect has no partial layouts");
+    }
+
+    #[test]
+    fn run_diff_outputs() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        run_diff(&DiffConfig {
+            old: DiffBaseline::Binary(&path),
+            new_path: &path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            cache_line_size: 64,
+            fail_on_regression: false,
+            fail_on: Severity::Error,
+            profile: None,
+            global_false_sharing: false,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
+        })
+        .expect("diff table");
+        run_diff(&DiffConfig {
+            old: DiffBaseline::Binary(&path),
+            new_path: &path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Json,
+            sarif_max_results: None,
+            cache_line_size: 64,
+            fail_on_regression: false,
+            fail_on: Severity::Error,
+            profile: None,
+            global_false_sharing: false,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
+        })
+        .expect("diff json");
+        run_diff(&DiffConfig {
+            old: DiffBaseline::Binary(&path),
+            new_path: &path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Sarif,
+            sarif_max_results: None,
+            cache_line_size: 64,
+            fail_on_regression: false,
+            fail_on: Severity::Error,
+            profile: None,
+            global_false_sharing: false,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
+        })
+        .expect("diff sarif");
+    }
+
+    #[test]
+    fn run_compare_builds_outputs() {
+        let (debug, release) =
+            match (find_fixture_path("test_simple"), find_fixture_path("test_modified")) {
+                (Some(d), Some(r)) => (d, r),
+                _ => return,
+            };
+
+        let base = CompareBuildsConfig {
+            debug_path: &debug,
+            release_path: &release,
+            filter: None,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            depfile: None,
+        };
+        run_compare_builds(&base).expect("compare-builds table");
+        let json_cfg = CompareBuildsConfig { output_format: OutputFormat::Json, ..base };
+        run_compare_builds(&json_cfg).expect("compare-builds json");
+        let sarif_cfg = CompareBuildsConfig { output_format: OutputFormat::Sarif, ..base };
+        run_compare_builds(&sarif_cfg).expect("compare-builds sarif");
+
+        let same = CompareBuildsConfig { release_path: &debug, ..base };
+        assert!(!run_compare_builds(&same).expect("compare-builds identical"));
+    }
+
+    #[test]
+    fn run_export_writes_sqlite_runs() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("audit.db");
+        let config = ExportConfig {
+            format: ExportFormat::Sqlite,
+            out: &out,
+            binary_paths: std::slice::from_ref(&path),
+            config_path: None,
+            filter: None,
+            cache_line_size: 64,
+            include_go_runtime: false,
+        };
+
+        #[cfg(feature = "sqlite")]
+        {
+            run_export(&config).unwrap();
+            run_export(&config).unwrap();
+            let conn = rusqlite::Connection::open(&out).unwrap();
+            let count = |sql: &str| -> i64 { conn.query_row(sql, [], |row| row.get(0)).unwrap() };
+            assert_eq!(count("SELECT COUNT(*) FROM runs"), 2);
+            assert_eq!(
+                count("SELECT padding_bytes FROM structs WHERE name = 'InternalPadding' LIMIT 1"),
+                6
+            );
+            assert!(count("SELECT COUNT(*) FROM holes") > 0);
+        }
+        #[cfg(not(feature = "sqlite"))]
+        {
+            let err = run_export(&config).unwrap_err();
+            assert!(err.to_string().contains("`sqlite` feature"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn run_schema_check_reports_missing_fields() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let schema = dir.path().join("simple.proto");
+        std::fs::write(
+            &schema,
+            "syntax = \"proto3\";\n\
+             message WithPointer { int32 tag = 1; bytes ptr = 2; int64 value = 3; int32 extra = 4; }\n\
+             message NotInBinary { int32 a = 1; }\n",
+        )
+        .unwrap();
+        let base = SchemaCheckConfig {
+            schema_path: &schema,
+            binary_path: &path,
+            config_path: None,
+            filter: None,
+            output_format: OutputFormat::Table,
+            pretty: false,
+            no_color: true,
+            include_go_runtime: false,
+        };
+
+        let err = run_schema_check(&base).expect_err("extra is not in the binary");
+        assert!(err.to_string().contains("1 field mismatch"), "{}", err);
+        run_schema_check(&SchemaCheckConfig {
+            filter: Some("NotInBinary"),
+            output_format: OutputFormat::Json,
+            ..base
+        })
+        .expect("a message without a struct is only reported");
+        let unknown = dir.path().join("simple.avsc");
+        std::fs::write(&unknown, "{}").unwrap();
+        let err =
+            run_schema_check(&SchemaCheckConfig { schema_path: &unknown, ..base }).unwrap_err();
+        assert!(format!("{:#}", err).contains("expected a .proto or .fbs file"), "{:#}", err);
+    }
+
+    #[test]
+    fn run_compare_targets_outputs() {
+        let Some(path) = find_fixture_path("test_simple") else { return };
+        let base = CompareTargetsConfig {
+            old_path: &path,
+            new_path: &path,
+            config_path: None,
+            filter: None,
+            output_format: OutputFormat::Table,
+            cache_line_size: 64,
+            pretty: false,
+            no_color: true,
+            include_go_runtime: false,
+        };
+        run_compare_targets(&base).expect("compare-targets table");
+        let json = CompareTargetsConfig { output_format: OutputFormat::Json, ..base };
+        run_compare_targets(&json).expect("compare-targets json");
+        let sarif = CompareTargetsConfig { output_format: OutputFormat::Sarif, ..base };
+        assert!(run_compare_targets(&sarif).is_err());
+    }
+
+    #[test]
+    fn run_check_outputs() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let config = create_temp_config(
+            r#"
+budgets:
+  NoPadding:
+    max_size: 100
+    max_padding: 20
+    max_padding_percent: 80.0
+"#,
+        );
+
+        run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        })
+        .expect("check table");
+        run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Json,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        })
+        .expect("check json");
+        run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Sarif,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        })
+        .expect("check sarif");
+
+        std::fs::remove_file(&config).ok();
+    }
+
+    #[test]
+    fn run_check_failure_path() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let config = create_temp_config(
+            r#"
+budgets:
+  InternalPadding:
+    max_size: 10
+"#,
+        );
+
+        let result = run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        });
+        std::fs::remove_file(&config).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn run_check_respects_analyze_scope() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let check = |content: &str| {
+            let config = create_temp_config(content);
+            let result = run_check(&CheckConfig {
+                binary_paths: std::slice::from_ref(&path),
+                config_path: &config,
+                profile_path: None,
+                previous_path: None,
+                list_suppressions: false,
+                lint_config: false,
+                validate_config_only: false,
+                lockfile_path: Path::new(DEFAULT_LOCKFILE),
+                accept: false,
+                budget_match: BudgetMatch::Exact,
+                output_format: OutputFormat::Table,
+                sarif_max_results: None,
+                fail_on: Severity::Error,
+                cache_line_size: 64,
+                include_go_runtime: false,
+                exported_only: false,
+                sort_stable: false,
+                depfile: None,
+                atomic_patterns_file: None,
+                time_budget: None,
+                attestation: None,
+            });
+            std::fs::remove_file(&config).ok();
+            result
+        };
+
+        let budgets = "budgets:\n  \"*\":\n    max_size: 10\n";
+        assert!(check(budgets).is_err());
+        assert!(check(&format!("analyze:\n  include: [\"NoPadding\"]\n{}", budgets)).is_err());
+        assert!(check(&format!("analyze:\n  include: [\"Inner\"]\n{}", budgets)).is_ok());
+        assert!(check(&format!("analyze:\n  exclude: [\"*\"]\n{}", budgets)).is_ok());
+        assert!(check(&format!("analyze:\n  include: [\"[\"]\n{}", budgets)).is_err());
+    }
+
+    #[test]
+    fn run_check_hot_only_budgets_follow_profile() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let config = create_temp_config(
+            r#"
+hot_threshold: 100
+budgets:
+  InternalPadding:
+    max_size: 10
+    applies_to: hot
+"#,
+        );
+        let hot = create_temp_config("InternalPadding: 500\n");
+        let cold = create_temp_config("InternalPadding: 3\n");
+
+        let check = |profile_path: Option<&Path>| {
+            run_check(&CheckConfig {
+                binary_paths: std::slice::from_ref(&path),
+                config_path: &config,
+                profile_path,
+                previous_path: None,
+                list_suppressions: false,
+                lint_config: false,
+                validate_config_only: false,
+                lockfile_path: Path::new(DEFAULT_LOCKFILE),
+                accept: false,
+                budget_match: BudgetMatch::Exact,
+                output_format: OutputFormat::Table,
+                sarif_max_results: None,
+                fail_on: Severity::Error,
+                cache_line_size: 64,
+                include_go_runtime: false,
+                exported_only: false,
+                sort_stable: false,
+                depfile: None,
+                atomic_patterns_file: None,
+                time_budget: None,
+                attestation: None,
+            })
+        };
+
+        let hot_result = check(Some(&hot));
+        let cold_result = check(Some(&cold));
+        let missing_result = check(None);
+        for p in [&config, &hot, &cold] {
+            std::fs::remove_file(p).ok();
+        }
+
+        assert!(hot_result.is_err(), "hot struct must be held to its budget");
+        assert!(cold_result.is_ok(), "cold struct is exempt from hot-only budgets");
+        let err = missing_result.expect_err("hot-only budgets need a profile").to_string();
+        assert!(err.contains("--profile"));
+    }
+
+    #[test]
+    fn run_check_json_failure_path() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let config = create_temp_config(
+            r#"
+budgets:
+  InternalPadding:
+    max_size: 1
+"#,
+        );
+
+        let result = run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Json,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        });
+        std::fs::remove_file(&config).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn run_check_sarif_failure_path() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let config = create_temp_config(
+            r#"
+budgets:
+  InternalPadding:
+    max_size: 1
+"#,
+        );
+
+        let result = run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Sarif,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        });
+        std::fs::remove_file(&config).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn run_check_false_sharing_violation() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let binary = match BinaryData::load(&path) {
+            Ok(b) => b,
+            Err(_) => return,
+        };
+        let loaded = match binary.load_dwarf() {
+            Ok(l) => l,
+            Err(_) => return,
+        };
+        let dwarf = DwarfContext::new(&loaded);
+        let mut layouts = match dwarf.find_structs(Some("WithAtomics"), false) {
+            Ok(l) => l,
+            Err(_) => return,
+        };
+        if layouts.is_empty() {
+            return;
+        }
+        let layout = &mut layouts[0];
+        analyze_layout(layout, 64);
+        let fs = analyze_false_sharing(layout, 64);
+        if fs.warnings.is_empty() {
+            return;
+        }
+
+        let config = create_temp_config(
+            r#"
+budgets:
+  WithAtomics:
+    max_false_sharing_warnings: 0
+"#,
+        );
+
+        let result = run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        });
+        std::fs::remove_file(&config).ok();
+        assert!(result.is_err());
+
+        // Known-good pairs no longer count against the budget.
+        let ignored = create_temp_config(
+            r#"
+budgets:
+  WithAtomics:
+    max_false_sharing_warnings: 0
+false_sharing_ignore:
+  - struct: WithAtomics
+    members: [a, b]
+"#,
+        );
+        let result = run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &ignored,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        });
+        std::fs::remove_file(&ignored).ok();
+        result.expect("ignored pair within budget");
+    }
+
+    #[test]
+    fn run_suggest_outputs() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        run_suggest(&SuggestConfig {
+            binary_path: &path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            min_savings: Some(1),
+            cache_line_size: 64,
+            pretty: true,
+            max_align: 8,
+            sort_by_savings: false,
+            strategy: OptimizeStrategy::Greedy,
+            static_access: false,
+            usage: false,
+            locality: false,
+            size_cap: None,
+            no_color: true,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            emit_code: None,
+            depfile: None,
+        })
+        .expect("suggest table");
+
+        run_suggest(&SuggestConfig {
+            binary_path: &path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Json,
+            sarif_max_results: None,
+            min_savings: Some(1),
+            cache_line_size: 64,
+            pretty: true,
+            max_align: 8,
+            sort_by_savings: false,
+            strategy: OptimizeStrategy::Greedy,
+            static_access: false,
+            usage: false,
+            locality: false,
+            size_cap: None,
+            no_color: true,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            emit_code: None,
+            depfile: None,
+        })
+        .expect("suggest json");
+
+        run_suggest(&SuggestConfig {
+            binary_path: &path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Sarif,
+            sarif_max_results: None,
+            min_savings: Some(1),
+            cache_line_size: 64,
+            pretty: true,
+            max_align: 8,
+            sort_by_savings: false,
+            strategy: OptimizeStrategy::Greedy,
+            static_access: false,
+            usage: false,
+            locality: false,
+            size_cap: None,
+            no_color: true,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            emit_code: None,
+            depfile: None,
+        })
+        .expect("suggest sarif");
+    }
+
+    #[test]
+    fn run_check_writes_depfile_with_config() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let config = create_temp_config(
+            r#"
+budgets:
+  NoPadding:
+    max_size: 100
+"#,
+        );
+        let depfile_path = config.with_extension("d");
+
+        run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: Some(DepfileConfig { path: &depfile_path, target: Some("audit.stamp") }),
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        })
+        .expect("check with depfile");
+
+        let content = std::fs::read_to_string(&depfile_path).expect("depfile written");
+        std::fs::remove_file(&config).ok();
+        std::fs::remove_file(&depfile_path).ok();
+
+        assert!(content.starts_with("audit.stamp:"));
+        assert!(content.contains("test_simple"));
+        assert!(content.contains(&config.display().to_string()));
     }
 
-    let (suggestions, locations): (Vec<_>, Vec<_>) = suggestions_with_locations.into_iter().unzip();
+    #[test]
+    fn run_placement_outputs() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
 
-    let output_str = match output_format {
-        OutputFormat::Table => {
-            let formatter = SuggestTableFormatter::new(no_color);
-            formatter.format(&suggestions)
-        }
-        OutputFormat::Json => {
-            let formatter = SuggestJsonFormatter::new(pretty);
-            formatter.format(&suggestions)
-        }
-        OutputFormat::Sarif => {
-            let formatter = SarifFormatter::new();
-            formatter.format_suggest(&suggestions, &locations)
-        }
-    };
+        let config =
+            create_temp_config("thread_owners:\n  io: [WithAtomic]\n  worker: [\"With*\"]\n");
+        let base = PlacementConfig {
+            binary_path: &path,
+            config_path: Some(&config),
+            filter: None,
+            lang: CodeLanguage::Rust,
+            cache_line_size: 64,
+            output_format: OutputFormat::Table,
+            pretty: false,
+            no_color: true,
+            include_go_runtime: false,
+        };
+        run_placement(&base).expect("table");
+        run_placement(&PlacementConfig { output_format: OutputFormat::Json, ..base })
+            .expect("json");
+        assert!(
+            run_placement(&PlacementConfig { output_format: OutputFormat::Sarif, ..base }).is_err()
+        );
 
-    println!("{}", output_str);
+        let invalid = create_temp_config("thread_owners:\n  io: [\"[\"]\n");
+        assert!(run_placement(&PlacementConfig { config_path: Some(&invalid), ..base }).is_err());
 
-    Ok(())
-}
+        std::fs::remove_file(&config).ok();
+        std::fs::remove_file(&invalid).ok();
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::path::{Path, PathBuf};
+    #[test]
+    fn run_check_writes_attestation() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
 
-    fn find_fixture_path(name: &str) -> Option<PathBuf> {
-        let base = Path::new("tests/fixtures/bin");
-        let dsym_path = base.join(format!("{}.dSYM/Contents/Resources/DWARF/{}", name, name));
-        if dsym_path.exists() {
-            return Some(dsym_path);
-        }
+        let config_text = "budgets:\n  NoPadding:\n    max_size: 1\n";
+        let config = create_temp_config(config_text);
+        let attestation_path = config.with_extension("intoto.json");
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: Some(AttestationConfig { path: &attestation_path, sign: None }),
+        };
 
-        let exe_path = base.join(format!("{}.exe", name));
-        if exe_path.exists() {
-            return Some(exe_path);
-        }
+        // A failing check still records its outcome.
+        assert!(run_check(&base).is_err());
+        let statement: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&attestation_path).unwrap()).unwrap();
+        let binary = std::fs::read(&path).unwrap();
+        assert_eq!(statement["subject"][0]["digest"]["sha256"], Digest::of(&binary).sha256);
+        assert_eq!(
+            statement["predicate"]["config"]["digest"]["sha256"],
+            Digest::of(config_text.as_bytes()).sha256
+        );
+        assert_eq!(statement["predicate"]["results"]["failing"], 1);
+        assert_eq!(statement["predicate"]["results"]["passed"], false);
 
-        let direct_path = base.join(name);
-        if direct_path.exists() {
-            return Some(direct_path);
+        #[cfg(unix)]
+        {
+            let signed = AttestationConfig { path: &attestation_path, sign: Some("cat") };
+            assert!(run_check(&CheckConfig { attestation: Some(signed), ..base }).is_err());
+            let envelope: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&attestation_path).unwrap()).unwrap();
+            assert_eq!(envelope["payloadType"], "application/vnd.in-toto+json");
+            assert_eq!(envelope["signatures"].as_array().unwrap().len(), 1);
         }
 
-        None
-    }
-
-    fn create_temp_config(content: &str) -> PathBuf {
-        use std::sync::atomic::{AtomicU64, Ordering};
-        static COUNTER: AtomicU64 = AtomicU64::new(0);
-        let temp_dir = std::env::temp_dir();
-        let unique_id = COUNTER.fetch_add(1, Ordering::SeqCst);
-        let config_path = temp_dir.join(format!(
-            "layout-audit-main-test-{}-{}.yaml",
-            std::process::id(),
-            unique_id
-        ));
-        std::fs::write(&config_path, content).expect("Failed to write temp config");
-        config_path
+        std::fs::remove_file(&config).ok();
+        std::fs::remove_file(&attestation_path).ok();
     }
 
     #[test]
-    fn run_inspect_outputs() {
+    fn run_inspect_no_matches() {
         let path = match find_fixture_path("test_simple") {
             Some(p) => p,
             None => return,
         };
 
-        let base = InspectConfig {
-            binary_path: &path,
-            filter: Some("Padding"),
+        let cfg = InspectConfig {
+            binary_paths: std::slice::from_ref(&path),
+            filter: Some("DoesNotExist"),
+            filter_regex: None,
+            member_filter: None,
             output_format: OutputFormat::Table,
+            csv_members: false,
+            sarif_max_results: None,
             sort_by: SortField::Name,
-            top: Some(1),
+            top: None,
             min_padding: None,
             no_color: true,
+            no_pager: false,
             cache_line_size: 64,
-            pretty: true,
-            warn_false_sharing: true,
+            pretty: false,
+            json_style: JsonStyle::Compact,
+            warn_false_sharing: false,
+            global_false_sharing: false,
+            fail_on_partial: false,
+            include_partial: false,
+            cache_heatmap: false,
+            by_file: false,
+            by_namespace: false,
+            namespace_depth: None,
+            collapse_templates: false,
+            summary: false,
+            codeowners: None,
+            layout_map: false,
+            summary_only: false,
+            columns: None,
+            flatten_bases: false,
+            bits: false,
+            page_size: None,
+            allocator: None,
+            static_access: false,
+            usage: false,
+            static_instances: false,
+            unit_timeout: None,
+            time_budget: None,
+            stats: false,
             include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            export_sqlite: None,
+            repro_bundle: None,
+            redact_strings: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
         };
 
-        run_inspect(&base).expect("inspect table");
-        let json_cfg = InspectConfig { output_format: OutputFormat::Json, ..base };
-        run_inspect(&json_cfg).expect("inspect json");
-        let sarif_cfg = InspectConfig { output_format: OutputFormat::Sarif, ..base };
-        run_inspect(&sarif_cfg).expect("inspect sarif");
+        run_inspect(&cfg).expect("inspect no matches");
     }
 
     #[test]
-    fn run_diff_outputs() {
+    fn run_inspect_min_padding_filters_all() {
         let path = match find_fixture_path("test_simple") {
             Some(p) => p,
             None => return,
         };
 
-        run_diff(&path, &path, None, OutputFormat::Table, 64, false, false).expect("diff table");
-        run_diff(&path, &path, None, OutputFormat::Json, 64, false, false).expect("diff json");
-        run_diff(&path, &path, None, OutputFormat::Sarif, 64, false, false).expect("diff sarif");
+        let cfg = InspectConfig {
+            binary_paths: std::slice::from_ref(&path),
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Table,
+            csv_members: false,
+            sarif_max_results: None,
+            sort_by: SortField::PaddingPct,
+            top: None,
+            min_padding: Some(10_000),
+            no_color: true,
+            no_pager: false,
+            cache_line_size: 64,
+            pretty: false,
+            json_style: JsonStyle::Compact,
+            warn_false_sharing: false,
+            global_false_sharing: false,
+            fail_on_partial: false,
+            include_partial: false,
+            cache_heatmap: false,
+            by_file: false,
+            by_namespace: false,
+            namespace_depth: None,
+            collapse_templates: false,
+            summary: false,
+            codeowners: None,
+            layout_map: false,
+            summary_only: false,
+            columns: None,
+            flatten_bases: false,
+            bits: false,
+            page_size: None,
+            allocator: None,
+            static_access: false,
+            usage: false,
+            static_instances: false,
+            unit_timeout: None,
+            time_budget: None,
+            stats: false,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            export_sqlite: None,
+            repro_bundle: None,
+            redact_strings: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
+        };
+
+        run_inspect(&cfg).expect("inspect min padding");
     }
 
     #[test]
-    fn run_check_outputs() {
-        let path = match find_fixture_path("test_simple") {
+    fn run_diff_with_changes_table() {
+        let old_path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+        let new_path = match find_fixture_path("test_modified") {
             Some(p) => p,
             None => return,
         };
 
-        let config = create_temp_config(
-            r#"
-budgets:
-  NoPadding:
-    max_size: 100
-    max_padding: 20
-    max_padding_percent: 80.0
-"#,
-        );
+        run_diff(&DiffConfig {
+            old: DiffBaseline::Binary(&old_path),
+            new_path: &new_path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            cache_line_size: 64,
+            fail_on_regression: false,
+            fail_on: Severity::Error,
+            profile: None,
+            global_false_sharing: false,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
+        })
+        .expect("diff table changes");
+    }
 
-        run_check(&path, &config, OutputFormat::Table, 64, false).expect("check table");
-        run_check(&path, &config, OutputFormat::Json, 64, false).expect("check json");
-        run_check(&path, &config, OutputFormat::Sarif, 64, false).expect("check sarif");
+    #[test]
+    fn run_check_alloc_slack_follows_allocator_model() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
 
-        std::fs::remove_file(&config).ok();
+        // WithArray is 20 bytes: 12 bytes slack in jemalloc's 32-byte class, 4 in mimalloc's 24.
+        let budgets = "budgets:\n  WithArray:\n    max_alloc_slack: 4\n";
+        let jemalloc = create_temp_config(budgets);
+        let mimalloc = create_temp_config(&format!("allocator: mimalloc\n{}", budgets));
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &jemalloc,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        let err = run_check(&base).expect_err("jemalloc slack over budget");
+        assert!(err.to_string().contains("1 violation"));
+        run_check(&CheckConfig { config_path: &mimalloc, ..base }).expect("mimalloc within budget");
+        std::fs::remove_file(&jemalloc).ok();
+        std::fs::remove_file(&mimalloc).ok();
     }
 
     #[test]
-    fn run_check_failure_path() {
+    fn run_check_member_count_and_size() {
         let path = match find_fixture_path("test_simple") {
             Some(p) => p,
             None => return,
         };
 
-        let config = create_temp_config(
-            r#"
-budgets:
-  InternalPadding:
-    max_size: 10
-"#,
+        // InternalPadding has 4 members; WithArray's `data` (char[10]) is the only member over
+        // 9 bytes.
+        let members = create_temp_config("budgets:\n  \"*\":\n    max_members: 3\n");
+        let member_size = create_temp_config("budgets:\n  \"*\":\n    max_single_member_size: 9\n");
+        let both = create_temp_config(
+            "budgets:\n  \"*\":\n    max_members: 4\n    max_single_member_size: 10\n",
         );
-
-        let result = run_check(&path, &config, OutputFormat::Table, 64, false);
-        std::fs::remove_file(&config).ok();
-        assert!(result.is_err());
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &members,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        let err = run_check(&base).expect_err("InternalPadding over member budget");
+        assert!(err.to_string().contains("1 violation"));
+        let err = run_check(&CheckConfig { config_path: &member_size, ..base })
+            .expect_err("WithArray::data over member size budget");
+        assert!(err.to_string().contains("1 violation"));
+        run_check(&CheckConfig { config_path: &both, ..base }).expect("within both budgets");
+        for config in [members, member_size, both] {
+            std::fs::remove_file(config).ok();
+        }
     }
 
     #[test]
-    fn run_check_json_failure_path() {
+    fn run_check_fail_on_severity() {
         let path = match find_fixture_path("test_simple") {
             Some(p) => p,
             None => return,
         };
 
+        // Every struct breaks the advisory budget; only InternalPadding breaks the hard one.
         let config = create_temp_config(
-            r#"
-budgets:
-  InternalPadding:
-    max_size: 1
-"#,
+            "budgets:\n  \"*\":\n    max_size: 1\n    severity: warn\n  \
+             InternalPadding:\n    max_members: 3\n",
         );
-
-        let result = run_check(&path, &config, OutputFormat::Json, 64, false);
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        let err = run_check(&base).expect_err("hard budget broken");
+        assert!(err.to_string().contains(": 1 violation"), "{}", err);
+
+        let advisory =
+            create_temp_config("budgets:\n  \"*\":\n    max_size: 1\n    severity: warn\n");
+        let advisory = CheckConfig { config_path: &advisory, ..base };
+        run_check(&advisory).expect("warn budgets do not fail by default");
+        run_check(&CheckConfig { output_format: OutputFormat::Sarif, ..advisory }).expect("sarif");
+        assert!(run_check(&CheckConfig { fail_on: Severity::Warn, ..advisory }).is_err());
+        assert!(run_check(&CheckConfig { fail_on: Severity::Info, ..advisory }).is_err());
         std::fs::remove_file(&config).ok();
-        assert!(result.is_err());
+        std::fs::remove_file(advisory.config_path).ok();
     }
 
     #[test]
-    fn run_check_sarif_failure_path() {
+    fn run_check_max_cache_lines() {
         let path = match find_fixture_path("test_simple") {
             Some(p) => p,
             None => return,
         };
 
-        let config = create_temp_config(
-            r#"
-budgets:
-  InternalPadding:
-    max_size: 1
-"#,
-        );
-
-        let result = run_check(&path, &config, OutputFormat::Sarif, 64, false);
-        std::fs::remove_file(&config).ok();
-        assert!(result.is_err());
+        // With 16-byte lines, WithArray, WithPointer, and WithQualifiers span two.
+        let config = create_temp_config("budgets:\n  \"*\":\n    max_cache_lines: 1\n");
+        let invalid = create_temp_config("budgets:\n  \"*\":\n    max_cache_lines: 0\n");
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 16,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        let err = run_check(&base).expect_err("structs over the cache line budget");
+        assert!(err.to_string().contains("3 violation"), "{}", err);
+        run_check(&CheckConfig { cache_line_size: 64, ..base }).expect("within one 64-byte line");
+        let err = run_check(&CheckConfig { config_path: &invalid, ..base }).unwrap_err();
+        assert!(err.to_string().contains("max_cache_lines"));
+        for config in [config, invalid] {
+            std::fs::remove_file(config).ok();
+        }
     }
 
     #[test]
-    fn run_check_false_sharing_violation() {
+    fn run_check_min_and_exact_size() {
         let path = match find_fixture_path("test_simple") {
             Some(p) => p,
             None => return,
         };
 
-        let binary = match BinaryData::load(&path) {
-            Ok(b) => b,
-            Err(_) => return,
+        let passing = create_temp_config(
+            "budgets:\n  WithPointer:\n    exact_size: 24\n  InternalPadding:\n    min_size: 12\n",
+        );
+        let failing = create_temp_config(
+            "budgets:\n  WithPointer:\n    exact_size: 64\n  InternalPadding:\n    min_size: 32\n",
+        );
+        let invalid =
+            create_temp_config("budgets:\n  WithPointer:\n    min_size: 32\n    max_size: 16\n");
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &passing,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
         };
-        let loaded = match binary.load_dwarf() {
-            Ok(l) => l,
-            Err(_) => return,
+        run_check(&base).expect("sizes match the budgets");
+        let err = run_check(&CheckConfig { config_path: &failing, ..base }).unwrap_err();
+        assert!(err.to_string().contains("2 violation"), "{}", err);
+        let err = run_check(&CheckConfig { config_path: &invalid, ..base }).unwrap_err();
+        assert!(err.to_string().contains("min_size 32 exceeds max_size 16"), "{}", err);
+        for config in [passing, failing, invalid] {
+            std::fs::remove_file(config).ok();
+        }
+    }
+
+    #[test]
+    fn run_check_max_stride_factor() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
         };
-        let dwarf = DwarfContext::new(&loaded);
-        let mut layouts = match dwarf.find_structs(Some("WithAtomics"), false) {
-            Ok(l) => l,
-            Err(_) => return,
+
+        // WithPointer strides 24 bytes for 13 bytes of members (1.85x); everything else is lower.
+        let strict = create_temp_config("budgets:\n  \"*\":\n    max_stride_factor: 1.8\n");
+        let loose = create_temp_config("budgets:\n  \"*\":\n    max_stride_factor: 2.0\n");
+        let invalid = create_temp_config("budgets:\n  \"*\":\n    max_stride_factor: 0.5\n");
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &strict,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
         };
-        if layouts.is_empty() {
-            return;
+        let err = run_check(&base).expect_err("WithPointer over stride budget");
+        assert!(err.to_string().contains("1 violation"));
+        run_check(&CheckConfig { config_path: &loose, ..base }).expect("within stride budget");
+        let err = run_check(&CheckConfig { config_path: &invalid, ..base }).unwrap_err();
+        assert!(err.to_string().contains("max_stride_factor"));
+        for config in [strict, loose, invalid] {
+            std::fs::remove_file(config).ok();
         }
-        let layout = &mut layouts[0];
-        analyze_layout(layout, 64);
-        let fs = analyze_false_sharing(layout, 64);
-        if fs.warnings.is_empty() {
-            return;
+    }
+
+    #[test]
+    fn run_check_member_budgets() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        // WithPointer: tag at 0, ptr at 8, value at 16.
+        let config = create_temp_config(
+            "budgets:\n  WithPointer:\n    members:\n      value: { max_offset: 8 }\n      \
+             tag: { same_cache_line_as: value }\n",
+        );
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        let err = run_check(&base).expect_err("value past max_offset");
+        assert!(err.to_string().contains("1 violation"));
+        // With 16-byte lines, value also lands on a different line than tag.
+        let err = run_check(&CheckConfig { cache_line_size: 16, ..base }).unwrap_err();
+        assert!(err.to_string().contains("2 violation"));
+        std::fs::remove_file(&config).ok();
+
+        let pinned = create_temp_config(
+            "budgets:\n  WithPointer:\n    member_offsets: { tag: 0, ptr: 8, value: 64 }\n",
+        );
+        let err = run_check(&CheckConfig { config_path: &pinned, ..base }).unwrap_err();
+        assert!(err.to_string().contains("1 violation"));
+        std::fs::remove_file(&pinned).ok();
+    }
+
+    #[test]
+    fn over_fetch_budget_reads_the_declared_hot_members() {
+        // hot_a and hot_b fit in one line, but 120 cold bytes push them onto two.
+        let mut layout = StructLayout::new("HotCold".to_string(), 136, Some(8));
+        layout.members = vec![
+            MemberLayout::new("hot_a".to_string(), "u64".to_string(), Some(0), Some(8)),
+            MemberLayout::new("cold".to_string(), "[u8; 120]".to_string(), Some(8), Some(120)),
+            MemberLayout::new("hot_b".to_string(), "u64".to_string(), Some(128), Some(8)),
+        ];
+        let config = Config::parse(
+            "budgets: { HotCold: { max_over_fetch: 4.0 } }\nhot_fields: { HotCold: [hot_a, hot_b] }",
+            "test",
+        )
+        .unwrap();
+        let budget = &config.budgets["HotCold"];
+        let violations = |read_path| {
+            budget.violations(
+                &layout,
+                AllocatorModel::default(),
+                &AtomicPatterns::default(),
+                64,
+                read_path,
+            )
+        };
+
+        let found = violations(config.read_path("HotCold"));
+        assert_eq!(found.len(), 1);
+        assert!(matches!(found[0].kind, CheckViolationKind::MaxOverFetch));
+        assert!(found[0].message.contains("loads 128 bytes (2 cache lines) for 16 used, 8.0x"));
+        assert!(violations(None).is_empty());
+
+        let invalid: Budget = serde_yaml::from_str("max_over_fetch: 0.5").unwrap();
+        assert!(invalid.validate("HotCold").is_err());
+    }
+
+    #[test]
+    fn member_offsets_report_where_members_are() {
+        // A ring buffer whose tail was meant to start the second cache line.
+        let mut layout = StructLayout::new("Ring".to_string(), 72, Some(8));
+        layout.members = vec![
+            MemberLayout::new("head".to_string(), "u64".to_string(), Some(0), Some(8)),
+            MemberLayout::new("tail".to_string(), "u64".to_string(), Some(8), Some(8)),
+            MemberLayout::new("len".to_string(), "u64".to_string(), None, Some(8)),
+        ];
+        let budget: Budget =
+            serde_yaml::from_str("member_offsets: { head: 0, tail: 64, len: 16, cap: 24 }")
+                .unwrap();
+
+        let messages: Vec<String> = budget
+            .violations(&layout, AllocatorModel::default(), &AtomicPatterns::default(), 64, None)
+            .into_iter()
+            .map(|v| v.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Ring: member tail is pinned to offset 64 by member_offsets, but is at offset 8 \
+                 (56 bytes early, cache line 0 instead of 1)",
+                "Ring: member len is pinned to offset 16 by member_offsets, but has no known offset",
+            ]
+        );
+        assert_eq!(budget.unknown_members(&layout), vec!["cap"]);
+
+        let budget: Budget =
+            serde_yaml::from_str("members: { head: { offset: 0 }, tail: { offset: 64 } }").unwrap();
+        budget.validate("Ring").unwrap();
+        let messages: Vec<String> = budget
+            .violations(&layout, AllocatorModel::default(), &AtomicPatterns::default(), 64, None)
+            .into_iter()
+            .map(|v| v.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Ring: member tail is pinned to offset 64 by members, but is at offset 8 \
+                 (56 bytes early, cache line 0 instead of 1)",
+            ]
+        );
+
+        for conflicting in [
+            "{ member_offsets: { tail: 64 }, members: { tail: { offset: 8 } } }",
+            "members: { tail: { offset: 64, max_offset: 8 } }",
+        ] {
+            let budget: Budget = serde_yaml::from_str(conflicting).unwrap();
+            assert!(budget.validate("Ring").is_err(), "{}", conflicting);
         }
+    }
+
+    #[test]
+    fn run_check_forbidden_member_types() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
 
+        // Outer embeds Inner; WithPointer only points at void.
         let config = create_temp_config(
-            r#"
-budgets:
-  WithAtomics:
-    max_false_sharing_warnings: 0
-"#,
+            "budgets:\n  \"*\":\n    forbid_members_of_type: [\"Inner\", \"void\"]\n",
         );
+        let invalid =
+            create_temp_config("budgets:\n  Outer:\n    forbid_members_of_type: [\"[\"]\n");
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        let err = run_check(&base).expect_err("Outer embeds Inner");
+        assert!(err.to_string().contains("1 violation"));
+        let err = run_check(&CheckConfig { config_path: &invalid, ..base }).unwrap_err();
+        assert!(format!("{:#}", err).contains("forbid_members_of_type"));
+        for config in [config, invalid] {
+            std::fs::remove_file(config).ok();
+        }
+    }
 
-        let result = run_check(&path, &config, OutputFormat::Table, 64, false);
-        std::fs::remove_file(&config).ok();
-        assert!(result.is_err());
+    #[test]
+    fn member_cache_lines_cover_member_bytes() {
+        let member = |offset, size| {
+            MemberLayout::new("m".to_string(), "int".to_string(), Some(offset), Some(size))
+        };
+        assert_eq!(member_cache_lines(&member(60, 4), 64), Some((0, 0)));
+        assert_eq!(member_cache_lines(&member(62, 4), 64), Some((0, 1)));
+        assert_eq!(member_cache_lines(&member(64, 0), 64), Some((1, 1)));
+        assert_eq!(describe_lines((0, 1)), "cache lines 0-1");
     }
 
     #[test]
-    fn run_suggest_outputs() {
+    fn run_check_honors_source_suppressions() {
         let path = match find_fixture_path("test_simple") {
             Some(p) => p,
             None => return,
         };
 
-        run_suggest(&path, None, OutputFormat::Table, Some(1), 64, true, 8, false, true, false)
-            .expect("suggest table");
-
-        run_suggest(&path, None, OutputFormat::Json, Some(1), 64, true, 8, false, true, false)
-            .expect("suggest json");
-
-        run_suggest(&path, None, OutputFormat::Sarif, Some(1), 64, true, 8, false, true, false)
-            .expect("suggest sarif");
+        // test_simple.c marks WithArray with `layout-audit: allow(max_padding)`.
+        let padding = create_temp_config("budgets:\n  WithArray:\n    max_padding: 0\n");
+        let size = create_temp_config("budgets:\n  WithArray:\n    max_size: 8\n");
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &padding,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        run_check(&base).expect("padding violation suppressed");
+        run_check(&CheckConfig { output_format: OutputFormat::Sarif, ..base })
+            .expect("suppressed sarif");
+        assert!(run_check(&CheckConfig { config_path: &size, ..base }).is_err());
+
+        let list = CheckConfig { list_suppressions: true, ..base };
+        run_check(&list).expect("list suppressions");
+        run_check(&CheckConfig { output_format: OutputFormat::Json, ..list }).expect("list json");
+        std::fs::remove_file(&padding).ok();
+        std::fs::remove_file(&size).ok();
     }
 
     #[test]
-    fn run_inspect_no_matches() {
+    fn run_check_honors_config_ignore_list() {
         let path = match find_fixture_path("test_simple") {
             Some(p) => p,
             None => return,
         };
 
-        let cfg = InspectConfig {
-            binary_path: &path,
-            filter: Some("DoesNotExist"),
+        let budgets = "budgets:\n  \"*Padding\":\n    max_padding: 0\n";
+        let ignored = create_temp_config(&format!(
+            "{}ignore:\n  - pattern: \"*Padding\"\n    reason: legacy wire format\n",
+            budgets
+        ));
+        let expired = create_temp_config(&format!(
+            "{}ignore:\n  - pattern: \"*Padding\"\n    expires: 2000-01-01\n",
+            budgets
+        ));
+        let invalid = create_temp_config(&format!(
+            "{}ignore:\n  - pattern: \"*Padding\"\n    expires: soon\n",
+            budgets
+        ));
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &ignored,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
             output_format: OutputFormat::Table,
-            sort_by: SortField::Name,
-            top: None,
-            min_padding: None,
-            no_color: true,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
             cache_line_size: 64,
-            pretty: false,
-            warn_false_sharing: false,
             include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
         };
+        run_check(&base).expect("padding violations ignored");
+        run_check(&CheckConfig { output_format: OutputFormat::Json, ..base }).expect("json");
+        run_check(&CheckConfig { output_format: OutputFormat::Sarif, ..base }).expect("sarif");
+        run_check(&CheckConfig { config_path: &expired, ..base })
+            .expect_err("expired entries no longer apply");
+        let err = run_check(&CheckConfig { config_path: &invalid, ..base }).unwrap_err();
+        assert!(err.to_string().contains("not a YYYY-MM-DD date"));
+        for config in [ignored, expired, invalid] {
+            std::fs::remove_file(config).ok();
+        }
+    }
 
-        run_inspect(&cfg).expect("inspect no matches");
+    #[test]
+    fn run_check_lockfile_ratchets_sizes() {
+        let (old_path, new_path) =
+            match (find_fixture_path("test_simple"), find_fixture_path("test_modified")) {
+                (Some(o), Some(n)) => (o, n),
+                _ => return,
+            };
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let lockfile = dir.path().join(DEFAULT_LOCKFILE);
+        let missing_config = dir.path().join("missing.yaml");
+        let accept = CheckConfig {
+            binary_paths: std::slice::from_ref(&old_path),
+            config_path: &missing_config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: &lockfile,
+            accept: true,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        // Without a lockfile or config there is nothing to check against.
+        let no_lock = dir.path().join("absent.lock");
+        assert!(
+            run_check(&CheckConfig { accept: false, lockfile_path: &no_lock, ..accept }).is_err()
+        );
+
+        run_check(&accept).expect("accept sizes");
+        let lock = SizeLock::load(&lockfile).expect("read lock").expect("lock written");
+        assert!(lock.locked_size("NoPadding").is_some());
+
+        let verify = CheckConfig { accept: false, ..accept };
+        run_check(&verify).expect("unchanged binary passes");
+        // test_modified grows NoPadding.
+        let grown = CheckConfig { binary_paths: std::slice::from_ref(&new_path), ..verify };
+        assert!(run_check(&grown).is_err());
+        assert!(run_check(&CheckConfig { output_format: OutputFormat::Sarif, ..grown }).is_err());
+
+        run_check(&CheckConfig { binary_paths: std::slice::from_ref(&new_path), ..accept })
+            .expect("accept growth");
+        run_check(&grown).expect("accepted growth passes");
     }
 
     #[test]
-    fn run_inspect_min_padding_filters_all() {
-        let path = match find_fixture_path("test_simple") {
-            Some(p) => p,
-            None => return,
+    fn run_check_new_struct_defaults_need_previous_binary() {
+        let (old_path, new_path) =
+            match (find_fixture_path("test_simple"), find_fixture_path("test_modified")) {
+                (Some(o), Some(n)) => (o, n),
+                _ => return,
+            };
+
+        // NewStruct (32 bytes) only exists in test_modified.
+        let config = create_temp_config(
+            r#"
+budgets:
+  NoPadding:
+    max_size: 64
+new_struct_defaults:
+  max_size: 16
+"#,
+        );
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&new_path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: Some(&old_path),
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
         };
+        let err = run_check(&base).expect_err("NewStruct exceeds the defaults");
+        assert!(err.to_string().contains("1 violation"));
 
-        let cfg = InspectConfig {
-            binary_path: &path,
-            filter: None,
+        // Without an older binary nothing counts as new.
+        run_check(&CheckConfig { previous_path: None, ..base }).expect("policy skipped");
+        // Structs that existed before are exempt.
+        run_check(&CheckConfig { previous_path: Some(&new_path), ..base }).expect("nothing new");
+        std::fs::remove_file(&config).ok();
+    }
+
+    #[test]
+    fn run_check_stops_at_budget_time() {
+        let Some(path) = find_fixture_path("test_simple") else { return };
+        let config = create_temp_config("budgets:\n  NoPadding:\n    max_size: 1\n");
+        let lockfile = std::env::temp_dir().join("layout_audit_budget_time.lock");
+        std::fs::remove_file(&lockfile).ok();
+        let spent =
+            TimeBudget { deadline: Instant::now(), limit: Duration::ZERO, emit_partial: true };
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: &lockfile,
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Json,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            time_budget: Some(spent),
+            depfile: None,
+            atomic_patterns_file: None,
+            attestation: None,
+        };
+        let attestation = std::env::temp_dir().join("layout_audit_budget_time.intoto.json");
+        std::fs::remove_file(&attestation).ok();
+        let attested = CheckConfig {
+            attestation: AttestationConfig::from_args(Some(&attestation), None),
+            ..base
+        };
+        let err = run_check(&attested).expect_err("out of time");
+        assert!(err.is::<OutOfTime>());
+        assert!(!attestation.exists());
+
+        // Accepting a partial scan would drop the structs it never read.
+        let err = run_check(&CheckConfig { accept: true, ..base }).expect_err("out of time");
+        assert!(err.is::<OutOfTime>());
+        assert!(!lockfile.exists());
+
+        let ample = TimeBudget::from_args(Some(Duration::from_secs(600)), false);
+        let err = run_check(&CheckConfig { time_budget: ample, ..base }).expect_err("violation");
+        assert!(!err.is::<OutOfTime>());
+        std::fs::remove_file(&config).ok();
+    }
+
+    #[test]
+    fn run_check_rejects_changes_to_frozen_layouts() {
+        let (old_path, new_path) =
+            match (find_fixture_path("test_simple"), find_fixture_path("test_modified")) {
+                (Some(o), Some(n)) => (o, n),
+                _ => return,
+            };
+
+        // test_modified grows NoPadding; nothing else is budgeted.
+        let config = create_temp_config("frozen_layouts:\n  - \"No*\"\n");
+        let base = CheckConfig {
+            binary_paths: std::slice::from_ref(&new_path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: Some(&old_path),
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
             output_format: OutputFormat::Table,
-            sort_by: SortField::PaddingPct,
-            top: None,
-            min_padding: Some(10_000),
-            no_color: true,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
             cache_line_size: 64,
-            pretty: false,
-            warn_false_sharing: false,
             include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
         };
+        let err = run_check(&base).expect_err("NoPadding is frozen");
+        assert!(err.to_string().contains("1 violation"));
+        assert!(run_check(&CheckConfig { output_format: OutputFormat::Sarif, ..base }).is_err());
 
-        run_inspect(&cfg).expect("inspect min padding");
+        run_check(&CheckConfig { previous_path: Some(&new_path), ..base }).expect("unchanged");
+        run_check(&CheckConfig { previous_path: None, ..base }).expect("nothing to compare");
+        std::fs::remove_file(&config).ok();
     }
 
     #[test]
-    fn run_diff_with_changes_table() {
-        let old_path = match find_fixture_path("test_simple") {
-            Some(p) => p,
-            None => return,
+    fn run_diff_applies_new_struct_defaults() {
+        let (old_path, new_path) =
+            match (find_fixture_path("test_simple"), find_fixture_path("test_modified")) {
+                (Some(o), Some(n)) => (o, n),
+                _ => return,
+            };
+
+        let strict = create_temp_config("new_struct_defaults:\n  max_size: 16\n");
+        let lenient = create_temp_config("new_struct_defaults:\n  max_size: 64\n");
+        let advisory =
+            create_temp_config("new_struct_defaults:\n  max_size: 16\n  severity: warn\n");
+        for format in [OutputFormat::Table, OutputFormat::Json, OutputFormat::Sarif] {
+            let base = DiffConfig {
+                old: DiffBaseline::Binary(&old_path),
+                new_path: &new_path,
+                filter: Some("NewStruct"),
+                filter_regex: None,
+                member_filter: None,
+                output_format: format,
+                sarif_max_results: None,
+                cache_line_size: 64,
+                fail_on_regression: false,
+                fail_on: Severity::Error,
+                profile: None,
+                global_false_sharing: false,
+                include_go_runtime: false,
+                exported_only: false,
+                sort_stable: false,
+                config_path: Some(&strict),
+                depfile: None,
+                atomic_patterns_file: None,
+            };
+            assert!(run_diff(&base).expect("diff strict"));
+            assert!(!run_diff(&DiffConfig { config_path: Some(&lenient), ..base }).expect("diff"));
+
+            // An advisory policy fails the diff only when --fail-on reaches its severity.
+            let advisory = DiffConfig { config_path: Some(&advisory), ..base };
+            assert!(!run_diff(&advisory).expect("diff advisory"));
+            assert!(run_diff(&DiffConfig { fail_on: Severity::Warn, ..advisory }).expect("diff"));
+        }
+        std::fs::remove_file(&strict).ok();
+        std::fs::remove_file(&lenient).ok();
+        std::fs::remove_file(&advisory).ok();
+    }
+
+    #[test]
+    fn run_diff_reports_globals_that_start_sharing_a_line() {
+        let (padded, packed) =
+            match (find_fixture_path("test_globals_padded"), find_fixture_path("test_globals")) {
+                (Some(p), Some(q)) => (p, q),
+                _ => return,
+            };
+
+        let base = DiffConfig {
+            old: DiffBaseline::Binary(&padded),
+            new_path: &packed,
+            filter: Some("Stats"),
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            cache_line_size: 64,
+            fail_on_regression: false,
+            fail_on: Severity::Error,
+            profile: None,
+            global_false_sharing: true,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
         };
-        let new_path = match find_fixture_path("test_modified") {
-            Some(p) => p,
-            None => return,
+        assert!(run_diff(&base).expect("packed globals regress"));
+        let json = DiffConfig { output_format: OutputFormat::Json, ..base };
+        assert!(run_diff(&json).expect("json"));
+        assert!(!run_diff(&DiffConfig { old: DiffBaseline::Binary(&packed), ..base }).unwrap());
+        assert!(!run_diff(&DiffConfig { global_false_sharing: false, ..base }).unwrap());
+
+        let packed_globals = || -> Vec<GlobalFalseSharingWarning> {
+            let binary = BinaryData::load(&packed).unwrap();
+            let loaded = binary.load_dwarf().unwrap();
+            find_global_false_sharing(&binary, &DwarfContext::new(&loaded), 64).unwrap()
         };
+        let warnings = packed_globals();
+        let names: std::collections::HashSet<&str> =
+            warnings.iter().flat_map(|w| [w.first.name.as_str(), w.second.name.as_str()]).collect();
+        // Plain data and thread-locals are not thread-shared.
+        let expected = ["errors", "requests", "shutdown_flag", "stats"];
+        assert_eq!(names, expected.into_iter().collect());
+        assert_eq!(warnings.len(), 6);
+    }
+
+    #[test]
+    fn run_diff_against_snapshot_baseline() {
+        let (old_path, new_path) =
+            match (find_fixture_path("test_simple"), find_fixture_path("test_modified")) {
+                (Some(o), Some(n)) => (o, n),
+                _ => return,
+            };
+
+        let dir = tempfile::tempdir().unwrap();
+        let snapshot = dir.path().join("baseline.json");
+        run_snapshot(&SnapshotConfig {
+            binary_path: &old_path,
+            output: Some(&snapshot),
+            filter: None,
+            include_go_runtime: false,
+            exported_only: false,
+            config_path: None,
+            pretty: true,
+            depfile: None,
+        })
+        .expect("snapshot");
 
-        run_diff(&old_path, &new_path, None, OutputFormat::Table, 64, false, false)
-            .expect("diff table changes");
+        let base = DiffConfig {
+            old: DiffBaseline::Snapshot(&snapshot),
+            new_path: &new_path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Json,
+            sarif_max_results: None,
+            cache_line_size: 64,
+            fail_on_regression: false,
+            fail_on: Severity::Error,
+            profile: None,
+            global_false_sharing: false,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
+        };
+        // NoPadding grows between the fixtures; the snapshot stands in for the old binary.
+        assert!(run_diff(&base).expect("diff against snapshot"));
+        assert!(!run_diff(&DiffConfig { new_path: &old_path, ..base }).expect("unchanged"));
+        assert!(!run_diff(&DiffConfig { filter: Some("Outer"), ..base }).expect("filtered"));
     }
 
     #[test]
@@ -996,7 +7153,29 @@ budgets:
         };
 
         let missing = Path::new("tests/fixtures/does-not-exist.yaml");
-        let result = run_check(&path, missing, OutputFormat::Table, 64, false);
+        let result = run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: missing,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        });
         assert!(result.is_err());
     }
 
@@ -1010,10 +7189,25 @@ budgets:
                     max_padding: None,
                     max_padding_percent: None,
                     max_false_sharing_warnings: None,
+                    max_cache_lines: None,
+                    max_over_fetch: None,
+                    min_size: None,
+                    exact_size: None,
+                    max_alloc_slack: None,
+                    max_stride_factor: None,
+                    max_members: None,
+                    max_single_member_size: None,
+                    members: Default::default(),
+                    member_offsets: Default::default(),
+                    forbid_members_of_type: Vec::new(),
+                    forbidden_types: None,
+                    applies_to: AppliesTo::All,
+                    severity: Severity::Error,
                 },
             )]
             .into_iter()
             .collect(),
+            ..Default::default()
         };
 
         assert!(cfg.compile().is_err());
@@ -1026,10 +7220,25 @@ budgets:
                     max_padding: None,
                     max_padding_percent: None,
                     max_false_sharing_warnings: None,
+                    max_cache_lines: None,
+                    max_over_fetch: None,
+                    min_size: None,
+                    exact_size: None,
+                    max_alloc_slack: None,
+                    max_stride_factor: None,
+                    max_members: None,
+                    max_single_member_size: None,
+                    members: Default::default(),
+                    member_offsets: Default::default(),
+                    forbid_members_of_type: Vec::new(),
+                    forbidden_types: None,
+                    applies_to: AppliesTo::All,
+                    severity: Severity::Error,
                 },
             )]
             .into_iter()
             .collect(),
+            ..Default::default()
         };
 
         assert!(cfg.compile().is_err());
@@ -1042,6 +7251,20 @@ budgets:
             max_padding: None,
             max_padding_percent: Some(200.0),
             max_false_sharing_warnings: None,
+            max_cache_lines: None,
+            max_over_fetch: None,
+            min_size: None,
+            exact_size: None,
+            max_alloc_slack: None,
+            max_stride_factor: None,
+            max_members: None,
+            max_single_member_size: None,
+            members: Default::default(),
+            member_offsets: Default::default(),
+            forbid_members_of_type: Vec::new(),
+            forbidden_types: None,
+            applies_to: AppliesTo::All,
+            severity: Severity::Error,
         };
         assert!(budget.validate("X").is_err());
     }
@@ -1063,10 +7286,153 @@ budgets:
 "#,
         );
 
-        run_check(&path, &config, OutputFormat::Table, 64, false).expect("check warnings");
+        run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        })
+        .expect("check warnings");
+        std::fs::remove_file(&config).ok();
+    }
+
+    #[test]
+    fn lint_budget_names_reports_ambiguity() {
+        let config = Config::parse(
+            r#"
+budgets:
+  Order:
+    max_size: 64
+  "net::*":
+    max_size: 128
+  "{net,io}::Conn":
+    max_size: 32
+  "net::Conn*":
+    max_size: 16
+  "Ord*":
+    max_size: 8
+  "Rx*":
+    max_size: 8
+"#,
+            "test",
+        )
+        .unwrap();
+
+        let lints: Vec<String> =
+            lint_budget_names(&config).unwrap().iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lints,
+            vec![
+                "'{net,io}::Conn' overlaps earlier pattern 'net::*' (both match 'net::Conn'); \
+                 'net::*' wins",
+                "'net::Conn*' never applies: every name it matches is taken by 'net::*', \
+                 '{net,io}::Conn'",
+                "'Order' is also matched by 'Ord*'; the exact budget wins",
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_config_locates_typos_and_bad_values() {
+        let text = "budgets:\n  Foo:\n    max_paddding: 8\n  \"Bar[\":\n    max_size: 8\n  \
+                    Baz:\n    max_padding_percent: 150\nanalyse:\n  include: [\"*\"]\n";
+        let issues: Vec<String> =
+            validate_config(text, None).iter().map(|issue| issue.render("cfg")).collect();
+        assert_eq!(issues.len(), 4, "{:?}", issues);
+        assert_eq!(
+            issues[0],
+            "cfg:3:5: budgets.Foo.max_paddding: unknown key 'max_paddding' (did you mean \
+             'max_padding'?)"
+        );
+        assert!(issues[1].starts_with("cfg:4:3: budgets.Bar[: Invalid glob pattern"));
+        assert!(issues[2].starts_with("cfg:6:3: budgets.Baz: Invalid budget for 'Baz'"));
+        assert!(issues[3].starts_with("cfg:8:1: analyse: unknown key 'analyse'"));
+
+        let parse = validate_config("budgets:\n  Foo:\n    max_size: lots\n", None);
+        assert_eq!(
+            parse[0].render("cfg"),
+            "cfg:3:15: budgets.Foo.max_size: invalid type: string \"lots\", expected u64"
+        );
+        assert!(validate_config("budgets:\n  Foo:\n    max_size: 8\n", None).is_empty());
+    }
+
+    #[test]
+    fn run_check_rejects_invalid_type_sizes() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let config = create_temp_config(
+            "type_sizes:\n  Impl: { size: 24, align: 16 }\nbudgets:\n  Simple:\n    max_size: 64\n",
+        );
+        let err = run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("size 24 is not a multiple of align 16"));
         std::fs::remove_file(&config).ok();
     }
 
+    #[test]
+    fn check_config_prefers_environment_over_file() {
+        let file = create_temp_config("budgets:\n  FromFile:\n    max_size: 8\n");
+        let env = Some("budgets:\n  FromEnv:\n    max_size: 16\n".to_string());
+
+        let (config, is_file) = load_check_config(&file, env.clone()).unwrap().unwrap();
+        assert!(!is_file);
+        assert!(config.budgets.contains_key("FromEnv"));
+        let (config, is_file) = load_check_config(&file, Some(" \n".to_string())).unwrap().unwrap();
+        assert!(is_file);
+        assert!(config.budgets.contains_key("FromFile"));
+
+        let missing = Path::new("/nonexistent/.layout-audit.yaml");
+        assert!(load_check_config(missing, None).unwrap().is_none());
+        assert!(load_check_config(missing, env).unwrap().is_some());
+        let Err(err) = load_check_config(missing, Some("budgets: [".to_string())) else {
+            panic!("invalid YAML in the environment should fail");
+        };
+        assert!(err.to_string().contains(BUDGETS_ENV));
+        std::fs::remove_file(&file).ok();
+    }
+
     #[test]
     fn run_check_empty_budgets() {
         let path = match find_fixture_path("test_simple") {
@@ -1075,7 +7441,30 @@ budgets:
         };
 
         let config = create_temp_config("budgets: {}");
-        run_check(&path, &config, OutputFormat::Table, 64, false).expect("check empty budgets");
+        run_check(&CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        })
+        .expect("check empty budgets");
         std::fs::remove_file(&config).ok();
     }
 
@@ -1086,8 +7475,32 @@ budgets:
             None => return,
         };
 
-        run_suggest(&path, None, OutputFormat::Table, None, 64, true, 8, true, true, false)
-            .expect("suggest sorted");
+        run_suggest(&SuggestConfig {
+            binary_path: &path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            min_savings: None,
+            cache_line_size: 64,
+            pretty: true,
+            max_align: 8,
+            sort_by_savings: true,
+            strategy: OptimizeStrategy::Greedy,
+            static_access: false,
+            usage: false,
+            locality: false,
+            size_cap: None,
+            no_color: true,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            emit_code: None,
+            depfile: None,
+        })
+        .expect("suggest sorted");
     }
 
     #[test]
@@ -1097,21 +7510,71 @@ budgets:
             None => return,
         };
 
-        run_suggest(
-            &path,
-            None,
-            OutputFormat::Table,
-            Some(10_000),
-            64,
-            true,
-            8,
-            false,
-            true,
-            false,
-        )
+        run_suggest(&SuggestConfig {
+            binary_path: &path,
+            filter: None,
+            filter_regex: None,
+            member_filter: None,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            min_savings: Some(10_000),
+            cache_line_size: 64,
+            pretty: true,
+            max_align: 8,
+            sort_by_savings: false,
+            strategy: OptimizeStrategy::Greedy,
+            static_access: false,
+            usage: false,
+            locality: false,
+            size_cap: None,
+            no_color: true,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            config_path: None,
+            emit_code: None,
+            depfile: None,
+        })
         .expect("suggest no savings");
     }
 
+    #[test]
+    fn run_suggest_strategies() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        for strategy in OptimizeStrategy::ALL {
+            run_suggest(&SuggestConfig {
+                binary_path: &path,
+                filter: None,
+                filter_regex: None,
+                member_filter: None,
+                output_format: OutputFormat::Json,
+                sarif_max_results: None,
+                min_savings: None,
+                cache_line_size: 64,
+                pretty: false,
+                max_align: 8,
+                sort_by_savings: false,
+                strategy,
+                static_access: false,
+                usage: false,
+                locality: false,
+                size_cap: None,
+                no_color: true,
+                include_go_runtime: false,
+                exported_only: false,
+                sort_stable: false,
+                config_path: None,
+                emit_code: None,
+                depfile: None,
+            })
+            .expect("suggest strategy");
+        }
+    }
+
     #[test]
     fn run_inspect_sort_variants() {
         let path = match find_fixture_path("test_simple") {
@@ -1120,22 +7583,80 @@ budgets:
         };
 
         let cfg = InspectConfig {
-            binary_path: &path,
+            binary_paths: std::slice::from_ref(&path),
             filter: Some("Padding"),
+            filter_regex: None,
+            member_filter: None,
             output_format: OutputFormat::Table,
+            csv_members: false,
+            sarif_max_results: None,
             sort_by: SortField::Size,
             top: None,
             min_padding: None,
             no_color: true,
+            no_pager: false,
             cache_line_size: 64,
             pretty: false,
+            json_style: JsonStyle::Compact,
             warn_false_sharing: false,
+            global_false_sharing: false,
+            fail_on_partial: false,
+            include_partial: false,
+            cache_heatmap: false,
+            by_file: false,
+            by_namespace: false,
+            namespace_depth: None,
+            collapse_templates: false,
+            summary: false,
+            codeowners: None,
+            layout_map: false,
+            summary_only: false,
+            columns: None,
+            flatten_bases: false,
+            bits: false,
+            page_size: None,
+            allocator: None,
+            static_access: false,
+            usage: false,
+            static_instances: false,
+            unit_timeout: None,
+            time_budget: None,
+            stats: false,
             include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            export_sqlite: None,
+            repro_bundle: None,
+            redact_strings: false,
+            config_path: None,
+            depfile: None,
+            atomic_patterns_file: None,
         };
         run_inspect(&cfg).expect("inspect size sort");
 
-        let cfg = InspectConfig { sort_by: SortField::Padding, ..cfg };
+        let cfg = InspectConfig {
+            sort_by: SortField::Padding,
+            layout_map: true,
+            summary_only: false,
+            columns: None,
+            flatten_bases: false,
+            bits: false,
+            page_size: Some(2),
+            ..cfg
+        };
         run_inspect(&cfg).expect("inspect padding sort");
+
+        let cfg = InspectConfig { sort_by: SortField::FinalLine, cache_heatmap: true, ..cfg };
+        run_inspect(&cfg).expect("inspect final-line sort with heatmap");
+        let cfg = InspectConfig { output_format: OutputFormat::Json, ..cfg };
+        run_inspect(&cfg).expect("inspect json heatmap");
+
+        let cfg = InspectConfig { allocator: Some(AllocatorModel::Glibc), ..cfg };
+        run_inspect(&cfg).expect("inspect json with allocation slack");
+
+        let timeout = Some(std::time::Duration::from_nanos(1));
+        let cfg = InspectConfig { unit_timeout: timeout, stats: true, ..cfg };
+        run_inspect(&cfg).expect("inspect with every unit skipped");
     }
 
     #[test]
@@ -1152,6 +7673,20 @@ budgets:
                         max_padding: None,
                         max_padding_percent: None,
                         max_false_sharing_warnings: None,
+                        max_cache_lines: None,
+                        max_over_fetch: None,
+                        min_size: None,
+                        exact_size: None,
+                        max_alloc_slack: None,
+                        max_stride_factor: None,
+                        max_members: None,
+                        max_single_member_size: None,
+                        members: Default::default(),
+                        member_offsets: Default::default(),
+                        forbid_members_of_type: Vec::new(),
+                        forbidden_types: None,
+                        applies_to: AppliesTo::All,
+                        severity: Severity::Error,
                     },
                 ),
                 (
@@ -1161,11 +7696,26 @@ budgets:
                         max_padding: None,
                         max_padding_percent: None,
                         max_false_sharing_warnings: None,
+                        max_cache_lines: None,
+                        max_over_fetch: None,
+                        min_size: None,
+                        exact_size: None,
+                        max_alloc_slack: None,
+                        max_stride_factor: None,
+                        max_members: None,
+                        max_single_member_size: None,
+                        members: Default::default(),
+                        member_offsets: Default::default(),
+                        forbid_members_of_type: Vec::new(),
+                        forbidden_types: None,
+                        applies_to: AppliesTo::All,
+                        severity: Severity::Error,
                     },
                 ),
             ]
             .into_iter()
             .collect(),
+            ..Default::default()
         };
 
         let compiled = cfg.compile().expect("compile budgets");
@@ -1173,6 +7723,137 @@ budgets:
         assert!(exact.is_some());
         let glob = compiled.find_budget("GlobName");
         assert!(glob.is_some());
+        assert!(compiled.find_budget("main.Exact").is_none());
+
+        let canonical = cfg.compile().and_then(CompiledBudgets::with_canonical_matching);
+        let canonical = canonical.expect("canonical budgets");
+        assert_eq!(canonical.exact_key("main.Exact"), Some("Exact"));
+        assert_eq!(canonical.exact_key("my_app::Exact"), Some("Exact"));
+        assert!(canonical.find_budget("pkg.GlobName").is_none());
+    }
+
+    #[test]
+    fn canonical_budget_names_must_be_unique() {
+        let budget = Budget {
+            max_size: Some(1),
+            max_padding: None,
+            max_padding_percent: None,
+            max_false_sharing_warnings: None,
+            max_cache_lines: None,
+            max_over_fetch: None,
+            min_size: None,
+            exact_size: None,
+            max_alloc_slack: None,
+            max_stride_factor: None,
+            max_members: None,
+            max_single_member_size: None,
+            members: Default::default(),
+            member_offsets: Default::default(),
+            forbid_members_of_type: Vec::new(),
+            forbidden_types: None,
+            applies_to: AppliesTo::All,
+            severity: Severity::Error,
+        };
+        let cfg = Config {
+            budgets: [("a::Order".to_string(), budget.clone()), ("main.Order".to_string(), budget)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        assert!(cfg.compile().expect("exact budgets").with_canonical_matching().is_err());
+    }
+
+    #[test]
+    fn run_check_canonical_budget_match() {
+        let path = match find_fixture_path("test_simple") {
+            Some(p) => p,
+            None => return,
+        };
+
+        let config = create_temp_config("budgets:\n  my_app::WithArray:\n    max_size: 8\n");
+        let exact = CheckConfig {
+            binary_paths: std::slice::from_ref(&path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: Path::new(DEFAULT_LOCKFILE),
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        // Exact matching only warns that the budget names no struct (WithArray is a near miss).
+        run_check(&exact).expect("qualified budget ignored by exact matching");
+        let canonical = CheckConfig { budget_match: BudgetMatch::Canonical, ..exact };
+        assert!(run_check(&canonical).is_err());
+        std::fs::remove_file(&config).ok();
+    }
+
+    #[test]
+    fn report_profiles_preset_inspect_settings() {
+        let exec = InspectProfile::new(Some(ReportProfile::Exec));
+        assert!(exec.summary_only && exec.sort_by == SortField::Padding);
+        assert_eq!(exec.top, Some(EXEC_TOP_OFFENDERS));
+        let ci = InspectProfile::new(Some(ReportProfile::Ci));
+        assert!(ci.no_color && ci.fail_on_partial && !ci.summary_only);
+        assert_eq!(ci.min_padding, Some(1));
+        assert!(InspectProfile::new(Some(ReportProfile::Dev)).layout_map);
+        assert!(!InspectProfile::new(None).layout_map);
+    }
+
+    #[test]
+    fn profiled_diff_narrows_the_table() {
+        colored::control::set_override(false);
+        let change =
+            |name: &str, size_delta: i64, padding_delta: i64| layout_audit::diff::StructChange {
+                name: name.to_string(),
+                old_size: 16,
+                new_size: (16 + size_delta) as u64,
+                size_delta,
+                old_padding: 4,
+                new_padding: (4 + padding_delta) as u64,
+                padding_delta,
+                member_changes: Vec::new(),
+                source_location: None,
+                old_source_location: None,
+            };
+        let diff = layout_audit::DiffResult {
+            added: vec![layout_audit::diff::StructSummary {
+                name: "Added".to_string(),
+                size: 8,
+                padding_bytes: 0,
+                source_location: None,
+            }],
+            removed: Vec::new(),
+            changed: vec![change("Shrunk", -8, -4), change("Grown", 8, 0), change("Padded", 0, 2)],
+            unchanged_count: 3,
+        };
+
+        let ci = format_profiled_diff(&diff, ReportProfile::Ci);
+        assert!(ci.contains("~ Grown") && ci.contains("~ Padded"), "{}", ci);
+        assert!(!ci.contains("Shrunk") && !ci.contains("Added"), "{}", ci);
+        assert!(ci.contains("2 regression(s) among 3 changed struct(s)"));
+
+        let exec = format_profiled_diff(&diff, ReportProfile::Exec);
+        assert!(
+            exec.contains("Largest growth (2 of 2):\n  ~ Grown (size +8, padding +0)"),
+            "{}",
+            exec
+        );
+        assert!(exec.contains("net size +8 bytes, net padding -2 bytes"), "{}", exec);
+        assert_eq!(format_profiled_diff(&diff, ReportProfile::Dev), format_diff_table(&diff));
     }
 
     #[test]
@@ -1184,42 +7865,131 @@ budgets:
 
         let inspect = Cli {
             command: Commands::Inspect {
-                binary: path.clone(),
+                binaries: vec![path.clone()],
                 filter: Some("Padding".to_string()),
+                filter_regex: None,
+                member_filter: None,
                 output: OutputFormat::Table,
-                sort_by: SortField::Name,
+                csv_members: false,
+                sarif_max_results: None,
+                report_profile: None,
+                sort_by: Some(SortField::Name),
                 top: Some(1),
                 min_padding: None,
                 no_color: true,
+                no_pager: false,
                 cache_line: 64,
                 pretty: false,
+                json_style: JsonStyle::Compact,
                 warn_false_sharing: false,
+                global_false_sharing: false,
+                fail_on_partial: false,
+                include_partial: false,
+                cache_heatmap: false,
+                by_file: false,
+                by_namespace: false,
+                namespace_depth: None,
+                collapse_templates: false,
+                summary: false,
+                codeowners: None,
+                layout_map: false,
+                columns: None,
+                flatten_bases: false,
+                bits: false,
+                page_size: None,
+                allocator: None,
+                static_access: false,
+                usage: false,
+                static_instances: false,
+                unit_timeout: None,
+                budget_time: None,
+                emit_partial_results: false,
+                stats: false,
                 include_go_runtime: false,
+                exported_only: false,
+                sort_stable: false,
+                export_sqlite: None,
+                repro_bundle: None,
+                redact_strings: false,
+                config: None,
+                emit_depfile: None,
+                depfile_target: None,
+                atomic_patterns_file: None,
             },
         };
         run_cli(inspect).expect("cli inspect");
 
         let diff = Cli {
             command: Commands::Diff {
-                old: path.clone(),
-                new: path.clone(),
+                binaries: vec![path.clone(), path.clone()],
+                baseline: None,
                 filter: None,
+                filter_regex: None,
+                member_filter: None,
                 output: OutputFormat::Json,
+                sarif_max_results: None,
                 cache_line: 64,
                 fail_on_regression: false,
+                fail_on: None,
+                report_profile: None,
+                global_false_sharing: false,
                 include_go_runtime: false,
+                exported_only: false,
+                sort_stable: false,
+                config: None,
+                emit_depfile: None,
+                depfile_target: None,
+                atomic_patterns_file: None,
             },
         };
         run_cli(diff).expect("cli diff");
 
+        let compare = Cli {
+            command: Commands::CompareBuilds {
+                debug: path.clone(),
+                release: path.clone(),
+                filter: None,
+                output: OutputFormat::Json,
+                sarif_max_results: None,
+                cache_line: 64,
+                fail_on_unexplained: true,
+                include_go_runtime: false,
+                exported_only: false,
+                sort_stable: false,
+                config: None,
+                emit_depfile: None,
+                depfile_target: None,
+            },
+        };
+        run_cli(compare).expect("cli compare-builds");
+
         let config = create_temp_config("budgets: {}");
         let check = Cli {
             command: Commands::Check {
-                binary: path.clone(),
+                binaries: vec![path.clone()],
                 config: config.clone(),
+                profile: None,
+                previous: None,
+                list_suppressions: false,
+                lint_config: false,
+                validate_config_only: false,
+                lockfile: PathBuf::from(DEFAULT_LOCKFILE),
+                accept: false,
+                budget_match: BudgetMatch::Exact,
+                fail_on: Severity::Error,
                 output: OutputFormat::Table,
+                sarif_max_results: None,
                 cache_line: 64,
                 include_go_runtime: false,
+                exported_only: false,
+                sort_stable: false,
+                emit_depfile: None,
+                depfile_target: None,
+                atomic_patterns_file: None,
+                budget_time: None,
+                emit_partial_results: false,
+                attestation: None,
+                attestation_sign: None,
             },
         };
         run_cli(check).expect("cli check");
@@ -1229,16 +7999,132 @@ budgets:
             command: Commands::Suggest {
                 binary: path,
                 filter: None,
+                filter_regex: None,
+                member_filter: None,
                 output: OutputFormat::Json,
+                sarif_max_results: None,
                 min_savings: None,
                 cache_line: 64,
                 pretty: false,
                 max_align: 8,
                 sort_by_savings: false,
+                strategy: OptimizeStrategy::Greedy,
+                static_access: false,
+                usage: false,
+                locality: false,
+                size_cap: None,
+                config: None,
+                emit_code: None,
                 no_color: true,
                 include_go_runtime: false,
+                exported_only: false,
+                sort_stable: false,
+                emit_depfile: None,
+                depfile_target: None,
             },
         };
         run_cli(suggest).expect("cli suggest");
     }
+
+    #[test]
+    fn watch_candidates_resolve_target_dirs() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        assert!(watch_candidates(dir.path(), None).is_err());
+        let file = dir.path().join("app");
+        assert_eq!(watch_candidates(&file, None).unwrap(), vec![file.clone()]);
+        assert!(watch_candidates(&file, Some("app")).is_err());
+
+        let candidates = watch_candidates(dir.path(), Some("app")).unwrap();
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(newest_binary(&candidates), None);
+        std::fs::write(&file, b"").expect("write binary");
+        assert_eq!(newest_binary(&candidates), Some(file.as_path()));
+    }
+
+    #[test]
+    fn watch_reports_only_changes_since_last_run() {
+        let (old_path, new_path) =
+            match (find_fixture_path("test_simple"), find_fixture_path("test_modified")) {
+                (Some(o), Some(n)) => (o, n),
+                _ => return,
+            };
+
+        let inspect = WatchConfig {
+            path: &old_path,
+            bin: None,
+            action: WatchAction::Inspect,
+            filter: None,
+            config_path: None,
+            debounce: Duration::ZERO,
+            cache_line_size: 64,
+            include_go_runtime: false,
+        };
+        let mut state = WatchState::default();
+        assert!(state.rerun(&old_path, &inspect).unwrap().contains("Baseline:"));
+        let changed = state.rerun(&new_path, &inspect).unwrap();
+        assert!(changed.contains("NewStruct"));
+        assert!(changed.contains("NoPadding"));
+        assert!(!changed.contains("TailPadding"));
+        assert!(state.rerun(&new_path, &inspect).unwrap().contains("No changes detected."));
+
+        // test_modified grows NoPadding from 12 to 16 bytes.
+        let config = create_temp_config("budgets:\n  NoPadding:\n    max_size: 12\n");
+        let check =
+            WatchConfig { action: WatchAction::Check, config_path: Some(&config), ..inspect };
+        let mut state = WatchState::default();
+        assert!(state.rerun(&old_path, &check).unwrap().contains("Baseline: 0 violation(s)"));
+        let grown = state.rerun(&new_path, &check).unwrap();
+        assert!(grown.contains("+ new: NoPadding"));
+        assert!(grown.contains("1 violation(s) remaining"));
+        assert!(state.rerun(&old_path, &check).unwrap().contains("- resolved: NoPadding"));
+        let _ = std::fs::remove_file(&config);
+    }
+
+    #[test]
+    fn run_check_expands_directories_of_binaries() {
+        let (old_path, new_path) =
+            match (find_fixture_path("test_simple"), find_fixture_path("test_modified")) {
+                (Some(o), Some(n)) => (o, n),
+                _ => return,
+            };
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::copy(&new_path, dir.path().join("modified")).expect("copy fixture");
+        std::fs::write(dir.path().join("notes.txt"), "not a binary").expect("write text");
+        let expanded = expand_binary_paths(&[old_path.clone(), dir.path().to_path_buf()]).unwrap();
+        assert_eq!(expanded, vec![old_path.clone(), dir.path().join("modified")]);
+        std::fs::create_dir(dir.path().join("nothing")).expect("mkdir");
+        assert!(expand_binary_paths(&[dir.path().join("nothing")]).is_err());
+
+        // NoPadding is 12 bytes in test_simple and 16 in test_modified.
+        let config = create_temp_config("budgets:\n  NoPadding:\n    max_size: 12\n");
+        let missing_lock = dir.path().join(DEFAULT_LOCKFILE);
+        let check = CheckConfig {
+            binary_paths: std::slice::from_ref(&old_path),
+            config_path: &config,
+            profile_path: None,
+            previous_path: None,
+            list_suppressions: false,
+            lint_config: false,
+            validate_config_only: false,
+            lockfile_path: &missing_lock,
+            accept: false,
+            budget_match: BudgetMatch::Exact,
+            output_format: OutputFormat::Table,
+            sarif_max_results: None,
+            fail_on: Severity::Error,
+            cache_line_size: 64,
+            include_go_runtime: false,
+            exported_only: false,
+            sort_stable: false,
+            depfile: None,
+            atomic_patterns_file: None,
+            time_budget: None,
+            attestation: None,
+        };
+        run_check(&check).expect("test_simple fits");
+        let both = [old_path.clone(), dir.path().to_path_buf()];
+        assert!(run_check(&CheckConfig { binary_paths: &both, ..check }).is_err());
+        let _ = std::fs::remove_file(&config);
+    }
 }
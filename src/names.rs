@@ -0,0 +1,236 @@
+//! Language-independent struct names, for matching budgets across C, C++, Rust, and Go.
+//!
+//! The canonical form of a name is its last path segment: `my_app::Order` (Rust, C++),
+//! `main.Order` and `github.com/acme/shop.Order` (Go), and plain `Order` (C) all become
+//! `Order`. Separators inside generic arguments are left alone, so `Vec<app::Order>` stays whole.
+
+/// Edit distance (between canonical names) still reported as a near miss.
+const NEAR_MISS_DISTANCE: usize = 2;
+
+/// The unqualified type name: everything after the last top-level `::`, `.`, or `/`.
+pub fn canonical_name(name: &str) -> &str {
+    let bytes = name.as_bytes();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'<' | b'[' | b'(' => depth += 1,
+            b'>' | b']' | b')' => depth = depth.saturating_sub(1),
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => {
+                start = i + 2;
+                i += 1;
+            }
+            b'.' | b'/' if depth == 0 => start = i + 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    // A trailing separator would leave nothing; keep the whole name instead.
+    if start >= name.len() { name } else { &name[start..] }
+}
+
+/// The qualifier [`canonical_name`] strips: a C++ namespace, Rust module path, or Go package
+/// (`my_app::net` for `my_app::net::Conn`, `github.com/acme/shop` for
+/// `github.com/acme/shop.Order`). `None` for unqualified names.
+pub fn namespace_of(name: &str) -> Option<&str> {
+    let unqualified = canonical_name(name);
+    if unqualified.len() == name.len() {
+        return None;
+    }
+    let qualifier = &name[..name.len() - unqualified.len()];
+    let qualifier = qualifier
+        .strip_suffix("::")
+        .or_else(|| qualifier.strip_suffix('.'))
+        .or_else(|| qualifier.strip_suffix('/'));
+    qualifier.filter(|q| !q.is_empty())
+}
+
+/// The first `depth` top-level segments of a namespace, split at `::` and `/` (`my_app` for
+/// `my_app::net::tcp` at depth 1).
+pub fn namespace_prefix(namespace: &str, depth: usize) -> &str {
+    let bytes = namespace.as_bytes();
+    let mut nesting = 0usize;
+    let mut segments = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let separator = match bytes[i] {
+            b'<' | b'[' | b'(' => {
+                nesting += 1;
+                0
+            }
+            b'>' | b']' | b')' => {
+                nesting = nesting.saturating_sub(1);
+                0
+            }
+            b':' if nesting == 0 && bytes.get(i + 1) == Some(&b':') => 2,
+            b'/' if nesting == 0 => 1,
+            _ => 0,
+        };
+        if separator > 0 {
+            segments += 1;
+            if segments >= depth.max(1) {
+                return &namespace[..i];
+            }
+            i += separator;
+        } else {
+            i += 1;
+        }
+    }
+    namespace
+}
+
+/// The template an instantiated name comes from: the name with its top-level `<...>` (C++,
+/// Rust) or `[...]` (Go) argument lists emptied, so `Vector<int>` and `Vector<Foo>` both give
+/// `Vector<>` and `Outer<int>::Inner` gives `Outer<>::Inner`. `None` for names without
+/// arguments.
+pub fn template_of(name: &str) -> Option<String> {
+    let mut template = String::with_capacity(name.len());
+    let mut depth = 0usize;
+    let mut generic = false;
+    for c in name.chars() {
+        match c {
+            '<' | '[' => {
+                if depth == 0 {
+                    template.push(c);
+                    generic = true;
+                }
+                depth += 1;
+            }
+            '>' | ']' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    template.push(c);
+                }
+            }
+            _ if depth == 0 => template.push(c),
+            _ => {}
+        }
+    }
+    generic.then_some(template)
+}
+
+/// The type a member stores inline: its type without qualifiers, or an array's element type.
+/// `None` for pointers and references, which embed nothing.
+pub fn embedded_type(type_name: &str) -> Option<&str> {
+    const QUALIFIERS: [&str; 4] = ["const ", "volatile ", "restrict ", "_Atomic "];
+    let mut name = type_name;
+    loop {
+        if let Some(rest) = QUALIFIERS.iter().find_map(|q| name.strip_prefix(q)) {
+            name = rest;
+        } else if let Some((element, _)) = name
+            .strip_prefix('[')
+            .and_then(|n| n.strip_suffix(']'))
+            .and_then(|n| n.rsplit_once("; "))
+        {
+            name = element;
+        } else {
+            break;
+        }
+    }
+    (!name.starts_with('*') && !name.starts_with('&')).then_some(name)
+}
+
+/// Names from `candidates` that look like a misspelling or differently qualified form of
+/// `wanted`: same canonical name ignoring case, or within a small edit distance of it.
+/// Exact matches are excluded. Results keep the order of `candidates`.
+pub fn near_misses<'a>(
+    wanted: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let target = canonical_name(wanted).to_ascii_lowercase();
+    candidates
+        .into_iter()
+        .filter(|c| *c != wanted)
+        .filter(|c| {
+            let canonical = canonical_name(c).to_ascii_lowercase();
+            canonical == target
+                || (target.len() > NEAR_MISS_DISTANCE
+                    && edit_distance(&canonical, &target) <= NEAR_MISS_DISTANCE)
+        })
+        .collect()
+}
+
+/// Levenshtein distance over bytes.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, &ca) in a.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_type_sees_through_qualifiers_and_arrays() {
+        assert_eq!(embedded_type("const [std::map<int, int>; 4]"), Some("std::map<int, int>"));
+        assert_eq!(embedded_type("_Atomic volatile Big"), Some("Big"));
+        assert_eq!(embedded_type("*Big"), None);
+        assert_eq!(embedded_type("const &Big"), None);
+        assert_eq!(embedded_type("[*Big; 2]"), None);
+    }
+
+    #[test]
+    fn canonical_name_strips_language_qualifiers() {
+        assert_eq!(canonical_name("my_app::Order"), "Order");
+        assert_eq!(canonical_name("main.Order"), "Order");
+        assert_eq!(canonical_name("github.com/acme/shop.Order"), "Order");
+        assert_eq!(canonical_name("Order"), "Order");
+        assert_eq!(canonical_name("std::vec::Vec<app::Order>"), "Vec<app::Order>");
+        assert_eq!(canonical_name("main.Pair[main.Key,int]"), "Pair[main.Key,int]");
+        assert_eq!(canonical_name("weird::"), "weird::");
+    }
+
+    #[test]
+    fn template_of_empties_argument_lists() {
+        assert_eq!(template_of("Vector<int>").as_deref(), Some("Vector<>"));
+        assert_eq!(template_of("ns::Map<std::pair<int, int>, Foo>").as_deref(), Some("ns::Map<>"));
+        assert_eq!(template_of("Outer<int>::Inner").as_deref(), Some("Outer<>::Inner"));
+        assert_eq!(template_of("main.Pair[main.Key,int]").as_deref(), Some("main.Pair[]"));
+        assert_eq!(template_of("(anonymous namespace)::Order"), None);
+        assert_eq!(template_of("Order"), None);
+    }
+
+    #[test]
+    fn namespaces_and_prefixes() {
+        assert_eq!(namespace_of("my_app::net::Conn"), Some("my_app::net"));
+        assert_eq!(namespace_of("github.com/acme/shop.Order"), Some("github.com/acme/shop"));
+        assert_eq!(namespace_of("main.Order"), Some("main"));
+        assert_eq!(namespace_of("std::vec::Vec<app::Order>"), Some("std::vec"));
+        assert_eq!(namespace_of("Order"), None);
+        assert_eq!(namespace_of("weird::"), None);
+
+        assert_eq!(namespace_prefix("my_app::net::tcp", 1), "my_app");
+        assert_eq!(namespace_prefix("my_app::net::tcp", 2), "my_app::net");
+        assert_eq!(namespace_prefix("my_app::net::tcp", 5), "my_app::net::tcp");
+        assert_eq!(namespace_prefix("github.com/acme/shop", 2), "github.com/acme");
+        assert_eq!(namespace_prefix("Outer<a::b>::Inner", 1), "Outer<a::b>");
+    }
+
+    #[test]
+    fn near_misses_cover_case_qualification_and_typos() {
+        let names = ["main.Order", "order", "Ordr", "Orders", "Customer", "my_app::Order"];
+        assert_eq!(
+            near_misses("my_app::Order", names),
+            vec!["main.Order", "order", "Ordr", "Orders"]
+        );
+        assert!(near_misses("Id", ["Ix", "Id2"]).is_empty());
+    }
+
+    #[test]
+    fn edit_distance_basics() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+}
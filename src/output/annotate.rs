@@ -0,0 +1,89 @@
+//! Output formatters for the annotate command.
+
+use crate::samples::FieldAccessReport;
+use colored::Colorize;
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+
+pub struct AnnotateTableFormatter {
+    no_color: bool,
+}
+
+impl AnnotateTableFormatter {
+    pub fn new(no_color: bool) -> Self {
+        Self { no_color }
+    }
+
+    pub fn format(&self, report: &FieldAccessReport) -> String {
+        let mut output = format!(
+            "{} sample(s), {} attributed to {} struct(s), {} unattributed\n",
+            report.samples,
+            report.samples - report.unattributed,
+            report.structs.len(),
+            report.unattributed
+        );
+
+        for access in &report.structs {
+            let header = format!("\nstruct {} ({} sample(s))", access.name, access.hits);
+            if self.no_color {
+                output.push_str(&header);
+            } else {
+                output.push_str(&header.bold().to_string());
+            }
+            output.push('\n');
+
+            let mut table = Table::new();
+            table.load_preset(UTF8_FULL_CONDENSED);
+            table.set_header(vec!["Offset", "Size", "Field", "Samples", "Share"]);
+            for member in &access.members {
+                let share = if access.hits > 0 {
+                    member.hits as f64 * 100.0 / access.hits as f64
+                } else {
+                    0.0
+                };
+                let mut row = vec![
+                    Cell::new(member.offset.to_string()),
+                    Cell::new(member.size.to_string()),
+                    Cell::new(&member.name),
+                    Cell::new(member.hits.to_string()),
+                    Cell::new(format!("{:.1}%", share)),
+                ];
+                if !self.no_color && member.hits > 0 {
+                    row = row.into_iter().map(|cell| cell.fg(Color::Red)).collect();
+                }
+                table.add_row(row);
+            }
+            output.push_str(&table.to_string());
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+#[derive(Serialize)]
+struct AnnotateJsonOutput<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    report: &'a FieldAccessReport,
+}
+
+pub struct AnnotateJsonFormatter {
+    pretty: bool,
+}
+
+impl AnnotateJsonFormatter {
+    pub fn new(pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    pub fn format(&self, report: &FieldAccessReport) -> String {
+        let output = AnnotateJsonOutput { version: env!("CARGO_PKG_VERSION"), report };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+        } else {
+            serde_json::to_string(&output)
+        }
+        .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
@@ -0,0 +1,426 @@
+//! Struct definitions in source form for suggested layouts.
+//!
+//! Member types are spelled from the debug info (`[char; 10]`, `*void`) and translated to the
+//! target language's declarator syntax. Primitives are mapped to the target's type of the same
+//! size, and C gets the `struct`, `union`, or `enum` tag of types no typedef names. Other named
+//! types are copied as they appear, so a C++ or Rust definition may need qualifying before it
+//! compiles.
+
+use crate::analysis::{OptimizedLayout, OptimizedMember};
+use crate::names::canonical_name;
+use crate::types::TypeTag;
+
+/// Primitives as `(size, [C, Rust, Go])`, assuming an LP64 target. A name is looked up with its
+/// size where known, which tells C's 4-byte `int` from Go's 8-byte one; fixed-width rows come
+/// first so they win when translating from Rust or Go.
+const PRIMITIVES: &[(u64, [&str; 3])] = &[
+    (1, ["int8_t", "i8", "int8"]),
+    (1, ["uint8_t", "u8", "uint8"]),
+    (2, ["int16_t", "i16", "int16"]),
+    (2, ["uint16_t", "u16", "uint16"]),
+    (4, ["int32_t", "i32", "int32"]),
+    (4, ["uint32_t", "u32", "uint32"]),
+    (8, ["int64_t", "i64", "int64"]),
+    (8, ["uint64_t", "u64", "uint64"]),
+    (4, ["float", "f32", "float32"]),
+    (8, ["double", "f64", "float64"]),
+    (1, ["_Bool", "bool", "bool"]),
+    (1, ["char", "core::ffi::c_char", "byte"]),
+    (1, ["signed char", "i8", "int8"]),
+    (1, ["unsigned char", "u8", "uint8"]),
+    (2, ["short", "i16", "int16"]),
+    (2, ["short int", "i16", "int16"]),
+    (2, ["unsigned short", "u16", "uint16"]),
+    (2, ["short unsigned int", "u16", "uint16"]),
+    (4, ["int", "i32", "int32"]),
+    (4, ["unsigned int", "u32", "uint32"]),
+    (8, ["long", "i64", "int64"]),
+    (8, ["long int", "i64", "int64"]),
+    (8, ["unsigned long", "u64", "uint64"]),
+    (8, ["long unsigned int", "u64", "uint64"]),
+    (8, ["long long", "i64", "int64"]),
+    (8, ["long long int", "i64", "int64"]),
+    (8, ["unsigned long long", "u64", "uint64"]),
+    (8, ["long long unsigned int", "u64", "uint64"]),
+    (8, ["intptr_t", "isize", "int"]),
+    (8, ["uintptr_t", "usize", "uintptr"]),
+    (8, ["size_t", "usize", "uintptr"]),
+];
+
+/// Language `suggest --emit-code` writes definitions in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CodeLanguage {
+    /// A `#[repr(C)]` struct
+    Rust,
+    /// A C struct
+    C,
+    /// A Go struct type
+    Go,
+}
+
+impl CodeLanguage {
+    fn column(self) -> usize {
+        match self {
+            CodeLanguage::C => 0,
+            CodeLanguage::Rust => 1,
+            CodeLanguage::Go => 2,
+        }
+    }
+
+    /// `name` in this language if it is a primitive of any of them, else `name` itself.
+    fn primitive(self, name: &str, size: Option<u64>) -> &str {
+        PRIMITIVES
+            .iter()
+            .find(|(row_size, row)| row.contains(&name) && size.is_none_or(|s| s == *row_size))
+            .map_or(name, |(_, row)| row[self.column()])
+    }
+}
+
+/// The C spelling of primitive `name` of any language, if it is one.
+pub(super) fn c_primitive(name: &str, size: Option<u64>) -> Option<&'static str> {
+    PRIMITIVES
+        .iter()
+        .find(|(row_size, row)| row.contains(&name) && size.is_none_or(|s| s == *row_size))
+        .map(|(_, row)| row[CodeLanguage::C.column()])
+}
+
+pub struct CodeFormatter {
+    language: CodeLanguage,
+}
+
+impl CodeFormatter {
+    pub fn new(language: CodeLanguage) -> Self {
+        Self { language }
+    }
+
+    /// One definition per suggestion, with members in the suggested order and padding spelled
+    /// out as fields.
+    pub fn format(&self, suggestions: &[OptimizedLayout]) -> String {
+        suggestions.iter().map(|s| self.format_struct(s)).collect::<Vec<_>>().join("\n")
+    }
+
+    fn format_struct(&self, s: &OptimizedLayout) -> String {
+        let name = canonical_name(&s.name);
+        let mut output = format!(
+            "// {}: {} bytes -> {} bytes (saves {} bytes)\n",
+            s.name, s.original_size, s.optimized_size, s.savings_bytes
+        );
+        if !s.skipped_members.is_empty() {
+            output.push_str(&format!(
+                "// Not placed (missing size/offset): {}\n",
+                s.skipped_members.join(", ")
+            ));
+        }
+
+        let natural_align = s.optimized_members.iter().map(|m| m.alignment).max().unwrap_or(1);
+        let over_aligned = (s.struct_alignment > natural_align).then_some(s.struct_alignment);
+        output.push_str(&match (self.language, over_aligned) {
+            (CodeLanguage::Rust, None) => format!("#[repr(C)]\npub struct {} {{\n", name),
+            (CodeLanguage::Rust, Some(align)) => {
+                format!("#[repr(C, align({}))]\npub struct {} {{\n", align, name)
+            }
+            (CodeLanguage::C, None) => format!("struct {} {{\n", name),
+            (CodeLanguage::C, Some(align)) => {
+                format!("struct __attribute__((aligned({}))) {} {{\n", align, name)
+            }
+            (CodeLanguage::Go, None) => format!("type {} struct {{\n", name),
+            (CodeLanguage::Go, Some(align)) => {
+                format!("// Go cannot request {}-byte alignment.\ntype {} struct {{\n", align, name)
+            }
+        });
+
+        let mut end = 0;
+        let mut pads = 0;
+        let mut bit_units = 0;
+        let mut members = s.optimized_members.iter().peekable();
+        while let Some(member) = members.next() {
+            if member.offset > end {
+                output.push_str(&self.padding_field(pads, member.offset - end));
+                pads += 1;
+            }
+            end = end.max(member.offset + member.size);
+
+            if member.bit_size.is_none() {
+                output.push_str(&self.field(member));
+                continue;
+            }
+            let mut group = vec![member];
+            while let Some(next) =
+                members.next_if(|m| m.bit_size.is_some() && m.offset == member.offset)
+            {
+                group.push(next);
+            }
+            output.push_str(&self.bitfields(bit_units, &group));
+            bit_units += 1;
+        }
+        if s.optimized_size > end {
+            output.push_str(&self.padding_field(pads, s.optimized_size - end));
+        }
+
+        output.push_str(match self.language {
+            CodeLanguage::C => "};\n",
+            CodeLanguage::Rust | CodeLanguage::Go => "}\n",
+        });
+        output
+    }
+
+    fn field(&self, member: &OptimizedMember) -> String {
+        let name = &member.name;
+        let ty = TypeExpr::parse(&member.type_name, Some(member.size));
+        match self.language {
+            CodeLanguage::Rust => format!("    pub {}: {},\n", name, ty.rust()),
+            CodeLanguage::C => format!("    {};\n", ty.c(name, member.type_tag)),
+            CodeLanguage::Go => format!("    {} {}\n", name, ty.go()),
+        }
+    }
+
+    fn padding_field(&self, index: usize, bytes: u64) -> String {
+        match self.language {
+            CodeLanguage::Rust => format!("    _pad{}: [u8; {}],\n", index, bytes),
+            CodeLanguage::C => format!("    char _pad{}[{}];\n", index, bytes),
+            CodeLanguage::Go => format!("    _ [{}]byte\n", bytes),
+        }
+    }
+
+    /// Bitfields sharing one storage unit. Rust and Go have no bitfields, so they get the unit
+    /// as raw bytes with the packed members listed above it.
+    fn bitfields(&self, index: usize, group: &[&OptimizedMember]) -> String {
+        if self.language == CodeLanguage::C {
+            return group
+                .iter()
+                .map(|m| {
+                    let ty = TypeExpr::Named(&m.type_name, Some(m.size)).c(&m.name, m.type_tag);
+                    format!("    {} : {};\n", ty, m.bit_size.unwrap_or(0))
+                })
+                .collect();
+        }
+        let packed: Vec<String> =
+            group.iter().map(|m| format!("{} : {}", m.name, m.bit_size.unwrap_or(0))).collect();
+        let size = group[0].size;
+        let field = match self.language {
+            CodeLanguage::Rust => format!("    pub bits{}: [u8; {}],\n", index, size),
+            _ => format!("    bits{} [{}]byte\n", index, size),
+        };
+        format!("    // bitfields: {}\n{}", packed.join(", "), field)
+    }
+}
+
+/// A member type as the debug info spells it.
+#[derive(Debug, PartialEq)]
+pub(super) enum TypeExpr<'a> {
+    /// A type name, with its size when known.
+    Named(&'a str, Option<u64>),
+    Pointer(Box<TypeExpr<'a>>),
+    Array(Box<TypeExpr<'a>>, &'a str),
+    Function,
+}
+
+impl<'a> TypeExpr<'a> {
+    pub(super) fn parse(type_name: &'a str, size: Option<u64>) -> Self {
+        let type_name = type_name.trim();
+        if let Some(pointee) = type_name.strip_prefix('*') {
+            return TypeExpr::Pointer(Box::new(TypeExpr::parse(pointee, None)));
+        }
+        if let Some((element, count)) = type_name
+            .strip_prefix('[')
+            .and_then(|t| t.strip_suffix(']'))
+            .and_then(|t| t.rsplit_once("; "))
+        {
+            let element_size = count
+                .parse::<u64>()
+                .ok()
+                .filter(|&n| n > 0)
+                .and_then(|n| size.map(|size| size / n));
+            return TypeExpr::Array(Box::new(TypeExpr::parse(element, element_size)), count);
+        }
+        if type_name.starts_with("fn(") {
+            return TypeExpr::Function;
+        }
+        TypeExpr::Named(type_name, size)
+    }
+
+    fn is_void(&self) -> bool {
+        matches!(self, TypeExpr::Named("void" | "()", _))
+    }
+
+    fn rust(&self) -> String {
+        match self {
+            TypeExpr::Named("void", _) => "core::ffi::c_void".to_string(),
+            TypeExpr::Named(name, size) => CodeLanguage::Rust.primitive(name, *size).to_string(),
+            // Rust function pointers are already pointers.
+            TypeExpr::Pointer(pointee) if **pointee == TypeExpr::Function => pointee.rust(),
+            TypeExpr::Pointer(pointee) => format!("*mut {}", pointee.rust()),
+            TypeExpr::Array(element, count) => format!("[{}; {}]", element.rust(), count),
+            TypeExpr::Function => "Option<unsafe extern \"C\" fn()>".to_string(),
+        }
+    }
+
+    /// C declaration of `declarator`; `tag` is the tag of the named type at the core.
+    fn c(&self, declarator: &str, tag: Option<TypeTag>) -> String {
+        match self {
+            TypeExpr::Named("()", _) => format!("void {}", declarator),
+            TypeExpr::Named(name, size) => match tag {
+                Some(tag) if c_primitive(name, *size).is_none() => {
+                    format!("{} {} {}", tag.keyword(), name, declarator)
+                }
+                _ => format!("{} {}", CodeLanguage::C.primitive(name, *size), declarator),
+            },
+            TypeExpr::Pointer(pointee) => match **pointee {
+                TypeExpr::Array(..) | TypeExpr::Function => {
+                    pointee.c(&format!("(*{})", declarator), tag)
+                }
+                _ => pointee.c(&format!("*{}", declarator), tag),
+            },
+            TypeExpr::Array(element, count) => {
+                element.c(&format!("{}[{}]", declarator, count), tag)
+            }
+            TypeExpr::Function => format!("void {}()", declarator),
+        }
+    }
+
+    fn go(&self) -> String {
+        match self {
+            TypeExpr::Named(name, size) => CodeLanguage::Go.primitive(name, *size).to_string(),
+            TypeExpr::Pointer(pointee) if pointee.is_void() => "unsafe.Pointer".to_string(),
+            // Go func values are already pointers.
+            TypeExpr::Pointer(pointee) if **pointee == TypeExpr::Function => pointee.go(),
+            TypeExpr::Pointer(pointee) => format!("*{}", pointee.go()),
+            TypeExpr::Array(element, count) => format!("[{}]{}", count, element.go()),
+            TypeExpr::Function => "func()".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::OptimizeStrategy;
+
+    fn member(name: &str, type_name: &str, offset: u64, size: u64) -> OptimizedMember {
+        OptimizedMember {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            offset,
+            size,
+            alignment: size.next_power_of_two().min(8),
+            alignment_source: crate::analysis::AlignmentSource::Size,
+            bit_offset: None,
+            bit_size: None,
+            type_tag: None,
+        }
+    }
+
+    fn suggestion(members: Vec<OptimizedMember>, size: u64) -> OptimizedLayout {
+        OptimizedLayout {
+            name: "app::Packet".to_string(),
+            original_size: 32,
+            optimized_size: size,
+            savings_bytes: 32 - size,
+            savings_percent: 0.0,
+            struct_alignment: 8,
+            original_members: Vec::new(),
+            optimized_members: members,
+            skipped_members: Vec::new(),
+            has_bitfields: false,
+            strategy: OptimizeStrategy::Greedy,
+            search_timed_out: false,
+            strategy_disagreement: Vec::new(),
+            access_simulation: None,
+            locality: None,
+            pack: None,
+            frozen: false,
+            usage: None,
+        }
+    }
+
+    fn packet() -> OptimizedLayout {
+        suggestion(
+            vec![
+                member("data", "*void", 0, 8),
+                member("cb", "*fn(...)", 8, 8),
+                member("len", "int", 16, 4),
+                member("tag", "[char; 3]", 20, 3),
+            ],
+            24,
+        )
+    }
+
+    #[test]
+    fn rust_definition_is_repr_c_with_explicit_padding() {
+        let out = CodeFormatter::new(CodeLanguage::Rust).format(&[packet()]);
+        assert!(out.starts_with("// app::Packet: 32 bytes -> 24 bytes (saves 8 bytes)\n"));
+        assert!(out.contains("#[repr(C)]\npub struct Packet {\n"));
+        assert!(out.contains("    pub data: *mut core::ffi::c_void,\n"));
+        assert!(out.contains("    pub cb: Option<unsafe extern \"C\" fn()>,\n"));
+        assert!(out.contains("    pub len: i32,\n"));
+        assert!(out.contains("    pub tag: [core::ffi::c_char; 3],\n    _pad0: [u8; 1],\n}\n"));
+    }
+
+    #[test]
+    fn c_definition_uses_declarator_syntax() {
+        let out = CodeFormatter::new(CodeLanguage::C).format(&[packet()]);
+        assert!(out.contains("struct Packet {\n    void *data;\n    void (*cb)();\n"));
+        assert!(out.contains("    int len;\n    char tag[3];\n    char _pad0[1];\n};\n"));
+    }
+
+    #[test]
+    fn c_definition_tags_struct_union_and_enum_members() {
+        let tagged = |name, type_name, offset, size, tag| OptimizedMember {
+            type_tag: Some(tag),
+            ..member(name, type_name, offset, size)
+        };
+        let s = suggestion(
+            vec![
+                tagged("inner", "Inner", 0, 8, TypeTag::Struct),
+                tagged("next", "*Packet", 8, 8, TypeTag::Struct),
+                tagged("slots", "[Slot; 2]", 16, 8, TypeTag::Union),
+                tagged("color", "Color", 24, 4, TypeTag::Enum),
+                // A typedef'd struct keeps its bare name.
+                member("alias", "AliasStruct", 28, 4),
+            ],
+            32,
+        );
+        let out = CodeFormatter::new(CodeLanguage::C).format(&[s]);
+        assert!(out.contains(
+            "    struct Inner inner;\n    struct Packet *next;\n    union Slot slots[2];\n    \
+             enum Color color;\n    AliasStruct alias;\n"
+        ));
+    }
+
+    #[test]
+    fn go_definition_uses_blank_padding_fields() {
+        let out = CodeFormatter::new(CodeLanguage::Go).format(&[packet()]);
+        assert!(out.contains("type Packet struct {\n    data unsafe.Pointer\n    cb func()\n"));
+        assert!(out.contains("    len int32\n    tag [3]byte\n    _ [1]byte\n}\n"));
+    }
+
+    #[test]
+    fn bitfields_and_interior_gaps() {
+        let mut a = member("a", "unsigned int", 0, 4);
+        a.bit_size = Some(1);
+        let mut b = member("b", "unsigned int", 0, 4);
+        b.bit_size = Some(31);
+        let mut s = suggestion(vec![a, b, member("p", "*u64", 8, 8)], 16);
+        s.struct_alignment = 64;
+
+        let c = CodeFormatter::new(CodeLanguage::C).format(std::slice::from_ref(&s));
+        assert!(c.contains("struct __attribute__((aligned(64))) Packet {\n"));
+        assert!(
+            c.contains("    unsigned int a : 1;\n    unsigned int b : 31;\n    char _pad0[4];\n")
+        );
+
+        let rust = CodeFormatter::new(CodeLanguage::Rust).format(std::slice::from_ref(&s));
+        assert!(rust.contains("#[repr(C, align(64))]\n"));
+        assert!(rust.contains("    // bitfields: a : 1, b : 31\n    pub bits0: [u8; 4],\n"));
+        assert!(rust.contains("    _pad0: [u8; 4],\n    pub p: *mut u64,\n}\n"));
+
+        let go = CodeFormatter::new(CodeLanguage::Go).format(&[s]);
+        assert!(go.contains("// Go cannot request 64-byte alignment.\n"));
+        assert!(go.contains("    _ [4]byte\n    p *uint64\n}\n"));
+
+        // Go's `int` is pointer-sized.
+        let s = suggestion(vec![member("n", "int", 0, 8)], 8);
+        let c = CodeFormatter::new(CodeLanguage::C).format(&[s]);
+        assert!(c.contains("    intptr_t n;\n"));
+    }
+}
@@ -0,0 +1,62 @@
+//! Per-struct columns for one-row-per-struct listings.
+
+use crate::types::StructLayout;
+use serde::Deserialize;
+
+/// A per-struct value shown as a column, chosen with `--columns` or the config's `columns`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    /// Total size in bytes
+    Size,
+    /// Padding bytes
+    Padding,
+    /// Padding as a percentage of the size
+    PaddingPct,
+    /// Cache lines spanned
+    CacheLines,
+    /// Alignment in bytes
+    Align,
+    /// Declaring source file
+    File,
+    /// Number of members
+    Members,
+}
+
+impl Column {
+    /// Columns used when none are chosen.
+    pub const DEFAULT: &[Column] =
+        &[Column::Size, Column::Padding, Column::PaddingPct, Column::CacheLines];
+
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::Size => "Size",
+            Column::Padding => "Padding",
+            Column::PaddingPct => "Padding %",
+            Column::CacheLines => "Cache lines",
+            Column::Align => "Align",
+            Column::File => "File",
+            Column::Members => "Members",
+        }
+    }
+
+    /// Whether values are numbers, which tables align right.
+    pub fn is_numeric(self) -> bool {
+        self != Column::File
+    }
+
+    /// This column's value for `layout`, which must already be analyzed.
+    pub fn value(self, layout: &StructLayout) -> String {
+        match self {
+            Column::Size => layout.size.to_string(),
+            Column::Padding => layout.metrics.padding_bytes.to_string(),
+            Column::PaddingPct => format!("{:.1}%", layout.metrics.padding_percentage),
+            Column::CacheLines => layout.metrics.cache_lines_spanned.to_string(),
+            Column::Align => layout.alignment.map_or_else(|| "?".to_string(), |a| a.to_string()),
+            Column::File => {
+                layout.source_location.as_ref().map_or_else(|| "-".to_string(), |l| l.file.clone())
+            }
+            Column::Members => layout.members.len().to_string(),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! CSV output of inspect results, for spreadsheets and BI dashboards.
+
+use super::Column;
+use crate::types::StructLayout;
+
+const STRUCT_HEADER: &[&str] = &[
+    "binary",
+    "name",
+    "size",
+    "alignment",
+    "members",
+    "useful_size",
+    "padding_bytes",
+    "padding_percent",
+    "padding_holes",
+    "cache_lines_spanned",
+    "cache_line_density",
+    "partial",
+    "source_file",
+    "source_line",
+];
+
+const MEMBER_HEADER: &[&str] = &[
+    "binary",
+    "struct",
+    "position",
+    "name",
+    "type",
+    "offset",
+    "size",
+    "bit_offset",
+    "bit_size",
+    "alignment",
+    "is_atomic",
+    "source_file",
+    "source_line",
+];
+
+/// Writes one row per struct, or with [`CsvFormatter::with_members`] one row per member.
+/// Unknown values are left empty and numbers carry no units, so columns import as numbers.
+#[derive(Default)]
+pub struct CsvFormatter {
+    members: bool,
+    columns: Option<Vec<Column>>,
+}
+
+impl CsvFormatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_members(mut self, members: bool) -> Self {
+        self.members = members;
+        self
+    }
+
+    /// Struct rows with the binary, the name, and these columns, in this order, instead of every
+    /// struct field. Member rows are unaffected.
+    pub fn with_columns(mut self, columns: Option<Vec<Column>>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// The header row and one row per struct (or member) of `layouts`, which must already be
+    /// analyzed.
+    pub fn format(&self, layouts: &[StructLayout]) -> String {
+        let mut output = String::new();
+        if self.members {
+            push_row(&mut output, MEMBER_HEADER.iter().map(|h| h.to_string()));
+            for layout in layouts {
+                for (position, m) in layout.members.iter().enumerate() {
+                    let location = m.source_location.as_ref();
+                    push_row(
+                        &mut output,
+                        [
+                            layout.binary.clone().unwrap_or_default(),
+                            layout.name.clone(),
+                            position.to_string(),
+                            m.name.clone(),
+                            m.type_name.clone(),
+                            opt(m.offset),
+                            opt(m.size),
+                            opt(m.bit_offset),
+                            opt(m.bit_size),
+                            opt(m.alignment),
+                            m.is_atomic.to_string(),
+                            location.map(|l| l.file.clone()).unwrap_or_default(),
+                            opt(location.map(|l| l.line)),
+                        ],
+                    );
+                }
+            }
+        } else if let Some(columns) = &self.columns {
+            let header = ["binary", "name"].into_iter().chain(columns.iter().map(|&c| header(c)));
+            push_row(&mut output, header.map(str::to_string));
+            for layout in layouts {
+                let row = [layout.binary.clone().unwrap_or_default(), layout.name.clone()];
+                push_row(
+                    &mut output,
+                    row.into_iter().chain(columns.iter().map(|&c| value(c, layout))),
+                );
+            }
+        } else {
+            push_row(&mut output, STRUCT_HEADER.iter().map(|h| h.to_string()));
+            for layout in layouts {
+                let m = &layout.metrics;
+                let location = layout.source_location.as_ref();
+                push_row(
+                    &mut output,
+                    [
+                        layout.binary.clone().unwrap_or_default(),
+                        layout.name.clone(),
+                        layout.size.to_string(),
+                        opt(layout.alignment),
+                        layout.members.len().to_string(),
+                        m.useful_size.to_string(),
+                        m.padding_bytes.to_string(),
+                        format!("{:.2}", m.padding_percentage),
+                        m.padding_holes.len().to_string(),
+                        m.cache_lines_spanned.to_string(),
+                        format!("{:.2}", m.cache_line_density),
+                        m.partial.to_string(),
+                        location.map(|l| l.file.clone()).unwrap_or_default(),
+                        opt(location.map(|l| l.line)),
+                    ],
+                );
+            }
+        }
+        output
+    }
+}
+
+/// The [`STRUCT_HEADER`] name of a column's field.
+fn header(column: Column) -> &'static str {
+    match column {
+        Column::Size => "size",
+        Column::Padding => "padding_bytes",
+        Column::PaddingPct => "padding_percent",
+        Column::CacheLines => "cache_lines_spanned",
+        Column::Align => "alignment",
+        Column::File => "source_file",
+        Column::Members => "members",
+    }
+}
+
+/// A column's value as the full struct row writes it: bare numbers, empty when unknown.
+fn value(column: Column, layout: &StructLayout) -> String {
+    match column {
+        Column::Size => layout.size.to_string(),
+        Column::Padding => layout.metrics.padding_bytes.to_string(),
+        Column::PaddingPct => format!("{:.2}", layout.metrics.padding_percentage),
+        Column::CacheLines => layout.metrics.cache_lines_spanned.to_string(),
+        Column::Align => opt(layout.alignment),
+        Column::File => layout.source_location.as_ref().map(|l| l.file.clone()).unwrap_or_default(),
+        Column::Members => layout.members.len().to_string(),
+    }
+}
+
+fn opt(value: Option<u64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn push_row(output: &mut String, fields: impl IntoIterator<Item = String>) {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            output.push(',');
+        }
+        output.push_str(&escape(&field));
+    }
+    output.push('\n');
+}
+
+/// Quote a field holding a separator, quote, or line break (RFC 4180), as type names such as
+/// `std::map<int, int>` do.
+fn escape(field: &str) -> std::borrow::Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\"")).into()
+    } else {
+        field.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    fn sample_layout() -> StructLayout {
+        let mut layout = StructLayout::new("Foo".to_string(), 16, Some(8));
+        layout.members = vec![
+            MemberLayout::new("a".to_string(), "char".to_string(), Some(0), Some(1)),
+            MemberLayout::new("m".to_string(), "std::map<int, int>".to_string(), Some(8), None),
+        ];
+        layout.metrics.useful_size = 9;
+        layout.metrics.padding_bytes = 7;
+        layout.metrics.padding_percentage = 43.75;
+        layout.metrics.cache_lines_spanned = 1;
+        layout
+    }
+
+    #[test]
+    fn struct_rows() {
+        let csv = CsvFormatter::new().format(&[sample_layout()]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], STRUCT_HEADER.join(","));
+        assert_eq!(lines[1], ",Foo,16,8,2,9,7,43.75,0,1,0.00,false,,");
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn struct_rows_with_columns() {
+        let csv = CsvFormatter::new()
+            .with_columns(Some(vec![Column::PaddingPct, Column::Align, Column::File]))
+            .format(&[sample_layout()]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines, ["binary,name,padding_percent,alignment,source_file", ",Foo,43.75,8,"]);
+    }
+
+    #[test]
+    fn member_rows_quote_type_names() {
+        let csv = CsvFormatter::new().with_members(true).format(&[sample_layout()]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], MEMBER_HEADER.join(","));
+        assert_eq!(lines[1], ",Foo,0,a,char,0,1,,,,false,,");
+        assert_eq!(lines[2], ",Foo,1,m,\"std::map<int, int>\",8,,,,,false,,");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
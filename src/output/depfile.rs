@@ -0,0 +1,88 @@
+//! Makefile-style dependency file output for build systems (Make, Ninja).
+
+use std::path::{Path, PathBuf};
+
+pub struct DepfileFormatter {
+    target: String,
+}
+
+impl DepfileFormatter {
+    pub fn new(target: impl Into<String>) -> Self {
+        Self { target: target.into() }
+    }
+
+    /// Derive the default target from the depfile path by stripping its extension
+    /// (`audit.stamp.d` -> `audit.stamp`), matching the usual `<output>.d` convention.
+    pub fn default_target(depfile: &Path) -> String {
+        depfile.with_extension("").to_string_lossy().into_owned()
+    }
+
+    pub fn format(&self, inputs: &[PathBuf]) -> String {
+        let mut output = escape_path(&self.target);
+        output.push(':');
+
+        // Deduplicate while preserving first-seen order (e.g. diff of a binary against itself).
+        let mut seen = std::collections::HashSet::new();
+        for input in inputs {
+            let input = input.to_string_lossy();
+            if seen.insert(input.clone()) {
+                output.push_str(" \\\n  ");
+                output.push_str(&escape_path(&input));
+            }
+        }
+        output.push('\n');
+
+        output
+    }
+}
+
+/// Escape a path for use in a Makefile rule. Ninja's depfile parser accepts the same syntax.
+fn escape_path(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            ' ' | '#' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '$' => escaped.push_str("$$"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depfile_lists_inputs() {
+        let formatter = DepfileFormatter::new("audit.stamp");
+        let out =
+            formatter.format(&[PathBuf::from("bin/app"), PathBuf::from(".layout-audit.yaml")]);
+        assert_eq!(out, "audit.stamp: \\\n  bin/app \\\n  .layout-audit.yaml\n");
+    }
+
+    #[test]
+    fn depfile_escapes_special_characters() {
+        let formatter = DepfileFormatter::new("out");
+        let out = formatter.format(&[PathBuf::from("my app/$bin#1")]);
+        assert!(out.contains("my\\ app/$$bin\\#1"));
+    }
+
+    #[test]
+    fn depfile_deduplicates_inputs() {
+        let formatter = DepfileFormatter::new("out");
+        let out = formatter.format(&[PathBuf::from("a"), PathBuf::from("a")]);
+        assert_eq!(out.matches(" a").count(), 1);
+    }
+
+    #[test]
+    fn depfile_default_target_strips_extension() {
+        assert_eq!(
+            DepfileFormatter::default_target(Path::new("build/audit.stamp.d")),
+            "build/audit.stamp"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+//! Output formatters for the verify-determinism command.
+
+use crate::diff::{BuildDifferenceKind, DeterminismReport};
+use colored::Colorize;
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+
+pub struct DeterminismTableFormatter {
+    no_color: bool,
+}
+
+impl DeterminismTableFormatter {
+    pub fn new(no_color: bool) -> Self {
+        Self { no_color }
+    }
+
+    pub fn format(&self, report: &DeterminismReport) -> String {
+        let differing: usize = report.runs.iter().map(|r| r.differing()).sum();
+        let mut output = format!(
+            "{} run(s) compared with {}: {} struct difference(s)\n",
+            report.runs.len(),
+            report.baseline,
+            differing
+        );
+        for run in report.runs.iter().filter(|r| r.differing() > 0) {
+            let mut table = Table::new();
+            table.load_preset(UTF8_FULL_CONDENSED);
+            table.set_header(vec!["Struct", "Difference", "Details"]);
+            let mut add = |name: &str, difference: &str, details: String| {
+                let difference = Cell::new(difference);
+                table.add_row(vec![
+                    Cell::new(name),
+                    if self.no_color { difference } else { difference.fg(Color::Red) },
+                    Cell::new(details),
+                ]);
+            };
+            for d in &run.differences {
+                let c = &d.change;
+                let mut details = vec![format!("{} -> {} bytes", c.old_size, c.new_size)];
+                details
+                    .extend(c.member_changes.iter().map(|m| format!("{}: {}", m.name, m.details)));
+                add(&c.name, difference_label(d.kind), details.join("\n"));
+            }
+            for s in &run.only_in_baseline {
+                add(&s.name, "only in baseline", format!("{} bytes", s.size));
+            }
+            for s in &run.only_in_run {
+                add(&s.name, "only in this run", format!("{} bytes", s.size));
+            }
+            output.push_str(&format!("\n{} ({} identical):\n", run.label, run.unchanged_count));
+            output.push_str(&table.to_string());
+            output.push('\n');
+        }
+        if differing == 0 {
+            return output;
+        }
+
+        let note = "\nDiffering members or member types point at configuration the build \
+                    environment decided (feature detection, defines); the same members laid out \
+                    differently point at nondeterministic code generation";
+        if self.no_color {
+            output.push_str(note);
+        } else {
+            output.push_str(&note.yellow().to_string());
+        }
+        output.push('\n');
+        output
+    }
+}
+
+fn difference_label(kind: BuildDifferenceKind) -> &'static str {
+    match kind {
+        BuildDifferenceKind::ConditionalMembers => "members differ",
+        BuildDifferenceKind::MemberTypes => "member types differ",
+        BuildDifferenceKind::Unexplained => "layout differs",
+    }
+}
+
+#[derive(Serialize)]
+struct DeterminismJsonOutput<'a> {
+    version: &'static str,
+    deterministic: bool,
+    #[serde(flatten)]
+    report: &'a DeterminismReport,
+}
+
+pub struct DeterminismJsonFormatter {
+    pretty: bool,
+}
+
+impl DeterminismJsonFormatter {
+    pub fn new(pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    pub fn format(&self, report: &DeterminismReport) -> String {
+        let output = DeterminismJsonOutput {
+            version: env!("CARGO_PKG_VERSION"),
+            deterministic: report.is_deterministic(),
+            report,
+        };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+        } else {
+            serde_json::to_string(&output)
+        }
+        .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
@@ -0,0 +1,164 @@
+//! Output formatters for the forecast command.
+
+use crate::forecast::Forecast;
+use colored::Colorize;
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+
+pub struct ForecastTableFormatter {
+    no_color: bool,
+}
+
+impl ForecastTableFormatter {
+    pub fn new(no_color: bool) -> Self {
+        Self { no_color }
+    }
+
+    pub fn format(&self, forecast: &Forecast) -> String {
+        let mut output = format!(
+            "Fleet waste: {} of padding in {} counted struct(s); suggested reorderings recover {}\n",
+            human_bytes(forecast.total_waste_bytes),
+            forecast.structs.len(),
+            human_bytes(forecast.total_recoverable_bytes)
+        );
+        let top = forecast.top.min(forecast.structs.len());
+        if top > 0 {
+            let header = format!(
+                "Applying the top {} suggestion(s) recovers {}",
+                top,
+                human_bytes(forecast.top_recoverable_bytes)
+            );
+            if self.no_color {
+                output.push_str(&header);
+            } else {
+                output.push_str(&header.green().bold().to_string());
+            }
+            output.push('\n');
+        }
+
+        if !forecast.structs.is_empty() {
+            let mut table = Table::new();
+            table.load_preset(UTF8_FULL_CONDENSED);
+            table.set_header(vec![
+                "Struct",
+                "Instances",
+                "Size",
+                "Padding",
+                "Waste",
+                "Recoverable",
+                "Budget",
+            ]);
+            for (i, s) in forecast.structs.iter().enumerate() {
+                let recoverable = Cell::new(human_bytes(s.recoverable_bytes));
+                let budget = Cell::new(if s.over_budget { "over" } else { "" });
+                table.add_row(vec![
+                    Cell::new(&s.name),
+                    Cell::new(s.instances.to_string()),
+                    Cell::new(format!("{} -> {}", s.size, s.optimized_size)),
+                    Cell::new(s.padding_bytes.to_string()),
+                    Cell::new(human_bytes(s.waste_bytes)),
+                    if self.no_color || i >= top {
+                        recoverable
+                    } else {
+                        recoverable.fg(Color::Green)
+                    },
+                    if self.no_color { budget } else { budget.fg(Color::Red) },
+                ]);
+            }
+            output.push('\n');
+            output.push_str(&table.to_string());
+            output.push('\n');
+        }
+
+        if let Some(trend) = &forecast.trend {
+            let points: Vec<String> = trend
+                .points
+                .iter()
+                .map(|p| format!("{} {}", p.label, human_bytes(p.waste_bytes)))
+                .collect();
+            let sign = if trend.bytes_per_snapshot < 0 { "-" } else { "+" };
+            output.push_str(&format!(
+                "\nTrend: {}\n{}{} per snapshot; in {} more snapshot(s): {} wasted, {} after the \
+                 top {}\n",
+                points.join(" -> "),
+                sign,
+                human_bytes(trend.bytes_per_snapshot.unsigned_abs()),
+                trend.horizon,
+                human_bytes(trend.projected_waste_bytes),
+                human_bytes(trend.projected_waste_after_top_bytes),
+                top
+            ));
+        }
+
+        if !forecast.unmatched.is_empty() {
+            let warning = format!(
+                "\nWarning: {} counted struct(s) not found in the binary: {}",
+                forecast.unmatched.len(),
+                forecast.unmatched.join(", ")
+            );
+            if self.no_color {
+                output.push_str(&warning);
+            } else {
+                output.push_str(&warning.yellow().to_string());
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+/// `n` bytes in the largest binary unit that keeps the number at least 1.
+fn human_bytes(n: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+    let mut value = n as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[derive(Serialize)]
+struct ForecastJsonOutput<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    forecast: &'a Forecast,
+}
+
+pub struct ForecastJsonFormatter {
+    pretty: bool,
+}
+
+impl ForecastJsonFormatter {
+    pub fn new(pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    pub fn format(&self, forecast: &Forecast) -> String {
+        let output = ForecastJsonOutput { version: env!("CARGO_PKG_VERSION"), forecast };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+        } else {
+            serde_json::to_string(&output)
+        }
+        .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn human_bytes_picks_largest_unit() {
+        assert_eq!(human_bytes(0), "0 B");
+        assert_eq!(human_bytes(1023), "1023 B");
+        assert_eq!(human_bytes(1536), "1.5 KiB");
+        assert_eq!(human_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
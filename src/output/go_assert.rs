@@ -0,0 +1,128 @@
+//! Go `_test.go` files that freeze struct layouts at compile time.
+//!
+//! Each assertion indexes a one-element array with the difference between the actual and the
+//! recorded size or offset. Both are constants, so any drift is a compile error ("index out of
+//! bounds" when the value grew, "constant overflows uintptr" when it shrank) in `go test` and
+//! `go vet`, without affecting the package's regular build.
+
+use crate::types::StructLayout;
+
+pub struct GoAssertionFormatter {
+    package_name: String,
+}
+
+impl GoAssertionFormatter {
+    pub fn new(package_name: impl Into<String>) -> Self {
+        Self { package_name: package_name.into() }
+    }
+
+    /// Package clause for a file in `import_path`: its last path segment.
+    pub fn default_package_name(import_path: &str) -> String {
+        import_path.rsplit('/').next().unwrap_or(import_path).to_string()
+    }
+
+    /// The layouts that can be asserted on from inside package `import_path`: its own named,
+    /// non-generic structs with complete layouts.
+    pub fn assertable<'a>(import_path: &str, layouts: &'a [StructLayout]) -> Vec<&'a StructLayout> {
+        let mut found: Vec<&StructLayout> = layouts
+            .iter()
+            .filter(|l| !l.metrics.partial)
+            .filter(|l| local_type_name(import_path, &l.name).is_some())
+            .collect();
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        found.dedup_by(|a, b| a.name == b.name);
+        found
+    }
+
+    /// A test file asserting the size and member offsets of each struct of `import_path`.
+    pub fn format(&self, import_path: &str, layouts: &[StructLayout]) -> String {
+        let mut output = String::from(
+            "// Code generated by layout-audit emit-assertions; DO NOT EDIT.\n\
+             //\n\
+             // Each line fails to compile if the size or offset it records changes.\n\n",
+        );
+        output.push_str(&format!("package {}\n\nimport \"unsafe\"\n", self.package_name));
+
+        for layout in Self::assertable(import_path, layouts) {
+            let Some(name) = local_type_name(import_path, &layout.name) else { continue };
+            output.push_str(&format!("\n// {}: {} bytes\n", name, layout.size));
+            output.push_str(&assertion(&format!("unsafe.Sizeof({}{{}})", name), layout.size));
+            for member in &layout.members {
+                let Some(offset) = member.offset else { continue };
+                if member.name == "_" || !is_identifier(&member.name) {
+                    continue;
+                }
+                let expr = format!("unsafe.Offsetof({}{{}}.{})", name, member.name);
+                output.push_str(&assertion(&expr, offset));
+            }
+        }
+
+        output
+    }
+}
+
+fn assertion(expr: &str, expected: u64) -> String {
+    if expected == 0 {
+        format!("var _ = [1]struct{{}}{{}}[{}]\n", expr)
+    } else {
+        format!("var _ = [1]struct{{}}{{}}[{}-{}]\n", expr, expected)
+    }
+}
+
+/// `Order` for `import_path.Order`; `None` for other packages, generic instantiations, and
+/// compiler-generated names.
+fn local_type_name<'a>(import_path: &str, name: &'a str) -> Option<&'a str> {
+    let local = name.strip_prefix(import_path)?.strip_prefix('.')?;
+    is_identifier(local).then_some(local)
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    fn layout(name: &str, size: u64, members: &[(&str, u64)]) -> StructLayout {
+        let mut l = StructLayout::new(name.to_string(), size, Some(8));
+        l.members = members
+            .iter()
+            .map(|(n, off)| {
+                MemberLayout::new(n.to_string(), "uint64".to_string(), Some(*off), Some(8))
+            })
+            .collect();
+        l
+    }
+
+    #[test]
+    fn asserts_sizes_and_offsets_of_package_structs() {
+        let layouts = [
+            layout("main.Order", 24, &[("id", 0), ("price", 8), ("_", 16)]),
+            layout("main.Pair[int]", 16, &[]),
+            layout("other.Order", 8, &[]),
+            layout("main.Order", 24, &[("id", 0)]),
+        ];
+        let out = GoAssertionFormatter::new("main").format("main", &layouts);
+        assert!(out.starts_with("// Code generated by layout-audit"));
+        assert!(out.contains("package main\n\nimport \"unsafe\"\n"));
+        assert!(out.contains("var _ = [1]struct{}{}[unsafe.Sizeof(Order{})-24]\n"));
+        assert!(out.contains("var _ = [1]struct{}{}[unsafe.Offsetof(Order{}.id)]\n"));
+        assert!(out.contains("var _ = [1]struct{}{}[unsafe.Offsetof(Order{}.price)-8]\n"));
+        assert!(!out.contains("Order{}._"));
+        assert!(!out.contains("Pair"));
+        assert_eq!(out.matches("// Order:").count(), 1);
+    }
+
+    #[test]
+    fn package_names_come_from_import_paths() {
+        assert_eq!(GoAssertionFormatter::default_package_name("github.com/acme/shop"), "shop");
+        assert_eq!(GoAssertionFormatter::default_package_name("main"), "main");
+        let layouts = [layout("github.com/acme/shop.Cart", 8, &[])];
+        assert_eq!(GoAssertionFormatter::assertable("github.com/acme/shop", &layouts).len(), 1);
+        assert!(GoAssertionFormatter::assertable("github.com/acme", &layouts).is_empty());
+    }
+}
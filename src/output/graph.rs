@@ -0,0 +1,112 @@
+//! Output formatters for the graph command.
+
+use crate::analysis::{ContainmentGraph, GraphNode};
+use serde::Serialize;
+
+/// Graphviz DOT: one box per struct, wider the more padding it has and colored by its padding
+/// percentage, with an arrow from each container to what it embeds.
+pub struct GraphDotFormatter;
+
+impl GraphDotFormatter {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn format(&self, graph: &ContainmentGraph) -> String {
+        let mut output = String::from(
+            "digraph containment {\n    rankdir=LR;\n    node [shape=box, style=filled, \
+             fontname=\"monospace\"];\n",
+        );
+        for node in &graph.nodes {
+            let mut label = format!(
+                "{}\\n{} bytes, {} padding ({:.1}%)",
+                escape(&node.name),
+                node.size,
+                node.padding_bytes,
+                node.padding_percent
+            );
+            if node.embedded_padding_bytes > 0 {
+                label.push_str(&format!(
+                    "\\nembedded {}x: {} bytes of padding",
+                    node.embedded_copies, node.embedded_padding_bytes
+                ));
+            }
+            output.push_str(&format!(
+                "    \"{}\" [label=\"{}\", width={:.2}, fillcolor=\"{}\"{}];\n",
+                escape(&node.name),
+                label,
+                width(node),
+                fill(node),
+                if node.partial { ", peripheries=2" } else { "" }
+            ));
+        }
+        for edge in &graph.edges {
+            let mut label = edge.members.join(", ");
+            if edge.count > 1 {
+                label.push_str(&format!(" (x{})", edge.count));
+            }
+            output.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                escape(&edge.from),
+                escape(&edge.to),
+                escape(&label)
+            ));
+        }
+        output.push_str("}\n");
+        output
+    }
+}
+
+impl Default for GraphDotFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Box width in inches: Graphviz's default for padding-free structs, growing with the square
+/// root of the padding so one huge struct does not dwarf the rest.
+fn width(node: &GraphNode) -> f64 {
+    0.75 + (node.padding_bytes as f64).sqrt() / 4.0
+}
+
+/// Fill color by padding percentage, in the ranges of the summary histogram.
+fn fill(node: &GraphNode) -> &'static str {
+    match node.padding_percent {
+        p if node.padding_bytes == 0 || p <= 0.0 => "#e8f5e9",
+        p if p < 10.0 => "#fff9c4",
+        p if p < 25.0 => "#ffe0b2",
+        p if p < 50.0 => "#ffab91",
+        _ => "#ef9a9a",
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[derive(Serialize)]
+struct GraphJsonOutput<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    graph: &'a ContainmentGraph,
+}
+
+pub struct GraphJsonFormatter {
+    pretty: bool,
+}
+
+impl GraphJsonFormatter {
+    pub fn new(pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    pub fn format(&self, graph: &ContainmentGraph) -> String {
+        let output = GraphJsonOutput { version: env!("CARGO_PKG_VERSION"), graph };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+        } else {
+            serde_json::to_string(&output)
+        }
+        .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
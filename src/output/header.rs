@@ -0,0 +1,462 @@
+//! C headers reconstructing struct definitions from the debug info.
+//!
+//! Every gap becomes an explicit `char _padN[..]` field and every definition is followed by
+//! `_Static_assert`s on its size and member offsets, so a tool compiled against the header
+//! either matches the binary's layout byte for byte or fails to build. Members whose type the
+//! header cannot spell (typedefs, enums, structs left out, flattened unions) become byte arrays
+//! of the same size and alignment, with the original type in a comment.
+
+use super::code::{TypeExpr, c_primitive};
+use crate::names::canonical_name;
+use crate::types::{MemberLayout, StructLayout};
+use std::collections::{HashMap, HashSet};
+
+/// Qualifiers the debug info prefixes to type names; they never change a layout.
+const QUALIFIERS: &[&str] = &["const ", "volatile ", "restrict ", "_Atomic "];
+
+pub struct HeaderFormatter {
+    guard: String,
+    source: Option<String>,
+}
+
+impl HeaderFormatter {
+    pub fn new(guard: impl Into<String>) -> Self {
+        Self { guard: guard.into(), source: None }
+    }
+
+    /// Name the binary the header was generated from in its opening comment.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Include guard for a header written to `file_name`: `LAYOUT_H` for `layout.h`.
+    pub fn default_guard(file_name: Option<&str>) -> String {
+        let guard: String = file_name
+            .unwrap_or("layout_audit.h")
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect();
+        if guard.starts_with(|c: char| c.is_ascii_digit()) { format!("_{}", guard) } else { guard }
+    }
+
+    /// The layouts a header can define: complete layouts whose unqualified name is a C
+    /// identifier and whose members do not overlap (other than bitfields sharing storage and
+    /// the fields of a flattened union), first of each name.
+    pub fn emittable(layouts: &[StructLayout]) -> Vec<&StructLayout> {
+        let mut found: Vec<&StructLayout> = layouts
+            .iter()
+            .filter(|l| !l.metrics.partial)
+            .filter(|l| is_identifier(canonical_name(&l.name)))
+            .filter(|l| plan(l).is_some())
+            .collect();
+        found.sort_by(|a, b| canonical_name(&a.name).cmp(canonical_name(&b.name)));
+        found.dedup_by(|a, b| canonical_name(&a.name) == canonical_name(&b.name));
+        found
+    }
+
+    /// A header defining each emittable struct, dependencies first, with its assertions.
+    pub fn format(&self, layouts: &[StructLayout]) -> String {
+        let structs = Self::emittable(layouts);
+        let known: HashMap<&str, &str> = structs
+            .iter()
+            .flat_map(|l| {
+                let name = canonical_name(&l.name);
+                [(l.name.as_str(), name), (name, name)]
+            })
+            .collect();
+
+        let mut output = String::from("/* Code generated by layout-audit emit-header");
+        if let Some(source) = &self.source {
+            output.push_str(&format!(" from {}", source));
+        }
+        output.push_str(
+            "; DO NOT EDIT.\n *\n * Padding is spelled out and each layout is asserted, so a \
+             layout change fails the build. */\n",
+        );
+        output.push_str(&format!("#ifndef {0}\n#define {0}\n\n", self.guard));
+        output.push_str("#include <stddef.h>\n#include <stdint.h>\n");
+
+        if !structs.is_empty() {
+            output.push('\n');
+        }
+        for layout in &structs {
+            let name = canonical_name(&layout.name);
+            output.push_str(&format!("typedef struct {0} {0};\n", name));
+        }
+        for layout in dependency_order(&structs, &known) {
+            output.push('\n');
+            output.push_str(&definition(layout, &known));
+        }
+
+        output.push_str(&format!("\n#endif /* {} */\n", self.guard));
+        output
+    }
+}
+
+/// One line of a definition, before padding fields are numbered.
+enum Item<'a> {
+    Field(&'a MemberLayout),
+    /// Bitfields sharing one storage unit.
+    Bitfields(Vec<&'a MemberLayout>),
+    /// Bytes standing in for members the header cannot spell.
+    Opaque {
+        offset: u64,
+        size: u64,
+        alignment: Option<u64>,
+        name: String,
+        comment: String,
+    },
+}
+
+impl Item<'_> {
+    fn span(&self) -> (u64, u64) {
+        match self {
+            Item::Field(m) => (m.offset.unwrap_or(0), m.size.unwrap_or(0)),
+            Item::Bitfields(group) => (group[0].offset.unwrap_or(0), group[0].size.unwrap_or(0)),
+            Item::Opaque { offset, size, .. } => (*offset, *size),
+        }
+    }
+}
+
+/// The items of `layout` in offset order, or `None` when members overlap in a way no C
+/// declaration reproduces.
+fn plan(layout: &StructLayout) -> Option<Vec<Item<'_>>> {
+    let mut items: Vec<Item> = Vec::new();
+    let mut end = 0;
+    let mut after_bitfields = false;
+    let mut members = layout.members.iter().filter(|m| m.size != Some(0)).peekable();
+    let mut opaque_count = 0;
+    while let Some(member) = members.next() {
+        let offset = member.offset?;
+        let size = member.size?;
+
+        let item = if let Some(aggregate) = &member.aggregate {
+            let mut fields = vec![member];
+            while let Some(next) = members.next_if(|m| m.aggregate.as_ref() == Some(aggregate)) {
+                fields.push(next);
+            }
+            let stop = fields.iter().filter_map(|m| m.end_offset()).max().unwrap_or(offset);
+            let alignment = fields.iter().filter_map(|m| m.alignment).max();
+            let names: Vec<&str> = fields.iter().map(|m| m.name.as_str()).collect();
+            let name = if is_identifier(aggregate) {
+                aggregate.clone()
+            } else {
+                opaque_count += 1;
+                format!("_anon{}", opaque_count - 1)
+            };
+            let comment = names.join(", ");
+            Item::Opaque { offset, size: stop - offset, alignment, name, comment }
+        } else if member.bit_size.is_some() {
+            let mut group = vec![member];
+            while let Some(next) = members.next_if(|m| {
+                m.bit_size.is_some() && m.offset == Some(offset) && m.aggregate.is_none()
+            }) {
+                group.push(next);
+            }
+            Item::Bitfields(group)
+        } else if member.base_layout.is_some()
+            || member.role.is_some()
+            || !is_identifier(&member.name)
+        {
+            opaque_count += 1;
+            Item::Opaque {
+                offset,
+                size,
+                alignment: member.alignment,
+                name: format!("_anon{}", opaque_count - 1),
+                comment: member.name.clone(),
+            }
+        } else {
+            Item::Field(member)
+        };
+
+        // Only a member packed into a bitfield's storage unit may start before the last ends;
+        // the compiler puts it there again from the same declarations.
+        if offset < end && !after_bitfields {
+            return None;
+        }
+        let (start, len) = item.span();
+        end = end.max(start + len);
+        after_bitfields = matches!(item, Item::Bitfields(_));
+        items.push(item);
+    }
+    Some(items)
+}
+
+/// Definitions before the structs that embed them by value.
+fn dependency_order<'a>(
+    structs: &[&'a StructLayout],
+    known: &HashMap<&str, &str>,
+) -> Vec<&'a StructLayout> {
+    fn visit<'a>(
+        layout: &'a StructLayout,
+        by_name: &HashMap<&str, &'a StructLayout>,
+        known: &HashMap<&str, &str>,
+        done: &mut HashSet<&'a str>,
+        order: &mut Vec<&'a StructLayout>,
+    ) {
+        if !done.insert(canonical_name(&layout.name)) {
+            return;
+        }
+        for member in &layout.members {
+            let expr = TypeExpr::parse(unqualified(&member.type_name), member.size);
+            if let Some(dep) = embedded_name(&expr).and_then(|n| known.get(n))
+                && let Some(dep) = by_name.get(dep)
+            {
+                visit(dep, by_name, known, done, order);
+            }
+        }
+        order.push(layout);
+    }
+
+    let by_name: HashMap<&str, &StructLayout> =
+        structs.iter().map(|l| (canonical_name(&l.name), *l)).collect();
+    let mut done = HashSet::new();
+    let mut order = Vec::with_capacity(structs.len());
+    for layout in structs {
+        visit(layout, &by_name, known, &mut done, &mut order);
+    }
+    order
+}
+
+/// The type a member holds by value, itself or as array elements.
+fn embedded_name<'a>(expr: &TypeExpr<'a>) -> Option<&'a str> {
+    match expr {
+        TypeExpr::Named(name, _) => Some(unqualified(name)),
+        TypeExpr::Array(element, _) => embedded_name(element),
+        _ => None,
+    }
+}
+
+fn definition(layout: &StructLayout, known: &HashMap<&str, &str>) -> String {
+    let name = canonical_name(&layout.name);
+    let items = plan(layout).unwrap_or_default();
+    let mut body = String::new();
+    let mut asserts = format!(
+        "_Static_assert(sizeof(struct {0}) == {1}, \"{0}: size is {1}\");\n",
+        name, layout.size
+    );
+    let mut natural_align = 1;
+    let mut end = 0;
+    let mut pads = 0;
+
+    for item in &items {
+        let (offset, size) = item.span();
+        if offset > end {
+            body.push_str(&format!("    char _pad{}[{}];\n", pads, offset - end));
+            pads += 1;
+        }
+        end = end.max(offset + size);
+
+        let (field_name, alignment) = match item {
+            Item::Field(member) => {
+                let expr = TypeExpr::parse(unqualified(&member.type_name), member.size);
+                match c_declaration(&expr, &member.name, known) {
+                    Some(decl) => body.push_str(&format!("    {};\n", decl)),
+                    None => body.push_str(&opaque_field(
+                        &member.name,
+                        size,
+                        member.alignment,
+                        &member.type_name,
+                    )),
+                }
+                (Some(member.name.as_str()), member.alignment.unwrap_or(1))
+            }
+            Item::Bitfields(group) => {
+                body.push_str(&bitfields(group, size));
+                (None, group.iter().filter_map(|m| m.alignment).max().unwrap_or(size.max(1)))
+            }
+            Item::Opaque { alignment, name, comment, .. } => {
+                body.push_str(&opaque_field(name, size, *alignment, comment));
+                (Some(name.as_str()), alignment.unwrap_or(1))
+            }
+        };
+        natural_align = natural_align.max(alignment);
+        if let Some(field_name) = field_name {
+            asserts.push_str(&format!(
+                "_Static_assert(offsetof(struct {0}, {1}) == {2}, \"{0}: {1} is at offset {2}\");\n",
+                name, field_name, offset
+            ));
+        }
+    }
+    if layout.size > end {
+        body.push_str(&format!("    char _pad{}[{}];\n", pads, layout.size - end));
+    }
+
+    let open = match layout.alignment {
+        Some(align) if align > natural_align => {
+            format!("struct __attribute__((aligned({}))) {} {{\n", align, name)
+        }
+        _ => format!("struct {} {{\n", name),
+    };
+    if let Some(align) = layout.alignment {
+        asserts.push_str(&format!(
+            "_Static_assert(_Alignof(struct {0}) == {1}, \"{0}: alignment is {1}\");\n",
+            name, align
+        ));
+    }
+    format!("/* {}: {} bytes */\n{}{}}};\n{}", layout.name, layout.size, open, body, asserts)
+}
+
+/// A C declaration of `declarator`, if every type in it is a primitive or a struct of the
+/// header. Pointers to anything else become `void *`, which has the same size.
+fn c_declaration(expr: &TypeExpr, declarator: &str, known: &HashMap<&str, &str>) -> Option<String> {
+    match expr {
+        TypeExpr::Named(name, size) => {
+            let name = unqualified(name);
+            let ty = match name {
+                "void" | "()" => "void",
+                _ => c_primitive(name, *size).or_else(|| known.get(name).copied())?,
+            };
+            Some(format!("{} {}", ty, declarator))
+        }
+        TypeExpr::Pointer(pointee) => {
+            let inner = match **pointee {
+                TypeExpr::Array(..) | TypeExpr::Function => format!("(*{})", declarator),
+                _ => format!("*{}", declarator),
+            };
+            c_declaration(pointee, &inner, known).or_else(|| Some(format!("void *{}", declarator)))
+        }
+        TypeExpr::Array(element, count) => {
+            c_declaration(element, &format!("{}[{}]", declarator, count), known)
+        }
+        TypeExpr::Function => Some(format!("void {}()", declarator)),
+    }
+}
+
+fn opaque_field(name: &str, size: u64, alignment: Option<u64>, comment: &str) -> String {
+    let align = alignment.filter(|&a| a > 1).map(|a| format!("_Alignas({}) ", a));
+    format!(
+        "    {}unsigned char {}[{}]; /* {} */\n",
+        align.unwrap_or_default(),
+        name,
+        size,
+        comment
+    )
+}
+
+/// Bitfields in bit order, with unnamed fields for the gaps between them.
+fn bitfields(group: &[&MemberLayout], storage: u64) -> String {
+    let fallback = match storage {
+        1 => "unsigned char",
+        2 => "unsigned short",
+        8 => "unsigned long long",
+        _ => "unsigned int",
+    };
+    let mut sorted = group.to_vec();
+    sorted.sort_by_key(|m| m.bit_offset.unwrap_or(0));
+
+    let mut output = String::new();
+    let mut next_bit = 0;
+    for member in sorted {
+        let ty = c_primitive(unqualified(&member.type_name), member.size).unwrap_or(fallback);
+        let bits = member.bit_size.unwrap_or(0);
+        if let Some(bit_offset) = member.bit_offset {
+            if bit_offset > next_bit {
+                output.push_str(&format!("    {} : {};\n", ty, bit_offset - next_bit));
+            }
+            next_bit = bit_offset + bits;
+        }
+        if is_identifier(&member.name) {
+            output.push_str(&format!("    {} {} : {};\n", ty, member.name, bits));
+        } else {
+            output.push_str(&format!("    {} : {};\n", ty, bits));
+        }
+    }
+    output
+}
+
+fn unqualified(mut type_name: &str) -> &str {
+    while let Some(rest) = QUALIFIERS.iter().find_map(|q| type_name.strip_prefix(q)) {
+        type_name = rest;
+    }
+    type_name.trim()
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(name: &str, type_name: &str, offset: u64, size: u64) -> MemberLayout {
+        let mut m =
+            MemberLayout::new(name.to_string(), type_name.to_string(), Some(offset), Some(size));
+        m.alignment = Some(size.min(8));
+        m
+    }
+
+    fn layout(name: &str, size: u64, members: Vec<MemberLayout>) -> StructLayout {
+        let mut l = StructLayout::new(name.to_string(), size, None);
+        l.members = members;
+        l
+    }
+
+    #[test]
+    fn definitions_spell_out_padding_and_assert_offsets() {
+        let layouts = [
+            layout(
+                "Outer",
+                16,
+                vec![
+                    member("tag", "char", 0, 1),
+                    member("inner", "Inner", 4, 8),
+                    member("n", "MyInt", 12, 4),
+                ],
+            ),
+            layout("Inner", 8, vec![member("x", "int", 0, 4), member("next", "*Inner", 4, 4)]),
+            layout("Vec<int>", 24, vec![]),
+        ];
+        let out = HeaderFormatter::new("LAYOUT_H").format(&layouts);
+        assert!(out.starts_with("/* Code generated by layout-audit emit-header; DO NOT EDIT."));
+        assert!(out.contains("#ifndef LAYOUT_H\n#define LAYOUT_H\n"));
+        assert!(out.contains("typedef struct Inner Inner;\ntypedef struct Outer Outer;\n"));
+        // Inner is defined first: Outer embeds it.
+        let inner = out.find("struct Inner {").unwrap();
+        assert!(inner < out.find("struct Outer {").unwrap());
+        assert!(out.contains("struct Inner {\n    int x;\n    Inner *next;\n};\n"));
+        assert!(out.contains(
+            "struct Outer {\n    char tag;\n    char _pad0[3];\n    Inner inner;\n    \
+             _Alignas(4) unsigned char n[4]; /* MyInt */\n};\n"
+        ));
+        assert!(
+            out.contains("_Static_assert(sizeof(struct Outer) == 16, \"Outer: size is 16\");\n")
+        );
+        assert!(out.contains(
+            "_Static_assert(offsetof(struct Outer, inner) == 4, \"Outer: inner is at offset 4\");\n"
+        ));
+        assert!(!out.contains("Vec"));
+        assert!(out.ends_with("#endif /* LAYOUT_H */\n"));
+    }
+
+    #[test]
+    fn bitfields_keep_their_bit_positions() {
+        let bit = |name: &str, bit_offset, bit_size| {
+            let mut m = member(name, "unsigned int", 0, 4);
+            m.bit_offset = Some(bit_offset);
+            m.bit_size = Some(bit_size);
+            m
+        };
+        let flags =
+            layout("Flags", 8, vec![bit("b", 4, 3), bit("a", 0, 1), member("c", "char", 1, 1)]);
+        let out = HeaderFormatter::new("H").format(&[flags]);
+        assert!(out.contains(
+            "    unsigned int a : 1;\n    unsigned int : 3;\n    unsigned int b : 3;\n    char c;\n    \
+             char _pad0[4];\n"
+        ));
+        assert!(!out.contains("offsetof(struct Flags, a)"));
+        assert!(out.contains("offsetof(struct Flags, c) == 1"));
+    }
+
+    #[test]
+    fn guards_come_from_file_names() {
+        assert_eq!(HeaderFormatter::default_guard(Some("shop-layout.h")), "SHOP_LAYOUT_H");
+        assert_eq!(HeaderFormatter::default_guard(Some("3d.h")), "_3D_H");
+        assert_eq!(HeaderFormatter::default_guard(None), "LAYOUT_AUDIT_H");
+    }
+}
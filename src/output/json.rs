@@ -1,24 +1,213 @@
-use crate::types::StructLayout;
+use crate::analysis::{
+    BinarySummary, CacheLineHeatmap, FileSummary, NamespaceSummary, OwnerSummary, TemplateSummary,
+};
+use crate::dwarf::DwarfFeatures;
+use crate::types::{GlobalFalseSharingWarning, StructLayout};
 use serde::Serialize;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+
+/// Version of the field set `--json-style explicit` promises; bumped when a field is renamed
+/// or removed.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// How inspect JSON treats fields with nothing to report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum JsonStyle {
+    /// Leave out fields that are absent, empty, or false
+    #[default]
+    Compact,
+    /// Write every field (absent ones as null) and a `schema_version`
+    Explicit,
+}
+
+thread_local! {
+    static EXPLICIT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// `skip_serializing_if` for optional fields: omitted unless explicit JSON is being written.
+pub(crate) fn skip_none<T>(value: &Option<T>) -> bool {
+    value.is_none() && !EXPLICIT.get()
+}
+
+/// `skip_serializing_if` for list fields: omitted when empty unless explicit JSON is being
+/// written.
+pub(crate) fn skip_empty<T>(value: &[T]) -> bool {
+    value.is_empty() && !EXPLICIT.get()
+}
+
+/// `skip_serializing_if` for map fields: omitted when empty unless explicit JSON is being
+/// written.
+pub(crate) fn skip_empty_map<K, V>(value: &BTreeMap<K, V>) -> bool {
+    value.is_empty() && !EXPLICIT.get()
+}
+
+/// `skip_serializing_if` for flags: omitted when false unless explicit JSON is being written.
+pub(crate) fn skip_false(value: &bool) -> bool {
+    !*value && !EXPLICIT.get()
+}
+
+/// Turns explicit serialization on for this thread until dropped.
+struct ExplicitGuard(bool);
+
+impl ExplicitGuard {
+    fn new(explicit: bool) -> Self {
+        Self(EXPLICIT.replace(explicit))
+    }
+}
+
+impl Drop for ExplicitGuard {
+    fn drop(&mut self) {
+        EXPLICIT.set(self.0);
+    }
+}
 
 #[derive(Serialize)]
 struct Output<'a> {
     version: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_version: Option<u32>,
     structs: &'a [StructLayout],
+    #[serde(skip_serializing_if = "skip_none")]
+    cache_heatmap: Option<&'a CacheLineHeatmap>,
+    #[serde(skip_serializing_if = "skip_none")]
+    files: Option<&'a [FileSummary]>,
+    #[serde(skip_serializing_if = "skip_none")]
+    owners: Option<&'a [OwnerSummary]>,
+    #[serde(skip_serializing_if = "skip_none")]
+    namespaces: Option<&'a [NamespaceSummary]>,
+    #[serde(skip_serializing_if = "skip_none")]
+    templates: Option<&'a [TemplateSummary]>,
+    #[serde(skip_serializing_if = "skip_none")]
+    global_false_sharing: Option<&'a [GlobalFalseSharingWarning]>,
+    #[serde(skip_serializing_if = "skip_none")]
+    summary: Option<&'a BinarySummary>,
+    #[serde(skip_serializing_if = "skip_empty")]
+    debug_info: &'a [DebugInfo],
+}
+
+#[derive(Serialize)]
+struct SummaryOutput<'a> {
+    version: &'static str,
+    summary: &'a BinarySummary,
+}
+
+/// The DWARF features of one inspected binary, for the `debug_info` section.
+#[derive(Debug, Clone, Serialize)]
+pub struct DebugInfo {
+    pub binary: String,
+    #[serde(flatten)]
+    pub features: DwarfFeatures,
+    /// Flags that would make partial results complete; see [`DwarfFeatures::hints`].
+    #[serde(skip_serializing_if = "skip_empty")]
+    pub hints: Vec<String>,
+}
+
+impl DebugInfo {
+    pub fn new(binary: String, features: DwarfFeatures) -> Self {
+        let hints = features.hints();
+        Self { binary, features, hints }
+    }
 }
 
 pub struct JsonFormatter {
     pretty: bool,
+    style: JsonStyle,
+    debug_info: Vec<DebugInfo>,
+    templates: Option<Vec<TemplateSummary>>,
+    summary: Option<BinarySummary>,
 }
 
 impl JsonFormatter {
     pub fn new(pretty: bool) -> Self {
-        Self { pretty }
+        Self {
+            pretty,
+            style: JsonStyle::Compact,
+            debug_info: Vec::new(),
+            templates: None,
+            summary: None,
+        }
+    }
+
+    pub fn with_style(mut self, style: JsonStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Report the DWARF features of each binary in a top-level `debug_info` list.
+    pub fn with_debug_info(mut self, debug_info: Vec<DebugInfo>) -> Self {
+        self.debug_info = debug_info;
+        self
+    }
+
+    /// List collapsed template instantiations in a top-level `templates` array.
+    pub fn with_templates(mut self, templates: Option<Vec<TemplateSummary>>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Add binary-wide totals in a top-level `summary` object.
+    pub fn with_summary(mut self, summary: Option<BinarySummary>) -> Self {
+        self.summary = summary;
+        self
+    }
+
+    /// Binary-wide totals alone, for the summary command.
+    pub fn format_summary(&self, summary: &BinarySummary) -> String {
+        let output = SummaryOutput { version: env!("CARGO_PKG_VERSION"), summary };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+                .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+        } else {
+            serde_json::to_string(&output).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+        }
     }
 
     pub fn format(&self, layouts: &[StructLayout]) -> String {
-        let output = Output { version: env!("CARGO_PKG_VERSION"), structs: layouts };
+        self.format_with_heatmap(layouts, None)
+    }
+
+    pub fn format_with_heatmap(
+        &self,
+        layouts: &[StructLayout],
+        cache_heatmap: Option<&CacheLineHeatmap>,
+    ) -> String {
+        self.format_with_summaries(layouts, cache_heatmap, None, None, None, None)
+    }
+
+    /// One struct as a single line of JSON Lines output, in this formatter's style. `pretty`
+    /// does not apply: a line holds exactly one object.
+    pub fn format_line(&self, layout: &StructLayout) -> String {
+        let _guard = ExplicitGuard::new(self.style == JsonStyle::Explicit);
+        serde_json::to_string(layout).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
 
+    /// Full inspect output, with the optional aggregate sections alongside the structs.
+    pub fn format_with_summaries(
+        &self,
+        layouts: &[StructLayout],
+        cache_heatmap: Option<&CacheLineHeatmap>,
+        files: Option<&[FileSummary]>,
+        owners: Option<&[OwnerSummary]>,
+        namespaces: Option<&[NamespaceSummary]>,
+        global_false_sharing: Option<&[GlobalFalseSharingWarning]>,
+    ) -> String {
+        let explicit = self.style == JsonStyle::Explicit;
+        let output = Output {
+            version: env!("CARGO_PKG_VERSION"),
+            schema_version: explicit.then_some(JSON_SCHEMA_VERSION),
+            structs: layouts,
+            cache_heatmap,
+            files,
+            owners,
+            namespaces,
+            templates: self.templates.as_deref(),
+            global_false_sharing,
+            summary: self.summary.as_ref(),
+            debug_info: &self.debug_info,
+        };
+
+        let _guard = ExplicitGuard::new(explicit);
         if self.pretty {
             serde_json::to_string_pretty(&output)
                 .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
@@ -31,7 +220,7 @@ impl JsonFormatter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{LayoutMetrics, StructLayout};
+    use crate::types::{LayoutMetrics, MemberLayout, StructLayout};
 
     fn layout(name: &str) -> StructLayout {
         let mut s = StructLayout::new(name.to_string(), 8, Some(8));
@@ -47,6 +236,37 @@ mod tests {
         assert!(parsed["structs"].is_array());
     }
 
+    #[test]
+    fn json_formatter_explicit_style_writes_every_field() {
+        let mut s = layout("Foo");
+        s.members = vec![MemberLayout::new("a".to_string(), "u64".to_string(), Some(0), Some(8))];
+
+        let compact: serde_json::Value =
+            serde_json::from_str(&JsonFormatter::new(false).format(std::slice::from_ref(&s)))
+                .unwrap();
+        assert!(compact.get("schema_version").is_none());
+        assert!(compact.get("files").is_none());
+        assert!(compact["structs"][0].get("source_location").is_none());
+        assert!(compact["structs"][0]["members"][0].get("is_atomic").is_none());
+
+        let debug_info = || vec![DebugInfo::new("bin".to_string(), DwarfFeatures::default())];
+        let formatter = JsonFormatter::new(false).with_debug_info(debug_info());
+        let compact: serde_json::Value =
+            serde_json::from_str(&formatter.format(std::slice::from_ref(&s))).unwrap();
+        assert!(compact["debug_info"][0].get("target").is_none());
+
+        let formatter =
+            JsonFormatter::new(false).with_style(JsonStyle::Explicit).with_debug_info(debug_info());
+        let explicit: serde_json::Value = serde_json::from_str(&formatter.format(&[s])).unwrap();
+        assert!(explicit["debug_info"][0]["target"].is_null());
+        assert_eq!(explicit["schema_version"], JSON_SCHEMA_VERSION);
+        assert!(explicit["files"].is_null());
+        assert!(explicit["structs"][0]["source_location"].is_null());
+        assert_eq!(explicit["structs"][0]["members"][0]["is_atomic"], false);
+        assert!(explicit["structs"][0]["members"][0]["bit_offset"].is_null());
+        assert_eq!(explicit["structs"][0]["metrics"]["partial_members"], serde_json::json!([]));
+    }
+
     #[test]
     fn json_formatter_compact() {
         let formatter = JsonFormatter::new(false);
@@ -0,0 +1,103 @@
+//! Output formatters for the locate command.
+
+use crate::locate::FieldLocation;
+use colored::Colorize;
+use comfy_table::{Cell, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+
+pub struct LocateTableFormatter {
+    no_color: bool,
+}
+
+impl LocateTableFormatter {
+    pub fn new(no_color: bool) -> Self {
+        Self { no_color }
+    }
+
+    pub fn format(&self, location: &FieldLocation) -> String {
+        let path = location.field_path();
+        let mut output = format!(
+            "{}+{:#x} ({} of {} bytes): {}\n",
+            location.type_name,
+            location.offset,
+            location.offset,
+            location.type_size,
+            if self.no_color { path } else { path.bold().to_string() }
+        );
+
+        if !location.path.is_empty() {
+            let mut table = Table::new();
+            table.load_preset(UTF8_FULL_CONDENSED);
+            table.set_header(vec!["Offset", "Size", "Type", "Member", "In"]);
+            for step in &location.path {
+                let name = match (step.index, step.bit_offset, step.bit_size) {
+                    (Some(index), _, _) => format!("{}[{}]", step.name, index),
+                    (None, Some(bit_offset), Some(bit_size)) => {
+                        format!("{} (bits {}-{})", step.name, bit_offset, bit_offset + bit_size - 1)
+                    }
+                    _ => step.name.clone(),
+                };
+                table.add_row(vec![
+                    Cell::new(format!("{:#x}", step.offset)),
+                    Cell::new(step.size),
+                    Cell::new(&step.type_name),
+                    Cell::new(name),
+                    Cell::new(&step.parent),
+                ]);
+            }
+            output.push('\n');
+            output.push_str(&table.to_string());
+            output.push('\n');
+        }
+
+        if let Some(hole) = &location.padding {
+            let note = format!(
+                "Padding: {} byte(s) at {:#x}..{:#x}{}; nothing should write here",
+                hole.size,
+                hole.offset,
+                hole.offset + hole.size,
+                hole.after_member.as_ref().map(|m| format!(" after `{}`", m)).unwrap_or_default()
+            );
+            output.push('\n');
+            if self.no_color {
+                output.push_str(&note);
+            } else {
+                output.push_str(&note.yellow().to_string());
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+#[derive(Serialize)]
+struct LocateJsonOutput<'a> {
+    version: &'static str,
+    field_path: String,
+    #[serde(flatten)]
+    location: &'a FieldLocation,
+}
+
+pub struct LocateJsonFormatter {
+    pretty: bool,
+}
+
+impl LocateJsonFormatter {
+    pub fn new(pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    pub fn format(&self, location: &FieldLocation) -> String {
+        let output = LocateJsonOutput {
+            version: env!("CARGO_PKG_VERSION"),
+            field_path: location.field_path(),
+            location,
+        };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+        } else {
+            serde_json::to_string(&output)
+        }
+        .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
@@ -1,9 +1,44 @@
+mod annotate;
+mod code;
+mod columns;
+mod csv;
+mod depfile;
+mod determinism;
+mod forecast;
+mod go_assert;
+mod graph;
+mod header;
 mod json;
+mod locate;
+mod placement;
 mod sarif;
+mod schema;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 mod suggest;
 mod table;
+mod targets;
+mod verify;
 
-pub use json::JsonFormatter;
-pub use sarif::{CheckViolation, CheckViolationKind, SarifFormatter};
+pub use annotate::{AnnotateJsonFormatter, AnnotateTableFormatter};
+pub use code::{CodeFormatter, CodeLanguage};
+pub use columns::Column;
+pub use csv::CsvFormatter;
+pub use depfile::DepfileFormatter;
+pub use determinism::{DeterminismJsonFormatter, DeterminismTableFormatter};
+pub use forecast::{ForecastJsonFormatter, ForecastTableFormatter};
+pub use go_assert::GoAssertionFormatter;
+pub use graph::{GraphDotFormatter, GraphJsonFormatter};
+pub use header::HeaderFormatter;
+pub use json::{DebugInfo, JSON_SCHEMA_VERSION, JsonFormatter, JsonStyle};
+pub(crate) use json::{skip_empty, skip_empty_map, skip_false, skip_none};
+pub use locate::{LocateJsonFormatter, LocateTableFormatter};
+pub use placement::{PlacementJsonFormatter, PlacementTableFormatter};
+pub use sarif::{CheckViolation, CheckViolationKind, IgnoredViolation, SarifFormatter, Severity};
+pub use schema::{SchemaJsonFormatter, SchemaTableFormatter};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{SQLITE_SCHEMA_VERSION, SqliteExporter, SqliteRun};
 pub use suggest::{SuggestJsonFormatter, SuggestTableFormatter};
 pub use table::TableFormatter;
+pub use targets::{TargetsJsonFormatter, TargetsTableFormatter};
+pub use verify::{VerifyJsonFormatter, VerifyTableFormatter};
@@ -0,0 +1,94 @@
+//! Output formatters for the placement command.
+
+use crate::placement::{Placement, SharedBy};
+use colored::Colorize;
+use comfy_table::{Cell, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+
+pub struct PlacementTableFormatter {
+    no_color: bool,
+}
+
+impl PlacementTableFormatter {
+    pub fn new(no_color: bool) -> Self {
+        Self { no_color }
+    }
+
+    pub fn format(&self, placement: &Placement) -> String {
+        if placement.advice.is_empty() {
+            let mut output = "No shared structs need cache line isolation.\n".to_string();
+            if !placement.already_isolated.is_empty() {
+                output.push_str(&format!(
+                    "Already isolated: {}\n",
+                    placement.already_isolated.join(", ")
+                ));
+            }
+            return output;
+        }
+
+        let header = format!(
+            "{} shared struct(s) to isolate on {}-byte lines; padding adds {} bytes (one instance of each)",
+            placement.advice.len(),
+            placement.line,
+            placement.total_cost_bytes
+        );
+        let mut output = if self.no_color { header } else { header.bold().to_string() };
+        output.push('\n');
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        table.set_header(vec!["Struct", "Shared by", "Size", "Padded", "Cost"]);
+        for advice in &placement.advice {
+            let shared_by = match &advice.shared_by {
+                SharedBy::Owners { owners } => format!("threads: {}", owners.join(", ")),
+                SharedBy::Atomics { members } => format!("atomics: {}", members.join(", ")),
+            };
+            table.add_row(vec![
+                Cell::new(&advice.struct_name),
+                Cell::new(shared_by),
+                Cell::new(advice.size.to_string()),
+                Cell::new(advice.padded_size.to_string()),
+                Cell::new(format!("+{}", advice.cost_bytes)),
+            ]);
+        }
+        output.push_str(&table.to_string());
+        output.push_str("\n\nWrappers:\n");
+        for advice in &placement.advice {
+            output.push_str(&format!("  {}\n", advice.wrapper));
+        }
+        if !placement.already_isolated.is_empty() {
+            output.push_str(&format!(
+                "\nAlready isolated: {}\n",
+                placement.already_isolated.join(", ")
+            ));
+        }
+        output
+    }
+}
+
+#[derive(Serialize)]
+struct PlacementJsonOutput<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    placement: &'a Placement,
+}
+
+pub struct PlacementJsonFormatter {
+    pretty: bool,
+}
+
+impl PlacementJsonFormatter {
+    pub fn new(pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    pub fn format(&self, placement: &Placement) -> String {
+        let output = PlacementJsonOutput { version: env!("CARGO_PKG_VERSION"), placement };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+        } else {
+            serde_json::to_string(&output)
+        }
+        .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
@@ -1,9 +1,9 @@
 use crate::analysis::OptimizedLayout;
-use crate::diff::DiffResult;
+use crate::diff::{BuildComparison, BuildDifferenceKind, DiffResult};
 use crate::types::{SourceLocation, StructLayout};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 const SARIF_VERSION: &str = "2.1.0";
 const SARIF_SCHEMA: &str = "https://schemastore.azurewebsites.net/schemas/json/sarif-2.1.0.json";
@@ -13,20 +13,161 @@ const TOOL_URI: &str = "https://github.com/avifenesh/layout-audit";
 const RULE_SIZE_INCREASE: &str = "LAYOUT-SIZE-INCREASE";
 const RULE_PADDING_INCREASE: &str = "LAYOUT-PADDING-INCREASE";
 const RULE_BUDGET_SIZE: &str = "LAYOUT-BUDGET-SIZE";
+const RULE_BUDGET_MIN_SIZE: &str = "LAYOUT-BUDGET-MIN-SIZE";
+const RULE_BUDGET_EXACT_SIZE: &str = "LAYOUT-BUDGET-EXACT-SIZE";
 const RULE_BUDGET_PADDING: &str = "LAYOUT-BUDGET-PADDING";
 const RULE_BUDGET_PADDING_PERCENT: &str = "LAYOUT-BUDGET-PADDING-PERCENT";
 const RULE_BUDGET_FALSE_SHARING: &str = "LAYOUT-BUDGET-FALSE-SHARING";
+const RULE_BUDGET_CACHE_LINES: &str = "LAYOUT-BUDGET-CACHE-LINES";
+const RULE_BUDGET_OVER_FETCH: &str = "LAYOUT-BUDGET-OVER-FETCH";
+const RULE_BUDGET_ALLOC_SLACK: &str = "LAYOUT-BUDGET-ALLOC-SLACK";
+const RULE_BUDGET_STRIDE: &str = "LAYOUT-BUDGET-STRIDE";
+const RULE_BUDGET_MEMBERS: &str = "LAYOUT-BUDGET-MEMBERS";
+const RULE_BUDGET_MEMBER_SIZE: &str = "LAYOUT-BUDGET-MEMBER-SIZE";
+const RULE_BUDGET_MEMBER_OFFSET: &str = "LAYOUT-BUDGET-MEMBER-OFFSET";
+const RULE_BUDGET_MEMBER_CACHE_LINE: &str = "LAYOUT-BUDGET-MEMBER-CACHE-LINE";
+const RULE_BUDGET_MEMBER_PINNED: &str = "LAYOUT-BUDGET-MEMBER-PINNED";
+const RULE_BUDGET_FORBIDDEN_MEMBER: &str = "LAYOUT-BUDGET-FORBIDDEN-MEMBER";
+const RULE_LOCKED_SIZE: &str = "LAYOUT-LOCKED-SIZE";
+const RULE_FROZEN_LAYOUT: &str = "LAYOUT-FROZEN-CHANGED";
+const RULE_NEW_STRUCT_POLICY: &str = "LAYOUT-NEW-STRUCT-POLICY";
 const RULE_PADDING: &str = "LAYOUT-PADDING";
+const RULE_PADDING_HOLE: &str = "LAYOUT-PADDING-HOLE";
 const RULE_FALSE_SHARING: &str = "LAYOUT-FALSE-SHARING";
 const RULE_REORDER_SUGGESTION: &str = "LAYOUT-REORDER-SUGGESTION";
+const RULE_RESULTS_TRUNCATED: &str = "LAYOUT-RESULTS-TRUNCATED";
+const RULE_BUILD_CONFIG: &str = "LAYOUT-BUILD-CONFIG";
+const RULE_BUILD_UNEXPLAINED: &str = "LAYOUT-BUILD-UNEXPLAINED";
 
 #[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum CheckViolationKind {
     MaxSize,
+    /// Size fell below the budget's `min_size`.
+    MinSize,
+    /// Size differs from the budget's `exact_size`.
+    ExactSize,
     MaxPaddingBytes,
     MaxPaddingPercent,
     MaxFalseSharingWarnings,
+    MaxAllocSlack,
+    MaxCacheLines,
+    /// A read of the struct's declared hot members loads too many bytes per byte used.
+    MaxOverFetch,
+    MaxStrideFactor,
+    MaxMembers,
+    MaxSingleMemberSize,
+    /// A budgeted member sits past its `max_offset`.
+    MemberMaxOffset,
+    /// A budgeted member is not on the same cache line as its `same_cache_line_as` partner.
+    MemberSameCacheLine,
+    /// A member is not at the exact offset `member_offsets` or its `members` entry pins it to.
+    MemberOffset,
+    /// A member stores a type listed in `forbid_members_of_type` inline.
+    ForbiddenMemberType,
+    /// Size grew past the size accepted in the lockfile.
+    LockedSize,
+    /// A struct listed in `frozen_layouts` changed since the previous build.
+    FrozenLayout,
+}
+
+impl CheckViolationKind {
+    /// The budget key for this limit, as written in the config and in
+    /// `layout-audit: allow(...)` markers.
+    pub fn budget_key(self) -> &'static str {
+        match self {
+            CheckViolationKind::MaxSize => "max_size",
+            CheckViolationKind::MinSize => "min_size",
+            CheckViolationKind::ExactSize => "exact_size",
+            CheckViolationKind::MaxPaddingBytes => "max_padding",
+            CheckViolationKind::MaxPaddingPercent => "max_padding_percent",
+            CheckViolationKind::MaxFalseSharingWarnings => "max_false_sharing_warnings",
+            CheckViolationKind::MaxAllocSlack => "max_alloc_slack",
+            CheckViolationKind::MaxCacheLines => "max_cache_lines",
+            CheckViolationKind::MaxOverFetch => "max_over_fetch",
+            CheckViolationKind::MaxStrideFactor => "max_stride_factor",
+            CheckViolationKind::MaxMembers => "max_members",
+            CheckViolationKind::MaxSingleMemberSize => "max_single_member_size",
+            CheckViolationKind::MemberMaxOffset => "max_offset",
+            CheckViolationKind::MemberSameCacheLine => "same_cache_line_as",
+            CheckViolationKind::MemberOffset => "member_offsets",
+            CheckViolationKind::ForbiddenMemberType => "forbid_members_of_type",
+            CheckViolationKind::LockedSize => "locked_size",
+            CheckViolationKind::FrozenLayout => "frozen_layout",
+        }
+    }
+
+    pub const ALL: [CheckViolationKind; 18] = [
+        CheckViolationKind::MaxSize,
+        CheckViolationKind::MinSize,
+        CheckViolationKind::ExactSize,
+        CheckViolationKind::MaxPaddingBytes,
+        CheckViolationKind::MaxPaddingPercent,
+        CheckViolationKind::MaxFalseSharingWarnings,
+        CheckViolationKind::MaxAllocSlack,
+        CheckViolationKind::MaxCacheLines,
+        CheckViolationKind::MaxOverFetch,
+        CheckViolationKind::MaxStrideFactor,
+        CheckViolationKind::MaxMembers,
+        CheckViolationKind::MaxSingleMemberSize,
+        CheckViolationKind::MemberMaxOffset,
+        CheckViolationKind::MemberSameCacheLine,
+        CheckViolationKind::MemberOffset,
+        CheckViolationKind::ForbiddenMemberType,
+        CheckViolationKind::LockedSize,
+        CheckViolationKind::FrozenLayout,
+    ];
+}
+
+/// How much a violation matters. Budgets set it with `severity`; `--fail-on` names the lowest
+/// severity that fails the run, so lower ones are reported without failing CI.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Default,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Reported for information only
+    Info,
+    /// Advisory: reported, fails only with `--fail-on warn` or `info`
+    Warn,
+    /// A hard failure
+    #[default]
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+        }
+    }
+
+    /// The SARIF result level for violations of this severity.
+    pub fn sarif_level(self) -> &'static str {
+        match self {
+            Severity::Info => "note",
+            Severity::Warn => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -34,21 +175,65 @@ pub struct CheckViolation {
     pub struct_name: String,
     pub kind: CheckViolationKind,
     pub message: String,
+    pub severity: Severity,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_location: Option<SourceLocation>,
+    /// True if the limit came from `new_struct_defaults` rather than a named budget.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub new_struct_policy: bool,
+}
+
+/// A violation by a struct on the config's `ignore` list.
+#[derive(Debug, Clone, Serialize)]
+pub struct IgnoredViolation {
+    #[serde(flatten)]
+    pub violation: CheckViolation,
+    /// The `ignore` pattern that matched the struct.
+    pub pattern: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires: Option<String>,
 }
 
 pub struct SarifFormatter {
     tool_version: &'static str,
+    max_results: Option<usize>,
+}
+
+/// A SARIF result plus the ranking used to decide what survives `max_results` truncation.
+struct Finding {
+    severity: u8,
+    magnitude: u64,
+    result: Value,
 }
 
 impl SarifFormatter {
     pub fn new() -> Self {
-        Self { tool_version: env!("CARGO_PKG_VERSION") }
+        Self { tool_version: env!("CARGO_PKG_VERSION"), max_results: None }
+    }
+
+    /// Cap the number of results per run (GitHub code scanning rejects oversized uploads).
+    /// When exceeded, the highest-severity, largest findings are kept and the last slot holds
+    /// a summary result counting what was dropped.
+    pub fn with_max_results(mut self, max_results: Option<usize>) -> Self {
+        self.max_results = max_results;
+        self
     }
 
     pub fn format_diff(&self, diff: &DiffResult, error_on_regression: bool) -> String {
-        let mut results: Vec<Value> = Vec::new();
+        self.format_diff_with_policy(diff, error_on_regression, &[])
+    }
+
+    /// Like [`SarifFormatter::format_diff`], plus `new_struct_defaults` violations by added
+    /// structs.
+    pub fn format_diff_with_policy(
+        &self,
+        diff: &DiffResult,
+        error_on_regression: bool,
+        policy_violations: &[CheckViolation],
+    ) -> String {
+        let mut results: Vec<Finding> = Vec::new();
         let mut used_rules: BTreeSet<&'static str> = BTreeSet::new();
         let level = if error_on_regression { "error" } else { "warning" };
 
@@ -70,6 +255,7 @@ impl SarifFormatter {
                         "new_size": change.new_size,
                         "delta": change.size_delta,
                     })),
+                    change.size_delta.unsigned_abs(),
                 ));
             }
 
@@ -90,36 +276,70 @@ impl SarifFormatter {
                         "new_padding": change.new_padding,
                         "delta": change.padding_delta,
                     })),
+                    change.padding_delta.unsigned_abs(),
                 ));
             }
         }
 
-        let rules = build_rules(&used_rules);
-        render_sarif(self.tool_version, rules, results)
+        for v in policy_violations {
+            results.push(violation_result(v, &mut used_rules));
+        }
+
+        self.render(used_rules, results)
     }
 
     pub fn format_check(&self, violations: &[CheckViolation]) -> String {
-        let mut results: Vec<Value> = Vec::new();
+        self.format_check_with_suppressed(violations, &[])
+    }
+
+    /// Like [`SarifFormatter::format_check`], also listing violations acknowledged by an
+    /// in-source marker as results with an `inSource` suppression. They rank last when
+    /// `max_results` truncates.
+    pub fn format_check_with_suppressed(
+        &self,
+        violations: &[CheckViolation],
+        suppressed: &[CheckViolation],
+    ) -> String {
+        self.format_check_with_ignored(violations, suppressed, &[])
+    }
+
+    /// Like [`SarifFormatter::format_check_with_suppressed`], plus violations by structs on
+    /// the config's `ignore` list, as results with an `external` suppression carrying the
+    /// entry's reason.
+    pub fn format_check_with_ignored(
+        &self,
+        violations: &[CheckViolation],
+        suppressed: &[CheckViolation],
+        ignored: &[IgnoredViolation],
+    ) -> String {
+        let mut results: Vec<Finding> = Vec::new();
         let mut used_rules: BTreeSet<&'static str> = BTreeSet::new();
 
         for v in violations {
-            let rule_id = rule_id_for_kind(v.kind);
-            used_rules.insert(rule_id);
-            results.push(make_result(
-                rule_id,
-                "error",
-                v.message.clone(),
-                v.source_location.as_ref(),
-                Some(json!({ "struct": v.struct_name })),
-            ));
+            results.push(violation_result(v, &mut used_rules));
+        }
+        for v in suppressed {
+            let mut finding = violation_result(v, &mut used_rules);
+            finding.result["suppressions"] = json!([{ "kind": "inSource" }]);
+            finding.severity = 0;
+            results.push(finding);
+        }
+        for ignored in ignored {
+            let mut finding = violation_result(&ignored.violation, &mut used_rules);
+            let mut suppression = json!({ "kind": "external" });
+            if let Some(reason) = &ignored.reason {
+                suppression["justification"] = json!(reason);
+            }
+            finding.result["suppressions"] = json!([suppression]);
+            finding.severity = 0;
+            results.push(finding);
         }
 
-        let rules = build_rules(&used_rules);
-        render_sarif(self.tool_version, rules, results)
+        self.render(used_rules, results)
     }
 
     pub fn format_inspect(&self, layouts: &[StructLayout]) -> String {
-        let mut results: Vec<Value> = Vec::new();
+        let mut results: Vec<Finding> = Vec::new();
         let mut used_rules: BTreeSet<&'static str> = BTreeSet::new();
 
         for layout in layouts {
@@ -144,7 +364,39 @@ impl SarifFormatter {
                         "padding_percent": layout.metrics.padding_percentage,
                         "cache_lines_spanned": layout.metrics.cache_lines_spanned,
                     })),
+                    layout.metrics.padding_bytes,
                 ));
+
+                // Member-level results point at the field preceding each hole, so inline
+                // annotations land on the declaration that causes the padding.
+                for hole in &layout.metrics.padding_holes {
+                    let Some(member) = hole.after_member.as_deref().and_then(|name| {
+                        layout
+                            .members
+                            .iter()
+                            .find(|m| m.name == name && m.source_location.is_some())
+                    }) else {
+                        continue;
+                    };
+                    used_rules.insert(RULE_PADDING_HOLE);
+                    let message = format!(
+                        "{} byte(s) of padding after {}.{} at offset {}",
+                        hole.size, layout.name, member.name, hole.offset
+                    );
+                    results.push(make_result(
+                        RULE_PADDING_HOLE,
+                        "note",
+                        message,
+                        member.source_location.as_ref(),
+                        Some(json!({
+                            "struct": layout.name,
+                            "member": member.name,
+                            "offset": hole.offset,
+                            "size": hole.size,
+                        })),
+                        hole.size,
+                    ));
+                }
             }
 
             if let Some(fs) = layout.metrics.false_sharing.as_ref() {
@@ -166,13 +418,13 @@ impl SarifFormatter {
                             "false_sharing_warnings": warning_count,
                             "spanning_warnings": spanning_count,
                         })),
+                        (warning_count + spanning_count) as u64,
                     ));
                 }
             }
         }
 
-        let rules = build_rules(&used_rules);
-        render_sarif(self.tool_version, rules, results)
+        self.render(used_rules, results)
     }
 
     pub fn format_suggest(
@@ -180,11 +432,12 @@ impl SarifFormatter {
         suggestions: &[OptimizedLayout],
         locations: &[Option<SourceLocation>],
     ) -> String {
-        let mut results: Vec<Value> = Vec::new();
+        let mut results: Vec<Finding> = Vec::new();
         let mut used_rules: BTreeSet<&'static str> = BTreeSet::new();
 
         for (idx, suggestion) in suggestions.iter().enumerate() {
-            if suggestion.savings_bytes == 0 {
+            // Frozen layouts must keep their order, so there is nothing to act on.
+            if suggestion.savings_bytes == 0 || suggestion.frozen {
                 continue;
             }
             let location = locations.get(idx).and_then(|loc| loc.as_ref());
@@ -204,12 +457,57 @@ impl SarifFormatter {
                     "optimized_size": suggestion.optimized_size,
                     "savings_bytes": suggestion.savings_bytes,
                     "savings_percent": suggestion.savings_percent,
+                    "optimized_order": suggestion.optimized_order(),
                 })),
+                suggestion.savings_bytes,
             ));
         }
 
-        let rules = build_rules(&used_rules);
-        render_sarif(self.tool_version, rules, results)
+        self.render(used_rules, results)
+    }
+
+    /// Differences explained by build configuration are notes; the rest are warnings so they
+    /// stand out from the expected debug/release drift.
+    pub fn format_compare_builds(&self, comparison: &BuildComparison) -> String {
+        let mut results: Vec<Finding> = Vec::new();
+        let mut used_rules: BTreeSet<&'static str> = BTreeSet::new();
+
+        for difference in &comparison.differences {
+            let change = &difference.change;
+            let (rule, level, reason) = match difference.kind {
+                BuildDifferenceKind::ConditionalMembers => {
+                    (RULE_BUILD_CONFIG, "note", "members present in only one build")
+                }
+                BuildDifferenceKind::MemberTypes => {
+                    (RULE_BUILD_CONFIG, "note", "member types differ between builds")
+                }
+                BuildDifferenceKind::Unexplained => {
+                    (RULE_BUILD_UNEXPLAINED, "warning", "same members laid out differently")
+                }
+            };
+            used_rules.insert(rule);
+            let message = format!(
+                "Struct {} is {} bytes in debug and {} bytes in release ({})",
+                change.name, change.old_size, change.new_size, reason
+            );
+            results.push(make_result(
+                rule,
+                level,
+                message,
+                change.source_location.as_ref(),
+                Some(json!({
+                    "struct": change.name,
+                    "kind": difference.kind,
+                    "debug_size": change.old_size,
+                    "release_size": change.new_size,
+                    "debug_padding": change.old_padding,
+                    "release_padding": change.new_padding,
+                })),
+                change.size_delta.unsigned_abs(),
+            ));
+        }
+
+        self.render(used_rules, results)
     }
 }
 
@@ -219,12 +517,43 @@ impl Default for SarifFormatter {
     }
 }
 
+fn violation_result(v: &CheckViolation, used_rules: &mut BTreeSet<&'static str>) -> Finding {
+    let (rule_id, properties) = if v.new_struct_policy {
+        (RULE_NEW_STRUCT_POLICY, json!({ "struct": v.struct_name, "limit": v.kind }))
+    } else {
+        (rule_id_for_kind(v.kind), json!({ "struct": v.struct_name }))
+    };
+    used_rules.insert(rule_id);
+    make_result(
+        rule_id,
+        v.severity.sarif_level(),
+        v.message.clone(),
+        v.source_location.as_ref(),
+        Some(properties),
+        0,
+    )
+}
+
 fn rule_id_for_kind(kind: CheckViolationKind) -> &'static str {
     match kind {
         CheckViolationKind::MaxSize => RULE_BUDGET_SIZE,
+        CheckViolationKind::MinSize => RULE_BUDGET_MIN_SIZE,
+        CheckViolationKind::ExactSize => RULE_BUDGET_EXACT_SIZE,
         CheckViolationKind::MaxPaddingBytes => RULE_BUDGET_PADDING,
         CheckViolationKind::MaxPaddingPercent => RULE_BUDGET_PADDING_PERCENT,
         CheckViolationKind::MaxFalseSharingWarnings => RULE_BUDGET_FALSE_SHARING,
+        CheckViolationKind::MaxAllocSlack => RULE_BUDGET_ALLOC_SLACK,
+        CheckViolationKind::MaxCacheLines => RULE_BUDGET_CACHE_LINES,
+        CheckViolationKind::MaxOverFetch => RULE_BUDGET_OVER_FETCH,
+        CheckViolationKind::MaxStrideFactor => RULE_BUDGET_STRIDE,
+        CheckViolationKind::MaxMembers => RULE_BUDGET_MEMBERS,
+        CheckViolationKind::MaxSingleMemberSize => RULE_BUDGET_MEMBER_SIZE,
+        CheckViolationKind::MemberMaxOffset => RULE_BUDGET_MEMBER_OFFSET,
+        CheckViolationKind::MemberSameCacheLine => RULE_BUDGET_MEMBER_CACHE_LINE,
+        CheckViolationKind::MemberOffset => RULE_BUDGET_MEMBER_PINNED,
+        CheckViolationKind::ForbiddenMemberType => RULE_BUDGET_FORBIDDEN_MEMBER,
+        CheckViolationKind::LockedSize => RULE_LOCKED_SIZE,
+        CheckViolationKind::FrozenLayout => RULE_FROZEN_LAYOUT,
     }
 }
 
@@ -251,6 +580,12 @@ fn rule_metadata(rule_id: &str) -> (&'static str, &'static str) {
             ("Struct padding increased", "Struct padding increased relative to baseline")
         }
         RULE_BUDGET_SIZE => ("Budget: size", "Struct size exceeded budget"),
+        RULE_BUDGET_MIN_SIZE => {
+            ("Budget: minimum size", "Struct shrank below the budget's minimum size")
+        }
+        RULE_BUDGET_EXACT_SIZE => {
+            ("Budget: exact size", "Struct size differs from the size the budget requires")
+        }
         RULE_BUDGET_PADDING => ("Budget: padding bytes", "Struct padding bytes exceeded budget"),
         RULE_BUDGET_PADDING_PERCENT => {
             ("Budget: padding percent", "Struct padding percentage exceeded budget")
@@ -258,22 +593,142 @@ fn rule_metadata(rule_id: &str) -> (&'static str, &'static str) {
         RULE_BUDGET_FALSE_SHARING => {
             ("Budget: false sharing", "Struct false sharing warnings exceeded budget")
         }
+        RULE_BUDGET_CACHE_LINES => {
+            ("Budget: cache lines", "Struct spans more cache lines than the budget allows")
+        }
+        RULE_BUDGET_OVER_FETCH => (
+            "Budget: over-fetch",
+            "Reading the struct's hot members loads more bytes per byte used than the budget allows",
+        ),
+        RULE_BUDGET_ALLOC_SLACK => (
+            "Budget: allocation slack",
+            "Struct leaves more of its allocator size class unused than the budget allows",
+        ),
+        RULE_BUDGET_STRIDE => (
+            "Budget: array stride",
+            "Struct's array stride exceeds its member bytes by more than the budget's factor",
+        ),
+        RULE_BUDGET_MEMBERS => {
+            ("Budget: member count", "Struct has more members than the budget allows")
+        }
+        RULE_BUDGET_MEMBER_SIZE => {
+            ("Budget: member size", "Struct member is larger than the budget allows")
+        }
+        RULE_BUDGET_MEMBER_OFFSET => {
+            ("Budget: member offset", "Struct member sits past the offset the budget allows")
+        }
+        RULE_BUDGET_MEMBER_CACHE_LINE => (
+            "Budget: member cache line",
+            "Struct member is not on the same cache line as the member the budget pairs it with",
+        ),
+        RULE_BUDGET_MEMBER_PINNED => {
+            ("Budget: pinned member offset", "Struct member is not at the offset the budget pins")
+        }
+        RULE_BUDGET_FORBIDDEN_MEMBER => (
+            "Budget: forbidden member type",
+            "Struct stores a member of a type the budget forbids inline",
+        ),
+        RULE_LOCKED_SIZE => {
+            ("Locked size", "Struct grew past the size accepted in layout-audit.lock")
+        }
+        RULE_FROZEN_LAYOUT => {
+            ("Frozen layout changed", "A struct listed in frozen_layouts changed its layout")
+        }
+        RULE_NEW_STRUCT_POLICY => {
+            ("New struct policy", "Newly added struct exceeds the default limits for new structs")
+        }
         RULE_PADDING => ("Padding detected", "Struct contains padding bytes"),
+        RULE_PADDING_HOLE => ("Padding hole", "Padding follows this member"),
         RULE_FALSE_SHARING => ("Potential false sharing", "Atomic members share cache lines"),
         RULE_REORDER_SUGGESTION => {
             ("Reorder suggestion", "Struct can be reordered to reduce padding")
         }
+        RULE_BUILD_CONFIG => {
+            ("Build configuration difference", "Layout differs between debug and release builds")
+        }
+        RULE_BUILD_UNEXPLAINED => {
+            ("Unexplained build difference", "Layout differs between builds with identical members")
+        }
+        RULE_RESULTS_TRUNCATED => {
+            ("Results truncated", "Lower-priority results were omitted to respect the result limit")
+        }
         _ => ("Layout issue", "Layout-audit reported an issue"),
     }
 }
 
+impl SarifFormatter {
+    fn render(&self, mut used_rules: BTreeSet<&'static str>, findings: Vec<Finding>) -> String {
+        let results = match self.max_results {
+            Some(max) if findings.len() > max => {
+                let kept = max.saturating_sub(1);
+                let total = findings.len();
+
+                // Rank by severity, then magnitude; ties keep emission order (stable sort).
+                let mut ranked: Vec<usize> = (0..total).collect();
+                ranked.sort_by_key(|&i| {
+                    std::cmp::Reverse((findings[i].severity, findings[i].magnitude))
+                });
+                let mut keep = vec![false; total];
+                for &i in &ranked[..kept] {
+                    keep[i] = true;
+                }
+
+                let mut omitted_by_rule: BTreeMap<String, u64> = BTreeMap::new();
+                let mut results = Vec::with_capacity(max);
+                for (finding, keep) in findings.into_iter().zip(keep) {
+                    if keep {
+                        results.push(finding.result);
+                    } else if let Some(rule) = finding.result["ruleId"].as_str() {
+                        *omitted_by_rule.entry(rule.to_string()).or_default() += 1;
+                    }
+                }
+
+                let omitted = total - kept;
+                used_rules.insert(RULE_RESULTS_TRUNCATED);
+                results.push(
+                    make_result(
+                        RULE_RESULTS_TRUNCATED,
+                        "note",
+                        format!(
+                            "{} of {} result(s) omitted to stay within the limit of {}",
+                            omitted, total, max
+                        ),
+                        None,
+                        Some(json!({
+                            "total_results": total,
+                            "omitted_results": omitted,
+                            "omitted_by_rule": omitted_by_rule,
+                        })),
+                        0,
+                    )
+                    .result,
+                );
+                results
+            }
+            _ => findings.into_iter().map(|f| f.result).collect(),
+        };
+
+        render_sarif(self.tool_version, build_rules(&used_rules), results)
+    }
+}
+
+fn severity_rank(level: &str) -> u8 {
+    match level {
+        "error" => 3,
+        "warning" => 2,
+        "note" => 1,
+        _ => 0,
+    }
+}
+
 fn make_result(
     rule_id: &str,
     level: &str,
     message: String,
     source_location: Option<&SourceLocation>,
     properties: Option<Value>,
-) -> Value {
+    magnitude: u64,
+) -> Finding {
     let mut result = json!({
         "ruleId": rule_id,
         "level": level,
@@ -294,7 +749,7 @@ fn make_result(
         result["properties"] = props;
     }
 
-    result
+    Finding { severity: severity_rank(level), magnitude, result }
 }
 
 fn render_sarif(tool_version: &str, rules: Vec<Value>, results: Vec<Value>) -> String {
@@ -320,10 +775,13 @@ fn render_sarif(tool_version: &str, rules: Vec<Value>, results: Vec<Value>) -> S
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::diff::{DiffResult, MemberChange, MemberChangeKind, StructChange, StructSummary};
+    use crate::analysis::{OptimizeStrategy, OptimizedMember};
+    use crate::diff::{
+        BuildDifference, DiffResult, MemberChange, MemberChangeKind, StructChange, StructSummary,
+    };
     use crate::types::{
         CacheLineSpanningWarning, FalseSharingAnalysis, FalseSharingWarning, LayoutMetrics,
-        SourceLocation, StructLayout,
+        MemberLayout, PaddingHole, SourceLocation, StructLayout,
     };
 
     fn parse_sarif(s: &str) -> Value {
@@ -366,6 +824,7 @@ mod tests {
                 kind: MemberChangeKind::Added,
                 name: "x".to_string(),
                 details: "offset Some(8), size Some(4)".to_string(),
+                source_location: None,
             }],
             source_location: Some(SourceLocation { file: "src/foo.c".to_string(), line: 10 }),
             old_source_location: None,
@@ -394,6 +853,115 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_sarif_marks_suppressed_results() {
+        let violation = CheckViolation {
+            struct_name: "Foo".to_string(),
+            kind: CheckViolationKind::MaxSize,
+            message: "too big".to_string(),
+            source_location: None,
+            new_struct_policy: false,
+            severity: Severity::Error,
+        };
+        let both = [violation.clone(), violation];
+        let sarif = SarifFormatter::new().format_check_with_suppressed(&both[..1], &both[1..]);
+        let results = parse_sarif(&sarif)["runs"][0]["results"].as_array().unwrap().clone();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].get("suppressions").is_none());
+        assert_eq!(results[1]["suppressions"][0]["kind"], "inSource");
+    }
+
+    #[test]
+    fn check_sarif_marks_ignored_results() {
+        let violation = CheckViolation {
+            struct_name: "LegacyFoo".to_string(),
+            kind: CheckViolationKind::MaxPaddingBytes,
+            message: "padded".to_string(),
+            source_location: None,
+            new_struct_policy: false,
+            severity: Severity::Error,
+        };
+        let ignored = IgnoredViolation {
+            violation,
+            pattern: "Legacy*".to_string(),
+            reason: Some("wire format".to_string()),
+            expires: None,
+        };
+        let sarif = SarifFormatter::new().format_check_with_ignored(&[], &[], &[ignored]);
+        let results = parse_sarif(&sarif)["runs"][0]["results"].as_array().unwrap().clone();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["suppressions"][0]["kind"], "external");
+        assert_eq!(results[0]["suppressions"][0]["justification"], "wire format");
+    }
+
+    #[test]
+    fn diff_sarif_reports_new_struct_policy() {
+        let diff = DiffResult {
+            added: vec![StructSummary {
+                name: "Bar".to_string(),
+                size: 256,
+                padding_bytes: 0,
+                source_location: None,
+            }],
+            removed: Vec::new(),
+            changed: Vec::new(),
+            unchanged_count: 0,
+        };
+        let policy = vec![CheckViolation {
+            struct_name: "Bar".to_string(),
+            kind: CheckViolationKind::MaxCacheLines,
+            message: "Bar: new struct spans 4 cache lines, default allows 2".to_string(),
+            source_location: None,
+            new_struct_policy: true,
+            severity: Severity::Error,
+        }];
+
+        let sarif = SarifFormatter::new().format_diff_with_policy(&diff, false, &policy);
+        let parsed = parse_sarif(&sarif);
+        let results = parsed["runs"][0]["results"].as_array().unwrap();
+        let policy_result = results.iter().find(|r| r["ruleId"] == RULE_NEW_STRUCT_POLICY).unwrap();
+        assert_eq!(policy_result["level"], "error");
+        assert_eq!(policy_result["properties"]["limit"], "max_cache_lines");
+        let rules = parsed["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap();
+        assert!(rules.iter().any(|r| r["id"] == RULE_NEW_STRUCT_POLICY));
+    }
+
+    #[test]
+    fn compare_builds_sarif_separates_unexplained() {
+        let change = |name: &str| StructChange {
+            name: name.to_string(),
+            old_size: 16,
+            new_size: 8,
+            size_delta: -8,
+            old_padding: 0,
+            new_padding: 0,
+            padding_delta: 0,
+            member_changes: Vec::new(),
+            source_location: None,
+            old_source_location: None,
+        };
+        let comparison = BuildComparison {
+            debug_only: Vec::new(),
+            release_only: Vec::new(),
+            differences: vec![
+                BuildDifference {
+                    kind: BuildDifferenceKind::ConditionalMembers,
+                    change: change("Gated"),
+                },
+                BuildDifference { kind: BuildDifferenceKind::Unexplained, change: change("Moved") },
+            ],
+            unchanged_count: 0,
+        };
+
+        let parsed = parse_sarif(&SarifFormatter::new().format_compare_builds(&comparison));
+        let results = parsed["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results[0]["ruleId"], RULE_BUILD_CONFIG);
+        assert_eq!(results[0]["level"], "note");
+        assert_eq!(results[0]["properties"]["kind"], "conditional_members");
+        assert_eq!(results[1]["ruleId"], RULE_BUILD_UNEXPLAINED);
+        assert_eq!(results[1]["level"], "warning");
+    }
+
     #[test]
     fn check_sarif_empty() {
         let formatter = SarifFormatter::new();
@@ -412,6 +980,8 @@ mod tests {
                 kind: CheckViolationKind::MaxSize,
                 message: "Foo: size 16 exceeds budget 8 (+8 bytes)".to_string(),
                 source_location: Some(SourceLocation { file: "src/foo.c".to_string(), line: 5 }),
+                new_struct_policy: false,
+                severity: Severity::Error,
             },
             CheckViolation {
                 struct_name: "Bar".to_string(),
@@ -419,6 +989,8 @@ mod tests {
                 message: "Bar: padding 50.0% exceeds budget 10.0% (+40.0 percentage points)"
                     .to_string(),
                 source_location: None,
+                new_struct_policy: false,
+                severity: Severity::Warn,
             },
         ];
         let sarif = formatter.format_check(&violations);
@@ -427,6 +999,8 @@ mod tests {
         assert_eq!(results.len(), 2);
         assert_eq!(results[0]["ruleId"], RULE_BUDGET_SIZE);
         assert_eq!(results[1]["ruleId"], RULE_BUDGET_PADDING_PERCENT);
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[1]["level"], "warning");
     }
 
     #[test]
@@ -462,6 +1036,77 @@ mod tests {
         assert_eq!(results.len(), 2);
     }
 
+    #[test]
+    fn inspect_sarif_padding_hole_points_at_member() {
+        let formatter = SarifFormatter::new();
+        let mut layout = basic_layout("Foo");
+        layout.metrics.padding_bytes = 4;
+        layout.source_location = Some(SourceLocation { file: "src/foo.c".to_string(), line: 3 });
+        let mut member = MemberLayout::new("a".to_string(), "int".to_string(), Some(0), Some(4));
+        member.source_location = Some(SourceLocation { file: "src/foo.c".to_string(), line: 4 });
+        layout.members =
+            vec![member, MemberLayout::new("b".to_string(), "long".to_string(), Some(8), Some(8))];
+        layout.metrics.padding_holes =
+            vec![PaddingHole { offset: 4, size: 4, after_member: Some("a".to_string()) }];
+
+        let sarif = formatter.format_inspect(&[layout]);
+        let parsed = parse_sarif(&sarif);
+        let results = parsed["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1]["ruleId"], RULE_PADDING_HOLE);
+        assert_eq!(results[1]["locations"][0]["physicalLocation"]["region"]["startLine"], 4);
+        assert_eq!(results[1]["properties"]["member"], "a");
+    }
+
+    #[test]
+    fn inspect_sarif_max_results_keeps_worst_offenders() {
+        let layouts: Vec<StructLayout> = [2u64, 9, 5]
+            .iter()
+            .enumerate()
+            .map(|(i, &padding)| {
+                let mut layout = basic_layout(&format!("S{}", i));
+                layout.metrics.padding_bytes = padding;
+                layout
+            })
+            .collect();
+
+        let sarif = SarifFormatter::new().with_max_results(Some(2)).format_inspect(&layouts);
+        let parsed = parse_sarif(&sarif);
+        let results = parsed["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["properties"]["struct"], "S1");
+        assert_eq!(results[1]["ruleId"], RULE_RESULTS_TRUNCATED);
+        assert_eq!(results[1]["properties"]["omitted_results"], 2);
+        assert_eq!(results[1]["properties"]["omitted_by_rule"][RULE_PADDING], 2);
+
+        let rules = parsed["runs"][0]["tool"]["driver"]["rules"].as_array().unwrap();
+        assert!(rules.iter().any(|r| r["id"] == RULE_RESULTS_TRUNCATED));
+    }
+
+    #[test]
+    fn sarif_max_results_only_truncates_when_exceeded() {
+        let violations = vec![CheckViolation {
+            struct_name: "Foo".to_string(),
+            kind: CheckViolationKind::MaxSize,
+            message: "too big".to_string(),
+            source_location: None,
+            new_struct_policy: false,
+            severity: Severity::Error,
+        }];
+        let check = SarifFormatter::new().with_max_results(Some(1)).format_check(&violations);
+        let results = parse_sarif(&check)["runs"][0]["results"].as_array().unwrap().clone();
+        assert_eq!(results.len(), 1, "limit not exceeded, no summary");
+        assert_eq!(results[0]["ruleId"], RULE_BUDGET_SIZE);
+
+        let formatter = SarifFormatter::new().with_max_results(Some(1));
+        let mut layout = basic_layout("Foo");
+        layout.metrics.padding_bytes = 4;
+        let sarif = formatter.format_inspect(&[layout.clone(), layout]);
+        let results = parse_sarif(&sarif)["runs"][0]["results"].as_array().unwrap().clone();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], RULE_RESULTS_TRUNCATED);
+    }
+
     #[test]
     fn suggest_sarif_skips_zero_savings() {
         let formatter = SarifFormatter::new();
@@ -476,12 +1121,31 @@ mod tests {
             optimized_members: Vec::new(),
             skipped_members: Vec::new(),
             has_bitfields: false,
+            strategy: OptimizeStrategy::Greedy,
+            search_timed_out: false,
+            strategy_disagreement: Vec::new(),
+            access_simulation: None,
+            locality: None,
+            pack: None,
+            frozen: false,
+            usage: None,
         };
         let mut savings = no_savings.clone();
         savings.name = "Savings".to_string();
         savings.optimized_size = 12;
         savings.savings_bytes = 4;
         savings.savings_percent = 25.0;
+        savings.optimized_members = vec![OptimizedMember {
+            name: "b".to_string(),
+            type_name: "long".to_string(),
+            offset: 0,
+            size: 8,
+            alignment: 8,
+            alignment_source: crate::analysis::AlignmentSource::Natural,
+            bit_offset: None,
+            bit_size: None,
+            type_tag: None,
+        }];
 
         let sarif = formatter.format_suggest(
             &[no_savings, savings],
@@ -491,5 +1155,8 @@ mod tests {
         let results = parsed["runs"][0]["results"].as_array().unwrap();
         assert_eq!(results.len(), 1);
         assert_eq!(results[0]["ruleId"], RULE_REORDER_SUGGESTION);
+        let order = &results[0]["properties"]["optimized_order"];
+        assert_eq!(order[0]["name"], "b");
+        assert_eq!(order[0]["offset"], 0);
     }
 }
@@ -0,0 +1,82 @@
+//! Output formatters for the schema-check command.
+
+use crate::schema::SchemaReport;
+use colored::Colorize;
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+
+pub struct SchemaTableFormatter {
+    no_color: bool,
+}
+
+impl SchemaTableFormatter {
+    pub fn new(no_color: bool) -> Self {
+        Self { no_color }
+    }
+
+    pub fn format(&self, report: &SchemaReport) -> String {
+        let mut output = format!(
+            "{} message(s) checked against the binary, {} field mismatch(es); {} message(s) \
+             without a struct in the debug info\n",
+            report.checked,
+            report.mismatches.len(),
+            report.missing.len()
+        );
+        if report.mismatches.is_empty() {
+            return output;
+        }
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        table.set_header(vec!["Message", "Struct", "Field", "Mismatch"]);
+        for mismatch in &report.mismatches {
+            let kind = Cell::new(mismatch.kind.label());
+            table.add_row(vec![
+                Cell::new(&mismatch.message),
+                Cell::new(&mismatch.struct_name),
+                Cell::new(&mismatch.field),
+                if self.no_color { kind } else { kind.fg(Color::Red) },
+            ]);
+        }
+        output.push('\n');
+        output.push_str(&table.to_string());
+        output.push('\n');
+
+        let note = "\nThe generated code and the schema disagree: regenerate the code, or map the \
+                    message to its struct in `schema_structs` if the wrong struct was matched";
+        if self.no_color {
+            output.push_str(note);
+        } else {
+            output.push_str(&note.yellow().to_string());
+        }
+        output.push('\n');
+        output
+    }
+}
+
+#[derive(Serialize)]
+struct SchemaJsonOutput<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    report: &'a SchemaReport,
+}
+
+pub struct SchemaJsonFormatter {
+    pretty: bool,
+}
+
+impl SchemaJsonFormatter {
+    pub fn new(pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    pub fn format(&self, report: &SchemaReport) -> String {
+        let output = SchemaJsonOutput { version: env!("CARGO_PKG_VERSION"), report };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+        } else {
+            serde_json::to_string(&output)
+        }
+        .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
@@ -0,0 +1,299 @@
+//! SQLite export of inspect results, normalized for cross-run SQL queries.
+//!
+//! Every export appends one row to `runs`; structs, members, padding holes, and
+//! false-sharing warnings reference it, so repeated runs accumulate in the same file.
+
+use crate::error::{Error, Result};
+use crate::types::StructLayout;
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bumped whenever the table layout changes incompatibly.
+pub const SQLITE_SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS schema_version (
+    version INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    created_at INTEGER NOT NULL,
+    tool_version TEXT NOT NULL,
+    binary TEXT NOT NULL,
+    cache_line_size INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS structs (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    name TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    alignment INTEGER,
+    useful_size INTEGER NOT NULL,
+    padding_bytes INTEGER NOT NULL,
+    padding_percent REAL NOT NULL,
+    cache_lines_spanned INTEGER NOT NULL,
+    cache_line_density REAL NOT NULL,
+    partial INTEGER NOT NULL,
+    source_file TEXT,
+    source_line INTEGER
+);
+CREATE TABLE IF NOT EXISTS members (
+    struct_id INTEGER NOT NULL REFERENCES structs(id),
+    position INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    type_name TEXT NOT NULL,
+    offset INTEGER,
+    size INTEGER,
+    bit_offset INTEGER,
+    bit_size INTEGER,
+    is_atomic INTEGER NOT NULL,
+    source_file TEXT,
+    source_line INTEGER
+);
+CREATE TABLE IF NOT EXISTS holes (
+    struct_id INTEGER NOT NULL REFERENCES structs(id),
+    offset INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    after_member TEXT
+);
+CREATE TABLE IF NOT EXISTS warnings (
+    struct_id INTEGER NOT NULL REFERENCES structs(id),
+    kind TEXT NOT NULL,
+    member TEXT NOT NULL,
+    other_member TEXT,
+    cache_line INTEGER NOT NULL,
+    detail INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS structs_run_name ON structs(run_id, name);
+";
+
+/// Metadata recorded in the `runs` table for one export.
+pub struct SqliteRun<'a> {
+    pub binary: &'a str,
+    pub cache_line_size: u32,
+}
+
+pub struct SqliteExporter {
+    conn: Connection,
+}
+
+impl SqliteExporter {
+    /// Open (or create) an export database, creating the schema on first use.
+    /// Fails if the file was written by an incompatible schema version.
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+
+        let version: Option<i64> = conn
+            .query_row("SELECT MAX(version) FROM schema_version", [], |row| row.get(0))
+            .optional()?
+            .flatten();
+        match version {
+            None => {
+                conn.execute(
+                    "INSERT INTO schema_version (version) VALUES (?1)",
+                    [SQLITE_SCHEMA_VERSION],
+                )?;
+            }
+            Some(SQLITE_SCHEMA_VERSION) => {}
+            Some(found) => {
+                return Err(Error::Sqlite(format!(
+                    "{} has schema version {}, expected {}",
+                    path.display(),
+                    found,
+                    SQLITE_SCHEMA_VERSION
+                )));
+            }
+        }
+
+        Ok(Self { conn })
+    }
+
+    /// Append one run and its analyzed layouts. Returns the new run id.
+    pub fn export(&mut self, run: &SqliteRun<'_>, layouts: &[StructLayout]) -> Result<i64> {
+        let tx = self.conn.transaction()?;
+        let created_at =
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+        tx.execute(
+            "INSERT INTO runs (created_at, tool_version, binary, cache_line_size)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![created_at as i64, env!("CARGO_PKG_VERSION"), run.binary, run.cache_line_size],
+        )?;
+        let run_id = tx.last_insert_rowid();
+
+        for layout in layouts {
+            insert_struct(&tx, run_id, layout)?;
+        }
+
+        tx.commit()?;
+        Ok(run_id)
+    }
+}
+
+/// SQLite integers are signed 64-bit; saturate rather than wrap for absurd DWARF values.
+fn int(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+fn insert_struct(tx: &Transaction<'_>, run_id: i64, layout: &StructLayout) -> Result<()> {
+    let m = &layout.metrics;
+    tx.execute(
+        "INSERT INTO structs (run_id, name, size, alignment, useful_size, padding_bytes,
+             padding_percent, cache_lines_spanned, cache_line_density, partial,
+             source_file, source_line)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            run_id,
+            layout.name,
+            int(layout.size),
+            layout.alignment.map(int),
+            int(m.useful_size),
+            int(m.padding_bytes),
+            m.padding_percentage,
+            m.cache_lines_spanned,
+            m.cache_line_density,
+            m.partial,
+            layout.source_location.as_ref().map(|l| l.file.as_str()),
+            layout.source_location.as_ref().map(|l| int(l.line)),
+        ],
+    )?;
+    let struct_id = tx.last_insert_rowid();
+
+    let mut member_stmt = tx.prepare_cached(
+        "INSERT INTO members (struct_id, position, name, type_name, offset, size, bit_offset,
+             bit_size, is_atomic, source_file, source_line)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+    )?;
+    for (position, member) in layout.members.iter().enumerate() {
+        member_stmt.execute(params![
+            struct_id,
+            position as i64,
+            member.name,
+            member.type_name,
+            member.offset.map(int),
+            member.size.map(int),
+            member.bit_offset.map(int),
+            member.bit_size.map(int),
+            member.is_atomic,
+            member.source_location.as_ref().map(|l| l.file.as_str()),
+            member.source_location.as_ref().map(|l| int(l.line)),
+        ])?;
+    }
+
+    let mut hole_stmt = tx.prepare_cached(
+        "INSERT INTO holes (struct_id, offset, size, after_member) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for hole in &m.padding_holes {
+        hole_stmt.execute(params![
+            struct_id,
+            int(hole.offset),
+            int(hole.size),
+            hole.after_member
+        ])?;
+    }
+
+    if let Some(fs) = &m.false_sharing {
+        let mut warning_stmt = tx.prepare_cached(
+            "INSERT INTO warnings (struct_id, kind, member, other_member, cache_line, detail)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for w in &fs.warnings {
+            warning_stmt.execute(params![
+                struct_id,
+                "false_sharing",
+                w.member_a,
+                w.member_b,
+                int(w.cache_line),
+                w.gap_bytes
+            ])?;
+        }
+        for w in &fs.spanning_warnings {
+            warning_stmt.execute(params![
+                struct_id,
+                "cache_line_spanning",
+                w.member,
+                None::<String>,
+                int(w.start_cache_line),
+                int(w.lines_spanned)
+            ])?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FalseSharingAnalysis, FalseSharingWarning, MemberLayout, PaddingHole};
+
+    fn sample_layout() -> StructLayout {
+        let mut layout = StructLayout::new("Foo".to_string(), 16, Some(8));
+        layout.members = vec![
+            MemberLayout::new("a".to_string(), "char".to_string(), Some(0), Some(1)),
+            MemberLayout::new("b".to_string(), "long".to_string(), Some(8), Some(8)),
+        ];
+        layout.metrics.padding_bytes = 7;
+        layout.metrics.padding_holes =
+            vec![PaddingHole { offset: 1, size: 7, after_member: Some("a".to_string()) }];
+        layout.metrics.false_sharing = Some(FalseSharingAnalysis {
+            warnings: vec![FalseSharingWarning {
+                member_a: "a".to_string(),
+                member_b: "b".to_string(),
+                cache_line: 0,
+                gap_bytes: 7,
+            }],
+            ..Default::default()
+        });
+        layout
+    }
+
+    fn count(conn: &Connection, table: &str) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn export_appends_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.db");
+        let run = SqliteRun { binary: "bin/app", cache_line_size: 64 };
+
+        let first = SqliteExporter::open(&path).unwrap().export(&run, &[sample_layout()]).unwrap();
+        let second = SqliteExporter::open(&path).unwrap().export(&run, &[sample_layout()]).unwrap();
+        assert_ne!(first, second);
+
+        let conn = Connection::open(&path).unwrap();
+        assert_eq!(count(&conn, "schema_version"), 1);
+        assert_eq!(count(&conn, "runs"), 2);
+        assert_eq!(count(&conn, "structs"), 2);
+        assert_eq!(count(&conn, "members"), 4);
+        assert_eq!(count(&conn, "holes"), 2);
+        assert_eq!(count(&conn, "warnings"), 2);
+
+        let padding: i64 = conn
+            .query_row(
+                "SELECT s.padding_bytes FROM structs s JOIN runs r ON s.run_id = r.id
+                 WHERE r.id = ?1 AND s.name = 'Foo'",
+                [second],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(padding, 7);
+    }
+
+    #[test]
+    fn open_rejects_other_schema_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.db");
+        drop(SqliteExporter::open(&path).unwrap());
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute("UPDATE schema_version SET version = 99", []).unwrap();
+        drop(conn);
+
+        let err = SqliteExporter::open(&path).err().expect("version mismatch");
+        assert!(err.to_string().contains("schema version 99"));
+    }
+}
@@ -1,6 +1,9 @@
 //! Output formatters for suggest command.
 
-use crate::analysis::OptimizedLayout;
+use crate::analysis::{
+    AccessSimulation, EXHAUSTIVE_TIME_LIMIT, LocalityFront, MemberPlacement, OptimizeStrategy,
+    OptimizedLayout, PackedRegion,
+};
 use colored::Colorize;
 use comfy_table::{Cell, Color, Table, presets::UTF8_FULL_CONDENSED};
 use serde::Serialize;
@@ -15,6 +18,15 @@ impl SuggestTableFormatter {
     }
 
     pub fn format(&self, suggestions: &[OptimizedLayout]) -> String {
+        self.format_with_regions(suggestions, &[])
+    }
+
+    /// Suggestions followed by the packed regions the structs were declared in.
+    pub fn format_with_regions(
+        &self,
+        suggestions: &[OptimizedLayout],
+        regions: &[PackedRegion],
+    ) -> String {
         let mut output = String::new();
 
         for (i, suggestion) in suggestions.iter().enumerate() {
@@ -24,6 +36,20 @@ impl SuggestTableFormatter {
             output.push_str(&self.format_suggestion(suggestion));
         }
 
+        if !regions.is_empty() {
+            output.push_str("\n\nPacked regions (suggestions keep their pack):\n");
+            for region in regions {
+                output.push_str(&format!(
+                    "  {}:{}-{} pack({}): {}\n",
+                    region.file,
+                    region.first_line,
+                    region.last_line,
+                    region.pack,
+                    region.structs.join(", ")
+                ));
+            }
+        }
+
         output
     }
 
@@ -48,6 +74,9 @@ impl SuggestTableFormatter {
             output.push_str(&header.bold().to_string());
         }
         output.push_str("\n\n");
+        if let Some(usage) = s.usage {
+            output.push_str(&format!("Used by: {}\n\n", usage));
+        }
 
         // Current layout
         output.push_str("Current layout:\n");
@@ -56,11 +85,23 @@ impl SuggestTableFormatter {
 
         // Suggested layout (only if there are savings)
         if s.savings_bytes > 0 {
-            output.push_str("\nSuggested layout:\n");
+            if s.strategy == OptimizeStrategy::Greedy {
+                output.push_str("\nSuggested layout:\n");
+            } else {
+                output.push_str(&format!("\nSuggested layout ({}):\n", s.strategy.name()));
+            }
             output.push_str(&self.format_members_table_colored(&s.optimized_members));
             output.push('\n');
         }
 
+        if let Some(sim) = &s.access_simulation {
+            output.push_str(&self.format_access_simulation(sim));
+        }
+
+        if let Some(front) = &s.locality {
+            output.push_str(&self.format_locality(front));
+        }
+
         // Warnings for skipped members
         if !s.skipped_members.is_empty() {
             let warning = format!(
@@ -76,6 +117,19 @@ impl SuggestTableFormatter {
             output.push('\n');
         }
 
+        if let Some(pack) = s.pack {
+            let note = format!(
+                "\nNote: Packed to {} byte(s); the suggestion aligns no member beyond that.",
+                pack
+            );
+            if self.no_color {
+                output.push_str(&note);
+            } else {
+                output.push_str(&note.cyan().to_string());
+            }
+            output.push('\n');
+        }
+
         // Note about bitfields
         if s.has_bitfields {
             let note = "\nNote: Bitfield members kept together in their storage units.";
@@ -87,8 +141,50 @@ impl SuggestTableFormatter {
             output.push('\n');
         }
 
-        // FFI warning (always show for optimizable structs)
-        if s.savings_bytes > 0 {
+        if s.search_timed_out {
+            let note = format!(
+                "\nNote: Exhaustive search stopped after {} ms; {}",
+                EXHAUSTIVE_TIME_LIMIT.as_millis(),
+                if s.strategy == OptimizeStrategy::Greedy {
+                    "nothing better than greedy was found"
+                } else {
+                    "the suggestion beats greedy but may not be optimal"
+                }
+            );
+            if self.no_color {
+                output.push_str(&note);
+            } else {
+                output.push_str(&note.cyan().to_string());
+            }
+            output.push('\n');
+        }
+
+        if !s.strategy_disagreement.is_empty() {
+            let sizes: Vec<String> = s
+                .strategy_disagreement
+                .iter()
+                .map(|o| format!("{} {} bytes", o.strategy.name(), o.optimized_size))
+                .collect();
+            let note = format!("\nNote: Strategies disagree: {}", sizes.join(", "));
+            if self.no_color {
+                output.push_str(&note);
+            } else {
+                output.push_str(&note.cyan().to_string());
+            }
+            output.push('\n');
+        }
+
+        if s.frozen && s.savings_bytes > 0 {
+            let warning = "\nWarning: Layout frozen by the config's frozen_layouts; do not \
+                           reorder, the layout is an ABI or wire format";
+            if self.no_color {
+                output.push_str(warning);
+            } else {
+                output.push_str(&warning.red().bold().to_string());
+            }
+            output.push('\n');
+        } else if s.savings_bytes > 0 {
+            // FFI warning (always show for optimizable structs)
             let ffi_warning = "\nReordering may affect serialization/FFI compatibility";
             if self.no_color {
                 output.push_str(ffi_warning);
@@ -101,16 +197,79 @@ impl SuggestTableFormatter {
         output
     }
 
+    fn format_access_simulation(&self, sim: &AccessSimulation) -> String {
+        let mut output = format!(
+            "\n{} ({}): {} cache line(s) touched, {} with suggested layout, {} with access-optimized layout\n",
+            if sim.from_static_analysis { "Static access pattern" } else { "Access pattern" },
+            sim.sequence.join(" -> "),
+            sim.original_lines_touched,
+            sim.optimized_lines_touched,
+            sim.locality_lines_touched
+        );
+        if let Some(fetch) = &sim.over_fetch {
+            output.push_str(&format!(
+                "Over-fetch: {} bytes loaded for {} used ({:.1}x); the members fit in {} line(s)\n",
+                fetch.bytes_loaded, fetch.bytes_used, fetch.ratio, fetch.min_lines_touched
+            ));
+        }
+
+        if sim.locality_lines_touched < sim.original_lines_touched.min(sim.optimized_lines_touched)
+        {
+            output.push_str(&format!("\nAccess-optimized layout ({} bytes):\n", sim.locality_size));
+            output.push_str(&self.format_members_table_colored(&sim.locality_members));
+            output.push('\n');
+        }
+
+        if !sim.unknown_members.is_empty() {
+            let warning = format!(
+                "\nWarning: access pattern references unknown member(s): {}",
+                sim.unknown_members.join(", ")
+            );
+            if self.no_color {
+                output.push_str(&warning);
+            } else {
+                output.push_str(&warning.yellow().to_string());
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
+    fn format_locality(&self, front: &LocalityFront) -> String {
+        let groups: Vec<String> = front.groups.iter().map(|g| g.join(" + ")).collect();
+        let mut output = format!(
+            "\nGrouped orderings (at most {} bytes; groups {}: {}):\n",
+            front.size_cap,
+            if front.groups_from_prefixes { "by name prefix" } else { "from config" },
+            groups.join(", ")
+        );
+        if front.orderings.is_empty() {
+            output.push_str("  none fit the size cap\n");
+        }
+        for ordering in &front.orderings {
+            let names: Vec<&str> = ordering.members.iter().map(|m| m.name.as_str()).collect();
+            output.push_str(&format!(
+                "  {} bytes, grouping {:.2}: {}\n",
+                ordering.size,
+                ordering.grouping_score,
+                names.join(", ")
+            ));
+        }
+        output
+    }
+
     fn format_members_table(&self, members: &[crate::analysis::OptimizedMember]) -> String {
         let mut table = Table::new();
         table.load_preset(UTF8_FULL_CONDENSED);
-        table.set_header(vec!["Offset", "Size", "Align", "Type", "Field"]);
+        table.set_header(vec!["Offset", "Size", "Align", "Align from", "Type", "Field"]);
 
         for m in members {
             table.add_row(vec![
                 Cell::new(m.offset.to_string()),
                 Cell::new(m.size.to_string()),
                 Cell::new(m.alignment.to_string()),
+                Cell::new(m.alignment_source.as_str()),
                 Cell::new(&m.type_name),
                 Cell::new(&m.name),
             ]);
@@ -122,7 +281,7 @@ impl SuggestTableFormatter {
     fn format_members_table_colored(&self, members: &[crate::analysis::OptimizedMember]) -> String {
         let mut table = Table::new();
         table.load_preset(UTF8_FULL_CONDENSED);
-        table.set_header(vec!["Offset", "Size", "Align", "Type", "Field"]);
+        table.set_header(vec!["Offset", "Size", "Align", "Align from", "Type", "Field"]);
 
         for m in members {
             let row = if self.no_color {
@@ -130,6 +289,7 @@ impl SuggestTableFormatter {
                     Cell::new(m.offset.to_string()),
                     Cell::new(m.size.to_string()),
                     Cell::new(m.alignment.to_string()),
+                    Cell::new(m.alignment_source.as_str()),
                     Cell::new(&m.type_name),
                     Cell::new(&m.name),
                 ]
@@ -138,6 +298,7 @@ impl SuggestTableFormatter {
                     Cell::new(m.offset.to_string()).fg(Color::Green),
                     Cell::new(m.size.to_string()).fg(Color::Green),
                     Cell::new(m.alignment.to_string()).fg(Color::Green),
+                    Cell::new(m.alignment_source.as_str()).fg(Color::Green),
                     Cell::new(&m.type_name).fg(Color::Green),
                     Cell::new(&m.name).fg(Color::Green),
                 ]
@@ -152,10 +313,19 @@ impl SuggestTableFormatter {
 #[derive(Serialize)]
 struct SuggestJsonOutput<'a> {
     version: &'static str,
-    suggestions: &'a [OptimizedLayout],
+    suggestions: Vec<SuggestionRecord<'a>>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    packed_regions: &'a [PackedRegion],
     summary: SuggestSummary,
 }
 
+#[derive(Serialize)]
+struct SuggestionRecord<'a> {
+    #[serde(flatten)]
+    layout: &'a OptimizedLayout,
+    optimized_order: Vec<MemberPlacement>,
+}
+
 #[derive(Serialize)]
 struct SuggestSummary {
     total_structs: usize,
@@ -173,12 +343,28 @@ impl SuggestJsonFormatter {
     }
 
     pub fn format(&self, suggestions: &[OptimizedLayout]) -> String {
+        self.format_with_regions(suggestions, &[])
+    }
+
+    /// Suggestions plus the packed regions the structs were declared in.
+    pub fn format_with_regions(
+        &self,
+        suggestions: &[OptimizedLayout],
+        regions: &[PackedRegion],
+    ) -> String {
         let optimizable = suggestions.iter().filter(|s| s.savings_bytes > 0).count();
         let total_savings: u64 = suggestions.iter().map(|s| s.savings_bytes).sum();
 
         let output = SuggestJsonOutput {
             version: env!("CARGO_PKG_VERSION"),
-            suggestions,
+            suggestions: suggestions
+                .iter()
+                .map(|layout| SuggestionRecord {
+                    layout,
+                    optimized_order: layout.optimized_order(),
+                })
+                .collect(),
+            packed_regions: regions,
             summary: SuggestSummary {
                 total_structs: suggestions.len(),
                 optimizable_structs: optimizable,
@@ -198,6 +384,7 @@ impl SuggestJsonFormatter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::{OverFetch, StrategyOutcome};
 
     fn suggestion(name: &str, savings: u64) -> OptimizedLayout {
         OptimizedLayout {
@@ -211,6 +398,14 @@ mod tests {
             optimized_members: Vec::new(),
             skipped_members: Vec::new(),
             has_bitfields: false,
+            strategy: OptimizeStrategy::Greedy,
+            search_timed_out: false,
+            strategy_disagreement: Vec::new(),
+            access_simulation: None,
+            locality: None,
+            pack: None,
+            frozen: false,
+            usage: None,
         }
     }
 
@@ -226,6 +421,83 @@ mod tests {
         assert!(out.contains("Bitfield"));
     }
 
+    #[test]
+    fn suggest_table_flags_frozen_layouts() {
+        let mut s = suggestion("Wire", 4);
+        s.frozen = true;
+        let out = SuggestTableFormatter::new(true).format(&[s]);
+        assert!(out.contains("Layout frozen"));
+        assert!(!out.contains("Reordering may affect"));
+    }
+
+    #[test]
+    fn suggest_table_includes_access_simulation() {
+        let mut s = suggestion("Foo", 0);
+        s.access_simulation = Some(AccessSimulation {
+            sequence: vec!["a".to_string(), "b".to_string()],
+            original_lines_touched: 2,
+            optimized_lines_touched: 2,
+            locality_lines_touched: 1,
+            locality_size: 16,
+            locality_members: Vec::new(),
+            over_fetch: Some(OverFetch {
+                bytes_used: 16,
+                bytes_loaded: 128,
+                lines_touched: 2,
+                min_lines_touched: 1,
+                ratio: 8.0,
+            }),
+            unknown_members: vec!["missing".to_string()],
+            from_static_analysis: false,
+        });
+        let formatter = SuggestTableFormatter::new(true);
+        let out = formatter.format(&[s]);
+        assert!(out.contains("Access pattern (a -> b): 2 cache line(s) touched"));
+        assert!(out.contains("Over-fetch: 128 bytes loaded for 16 used (8.0x)"));
+        assert!(out.contains("Access-optimized layout (16 bytes)"));
+        assert!(out.contains("unknown member(s): missing"));
+    }
+
+    #[test]
+    fn suggest_table_lists_grouped_orderings() {
+        let mut s = suggestion("Foo", 0);
+        s.locality = Some(LocalityFront {
+            size_cap: 16,
+            groups: vec![vec!["rx_a".to_string(), "rx_b".to_string()]],
+            groups_from_prefixes: true,
+            orderings: Vec::new(),
+        });
+        let out = SuggestTableFormatter::new(true).format(&[s]);
+        assert!(
+            out.contains(
+                "Grouped orderings (at most 16 bytes; groups by name prefix: rx_a + rx_b)"
+            )
+        );
+        assert!(out.contains("none fit the size cap"));
+    }
+
+    #[test]
+    fn suggest_table_notes_strategy_disagreement() {
+        let mut s = suggestion("Foo", 8);
+        s.strategy = OptimizeStrategy::Pahole;
+        s.strategy_disagreement = vec![
+            StrategyOutcome { strategy: OptimizeStrategy::Greedy, optimized_size: 8 },
+            StrategyOutcome { strategy: OptimizeStrategy::Pahole, optimized_size: 12 },
+        ];
+        let out = SuggestTableFormatter::new(true).format(&[s]);
+        assert!(out.contains("Suggested layout (pahole):"));
+        assert!(out.contains("Strategies disagree: greedy 8 bytes, pahole 12 bytes"));
+    }
+
+    #[test]
+    fn suggest_table_notes_search_timeout() {
+        let mut s = suggestion("Foo", 8);
+        s.search_timed_out = true;
+        let out = SuggestTableFormatter::new(true).format(&[s]);
+        assert!(out.contains("Exhaustive search stopped after 100 ms"));
+        assert!(out.contains("nothing better than greedy was found"));
+    }
+
     #[test]
     fn suggest_table_handles_no_savings() {
         let formatter = SuggestTableFormatter::new(true);
@@ -240,5 +512,6 @@ mod tests {
         let parsed: serde_json::Value = serde_json::from_str(&out).expect("valid JSON");
         assert_eq!(parsed["summary"]["total_structs"], 2);
         assert_eq!(parsed["summary"]["optimizable_structs"], 1);
+        assert!(parsed["suggestions"][0]["optimized_order"].is_array());
     }
 }
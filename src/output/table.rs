@@ -1,30 +1,506 @@
-use crate::types::StructLayout;
+use super::Column;
+use crate::analysis::{
+    BinarySummary, CacheLineHeatmap, FileSummary, NamespaceSummary, OwnerSummary, TemplateSummary,
+    WorstStruct,
+};
+use crate::types::{
+    BitLayout, EnumLayout, GlobalFalseSharingWarning, MemberLayout, SharedGlobal, StructLayout,
+};
 use colored::Colorize;
 use comfy_table::{Cell, CellAlignment, Color, Table, presets::UTF8_FULL_CONDENSED};
+use std::io::{self, Write};
+
+/// Member symbols in a layout map, reused cyclically for structs with more members.
+const LAYOUT_MAP_SYMBOLS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const LAYOUT_MAP_PADDING: char = '.';
+const LAYOUT_MAP_SHARED: char = '+';
+/// Widest row in a layout map; larger cache lines wrap onto several rows.
+const LAYOUT_MAP_MAX_ROW: u64 = 64;
+/// Structs larger than this get no layout map.
+const LAYOUT_MAP_MAX_BYTES: u64 = 64 * 1024;
+/// Stride-to-member-bytes ratio from which the table notes the array stride.
+const STRIDE_NOTE_FACTOR: f64 = 2.0;
+/// Structs rendered per batch by `write_to`; output held in memory never exceeds one batch.
+const RENDER_BATCH: usize = 256;
 
 pub struct TableFormatter {
     no_color: bool,
     cache_line_size: u32,
+    include_partial: bool,
+    layout_map: bool,
+    page_size: Option<usize>,
+    columns: Option<Vec<Column>>,
 }
 
 impl TableFormatter {
     pub fn new(no_color: bool, cache_line_size: u32) -> Self {
-        Self { no_color, cache_line_size }
+        Self {
+            no_color,
+            cache_line_size,
+            include_partial: false,
+            layout_map: false,
+            page_size: None,
+            columns: None,
+        }
+    }
+
+    /// Draw a byte grid under each struct: one character per byte, one cache line (or 64
+    /// bytes, whichever is smaller) per row.
+    pub fn with_layout_map(mut self, layout_map: bool) -> Self {
+        self.layout_map = layout_map;
+        self
+    }
+
+    /// Count partial layouts in the totals line. Off by default because their padding can't
+    /// be measured and would understate the totals.
+    pub fn with_include_partial(mut self, include_partial: bool) -> Self {
+        self.include_partial = include_partial;
+        self
+    }
+
+    /// Start a new page, headed by its struct range, every `page_size` structs.
+    pub fn with_page_size(mut self, page_size: Option<usize>) -> Self {
+        self.page_size = page_size.filter(|&n| n > 0);
+        self
+    }
+
+    /// List one row per struct with these columns, in this order, instead of a member table
+    /// per struct.
+    pub fn with_columns(mut self, columns: Option<Vec<Column>>) -> Self {
+        self.columns = columns;
+        self
     }
 
     pub fn format(&self, layouts: &[StructLayout]) -> String {
+        let mut output = Vec::new();
+        self.write_to(layouts, &mut output).expect("writing to a Vec cannot fail");
+        String::from_utf8(output).expect("table output is UTF-8")
+    }
+
+    /// Write the tables for `layouts` to `out` as they are rendered. Structs are rendered in
+    /// batches across threads, so memory stays flat however many structs there are.
+    pub fn write_to<W: Write>(&self, layouts: &[StructLayout], out: &mut W) -> io::Result<()> {
+        if self.columns.is_some() {
+            return self.write_rows(layouts, out);
+        }
+
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        for (batch_index, batch) in layouts.chunks(RENDER_BATCH).enumerate() {
+            let rendered = self.render_batch(batch, threads);
+            for (offset, text) in rendered.iter().enumerate() {
+                let i = batch_index * RENDER_BATCH + offset;
+                if i > 0 {
+                    out.write_all(b"\n\n")?;
+                }
+                if let Some(page_size) = self.page_size
+                    && i % page_size == 0
+                {
+                    out.write_all(self.page_header(i, page_size, layouts.len()).as_bytes())?;
+                }
+                out.write_all(text.as_bytes())?;
+            }
+        }
+
+        if layouts.len() > 1 {
+            out.write_all(self.format_totals(layouts).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// One row per struct, paged like the member tables.
+    fn write_rows<W: Write>(&self, layouts: &[StructLayout], out: &mut W) -> io::Result<()> {
+        let page_size = self.page_size.unwrap_or(layouts.len()).max(1);
+        for (page, chunk) in layouts.chunks(page_size).enumerate() {
+            if page > 0 {
+                out.write_all(b"\n\n")?;
+            }
+            if self.page_size.is_some() {
+                let header = self.page_header(page * page_size, page_size, layouts.len());
+                out.write_all(header.as_bytes())?;
+            }
+            out.write_all(self.struct_rows(chunk).to_string().as_bytes())?;
+        }
+
+        if layouts.len() > 1 {
+            out.write_all(self.format_totals(layouts).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn struct_rows(&self, layouts: &[StructLayout]) -> Table {
+        let columns = self.columns.as_deref().unwrap_or(Column::DEFAULT);
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        let mut header = vec!["Struct"];
+        header.extend(columns.iter().map(|c| c.header()));
+        table.set_header(header);
+        for layout in layouts {
+            let mut row = vec![Cell::new(&layout.name)];
+            row.extend(columns.iter().map(|column| {
+                let cell = Cell::new(column.value(layout));
+                if column.is_numeric() { cell.set_alignment(CellAlignment::Right) } else { cell }
+            }));
+            table.add_row(row);
+        }
+        table
+    }
+
+    /// Render `batch` in order, splitting it into contiguous chunks across `threads`.
+    fn render_batch(&self, batch: &[StructLayout], threads: usize) -> Vec<String> {
+        if threads <= 1 || batch.len() <= 1 {
+            return batch.iter().map(|l| self.format_struct(l)).collect();
+        }
+        let chunk = batch.len().div_ceil(threads);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .chunks(chunk)
+                .map(|part| {
+                    scope.spawn(move || {
+                        part.iter().map(|l| self.format_struct(l)).collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().expect("table rendering thread panicked"))
+                .collect()
+        })
+    }
+
+    /// Header for the page starting at struct index `first` (0-based).
+    fn page_header(&self, first: usize, page_size: usize, total: usize) -> String {
+        let last = (first + page_size).min(total);
+        let header = format!(
+            "Page {} of {} (structs {}-{} of {})",
+            first / page_size + 1,
+            total.div_ceil(page_size),
+            first + 1,
+            last,
+            total
+        );
+        let header = if self.no_color { header } else { header.bold().to_string() };
+        format!("{}\n\n", header)
+    }
+
+    /// Histogram of final cache line usage, followed by the structs that spill a few bytes
+    /// into an extra line.
+    pub fn format_heatmap(&self, heatmap: &CacheLineHeatmap) -> String {
         let mut output = String::new();
+        let header = format!(
+            "\n\nFinal cache line usage ({} structs, {}-byte lines)",
+            heatmap.total_structs, heatmap.cache_line_size
+        );
+        if self.no_color {
+            output.push_str(&header);
+        } else {
+            output.push_str(&header.bold().to_string());
+        }
+        output.push('\n');
+
+        let max = heatmap.buckets.iter().map(|b| b.structs).max().unwrap_or(0).max(1);
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        table.set_header(vec!["Bytes used", "Structs", "Multi-line", ""]);
+        for bucket in &heatmap.buckets {
+            let bar = "#".repeat(bucket.structs.div_ceil(max.div_ceil(40)));
+            table.add_row(vec![
+                Cell::new(format!("{}-{}", bucket.min_bytes, bucket.max_bytes)),
+                Cell::new(bucket.structs).set_alignment(CellAlignment::Right),
+                Cell::new(bucket.multi_line_structs).set_alignment(CellAlignment::Right),
+                Cell::new(bar),
+            ]);
+        }
+        output.push_str(&table.to_string());
+        output.push('\n');
 
-        for (i, layout) in layouts.iter().enumerate() {
-            if i > 0 {
-                output.push_str("\n\n");
+        if !heatmap.just_over_the_line.is_empty() {
+            output.push_str(&format!(
+                "\nJust over the line ({} struct{}):\n",
+                heatmap.just_over_the_line.len(),
+                if heatmap.just_over_the_line.len() == 1 { "" } else { "s" }
+            ));
+            for s in &heatmap.just_over_the_line {
+                output.push_str(&format!(
+                    "  - {} ({} bytes, {} cache lines): trim {} byte{} to save a line\n",
+                    s.name,
+                    s.size,
+                    s.cache_lines_spanned,
+                    s.overflow_bytes,
+                    if s.overflow_bytes == 1 { "" } else { "s" }
+                ));
             }
-            output.push_str(&self.format_struct(layout));
         }
 
         output
     }
 
+    /// Padding totals per declaring source file.
+    pub fn format_file_summary(&self, files: &[FileSummary]) -> String {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        table.set_header(vec!["File", "Structs", "Size", "Padding", "Padding %", "Worst struct"]);
+        for f in files {
+            let mut row = vec![Cell::new(f.file.as_deref().unwrap_or("(unknown)"))];
+            row.extend(summary_cells(f.structs, f.total_size, f.padding_bytes, &f.worst_struct));
+            table.add_row(row);
+        }
+        self.summary_section(
+            &format!(
+                "Padding by file ({} file{})",
+                files.len(),
+                if files.len() == 1 { "" } else { "s" }
+            ),
+            table,
+        )
+    }
+
+    /// Padding totals per namespace, crate, or Go package.
+    pub fn format_namespace_summary(&self, namespaces: &[NamespaceSummary]) -> String {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        table.set_header(vec![
+            "Namespace",
+            "Structs",
+            "Size",
+            "Padding",
+            "Padding %",
+            "Worst struct",
+        ]);
+        for n in namespaces {
+            let mut row = vec![Cell::new(n.namespace.as_deref().unwrap_or("(global)"))];
+            row.extend(summary_cells(n.structs, n.total_size, n.padding_bytes, &n.worst_struct));
+            table.add_row(row);
+        }
+        self.summary_section(
+            &format!(
+                "Padding by namespace ({} namespace{})",
+                namespaces.len(),
+                if namespaces.len() == 1 { "" } else { "s" }
+            ),
+            table,
+        )
+    }
+
+    /// One row per template whose instantiations were collapsed.
+    pub fn format_template_summary(&self, templates: &[TemplateSummary]) -> String {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        table.set_header(vec![
+            "Template",
+            "Instantiations",
+            "Size",
+            "Padding",
+            "Padding %",
+            "Worst struct",
+        ]);
+        for t in templates {
+            let size = if t.min_size == t.max_size {
+                t.min_size.to_string()
+            } else {
+                format!("{}-{}", t.min_size, t.max_size)
+            };
+            let mut row = vec![Cell::new(&t.template)];
+            row.extend(summary_cells(
+                t.instantiations,
+                t.total_size,
+                t.padding_bytes,
+                &t.worst_struct,
+            ));
+            row[2] = Cell::new(size).set_alignment(CellAlignment::Right);
+            table.add_row(row);
+        }
+        self.summary_section(
+            &format!(
+                "Template instantiations ({} template{})",
+                templates.len(),
+                if templates.len() == 1 { "" } else { "s" }
+            ),
+            table,
+        )
+    }
+
+    /// Pairs of thread-shared globals placed on the same cache line.
+    pub fn format_global_false_sharing(&self, warnings: &[GlobalFalseSharingWarning]) -> String {
+        let title = format!(
+            "Thread-shared globals sharing a cache line ({} pair{})",
+            warnings.len(),
+            if warnings.len() == 1 { "" } else { "s" }
+        );
+        if warnings.is_empty() {
+            let header = format!("\n\n{}\n", title);
+            return if self.no_color { header } else { header.bold().to_string() };
+        }
+
+        let with_binary = warnings.iter().any(|w| w.binary.is_some());
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        let mut header = vec!["Cache line", "First", "Second", "Section"];
+        if with_binary {
+            header.push("Binary");
+        }
+        table.set_header(header);
+        for w in warnings {
+            let mut row = vec![
+                Cell::new(format!("{:#x}", w.cache_line_address)),
+                Cell::new(global_cell(&w.first)),
+                Cell::new(global_cell(&w.second)),
+                Cell::new(w.second.section.as_deref().unwrap_or("-")),
+            ];
+            if with_binary {
+                row.push(Cell::new(w.binary.as_deref().unwrap_or("-")));
+            }
+            table.add_row(row);
+        }
+        self.summary_section(&title, table)
+    }
+
+    /// Padding totals per CODEOWNERS owner.
+    pub fn format_owner_summary(&self, owners: &[OwnerSummary]) -> String {
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        table.set_header(vec![
+            "Owner",
+            "Files",
+            "Structs",
+            "Size",
+            "Padding",
+            "Padding %",
+            "Worst struct",
+        ]);
+        for o in owners {
+            let mut row = vec![
+                Cell::new(o.owner.as_deref().unwrap_or("(unowned)")),
+                Cell::new(o.files).set_alignment(CellAlignment::Right),
+            ];
+            row.extend(summary_cells(o.structs, o.total_size, o.padding_bytes, &o.worst_struct));
+            table.add_row(row);
+        }
+        self.summary_section(
+            &format!(
+                "Padding by owner ({} owner{})",
+                owners.len(),
+                if owners.len() == 1 { "" } else { "s" }
+            ),
+            table,
+        )
+    }
+
+    /// One row per struct in `offenders`, followed by the totals over `all` (which the
+    /// offenders were picked from).
+    pub fn format_offenders(&self, offenders: &[StructLayout], all: &[StructLayout]) -> String {
+        let table = self.struct_rows(offenders);
+        let title = format!("Worst offenders ({} of {} structs)", offenders.len(), all.len());
+        let mut output = if self.no_color { title } else { title.bold().to_string() };
+        output.push('\n');
+        output.push_str(&table.to_string());
+        output.push_str(&self.format_totals(all));
+        output
+    }
+
+    /// Binary-wide totals, a histogram of padding percentages, and the worst offenders.
+    pub fn format_binary_summary(&self, summary: &BinarySummary) -> String {
+        let mut totals = format!(
+            "Structs: {}, {} bytes, {} padding bytes ({:.1}%)",
+            summary.structs, summary.total_bytes, summary.padding_bytes, summary.padding_percent
+        );
+        if summary.partial_structs > 0 {
+            totals.push_str(&format!(
+                " [{} partial struct{} excluded]",
+                summary.partial_structs,
+                if summary.partial_structs == 1 { "" } else { "s" }
+            ));
+        }
+        totals.push_str(&format!(
+            "\nStatic instances: {}, wasting {} padding bytes\n",
+            summary.static_instances, summary.static_waste_bytes
+        ));
+
+        let max = summary.histogram.iter().map(|b| b.structs).max().unwrap_or(0).max(1);
+        let mut histogram = Table::new();
+        histogram.load_preset(UTF8_FULL_CONDENSED);
+        histogram.set_header(vec!["Padding %", "Structs", "Padding", ""]);
+        for bucket in &summary.histogram {
+            let bar = "#".repeat(bucket.structs.div_ceil(max.div_ceil(40)));
+            histogram.add_row(vec![
+                Cell::new(bucket.range),
+                Cell::new(bucket.structs).set_alignment(CellAlignment::Right),
+                Cell::new(bucket.padding_bytes).set_alignment(CellAlignment::Right),
+                Cell::new(bar),
+            ]);
+        }
+
+        let mut offenders = Table::new();
+        offenders.load_preset(UTF8_FULL_CONDENSED);
+        offenders.set_header(vec![
+            "Struct",
+            "Size",
+            "Padding",
+            "Padding %",
+            "Static instances",
+            "Static waste",
+        ]);
+        for o in &summary.top_offenders {
+            offenders.add_row(vec![
+                Cell::new(&o.name),
+                Cell::new(o.size).set_alignment(CellAlignment::Right),
+                Cell::new(o.padding_bytes).set_alignment(CellAlignment::Right),
+                Cell::new(format!("{:.1}%", o.padding_percent)).set_alignment(CellAlignment::Right),
+                Cell::new(o.static_instances).set_alignment(CellAlignment::Right),
+                Cell::new(o.static_waste_bytes).set_alignment(CellAlignment::Right),
+            ]);
+        }
+
+        let header = "\n\nSummary".to_string();
+        let mut output = if self.no_color { header } else { header.bold().to_string() };
+        output.push('\n');
+        output.push_str(&totals);
+        output.push_str(&histogram.to_string());
+        output.push('\n');
+        if !summary.top_offenders.is_empty() {
+            output.push_str(&self.summary_section(
+                &format!("Top {} offenders", summary.top_offenders.len()),
+                offenders,
+            ));
+        }
+        output
+    }
+
+    fn summary_section(&self, title: &str, table: Table) -> String {
+        let header = format!("\n\n{}", title);
+        let mut output = if self.no_color { header } else { header.bold().to_string() };
+        output.push('\n');
+        output.push_str(&table.to_string());
+        output.push('\n');
+        output
+    }
+
+    fn format_totals(&self, layouts: &[StructLayout]) -> String {
+        let counted: Vec<&StructLayout> =
+            layouts.iter().filter(|l| self.include_partial || !l.metrics.partial).collect();
+        let size: u64 = counted.iter().map(|l| l.size).sum();
+        let padding: u64 = counted.iter().map(|l| l.metrics.padding_bytes).sum();
+        let percent = if size > 0 { padding as f64 / size as f64 * 100.0 } else { 0.0 };
+
+        let mut output = format!(
+            "\n\nTotal: {} struct{}, {} bytes, {} padding bytes ({:.1}%)",
+            counted.len(),
+            if counted.len() == 1 { "" } else { "s" },
+            size,
+            padding,
+            percent
+        );
+        let excluded = layouts.len() - counted.len();
+        if excluded > 0 {
+            output.push_str(&format!(
+                " [{} partial struct{} excluded; use --include-partial to count them]",
+                excluded,
+                if excluded == 1 { "" } else { "s" }
+            ));
+        }
+        output.push('\n');
+        output
+    }
+
     fn format_struct(&self, layout: &StructLayout) -> String {
         let mut output = String::new();
 
@@ -47,6 +523,17 @@ impl TableFormatter {
         if let Some(ref loc) = layout.source_location {
             output.push_str(&format!("  defined at {}:{}\n", loc.file, loc.line));
         }
+        if let Some(binary) = &layout.binary {
+            output.push_str(&format!("  in {}\n", binary));
+        }
+        for member in &layout.members {
+            let Some(assumed) = member.assumed else { continue };
+            let align = assumed.align.map(|a| format!(", align {}", a)).unwrap_or_default();
+            output.push_str(&format!(
+                "  assumes {}: {} is {} bytes{} (type_sizes)\n",
+                member.name, member.type_name, assumed.size, align
+            ));
+        }
         output.push('\n');
 
         let mut table = Table::new();
@@ -154,16 +641,113 @@ impl TableFormatter {
             }
         }
 
-        output.push_str(&table.to_string());
+        // Enum payloads are listed per variant below instead.
+        if !(layout.members.is_empty() && layout.enum_layout.is_some()) {
+            output.push_str(&table.to_string());
+            output.push_str("\n\n");
+        }
 
         output.push_str(&format!(
-            "\n\nSummary: {} useful bytes, {} padding bytes ({:.1}%), cache density: {:.1}%\n",
+            "Summary: {} useful bytes, {} padding bytes ({:.1}%), cache density: {:.1}%\n",
             layout.metrics.useful_size,
             layout.metrics.padding_bytes,
             layout.metrics.padding_percentage,
             layout.metrics.cache_line_density
         ));
 
+        if let Some(factor) = layout.metrics.stride_factor()
+            && factor >= STRIDE_NOTE_FACTOR
+        {
+            let line = format!(
+                "Array stride: {} bytes, {} wasted per element ({:.1}x member bytes)\n",
+                layout.metrics.stride, layout.metrics.stride_waste, factor
+            );
+            if self.no_color {
+                output.push_str(&line);
+            } else {
+                output.push_str(&line.yellow().to_string());
+            }
+        }
+
+        if let Some(bits) = &layout.metrics.bits {
+            output.push_str(&self.format_bits(bits));
+        }
+
+        if let Some(enum_layout) = &layout.enum_layout {
+            output.push_str(&format_enum(enum_layout));
+        }
+
+        if let Some(slack) = layout.metrics.alloc_slack {
+            let line = format!(
+                "Allocation: {}-byte size class, {} bytes slack\n",
+                slack.size_class, slack.slack_bytes
+            );
+            if self.no_color || slack.slack_bytes == 0 {
+                output.push_str(&line);
+            } else {
+                output.push_str(&line.yellow().to_string());
+            }
+        }
+
+        if let Some(usage) = layout.usage {
+            output.push_str(&format!("Used by: {}\n", usage));
+        }
+
+        if let (Some(instances), Some(waste)) =
+            (layout.metrics.static_instances, layout.metrics.weighted_waste_bytes)
+        {
+            output.push_str(&format!(
+                "Static instances: {}, wasting {} padding bytes\n",
+                instances, waste
+            ));
+        }
+
+        if !layout.metrics.partial_members.is_empty() {
+            let warning = format!(
+                "Partial layout: missing offset or size for {}; padding is not measured\n",
+                layout.metrics.partial_members.join(", ")
+            );
+            if self.no_color {
+                output.push_str(&warning);
+            } else {
+                output.push_str(&warning.yellow().to_string());
+            }
+        }
+
+        if self.layout_map {
+            output.push_str(&self.format_layout_map(layout));
+        }
+
+        if !layout.metrics.bases.is_empty() {
+            output.push_str("\nBase classes:\n");
+            for b in &layout.metrics.bases {
+                let kind = if b.is_virtual { "virtual " } else { "" };
+                if b.size == 0 {
+                    output.push_str(&format!(
+                        "  - {}{} at offset {}: empty, optimized away\n",
+                        kind, b.type_name, b.offset
+                    ));
+                    continue;
+                }
+                let reuse = if b.tail_padding_reused {
+                    format!(", tail padding reused by {}", b.tail_reused_by.join(", "))
+                } else {
+                    String::new()
+                };
+                output.push_str(&format!(
+                    "  - {}{} at offset {}: {} bytes ({} data), {} padding bytes ({} tail){}\n",
+                    kind,
+                    b.type_name,
+                    b.offset,
+                    b.size,
+                    b.data_size,
+                    b.padding_bytes,
+                    b.tail_padding,
+                    reuse
+                ));
+            }
+        }
+
         if let Some(ref fs) = layout.metrics.false_sharing {
             if !fs.spanning_warnings.is_empty() {
                 let header = "\nCache Line Spanning (severe):";
@@ -229,6 +813,196 @@ impl TableFormatter {
 
         output
     }
+
+    /// Bit ranges (inclusive) of members and unused bits, in bit order.
+    fn format_bits(&self, bits: &BitLayout) -> String {
+        let mut output = format!(
+            "\nBits: {} of {} used, {} unused\n",
+            bits.used_bits,
+            bits.total_bits,
+            bits.total_bits.saturating_sub(bits.used_bits)
+        );
+
+        let mut rows: Vec<(u64, u64, Option<&str>)> =
+            bits.fields.iter().map(|f| (f.start_bit, f.bits, Some(f.name.as_str()))).collect();
+        rows.extend(bits.gaps.iter().map(|g| (g.start_bit, g.bits, None)));
+        rows.sort_by_key(|&(start, bits, name)| (start, name.is_none(), bits));
+
+        for (start, count, name) in rows {
+            let range = match count {
+                0 | 1 => format!("{}", start),
+                _ => format!("{}-{}", start, start + count - 1),
+            };
+            let line =
+                format!("  bits {:<11} {:>4}  {}\n", range, count, name.unwrap_or("(unused)"));
+            if name.is_some() || self.no_color {
+                output.push_str(&line);
+            } else {
+                output.push_str(&line.yellow().to_string());
+            }
+        }
+        output
+    }
+
+    fn format_layout_map(&self, layout: &StructLayout) -> String {
+        if layout.size == 0 {
+            return String::new();
+        }
+        if layout.size > LAYOUT_MAP_MAX_BYTES {
+            return format!(
+                "\nLayout map omitted (struct is larger than {} bytes)\n",
+                LAYOUT_MAP_MAX_BYTES
+            );
+        }
+
+        // None: padding; Some(c): the member symbol, or LAYOUT_MAP_SHARED when several
+        // members (bitfields, unions) claim the byte.
+        let mut bytes: Vec<Option<char>> = vec![None; layout.size as usize];
+        let mut legend = Vec::new();
+        for member in &layout.members {
+            let Some((start, end)) = member_byte_range(member) else {
+                continue;
+            };
+            let end = end.min(layout.size);
+            if start >= end {
+                continue;
+            }
+            let symbol = LAYOUT_MAP_SYMBOLS[legend.len() % LAYOUT_MAP_SYMBOLS.len()] as char;
+            legend.push((symbol, member));
+            for byte in &mut bytes[start as usize..end as usize] {
+                *byte = Some(if byte.is_some() { LAYOUT_MAP_SHARED } else { symbol });
+            }
+        }
+
+        let line = self.cache_line_size as u64;
+        let row_width = line.min(LAYOUT_MAP_MAX_ROW);
+        let mut output = format!(
+            "\nLayout map (1 char per byte, {} bytes per row, '{}' = padding, '{}' = shared):\n",
+            row_width, LAYOUT_MAP_PADDING, LAYOUT_MAP_SHARED
+        );
+
+        let rows: Vec<String> = bytes
+            .chunks(row_width as usize)
+            .map(|row| row.iter().map(|b| b.unwrap_or(LAYOUT_MAP_PADDING)).collect())
+            .collect();
+        let mut i = 0;
+        while i < rows.len() {
+            let offset = i as u64 * row_width;
+            if offset > 0 && offset % line == 0 {
+                output.push_str(&format!(
+                    "          -- cache line {} ({}) --\n",
+                    offset / line,
+                    offset
+                ));
+            }
+            output.push_str(&format!(
+                "  {:>7} |{}|\n",
+                offset,
+                self.color_map_row(&format!("{:<width$}", rows[i], width = row_width as usize))
+            ));
+
+            // Collapse long runs of identical rows, such as large arrays.
+            let repeats = rows[i + 1..].iter().take_while(|r| **r == rows[i]).count();
+            if repeats > 1 {
+                output.push_str(&format!(
+                    "          ... {} identical rows through offset {}\n",
+                    repeats,
+                    (i + repeats + 1) as u64 * row_width - 1
+                ));
+                i += repeats;
+            }
+            i += 1;
+        }
+
+        for (symbol, member) in legend {
+            output.push_str(&format!(
+                "  {} {} ({}, offset {})\n",
+                symbol,
+                member.name,
+                member.type_name,
+                member.offset.unwrap_or(0)
+            ));
+        }
+        output
+    }
+
+    fn color_map_row(&self, row: &str) -> String {
+        if self.no_color {
+            return row.to_string();
+        }
+        let mut colored = String::new();
+        let mut rest = row;
+        while let Some(first) = rest.chars().next() {
+            let run = rest.find(|c| c != first).unwrap_or(rest.len());
+            let (chunk, tail) = rest.split_at(run);
+            colored.push_str(&match first {
+                LAYOUT_MAP_PADDING => chunk.yellow().to_string(),
+                LAYOUT_MAP_SHARED => chunk.cyan().to_string(),
+                _ => chunk.to_string(),
+            });
+            rest = tail;
+        }
+        colored
+    }
+}
+
+/// Discriminant summary and one line per variant, largest payload marked.
+fn format_enum(enum_layout: &EnumLayout) -> String {
+    let count = enum_layout.variants.len();
+    let variants = format!("{} variant{}", count, if count == 1 { "" } else { "s" });
+    let mut output = match &enum_layout.discriminant {
+        None => format!("Enum: {}, no discriminant\n", variants),
+        Some(d) => {
+            let offset = d.offset.map_or_else(|| "?".to_string(), |o| o.to_string());
+            if enum_layout.niche {
+                format!(
+                    "Enum: {}, niche discriminant in {} at offset {} (no tag bytes)\n",
+                    variants, d.type_name, offset
+                )
+            } else {
+                format!(
+                    "Enum: {}, {}-byte discriminant ({}) at offset {}\n",
+                    variants,
+                    enum_layout.tag_bytes(),
+                    d.type_name,
+                    offset
+                )
+            }
+        }
+    };
+
+    let largest = enum_layout.largest_variant();
+    let name_width = enum_layout.variants.iter().map(|v| v.name.len()).max().unwrap_or(0);
+    let size_width =
+        enum_layout.variants.iter().map(|v| v.payload_size.to_string().len()).max().unwrap_or(0);
+    for variant in &enum_layout.variants {
+        let value = match variant.discriminant_value {
+            Some(v) => format!("= {}", v),
+            None => "(otherwise)".to_string(),
+        };
+        let marker = if count > 1 && largest.is_some_and(|l| std::ptr::eq(l, variant)) {
+            " (largest)"
+        } else {
+            ""
+        };
+        output.push_str(&format!(
+            "  {:<name_width$}  {:<11}  {:>size_width$} payload bytes{}\n",
+            variant.name, value, variant.payload_size, marker
+        ));
+    }
+    output
+}
+
+/// Bytes `[start, end)` a member occupies; bitfields cover only the bytes holding their bits.
+fn member_byte_range(member: &MemberLayout) -> Option<(u64, u64)> {
+    let offset = member.offset?;
+    match (member.bit_offset, member.bit_size) {
+        (Some(bit_offset), Some(bit_size)) => {
+            let start_bit = offset.checked_mul(8)?.checked_add(bit_offset)?;
+            Some((start_bit / 8, start_bit.checked_add(bit_size)?.div_ceil(8)))
+        }
+        _ => Some((offset, offset.checked_add(member.size?)?)),
+    }
 }
 
 enum TableEntry<'a> {
@@ -246,12 +1020,37 @@ enum TableEntry<'a> {
     },
 }
 
+/// Structs, size, padding, padding percentage, and worst struct cells of a summary row.
+fn global_cell(global: &SharedGlobal) -> String {
+    format!("{}: {} ({} bytes @ {:#x})", global.name, global.type_name, global.size, global.address)
+}
+
+fn summary_cells(
+    structs: usize,
+    total_size: u64,
+    padding_bytes: u64,
+    worst: &Option<WorstStruct>,
+) -> Vec<Cell> {
+    let percent =
+        if total_size > 0 { padding_bytes as f64 / total_size as f64 * 100.0 } else { 0.0 };
+    vec![
+        Cell::new(structs).set_alignment(CellAlignment::Right),
+        Cell::new(total_size).set_alignment(CellAlignment::Right),
+        Cell::new(padding_bytes).set_alignment(CellAlignment::Right),
+        Cell::new(format!("{:.1}%", percent)).set_alignment(CellAlignment::Right),
+        Cell::new(match worst {
+            Some(w) => format!("{} ({} bytes)", w.name, w.padding_bytes),
+            None => "-".to_string(),
+        }),
+    ]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::{
-        CacheLineSpanningWarning, FalseSharingAnalysis, FalseSharingWarning, LayoutMetrics,
-        MemberLayout, PaddingHole, StructLayout,
+        AllocSlack, CacheLineSpanningWarning, FalseSharingAnalysis, FalseSharingWarning,
+        LayoutMetrics, MemberLayout, PaddingHole, StructLayout, VariantLayout,
     };
 
     fn sample_layout() -> StructLayout {
@@ -272,6 +1071,18 @@ mod tests {
                 size: 3,
                 after_member: Some("a".to_string()),
             }],
+            bases: vec![crate::types::BaseAttribution {
+                name: "<base: Base>".to_string(),
+                type_name: "Base".to_string(),
+                offset: 0,
+                size: 16,
+                data_size: 9,
+                padding_bytes: 7,
+                tail_padding: 7,
+                tail_padding_reused: true,
+                is_virtual: false,
+                tail_reused_by: vec!["c".to_string()],
+            }],
             false_sharing: Some(FalseSharingAnalysis {
                 warnings: vec![FalseSharingWarning {
                     member_a: "a".to_string(),
@@ -299,6 +1110,13 @@ mod tests {
                 }],
             }),
             partial: false,
+            partial_members: Vec::new(),
+            alloc_slack: None,
+            bits: None,
+            stride: 16,
+            stride_waste: 11,
+            static_instances: None,
+            weighted_waste_bytes: None,
         };
         layout
     }
@@ -312,6 +1130,232 @@ mod tests {
         assert!(out.contains("Potential False Sharing"));
         assert!(out.contains("Cache Line Spanning"));
         assert!(out.contains("Atomic members"));
+        assert!(out.contains("Base classes:"));
+        assert!(out.contains("tail padding reused by c"));
+    }
+
+    #[test]
+    fn bit_report_lists_fields_and_unused_bits() {
+        let mut layout = StructLayout::new("Reg".to_string(), 1, Some(1));
+        let mut en = MemberLayout::new("en".to_string(), "u8".to_string(), Some(0), Some(1));
+        en.bit_offset = Some(0);
+        en.bit_size = Some(1);
+        let mut mode = MemberLayout::new("mode".to_string(), "u8".to_string(), Some(0), Some(1));
+        mode.bit_offset = Some(2);
+        mode.bit_size = Some(3);
+        layout.members = vec![en, mode];
+        layout.metrics.bits = crate::analysis::analyze_bits(&layout);
+
+        let out = TableFormatter::new(true, 64).format(&[layout]);
+        assert!(out.contains("Bits: 4 of 8 used, 4 unused"), "{}", out);
+        assert!(out.contains("bits 0              1  en"), "{}", out);
+        assert!(out.contains("bits 1              1  (unused)"), "{}", out);
+        assert!(out.contains("bits 2-4            3  mode"), "{}", out);
+        assert!(out.contains("bits 5-7            3  (unused)"), "{}", out);
+    }
+
+    #[test]
+    fn layout_map_draws_members_padding_and_cache_lines() {
+        let mut layout = StructLayout::new("Map".to_string(), 24, Some(8));
+        layout.members = vec![
+            MemberLayout::new("a".to_string(), "char".to_string(), Some(0), Some(1)),
+            MemberLayout::new("b".to_string(), "long".to_string(), Some(8), Some(8)),
+            MemberLayout::new("c".to_string(), "int".to_string(), Some(16), Some(4)),
+        ];
+        let out = TableFormatter::new(true, 8).with_layout_map(true).format(&[layout]);
+        assert!(out.contains("|A.......|"));
+        assert!(out.contains("-- cache line 1 (8) --"));
+        assert!(out.contains("|BBBBBBBB|"));
+        assert!(out.contains("|CCCC....|"));
+        assert!(out.contains("  B b (long, offset 8)"));
+    }
+
+    #[test]
+    fn layout_map_marks_shared_bytes_and_collapses_repeats() {
+        let mut layout = StructLayout::new("Bits".to_string(), 64, Some(4));
+        let mut a = MemberLayout::new("a".to_string(), "u32".to_string(), Some(0), Some(4));
+        a.bit_offset = Some(0);
+        a.bit_size = Some(1);
+        let mut b = MemberLayout::new("b".to_string(), "u32".to_string(), Some(0), Some(4));
+        b.bit_offset = Some(1);
+        b.bit_size = Some(15);
+        let buf = MemberLayout::new("buf".to_string(), "[u8; 60]".to_string(), Some(4), Some(60));
+        layout.members = vec![a, b, buf];
+
+        let out = TableFormatter::new(true, 8).format_layout_map(&layout);
+        assert!(out.contains("|+B..CCCC|"));
+        assert!(out.contains("6 identical rows through offset 63"));
+    }
+
+    #[test]
+    fn table_formatter_lists_enum_variants() {
+        let variant = |name: &str, value: Option<i128>, members: Vec<MemberLayout>| {
+            let payload_size = members.iter().filter_map(|m| m.size).sum();
+            VariantLayout {
+                name: name.to_string(),
+                discriminant_value: value,
+                payload_size,
+                members,
+            }
+        };
+        let tag = MemberLayout::new("<discriminant>".into(), "u8".into(), Some(0), Some(1));
+        let mut layout = StructLayout::new("Shape".to_string(), 16, Some(8));
+        layout.enum_layout = Some(EnumLayout::new(
+            Some(tag.clone()),
+            vec![
+                variant("Empty", Some(0), vec![]),
+                variant(
+                    "Big",
+                    Some(1),
+                    vec![MemberLayout::new("__0".into(), "u64".into(), Some(8), Some(8))],
+                ),
+            ],
+        ));
+        let out = TableFormatter::new(true, 64).format(&[layout.clone()]);
+        assert!(out.contains("Enum: 2 variants, 1-byte discriminant (u8) at offset 0"));
+        assert!(out.contains("Big    = 1  "));
+        assert!(out.contains("8 payload bytes (largest)"));
+        assert!(!out.contains("Offset"), "empty member table is omitted for enums");
+
+        // The tag overlapping the payload means the enum uses a niche.
+        let niche = EnumLayout::new(
+            Some(tag),
+            vec![
+                variant(
+                    "Flag",
+                    None,
+                    vec![MemberLayout::new("__0".into(), "bool".into(), Some(0), Some(1))],
+                ),
+                variant("Unknown", Some(2), vec![]),
+            ],
+        );
+        assert!(niche.niche);
+        layout.enum_layout = Some(niche);
+        let out = TableFormatter::new(true, 64).format(&[layout]);
+        assert!(out.contains("niche discriminant in u8 at offset 0 (no tag bytes)"));
+        assert!(out.contains("(otherwise)"));
+    }
+
+    #[test]
+    fn table_formatter_shows_alloc_slack_and_stride() {
+        let mut layout = sample_layout();
+        layout.metrics.alloc_slack = Some(AllocSlack { size_class: 32, slack_bytes: 16 });
+        let out = TableFormatter::new(true, 64).format(&[layout]);
+        assert!(out.contains("Allocation: 32-byte size class, 16 bytes slack"));
+        assert!(out.contains("Array stride: 16 bytes, 11 wasted per element (3.2x member bytes)"));
+    }
+
+    #[test]
+    fn table_formatter_totals_exclude_partial_by_default() {
+        let mut partial = sample_layout();
+        partial.name = "Partial".to_string();
+        partial.metrics.partial = true;
+        partial.metrics.partial_members = vec!["b".to_string()];
+        let layouts = [sample_layout(), partial];
+
+        let out = TableFormatter::new(true, 64).format(&layouts);
+        assert!(out.contains("missing offset or size for b"));
+        assert!(out.contains("Total: 1 struct, 16 bytes, 11 padding bytes"));
+        assert!(out.contains("1 partial struct excluded"));
+
+        let out = TableFormatter::new(true, 64).with_include_partial(true).format(&layouts);
+        assert!(out.contains("Total: 2 structs, 32 bytes, 22 padding bytes"));
+        assert!(!out.contains("excluded"));
+    }
+
+    #[test]
+    fn table_formatter_offenders_total_every_struct() {
+        let mut other = sample_layout();
+        other.name = "Other".to_string();
+        let layouts = [sample_layout(), other];
+
+        let out = TableFormatter::new(true, 64).format_offenders(&layouts[..1], &layouts);
+        assert!(out.starts_with("Worst offenders (1 of 2 structs)"));
+        assert!(!out.contains("Other"));
+        assert!(!out.contains("Offset"));
+        assert!(out.contains("Total: 2 structs, 32 bytes, 22 padding bytes"));
+    }
+
+    #[test]
+    fn table_formatter_columns_list_one_row_per_struct() {
+        let mut other = sample_layout();
+        other.name = "Other".to_string();
+        let layouts = [sample_layout(), other];
+
+        let out = TableFormatter::new(true, 64)
+            .with_columns(Some(vec![Column::Members, Column::PaddingPct, Column::File]))
+            .with_page_size(Some(1))
+            .format(&layouts);
+        let header = out.lines().find(|l| l.contains("Struct")).unwrap();
+        let order: Vec<&str> = header.split('┆').map(|c| c.trim_matches(['│', ' '])).collect();
+        assert_eq!(order, ["Struct", "Members", "Padding %", "File"]);
+        assert!(out.contains("Page 2 of 2 (structs 2-2 of 2)"));
+        assert!(!out.contains("Offset"));
+        assert!(out.contains("Total: 2 structs"));
+    }
+
+    #[test]
+    fn table_formatter_heatmap_lists_just_over_the_line() {
+        let layouts = [
+            StructLayout::new("Over".to_string(), 72, Some(8)),
+            StructLayout::new("Fits".to_string(), 32, Some(8)),
+        ];
+        let heatmap = crate::analysis::cache_line_heatmap(&layouts, 64);
+        let out = TableFormatter::new(true, 64).format_heatmap(&heatmap);
+        assert!(out.contains("Final cache line usage (2 structs, 64-byte lines)"));
+        assert!(out.contains("1-8"));
+        assert!(out.contains("Over (72 bytes, 2 cache lines): trim 8 bytes to save a line"));
+        assert!(!out.contains("Fits"));
+    }
+
+    #[test]
+    fn table_formatter_file_summary_names_worst_struct() {
+        let mut padded = StructLayout::new("Padded".to_string(), 16, Some(8));
+        padded.metrics.padding_bytes = 6;
+        let files = crate::analysis::summarize_by_file(&[padded]);
+        let out = TableFormatter::new(true, 64).format_file_summary(&files);
+        assert!(out.contains("Padding by file (1 file)"));
+        assert!(out.contains("(unknown)"));
+        assert!(out.contains("37.5%"));
+        assert!(out.contains("Padded (6 bytes)"));
+    }
+
+    #[test]
+    fn table_formatter_notes_assumed_sizes() {
+        let mut layout = sample_layout();
+        layout.members[1].assumed = Some(crate::types::DeclaredSize { size: 4, align: Some(4) });
+        let out = TableFormatter::new(true, 64).format(&[layout]);
+        assert!(out.contains("  assumes b: u32 is 4 bytes, align 4 (type_sizes)\n"));
+        assert!(!out.contains("assumes a:"));
+    }
+
+    #[test]
+    fn table_formatter_pages_repeat_headers() {
+        let layouts: Vec<StructLayout> = (0..5)
+            .map(|i| {
+                let mut layout = sample_layout();
+                layout.name = format!("S{}", i);
+                layout
+            })
+            .collect();
+        let formatter = TableFormatter::new(true, 64).with_page_size(Some(2));
+        let out = formatter.format(&layouts);
+        assert!(out.starts_with("Page 1 of 3 (structs 1-2 of 5)\n\nstruct S0"));
+        assert!(out.contains("\n\nPage 3 of 3 (structs 5-5 of 5)\n\nstruct S4"));
+        assert_eq!(out.matches("│ Offset").count(), 5);
+
+        // Batched, threaded rendering keeps struct order.
+        let many: Vec<StructLayout> = (0..RENDER_BATCH + 3)
+            .map(|i| {
+                let mut layout = sample_layout();
+                layout.name = format!("S{:04}", i);
+                layout
+            })
+            .collect();
+        let out = TableFormatter::new(true, 64).format(&many);
+        let positions: Vec<usize> =
+            many.iter().map(|l| out.find(&format!("struct {} ", l.name)).unwrap()).collect();
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
     }
 
     #[test]
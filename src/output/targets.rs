@@ -0,0 +1,114 @@
+//! Output formatters for the compare-targets command.
+
+use crate::diff::{MemberChangeKind, TargetComparison};
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+
+pub struct TargetsTableFormatter {
+    no_color: bool,
+}
+
+impl TargetsTableFormatter {
+    pub fn new(no_color: bool) -> Self {
+        Self { no_color }
+    }
+
+    pub fn format(&self, comparison: &TargetComparison) -> String {
+        let differing = comparison.differences.len()
+            + comparison.only_in_old.len()
+            + comparison.only_in_new.len();
+        let mut output = format!(
+            "{} -> {}: {} struct(s) differ, {} identical\n",
+            comparison.old_target, comparison.new_target, differing, comparison.unchanged_count
+        );
+        if differing == 0 {
+            return output;
+        }
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        table.set_header(vec!["Struct", "Size", "Align", "Padding", "Cause", "Details"]);
+        let highlight = |text: String| {
+            let cell = Cell::new(text);
+            if self.no_color { cell } else { cell.fg(Color::Yellow) }
+        };
+        let alignment = |a: Option<u64>| a.map_or_else(|| "?".to_string(), |a| a.to_string());
+        for d in &comparison.differences {
+            let c = &d.change;
+            let causes: Vec<&str> = d.causes.iter().map(|cause| cause.as_str()).collect();
+            let details: Vec<String> = c
+                .member_changes
+                .iter()
+                .map(|m| format!("{} {}: {}", m.name, change_label(&m.kind), m.details))
+                .collect();
+            table.add_row(vec![
+                Cell::new(&c.name),
+                Cell::new(format!("{} -> {}", c.old_size, c.new_size)),
+                Cell::new(format!(
+                    "{} -> {}",
+                    alignment(d.old_alignment),
+                    alignment(d.new_alignment)
+                )),
+                Cell::new(format!("{} -> {}", c.old_padding, c.new_padding)),
+                highlight(causes.join(", ")),
+                Cell::new(details.join("\n")),
+            ]);
+        }
+        for (structs, target) in [
+            (&comparison.only_in_old, &comparison.old_target),
+            (&comparison.only_in_new, &comparison.new_target),
+        ] {
+            for s in structs {
+                table.add_row(vec![
+                    Cell::new(&s.name),
+                    Cell::new(s.size.to_string()),
+                    Cell::new(""),
+                    Cell::new(s.padding_bytes.to_string()),
+                    highlight(format!("only in {}", target)),
+                    Cell::new(""),
+                ]);
+            }
+        }
+        output.push_str(&table.to_string());
+        output.push('\n');
+        output
+    }
+}
+
+fn change_label(kind: &MemberChangeKind) -> &'static str {
+    match kind {
+        MemberChangeKind::Added => "only in new",
+        MemberChangeKind::Removed => "only in old",
+        MemberChangeKind::Renamed => "renamed",
+        MemberChangeKind::OffsetChanged => "offset",
+        MemberChangeKind::SizeChanged => "size",
+        MemberChangeKind::TypeChanged => "type",
+    }
+}
+
+#[derive(Serialize)]
+struct TargetsJsonOutput<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    comparison: &'a TargetComparison,
+}
+
+pub struct TargetsJsonFormatter {
+    pretty: bool,
+}
+
+impl TargetsJsonFormatter {
+    pub fn new(pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    pub fn format(&self, comparison: &TargetComparison) -> String {
+        let output = TargetsJsonOutput { version: env!("CARGO_PKG_VERSION"), comparison };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+        } else {
+            serde_json::to_string(&output)
+        }
+        .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
@@ -0,0 +1,83 @@
+//! Output formatters for the verify command.
+
+use crate::rustc_sizes::VerifyReport;
+use colored::Colorize;
+use comfy_table::{Cell, Color, Table, presets::UTF8_FULL_CONDENSED};
+use serde::Serialize;
+
+pub struct VerifyTableFormatter {
+    no_color: bool,
+}
+
+impl VerifyTableFormatter {
+    pub fn new(no_color: bool) -> Self {
+        Self { no_color }
+    }
+
+    pub fn format(&self, report: &VerifyReport) -> String {
+        let mut output = format!(
+            "{} type(s) checked against rustc, {} mismatch(es); {} rustc type(s) not in the debug \
+             info\n",
+            report.checked,
+            report.mismatches.len(),
+            report.missing.len()
+        );
+        if report.mismatches.is_empty() {
+            return output;
+        }
+
+        let mut table = Table::new();
+        table.load_preset(UTF8_FULL_CONDENSED);
+        table.set_header(vec!["Type", "Member", "Mismatch", "rustc", "DWARF"]);
+        let value = |v: Option<u64>| v.map_or_else(|| "-".to_string(), |v| v.to_string());
+        for mismatch in &report.mismatches {
+            let kind = Cell::new(mismatch.kind.label());
+            table.add_row(vec![
+                Cell::new(&mismatch.type_name),
+                Cell::new(mismatch.member.as_deref().unwrap_or("-")),
+                if self.no_color { kind } else { kind.fg(Color::Red) },
+                Cell::new(value(mismatch.rustc)),
+                Cell::new(value(mismatch.dwarf)),
+            ]);
+        }
+        output.push('\n');
+        output.push_str(&table.to_string());
+        output.push('\n');
+
+        let note = "\nrustc and the debug info disagree; trust neither layout until resolved";
+        if self.no_color {
+            output.push_str(note);
+        } else {
+            output.push_str(&note.yellow().to_string());
+        }
+        output.push('\n');
+        output
+    }
+}
+
+#[derive(Serialize)]
+struct VerifyJsonOutput<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    report: &'a VerifyReport,
+}
+
+pub struct VerifyJsonFormatter {
+    pretty: bool,
+}
+
+impl VerifyJsonFormatter {
+    pub fn new(pretty: bool) -> Self {
+        Self { pretty }
+    }
+
+    pub fn format(&self, report: &VerifyReport) -> String {
+        let output = VerifyJsonOutput { version: env!("CARGO_PKG_VERSION"), report };
+        if self.pretty {
+            serde_json::to_string_pretty(&output)
+        } else {
+            serde_json::to_string(&output)
+        }
+        .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
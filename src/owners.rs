@@ -0,0 +1,155 @@
+//! CODEOWNERS files, for attributing per-file padding totals to teams.
+//!
+//! Patterns follow GitHub's rules: later lines override earlier ones, a pattern without a
+//! slash matches at any depth, a leading `/` anchors to the repository root, `*` stays within
+//! one path segment, and a pattern naming a directory covers everything below it.
+
+use crate::error::{Error, Result};
+use globset::{GlobBuilder, GlobMatcher};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug)]
+pub struct CodeOwners {
+    /// Repository root that patterns are relative to.
+    root: PathBuf,
+    rules: Vec<OwnerRule>,
+}
+
+#[derive(Debug)]
+struct OwnerRule {
+    globs: Vec<GlobMatcher>,
+    owners: Vec<String>,
+}
+
+impl CodeOwners {
+    /// Read a CODEOWNERS file. Its repository root is the containing directory, or that
+    /// directory's parent when the file lives in `.github/` or `docs/`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let root = match dir.file_name().and_then(|n| n.to_str()) {
+            Some(".github" | "docs") => dir.parent().unwrap_or(Path::new("")),
+            _ => dir,
+        };
+        Self::parse(&text, root)
+            .map_err(|e| Error::Codeowners(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn parse(text: &str, root: &Path) -> Result<Self> {
+        let mut rules = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else { continue };
+            let owners: Vec<String> =
+                fields.take_while(|f| !f.starts_with('#')).map(str::to_string).collect();
+            let globs = pattern_globs(pattern)
+                .map_err(|e| Error::Codeowners(format!("line {}: {}", number + 1, e)))?;
+            rules.push(OwnerRule { globs, owners });
+        }
+        Ok(Self { root: root.to_path_buf(), rules })
+    }
+
+    /// Owners of a source file as recorded in debug info. Absolute paths must lie under the
+    /// repository root; relative ones are taken as relative to it. An empty result means the
+    /// file is unowned.
+    pub fn owners_of(&self, file: &str) -> &[String] {
+        let Some(relative) = self.relative_path(Path::new(file)) else {
+            return &[];
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.globs.iter().any(|g| g.is_match(&relative)))
+            .map_or(&[], |rule| rule.owners.as_slice())
+    }
+
+    fn relative_path(&self, file: &Path) -> Option<PathBuf> {
+        let relative = if file.is_absolute() {
+            let root = Path::new(".").join(&self.root);
+            clean(file).strip_prefix(clean(&std::path::absolute(root).ok()?)).ok()?.to_path_buf()
+        } else {
+            clean(file)
+        };
+        if relative.components().any(|c| matches!(c, Component::ParentDir)) {
+            return None;
+        }
+        Some(relative)
+    }
+}
+
+/// Drop `.` components, so `./src` matches patterns anchored at the root.
+fn clean(path: &Path) -> PathBuf {
+    path.components().filter(|c| !matches!(c, Component::CurDir)).collect()
+}
+
+/// Globs equivalent to one CODEOWNERS pattern.
+fn pattern_globs(pattern: &str) -> std::result::Result<Vec<GlobMatcher>, globset::Error> {
+    let directory_only = pattern.ends_with('/');
+    let trimmed = pattern.trim_end_matches('/');
+    let anchored = trimmed.starts_with('/') || trimmed.contains('/');
+    let body = trimmed.trim_start_matches('/');
+    let base = if anchored { body.to_string() } else { format!("**/{}", body) };
+
+    let mut sources = vec![format!("{}/**", base)];
+    if !directory_only {
+        sources.push(base);
+    }
+    sources
+        .iter()
+        .map(|s| Ok(GlobBuilder::new(s).literal_separator(true).build()?.compile_matcher()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+# Default owners
+*            @acme/core
+*.h          @acme/abi   # headers are ABI
+/src/net/    @acme/net
+docs/        @acme/docs
+src/gen/*.c
+";
+
+    fn owners(file: &str) -> Vec<String> {
+        let codeowners = CodeOwners::parse(SAMPLE, Path::new("/repo")).unwrap();
+        codeowners.owners_of(file).to_vec()
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        assert_eq!(owners("src/main.c"), vec!["@acme/core"]);
+        assert_eq!(owners("include/deep/api.h"), vec!["@acme/abi"]);
+        assert_eq!(owners("src/net/socket.h"), vec!["@acme/net"]);
+        assert_eq!(owners("./src/net/tcp/conn.c"), vec!["@acme/net"]);
+        // A rule with no owners clears ownership.
+        assert!(owners("src/gen/tables.c").is_empty());
+        assert_eq!(owners("src/gen/sub/tables.c"), vec!["@acme/core"]);
+    }
+
+    #[test]
+    fn anchoring_and_absolute_paths() {
+        assert_eq!(owners("lib/src/net/socket.c"), vec!["@acme/core"]);
+        assert_eq!(owners("lib/docs/intro.c"), vec!["@acme/docs"]);
+        assert_eq!(owners("/repo/src/net/socket.c"), vec!["@acme/net"]);
+        assert!(owners("/usr/include/stdio.h").is_empty());
+        assert!(owners("../vendor/lib.c").is_empty());
+    }
+
+    #[test]
+    fn root_follows_codeowners_location() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join(".github")).unwrap();
+        let path = dir.path().join(".github/CODEOWNERS");
+        std::fs::write(&path, "/src/ @acme/core\n").unwrap();
+        let codeowners = CodeOwners::load(&path).unwrap();
+        let file = dir.path().join("src/lib.c");
+        assert_eq!(codeowners.owners_of(file.to_str().unwrap()), ["@acme/core".to_string()]);
+    }
+}
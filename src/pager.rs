@@ -0,0 +1,99 @@
+//! Paging long table output through `$PAGER` when stdout is a terminal, the way git does.
+
+use std::ffi::OsString;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// Pager run when `$PAGER` is unset.
+const DEFAULT_PAGER: &str = "less";
+
+/// `LESS` options set when the user has none: quit if the output fits on one screen, pass
+/// color codes through, and leave the output on screen after quitting.
+const DEFAULT_LESS: &str = "FRX";
+
+/// A running pager; writes go to its stdin.
+pub struct Pager {
+    child: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl Pager {
+    /// Start `$PAGER` (default `less`) if stdout is a terminal. `None` when output is
+    /// redirected, when `$PAGER` is empty or `cat`, or when the pager cannot be started.
+    pub fn spawn() -> Option<Self> {
+        if !io::stdout().is_terminal() {
+            return None;
+        }
+        Self::spawn_command(std::env::var_os("PAGER"))
+    }
+
+    fn spawn_command(pager: Option<OsString>) -> Option<Self> {
+        let pager = pager.unwrap_or_else(|| DEFAULT_PAGER.into());
+        let pager = pager.to_string_lossy();
+        let mut words = pager.split_whitespace();
+        let program = words.next().filter(|p| *p != "cat")?;
+
+        let mut command = Command::new(program);
+        command.args(words).stdin(Stdio::piped());
+        if std::env::var_os("LESS").is_none() {
+            command.env("LESS", DEFAULT_LESS);
+        }
+        let mut child = command.spawn().ok()?;
+        let stdin = child.stdin.take();
+        Some(Self { child, stdin })
+    }
+
+    /// Close the pager's input and wait for the user to quit it.
+    pub fn finish(mut self) -> io::Result<()> {
+        drop(self.stdin.take());
+        self.child.wait().map(drop)
+    }
+}
+
+impl Write for Pager {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let Some(stdin) = &mut self.stdin else {
+            return Ok(buf.len());
+        };
+        match stdin.write(buf) {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                // The user quit the pager early; discard the rest of the output.
+                self.stdin = None;
+                Ok(buf.len())
+            }
+            result => result,
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let Some(stdin) = &mut self.stdin else {
+            return Ok(());
+        };
+        match stdin.flush() {
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                self.stdin = None;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cat_and_empty_pagers_are_skipped() {
+        assert!(Pager::spawn_command(Some("cat".into())).is_none());
+        assert!(Pager::spawn_command(Some("".into())).is_none());
+        assert!(Pager::spawn_command(Some("layout-audit-no-such-pager".into())).is_none());
+    }
+}
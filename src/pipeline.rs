@@ -0,0 +1,852 @@
+//! The inspect and diff pipelines behind the CLI: load each binary, find its structs, then
+//! analyze and sort them. Custom front ends and tests get the CLI's results without spawning
+//! it; rendering them is left to the formatters in [`crate::output`].
+
+use crate::analysis::{
+    AllocatorModel, AtomicPatterns, BinarySummary, CacheLineHeatmap, FileSummary,
+    GlobalFalseSharingDiff, NamespaceSummary, OwnerSummary, TemplateSummary, TypeSizeConflict,
+    TypeSizes, analyze_bases, analyze_bits, analyze_false_sharing_with_patterns,
+    analyze_global_false_sharing, analyze_layout, cache_line_heatmap, collapse_templates,
+    count_static_instances, diff_global_false_sharing, final_line_bytes, flatten_bases,
+    is_bitfield_only, place_globals, summarize_binary, summarize_by_file, summarize_by_namespace,
+    summarize_by_owner,
+};
+use crate::cli::SortField;
+use crate::diff::{DiffResult, diff_layouts};
+use crate::dwarf::{
+    DwarfContext, DwarfFeatures, ScanStats, StructScope, UnitStats, is_go_internal_type,
+};
+use crate::error::{Error, Result};
+use crate::loader::BinaryData;
+use crate::owners::CodeOwners;
+use crate::snapshot::Snapshot;
+use crate::target::TargetMismatch;
+use crate::types::{GlobalFalseSharingWarning, SharedGlobal, StructLayout};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Something the user should hear about that does not stop the pipeline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notice {
+    /// A compilation unit ran past the unit timeout and was dropped.
+    SkippedUnit { label: String, elapsed: Duration },
+    /// Units were dropped, so structs defined only in them are missing.
+    IncompleteCoverage { skipped: usize, units: usize },
+    /// Only exported structs were asked for, but the binary exports no symbols.
+    NoExports,
+    /// A configured type size disagrees with the debug info, which is used instead.
+    TypeSizeConflict(TypeSizeConflict),
+    /// The DWARF disagrees with the object file headers about the target.
+    TargetMismatch(TargetMismatch),
+    /// The scan stopped at its deadline after reading `units` compilation units.
+    OutOfTime { units: usize },
+    /// Both sides of a diff are the same build, so it was analyzed once.
+    SameBuild,
+}
+
+impl Notice {
+    /// Informational, where the others are warnings.
+    pub fn is_note(&self) -> bool {
+        matches!(self, Notice::IncompleteCoverage { .. } | Notice::SameBuild)
+    }
+}
+
+impl fmt::Display for Notice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Notice::SkippedUnit { label, elapsed } => write!(
+                f,
+                "Skipped {} after {:.1} ms (--unit-timeout)",
+                label,
+                elapsed.as_secs_f64() * 1000.0
+            ),
+            Notice::IncompleteCoverage { skipped, units } => write!(
+                f,
+                "Coverage is incomplete: {} of {} compilation units skipped; structs defined \
+                 only in them are missing",
+                skipped, units
+            ),
+            Notice::OutOfTime { units } => write!(
+                f,
+                "Time budget ran out after {} compilation unit(s); structs defined only in the \
+                 rest are missing",
+                units
+            ),
+            Notice::SameBuild => {
+                write!(f, "Old and new binaries are the same build; analyzing it once")
+            }
+            Notice::NoExports => {
+                write!(f, "Binary exports no symbols; --exported-only matches nothing")
+            }
+            Notice::TypeSizeConflict(c) => write!(
+                f,
+                "type_sizes declares {} as {} bytes, but {}.{} is {} bytes in the debug info; \
+                 using the debug info",
+                c.type_name, c.declared, c.struct_name, c.member, c.actual
+            ),
+            Notice::TargetMismatch(m) => write!(f, "{}", m),
+        }
+    }
+}
+
+/// Which structs to read from a binary.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanOptions<'a> {
+    /// Substring struct names must contain.
+    pub filter: Option<&'a str>,
+    pub include_go_runtime: bool,
+    /// Keep only structs reachable from exported symbols.
+    pub exported_only: bool,
+    /// Put layouts in canonical order, so later (stable) sorts break ties the same way
+    /// regardless of how layouts were gathered.
+    pub sort_stable: bool,
+}
+
+/// Structs read from one binary.
+#[derive(Debug)]
+pub struct Scan {
+    pub layouts: Vec<StructLayout>,
+    pub stats: ScanStats,
+    pub notices: Vec<Notice>,
+}
+
+/// Notices for units dropped by the unit timeout out of `units` read, and for a scan that
+/// stopped at its deadline.
+fn coverage_notices<'s>(
+    skipped: impl Iterator<Item = &'s UnitStats>,
+    units: usize,
+    out_of_time: bool,
+) -> Vec<Notice> {
+    let mut notices: Vec<Notice> = skipped
+        .map(|unit| Notice::SkippedUnit { label: unit.label(), elapsed: unit.elapsed })
+        .collect();
+    if !notices.is_empty() {
+        notices.push(Notice::IncompleteCoverage { skipped: notices.len(), units });
+    }
+    if out_of_time {
+        notices.push(Notice::OutOfTime { units });
+    }
+    notices
+}
+
+/// Parse the struct layouts of `binary` that `options` select. Units dropped by the context's
+/// unit timeout are reported as notices.
+pub fn find_layouts(
+    binary: &BinaryData,
+    dwarf: &DwarfContext<'_>,
+    options: &ScanOptions<'_>,
+) -> Result<Scan> {
+    let (mut layouts, stats) = dwarf
+        .find_structs_with_stats(options.filter, options.include_go_runtime)
+        .map_err(|e| e.context("Failed to parse struct layouts"))?;
+
+    let mut notices = coverage_notices(stats.skipped(), stats.units.len(), stats.out_of_time);
+    notices.extend(dwarf.target_mismatches().iter().cloned().map(Notice::TargetMismatch));
+
+    if options.exported_only {
+        let exports =
+            binary.exported_symbols().map_err(|e| e.context("Failed to read exported symbols"))?;
+        if exports.is_empty() {
+            notices.push(Notice::NoExports);
+        }
+        let reachable = dwarf
+            .exported_structs(&exports)
+            .map_err(|e| e.context("Failed to resolve exported signature types"))?;
+        layouts.retain(|l| reachable.contains(l));
+    }
+
+    if options.sort_stable {
+        layouts.sort_by(StructLayout::canonical_cmp);
+    }
+
+    Ok(Scan { layouts, stats, notices })
+}
+
+/// `paths` with each directory replaced by the binaries inside it.
+pub fn expand_binary_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut binaries = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            binaries.push(path.clone());
+            continue;
+        }
+        let found = BinaryData::find_in_dir(path)
+            .map_err(|e| e.context(format!("Failed to read directory: {}", path.display())))?;
+        if found.is_empty() {
+            return Err(Error::NoBinaries(path.clone()));
+        }
+        binaries.extend(found);
+    }
+    Ok(binaries)
+}
+
+/// Thread-shared globals of one binary that share a cache line.
+pub fn find_global_false_sharing(
+    binary: &BinaryData,
+    dwarf: &DwarfContext<'_>,
+    cache_line_size: u32,
+) -> Result<Vec<GlobalFalseSharingWarning>> {
+    let globals =
+        dwarf.shared_globals().map_err(|e| e.context("Failed to read global variables"))?;
+    let symbols = binary.data_symbols().map_err(|e| e.context("Failed to read data symbols"))?;
+    let sections = binary.data_sections().map_err(|e| e.context("Failed to read data sections"))?;
+    Ok(analyze_global_false_sharing(&place_globals(&globals, &symbols, &sections), cache_line_size))
+}
+
+/// Static instances of each struct in `layouts` by name, from the globals in the debug info
+/// sized and deduplicated by the symbol table (see [`place_globals`]).
+pub fn find_static_instances(
+    binary: &BinaryData,
+    dwarf: &DwarfContext<'_>,
+    layouts: &[StructLayout],
+) -> Result<HashMap<String, u64>> {
+    let globals: Vec<SharedGlobal> = dwarf
+        .global_variables()
+        .map_err(|e| e.context("Failed to read global variables"))?
+        .into_iter()
+        .map(SharedGlobal::from)
+        .collect();
+    let symbols = binary.stored_symbols().map_err(|e| e.context("Failed to read data symbols"))?;
+    let sections =
+        binary.stored_sections().map_err(|e| e.context("Failed to read data sections"))?;
+    Ok(count_static_instances(&place_globals(&globals, &symbols, &sections), layouts))
+}
+
+/// Sort `layouts` in place the way `inspect --sort-by` does.
+pub fn sort_layouts(layouts: &mut [StructLayout], sort_by: SortField, cache_line_size: u32) {
+    match sort_by {
+        SortField::Name => layouts.sort_by(|a, b| a.name.cmp(&b.name)),
+        SortField::Size => layouts.sort_by_key(|l| std::cmp::Reverse(l.size)),
+        SortField::Padding => layouts.sort_by_key(|l| std::cmp::Reverse(l.metrics.padding_bytes)),
+        SortField::PaddingPct => layouts.sort_by(|a, b| {
+            match (a.metrics.padding_percentage.is_nan(), b.metrics.padding_percentage.is_nan()) {
+                (true, true) => std::cmp::Ordering::Equal,
+                (true, false) => std::cmp::Ordering::Greater,
+                (false, true) => std::cmp::Ordering::Less,
+                (false, false) => b
+                    .metrics
+                    .padding_percentage
+                    .partial_cmp(&a.metrics.padding_percentage)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            }
+        }),
+        SortField::FinalLine => layouts.sort_by_key(|l| {
+            (l.size <= cache_line_size as u64, final_line_bytes(l.size, cache_line_size))
+        }),
+        SortField::Impact => layouts.sort_by_key(|l| {
+            let impact = l.usage.map_or(0, |u| u.impact as u64);
+            std::cmp::Reverse(l.metrics.padding_bytes.saturating_mul(impact))
+        }),
+        SortField::WeightedWaste => layouts.sort_by_key(|l| {
+            std::cmp::Reverse((l.metrics.weighted_waste_bytes, l.metrics.padding_bytes))
+        }),
+    }
+}
+
+/// Mark layouts as read from `path`, when several binaries are analyzed together.
+pub fn record_binary(layouts: &mut [StructLayout], path: &Path) {
+    let label = path.display().to_string();
+    for layout in layouts {
+        layout.binary = Some(label.clone());
+    }
+}
+
+fn load_binary(path: &Path, side: &str) -> Result<BinaryData> {
+    BinaryData::load(path)
+        .map_err(|e| e.context(format!("Failed to load {}binary: {}", side, path.display())))
+}
+
+/// Options for [`run_inspect`]. [`InspectOptions::new`] gives the CLI's defaults.
+#[derive(Clone, Copy)]
+pub struct InspectOptions<'a> {
+    /// Binaries, or directories searched for them.
+    pub binary_paths: &'a [PathBuf],
+    pub scan: ScanOptions<'a>,
+    /// Namespaces, name patterns, and members structs must match; everything by default.
+    pub scope: Option<&'a StructScope>,
+    /// Declared sizes for members the debug info leaves unsized.
+    pub type_sizes: Option<&'a TypeSizes>,
+    pub unit_timeout: Option<Duration>,
+    /// When to stop scanning; see [`DwarfContext::with_deadline`].
+    pub deadline: Option<Instant>,
+    pub cache_line_size: u32,
+    pub sort_by: SortField,
+    /// How many of the sorted structs [`InspectReport::shown`] lists.
+    pub top: Option<usize>,
+    pub min_padding: Option<u64>,
+    pub warn_false_sharing: bool,
+    pub global_false_sharing: bool,
+    /// Types counted as atomics or locks by both false-sharing checks; the built-in set by
+    /// default.
+    pub atomic_patterns: Option<&'a AtomicPatterns>,
+    pub flatten_bases: bool,
+    pub bits: bool,
+    pub allocator: Option<AllocatorModel>,
+    pub static_access: bool,
+    /// Count what refers to each struct into its `usage`; also done for [`SortField::Impact`].
+    pub usage: bool,
+    /// Count static instances of each struct from the symbol table and weight its padding by
+    /// them; also done for [`SortField::WeightedWaste`] and `summary`.
+    pub static_instances: bool,
+    pub cache_heatmap: bool,
+    pub by_file: bool,
+    /// Roll file summaries up by owner; needs `by_file`.
+    pub codeowners: Option<&'a CodeOwners>,
+    pub by_namespace: bool,
+    pub namespace_depth: Option<usize>,
+    /// List each template instantiated more than once as one summary instead of its
+    /// instantiations.
+    pub collapse_templates: bool,
+    /// Total padding over every matching struct, weighting it by static instances.
+    pub summary: bool,
+}
+
+impl<'a> InspectOptions<'a> {
+    pub fn new(binary_paths: &'a [PathBuf]) -> Self {
+        Self {
+            binary_paths,
+            scan: ScanOptions::default(),
+            scope: None,
+            type_sizes: None,
+            unit_timeout: None,
+            deadline: None,
+            cache_line_size: 64,
+            sort_by: SortField::Name,
+            top: None,
+            min_padding: None,
+            warn_false_sharing: false,
+            global_false_sharing: false,
+            atomic_patterns: None,
+            flatten_bases: false,
+            bits: false,
+            allocator: None,
+            static_access: false,
+            usage: false,
+            static_instances: false,
+            cache_heatmap: false,
+            by_file: false,
+            codeowners: None,
+            by_namespace: false,
+            namespace_depth: None,
+            collapse_templates: false,
+            summary: false,
+        }
+    }
+}
+
+/// Structs read from one binary by [`run_inspect`].
+#[derive(Debug)]
+pub struct BinaryScan {
+    pub path: PathBuf,
+    pub stats: ScanStats,
+    /// DWARF versions, producers, and unsupported forms seen while scanning.
+    pub dwarf: DwarfFeatures,
+    pub notices: Vec<Notice>,
+}
+
+/// What [`run_inspect`] found.
+#[derive(Debug)]
+pub struct InspectReport {
+    /// One per binary, after expanding directories.
+    pub scans: Vec<BinaryScan>,
+    /// Files the result depends on: the binaries and any separate debug info they use.
+    pub inputs: Vec<PathBuf>,
+    /// Structs found, before `min_padding` narrowed them.
+    pub found: usize,
+    /// Every matching struct, analyzed and sorted, less those collapsed into `templates`.
+    /// Aggregates below cover all of them.
+    pub layouts: Vec<StructLayout>,
+    pub top: Option<usize>,
+    pub heatmap: Option<CacheLineHeatmap>,
+    pub files: Option<Vec<FileSummary>>,
+    pub owners: Option<Vec<OwnerSummary>>,
+    pub namespaces: Option<Vec<NamespaceSummary>>,
+    pub templates: Option<Vec<TemplateSummary>>,
+    pub global_false_sharing: Option<Vec<GlobalFalseSharingWarning>>,
+    /// Totals over every matching struct, template instantiations included.
+    pub summary: Option<BinarySummary>,
+    /// True if a scan stopped at [`InspectOptions::deadline`] or the analysis finished after
+    /// it; the report covers only what was read in time.
+    pub out_of_time: bool,
+}
+
+impl InspectReport {
+    /// The structs to list: the first `top` of [`InspectReport::layouts`].
+    pub fn shown(&self) -> &[StructLayout] {
+        &self.layouts[..self.top.map_or(self.layouts.len(), |n| n.min(self.layouts.len()))]
+    }
+}
+
+/// Find, analyze, and sort the structs of every binary in `options`. Layouts are tagged with
+/// their binary when there is more than one.
+pub fn run_inspect(options: &InspectOptions<'_>) -> Result<InspectReport> {
+    let binary_paths = expand_binary_paths(options.binary_paths)?;
+    let provenance = binary_paths.len() > 1;
+    let cache_line_size = options.cache_line_size;
+
+    let mut scans = Vec::new();
+    let mut inputs = Vec::new();
+    let mut layouts = Vec::new();
+    let mut global_sharing = options.global_false_sharing.then(Vec::new);
+    let count_instances =
+        options.static_instances || options.summary || options.sort_by == SortField::WeightedWaste;
+    // Static instances of each layout, in step with `layouts`.
+    let mut static_instances: Vec<Option<u64>> = Vec::new();
+    for path in &binary_paths {
+        let binary = load_binary(path, "")?;
+        let loaded = binary.load_dwarf().map_err(|e| {
+            e.context(format!("Failed to load DWARF debug info: {}", path.display()))
+        })?;
+        inputs.extend(binary.input_files());
+
+        let dwarf = DwarfContext::new(&loaded)
+            .with_scope(options.scope.cloned().unwrap_or_default())
+            .with_unit_timeout(options.unit_timeout)
+            .with_deadline(options.deadline)
+            .with_atomic_patterns(options.atomic_patterns.cloned().unwrap_or_default());
+        let Scan { layouts: mut found, stats, mut notices } =
+            find_layouts(&binary, &dwarf, &options.scan)?;
+        if let Some(type_sizes) = options.type_sizes {
+            notices.extend(type_sizes.apply(&mut found).into_iter().map(Notice::TypeSizeConflict));
+        }
+        if options.static_access {
+            dwarf.annotate_static_popularity(&mut found)?;
+        }
+        if options.usage || options.sort_by == SortField::Impact {
+            dwarf.annotate_usage(&mut found)?;
+        }
+        if let Some(all) = &mut global_sharing {
+            let mut warnings = find_global_false_sharing(&binary, &dwarf, cache_line_size)?;
+            if provenance {
+                for warning in &mut warnings {
+                    warning.binary = Some(path.display().to_string());
+                }
+            }
+            all.extend(warnings);
+        }
+        if count_instances {
+            let counts = find_static_instances(&binary, &dwarf, &found)?;
+            static_instances
+                .extend(found.iter().map(|l| Some(counts.get(&l.name).copied().unwrap_or(0))));
+        } else {
+            static_instances.extend(found.iter().map(|_| None));
+        }
+        if provenance {
+            record_binary(&mut found, path);
+        }
+        layouts.extend(found);
+        let features = DwarfFeatures::new(&loaded, &stats);
+        scans.push(BinaryScan { path: path.clone(), stats, dwarf: features, notices });
+    }
+    let found = layouts.len();
+
+    for (layout, instances) in layouts.iter_mut().zip(static_instances) {
+        analyze_inspected(layout, options, instances);
+    }
+
+    if let Some(min) = options.min_padding {
+        layouts.retain(|l| l.metrics.padding_bytes >= min);
+    }
+    sort_layouts(&mut layouts, options.sort_by, cache_line_size);
+
+    let heatmap = options.cache_heatmap.then(|| cache_line_heatmap(&layouts, cache_line_size));
+    let files = options.by_file.then(|| summarize_by_file(&layouts));
+    let namespaces =
+        options.by_namespace.then(|| summarize_by_namespace(&layouts, options.namespace_depth));
+    let owners = match (options.codeowners, &files) {
+        (Some(codeowners), Some(files)) => Some(summarize_by_owner(files, codeowners)),
+        _ => None,
+    };
+    let summary = options.summary.then(|| summarize_binary(&layouts));
+    let templates = options.collapse_templates.then(|| collapse_templates(&mut layouts));
+    let out_of_time = scans.iter().any(|s| s.stats.out_of_time)
+        || options.deadline.is_some_and(|d| Instant::now() >= d);
+
+    Ok(InspectReport {
+        scans,
+        inputs,
+        found,
+        layouts,
+        top: options.top,
+        heatmap,
+        files,
+        owners,
+        namespaces,
+        templates,
+        global_false_sharing: global_sharing,
+        summary,
+        out_of_time,
+    })
+}
+
+/// The per-struct analysis of inspect: metrics, bases, bits, false sharing, allocator slack,
+/// and the padding weighted by `instances`.
+fn analyze_inspected(
+    layout: &mut StructLayout,
+    options: &InspectOptions<'_>,
+    instances: Option<u64>,
+) {
+    let cache_line_size = options.cache_line_size;
+    let bases = analyze_bases(layout);
+    if options.flatten_bases {
+        *layout = flatten_bases(layout);
+    }
+    analyze_layout(layout, cache_line_size);
+    layout.metrics.bases = bases;
+    if options.bits || is_bitfield_only(layout) {
+        layout.metrics.bits = analyze_bits(layout);
+    }
+    if options.warn_false_sharing {
+        let default_patterns = AtomicPatterns::default();
+        let patterns = options.atomic_patterns.unwrap_or(&default_patterns);
+        layout.metrics.false_sharing =
+            Some(analyze_false_sharing_with_patterns(layout, cache_line_size, patterns));
+    }
+    layout.metrics.alloc_slack = options.allocator.map(|a| a.slack(layout.size));
+    layout.metrics.static_instances = instances;
+    layout.metrics.weighted_waste_bytes =
+        instances.map(|n| n.saturating_mul(layout.metrics.padding_bytes));
+}
+
+/// What [`stream_inspect`] found.
+#[derive(Debug, Default)]
+pub struct StreamReport {
+    /// Files the result depends on: the binaries and any separate debug info they use.
+    pub inputs: Vec<PathBuf>,
+    /// Structs found, before `min_padding` narrowed them.
+    pub found: usize,
+    /// Structs handed to the callback.
+    pub emitted: usize,
+    pub notices: Vec<Notice>,
+    /// True if the scan stopped at [`InspectOptions::deadline`]; structs defined only in later
+    /// units are missing.
+    pub out_of_time: bool,
+}
+
+/// Like [`run_inspect`], but hand each struct to `emit` as soon as its compilation unit is
+/// parsed and the struct analyzed, so consumers start early and memory is the largest unit
+/// plus a 32-byte digest per distinct struct. Structs come in `.debug_info` order, binary by
+/// binary, with repeated definitions dropped. Units dropped by the unit timeout, and a scan
+/// cut short by `deadline`, are reported in [`StreamReport::notices`].
+///
+/// Only per-struct work applies: `scope`, `type_sizes`, `min_padding`, and the analysis
+/// switches. Sorting, `top`, the aggregate summaries, and the annotations that need every
+/// struct of a binary first (usage, static access and instances, global false sharing, and
+/// `scan.exported_only`) are ignored; callers wanting them use [`run_inspect`].
+pub fn stream_inspect(
+    options: &InspectOptions<'_>,
+    mut emit: impl FnMut(&StructLayout) -> Result<()>,
+) -> Result<StreamReport> {
+    let binary_paths = expand_binary_paths(options.binary_paths)?;
+    let provenance = binary_paths.len() > 1;
+    let mut report = StreamReport::default();
+    for path in &binary_paths {
+        let binary = load_binary(path, "")?;
+        let loaded = binary.load_dwarf().map_err(|e| {
+            e.context(format!("Failed to load DWARF debug info: {}", path.display()))
+        })?;
+        report.inputs.extend(binary.input_files());
+
+        let dwarf = DwarfContext::new(&loaded)
+            .with_scope(options.scope.cloned().unwrap_or_default())
+            .with_unit_timeout(options.unit_timeout)
+            .with_deadline(options.deadline)
+            .with_atomic_patterns(options.atomic_patterns.cloned().unwrap_or_default());
+        let mut structs =
+            dwarf.iter_structs(options.scan.filter, options.scan.include_go_runtime).deduplicated();
+        for layout in structs.by_ref() {
+            let mut layout = layout.map_err(|e| e.context("Failed to parse struct layouts"))?;
+            report.found += 1;
+            let single = std::slice::from_mut(&mut layout);
+            if let Some(type_sizes) = options.type_sizes {
+                let conflicts = type_sizes.apply(single);
+                report.notices.extend(conflicts.into_iter().map(Notice::TypeSizeConflict));
+            }
+            if provenance {
+                record_binary(single, path);
+            }
+            analyze_inspected(&mut layout, options, None);
+            if options.min_padding.is_some_and(|min| layout.metrics.padding_bytes < min) {
+                continue;
+            }
+            emit(&layout)?;
+            report.emitted += 1;
+        }
+        report.notices.extend(coverage_notices(
+            structs.skipped_units().iter(),
+            structs.units_read(),
+            structs.out_of_time(),
+        ));
+        report
+            .notices
+            .extend(dwarf.target_mismatches().iter().cloned().map(Notice::TargetMismatch));
+        if structs.out_of_time() {
+            report.out_of_time = true;
+            break;
+        }
+    }
+    Ok(report)
+}
+
+/// The old side of a diff.
+#[derive(Debug, Clone, Copy)]
+pub enum DiffBaseline<'a> {
+    Binary(&'a Path),
+    /// A JSON snapshot written by `layout-audit snapshot`.
+    Snapshot(&'a Path),
+}
+
+/// Options for [`run_diff`]. [`DiffOptions::new`] gives the CLI's defaults.
+#[derive(Clone, Copy)]
+pub struct DiffOptions<'a> {
+    pub old: DiffBaseline<'a>,
+    pub new_path: &'a Path,
+    pub scan: ScanOptions<'a>,
+    pub scope: Option<&'a StructScope>,
+    pub type_sizes: Option<&'a TypeSizes>,
+    pub cache_line_size: u32,
+    /// Also compare thread-shared globals sharing a cache line. Needs a binary baseline.
+    pub global_false_sharing: bool,
+    pub atomic_patterns: Option<&'a AtomicPatterns>,
+}
+
+impl<'a> DiffOptions<'a> {
+    pub fn new(old: DiffBaseline<'a>, new_path: &'a Path) -> Self {
+        Self {
+            old,
+            new_path,
+            scan: ScanOptions::default(),
+            scope: None,
+            type_sizes: None,
+            cache_line_size: 64,
+            global_false_sharing: false,
+            atomic_patterns: None,
+        }
+    }
+}
+
+/// What [`run_diff`] found.
+#[derive(Debug)]
+pub struct DiffReport {
+    pub diff: DiffResult,
+    /// Analyzed layouts of each side.
+    pub old_layouts: Vec<StructLayout>,
+    pub new_layouts: Vec<StructLayout>,
+    pub global_false_sharing: Option<GlobalFalseSharingDiff>,
+    /// Files the result depends on: both sides and any separate debug info they use.
+    pub inputs: Vec<PathBuf>,
+    pub notices: Vec<Notice>,
+}
+
+/// Compare the structs of the old and new side of `options`. A snapshot baseline was narrowed
+/// when it was taken; this run's filters apply on top, except `exported_only`, which needs
+/// symbols and so only narrows the new side.
+pub fn run_diff(options: &DiffOptions<'_>) -> Result<DiffReport> {
+    let cache_line_size = options.cache_line_size;
+    let scope = options.scope.cloned().unwrap_or_default();
+    let patterns = options.atomic_patterns.cloned().unwrap_or_default();
+    let ScanOptions { filter, include_go_runtime, sort_stable, .. } = options.scan;
+    if options.global_false_sharing && matches!(options.old, DiffBaseline::Snapshot(_)) {
+        return Err(Error::SnapshotGlobals);
+    }
+
+    let new_binary = load_binary(options.new_path, "new ")?;
+    let new_loaded =
+        new_binary.load_dwarf().map_err(|e| e.context("Failed to load DWARF from new binary"))?;
+
+    let mut inputs = Vec::new();
+    let mut notices = Vec::new();
+    let mut old_global_sharing = Vec::new();
+    // `None` when the old side is the new build, whose analysis is then reused.
+    let mut old_layouts = match options.old {
+        DiffBaseline::Binary(old_path) => {
+            let old_binary = load_binary(old_path, "old ")?;
+            if old_binary.is_same_build(&new_binary) {
+                inputs.extend(old_binary.input_files());
+                notices.push(Notice::SameBuild);
+                None
+            } else {
+                let old_loaded = old_binary
+                    .load_dwarf()
+                    .map_err(|e| e.context("Failed to load DWARF from old binary"))?;
+                inputs.extend(old_binary.input_files());
+                let old_dwarf = DwarfContext::new(&old_loaded)
+                    .with_scope(scope.clone())
+                    .with_atomic_patterns(patterns.clone());
+                if options.global_false_sharing {
+                    old_global_sharing =
+                        find_global_false_sharing(&old_binary, &old_dwarf, cache_line_size)?;
+                }
+                let scan = find_layouts(&old_binary, &old_dwarf, &options.scan)?;
+                notices.extend(scan.notices);
+                Some(scan.layouts)
+            }
+        }
+        DiffBaseline::Snapshot(path) => {
+            inputs.push(path.to_path_buf());
+            let mut layouts = Snapshot::load(path)?.into_layouts();
+            layouts.retain(|l| {
+                let variant_members =
+                    l.enum_layout.iter().flat_map(|e| &e.variants).flat_map(|v| &v.members);
+                filter.is_none_or(|f| l.name.contains(f))
+                    && scope.contains(&l.name)
+                    && scope.admits_members(l.members.iter().chain(variant_members))
+                    && (include_go_runtime || !is_go_internal_type(&l.name))
+            });
+            if sort_stable {
+                layouts.sort_by(StructLayout::canonical_cmp);
+            }
+            Some(layouts)
+        }
+    };
+    inputs.extend(new_binary.input_files());
+
+    let new_dwarf = DwarfContext::new(&new_loaded).with_scope(scope).with_atomic_patterns(patterns);
+    let scan = find_layouts(&new_binary, &new_dwarf, &options.scan)?;
+    notices.extend(scan.notices);
+    let mut new_layouts = scan.layouts;
+
+    if let Some(type_sizes) = options.type_sizes {
+        for layouts in old_layouts.iter_mut().chain([&mut new_layouts]) {
+            notices.extend(type_sizes.apply(layouts).into_iter().map(Notice::TypeSizeConflict));
+        }
+    }
+    for layout in old_layouts.iter_mut().flatten().chain(&mut new_layouts) {
+        analyze_layout(layout, cache_line_size);
+    }
+    let same_build = old_layouts.is_none();
+    let old_layouts = old_layouts.unwrap_or_else(|| new_layouts.clone());
+
+    let diff = diff_layouts(&old_layouts, &new_layouts);
+    let global_false_sharing = if options.global_false_sharing {
+        let new_global_sharing =
+            find_global_false_sharing(&new_binary, &new_dwarf, cache_line_size)?;
+        if same_build {
+            old_global_sharing.clone_from(&new_global_sharing);
+        }
+        Some(diff_global_false_sharing(&old_global_sharing, &new_global_sharing))
+    } else {
+        None
+    };
+
+    Ok(DiffReport { diff, old_layouts, new_layouts, global_false_sharing, inputs, notices })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::find_fixture_path;
+
+    #[test]
+    fn stream_inspect_emits_what_run_inspect_finds() {
+        let Some(path) = find_fixture_path("test_simple") else { return };
+        let paths = [path];
+        let options = InspectOptions { min_padding: Some(1), ..InspectOptions::new(&paths) };
+        let mut streamed = Vec::new();
+        let report = stream_inspect(&options, |layout| {
+            streamed.push((layout.name.clone(), layout.metrics.padding_bytes));
+            Ok(())
+        })
+        .unwrap();
+
+        let mut collected: Vec<(String, u64)> = run_inspect(&options)
+            .unwrap()
+            .layouts
+            .into_iter()
+            .map(|l| (l.name, l.metrics.padding_bytes))
+            .collect();
+        streamed.sort();
+        collected.sort();
+        assert_eq!(streamed, collected);
+        assert_eq!(report.emitted, streamed.len());
+        assert!(report.found > report.emitted);
+    }
+
+    #[test]
+    fn stream_inspect_reports_skipped_units_and_deadline() {
+        let Some(path) = find_fixture_path("test_simple") else { return };
+        let paths = [path];
+        let options = InspectOptions {
+            unit_timeout: Some(Duration::from_nanos(1)),
+            ..InspectOptions::new(&paths)
+        };
+        let report = stream_inspect(&options, |_| Ok(())).unwrap();
+        assert_eq!(report.emitted, 0);
+        assert!(matches!(report.notices[0], Notice::SkippedUnit { .. }));
+        assert!(report.notices.iter().any(|n| matches!(n, Notice::IncompleteCoverage { .. })));
+        assert!(!report.out_of_time);
+
+        let options =
+            InspectOptions { deadline: Some(Instant::now()), ..InspectOptions::new(&paths) };
+        let report = stream_inspect(&options, |_| Ok(())).unwrap();
+        assert_eq!(report.emitted, 0);
+        assert!(report.out_of_time);
+        assert!(report.notices.contains(&Notice::OutOfTime { units: 0 }));
+    }
+
+    #[test]
+    fn inspect_sorts_and_keeps_every_match_behind_top() {
+        let Some(path) = find_fixture_path("test_simple") else { return };
+        let paths = [path];
+        let options = InspectOptions {
+            sort_by: SortField::Padding,
+            top: Some(1),
+            cache_heatmap: true,
+            ..InspectOptions::new(&paths)
+        };
+        let report = run_inspect(&options).unwrap();
+
+        assert_eq!(report.scans.len(), 1);
+        assert_eq!(report.found, report.layouts.len());
+        assert!(report.layouts.len() > 1);
+        assert_eq!(report.shown().len(), 1);
+        assert!(
+            report
+                .layouts
+                .windows(2)
+                .all(|w| w[0].metrics.padding_bytes >= w[1].metrics.padding_bytes)
+        );
+        assert!(report.layouts.iter().all(|l| l.binary.is_none()));
+        assert!(report.heatmap.is_some() && report.files.is_none());
+
+        let min = report.layouts[0].metrics.padding_bytes;
+        let narrowed = run_inspect(&InspectOptions { min_padding: Some(min), ..options }).unwrap();
+        assert_eq!(narrowed.found, report.found);
+        assert!(narrowed.layouts.iter().all(|l| l.metrics.padding_bytes >= min));
+    }
+
+    #[test]
+    fn diff_reports_changes_between_binaries() {
+        let (Some(old), Some(new)) =
+            (find_fixture_path("test_simple"), find_fixture_path("test_modified"))
+        else {
+            return;
+        };
+        let options = DiffOptions::new(DiffBaseline::Binary(&old), &new);
+        let report = run_diff(&options).unwrap();
+        assert!(report.diff.has_changes());
+        assert!(report.inputs.contains(&old) && report.inputs.contains(&new));
+
+        assert!(!report.notices.contains(&Notice::SameBuild));
+
+        let same = run_diff(&DiffOptions::new(DiffBaseline::Binary(&new), &new)).unwrap();
+        assert!(!same.diff.has_changes());
+        assert!(same.notices.contains(&Notice::SameBuild));
+        assert_eq!(same.old_layouts.len(), same.new_layouts.len());
+
+        // A copy is recognized by its build-id or contents, not its path.
+        let copy = std::env::temp_dir().join("layout_audit_same_build_copy");
+        std::fs::copy(&new, &copy).unwrap();
+        let copied = run_diff(&DiffOptions::new(DiffBaseline::Binary(&copy), &new)).unwrap();
+        std::fs::remove_file(&copy).ok();
+        assert!(copied.notices.contains(&Notice::SameBuild));
+
+        let snapshot = DiffOptions {
+            global_false_sharing: true,
+            ..DiffOptions::new(DiffBaseline::Snapshot(&old), &new)
+        };
+        assert!(matches!(run_diff(&snapshot), Err(Error::SnapshotGlobals)));
+    }
+}
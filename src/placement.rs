@@ -0,0 +1,231 @@
+//! Placement advice for structs written by more than one thread: which structs should get
+//! cache lines of their own, why, the wrapper that does it in the target language, and what it
+//! costs in size.
+//!
+//! A struct is shared when the config's `thread_owners` gives it two or more owners. A struct
+//! no owner claims is presumed shared when it holds an atomic or a lock; one claimed by a single
+//! owner is thread-private even if it does.
+
+use crate::analysis::AtomicPatterns;
+use crate::error::{Error, Result};
+use crate::names::canonical_name;
+use crate::output::CodeLanguage;
+use crate::types::StructLayout;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Serialize;
+
+/// Struct name globs per owning thread (or thread pool), from the config's `thread_owners`.
+#[derive(Debug, Clone, Default)]
+pub struct ThreadOwners {
+    owners: Vec<(String, GlobSet)>,
+}
+
+impl ThreadOwners {
+    pub fn new<'a>(
+        owners: impl IntoIterator<Item = (&'a String, &'a Vec<String>)>,
+    ) -> Result<Self> {
+        let mut compiled = Vec::new();
+        for (owner, patterns) in owners {
+            let mut set = GlobSetBuilder::new();
+            for pattern in patterns {
+                let glob = GlobBuilder::new(pattern)
+                    .literal_separator(false)
+                    .build()
+                    .map_err(|e| Error::InvalidPattern(format!("'{}': {}", pattern, e)))?;
+                set.add(glob);
+            }
+            let set = set
+                .build()
+                .map_err(|e| Error::InvalidPattern(format!("thread owner '{}': {}", owner, e)))?;
+            compiled.push((owner.clone(), set));
+        }
+        Ok(Self { owners: compiled })
+    }
+
+    /// Owners whose patterns match `struct_name`, in config order.
+    pub fn owners_of(&self, struct_name: &str) -> Vec<String> {
+        self.owners
+            .iter()
+            .filter(|(_, set)| set.is_match(struct_name))
+            .map(|(owner, _)| owner.clone())
+            .collect()
+    }
+}
+
+/// Why a struct counts as shared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SharedBy {
+    /// `thread_owners` names more than one owner.
+    Owners { owners: Vec<String> },
+    /// No owner is declared, and these members are atomics or locks.
+    Atomics { members: Vec<String> },
+}
+
+/// How to give one shared struct cache lines of its own.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlacementAdvice {
+    pub struct_name: String,
+    pub shared_by: SharedBy,
+    pub size: u64,
+    /// Size once padded to a multiple of the line.
+    pub padded_size: u64,
+    /// Bytes the padding adds per instance.
+    pub cost_bytes: u64,
+    /// Declaration of the wrapper in the target language.
+    pub wrapper: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Placement {
+    /// Bytes each shared struct is aligned and padded to.
+    pub line: u64,
+    pub advice: Vec<PlacementAdvice>,
+    /// Shared structs already aligned to and sized in whole lines.
+    #[serde(skip_serializing_if = "crate::output::skip_empty")]
+    pub already_isolated: Vec<String>,
+    /// `cost_bytes` summed over `advice`.
+    pub total_cost_bytes: u64,
+}
+
+impl Placement {
+    pub fn new(
+        layouts: &[StructLayout],
+        owners: &ThreadOwners,
+        atomic_patterns: &AtomicPatterns,
+        line: u64,
+        language: CodeLanguage,
+    ) -> Self {
+        let mut advice = Vec::new();
+        let mut already_isolated = Vec::new();
+        for layout in layouts {
+            let Some(shared_by) = shared_by(layout, owners, atomic_patterns) else {
+                continue;
+            };
+            let padded_size = layout.size.next_multiple_of(line);
+            if layout.alignment.is_some_and(|a| a >= line) && padded_size == layout.size {
+                already_isolated.push(layout.name.clone());
+                continue;
+            }
+            advice.push(PlacementAdvice {
+                struct_name: layout.name.clone(),
+                shared_by,
+                size: layout.size,
+                padded_size,
+                cost_bytes: padded_size - layout.size,
+                wrapper: wrapper(
+                    canonical_name(&layout.name),
+                    layout.size,
+                    padded_size,
+                    line,
+                    language,
+                ),
+            });
+        }
+        let total_cost_bytes = advice.iter().map(|a| a.cost_bytes).sum();
+        Self { line, advice, already_isolated, total_cost_bytes }
+    }
+}
+
+fn shared_by(
+    layout: &StructLayout,
+    owners: &ThreadOwners,
+    atomic_patterns: &AtomicPatterns,
+) -> Option<SharedBy> {
+    let owners = owners.owners_of(&layout.name);
+    match owners.len() {
+        0 => {
+            let members: Vec<String> = layout
+                .members
+                .iter()
+                .filter(|m| m.is_atomic || atomic_patterns.matches(&m.type_name))
+                .map(|m| m.name.clone())
+                .collect();
+            (!members.is_empty()).then_some(SharedBy::Atomics { members })
+        }
+        1 => None,
+        _ => Some(SharedBy::Owners { owners }),
+    }
+}
+
+/// A wrapper that aligns `name` to `line` bytes and pads it to `padded_size`. Go cannot raise
+/// alignment, so its wrapper only pads: array elements stay apart, but the first line may still
+/// be shared with whatever the allocator put before it.
+fn wrapper(name: &str, size: u64, padded_size: u64, line: u64, language: CodeLanguage) -> String {
+    // `Padded` plus the name's identifier characters, so generics still give a valid name.
+    let padded: String =
+        "Padded".chars().chain(name.chars().filter(|c| c.is_alphanumeric() || *c == '_')).collect();
+    match language {
+        // crossbeam's CachePadded aligns to 128 bytes on x86_64 and aarch64.
+        CodeLanguage::Rust if line == 128 => format!("crossbeam_utils::CachePadded<{}>", name),
+        CodeLanguage::Rust => format!("#[repr(align({}))] struct {}({});", line, padded, name),
+        CodeLanguage::C => format!(
+            "struct __attribute__((aligned({}))) {} {{ struct {} value; }};",
+            line, padded, name
+        ),
+        CodeLanguage::Go => {
+            format!("type {} struct {{ value {}; _ [{}]byte }}", padded, name, padded_size - size)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    fn layout(name: &str, size: u64, members: &[(&str, &str)]) -> StructLayout {
+        let mut layout = StructLayout::new(name.to_string(), size, Some(8));
+        layout.members = members
+            .iter()
+            .enumerate()
+            .map(|(i, (member, ty))| {
+                MemberLayout::new(member.to_string(), ty.to_string(), Some(i as u64 * 8), Some(8))
+            })
+            .collect();
+        layout
+    }
+
+    #[test]
+    fn shared_structs_get_wrappers_and_costs() {
+        let owners: indexmap::IndexMap<String, Vec<String>> = [
+            ("io".to_string(), vec!["Queue".to_string(), "Conn*".to_string()]),
+            ("worker".to_string(), vec!["Queue".to_string(), "Stats".to_string()]),
+        ]
+        .into_iter()
+        .collect();
+        let owners = ThreadOwners::new(&owners).unwrap();
+        let layouts = vec![
+            layout("Queue", 24, &[("head", "u64"), ("tail", "u64"), ("len", "u64")]),
+            // Claimed by one owner: private despite the atomic.
+            layout("Stats", 8, &[("hits", "core::sync::atomic::AtomicU64")]),
+            layout("Counter", 16, &[("value", "core::sync::atomic::AtomicU64"), ("id", "u64")]),
+            layout("Plain", 8, &[("x", "u64")]),
+        ];
+
+        let placement =
+            Placement::new(&layouts, &owners, &AtomicPatterns::default(), 64, CodeLanguage::Rust);
+
+        let names: Vec<&str> = placement.advice.iter().map(|a| a.struct_name.as_str()).collect();
+        assert_eq!(names, vec!["Queue", "Counter"]);
+        let queue = &placement.advice[0];
+        assert_eq!(
+            queue.shared_by,
+            SharedBy::Owners { owners: vec!["io".into(), "worker".into()] }
+        );
+        assert_eq!((queue.padded_size, queue.cost_bytes), (64, 40));
+        assert_eq!(queue.wrapper, "#[repr(align(64))] struct PaddedQueue(Queue);");
+        assert_eq!(
+            placement.advice[1].shared_by,
+            SharedBy::Atomics { members: vec!["value".into()] }
+        );
+        assert_eq!(placement.total_cost_bytes, 40 + 48);
+
+        let crossbeam =
+            Placement::new(&layouts, &owners, &AtomicPatterns::default(), 128, CodeLanguage::Rust);
+        assert_eq!(crossbeam.advice[0].wrapper, "crossbeam_utils::CachePadded<Queue>");
+        let go =
+            Placement::new(&layouts, &owners, &AtomicPatterns::default(), 64, CodeLanguage::Go);
+        assert_eq!(go.advice[0].wrapper, "type PaddedQueue struct { value Queue; _ [40]byte }");
+    }
+}
@@ -0,0 +1,195 @@
+//! Reproduction bundles: a zstd-compressed tar holding just the DWARF a bug report needs.
+//!
+//! The debug sections are copied into a fresh ELF object (`debug.elf`) so maintainers can run
+//! layout-audit on it directly, without access to the original binary's code or data.
+
+use crate::error::{Error, Result};
+use object::write::Object as WriteObject;
+use object::{Object, ObjectSection, SectionKind};
+use std::path::Path;
+
+/// Name of the extracted debug object inside a bundle.
+pub const REPRO_DEBUG_OBJECT: &str = "debug.elf";
+
+/// String sections rewritten by redaction. Offsets into them stay valid because every
+/// string keeps its length.
+const STRING_SECTIONS: &[&str] = &[".debug_str", ".debug_line_str"];
+
+const TAR_BLOCK: usize = 512;
+
+#[derive(Default)]
+pub struct ReproBundle {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl ReproBundle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, data: impl Into<Vec<u8>>) {
+        self.entries.push((name.into(), data.into()));
+    }
+
+    /// Archive the entries as an uncompressed ustar stream, in insertion order.
+    pub fn to_tar(&self) -> Result<Vec<u8>> {
+        let mut tar = Vec::new();
+        for (name, data) in &self.entries {
+            tar.extend_from_slice(&tar_header(name, data.len())?);
+            tar.extend_from_slice(data);
+            tar.resize(tar.len().next_multiple_of(TAR_BLOCK), 0);
+        }
+        tar.resize(tar.len() + 2 * TAR_BLOCK, 0);
+        Ok(tar)
+    }
+
+    /// Write the bundle as `.tar.zst`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let tar = self.to_tar()?;
+        let compressed = ruzstd::encoding::compress_to_vec(
+            &tar[..],
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+        std::fs::write(path, compressed)?;
+        Ok(())
+    }
+}
+
+/// Copy the DWARF sections of `data` into a new ELF object with the same architecture and
+/// endianness. Compressed sections are stored decompressed, and Mach-O `__debug_*` names are
+/// mapped to `.debug_*`. With `redact_strings`, every string in the string sections is
+/// replaced by same-length filler derived from its contents.
+pub fn extract_debug_object(data: &[u8], redact_strings: bool) -> Result<Vec<u8>> {
+    let file = object::File::parse(data)?;
+    let mut out =
+        WriteObject::new(object::BinaryFormat::Elf, file.architecture(), file.endianness());
+
+    for section in file.sections() {
+        let Ok(name) = section.name() else {
+            continue;
+        };
+        let Some(base) = name
+            .strip_prefix(".debug_")
+            .or_else(|| name.strip_prefix(".zdebug_"))
+            .or_else(|| name.strip_prefix("__debug_"))
+        else {
+            continue;
+        };
+        let name = format!(".debug_{}", base);
+
+        let mut contents = section.uncompressed_data()?.into_owned();
+        if redact_strings && STRING_SECTIONS.contains(&name.as_str()) {
+            redact_string_table(&mut contents);
+        }
+
+        let id = out.add_section(Vec::new(), name.into_bytes(), SectionKind::Debug);
+        out.set_section_data(id, contents, 1);
+    }
+
+    out.write().map_err(|e| Error::Repro(format!("failed to write debug object: {}", e)))
+}
+
+/// Replace each NUL-terminated string with lowercase letters seeded by an FNV-1a hash of the
+/// original, so distinct names usually stay distinct and offsets are unchanged.
+fn redact_string_table(table: &mut [u8]) {
+    for string in table.split_mut(|&b| b == 0) {
+        let mut state = string.iter().fold(0xcbf2_9ce4_8422_2325u64, |h, &b| {
+            (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        for byte in string.iter_mut() {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            *byte = b'a' + (state % 26) as u8;
+        }
+    }
+}
+
+fn tar_header(name: &str, size: usize) -> Result<[u8; TAR_BLOCK]> {
+    if name.len() > 100 {
+        return Err(Error::Repro(format!("entry name too long: {}", name)));
+    }
+
+    let mut header = [0u8; TAR_BLOCK];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size as u64);
+    // mtime 0 keeps bundles byte-identical for identical inputs.
+    write_octal(&mut header[136..148], 0);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // The checksum is computed with its own field filled with spaces.
+    header[148..156].fill(b' ');
+    let checksum: u64 = header.iter().map(|&b| b as u64).sum();
+    write_octal(&mut header[148..155], checksum);
+
+    Ok(header)
+}
+
+/// Zero-padded octal followed by NUL, filling `field`.
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = format!("{:0width$o}", value, width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+    field[field.len() - 1] = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_names(tar: &[u8]) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut pos = 0;
+        while pos + TAR_BLOCK <= tar.len() && tar[pos] != 0 {
+            let header = &tar[pos..pos + TAR_BLOCK];
+            let name_len = header[..100].iter().position(|&b| b == 0).unwrap_or(100);
+            names.push(String::from_utf8_lossy(&header[..name_len]).into_owned());
+            let size_field = std::str::from_utf8(&header[124..135]).unwrap();
+            let size = usize::from_str_radix(size_field, 8).unwrap();
+            pos += TAR_BLOCK + size.next_multiple_of(TAR_BLOCK);
+        }
+        names
+    }
+
+    #[test]
+    fn tar_lists_entries_in_order() {
+        let mut bundle = ReproBundle::new();
+        bundle.add("manifest.json", b"{}".to_vec());
+        bundle.add("output.json", vec![b'x'; 600]);
+
+        let tar = bundle.to_tar().unwrap();
+        assert_eq!(tar.len() % TAR_BLOCK, 0);
+        assert_eq!(entry_names(&tar), vec!["manifest.json", "output.json"]);
+    }
+
+    #[test]
+    fn tar_header_checksum_is_valid() {
+        let header = tar_header("a", 3).unwrap();
+        let stored = u64::from_str_radix(std::str::from_utf8(&header[148..154]).unwrap(), 8);
+
+        let mut blank = header;
+        blank[148..156].fill(b' ');
+        let expected: u64 = blank.iter().map(|&b| b as u64).sum();
+        assert_eq!(stored.unwrap(), expected);
+    }
+
+    #[test]
+    fn redaction_keeps_lengths_and_offsets() {
+        let mut table = b"MyStruct\0secret_field\0\0MyStruct\0".to_vec();
+        let original = table.clone();
+        redact_string_table(&mut table);
+
+        assert_eq!(table.len(), original.len());
+        for (a, b) in table.iter().zip(&original) {
+            assert_eq!(*a == 0, *b == 0);
+        }
+        assert!(!table.windows(8).any(|w| w == b"MyStruct"));
+        // Identical strings redact identically.
+        assert_eq!(table[..8], table[23..31]);
+    }
+}
@@ -0,0 +1,383 @@
+//! Type layouts printed by `rustc -Zprint-type-sizes`, cross-checked against the debug info.
+//!
+//! rustc lists each type's size and alignment, then its fields in memory order with the padding
+//! between them, so field offsets are recovered by adding up the lines. Enum variants start
+//! after the discriminant.
+
+use crate::error::{Error, Result};
+use crate::names::canonical_name;
+use crate::types::{MemberLayout, StructLayout};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+const PREFIX: &str = "print-type-size";
+
+/// One type from `-Zprint-type-sizes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustcLayout {
+    pub name: String,
+    pub size: u64,
+    pub alignment: u64,
+    /// Fields in memory order. Empty for types with entries that are not plain fields
+    /// (generator upvars and locals), whose offsets cannot be recovered.
+    pub fields: Vec<RustcField>,
+    pub variants: Vec<RustcVariant>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustcVariant {
+    pub name: String,
+    pub fields: Vec<RustcField>,
+}
+
+/// A field, named as the debug info spells it (`__0` for tuple field `.0`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustcField {
+    pub name: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl RustcLayout {
+    pub fn load(path: &Path) -> Result<Vec<Self>> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse compiler output; lines without the `print-type-size` prefix (cargo's progress,
+    /// warnings) are skipped.
+    pub fn parse(text: &str) -> Result<Vec<Self>> {
+        let mut layouts: Vec<RustcLayout> = Vec::new();
+        let mut complete = true;
+        let mut discriminant = 0;
+        let mut cursor = 0;
+
+        for (index, line) in text.lines().enumerate() {
+            let Some(body) = line.strip_prefix(PREFIX) else { continue };
+            let invalid =
+                || Error::RustcSizes(format!("line {}: cannot parse '{}'", index + 1, line));
+
+            if let Some(header) = body.strip_prefix(" type: `") {
+                let (name, rest) = header.rsplit_once("`: ").ok_or_else(invalid)?;
+                let (size, alignment) = rest.split_once(", alignment: ").ok_or_else(invalid)?;
+                if !complete && let Some(last) = layouts.last_mut() {
+                    last.fields.clear();
+                    last.variants.clear();
+                }
+                layouts.push(RustcLayout {
+                    name: name.to_string(),
+                    size: bytes(size).ok_or_else(invalid)?,
+                    alignment: bytes(alignment).ok_or_else(invalid)?,
+                    fields: Vec::new(),
+                    variants: Vec::new(),
+                });
+                (complete, discriminant, cursor) = (true, 0, 0);
+                continue;
+            }
+
+            let Some(layout) = layouts.last_mut() else { continue };
+            let entry = body.trim_start();
+            let in_variant = body.len() - entry.len() > 5;
+            if let Some(size) = entry.strip_prefix("discriminant: ") {
+                discriminant = bytes(size).ok_or_else(invalid)?;
+                cursor = discriminant;
+            } else if let Some(variant) = entry.strip_prefix("variant `") {
+                let (name, _) = variant.split_once('`').ok_or_else(invalid)?;
+                layout.variants.push(RustcVariant { name: name.to_string(), fields: Vec::new() });
+                cursor = discriminant;
+            } else if let Some(size) = entry.strip_prefix("padding: ") {
+                cursor += bytes(size).ok_or_else(invalid)?;
+            } else if let Some(field) = entry.strip_prefix("field `.") {
+                let (name, rest) = field.split_once("`: ").ok_or_else(invalid)?;
+                let mut parts = rest.split(", ");
+                let size = parts.next().and_then(bytes).ok_or_else(invalid)?;
+                let offset = parts
+                    .find_map(|p| p.strip_prefix("offset: "))
+                    .and_then(bytes)
+                    .unwrap_or(cursor);
+                let field = RustcField { name: debug_info_field_name(name), offset, size };
+                cursor = offset + size;
+                match layout.variants.last_mut() {
+                    Some(variant) if in_variant => variant.fields.push(field),
+                    _ => layout.fields.push(field),
+                }
+            } else if !entry.starts_with("end padding: ") {
+                complete = false;
+            }
+        }
+        if !complete && let Some(last) = layouts.last_mut() {
+            last.fields.clear();
+            last.variants.clear();
+        }
+
+        if layouts.is_empty() {
+            return Err(Error::RustcSizes(
+                "no `print-type-size` lines found; build with `cargo rustc -- -Zprint-type-sizes`"
+                    .to_string(),
+            ));
+        }
+        Ok(layouts)
+    }
+
+    /// The name the debug info gives this type, for matching: unqualified, without lifetimes.
+    pub fn debug_info_name(&self) -> String {
+        canonical_name(&strip_lifetimes(&self.name)).to_string()
+    }
+}
+
+/// `N bytes`.
+fn bytes(s: &str) -> Option<u64> {
+    s.strip_suffix(" bytes")?.parse().ok()
+}
+
+/// Tuple fields `.0` are `__0` in the debug info.
+fn debug_info_field_name(name: &str) -> String {
+    if name.bytes().all(|b| b.is_ascii_digit()) { format!("__{}", name) } else { name.to_string() }
+}
+
+/// Drop lifetime arguments, which the debug info omits: `Ref<'a, T>` -> `Ref<T>`,
+/// `Guard<'_>` -> `Guard`, `&'a u8` -> `&u8`.
+fn strip_lifetimes(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            out.push(c);
+            continue;
+        }
+        while chars.next_if(|c| c.is_alphanumeric() || *c == '_').is_some() {}
+        if chars.next_if_eq(&',').is_some() {
+            chars.next_if_eq(&' ');
+        } else if chars.next_if_eq(&' ').is_none() && out.ends_with(", ") {
+            out.truncate(out.len() - 2);
+        }
+    }
+    out.replace("<>", "")
+}
+
+/// What disagrees between rustc and the debug info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MismatchKind {
+    Size,
+    Alignment,
+    Offset,
+    MemberSize,
+    MissingMember,
+    MissingVariant,
+}
+
+impl MismatchKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            MismatchKind::Size => "size",
+            MismatchKind::Alignment => "alignment",
+            MismatchKind::Offset => "offset",
+            MismatchKind::MemberSize => "member size",
+            MismatchKind::MissingMember => "missing member",
+            MismatchKind::MissingVariant => "missing variant",
+        }
+    }
+}
+
+/// One disagreement. `member` is `field` or `Variant.field` for member checks, the variant
+/// name for a missing variant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayoutMismatch {
+    pub type_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    pub kind: MismatchKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustc: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dwarf: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    /// rustc types compared against a struct in the debug info.
+    pub checked: usize,
+    /// rustc types with no struct of the same name in the debug info.
+    pub missing: Vec<String>,
+    pub mismatches: Vec<LayoutMismatch>,
+}
+
+/// Compare each rustc layout with the debug-info structs of the same unqualified name. A type
+/// passes if any of them agrees; otherwise the closest one's disagreements are reported.
+pub fn verify_layouts(rustc: &[RustcLayout], layouts: &[StructLayout]) -> VerifyReport {
+    let mut by_name: HashMap<&str, Vec<&StructLayout>> = HashMap::new();
+    for layout in layouts {
+        by_name.entry(canonical_name(&layout.name)).or_default().push(layout);
+    }
+
+    let mut report = VerifyReport::default();
+    for expected in rustc {
+        let Some(candidates) = by_name.get(expected.debug_info_name().as_str()) else {
+            report.missing.push(expected.name.clone());
+            continue;
+        };
+        report.checked += 1;
+        let closest = candidates
+            .iter()
+            .map(|layout| compare(expected, layout))
+            .min_by_key(Vec::len)
+            .unwrap_or_default();
+        report.mismatches.extend(closest);
+    }
+    report
+}
+
+fn compare(expected: &RustcLayout, layout: &StructLayout) -> Vec<LayoutMismatch> {
+    let mut mismatches = Vec::new();
+    let mut push = |member: Option<String>, kind, rustc, dwarf| {
+        mismatches.push(LayoutMismatch {
+            type_name: expected.name.clone(),
+            member,
+            kind,
+            rustc,
+            dwarf,
+        });
+    };
+
+    if layout.size != expected.size {
+        push(None, MismatchKind::Size, Some(expected.size), Some(layout.size));
+    }
+    if let Some(alignment) = layout.alignment.filter(|&a| a != expected.alignment) {
+        push(None, MismatchKind::Alignment, Some(expected.alignment), Some(alignment));
+    }
+
+    let mut fields: Vec<(String, &RustcField, &[MemberLayout])> =
+        expected.fields.iter().map(|f| (f.name.clone(), f, layout.members.as_slice())).collect();
+    if let Some(enum_layout) = &layout.enum_layout {
+        for variant in &expected.variants {
+            let Some(found) = enum_layout.variants.iter().find(|v| v.name == variant.name) else {
+                push(Some(variant.name.clone()), MismatchKind::MissingVariant, None, None);
+                continue;
+            };
+            fields.extend(
+                variant
+                    .fields
+                    .iter()
+                    .map(|f| (format!("{}.{}", variant.name, f.name), f, found.members.as_slice())),
+            );
+        }
+    }
+
+    for (label, field, members) in fields {
+        let Some(member) = members.iter().find(|m| m.name == field.name) else {
+            push(Some(label), MismatchKind::MissingMember, None, None);
+            continue;
+        };
+        if member.offset != Some(field.offset) {
+            push(Some(label.clone()), MismatchKind::Offset, Some(field.offset), member.offset);
+        }
+        if member.size.is_some_and(|size| size != field.size) {
+            push(Some(label), MismatchKind::MemberSize, Some(field.size), member.size);
+        }
+    }
+
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{EnumLayout, VariantLayout};
+
+    const OUTPUT: &str = "\
+   Compiling demo v0.1.0
+print-type-size type: `demo::Packet<'_>`: 24 bytes, alignment: 8 bytes
+print-type-size     field `.data`: 8 bytes
+print-type-size     field `.len`: 4 bytes
+print-type-size     field `.0`: 1 bytes
+print-type-size     end padding: 11 bytes
+print-type-size type: `Shape`: 24 bytes, alignment: 8 bytes
+print-type-size     discriminant: 4 bytes
+print-type-size     variant `Rect`: 20 bytes
+print-type-size         padding: 4 bytes
+print-type-size         field `.w`: 8 bytes, alignment: 8 bytes
+print-type-size         field `.h`: 8 bytes
+print-type-size     variant `Circle`: 4 bytes
+print-type-size         field `.0`: 4 bytes
+print-type-size type: `{async fn body of run()}`: 16 bytes, alignment: 8 bytes
+print-type-size     discriminant: 1 bytes
+print-type-size     variant `Unresumed`: 15 bytes
+print-type-size         upvar `.conn`: 8 bytes, offset: 8 bytes, alignment: 8 bytes
+";
+
+    fn member(name: &str, offset: u64, size: u64) -> MemberLayout {
+        MemberLayout::new(name.to_string(), "u64".to_string(), Some(offset), Some(size))
+    }
+
+    #[test]
+    fn parses_fields_variants_and_offsets() {
+        let layouts = RustcLayout::parse(OUTPUT).expect("parse");
+        assert_eq!(layouts.len(), 3);
+
+        let packet = &layouts[0];
+        assert_eq!(packet.debug_info_name(), "Packet");
+        assert_eq!((packet.size, packet.alignment), (24, 8));
+        let fields: Vec<_> =
+            packet.fields.iter().map(|f| (f.name.as_str(), f.offset, f.size)).collect();
+        assert_eq!(fields, vec![("data", 0, 8), ("len", 8, 4), ("__0", 12, 1)]);
+
+        let shape = &layouts[1];
+        assert!(shape.fields.is_empty());
+        let rect: Vec<_> = shape.variants[0].fields.iter().map(|f| (f.offset, f.size)).collect();
+        assert_eq!(rect, vec![(8, 8), (16, 8)]);
+        assert_eq!(shape.variants[1].fields[0].offset, 4);
+
+        // Upvars have no field-level equivalent; only size and alignment are checked.
+        assert!(layouts[2].variants.is_empty());
+    }
+
+    #[test]
+    fn rejects_output_without_type_sizes() {
+        assert!(RustcLayout::parse("   Compiling demo v0.1.0\n").is_err());
+        assert!(RustcLayout::parse("print-type-size type: `A`: 4 bytes\n").is_err());
+    }
+
+    #[test]
+    fn strips_lifetime_arguments() {
+        assert_eq!(strip_lifetimes("Ref<'a, T>"), "Ref<T>");
+        assert_eq!(strip_lifetimes("Pair<T, 'a>"), "Pair<T>");
+        assert_eq!(strip_lifetimes("Guard<'_>"), "Guard");
+        assert_eq!(strip_lifetimes("Slot<&'static u8>"), "Slot<&u8>");
+    }
+
+    #[test]
+    fn reports_disagreements_with_debug_info() {
+        let rustc = RustcLayout::parse(OUTPUT).expect("parse");
+
+        let mut packet = StructLayout::new("Packet".to_string(), 24, Some(8));
+        packet.members = vec![member("data", 0, 8), member("len", 12, 4)];
+        let mut shape = StructLayout::new("demo::Shape".to_string(), 24, Some(8));
+        shape.enum_layout = Some(EnumLayout::new(
+            None,
+            vec![VariantLayout {
+                name: "Rect".to_string(),
+                discriminant_value: Some(0),
+                payload_size: 16,
+                members: vec![member("w", 8, 8), member("h", 16, 8)],
+            }],
+        ));
+
+        let report = verify_layouts(&rustc, &[packet, shape]);
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.missing, vec!["{async fn body of run()}"]);
+        let found: Vec<_> = report
+            .mismatches
+            .iter()
+            .map(|m| (m.type_name.as_str(), m.member.as_deref(), m.kind))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("demo::Packet<'_>", Some("len"), MismatchKind::Offset),
+                ("demo::Packet<'_>", Some("__0"), MismatchKind::MissingMember),
+                ("Shape", Some("Circle"), MismatchKind::MissingVariant),
+            ]
+        );
+    }
+}
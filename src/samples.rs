@@ -0,0 +1,499 @@
+//! Sampled memory accesses, read from a `perf record -d` / `perf mem record` data file or a
+//! text list of addresses, and attributed to struct members through the global and static
+//! variables they land in.
+//!
+//! Heap and stack addresses cannot be attributed: the debug info gives a type only to
+//! variables at fixed addresses.
+
+use crate::error::{Error, Result};
+use crate::names::embedded_type;
+use crate::types::{GlobalVariable, StructLayout};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+const PERF_MAGIC: &[u8; 8] = b"PERFILE2";
+const PERF_HEADER_SIZE: usize = 104;
+
+const PERF_SAMPLE_IP: u64 = 1 << 0;
+const PERF_SAMPLE_TID: u64 = 1 << 1;
+const PERF_SAMPLE_TIME: u64 = 1 << 2;
+const PERF_SAMPLE_ADDR: u64 = 1 << 3;
+const PERF_SAMPLE_IDENTIFIER: u64 = 1 << 16;
+
+const PERF_RECORD_MMAP: u32 = 1;
+const PERF_RECORD_SAMPLE: u32 = 9;
+const PERF_RECORD_MMAP2: u32 = 10;
+const PERF_RECORD_COMPRESSED: u32 = 81;
+
+/// Data addresses sampled `count` times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessSample {
+    pub address: u64,
+    pub count: u64,
+}
+
+/// A file mapped into the profiled process, from a perf `MMAP`/`MMAP2` record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    pub start: u64,
+    pub len: u64,
+    pub pgoff: u64,
+    pub filename: String,
+}
+
+/// Samples read from one file, with the mappings needed to relocate them.
+#[derive(Debug, Clone, Default)]
+pub struct SampleSet {
+    pub samples: Vec<AccessSample>,
+    /// Empty for text input, whose addresses are taken as link-time addresses.
+    pub mappings: Vec<Mapping>,
+}
+
+impl SampleSet {
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::parse(&std::fs::read(path)?)
+    }
+
+    /// Parse a perf data file (recognized by its magic) or a text list of addresses.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.starts_with(PERF_MAGIC) {
+            parse_perf(data)
+        } else {
+            let text = std::str::from_utf8(data)
+                .map_err(|_| Error::Samples("not a perf data file or a text file".to_string()))?;
+            parse_text(text)
+        }
+    }
+
+    /// Total number of samples.
+    pub fn total(&self) -> u64 {
+        self.samples.iter().map(|s| s.count).sum()
+    }
+
+    /// How far `binary` was moved from its link-time addresses when profiled, from the first
+    /// mapping of a file with the same name. `link_address` maps a file offset of the binary to
+    /// its link-time address. `None` when no mapping of the binary was recorded.
+    pub fn load_bias(
+        &self,
+        binary: &Path,
+        link_address: impl Fn(u64) -> Option<u64>,
+    ) -> Option<u64> {
+        let name = binary.file_name()?;
+        self.mappings
+            .iter()
+            .filter(|m| Path::new(&m.filename).file_name() == Some(name))
+            .find_map(|m| link_address(m.pgoff).map(|link| m.start.wrapping_sub(link)))
+    }
+}
+
+/// Text input: one `ADDRESS[,COUNT]` per line (a comma or whitespace between the two), with
+/// hexadecimal addresses (`0x` optional) and decimal counts. Blank lines and `#` comments are
+/// skipped, as is a header line at the top.
+fn parse_text(text: &str) -> Result<SampleSet> {
+    let mut samples = Vec::new();
+    let mut first = true;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        match parse_text_line(line) {
+            Some(sample) => samples.push(sample),
+            // A header names its columns rather than holding numbers.
+            None if first => {}
+            None => {
+                return Err(Error::Samples(format!(
+                    "line {}: expected ADDRESS[,COUNT], found '{}'",
+                    index + 1,
+                    line
+                )));
+            }
+        }
+        first = false;
+    }
+    Ok(SampleSet { samples, mappings: Vec::new() })
+}
+
+fn parse_text_line(line: &str) -> Option<AccessSample> {
+    let mut fields = line.split(|c: char| c == ',' || c.is_whitespace()).filter(|f| !f.is_empty());
+    let address = fields.next()?;
+    let address =
+        address.strip_prefix("0x").or_else(|| address.strip_prefix("0X")).unwrap_or(address);
+    let address = u64::from_str_radix(address, 16).ok()?;
+    let count = match fields.next() {
+        Some(count) => count.parse().ok()?,
+        None => 1,
+    };
+    fields.next().is_none().then_some(AccessSample { address, count })
+}
+
+/// Little-endian reader over a perf data file.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .pos
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.pos..end))
+            .ok_or_else(|| Error::Samples("perf data file is truncated".to_string()))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    /// A NUL-padded string filling the rest of the record.
+    fn string(&mut self) -> String {
+        let rest = &self.data[self.pos.min(self.data.len())..];
+        let end = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+        self.pos = self.data.len();
+        String::from_utf8_lossy(&rest[..end]).into_owned()
+    }
+}
+
+/// A `(offset, size)` file section from the perf header, bounds-checked against `data`.
+fn perf_section<'a>(data: &'a [u8], header: &mut Reader<'_>) -> Result<&'a [u8]> {
+    let (offset, size) = (header.u64()?, header.u64()?);
+    usize::try_from(offset)
+        .ok()
+        .zip(usize::try_from(size).ok())
+        .and_then(|(offset, size)| data.get(offset..offset.checked_add(size)?))
+        .ok_or_else(|| Error::Samples("perf data file is truncated".to_string()))
+}
+
+fn parse_perf(data: &[u8]) -> Result<SampleSet> {
+    let mut header = Reader::new(data);
+    header.bytes(PERF_MAGIC.len())?;
+    let header_size = header.u64()?;
+    if header_size as usize != PERF_HEADER_SIZE {
+        return Err(Error::Samples(
+            "piped perf data is not supported; record to a file with `perf record -o`".to_string(),
+        ));
+    }
+    let attr_size = header.u64()? as usize;
+    let attrs = perf_section(data, &mut header)?;
+    let records = perf_section(data, &mut header)?;
+
+    let sample_type = perf_sample_type(attrs, attr_size)?;
+    if sample_type & PERF_SAMPLE_ADDR == 0 {
+        return Err(Error::Samples(
+            "samples carry no data addresses; record with `perf record -d` or `perf mem record`"
+                .to_string(),
+        ));
+    }
+
+    let mut counts: HashMap<u64, u64> = HashMap::new();
+    let mut mappings = Vec::new();
+    let mut reader = Reader::new(records);
+    while reader.pos < records.len() {
+        let kind = reader.u32()?;
+        let _misc = reader.u16()?;
+        let size = reader.u16()? as usize;
+        if size < 8 {
+            return Err(Error::Samples("perf data file has a malformed record".to_string()));
+        }
+        let mut record = Reader::new(reader.bytes(size - 8)?);
+        match kind {
+            PERF_RECORD_SAMPLE => {
+                let address = perf_sample_address(&mut record, sample_type)?;
+                // perf writes 0 when the hardware did not report an address.
+                if address != 0 {
+                    *counts.entry(address).or_default() += 1;
+                }
+            }
+            PERF_RECORD_MMAP | PERF_RECORD_MMAP2 => {
+                let (_pid, _tid) = (record.u32()?, record.u32()?);
+                let (start, len, pgoff) = (record.u64()?, record.u64()?, record.u64()?);
+                if kind == PERF_RECORD_MMAP2 {
+                    // Device and inode (or build id), then protection and flags.
+                    record.bytes(24 + 8)?;
+                }
+                mappings.push(Mapping { start, len, pgoff, filename: record.string() });
+            }
+            PERF_RECORD_COMPRESSED => {
+                return Err(Error::Samples(
+                    "compressed perf data is not supported; record without `-z`".to_string(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let mut samples: Vec<AccessSample> =
+        counts.into_iter().map(|(address, count)| AccessSample { address, count }).collect();
+    samples.sort_by_key(|s| s.address);
+    Ok(SampleSet { samples, mappings })
+}
+
+/// The `sample_type` shared by every event in the attrs section.
+fn perf_sample_type(attrs: &[u8], attr_size: usize) -> Result<u64> {
+    // Each entry is a `perf_event_attr` followed by the file section of its ids.
+    if attr_size < 40 || attrs.is_empty() {
+        return Err(Error::Samples("perf data file records no events".to_string()));
+    }
+    let mut sample_type = None;
+    for attr in attrs.chunks(attr_size) {
+        let mut reader = Reader::new(attr);
+        reader.bytes(24)?;
+        let this = reader.u64()?;
+        if sample_type.is_some_and(|t| t != this) {
+            return Err(Error::Samples(
+                "events record different sample fields; record one event at a time".to_string(),
+            ));
+        }
+        sample_type = Some(this);
+    }
+    Ok(sample_type.unwrap_or_default())
+}
+
+/// The data address of a `SAMPLE` record, skipping the fields stored before it.
+fn perf_sample_address(record: &mut Reader<'_>, sample_type: u64) -> Result<u64> {
+    for field in [PERF_SAMPLE_IDENTIFIER, PERF_SAMPLE_IP, PERF_SAMPLE_TID, PERF_SAMPLE_TIME] {
+        if sample_type & field != 0 {
+            record.u64()?;
+        }
+    }
+    record.u64()
+}
+
+/// Sampled accesses attributed to structs and their members.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FieldAccessReport {
+    /// Total number of samples read.
+    pub samples: u64,
+    /// Samples outside every struct-typed global variable.
+    pub unattributed: u64,
+    /// Structs with at least one sample, hottest first.
+    pub structs: Vec<StructAccess>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StructAccess {
+    pub name: String,
+    /// Samples anywhere in the struct, padding included.
+    pub hits: u64,
+    /// Every member with a known offset and size, in layout order.
+    pub members: Vec<MemberAccess>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MemberAccess {
+    pub name: String,
+    pub offset: u64,
+    pub size: u64,
+    pub hits: u64,
+}
+
+impl StructAccess {
+    /// Names of members with samples, hottest first (ties keep layout order).
+    pub fn hot_members(&self) -> Vec<&str> {
+        let mut hot: Vec<&MemberAccess> = self.members.iter().filter(|m| m.hits > 0).collect();
+        hot.sort_by_key(|m| std::cmp::Reverse(m.hits));
+        hot.into_iter().map(|m| m.name.as_str()).collect()
+    }
+}
+
+/// Attribute samples to the members of `layouts`. Sample addresses minus `load_bias` are
+/// matched against `globals`; an access to an array of structs counts for the member at the
+/// same offset within its element.
+pub fn attribute_accesses(
+    samples: &[AccessSample],
+    load_bias: u64,
+    globals: &[GlobalVariable],
+    layouts: &[StructLayout],
+) -> FieldAccessReport {
+    let by_name: HashMap<&str, &StructLayout> =
+        layouts.iter().map(|l| (l.name.as_str(), l)).collect();
+    let mut globals: Vec<&GlobalVariable> = globals.iter().filter(|g| g.size > 0).collect();
+    globals.sort_by_key(|g| g.address);
+
+    let mut report = FieldAccessReport::default();
+    let mut structs: HashMap<&str, StructAccess> = HashMap::new();
+    for sample in samples {
+        report.samples += sample.count;
+        let address = sample.address.wrapping_sub(load_bias);
+        let index = globals.partition_point(|g| g.address <= address);
+        let target = index
+            .checked_sub(1)
+            .map(|i| globals[i])
+            .filter(|g| address - g.address < g.size)
+            .and_then(|g| {
+                let layout = by_name.get(embedded_type(&g.type_name)?)?;
+                (layout.size > 0).then_some((g, *layout))
+            });
+        let Some((global, layout)) = target else {
+            report.unattributed += sample.count;
+            continue;
+        };
+
+        let access = structs.entry(layout.name.as_str()).or_insert_with(|| StructAccess {
+            name: layout.name.clone(),
+            hits: 0,
+            members: layout
+                .members
+                .iter()
+                .filter_map(|m| {
+                    Some(MemberAccess {
+                        name: m.name.clone(),
+                        offset: m.offset?,
+                        size: m.size?,
+                        hits: 0,
+                    })
+                })
+                .collect(),
+        });
+        access.hits += sample.count;
+        let offset = (address - global.address) % layout.size;
+        if let Some(member) =
+            access.members.iter_mut().find(|m| (m.offset..m.offset + m.size).contains(&offset))
+        {
+            member.hits += sample.count;
+        }
+    }
+
+    report.structs = structs.into_values().collect();
+    report.structs.sort_by(|a, b| b.hits.cmp(&a.hits).then_with(|| a.name.cmp(&b.name)));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MemberLayout;
+
+    #[test]
+    fn text_samples_parse() {
+        let set =
+            SampleSet::parse(b"address,count\n0x4028,3\n# comment\n\n402c 2\n4030\n").unwrap();
+        assert_eq!(
+            set.samples,
+            vec![
+                AccessSample { address: 0x4028, count: 3 },
+                AccessSample { address: 0x402c, count: 2 },
+                AccessSample { address: 0x4030, count: 1 },
+            ]
+        );
+        assert_eq!(set.total(), 6);
+        let err = SampleSet::parse(b"0x10\nnot an address\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+    }
+
+    fn record(kind: u32, body: &[u8]) -> Vec<u8> {
+        let mut record = kind.to_le_bytes().to_vec();
+        record.extend(0u16.to_le_bytes());
+        record.extend(((body.len() + 8) as u16).to_le_bytes());
+        record.extend(body);
+        record
+    }
+
+    fn perf_data(sample_type: u64, records: &[Vec<u8>]) -> Vec<u8> {
+        let attr_size = 64 + 16;
+        let mut attr = vec![0u8; attr_size];
+        attr[24..32].copy_from_slice(&sample_type.to_le_bytes());
+        let records = records.concat();
+
+        let mut data = PERF_MAGIC.to_vec();
+        for value in [
+            PERF_HEADER_SIZE as u64,
+            attr_size as u64,
+            PERF_HEADER_SIZE as u64,
+            attr_size as u64,
+            (PERF_HEADER_SIZE + attr_size) as u64,
+            records.len() as u64,
+        ] {
+            data.extend(value.to_le_bytes());
+        }
+        data.resize(PERF_HEADER_SIZE, 0);
+        data.extend(attr);
+        data.extend(records);
+        data
+    }
+
+    #[test]
+    fn perf_samples_and_mappings_parse() {
+        let sample_type = PERF_SAMPLE_IP | PERF_SAMPLE_TID | PERF_SAMPLE_ADDR;
+        let sample = |addr: u64| {
+            let mut body = 0x1000u64.to_le_bytes().to_vec();
+            body.extend([0u8; 8]);
+            body.extend(addr.to_le_bytes());
+            record(PERF_RECORD_SAMPLE, &body)
+        };
+        let mut mmap = vec![0u8; 8];
+        for value in [0x5555_0000_0000u64, 0x2000, 0] {
+            mmap.extend(value.to_le_bytes());
+        }
+        mmap.extend(b"/tmp/app\0\0\0\0\0\0\0\0");
+
+        let data = perf_data(
+            sample_type,
+            &[
+                record(PERF_RECORD_MMAP, &mmap),
+                sample(0x5555_0000_4028),
+                sample(0x5555_0000_4028),
+                sample(0),
+            ],
+        );
+        let set = SampleSet::parse(&data).unwrap();
+        assert_eq!(set.samples, vec![AccessSample { address: 0x5555_0000_4028, count: 2 }]);
+        assert_eq!(set.mappings[0].filename, "/tmp/app");
+        assert_eq!(set.load_bias(Path::new("build/app"), Some), Some(0x5555_0000_0000));
+        assert_eq!(set.load_bias(Path::new("build/other"), Some), None);
+
+        let err = SampleSet::parse(&perf_data(PERF_SAMPLE_IP, &[])).unwrap_err();
+        assert!(err.to_string().contains("perf record -d"), "{}", err);
+        assert!(SampleSet::parse(&data[..data.len() - 4]).is_err());
+    }
+
+    #[test]
+    fn accesses_are_attributed_to_members() {
+        let member = |name: &str, offset, size| {
+            MemberLayout::new(name.to_string(), "int".to_string(), Some(offset), Some(size))
+        };
+        let mut layout = StructLayout::new("Stats".to_string(), 8, Some(4));
+        layout.members = vec![member("hits", 0, 4), member("misses", 4, 4)];
+        let global = |name: &str, type_name: &str, address, size| GlobalVariable {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            address,
+            size,
+        };
+        let globals = vec![
+            global("stats", "Stats", 0x1000, 8),
+            global("table", "[Stats; 4]", 0x2000, 32),
+            global("count", "int", 0x3000, 4),
+        ];
+        let samples = [
+            AccessSample { address: 0x1_1004, count: 5 },
+            AccessSample { address: 0x1_2010, count: 2 },
+            AccessSample { address: 0x1_2014, count: 1 },
+            AccessSample { address: 0x1_3000, count: 7 },
+        ];
+
+        let report = attribute_accesses(&samples, 0x1_0000, &globals, &[layout]);
+        assert_eq!((report.samples, report.unattributed), (15, 7));
+        let stats = &report.structs[0];
+        assert_eq!(stats.hits, 8);
+        assert_eq!(stats.members[0].hits, 2);
+        assert_eq!(stats.members[1].hits, 6);
+        assert_eq!(stats.hot_members(), vec!["misses", "hits"]);
+    }
+}
@@ -0,0 +1,613 @@
+//! Message schemas from `.proto` and FlatBuffers `.fbs` files, cross-checked against the
+//! structs code generators produce from them.
+//!
+//! Only the declarations that shape generated structs are read: messages, tables, and structs,
+//! with their fields. Generated members are matched to schema fields by name with case and
+//! underscores ignored, so `user_id`, protobuf C++'s `user_id_`, and Go's `UserId` agree.
+
+use crate::error::{Error, Result};
+use crate::names::canonical_name;
+use crate::types::{MemberLayout, StructLayout};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    Proto,
+    FlatBuffers,
+}
+
+impl SchemaFormat {
+    /// The format a schema file's extension names: `.proto` or `.fbs`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "proto" => Some(SchemaFormat::Proto),
+            "fbs" => Some(SchemaFormat::FlatBuffers),
+            _ => None,
+        }
+    }
+}
+
+/// A protobuf message or FlatBuffers table or struct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMessage {
+    /// Package- and parent-qualified, dot-separated: `shop.Order.Item`.
+    pub name: String,
+    /// Enclosing messages and the message itself, without the package: `["Order", "Item"]`.
+    pub path: Vec<String>,
+    /// Field names in declaration order. A protobuf `oneof` counts as one field, named after
+    /// it, as generated code holds it in one member.
+    pub fields: Vec<String>,
+    pub format: SchemaFormat,
+}
+
+impl SchemaMessage {
+    pub fn load(path: &Path) -> Result<Vec<Self>> {
+        let format = SchemaFormat::from_path(path).ok_or_else(|| {
+            Error::Schema(format!("{}: expected a .proto or .fbs file", path.display()))
+        })?;
+        Self::parse(&std::fs::read_to_string(path)?, format)
+    }
+
+    pub fn parse(text: &str, format: SchemaFormat) -> Result<Vec<Self>> {
+        let mut parser = Parser { tokens: tokenize(text)?, pos: 0, format, package: Vec::new() };
+        let mut messages = Vec::new();
+        parser.parse_scope(&mut Vec::new(), &mut messages, true)?;
+        Ok(messages)
+    }
+
+    /// Names generated structs commonly have: nested messages joined with `_` (protobuf C++
+    /// and Go), the bare name (Rust), and for FlatBuffers the object API's `NameT`.
+    fn struct_names(&self) -> Vec<String> {
+        let mut names = vec![self.path.join("_")];
+        let short = self.path.last().cloned().unwrap_or_default();
+        if !names.contains(&short) {
+            names.push(short);
+        }
+        if self.format == SchemaFormat::FlatBuffers {
+            let object_api: Vec<String> = names.iter().map(|n| format!("{}T", n)).collect();
+            names.extend(object_api);
+        }
+        names
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Str,
+    Punct(char),
+}
+
+/// Identifiers (dotted names and numbers included), string literals, and punctuation, with
+/// comments dropped.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => while chars.next_if(|&c| c != '\n').is_some() {},
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = ' ';
+                loop {
+                    match chars.next() {
+                        Some('/') if last == '*' => break,
+                        Some(c) => {
+                            line += usize::from(c == '\n');
+                            last = c;
+                        }
+                        None => {
+                            return Err(Error::Schema(format!(
+                                "line {}: unterminated comment",
+                                line
+                            )));
+                        }
+                    }
+                }
+            }
+            '"' | '\'' => {
+                let mut escaped = false;
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c && !escaped => break,
+                        Some(ch) => escaped = ch == '\\' && !escaped,
+                        None => {
+                            return Err(Error::Schema(format!(
+                                "line {}: unterminated string",
+                                line
+                            )));
+                        }
+                    }
+                }
+                tokens.push((Token::Str, line));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '+' => {
+                let mut word = String::from(c);
+                while let Some(c) =
+                    chars.next_if(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '+'))
+                {
+                    word.push(c);
+                }
+                tokens.push((Token::Ident(word), line));
+            }
+            c => tokens.push((Token::Punct(c), line)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    format: SchemaFormat,
+    package: Vec<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(t, _)| t.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn error(&self, message: &str) -> Error {
+        match self.tokens.get(self.pos.min(self.tokens.len().saturating_sub(1))) {
+            Some((_, line)) => Error::Schema(format!("line {}: {}", line, message)),
+            None => Error::Schema(message.to_string()),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Ident(name)) => Ok(name),
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected a name"))
+            }
+        }
+    }
+
+    /// Skip to the end of a statement: past its `;`, or past a `{ ... }` block that ends it.
+    fn skip_statement(&mut self) -> Result<()> {
+        loop {
+            match self.next() {
+                Some(Token::Punct(';')) => return Ok(()),
+                Some(Token::Punct('{')) => return self.skip_block(),
+                Some(_) => {}
+                None => return Err(self.error("unexpected end of schema")),
+            }
+        }
+    }
+
+    /// Skip past the `}` closing a block whose `{` was just read.
+    fn skip_block(&mut self) -> Result<()> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(Token::Punct('{')) => depth += 1,
+                Some(Token::Punct('}')) => depth -= 1,
+                Some(_) => {}
+                None => return Err(self.error("unclosed '{'")),
+            }
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, punct: char) -> Result<()> {
+        if self.next() == Some(Token::Punct(punct)) {
+            Ok(())
+        } else {
+            self.pos -= 1;
+            Err(self.error(&format!("expected '{}'", punct)))
+        }
+    }
+
+    /// Declarations up to the end of the file (`top_level`) or the `}` closing the current
+    /// message, whose fields are returned.
+    fn parse_scope(
+        &mut self,
+        path: &mut Vec<String>,
+        messages: &mut Vec<SchemaMessage>,
+        top_level: bool,
+    ) -> Result<Vec<String>> {
+        let mut fields = Vec::new();
+        loop {
+            let keyword = match self.next() {
+                None if top_level => return Ok(fields),
+                None => return Err(self.error("unclosed message")),
+                Some(Token::Punct('}')) if !top_level => return Ok(fields),
+                Some(Token::Punct(';')) => continue,
+                Some(Token::Ident(word)) => word,
+                Some(_) => {
+                    self.pos -= 1;
+                    return Err(self.error("unexpected token"));
+                }
+            };
+            let is_message = match self.format {
+                SchemaFormat::Proto => keyword == "message",
+                SchemaFormat::FlatBuffers => {
+                    top_level && (keyword == "table" || keyword == "struct")
+                }
+            };
+            if is_message {
+                let name = self.ident()?;
+                // FlatBuffers attributes such as `(force_align: 8)` come before the body.
+                while self.peek().is_some_and(|t| *t != Token::Punct('{')) {
+                    self.next();
+                }
+                self.expect('{')?;
+                path.push(name);
+                let index = messages.len();
+                messages.push(SchemaMessage {
+                    name: self
+                        .package
+                        .iter()
+                        .chain(path.iter())
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join("."),
+                    path: path.clone(),
+                    fields: Vec::new(),
+                    format: self.format,
+                });
+                messages[index].fields = self.parse_scope(path, messages, false)?;
+                path.pop();
+                continue;
+            }
+            if top_level && (keyword == "package" || keyword == "namespace") {
+                self.package = self.ident()?.split('.').map(str::to_string).collect();
+                self.expect(';')?;
+                continue;
+            }
+            if top_level {
+                self.skip_statement()?;
+                continue;
+            }
+            match self.format {
+                SchemaFormat::Proto => self.proto_member(&keyword, &mut fields)?,
+                SchemaFormat::FlatBuffers => self.flatbuffers_field(keyword, &mut fields)?,
+            }
+        }
+    }
+
+    /// A statement inside a protobuf message, starting with `keyword`.
+    fn proto_member(&mut self, keyword: &str, fields: &mut Vec<String>) -> Result<()> {
+        match keyword {
+            "oneof" => {
+                fields.push(self.ident()?);
+                self.expect('{')?;
+                self.skip_block()
+            }
+            "enum" | "extend" | "service" | "option" | "reserved" | "extensions" => {
+                self.skip_statement()
+            }
+            _ => {
+                // `[label] type name = number [options];`, or `map<K, V> name = number;`.
+                let mut words = Vec::new();
+                loop {
+                    match self.next() {
+                        Some(Token::Punct('=')) => break,
+                        Some(Token::Ident(word)) => words.push(word),
+                        Some(Token::Punct(_)) => {}
+                        _ => return Err(self.error("expected a field")),
+                    }
+                }
+                let name = words.pop().ok_or_else(|| self.error("expected a field name"))?;
+                // A proto2 group declares a nested message; its field is the lowercased name.
+                let is_group = words.last().map_or(keyword, String::as_str) == "group";
+                fields.push(if is_group { name.to_lowercase() } else { name });
+                self.skip_statement()
+            }
+        }
+    }
+
+    /// `name: type [= default] [(attributes)];` inside a FlatBuffers table or struct. Deprecated
+    /// fields are left out, as generated code drops them.
+    fn flatbuffers_field(&mut self, name: String, fields: &mut Vec<String>) -> Result<()> {
+        self.expect(':')?;
+        let mut deprecated = false;
+        loop {
+            match self.next() {
+                Some(Token::Punct(';')) => break,
+                Some(Token::Ident(word)) if word == "deprecated" => deprecated = true,
+                Some(_) => {}
+                None => return Err(self.error("expected ';'")),
+            }
+        }
+        if !deprecated {
+            fields.push(name);
+        }
+        Ok(())
+    }
+}
+
+/// Which side of the cross-check lacks a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SchemaMismatchKind {
+    /// Declared in the schema, but the struct has no such member.
+    MissingInBinary,
+    /// A member of the struct the schema does not declare.
+    MissingInSchema,
+}
+
+impl SchemaMismatchKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            SchemaMismatchKind::MissingInBinary => "missing in binary",
+            SchemaMismatchKind::MissingInSchema => "missing in schema",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaMismatch {
+    pub message: String,
+    pub struct_name: String,
+    /// The schema's spelling for a field missing in the binary, the member's otherwise.
+    pub field: String,
+    pub kind: SchemaMismatchKind,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchemaReport {
+    /// Messages compared against a struct in the debug info.
+    pub checked: usize,
+    /// Messages with no generated struct in the debug info.
+    pub missing: Vec<String>,
+    pub mismatches: Vec<SchemaMismatch>,
+}
+
+/// Compare each message's fields with the members of its generated struct. `struct_names` maps
+/// a message (by qualified or bare name) to its struct's name; otherwise the struct is found by
+/// the names generators use, unqualified. Of several candidates, the closest one is reported.
+pub fn check_schema(
+    messages: &[SchemaMessage],
+    layouts: &[StructLayout],
+    struct_names: &HashMap<String, String>,
+) -> SchemaReport {
+    let mut by_name: HashMap<&str, Vec<&StructLayout>> = HashMap::new();
+    let mut exact: HashMap<&str, &StructLayout> = HashMap::new();
+    for layout in layouts {
+        by_name.entry(canonical_name(&layout.name)).or_default().push(layout);
+        exact.entry(layout.name.as_str()).or_insert(layout);
+    }
+
+    let mut report = SchemaReport::default();
+    for message in messages {
+        let short = message.path.last().map_or("", String::as_str);
+        let candidates: Vec<&StructLayout> =
+            match struct_names.get(&message.name).or_else(|| struct_names.get(short)) {
+                Some(name) => match exact.get(name.as_str()) {
+                    Some(layout) => vec![*layout],
+                    None => by_name.get(canonical_name(name)).cloned().unwrap_or_default(),
+                },
+                None => message
+                    .struct_names()
+                    .iter()
+                    .flat_map(|name| by_name.get(name.as_str()).into_iter().flatten().copied())
+                    .collect(),
+            };
+        let Some(closest) = candidates
+            .into_iter()
+            .map(|layout| compare(message, layout, &exact))
+            .min_by_key(Vec::len)
+        else {
+            report.missing.push(message.name.clone());
+            continue;
+        };
+        report.checked += 1;
+        report.mismatches.extend(closest);
+    }
+    report
+}
+
+/// `user_id`, `user_id_`, `userId`, and `UserId` all become `userid`.
+fn normalize(name: &str) -> String {
+    name.trim_end_matches('_').chars().filter(|&c| c != '_').flat_map(char::to_lowercase).collect()
+}
+
+/// Members generated code adds for its own bookkeeping: protobuf C++'s `_has_bits_` and
+/// `_cached_size_`, FlatBuffers Rust's `_tab`, and Go protobuf's message state.
+fn is_bookkeeping(member: &MemberLayout) -> bool {
+    member.role.is_some()
+        || member.base_layout.is_some()
+        || member.name.starts_with('_')
+        || matches!(member.name.as_str(), "state" | "sizeCache" | "unknownFields")
+}
+
+/// The struct's data members. Newer protobuf C++ keeps every field in an `_impl_` member, whose
+/// own struct is read instead.
+fn data_members<'a>(
+    layout: &'a StructLayout,
+    exact: &HashMap<&str, &'a StructLayout>,
+    depth: usize,
+) -> Vec<&'a MemberLayout> {
+    let mut members = Vec::new();
+    for member in &layout.members {
+        if member.name == "_impl_"
+            && depth < 4
+            && let Some(inner) = exact.get(member.type_name.as_str())
+        {
+            members.extend(data_members(inner, exact, depth + 1));
+        } else if !is_bookkeeping(member) {
+            members.push(member);
+        }
+    }
+    members
+}
+
+fn compare(
+    message: &SchemaMessage,
+    layout: &StructLayout,
+    exact: &HashMap<&str, &StructLayout>,
+) -> Vec<SchemaMismatch> {
+    let members = data_members(layout, exact, 0);
+    let member_names: Vec<String> = members.iter().map(|m| normalize(&m.name)).collect();
+    let field_names: Vec<String> = message.fields.iter().map(|f| normalize(f)).collect();
+    let mismatch = |field: &str, kind| SchemaMismatch {
+        message: message.name.clone(),
+        struct_name: layout.name.clone(),
+        field: field.to_string(),
+        kind,
+    };
+
+    let missing_in_binary = message
+        .fields
+        .iter()
+        .zip(&field_names)
+        .filter(|(_, normalized)| !member_names.contains(normalized))
+        .map(|(field, _)| mismatch(field, SchemaMismatchKind::MissingInBinary));
+    let missing_in_schema = members
+        .iter()
+        .zip(&member_names)
+        .filter(|(_, normalized)| !field_names.contains(normalized))
+        .map(|(member, _)| mismatch(&member.name, SchemaMismatchKind::MissingInSchema));
+    missing_in_binary.chain(missing_in_schema).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROTO: &str = r#"
+syntax = "proto3";
+package shop.v1;
+import "google/protobuf/timestamp.proto";
+option go_package = "example.com/shop";
+
+/* An order, with its line items. */
+message Order {
+  reserved 7;
+  int64 order_id = 1;
+  repeated Item items = 2 [packed = false];
+  map<string, string> labels = 3;
+  oneof payment {
+    string card = 4;
+    string voucher = 5;
+  }
+  enum Status { PENDING = 0; SHIPPED = 1; }
+  message Item {
+    string sku = 1; // stock keeping unit
+    uint32 quantity = 2;
+  }
+  Status status = 6;
+}
+service Shop { rpc Place(Order) returns (Order); }
+"#;
+
+    const FBS: &str = r#"
+include "common.fbs";
+namespace game.sample;
+attribute "priority";
+enum Color : byte { Red = 0, Green, Blue = 2 }
+struct Vec3 (force_align: 16) { x: float; y: float; z: float; }
+table Monster {
+  pos: Vec3;
+  hp: short = 100;
+  name: string (required);
+  friendly: bool = false (deprecated, priority: 1);
+  color: Color = Blue;
+}
+root_type Monster;
+"#;
+
+    fn member(name: &str) -> MemberLayout {
+        MemberLayout::new(name.to_string(), "int".to_string(), Some(0), Some(4))
+    }
+
+    fn layout(name: &str, members: &[&str]) -> StructLayout {
+        let mut layout = StructLayout::new(name.to_string(), 64, Some(8));
+        layout.members = members.iter().map(|m| member(m)).collect();
+        layout
+    }
+
+    #[test]
+    fn parses_proto_messages() {
+        let messages = SchemaMessage::parse(PROTO, SchemaFormat::Proto).unwrap();
+        let summary: Vec<(&str, Vec<&str>)> = messages
+            .iter()
+            .map(|m| (m.name.as_str(), m.fields.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("shop.v1.Order", vec!["order_id", "items", "labels", "payment", "status"]),
+                ("shop.v1.Order.Item", vec!["sku", "quantity"]),
+            ]
+        );
+        assert_eq!(messages[1].struct_names(), vec!["Order_Item", "Item"]);
+    }
+
+    #[test]
+    fn parses_flatbuffers_tables_and_structs() {
+        let messages = SchemaMessage::parse(FBS, SchemaFormat::FlatBuffers).unwrap();
+        let summary: Vec<(&str, Vec<&str>)> = messages
+            .iter()
+            .map(|m| (m.name.as_str(), m.fields.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("game.sample.Vec3", vec!["x", "y", "z"]),
+                ("game.sample.Monster", vec!["pos", "hp", "name", "color"]),
+            ]
+        );
+        assert_eq!(messages[1].struct_names(), vec!["Monster", "MonsterT"]);
+    }
+
+    #[test]
+    fn reports_unterminated_input_with_its_line() {
+        let err =
+            SchemaMessage::parse("message A {\n  int32 a = 1;\n", SchemaFormat::Proto).unwrap_err();
+        assert!(err.to_string().contains("unclosed message"), "{}", err);
+        let err = SchemaMessage::parse("/* open", SchemaFormat::Proto).unwrap_err();
+        assert!(err.to_string().contains("line 1: unterminated comment"), "{}", err);
+    }
+
+    #[test]
+    fn checks_fields_against_generated_members() {
+        let messages = SchemaMessage::parse(PROTO, SchemaFormat::Proto).unwrap();
+        let mut order = layout("shop::v1::Order", &["_has_bits_", "_impl_"]);
+        order.members[1].type_name = "shop::v1::Order::Impl_".to_string();
+        let layouts = vec![
+            order,
+            // protobuf C++ 22+: the fields live in `_impl_`; `status_` is absent, `legacy_` extra.
+            layout(
+                "shop::v1::Order::Impl_",
+                &["items_", "labels_", "order_id_", "payment_", "legacy_", "_cached_size_"],
+            ),
+            // Go: CamelCase fields after the protoimpl bookkeeping.
+            layout("shop.Order_Item", &["state", "sizeCache", "unknownFields", "Sku", "Quantity"]),
+        ];
+
+        let report = check_schema(&messages, &layouts, &HashMap::new());
+        assert_eq!((report.checked, report.missing.len()), (2, 0));
+        let found: Vec<(&str, &str, SchemaMismatchKind)> = report
+            .mismatches
+            .iter()
+            .map(|m| (m.struct_name.as_str(), m.field.as_str(), m.kind))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("shop::v1::Order", "status", SchemaMismatchKind::MissingInBinary),
+                ("shop::v1::Order", "legacy_", SchemaMismatchKind::MissingInSchema),
+            ]
+        );
+
+        // A configured name overrides the generators' conventions.
+        let names = HashMap::from([("shop.v1.Order.Item".to_string(), "LineItem".to_string())]);
+        let report = check_schema(&messages, &layouts, &names);
+        assert_eq!(report.missing, vec!["shop.v1.Order.Item"]);
+    }
+}
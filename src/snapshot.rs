@@ -0,0 +1,167 @@
+//! Layout snapshots: a binary's struct layouts saved as JSON, so `diff --baseline` can compare
+//! against an earlier build without keeping its debug binary around.
+//!
+//! A snapshot keeps what padding analysis and diffing need (sizes, alignment, members, and
+//! declaration sites); metrics are recomputed on load. Rust enum variants are not recorded.
+
+use crate::error::{Error, Result};
+use crate::types::{MemberLayout, SourceLocation, StructLayout};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub snapshot_version: u32,
+    /// layout-audit version that wrote the snapshot.
+    pub tool_version: String,
+    pub structs: Vec<SnapshotStruct>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotStruct {
+    pub name: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_location: Option<SourceLocation>,
+    #[serde(default)]
+    pub members: Vec<SnapshotMember>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotMember {
+    pub name: String,
+    pub type_name: String,
+    pub offset: Option<u64>,
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<u64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_atomic: bool,
+}
+
+impl Snapshot {
+    pub fn from_layouts(layouts: &[StructLayout]) -> Self {
+        let structs = layouts
+            .iter()
+            .map(|l| SnapshotStruct {
+                name: l.name.clone(),
+                size: l.size,
+                alignment: l.alignment,
+                source_location: l.source_location.clone(),
+                members: l
+                    .members
+                    .iter()
+                    .map(|m| SnapshotMember {
+                        name: m.name.clone(),
+                        type_name: m.type_name.clone(),
+                        offset: m.offset,
+                        size: m.size,
+                        bit_offset: m.bit_offset,
+                        bit_size: m.bit_size,
+                        alignment: m.alignment,
+                        is_atomic: m.is_atomic,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self {
+            snapshot_version: SNAPSHOT_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            structs,
+        }
+    }
+
+    /// The recorded layouts, with default metrics; run `analyze_layout` before using them.
+    pub fn into_layouts(self) -> Vec<StructLayout> {
+        self.structs
+            .into_iter()
+            .map(|s| {
+                let mut layout = StructLayout::new(s.name, s.size, s.alignment);
+                layout.source_location = s.source_location;
+                layout.members = s
+                    .members
+                    .into_iter()
+                    .map(|m| {
+                        let mut member = MemberLayout::new(m.name, m.type_name, m.offset, m.size);
+                        member.bit_offset = m.bit_offset;
+                        member.bit_size = m.bit_size;
+                        member.alignment = m.alignment;
+                        member.is_atomic = m.is_atomic;
+                        member
+                    })
+                    .collect();
+                layout
+            })
+            .collect()
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|e| Error::Snapshot(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let snapshot: Snapshot =
+            serde_json::from_str(text).map_err(|e| Error::Snapshot(e.to_string()))?;
+        if snapshot.snapshot_version != SNAPSHOT_VERSION {
+            return Err(Error::Snapshot(format!(
+                "unsupported snapshot version {} (expected {})",
+                snapshot.snapshot_version, SNAPSHOT_VERSION
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    pub fn render(&self, pretty: bool) -> String {
+        // Serializing strings, integers, and options cannot fail.
+        let json =
+            if pretty { serde_json::to_string_pretty(self) } else { serde_json::to_string(self) };
+        json.unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout() -> StructLayout {
+        let mut l = StructLayout::new("Flags".to_string(), 8, Some(4));
+        l.source_location = Some(SourceLocation { file: "flags.h".to_string(), line: 3 });
+        let mut bits = MemberLayout::new("mode".to_string(), "u32".to_string(), Some(0), Some(4));
+        bits.bit_offset = Some(0);
+        bits.bit_size = Some(3);
+        let mut counter =
+            MemberLayout::new("count".to_string(), "_Atomic int".to_string(), Some(4), Some(4));
+        counter.is_atomic = true;
+        l.members = vec![bits, counter];
+        l
+    }
+
+    #[test]
+    fn round_trips_layouts() {
+        let text = Snapshot::from_layouts(&[layout()]).render(false);
+        let layouts = Snapshot::parse(&text).unwrap().into_layouts();
+        assert_eq!(layouts.len(), 1);
+        let l = &layouts[0];
+        assert_eq!((l.name.as_str(), l.size, l.alignment), ("Flags", 8, Some(4)));
+        assert_eq!(l.source_location.as_ref().unwrap().line, 3);
+        assert_eq!(l.members[0].bit_size, Some(3));
+        assert!(l.members[1].is_atomic);
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        let text = r#"{"snapshot_version": 2, "tool_version": "9.0.0", "structs": []}"#;
+        assert!(Snapshot::parse(text).is_err());
+        // An inspect report is not a snapshot.
+        assert!(Snapshot::parse(r#"{"version": "0.5.0", "structs": []}"#).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! In-source suppressions: `layout-audit: allow(max_size, ...)` comments next to a struct.
+//!
+//! A marker applies to the struct declared on its line, or to the next declaration when it sits
+//! in the block of comments and attributes directly above it. Source files are found through
+//! the DWARF declaration location, resolved against the working directory when relative.
+
+use crate::types::SourceLocation;
+use serde::Serialize;
+use std::collections::HashMap;
+
+const MARKER: &str = "layout-audit:";
+
+/// Rule name that suppresses every limit.
+pub const ALLOW_ALL: &str = "all";
+
+/// An `allow(...)` marker found for one struct.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceSuppression {
+    pub struct_name: String,
+    /// Where the struct is declared (the marker itself may be a few lines above).
+    pub location: SourceLocation,
+    pub rules: Vec<String>,
+}
+
+impl SourceSuppression {
+    pub fn allows(&self, rule: &str) -> bool {
+        self.rules.iter().any(|r| r == rule || r == ALLOW_ALL)
+    }
+}
+
+/// Reads source files on demand, caching their lines.
+#[derive(Default)]
+pub struct SuppressionScanner {
+    files: HashMap<String, Option<Vec<String>>>,
+}
+
+impl SuppressionScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The suppression for the struct declared at `location`, if any. Unreadable files have
+    /// none.
+    pub fn find(
+        &mut self,
+        struct_name: &str,
+        location: &SourceLocation,
+    ) -> Option<SourceSuppression> {
+        let lines = self
+            .files
+            .entry(location.file.clone())
+            .or_insert_with(|| {
+                std::fs::read_to_string(&location.file)
+                    .ok()
+                    .map(|text| text.lines().map(str::to_string).collect())
+            })
+            .as_ref()?;
+
+        let rules = rules_above(lines, location.line)?;
+        Some(SourceSuppression {
+            struct_name: struct_name.to_string(),
+            location: location.clone(),
+            rules,
+        })
+    }
+}
+
+/// Rules allowed for the declaration on 1-based line `decl_line`.
+fn rules_above(lines: &[String], decl_line: u64) -> Option<Vec<String>> {
+    let decl = usize::try_from(decl_line).ok()?.checked_sub(1)?;
+    let mut rules = Vec::new();
+
+    if let Some(line) = lines.get(decl) {
+        rules.extend(parse_allow(line).unwrap_or_default());
+    }
+    for line in lines[..decl.min(lines.len())].iter().rev() {
+        let trimmed = line.trim_start();
+        let is_preamble = ["//", "/*", "*", "#[", "[["].iter().any(|p| trimmed.starts_with(p));
+        if !is_preamble {
+            break;
+        }
+        rules.extend(parse_allow(line).unwrap_or_default());
+    }
+
+    (!rules.is_empty()).then_some(rules)
+}
+
+/// Parse the rule list of a `layout-audit: allow(a, b)` marker anywhere in `line`.
+pub fn parse_allow(line: &str) -> Option<Vec<String>> {
+    let rest = line[line.find(MARKER)? + MARKER.len()..].trim_start();
+    let args = rest.strip_prefix("allow")?.trim_start().strip_prefix('(')?;
+    let args = &args[..args.find(')')?];
+    Some(args.split(',').map(str::trim).filter(|r| !r.is_empty()).map(str::to_string).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn parse_allow_extracts_rules() {
+        assert_eq!(
+            parse_allow("// layout-audit: allow(max_size, max_padding)"),
+            Some(vec!["max_size".to_string(), "max_padding".to_string()])
+        );
+        assert_eq!(
+            parse_allow("struct Foo { // layout-audit:allow(all)"),
+            Some(vec!["all".into()])
+        );
+        assert_eq!(parse_allow("// layout-audit: deny(max_size)"), None);
+        assert_eq!(parse_allow("// unrelated"), None);
+    }
+
+    #[test]
+    fn markers_in_preamble_apply_to_declaration() {
+        let src = lines(
+            "// layout-audit: allow(max_size)\n\
+             struct A { int x; };\n\
+             \n\
+             /// Docs\n\
+             // layout-audit: allow(max_padding)\n\
+             #[repr(C)]\n\
+             struct B { x: u8 }\n",
+        );
+        assert_eq!(rules_above(&src, 2), Some(vec!["max_size".to_string()]));
+        assert_eq!(rules_above(&src, 7), Some(vec!["max_padding".to_string()]));
+        // Code between the marker and the declaration breaks the association.
+        assert_eq!(rules_above(&src, 3), None);
+        assert_eq!(rules_above(&src, 0), None);
+        assert_eq!(rules_above(&src, 99), None);
+    }
+}
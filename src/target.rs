@@ -0,0 +1,198 @@
+//! The target a binary was built for, read from its object file headers, and checks that its
+//! DWARF agrees with them.
+//!
+//! Pointer sizes come from each unit's header and byte order from the DWARF itself, so a
+//! header that disagrees (a 32-bit ELF class holding 16-bit pointers, a byte-swapped firmware
+//! image) would otherwise skew sizes and offsets without any error.
+
+use gimli::RunTimeEndian;
+use object::{Architecture, BinaryFormat, Object, ObjectSection};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Endian::Little => "little",
+            Endian::Big => "big",
+        }
+    }
+
+    fn swapped(self) -> Self {
+        match self {
+            Endian::Little => Endian::Big,
+            Endian::Big => Endian::Little,
+        }
+    }
+}
+
+impl From<Endian> for RunTimeEndian {
+    fn from(endian: Endian) -> Self {
+        match endian {
+            Endian::Little => RunTimeEndian::Little,
+            Endian::Big => RunTimeEndian::Big,
+        }
+    }
+}
+
+/// What the object file headers say about the target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Target {
+    /// Best-effort target triple (`armeb-unknown-linux-gnu`, `mips64el-unknown-none`). The
+    /// OS is `linux-gnu` or `linux-musl` for ELF files with a Linux interpreter or ABI note,
+    /// and `none` for other ELF files such as firmware.
+    pub triple: String,
+    /// Pointer size the architecture implies, in bytes.
+    pub address_size: u8,
+    /// Byte order of the file's data, and so of its DWARF. ARM BE8 images are big-endian here
+    /// even though their code is little-endian.
+    pub endian: Endian,
+}
+
+impl Target {
+    pub fn of(object: &object::File<'_>) -> Self {
+        let endian = if object.is_little_endian() { Endian::Little } else { Endian::Big };
+        let architecture = object.architecture();
+        let address_size = match architecture {
+            // Data pointers are 16 bits; object reports the 8-bit register width.
+            Architecture::Avr => 2,
+            arch => {
+                arch.address_size().map_or(if object.is_64() { 8 } else { 4 }, |size| size.bytes())
+            }
+        };
+        let arch = arch_name(architecture, endian);
+        let triple = match object.format() {
+            BinaryFormat::MachO => format!("{}-apple-darwin", arch),
+            BinaryFormat::Pe | BinaryFormat::Coff => format!("{}-pc-windows", arch),
+            BinaryFormat::Elf => format!("{}-unknown-{}", arch, elf_os(object)),
+            _ => format!("{}-unknown-unknown", arch),
+        };
+        Self { triple, address_size, endian }
+    }
+}
+
+fn arch_name(architecture: Architecture, endian: Endian) -> String {
+    let big = endian == Endian::Big;
+    let name = match architecture {
+        Architecture::X86_64 | Architecture::X86_64_X32 => "x86_64",
+        Architecture::I386 => "i686",
+        Architecture::Aarch64 | Architecture::Aarch64_Ilp32 if big => "aarch64_be",
+        Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => "aarch64",
+        Architecture::Arm if big => "armeb",
+        Architecture::Arm => "arm",
+        Architecture::Mips if big => "mips",
+        Architecture::Mips => "mipsel",
+        Architecture::Mips64 | Architecture::Mips64_N32 if big => "mips64",
+        Architecture::Mips64 | Architecture::Mips64_N32 => "mips64el",
+        Architecture::PowerPc => "powerpc",
+        Architecture::PowerPc64 if big => "powerpc64",
+        Architecture::PowerPc64 => "powerpc64le",
+        Architecture::Riscv32 => "riscv32",
+        Architecture::Riscv64 => "riscv64",
+        Architecture::Sparc | Architecture::Sparc32Plus => "sparc",
+        Architecture::Sparc64 => "sparc64",
+        Architecture::LoongArch64 => "loongarch64",
+        other => return format!("{:?}", other).to_lowercase(),
+    };
+    name.to_string()
+}
+
+fn elf_os(object: &object::File<'_>) -> &'static str {
+    let interp = object.section_by_name(".interp").and_then(|s| s.data().ok());
+    if interp.is_some_and(|i| i.windows(4).any(|w| w == b"musl")) {
+        "linux-musl"
+    } else if interp.is_some() || object.section_by_name(".note.ABI-tag").is_some() {
+        "linux-gnu"
+    } else {
+        "none"
+    }
+}
+
+/// A way the DWARF disagrees with the object file headers. The DWARF wins: units are read with
+/// their own address size, and `.debug_info` in the byte order it parses in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TargetMismatch {
+    /// Unit headers give another address size than the architecture.
+    AddressSize { target: u8, dwarf: u8, units: usize },
+    /// `.debug_info` only parses in the opposite byte order to the file header's.
+    Endian { target: Endian, dwarf: Endian },
+}
+
+impl fmt::Display for TargetMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetMismatch::AddressSize { target, dwarf, units } => write!(
+                f,
+                "{} compilation unit(s) use {}-byte addresses, but the object file header \
+                 implies {}; sizing pointers in them from the unit header",
+                units, dwarf, target
+            ),
+            TargetMismatch::Endian { target, dwarf } => write!(
+                f,
+                "the object file header says {}-endian, but .debug_info is {}-endian; reading \
+                 the debug info as {}-endian",
+                target.as_str(),
+                dwarf.as_str(),
+                dwarf.as_str()
+            ),
+        }
+    }
+}
+
+/// The byte order `.debug_info` parses in: `declared` unless the first unit header only has a
+/// valid DWARF version (2 to 5) when read the other way round.
+pub fn debug_info_endian(debug_info: &[u8], declared: Endian) -> Endian {
+    let version_at = match debug_info.get(..4) {
+        // DWARF64: an escape, then an 8-byte length.
+        Some([0xff, 0xff, 0xff, 0xff]) => 12,
+        Some(_) => 4,
+        None => return declared,
+    };
+    let Some(&[a, b]) = debug_info.get(version_at..version_at + 2) else {
+        return declared;
+    };
+    let valid = |version: u16| (2..=5).contains(&version);
+    let version = |endian| match endian {
+        Endian::Little => u16::from_le_bytes([a, b]),
+        Endian::Big => u16::from_be_bytes([a, b]),
+    };
+    if !valid(version(declared)) && valid(version(declared.swapped())) {
+        declared.swapped()
+    } else {
+        declared
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_info_endian_follows_the_unit_header() {
+        // unit_length, then version 4.
+        let little = [0x20, 0, 0, 0, 4, 0, 0, 0];
+        let big = [0, 0, 0, 0x20, 0, 4, 0, 0];
+        assert_eq!(debug_info_endian(&little, Endian::Little), Endian::Little);
+        assert_eq!(debug_info_endian(&big, Endian::Little), Endian::Big);
+        assert_eq!(debug_info_endian(&big, Endian::Big), Endian::Big);
+        let dwarf64 = [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0x20, 0, 5];
+        assert_eq!(debug_info_endian(&dwarf64, Endian::Little), Endian::Big);
+        assert_eq!(debug_info_endian(&[], Endian::Big), Endian::Big);
+    }
+
+    #[test]
+    fn triples_name_byte_order() {
+        assert_eq!(arch_name(Architecture::Arm, Endian::Big), "armeb");
+        assert_eq!(arch_name(Architecture::Mips, Endian::Little), "mipsel");
+        assert_eq!(arch_name(Architecture::Mips64_N32, Endian::Big), "mips64");
+        assert_eq!(arch_name(Architecture::Xtensa, Endian::Little), "xtensa");
+    }
+}
@@ -0,0 +1,27 @@
+//! Locating the compiled test fixtures. The library's unit tests, the CLI's, and the
+//! integration tests all include this one file.
+
+use std::path::{Path, PathBuf};
+
+/// Path to the fixture binary `name` in `tests/fixtures/bin`: its dSYM on macOS, its `.exe`
+/// on Windows, or the binary itself (Linux, with embedded debug info). None if the fixture
+/// was not compiled.
+pub(crate) fn find_fixture_path(name: &str) -> Option<PathBuf> {
+    let base = Path::new("tests/fixtures/bin");
+    let dsym_path = base.join(format!("{}.dSYM/Contents/Resources/DWARF/{}", name, name));
+    if dsym_path.exists() {
+        return Some(dsym_path);
+    }
+
+    let exe_path = base.join(format!("{}.exe", name));
+    if exe_path.exists() {
+        return Some(exe_path);
+    }
+
+    let direct_path = base.join(name);
+    if direct_path.exists() {
+        return Some(direct_path);
+    }
+
+    None
+}
@@ -0,0 +1,38 @@
+//! Helpers shared by the unit tests.
+
+mod fixtures;
+
+use gimli::write::{EndianVec, Sections};
+use object::write::Object as WriteObject;
+use object::{Architecture, BinaryFormat, Endianness, SectionKind};
+use tempfile::NamedTempFile;
+
+pub(crate) use fixtures::find_fixture_path;
+
+/// Write `object` to a temporary file, deleted when the handle drops.
+pub(crate) fn write_object(object: WriteObject<'_>) -> NamedTempFile {
+    let file = NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), object.write().unwrap()).unwrap();
+    file
+}
+
+/// An x86-64 ELF file holding the DWARF sections `write` produces, e.g.
+/// `write_elf(|sections| dwarf.write(sections))` for a `gimli::write::DwarfUnit`.
+pub(crate) fn write_elf(
+    write: impl FnOnce(&mut Sections<EndianVec<gimli::LittleEndian>>) -> gimli::write::Result<()>,
+) -> NamedTempFile {
+    let mut sections = Sections::new(EndianVec::new(gimli::LittleEndian));
+    write(&mut sections).unwrap();
+    let mut out = WriteObject::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+    sections
+        .for_each(|id, data| {
+            if !data.slice().is_empty() {
+                let section =
+                    out.add_section(Vec::new(), id.name().as_bytes().to_vec(), SectionKind::Debug);
+                out.set_section_data(section, data.slice().to_vec(), 1);
+            }
+            Ok::<_, gimli::write::Error>(())
+        })
+        .unwrap();
+    write_object(out)
+}
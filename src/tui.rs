@@ -0,0 +1,595 @@
+//! `tui`: an interactive explorer over a binary's struct layouts. A scrollable struct list,
+//! narrowed by fuzzy search as the query is typed, sits beside a detail pane with the selected
+//! struct's members, padding holes, and byte map, or the reordering `suggest` would propose.
+//! A false-sharing overlay marks atomic members and the cache lines they share.
+
+use crate::analysis::{AtomicPatterns, analyze_false_sharing_with_patterns, optimize_layout};
+use crate::error::Result;
+use crate::types::{MemberLayout, StructLayout};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+const MAP_SYMBOLS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const MAP_PADDING: char = '.';
+const MAP_SHARED: char = '+';
+const MAP_COLORS: [Color; 5] =
+    [Color::Cyan, Color::Green, Color::Blue, Color::Magenta, Color::Yellow];
+/// Widest byte map row; rows never cross a cache line.
+const MAP_MAX_ROW: u64 = 64;
+/// Larger structs are listed without a byte map.
+const MAP_MAX_BYTES: u64 = 64 * 1024;
+/// Structs PageUp and PageDown move the selection by.
+const LIST_PAGE: isize = 20;
+
+/// State of the explorer: the structs, the search narrowing them, the selection, and which
+/// views are toggled on. Keys go through [`Explorer::handle_key`], so it runs without a
+/// terminal as well.
+pub struct Explorer {
+    layouts: Vec<StructLayout>,
+    cache_line_size: u32,
+    max_align: u64,
+    atomic_patterns: AtomicPatterns,
+    query: String,
+    searching: bool,
+    /// Indices into `layouts` matching `query`, best match first.
+    matches: Vec<usize>,
+    list: ListState,
+    detail_scroll: u16,
+    suggest: bool,
+    false_sharing: bool,
+}
+
+impl Explorer {
+    /// Explore `layouts`, listed in the given order until a search ranks them. They should
+    /// already be analyzed with `cache_line_size`.
+    pub fn new(layouts: Vec<StructLayout>, cache_line_size: u32) -> Self {
+        let mut explorer = Self {
+            layouts,
+            cache_line_size,
+            max_align: 8,
+            atomic_patterns: AtomicPatterns::default(),
+            query: String::new(),
+            searching: false,
+            matches: Vec::new(),
+            list: ListState::default(),
+            detail_scroll: 0,
+            suggest: false,
+            false_sharing: false,
+        };
+        explorer.update_matches();
+        explorer
+    }
+
+    /// Maximum alignment the suggest view assumes, as `suggest --max-align`.
+    pub fn with_max_align(mut self, max_align: u64) -> Self {
+        self.max_align = max_align;
+        self
+    }
+
+    /// Types the false-sharing overlay treats as atomics, besides DWARF atomic types.
+    pub fn with_atomic_patterns(mut self, atomic_patterns: AtomicPatterns) -> Self {
+        self.atomic_patterns = atomic_patterns;
+        self
+    }
+
+    /// Take over the terminal until the user quits, then restore it.
+    pub fn run(mut self) -> Result<()> {
+        let mut terminal = ratatui::try_init()?;
+        let result = self.event_loop(&mut terminal);
+        ratatui::restore();
+        result
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.render(frame))?;
+            if let Event::Key(key) = event::read()?
+                && !self.handle_key(key)
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    /// The selected struct, if any matches the search.
+    pub fn selected(&self) -> Option<&StructLayout> {
+        let index = self.matches.get(self.list.selected()?)?;
+        Some(&self.layouts[*index])
+    }
+
+    /// Names of the structs matching the search, in list order.
+    pub fn matching_names(&self) -> impl Iterator<Item = &str> {
+        self.matches.iter().map(|&i| self.layouts[i].name.as_str())
+    }
+
+    /// Apply one key press. Returns false once the user asks to quit.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.kind != KeyEventKind::Press {
+            return true;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return key.code != KeyCode::Char('c');
+        }
+        if self.searching {
+            match key.code {
+                KeyCode::Char(c) => {
+                    self.query.push(c);
+                    self.update_matches();
+                }
+                KeyCode::Backspace => {
+                    self.query.pop();
+                    self.update_matches();
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Esc => {
+                    self.searching = false;
+                    self.query.clear();
+                    self.update_matches();
+                }
+                KeyCode::Down => self.move_selection(1),
+                KeyCode::Up => self.move_selection(-1),
+                _ => {}
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Esc if self.query.is_empty() => return false,
+            KeyCode::Esc => {
+                self.query.clear();
+                self.update_matches();
+            }
+            KeyCode::Char('/') => self.searching = true,
+            KeyCode::Char('s') => {
+                self.suggest = !self.suggest;
+                self.detail_scroll = 0;
+            }
+            KeyCode::Char('f') => self.false_sharing = !self.false_sharing,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(LIST_PAGE),
+            KeyCode::PageUp => self.move_selection(-LIST_PAGE),
+            KeyCode::Home | KeyCode::Char('g') => self.move_selection(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_selection(isize::MAX),
+            KeyCode::Char('J') => self.detail_scroll = self.detail_scroll.saturating_add(1),
+            KeyCode::Char('K') => self.detail_scroll = self.detail_scroll.saturating_sub(1),
+            _ => {}
+        }
+        true
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(u32, usize)> = self
+            .layouts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, layout)| Some((fuzzy_score(&self.query, &layout.name)?, i)))
+            .collect();
+        if !self.query.is_empty() {
+            scored.sort_by_key(|&(score, i)| (Reverse(score), self.layouts[i].name.len(), i));
+        }
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.list.select((!self.matches.is_empty()).then_some(0));
+        self.detail_scroll = 0;
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let Some(last) = self.matches.len().checked_sub(1) else {
+            return;
+        };
+        let current = self.list.selected().unwrap_or(0);
+        let next = current.saturating_add_signed(delta).min(last);
+        if Some(next) != self.list.selected() {
+            self.list.select(Some(next));
+            self.detail_scroll = 0;
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(main);
+
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|&i| {
+                let layout = &self.layouts[i];
+                ListItem::new(Line::from(vec![
+                    Span::raw(layout.name.clone()),
+                    Span::raw(format!("  {}B pad", layout.metrics.padding_bytes)).dark_gray(),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(
+                " Structs {}/{} ",
+                self.matches.len(),
+                self.layouts.len()
+            )))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        let title = match (self.suggest, self.false_sharing) {
+            (false, false) => " Layout ",
+            (false, true) => " Layout: false sharing ",
+            (true, false) => " Suggested layout ",
+            (true, true) => " Suggested layout: false sharing ",
+        };
+        let lines = match self.selected() {
+            Some(layout) => self.detail_lines(layout),
+            None => vec![Line::raw("No struct matches the search")],
+        };
+        let detail = Paragraph::new(lines)
+            .block(Block::bordered().title(title))
+            .scroll((self.detail_scroll, 0));
+        frame.render_widget(detail, detail_area);
+
+        let status_line = if self.searching {
+            Line::from(vec![Span::raw("/").bold(), Span::raw(format!("{}_", self.query))])
+        } else {
+            let mut help = String::new();
+            if !self.query.is_empty() {
+                help.push_str(&format!("search: {}  Esc clear  ", self.query));
+            }
+            help.push_str("/ search  s suggest  f false sharing  J/K scroll  q quit");
+            Line::raw(help).dark_gray()
+        };
+        frame.render_widget(Paragraph::new(status_line), status);
+    }
+
+    /// The detail pane for `layout`.
+    fn detail_lines(&self, layout: &StructLayout) -> Vec<Line<'static>> {
+        let mut lines = vec![Line::raw(layout.name.clone()).bold()];
+        if let Some(location) = &layout.source_location {
+            lines.push(Line::raw(format!("defined at {}:{}", location.file, location.line)));
+        }
+
+        let shown = if self.suggest {
+            let optimized = optimize_layout(layout, self.max_align);
+            lines.push(Line::raw(if optimized.savings_bytes == 0 {
+                format!("No reordering saves space ({} bytes)", optimized.original_size)
+            } else {
+                format!(
+                    "Reordered: {} -> {} bytes, saves {} ({:.1}%)",
+                    optimized.original_size,
+                    optimized.optimized_size,
+                    optimized.savings_bytes,
+                    optimized.savings_percent
+                )
+            }));
+            if !optimized.skipped_members.is_empty() {
+                lines.push(
+                    Line::raw(format!(
+                        "Not reordered (missing offset or size): {}",
+                        optimized.skipped_members.join(", ")
+                    ))
+                    .yellow(),
+                );
+            }
+            let mut suggested = StructLayout::new(
+                layout.name.clone(),
+                optimized.optimized_size,
+                Some(optimized.struct_alignment),
+            );
+            suggested.members = optimized
+                .optimized_members
+                .iter()
+                .map(|m| {
+                    let original = layout.members.iter().find(|o| o.name == m.name);
+                    let mut member = MemberLayout::new(
+                        m.name.clone(),
+                        m.type_name.clone(),
+                        Some(m.offset),
+                        Some(m.size),
+                    );
+                    member.bit_offset = m.bit_offset;
+                    member.bit_size = m.bit_size;
+                    member.is_atomic = original.is_some_and(|o| o.is_atomic);
+                    member
+                })
+                .collect();
+            crate::analysis::analyze_layout(&mut suggested, self.cache_line_size);
+            suggested
+        } else {
+            layout.clone()
+        };
+
+        let metrics = &shown.metrics;
+        lines.push(Line::raw(format!(
+            "{} bytes, {} padding ({:.1}%), {} cache line(s)",
+            shown.size,
+            metrics.padding_bytes,
+            metrics.padding_percentage,
+            metrics.cache_lines_spanned
+        )));
+        lines.push(Line::default());
+        lines.extend(self.member_lines(&shown));
+        lines.push(Line::default());
+        lines.extend(self.byte_map(&shown));
+        lines
+    }
+
+    /// Members and padding holes in offset order.
+    fn member_lines(&self, layout: &StructLayout) -> Vec<Line<'static>> {
+        let mut rows: Vec<(u64, Line<'static>)> = layout
+            .members
+            .iter()
+            .map(|m| {
+                let offset = match (m.offset, m.bit_offset) {
+                    (Some(offset), Some(bit)) => format!("{}:{}", offset, bit),
+                    (Some(offset), None) => offset.to_string(),
+                    (None, _) => "?".to_string(),
+                };
+                let size = match (m.size, m.bit_size) {
+                    (_, Some(bits)) => format!("{}b", bits),
+                    (Some(size), None) => size.to_string(),
+                    (None, None) => "?".to_string(),
+                };
+                let line = format!("{:>8} {:>6}  {} {}", offset, size, m.type_name, m.name);
+                (m.offset.unwrap_or(u64::MAX), Line::raw(line))
+            })
+            .collect();
+        rows.extend(layout.metrics.padding_holes.iter().map(|hole| {
+            let line = format!("{:>8} {:>6}  (padding)", hole.offset, hole.size);
+            (hole.offset, Line::raw(line).red())
+        }));
+        rows.sort_by_key(|(offset, _)| *offset);
+
+        let mut lines = vec![Line::raw(format!("{:>8} {:>6}  Member", "Offset", "Size")).bold()];
+        lines.extend(rows.into_iter().map(|(_, line)| line));
+        lines
+    }
+
+    /// One character per byte, one row per cache line (or [`MAP_MAX_ROW`] bytes of it), with a
+    /// legend. With the false-sharing overlay, the bytes of atomic members are highlighted and
+    /// the lines they share are listed.
+    fn byte_map(&self, layout: &StructLayout) -> Vec<Line<'static>> {
+        if layout.size == 0 {
+            return Vec::new();
+        }
+        if layout.size > MAP_MAX_BYTES {
+            return vec![Line::raw(format!("No byte map: larger than {} bytes", MAP_MAX_BYTES))];
+        }
+
+        let analysis = self.false_sharing.then(|| {
+            analyze_false_sharing_with_patterns(layout, self.cache_line_size, &self.atomic_patterns)
+        });
+        let hot: HashSet<&str> = analysis
+            .iter()
+            .flat_map(|a| a.atomic_members.iter().map(|m| m.name.as_str()))
+            .collect();
+
+        // Which legend entry owns each byte; None is padding, Some(None) a byte several
+        // members claim.
+        let mut owners: Vec<Option<Option<usize>>> = vec![None; layout.size as usize];
+        let mut legend: Vec<&MemberLayout> = Vec::new();
+        for member in &layout.members {
+            let Some((start, end)) = byte_range(member) else {
+                continue;
+            };
+            let end = end.min(layout.size);
+            if start >= end {
+                continue;
+            }
+            for owner in &mut owners[start as usize..end as usize] {
+                *owner = Some(if owner.is_some() { None } else { Some(legend.len()) });
+            }
+            legend.push(member);
+        }
+
+        let style_of = |index: usize| {
+            let style = Style::new().fg(MAP_COLORS[index % MAP_COLORS.len()]);
+            if hot.contains(legend[index].name.as_str()) {
+                Style::new().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD)
+            } else if self.false_sharing {
+                style.add_modifier(Modifier::DIM)
+            } else {
+                style
+            }
+        };
+        let symbol_of = |index: usize| MAP_SYMBOLS[index % MAP_SYMBOLS.len()] as char;
+
+        let line = u64::from(self.cache_line_size);
+        let row_width = line.min(MAP_MAX_ROW);
+        let mut lines = vec![
+            Line::raw(format!(
+                "Byte map ({} bytes per row, '{}' = padding, '{}' = shared):",
+                row_width, MAP_PADDING, MAP_SHARED
+            ))
+            .bold(),
+        ];
+        for (row, bytes) in owners.chunks(row_width as usize).enumerate() {
+            let offset = row as u64 * row_width;
+            if offset > 0 && offset % line == 0 {
+                lines.push(
+                    Line::raw(format!("         -- cache line {} --", offset / line)).dark_gray(),
+                );
+            }
+            let mut spans = vec![Span::raw(format!("{:>8} |", offset))];
+            spans.extend(bytes.iter().map(|owner| match owner {
+                None => Span::raw(MAP_PADDING.to_string()).red(),
+                Some(None) => Span::raw(MAP_SHARED.to_string()).white(),
+                Some(Some(index)) => Span::styled(symbol_of(*index).to_string(), style_of(*index)),
+            }));
+            spans.push(Span::raw("|"));
+            lines.push(Line::from(spans));
+        }
+
+        lines.push(Line::default());
+        for (index, member) in legend.iter().enumerate() {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {}", symbol_of(index)), style_of(index)),
+                Span::raw(format!(" {}", member.name)),
+            ]));
+        }
+
+        if let Some(analysis) = analysis {
+            lines.push(Line::default());
+            if analysis.atomic_members.is_empty() {
+                lines.push(Line::raw("No atomic members"));
+            }
+            for w in &analysis.warnings {
+                lines.push(
+                    Line::raw(format!(
+                        "'{}' and '{}' share cache line {}",
+                        w.member_a, w.member_b, w.cache_line
+                    ))
+                    .yellow(),
+                );
+            }
+            for w in &analysis.spanning_warnings {
+                lines.push(
+                    Line::raw(format!(
+                        "'{}' spans cache lines {}-{}",
+                        w.member, w.start_cache_line, w.end_cache_line
+                    ))
+                    .red(),
+                );
+            }
+            if !analysis.atomic_members.is_empty()
+                && analysis.warnings.is_empty()
+                && analysis.spanning_warnings.is_empty()
+            {
+                lines.push(Line::raw("Atomic members do not share cache lines").green());
+            }
+        }
+        lines
+    }
+}
+
+/// Bytes `member` occupies, rounding bitfields out to whole bytes.
+fn byte_range(member: &MemberLayout) -> Option<(u64, u64)> {
+    let offset = member.offset?;
+    match (member.bit_offset, member.bit_size) {
+        (Some(bit_offset), Some(bit_size)) => {
+            let start_bit = offset.checked_mul(8)?.checked_add(bit_offset)?;
+            Some((start_bit / 8, start_bit.checked_add(bit_size)?.div_ceil(8)))
+        }
+        _ => Some((offset, offset.checked_add(member.size?)?)),
+    }
+}
+
+/// How well `query` matches `name` as a case-insensitive subsequence, or `None` if it is not
+/// one. Consecutive characters and characters starting a word (after a separator, or a
+/// lowercase-to-uppercase change) score higher. An empty query matches everything equally.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<u32> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let mut next = 0;
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut last_match = None;
+    for (i, c) in name.chars().enumerate() {
+        if next == query.len() {
+            break;
+        }
+        if c.to_lowercase().eq(std::iter::once(query[next])) {
+            score += 1;
+            if last_match.is_some_and(|last| last + 1 == i) {
+                score += 4;
+            }
+            let word_start = previous
+                .is_none_or(|p| !p.is_alphanumeric() || (p.is_lowercase() && c.is_uppercase()));
+            if word_start {
+                score += 3;
+            }
+            last_match = Some(i);
+            next += 1;
+        }
+        previous = Some(c);
+    }
+    (next == query.len()).then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::analyze_layout;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    fn layout(name: &str, members: &[(&str, &str, u64, u64)], size: u64) -> StructLayout {
+        let mut layout = StructLayout::new(name.to_string(), size, Some(8));
+        layout.members = members
+            .iter()
+            .map(|&(name, ty, offset, size)| {
+                let mut member =
+                    MemberLayout::new(name.to_string(), ty.to_string(), Some(offset), Some(size));
+                member.is_atomic = ty.starts_with("_Atomic");
+                member
+            })
+            .collect();
+        analyze_layout(&mut layout, 64);
+        layout
+    }
+
+    fn press(explorer: &mut Explorer, keys: &str) {
+        for c in keys.chars() {
+            explorer.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+    }
+
+    fn screen(explorer: &mut Explorer) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| explorer.render(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts_and_runs() {
+        assert_eq!(fuzzy_score("", "Anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "Connection"), None);
+        let conn = fuzzy_score("conn", "Connection").unwrap();
+        assert!(conn > fuzzy_score("conn", "ClientOwnedNodeName").unwrap());
+        assert!(fuzzy_score("rb", "RingBuffer").unwrap() > fuzzy_score("rb", "Rubber").unwrap());
+        assert!(fuzzy_score("RING", "ring_buffer").is_some());
+    }
+
+    #[test]
+    fn search_narrows_the_list_and_keys_toggle_views() {
+        let layouts = vec![
+            layout("Padded", &[("a", "char", 0, 1), ("b", "long", 8, 8), ("c", "char", 16, 1)], 24),
+            layout(
+                "RingBuffer",
+                &[("head", "_Atomic long", 0, 8), ("tail", "_Atomic long", 8, 8)],
+                16,
+            ),
+            layout("Rubber", &[("x", "int", 0, 4)], 4),
+        ];
+        let mut explorer = Explorer::new(layouts, 64);
+        assert_eq!(explorer.matching_names().count(), 3);
+
+        press(&mut explorer, "/rb");
+        let names: Vec<&str> = explorer.matching_names().collect();
+        assert_eq!(names, vec!["RingBuffer", "Rubber"]);
+        explorer.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(explorer.selected().unwrap().name, "RingBuffer");
+
+        press(&mut explorer, "f");
+        let overlay = screen(&mut explorer);
+        assert!(overlay.contains("'head' and 'tail' share cache line 0"));
+
+        explorer.handle_key(KeyEvent::from(KeyCode::Esc));
+        assert_eq!(explorer.selected().unwrap().name, "Padded");
+        let detail = screen(&mut explorer);
+        assert!(detail.contains("(padding)"));
+        assert!(detail.contains("|A.......BBBBBBBBC.......|"));
+
+        press(&mut explorer, "s");
+        let suggested = screen(&mut explorer);
+        assert!(suggested.contains("Reordered: 24 -> 16 bytes, saves 8"));
+        assert!(suggested.contains("|AAAAAAAABC......|"));
+
+        assert!(!explorer.handle_key(KeyEvent::from(KeyCode::Char('q'))));
+    }
+}
@@ -1,4 +1,7 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::cmp::Ordering;
+use std::hash::Hash;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct StructLayout {
@@ -7,8 +10,126 @@ pub struct StructLayout {
     pub alignment: Option<u64>,
     pub members: Vec<MemberLayout>,
     pub metrics: LayoutMetrics,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
     pub source_location: Option<SourceLocation>,
+    /// Variants of a Rust enum (a struct with a DW_TAG_variant_part). Enum members are not
+    /// listed in `members`, since variants overlap.
+    #[serde(rename = "enum", skip_serializing_if = "crate::output::skip_none")]
+    pub enum_layout: Option<EnumLayout>,
+    /// Binary the layout was read from, recorded when several binaries are analyzed at once.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub binary: Option<String>,
+    /// Static data members (C++ `static` / `static constexpr`). They live outside the object,
+    /// so they are kept out of `members` and the layout math.
+    #[serde(skip_serializing_if = "crate::output::skip_empty")]
+    pub static_members: Vec<StaticMember>,
+    /// Enclosing DW_TAG_namespace entries joined with `::` (a C++ namespace or Rust module
+    /// path), since struct names in debug info are unqualified.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub namespace: Option<String>,
+    /// What refers to the struct, when usage counting ran (see
+    /// `DwarfContext::annotate_usage`).
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub usage: Option<StructUsage>,
+}
+
+/// Distinct functions, types, and globals that refer to a struct by value, through one pointer
+/// or reference, or as an array element. Each is counted once however many definitions of it
+/// the debug info repeats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct StructUsage {
+    /// Functions with a parameter, return value, or local variable (inlined code included)
+    /// of the struct.
+    pub functions: usize,
+    /// Other structs, classes, and unions with a member or base class of the struct.
+    pub types: usize,
+    /// Global and static variables of the struct.
+    pub variables: usize,
+    /// The three counts summed: how many places a layout change reaches.
+    pub impact: usize,
+}
+
+impl StructUsage {
+    pub fn new(functions: usize, types: usize, variables: usize) -> Self {
+        Self { functions, types, variables, impact: functions + types + variables }
+    }
+}
+
+impl std::fmt::Display for StructUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} function(s), {} type(s), {} global(s); impact {}",
+            self.functions, self.types, self.variables, self.impact
+        )
+    }
+}
+
+/// A static data member declared in a struct.
+#[derive(Debug, Clone, Serialize)]
+pub struct StaticMember {
+    pub name: String,
+    pub type_name: String,
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub size: Option<u64>,
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub source_location: Option<SourceLocation>,
+}
+
+/// Discriminant and per-variant payloads of a Rust enum.
+#[derive(Debug, Clone, Serialize)]
+pub struct EnumLayout {
+    /// Storage read to tell variants apart. Absent for single-variant enums without a tag.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub discriminant: Option<MemberLayout>,
+    /// True if the discriminant shares bytes with a variant's payload (niche optimization):
+    /// invalid values of a payload field, such as a null reference, encode the other variants,
+    /// so the tag costs no extra space.
+    pub niche: bool,
+    pub variants: Vec<VariantLayout>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantLayout {
+    pub name: String,
+    /// DW_AT_discr_value. Absent for the variant selected when no other value matches, such as
+    /// the dataful variant of a niche-optimized enum.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub discriminant_value: Option<i128>,
+    /// Bytes of payload fields.
+    pub payload_size: u64,
+    /// Payload fields, with offsets from the start of the enum.
+    pub members: Vec<MemberLayout>,
+}
+
+impl EnumLayout {
+    /// Build from the discriminant and variants, detecting niche use.
+    pub fn new(discriminant: Option<MemberLayout>, variants: Vec<VariantLayout>) -> Self {
+        let tag_range = discriminant
+            .as_ref()
+            .and_then(|d| Some((d.offset?, d.offset?.saturating_add(d.size?))));
+        let niche = tag_range.is_some_and(|(start, end)| {
+            variants.iter().flat_map(|v| &v.members).any(|m| {
+                let (Some(offset), Some(size)) = (m.offset, m.size) else {
+                    return false;
+                };
+                size > 0 && offset < end && start < offset.saturating_add(size)
+            })
+        });
+        Self { discriminant, niche, variants }
+    }
+
+    /// Bytes spent on the tag itself: 0 without a tag or when it lives in a niche.
+    pub fn tag_bytes(&self) -> u64 {
+        match &self.discriminant {
+            Some(d) if !self.niche => d.size.unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    pub fn largest_variant(&self) -> Option<&VariantLayout> {
+        self.variants.iter().max_by_key(|v| v.payload_size)
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -17,14 +138,110 @@ pub struct MemberLayout {
     pub type_name: String,
     pub offset: Option<u64>,
     pub size: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
     pub bit_offset: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
     pub bit_size: Option<u64>,
+    /// Alignment the debug info establishes for the member: its own or its type's
+    /// `DW_AT_alignment`, else the type's natural alignment. Absent when only the size can
+    /// suggest one.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub alignment: Option<u64>,
+    /// True if `alignment` comes from a `DW_AT_alignment` (alignas, aligned attributes,
+    /// #[repr(align)]) rather than the type's natural alignment.
+    #[serde(skip_serializing_if = "crate::output::skip_false")]
+    pub explicit_alignment: bool,
     /// True if the type was marked with DW_TAG_atomic_type in DWARF debug info.
     /// This provides more reliable atomic detection than string pattern matching.
-    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    #[serde(skip_serializing_if = "crate::output::skip_false")]
     pub is_atomic: bool,
+    /// Declaration site of the member (DW_AT_decl_file/DW_AT_decl_line), if recorded.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub source_location: Option<SourceLocation>,
+    /// Number of functions whose variable locations reference this member, when static access
+    /// analysis ran (see `DwarfContext::annotate_static_popularity`).
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub static_popularity: Option<u32>,
+    /// Size (and alignment) taken from the config's `type_sizes` because the debug info lacks
+    /// them; metrics that use this member rest on the assumption.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub assumed: Option<DeclaredSize>,
+    /// What a C++ implementation-defined member stands for; `None` for ordinary members.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub role: Option<MemberRole>,
+    /// The unnamed struct or union this member was flattened out of: the member's name (`u`
+    /// for `union { .. } u;`) or `<anonymous>@offset` for a C11 anonymous member. Reordering
+    /// keeps members of one aggregate together.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub aggregate: Option<String>,
+    /// Size (DW_AT_byte_size, tail padding included) and alignment of that aggregate, for
+    /// moving it as one block.
+    #[serde(skip)]
+    pub aggregate_extent: Option<AggregateExtent>,
+    /// Tag of the named type at the core of `type_name`, through pointers, arrays, and
+    /// qualifiers: C spells it out (`struct Inner`) where no typedef names the type.
+    #[serde(skip)]
+    pub type_tag: Option<TypeTag>,
+    /// Layout of the base class for inheritance members, used for padding attribution.
+    #[serde(skip)]
+    pub base_layout: Option<Box<StructLayout>>,
+}
+
+/// The kind of a tagged C type: `struct`, `union`, or `enum`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeTag {
+    Struct,
+    Union,
+    Enum,
+}
+
+impl TypeTag {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            TypeTag::Struct => "struct",
+            TypeTag::Union => "union",
+            TypeTag::Enum => "enum",
+        }
+    }
+}
+
+/// Bytes an unnamed struct or union takes in its parent, from the member holding it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggregateExtent {
+    pub size: u64,
+    pub alignment: Option<u64>,
+}
+
+/// C++ members the compiler adds or places on its own rather than the source declaring them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberRole {
+    /// The vtable pointer of a dynamic class, whether recorded (`_vptr.X`) or synthesized.
+    VtablePointer,
+    /// An empty base class laid out at zero size by the empty base optimization.
+    EmptyBase,
+    /// A virtual base, shared by every path that inherits it.
+    VirtualBase,
+}
+
+impl MemberRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MemberRole::VtablePointer => "vtable pointer",
+            MemberRole::EmptyBase => "empty base",
+            MemberRole::VirtualBase => "virtual base",
+        }
+    }
+}
+
+/// A type size (and optionally alignment) declared in the config rather than read from the
+/// debug info.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeclaredSize {
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "crate::output::skip_none")]
+    pub align: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Default)]
@@ -36,10 +253,75 @@ pub struct LayoutMetrics {
     pub cache_lines_spanned: u32,
     pub cache_line_density: f64,
     pub padding_holes: Vec<PaddingHole>,
-    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    #[serde(skip_serializing_if = "crate::output::skip_false")]
     pub partial: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Members missing an offset or size; any entry makes the layout partial.
+    #[serde(skip_serializing_if = "crate::output::skip_empty")]
+    pub partial_members: Vec<String>,
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
     pub false_sharing: Option<FalseSharingAnalysis>,
+    #[serde(skip_serializing_if = "crate::output::skip_empty")]
+    pub bases: Vec<BaseAttribution>,
+    /// Allocation slack under the selected allocator model, when one was requested.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub alloc_slack: Option<AllocSlack>,
+    /// Bit-level view, for bitfield-only structs or when requested with `--bits`.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub bits: Option<BitLayout>,
+    /// Distance between consecutive array elements: the size rounded up to the alignment.
+    pub stride: u64,
+    /// Bytes of each array element that hold no member data (0 for partial layouts).
+    pub stride_waste: u64,
+    /// Global and static variables of this type, one per array element, when counted from the
+    /// symbol table.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub static_instances: Option<u64>,
+    /// Padding bytes times `static_instances`: what the padding costs in the binary's data.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub weighted_waste_bytes: Option<u64>,
+}
+
+impl LayoutMetrics {
+    /// Stride over the bytes members occupy, e.g. 8.0 for an 8-byte value in a 64-byte
+    /// aligned slot. `None` when nothing is known to be occupied.
+    pub fn stride_factor(&self) -> Option<f64> {
+        (self.useful_size > 0 && !self.partial)
+            .then(|| self.stride as f64 / self.useful_size as f64)
+    }
+}
+
+/// How much of its heap allocation a struct leaves unused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AllocSlack {
+    /// Bytes the allocator hands out (for glibc, the whole chunk including its header).
+    pub size_class: u64,
+    /// `size_class` minus the struct size.
+    pub slack_bytes: u64,
+}
+
+/// Bit ranges of a struct's members and the bits none of them use.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BitLayout {
+    /// Bits allocated for the struct (its size times 8).
+    pub total_bits: u64,
+    /// Bits covered by at least one member.
+    pub used_bits: u64,
+    pub fields: Vec<BitRange>,
+    pub gaps: Vec<BitGap>,
+}
+
+/// Bits `start_bit..start_bit + bits` of the struct, counted from bit 0 of byte 0.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BitRange {
+    pub name: String,
+    pub start_bit: u64,
+    pub bits: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct BitGap {
+    pub start_bit: u64,
+    pub bits: u64,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -49,7 +331,27 @@ pub struct PaddingHole {
     pub after_member: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// Padding attribution for one base class subobject of a derived class.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BaseAttribution {
+    pub name: String,
+    pub type_name: String,
+    pub offset: u64,
+    pub size: u64,
+    /// Bytes up to the end of the base's last member (size without tail padding).
+    pub data_size: u64,
+    /// Padding inside the base subobject, including its tail padding.
+    pub padding_bytes: u64,
+    pub tail_padding: u64,
+    /// True if the derived class placed members into the base's tail padding.
+    pub tail_padding_reused: bool,
+    #[serde(skip_serializing_if = "crate::output::skip_false")]
+    pub is_virtual: bool,
+    #[serde(skip_serializing_if = "crate::output::skip_empty")]
+    pub tail_reused_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceLocation {
     pub file: String,
     pub line: u64,
@@ -80,7 +382,7 @@ pub struct CacheLineSpanningWarning {
 pub struct FalseSharingAnalysis {
     pub atomic_members: Vec<AtomicMember>,
     pub warnings: Vec<FalseSharingWarning>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(skip_serializing_if = "crate::output::skip_empty")]
     pub spanning_warnings: Vec<CacheLineSpanningWarning>,
 }
 
@@ -95,6 +397,53 @@ pub struct AtomicMember {
     pub spans_cache_lines: bool,
 }
 
+/// A global or static variable at a fixed address.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct GlobalVariable {
+    pub name: String,
+    pub type_name: String,
+    pub address: u64,
+    pub size: u64,
+}
+
+impl From<GlobalVariable> for SharedGlobal {
+    fn from(g: GlobalVariable) -> Self {
+        Self {
+            name: g.name,
+            type_name: g.type_name,
+            address: g.address,
+            size: g.size,
+            section: None,
+        }
+    }
+}
+
+/// A global or static variable whose type holds an atomic (or a lock built on one), so threads
+/// are expected to write it concurrently.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SharedGlobal {
+    pub name: String,
+    pub type_name: String,
+    pub address: u64,
+    pub size: u64,
+    /// Section holding the variable, once placed against the symbol table.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub section: Option<String>,
+}
+
+/// Two thread-shared globals that touch the same cache line.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct GlobalFalseSharingWarning {
+    /// The global at the lower address.
+    pub first: SharedGlobal,
+    pub second: SharedGlobal,
+    /// Address of the first cache line both touch.
+    pub cache_line_address: u64,
+    /// Binary the globals live in, when several binaries are analyzed together.
+    #[serde(skip_serializing_if = "crate::output::skip_none")]
+    pub binary: Option<String>,
+}
+
 impl StructLayout {
     pub fn new(name: String, size: u64, alignment: Option<u64>) -> Self {
         Self {
@@ -104,13 +453,117 @@ impl StructLayout {
             members: Vec::new(),
             metrics: LayoutMetrics::default(),
             source_location: None,
+            enum_layout: None,
+            binary: None,
+            static_members: Vec::new(),
+            namespace: None,
+            usage: None,
+        }
+    }
+}
+
+impl StructLayout {
+    /// The canonical total order used to break ties after any user-requested sort:
+    /// name, then size, then the remaining fingerprint (alignment, source location, members).
+    /// Two layouts compare equal only if they are indistinguishable duplicates.
+    pub fn canonical_cmp(&self, other: &Self) -> Ordering {
+        self.name
+            .cmp(&other.name)
+            .then_with(|| self.size.cmp(&other.size))
+            .then_with(|| self.fingerprint().cmp(&other.fingerprint()))
+    }
+
+    pub(crate) fn fingerprint(&self) -> StructFingerprint {
+        StructFingerprint {
+            name: self.name.clone(),
+            size: self.size,
+            alignment: self.alignment,
+            source: self.source_location.as_ref().map(|l| (l.file.clone(), l.line)),
+            members: self
+                .members
+                .iter()
+                .map(|m| MemberFingerprint {
+                    name: m.name.clone(),
+                    type_name: m.type_name.clone(),
+                    offset: m.offset,
+                    size: m.size,
+                    bit_offset: m.bit_offset,
+                    bit_size: m.bit_size,
+                    is_atomic: m.is_atomic,
+                })
+                .collect(),
         }
     }
 }
 
+/// Identity of a layout for deduplication and canonical ordering. Field order matters:
+/// the derived `Ord` compares name and size first.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct StructFingerprint {
+    name: String,
+    size: u64,
+    alignment: Option<u64>,
+    source: Option<(String, u64)>,
+    members: Vec<MemberFingerprint>,
+}
+
+impl StructFingerprint {
+    /// SHA-256 of the whole fingerprint: a fixed-size stand-in for it when many must be kept,
+    /// with no practical chance of two distinct layouts colliding.
+    pub(crate) fn digest(&self) -> [u8; 32] {
+        let mut writer = DigestWriter(Sha256::new());
+        self.hash(&mut writer);
+        writer.0.finalize().into()
+    }
+}
+
+/// Feeds what [`Hash`] writes into SHA-256. Derived `Hash` length-prefixes collections and
+/// terminates strings, so equal digests mean equal fingerprints.
+struct DigestWriter(Sha256);
+
+impl std::hash::Hasher for DigestWriter {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    /// Unused: only the full digest is read.
+    fn finish(&self) -> u64 {
+        0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct MemberFingerprint {
+    name: String,
+    type_name: String,
+    offset: Option<u64>,
+    size: Option<u64>,
+    bit_offset: Option<u64>,
+    bit_size: Option<u64>,
+    is_atomic: bool,
+}
+
 impl MemberLayout {
     pub fn new(name: String, type_name: String, offset: Option<u64>, size: Option<u64>) -> Self {
-        Self { name, type_name, offset, size, bit_offset: None, bit_size: None, is_atomic: false }
+        Self {
+            name,
+            type_name,
+            offset,
+            size,
+            bit_offset: None,
+            bit_size: None,
+            alignment: None,
+            explicit_alignment: false,
+            is_atomic: false,
+            source_location: None,
+            static_popularity: None,
+            assumed: None,
+            role: None,
+            aggregate: None,
+            aggregate_extent: None,
+            type_tag: None,
+            base_layout: None,
+        }
     }
 
     pub fn with_atomic(mut self, is_atomic: bool) -> Self {
@@ -131,6 +584,41 @@ impl MemberLayout {
 mod tests {
     use super::*;
 
+    #[test]
+    fn canonical_cmp_orders_by_name_size_then_fingerprint() {
+        let a = StructLayout::new("A".to_string(), 16, None);
+        let b_small = StructLayout::new("B".to_string(), 8, None);
+        let b_large = StructLayout::new("B".to_string(), 32, None);
+        let mut b_located = b_small.clone();
+        b_located.source_location = Some(SourceLocation { file: "b.c".to_string(), line: 1 });
+
+        let mut layouts = [b_located, b_large, a, b_small.clone()];
+        layouts.sort_by(StructLayout::canonical_cmp);
+        let order: Vec<(&str, u64, bool)> = layouts
+            .iter()
+            .map(|l| (l.name.as_str(), l.size, l.source_location.is_some()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![("A", 16, false), ("B", 8, false), ("B", 8, true), ("B", 32, false)]
+        );
+        assert_eq!(b_small.canonical_cmp(&b_small.clone()), Ordering::Equal);
+    }
+
+    #[test]
+    fn fingerprint_digest_tells_members_apart() {
+        let mut ab = StructLayout::new("S".to_string(), 8, Some(4));
+        let member = |name: &str, offset| {
+            MemberLayout::new(name.to_string(), "int".to_string(), Some(offset), Some(4))
+        };
+        ab.members = vec![member("a", 0), member("b", 4)];
+        let mut joined = ab.clone();
+        joined.members = vec![member("ab", 0), member("", 4)];
+
+        assert_eq!(ab.fingerprint().digest(), ab.clone().fingerprint().digest());
+        assert_ne!(ab.fingerprint().digest(), joined.fingerprint().digest());
+    }
+
     #[test]
     fn member_end_offset_handles_overflow() {
         let member = MemberLayout::new("a".to_string(), "u8".to_string(), Some(u64::MAX), Some(1));
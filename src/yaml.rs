@@ -0,0 +1,213 @@
+//! Key paths into YAML documents, for pointing config errors at a line and column. serde
+//! skips keys a struct does not declare, so finding typos needs the declared keys and a way
+//! back from a key path to where it sits in the text.
+
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+
+/// One step of a key path: a mapping key or a sequence index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// A dotted rendering such as `budgets.Foo.max_size` or `ignore[0].pattern`.
+pub struct DisplayPath<'a>(pub &'a [PathSegment]);
+
+impl fmt::Display for DisplayPath<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Key(key) if i == 0 => write!(f, "{}", key)?,
+                PathSegment::Key(key) => write!(f, ".{}", key)?,
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The keys a derived `Deserialize` struct accepts, in declaration order. Empty for types
+/// that do not deserialize as a struct.
+pub fn struct_fields<'de, T: de::Deserialize<'de>>() -> &'static [&'static str] {
+    match T::deserialize(FieldsProbe) {
+        Err(ProbeError::Fields(fields)) => fields,
+        _ => &[],
+    }
+}
+
+/// Line and column (both 1-based) of the last key in `path` within `text`. `None` when the
+/// key is missing or the path does not end in a key.
+pub fn locate(text: &str, path: &[PathSegment]) -> Option<(usize, usize)> {
+    if !matches!(path.last(), Some(PathSegment::Key(_))) {
+        return None;
+    }
+    let error = Locate(path).deserialize(serde_yaml::Deserializer::from_str(text)).err()?;
+    if !error.to_string().contains(FOUND) {
+        return None;
+    }
+    error.location().map(|location| (location.line(), location.column()))
+}
+
+/// Error text that marks the node [`locate`] is looking for.
+const FOUND: &str = "layout-audit: located";
+
+struct Locate<'p>(&'p [PathSegment]);
+
+impl<'de> DeserializeSeed<'de> for Locate<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Locate<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a mapping or sequence containing {}", DisplayPath(self.0))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let Some((PathSegment::Key(wanted), rest)) = self.0.split_first() else {
+            return Ok(());
+        };
+        while let Some(found) = map.next_key_seed(KeySeed { wanted, last: rest.is_empty() })? {
+            if found {
+                map.next_value_seed(Locate(rest))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let Some((&PathSegment::Index(wanted), rest)) = self.0.split_first() else {
+            return Ok(());
+        };
+        let mut index = 0;
+        while index < wanted && seq.next_element::<IgnoredAny>()?.is_some() {
+            index += 1;
+        }
+        if !rest.is_empty() {
+            seq.next_element_seed(Locate(rest))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads one mapping key: whether it is the wanted one, failing at it when it ends the path.
+struct KeySeed<'a> {
+    wanted: &'a str,
+    last: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for KeySeed<'_> {
+    type Value = bool;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<bool, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeySeed<'_> {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a mapping key")
+    }
+
+    fn visit_str<E: de::Error>(self, key: &str) -> Result<bool, E> {
+        match key == self.wanted {
+            true if self.last => Err(E::custom(FOUND)),
+            found => Ok(found),
+        }
+    }
+}
+
+/// A deserializer that only reports the field list a struct asks it for.
+struct FieldsProbe;
+
+#[derive(Debug)]
+enum ProbeError {
+    Fields(&'static [&'static str]),
+    NotAStruct,
+}
+
+impl fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("not a struct")
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+impl de::Error for ProbeError {
+    fn custom<T: fmt::Display>(_: T) -> Self {
+        ProbeError::NotAStruct
+    }
+}
+
+impl<'de> Deserializer<'de> for FieldsProbe {
+    type Error = ProbeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, ProbeError> {
+        Err(ProbeError::NotAStruct)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, ProbeError> {
+        Err(ProbeError::Fields(fields))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+        ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Sample {
+        name: String,
+        #[serde(default)]
+        limit: Option<u64>,
+    }
+
+    #[test]
+    fn struct_fields_lists_declared_keys() {
+        assert_eq!(struct_fields::<Sample>(), &["name", "limit"]);
+        assert!(struct_fields::<Vec<String>>().is_empty());
+    }
+
+    #[test]
+    fn locate_points_at_keys() {
+        let text = "budgets:\n  Foo:\n    max_size: 8\n    max_paddding: 2\nignore:\n  - pattern: A\n  - pattern: B\n";
+        let key = |k: &str| PathSegment::Key(k.to_string());
+
+        assert_eq!(locate(text, &[key("budgets"), key("Foo"), key("max_paddding")]), Some((4, 5)));
+        assert_eq!(locate(text, &[key("budgets"), key("Foo")]), Some((2, 3)));
+        assert_eq!(
+            locate(text, &[key("ignore"), PathSegment::Index(1), key("pattern")]),
+            Some((7, 5))
+        );
+        assert_eq!(locate(text, &[key("ignore"), PathSegment::Index(1)]), None);
+        assert_eq!(locate(text, &[key("budgets"), key("Bar")]), None);
+        assert_eq!(
+            DisplayPath(&[key("ignore"), PathSegment::Index(0), key("pattern")]).to_string(),
+            "ignore[0].pattern"
+        );
+    }
+}
@@ -0,0 +1,45 @@
+// Fixture for alignment read from the debug info: members whose alignment a size-based guess
+// gets wrong (alignas, aligned typedefs, SIMD vectors), and structs inside `#pragma pack`.
+// Build: gcc -g -o tests/fixtures/bin/test_aligned tests/fixtures/test_aligned.c
+
+#include <stdalign.h>
+
+typedef float Vec4 __attribute__((vector_size(16)));
+typedef int SlotIndex __attribute__((aligned(32)));
+
+struct OverAligned {
+    char tag;
+    alignas(64) int slot;
+    char flag;
+    Vec4 lanes;
+    SlotIndex index;
+};
+
+#pragma pack(push, 2)
+struct WireHeader {
+    char kind;
+    int length;
+    char flags;
+    long sequence;
+};
+
+struct WireTrailer {
+    short checksum;
+    int crc;
+};
+#pragma pack(pop)
+
+struct NaturalRecord {
+    char kind;
+    int length;
+};
+
+struct OverAligned over_aligned;
+struct WireHeader wire_header;
+struct WireTrailer wire_trailer;
+struct NaturalRecord natural_record;
+
+int main(void) {
+    over_aligned.tag = 1;
+    return over_aligned.flag + wire_header.kind + wire_trailer.crc + natural_record.kind;
+}
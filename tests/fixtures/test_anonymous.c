@@ -0,0 +1,27 @@
+// Fixture for flattening unnamed structs and unions into their parent layout.
+// Build: gcc -g -o tests/fixtures/bin/test_anonymous tests/fixtures/test_anonymous.c
+
+struct Message {
+    char kind;
+    union {
+        int code;
+        double ratio;
+    };
+    struct {
+        short lo;
+        long hi;
+    };
+    union {
+        char bytes[4];
+        int word;
+    } u;
+};
+
+int main(void) {
+    struct Message m = {0};
+    m.kind = 1;
+    m.code = 2;
+    m.hi = 3;
+    m.u.word = 4;
+    return m.kind + m.code + (int)m.hi + m.u.word;
+}
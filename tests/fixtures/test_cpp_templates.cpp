@@ -164,6 +164,48 @@ struct Task {
     uint32_t priority;
 };
 
+// Polymorphic classes: the vtable pointer is an implicit member
+struct Shape {
+    virtual ~Shape() {}
+    virtual int area() const { return 0; }
+    uint32_t id;
+};
+
+struct Circle : Shape {
+    int area() const override { return 1; }
+    uint32_t radius;
+};
+
+// Empty base optimization
+struct EmptyPolicy {};
+
+struct WithEmptyBase : EmptyPolicy {
+    uint64_t value;
+};
+
+// Virtual inheritance
+struct SharedState {
+    uint64_t refs;
+};
+
+struct LeftPath : virtual SharedState {
+    uint32_t left;
+};
+
+struct RightPath : virtual SharedState {
+    uint32_t right;
+};
+
+struct DiamondPath : LeftPath, RightPath {
+    uint32_t both;
+};
+
+// Pointer to member function
+struct MethodHolder {
+    int (Shape::*method)() const;
+    uint8_t tag;
+};
+
 // Instantiate templates with various types to generate DWARF entries
 int main() {
     // Simple containers
@@ -237,6 +279,12 @@ int main() {
     int ref_value = 7;
     RefHolder ref_holder{ref_value};
 
+    // C++ object model
+    Circle circle{};
+    WithEmptyBase with_empty{};
+    DiamondPath diamond{};
+    MethodHolder method_holder{};
+
     // Prevent optimization
     (void)c_int; (void)c_double; (void)c_ptr;
     (void)p_int_int; (void)p_char_double; (void)p_u64_u8;
@@ -256,6 +304,7 @@ int main() {
     (void)widget_class;
     (void)task_int; (void)task_vec;
     (void)ref_holder;
+    (void)circle; (void)with_empty; (void)diamond; (void)method_holder;
 
     return 0;
 }
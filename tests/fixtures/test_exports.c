@@ -0,0 +1,41 @@
+// Shared library fixture for --exported-only.
+// Build: gcc -g -shared -fPIC -o tests/fixtures/bin/libtest_exports.so tests/fixtures/test_exports.c
+
+struct Inner {
+    char tag;
+    long value;
+};
+
+// Reachable through a pointer parameter of an exported function.
+struct Request {
+    int id;
+    struct Inner inner;
+};
+
+// Reachable only through the return type of an exported function.
+struct Response {
+    char ok;
+    double latency;
+};
+
+// Used only by a function with hidden visibility.
+struct Hidden {
+    char a;
+    long b;
+};
+
+// Referenced only inside a function body, never in a signature.
+struct Local {
+    short s;
+    long l;
+};
+
+struct Response handle_request(const struct Request *req) {
+    struct Local local = { (short)req->id, req->inner.value };
+    struct Response resp = { 1, (double)local.l };
+    return resp;
+}
+
+__attribute__((visibility("hidden"))) long hidden_helper(struct Hidden *h) {
+    return h->a + h->b;
+}
@@ -0,0 +1,35 @@
+// Fixture for --global-false-sharing: thread-shared globals (atomics, or structs holding one)
+// that the linker packs next to each other. Built a second time with -DPADDED, which gives each
+// of them a cache line of its own.
+// Build: gcc -g -o tests/fixtures/bin/test_globals tests/fixtures/test_globals.c
+//        gcc -g -DPADDED -o tests/fixtures/bin/test_globals_padded tests/fixtures/test_globals.c
+
+#ifdef PADDED
+#define OWN_LINE __attribute__((aligned(64)))
+#else
+#define OWN_LINE
+#endif
+
+struct Stats {
+    _Atomic int hits;
+    int misses;
+};
+
+_Atomic long requests OWN_LINE;
+_Atomic long errors OWN_LINE;
+struct Stats stats OWN_LINE;
+static _Atomic int shutdown_flag OWN_LINE;
+
+// Not thread-shared: plain data, and a per-thread counter.
+long plain_counter;
+_Thread_local _Atomic int per_thread_hits;
+
+int main(void) {
+    requests += 1;
+    errors += 1;
+    stats.hits += 1;
+    shutdown_flag = 1;
+    per_thread_hits += 1;
+    plain_counter += 1;
+    return 0;
+}
@@ -0,0 +1,36 @@
+// Rust enums for variant layout tests.
+// Build: rustc -g -C opt-level=0 -o tests/fixtures/bin/test_rust_enums tests/fixtures/test_rust_enums.rs
+
+use std::hint::black_box;
+
+// Tagged: a one-byte tag before the largest payload.
+pub enum Shape {
+    Empty,
+    Circle(u32),
+    Rect { w: u64, h: u64 },
+}
+
+// Niche: the null pointer encodes None, so there is no separate tag.
+pub enum MaybeRef<'a> {
+    Nothing,
+    Just(&'a u64),
+}
+
+// Niche in a bool: values 2 and 3 encode the dataless variants.
+pub enum Tri {
+    Flag(bool),
+    Unknown,
+    Invalid,
+}
+
+fn main() {
+    let value = 7u64;
+    black_box(Shape::Empty);
+    black_box(Shape::Circle(3));
+    black_box(Shape::Rect { w: 1, h: 2 });
+    black_box(MaybeRef::Nothing);
+    black_box(MaybeRef::Just(&value));
+    black_box(Tri::Flag(true));
+    black_box(Tri::Unknown);
+    black_box(Tri::Invalid);
+}
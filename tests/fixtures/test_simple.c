@@ -38,7 +38,7 @@ struct Outer {
     // 3 bytes tail padding
 };
 
-// Array member
+// Array member (layout-audit: allow(max_padding))
 struct WithArray {
     int count;
     char data[10];
@@ -0,0 +1,29 @@
+// Optimized fixture for --static-access: SROA splits `struct Pair` locals into registers,
+// so their location lists describe which members each function keeps live.
+// Build: gcc -O2 -g -o tests/fixtures/bin/test_static_access tests/fixtures/test_static_access.c
+
+struct Pair {
+    long hot;
+    long cold;
+    int other;
+};
+
+static inline long combine(struct Pair p) {
+    return p.hot * 3 + p.cold;
+}
+
+__attribute__((noinline)) long uses_both(long a, long b) {
+    struct Pair p = {a, b, (int)a};
+    return combine(p);
+}
+
+__attribute__((noinline)) long uses_hot(long a) {
+    struct Pair p = {a, 0, 0};
+    volatile long h = p.hot;
+    return h;
+}
+
+int main(int argc, char **argv) {
+    (void)argv;
+    return (int)(uses_both(argc, 2) + uses_hot(argc));
+}
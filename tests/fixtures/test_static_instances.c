@@ -0,0 +1,26 @@
+// Fixture for static instance weighting: two structs with the same padding, one stored in large
+// static arrays and the other once.
+// Build: gcc -g -o tests/fixtures/bin/test_static_instances tests/fixtures/test_static_instances.c
+
+struct Entry {
+    char tag;
+    long value;
+};
+
+struct Limits {
+    long max;
+    char mode;
+};
+
+static struct Entry table[1000];
+const struct Entry defaults[4] = {{1, 10}, {2, 20}, {3, 30}, {4, 40}};
+struct Limits limits;
+// A pointer stores no Entry of its own.
+struct Entry *cursor;
+
+int main(void) {
+    table[0].tag = 1;
+    cursor = &table[1];
+    limits.mode = 2;
+    return table[0].tag + limits.mode + defaults[1].tag + (cursor != 0);
+}
@@ -0,0 +1,33 @@
+// Fixture for compare-targets: one source built for x86_64 and i386, whose structs differ in
+// pointer width, `long double`, and the alignment of `double`. The i386 build is an object file,
+// so it needs no 32-bit C library.
+// Build: gcc -g -o tests/fixtures/bin/test_targets_x86_64 tests/fixtures/test_targets.c
+//        gcc -m32 -g -c -o tests/fixtures/bin/test_targets_i386 tests/fixtures/test_targets.c
+
+struct ListNode {
+    int value;
+    struct ListNode *next;
+};
+
+struct Sample {
+    int count;
+    double mean;
+};
+
+struct Extended {
+    char tag;
+    long double value;
+};
+
+struct Rgb {
+    unsigned char r, g, b;
+};
+
+struct ListNode list_head;
+struct Sample sample;
+struct Extended extended;
+struct Rgb rgb;
+
+int main(void) {
+    return list_head.value + sample.count + extended.tag + rgb.r;
+}
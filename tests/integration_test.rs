@@ -1,4 +1,14 @@
-use layout_audit::{BinaryData, DwarfContext, analyze_layout};
+use layout_audit::{
+    BinaryData, DwarfContext, DwarfFeatures, MemberRole, StructLayout, analyze_bases,
+    analyze_layout,
+};
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+#[path = "../src/test_support/fixtures.rs"]
+mod fixtures;
+
+use fixtures::find_fixture_path;
 
 /// Check if fixture tests should be skipped (for local dev without compiled fixtures).
 /// Set SKIP_FIXTURE_TESTS=1 to skip. CI should never set this.
@@ -6,30 +16,6 @@ fn should_skip_fixture_tests() -> bool {
     std::env::var("SKIP_FIXTURE_TESTS").is_ok_and(|v| v == "1")
 }
 
-/// Get the path to the test fixture binary, or None if not found.
-fn find_fixture_path(name: &str) -> Option<std::path::PathBuf> {
-    // Try dSYM path first (macOS)
-    let dsym_path = std::path::Path::new("tests/fixtures/bin")
-        .join(format!("{}.dSYM/Contents/Resources/DWARF/{}", name, name));
-    if dsym_path.exists() {
-        return Some(dsym_path);
-    }
-
-    // Try Windows PE binary with .exe extension
-    let exe_path = std::path::Path::new("tests/fixtures/bin").join(format!("{}.exe", name));
-    if exe_path.exists() {
-        return Some(exe_path);
-    }
-
-    // Fall back to direct binary (Linux with embedded debug info)
-    let direct_path = std::path::Path::new("tests/fixtures/bin").join(name);
-    if direct_path.exists() {
-        return Some(direct_path);
-    }
-
-    None
-}
-
 /// Get fixture path, panicking if not found (unless SKIP_FIXTURE_TESTS=1).
 /// Returns None only when SKIP_FIXTURE_TESTS=1 and fixture is missing.
 fn get_fixture_path() -> Option<std::path::PathBuf> {
@@ -74,6 +60,127 @@ fn test_parse_simple_struct() {
     assert_eq!(layout.metrics.padding_bytes, 0);
 }
 
+/// Structs and input files read from a fixture whose debug info lives outside the binary
+/// (Linux-only fixtures; None when not compiled).
+fn external_debug_fixture(name: &str) -> Option<(Vec<String>, Vec<std::path::PathBuf>)> {
+    let path = std::path::Path::new("tests/fixtures/bin").join(name);
+    if !path.exists() {
+        return None;
+    }
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let layouts = DwarfContext::new(&loaded)
+        .find_structs(Some("Outer"), false)
+        .expect("Failed to parse structs");
+    let names = layouts.into_iter().map(|l| l.name).collect();
+    Some((names, binary.input_files()))
+}
+
+#[test]
+fn test_external_debug_info() {
+    // gcc -g, then objcopy --only-keep-debug / --strip-debug --add-gnu-debuglink
+    if let Some((names, inputs)) = external_debug_fixture("test_debuglink") {
+        assert_eq!(names, vec!["Outer"]);
+        assert!(inputs.iter().any(|p| p.ends_with("test_debuglink.debug")), "{:?}", inputs);
+    }
+
+    // gcc -gsplit-dwarf, .dwo left next to the binary
+    if let Some((names, inputs)) = external_debug_fixture("test_split") {
+        assert_eq!(names, vec!["Outer"]);
+        assert!(inputs.iter().any(|p| p.ends_with("test_split-test_simple.dwo")), "{:?}", inputs);
+    }
+
+    // gcc -gdwarf-4 -gsplit-dwarf, packaged with dwp and the .dwo removed
+    if let Some((names, inputs)) = external_debug_fixture("test_split_dwp") {
+        assert_eq!(names, vec!["Outer"]);
+        assert!(inputs.iter().any(|p| p.ends_with("test_split_dwp.dwp")), "{:?}", inputs);
+    }
+}
+
+#[test]
+fn test_compressed_debug_sections() {
+    // objcopy --compress-debug-sections={zlib,zlib-gnu,zstd}
+    for name in ["test_simple_zlib", "test_simple_zlib_gnu", "test_simple_zstd"] {
+        if let Some((names, _)) = external_debug_fixture(name) {
+            assert_eq!(names, vec!["Outer"], "{}", name);
+        }
+    }
+}
+
+/// DWARF features of a fixture, or None when it was not compiled.
+fn dwarf_features(name: &str) -> Option<DwarfFeatures> {
+    let path = std::path::Path::new("tests/fixtures/bin").join(name);
+    if !path.exists() {
+        return None;
+    }
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let (_, stats) = DwarfContext::new(&loaded).find_structs_with_stats(None, false).expect("scan");
+    Some(DwarfFeatures::new(&loaded, &stats))
+}
+
+#[test]
+fn test_dwarf_features() {
+    if let Some(features) = dwarf_features("test_cpp_templates_dwarf4") {
+        assert_eq!(features.versions.keys().copied().collect::<Vec<_>>(), vec![4]);
+        assert!(features.unsupported_forms.is_empty(), "{:?}", features.unsupported_forms);
+    }
+    if let Some(features) = dwarf_features("test_simple_zlib") {
+        assert!(features.compressed_sections.iter().any(|s| s == ".debug_info"));
+    }
+    if let Some(features) = dwarf_features("test_split") {
+        assert_eq!(features.split_units, 1);
+        assert!(features.missing_dwo.is_empty());
+        assert!(features.hints().is_empty());
+    }
+}
+
+#[test]
+fn test_cli_inspect_reports_debug_info() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect", path.to_str().unwrap(), "-o", "json", "--stats"])
+        .output()
+        .expect("Failed to run command");
+    assert!(output.status.success());
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("valid JSON");
+    let debug_info = &parsed["debug_info"][0];
+    assert_eq!(debug_info["binary"], path.to_str().unwrap());
+    assert!(debug_info["versions"].as_object().is_some_and(|v| !v.is_empty()));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("DWARF versions: v"));
+}
+
+#[test]
+fn test_member_source_locations() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let dwarf = DwarfContext::new(&loaded);
+
+    let layouts =
+        dwarf.find_structs(Some("InternalPadding"), false).expect("Failed to parse structs");
+    let layout = &layouts[0];
+    let struct_line = layout.source_location.as_ref().expect("struct decl line").line;
+
+    let lines: Vec<u64> = layout
+        .members
+        .iter()
+        .map(|m| m.source_location.as_ref().expect("member decl line").line)
+        .collect();
+    assert!(lines.iter().all(|&line| line > struct_line));
+    assert!(lines.windows(2).all(|w| w[0] < w[1]));
+}
+
 #[test]
 fn test_detect_padding() {
     let path = match get_fixture_path() {
@@ -99,6 +206,101 @@ fn test_detect_padding() {
     assert!(!layout.metrics.padding_holes.is_empty());
 }
 
+#[test]
+fn test_iter_structs_matches_find_structs() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let dwarf = DwarfContext::new(&loaded);
+
+    let found = dwarf
+        .iter_structs(None, false)
+        .find(|l| l.as_ref().is_ok_and(|l| l.name == "InternalPadding"))
+        .expect("InternalPadding")
+        .expect("parsed");
+    assert_eq!(found.size, 16);
+
+    let mut streamed: Vec<String> =
+        dwarf.iter_structs(None, false).map(|l| l.expect("parsed").name).collect();
+    streamed.sort();
+    streamed.dedup();
+    let mut collected: Vec<String> = dwarf
+        .find_structs(None, false)
+        .expect("Failed to parse structs")
+        .into_iter()
+        .map(|l| l.name)
+        .collect();
+    collected.sort();
+    collected.dedup();
+    assert_eq!(streamed, collected);
+}
+
+#[test]
+fn test_visit_structs_deduplicates_and_stops_early() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let dwarf = DwarfContext::new(&loaded);
+
+    let collected = dwarf.find_structs(None, false).expect("Failed to parse structs");
+    let unique = dwarf.iter_structs(None, false).deduplicated().count();
+    assert_eq!(unique, collected.len());
+
+    let mut visited = Vec::new();
+    dwarf
+        .visit_structs(None, false, |layout| {
+            visited.push(layout.name);
+            if visited.len() == 2 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        })
+        .expect("visit");
+    assert_eq!(visited.len(), 2);
+}
+
+#[test]
+fn test_unit_stats_and_timeout() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+
+    let dwarf = DwarfContext::new(&loaded);
+    let (layouts, stats) = dwarf.find_structs_with_stats(None, false).expect("scan");
+    assert!(!layouts.is_empty());
+    assert!(!stats.units.is_empty());
+    assert_eq!(stats.skipped().count(), 0);
+    assert!(stats.units.iter().map(|u| u.structs).sum::<usize>() >= layouts.len());
+
+    let dwarf = DwarfContext::new(&loaded).with_unit_timeout(Some(Duration::from_nanos(1)));
+    let (layouts, stats) = dwarf.find_structs_with_stats(None, false).expect("scan");
+    assert!(layouts.is_empty());
+    assert_eq!(stats.skipped().count(), stats.units.len());
+    assert!(!stats.out_of_time);
+    let mut streamed = dwarf.iter_structs(None, false);
+    assert_eq!(streamed.by_ref().count(), 0);
+    assert_eq!(streamed.skipped_units().len(), stats.units.len());
+    assert_eq!(streamed.units_read(), stats.units.len());
+
+    let dwarf = DwarfContext::new(&loaded).with_deadline(Some(Instant::now()));
+    let (layouts, stats) = dwarf.find_structs_with_stats(None, false).expect("scan");
+    assert!(layouts.is_empty());
+    assert!(stats.out_of_time);
+    assert!(stats.units.is_empty());
+    let mut streamed = dwarf.iter_structs(None, false);
+    assert_eq!(streamed.by_ref().count(), 0);
+    assert!(streamed.out_of_time());
+}
+
 #[test]
 fn test_tail_padding() {
     let path = match get_fixture_path() {
@@ -207,6 +409,89 @@ fn test_cli_json_output() {
     assert_eq!(structs[0]["name"], "NoPadding");
 }
 
+#[test]
+fn test_cli_jsonl_output() {
+    let Some(path) = get_fixture_path() else {
+        eprintln!("Test fixtures not compiled, skipping");
+        return;
+    };
+    let inspect = |format: &str, extra: &[&str]| {
+        std::process::Command::new("cargo")
+            .args(["run", "--", "inspect"])
+            .arg(&path)
+            .args(["-o", format])
+            .args(extra)
+            .output()
+            .expect("Failed to run CLI")
+    };
+
+    let output = inspect("jsonl", &[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<serde_json::Value> =
+        stdout.lines().map(|l| serde_json::from_str(l).expect("each line is JSON")).collect();
+    let json: serde_json::Value = serde_json::from_slice(&inspect("json", &[]).stdout).unwrap();
+    assert_eq!(lines.len(), json["structs"].as_array().unwrap().len());
+    let padded = lines.iter().find(|l| l["name"] == "InternalPadding").unwrap();
+    assert_eq!(padded["metrics"]["padding_bytes"], 6);
+
+    let output = inspect("jsonl", &["--top", "3"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--top needs every struct"));
+    let output = inspect("jsonl", &["--budget-time", "600"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--budget-time needs every struct"));
+
+    // Units dropped by the unit timeout are named, not silently missing.
+    let output = inspect("jsonl", &["--unit-timeout", "0.000000001"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("(--unit-timeout)"), "{}", stderr);
+    assert!(stderr.contains("Coverage is incomplete"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_csv_output() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let run = |extra: &[&str]| {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "inspect", path.to_str().unwrap(), "-o", "csv"])
+            .args(["--filter", "InternalPadding"])
+            .args(extra)
+            .output()
+            .expect("Failed to run CLI");
+        assert!(output.status.success(), "CLI failed: {}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let structs = run(&[]);
+    let lines: Vec<&str> = structs.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", structs);
+    assert!(lines[0].starts_with("binary,name,size,alignment,"));
+    assert!(lines[1].starts_with(",InternalPadding,16,"), "{}", lines[1]);
+
+    let members = run(&["--csv-members"]);
+    let lines: Vec<&str> = members.lines().collect();
+    assert!(lines[0].starts_with("binary,struct,position,name,type,offset,"));
+    assert!(lines[1..].iter().all(|l| l.starts_with(",InternalPadding,")), "{}", members);
+    assert!(lines.len() > 2);
+
+    let chosen = run(&["--columns", "padding,size"]);
+    let lines: Vec<&str> = chosen.lines().collect();
+    assert_eq!(lines, ["binary,name,padding_bytes,size", ",InternalPadding,6,16"]);
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect", path.to_str().unwrap(), "-o", "csv"])
+        .args(["--csv-members", "--columns", "size"])
+        .output()
+        .expect("Failed to run CLI");
+    assert!(!output.status.success());
+}
+
 #[test]
 fn test_cli_sorting() {
     let path = match get_fixture_path() {
@@ -243,6 +528,65 @@ fn test_cli_sorting() {
     }
 }
 
+#[test]
+fn test_cli_report_profiles() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+    let inspect = |args: &[&str]| {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "inspect", path.to_str().unwrap(), "--no-color"])
+            .args(args)
+            .output()
+            .expect("Failed to run CLI");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let exec = inspect(&["--report-profile", "exec"]);
+    assert!(exec.starts_with("Worst offenders (10 of "), "{}", exec);
+    assert!(exec.contains("WithPointer") && !exec.contains("Offset"), "{}", exec);
+    assert!(exec.contains("\nTotal: "), "{}", exec);
+
+    // Explicit flags override the profile's presets.
+    let exec = inspect(&["--report-profile", "exec", "--top", "1"]);
+    assert!(exec.starts_with("Worst offenders (1 of "), "{}", exec);
+
+    let ci = inspect(&["--report-profile", "ci"]);
+    assert!(ci.starts_with("struct WithPointer"), "{}", ci);
+    assert!(!ci.contains("struct NoPadding"), "{}", ci);
+}
+
+#[test]
+fn test_cli_columns() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+    let dir = tempfile::tempdir().expect("tempdir");
+    let config = dir.path().join("layout-audit.yaml");
+    std::fs::write(&config, "columns: [padding-pct, file]\n").unwrap();
+    let inspect = |args: &[&str]| {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "inspect", path.to_str().unwrap(), "--no-color"])
+            .args(["--config", config.to_str().unwrap()])
+            .args(args)
+            .output()
+            .expect("Failed to run CLI");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let from_config = inspect(&[]);
+    assert!(from_config.contains("Padding % ┆ File"), "{}", from_config);
+    assert!(from_config.contains("test_simple.c"), "{}", from_config);
+
+    let from_flag = inspect(&["--columns", "members,size"]);
+    assert!(from_flag.contains("Members ┆ Size"), "{}", from_flag);
+    assert!(!from_flag.contains("File"), "{}", from_flag);
+}
+
 #[test]
 fn test_cli_top_n() {
     let path = match get_fixture_path() {
@@ -291,6 +635,352 @@ fn test_cli_min_padding_filter() {
     }
 }
 
+#[test]
+fn test_cli_by_file_with_codeowners() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let codeowners = dir.path().join("CODEOWNERS");
+    std::fs::write(&codeowners, "*.c @acme/core\n/tests/fixtures/ @acme/qa\n")
+        .expect("write CODEOWNERS");
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect", path.to_str().unwrap(), "-o", "json", "--by-file"])
+        .arg("--codeowners")
+        .arg(&codeowners)
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(output.status.success(), "CLI failed: {:?}", String::from_utf8_lossy(&output.stderr));
+
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    let structs = parsed["structs"].as_array().unwrap();
+    let files = parsed["files"].as_array().expect("files summary");
+    let counted: u64 = files.iter().map(|f| f["structs"].as_u64().unwrap()).sum();
+    assert_eq!(counted, structs.len() as u64);
+
+    // The fixture's DWARF path is relative, so it is matched as-is; the later rule wins.
+    let owners = parsed["owners"].as_array().expect("owner summary");
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners[0]["owner"], "@acme/qa");
+    assert_eq!(owners[0]["structs"].as_u64(), Some(structs.len() as u64));
+}
+
+#[test]
+fn test_cli_inspect_global_false_sharing() {
+    let (packed, padded) =
+        match (find_fixture_path("test_globals"), find_fixture_path("test_globals_padded")) {
+            (Some(p), Some(q)) => (p, q),
+            _ => return,
+        };
+
+    let pairs = |path: &std::path::Path| -> Vec<(String, String)> {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "inspect", path.to_str().unwrap(), "-o", "json"])
+            .arg("--global-false-sharing")
+            .output()
+            .expect("Failed to run CLI");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+        parsed["global_false_sharing"]
+            .as_array()
+            .expect("global_false_sharing")
+            .iter()
+            .map(|w| {
+                assert_eq!(w["second"]["section"], ".bss");
+                let name = |g: &str| w[g]["name"].as_str().unwrap().to_string();
+                (name("first"), name("second"))
+            })
+            .collect()
+    };
+
+    let packed_pairs = pairs(&packed);
+    assert_eq!(packed_pairs.len(), 6, "{:?}", packed_pairs);
+    assert!(packed_pairs.iter().all(|(a, b)| a != "plain_counter" && b != "plain_counter"));
+    assert!(pairs(&padded).is_empty());
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "diff", padded.to_str().unwrap(), packed.to_str().unwrap()])
+        .args(["--global-false-sharing", "--fail-on-regression"])
+        .output()
+        .expect("Failed to run diff");
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("+ requests and errors"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_atomic_patterns_file() {
+    let Some(path) = find_fixture_path("test_globals") else { return };
+    let dir = tempfile::tempdir().expect("tempdir");
+    let patterns = dir.path().join("atomics.yaml");
+    // Treat plain `long` and `int` as in-house lock words.
+    std::fs::write(&patterns, "regex:\n  - '^long int$'\nnames: [int]\n").unwrap();
+
+    let inspect = |extra: &[&str]| -> serde_json::Value {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "inspect", path.to_str().unwrap(), "-o", "json"])
+            .args(["--filter", "Stats", "--warn-false-sharing", "--global-false-sharing"])
+            .args(extra)
+            .output()
+            .expect("Failed to run CLI");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice(&output.stdout).expect("JSON")
+    };
+    let names = |parsed: &serde_json::Value| -> Vec<String> {
+        let pairs = parsed["global_false_sharing"].as_array().expect("global_false_sharing");
+        pairs
+            .iter()
+            .flat_map(|w| [&w["first"]["name"], &w["second"]["name"]])
+            .map(|n| n.as_str().unwrap().to_string())
+            .collect()
+    };
+    let stats_warnings = |parsed: &serde_json::Value| {
+        parsed["structs"][0]["metrics"]["false_sharing"]["warnings"].as_array().map_or(0, Vec::len)
+    };
+
+    let builtin = inspect(&[]);
+    assert!(!names(&builtin).contains(&"plain_counter".to_string()));
+    assert_eq!(stats_warnings(&builtin), 0);
+
+    let custom = inspect(&["--atomic-patterns-file", patterns.to_str().unwrap()]);
+    assert!(names(&custom).contains(&"plain_counter".to_string()));
+    assert_eq!(stats_warnings(&custom), 1, "{}", custom["structs"][0]);
+}
+
+#[test]
+fn test_cli_suggest_uses_dwarf_alignment() {
+    // gcc -g -o tests/fixtures/bin/test_aligned tests/fixtures/test_aligned.c
+    let Some(path) = find_fixture_path("test_aligned") else { return };
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "suggest", path.to_str().unwrap(), "-o", "json"])
+        .output()
+        .expect("Failed to run CLI");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+
+    let suggestions = parsed["suggestions"].as_array().expect("suggestions");
+    let suggestion = suggestions.iter().find(|s| s["name"] == "OverAligned").expect("OverAligned");
+    let alignments: Vec<(String, u64)> = suggestion["original_members"]
+        .as_array()
+        .expect("original_members")
+        .iter()
+        .map(|m| (m["name"].as_str().unwrap().to_string(), m["alignment"].as_u64().unwrap()))
+        .collect();
+    for (name, align) in [("slot", 64), ("lanes", 16), ("index", 32)] {
+        assert!(alignments.contains(&(name.to_string(), align)), "{:?}", alignments);
+    }
+    let sources = |name: &str| -> Vec<(String, String)> {
+        let suggestion = suggestions.iter().find(|s| s["name"] == name).expect("suggestion");
+        suggestion["original_members"]
+            .as_array()
+            .expect("original_members")
+            .iter()
+            .map(|m| {
+                let source = m["alignment_requirement_source"].as_str().unwrap();
+                (m["name"].as_str().unwrap().to_string(), source.to_string())
+            })
+            .collect()
+    };
+    let over_aligned = sources("OverAligned");
+    for (name, source) in
+        [("tag", "natural"), ("slot", "explicit"), ("lanes", "natural"), ("index", "explicit")]
+    {
+        assert!(over_aligned.contains(&(name.into(), source.into())), "{:?}", over_aligned);
+    }
+    let wire_header = sources("WireHeader");
+    assert!(wire_header.contains(&("length".into(), "packed".into())), "{:?}", wire_header);
+    // Over-aligned members keep the struct on a 64-byte boundary, so no bytes are promised
+    // back by moving them.
+    assert_eq!(suggestion["optimized_size"].as_u64().unwrap() % 64, 0);
+}
+
+#[test]
+fn test_cli_inspect_explicit_json_style() {
+    let Some(path) = find_fixture_path("test_simple") else { return };
+    let inspect = |style: &str| -> serde_json::Value {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "inspect", path.to_str().unwrap(), "-o", "json"])
+            .args(["--filter", "NoPadding", "--json-style", style])
+            .output()
+            .expect("Failed to run CLI");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice(&output.stdout).expect("JSON")
+    };
+
+    let compact = inspect("compact");
+    assert!(compact.get("schema_version").is_none());
+    assert!(compact["structs"][0]["members"][0].get("bit_offset").is_none());
+
+    let explicit = inspect("explicit");
+    assert_eq!(explicit["schema_version"], 1);
+    let member = &explicit["structs"][0]["members"][0];
+    assert!(member["bit_offset"].is_null());
+    assert_eq!(member["is_atomic"], false);
+    assert!(explicit["global_false_sharing"].is_null());
+}
+
+#[test]
+fn test_cli_suggest_keeps_pragma_pack() {
+    let Some(path) = find_fixture_path("test_aligned") else { return };
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "suggest", path.to_str().unwrap(), "-o", "json"])
+        .output()
+        .expect("Failed to run CLI");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+
+    let suggestions = parsed["suggestions"].as_array().expect("suggestions");
+    let header = suggestions.iter().find(|s| s["name"] == "WireHeader").expect("WireHeader");
+    assert_eq!(header["pack"], 2);
+    assert_eq!(header["struct_alignment"], 2);
+    // Under pack(2) the 8-byte member needs no 8-byte slot: 16 bytes shrink to 14.
+    assert_eq!(header["optimized_size"], 14);
+    assert!(header["optimized_members"].as_array().unwrap().iter().all(|m| m["alignment"] != 8));
+    let natural = suggestions.iter().find(|s| s["name"] == "NaturalRecord").expect("natural");
+    assert!(natural.get("pack").is_none());
+
+    let regions = parsed["packed_regions"].as_array().expect("packed_regions");
+    assert_eq!(regions.len(), 1);
+    assert_eq!(regions[0]["pack"], 2);
+    assert_eq!(regions[0]["structs"], serde_json::json!(["WireHeader", "WireTrailer"]));
+}
+
+#[test]
+fn test_cli_check_lint_config() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let lint = |yaml: &str| {
+        let config = dir.path().join("layout-audit.yaml");
+        std::fs::write(&config, yaml).unwrap();
+        std::process::Command::new("cargo")
+            .args(["run", "--", "check", "--lint-config", "-o", "json", "--config"])
+            .arg(&config)
+            .output()
+            .expect("Failed to run CLI")
+    };
+
+    let clean = lint("budgets:\n  Order: { max_size: 64 }\n  \"net::*\": { max_size: 128 }\n");
+    assert!(clean.status.success(), "{}", String::from_utf8_lossy(&clean.stderr));
+
+    let output = lint("budgets:\n  \"*Queue\": { max_size: 64 }\n  \"Rx*\": { max_size: 32 }\n");
+    assert!(!output.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+    let lint = &parsed["lints"][0];
+    assert_eq!(lint["kind"], "overlapping_patterns");
+    assert_eq!(lint["pattern"], "Rx*");
+    assert_eq!(lint["example"], "RxQueue");
+}
+
+#[test]
+fn test_cli_check_validate_config_only() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let validate = |yaml: &str| {
+        let config = dir.path().join("layout-audit.yaml");
+        std::fs::write(&config, yaml).unwrap();
+        std::process::Command::new("cargo")
+            .args(["run", "--", "check", "--validate-config-only", "-o", "json", "--config"])
+            .arg(&config)
+            .output()
+            .expect("Failed to run CLI")
+    };
+
+    let clean = validate("budgets:\n  Order: { max_size: 64, max_padding: 8 }\n");
+    assert!(clean.status.success(), "{}", String::from_utf8_lossy(&clean.stderr));
+
+    let output = validate("budgets:\n  Order:\n    max_size: 64\n    max_paddding: 8\n");
+    assert!(!output.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+    let issue = &parsed["issues"][0];
+    assert_eq!(issue["path"], "budgets.Order.max_paddding");
+    assert_eq!((issue["line"].as_u64(), issue["column"].as_u64()), (Some(4), Some(5)));
+    assert!(issue["message"].as_str().unwrap().contains("did you mean 'max_padding'"));
+}
+
+#[test]
+fn test_cli_annotate_sampled_accesses() {
+    let Some(path) = find_fixture_path("test_globals") else { return };
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let globals = DwarfContext::new(&loaded).global_variables().expect("globals");
+    let address = |name: &str| globals.iter().find(|g| g.name == name).expect(name).address;
+
+    // Addresses as if the binary was loaded 0x10000 above its link-time addresses.
+    let bias = 0x10000;
+    let dir = tempfile::tempdir().expect("tempdir");
+    let samples = dir.path().join("samples.csv");
+    let config = dir.path().join("hot.yaml");
+    std::fs::write(
+        &samples,
+        format!(
+            "address,count\n{:#x},3\n{:#x},5\n{:#x},2\n",
+            address("stats") + bias,
+            address("stats") + 4 + bias,
+            address("requests") + bias
+        ),
+    )
+    .unwrap();
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "annotate", path.to_str().unwrap(), samples.to_str().unwrap()])
+        .args(["--load-bias", "0x10000", "-o", "json", "--emit-config", config.to_str().unwrap()])
+        .output()
+        .expect("Failed to run annotate");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+    assert_eq!(report["samples"], 10);
+    assert_eq!(report["unattributed"], 2);
+    let stats = &report["structs"][0];
+    assert_eq!(stats["name"], "Stats");
+    assert_eq!(stats["members"][0]["hits"], 3);
+    assert_eq!(stats["members"][1]["hits"], 5);
+
+    let hot = std::fs::read_to_string(&config).unwrap();
+    assert!(hot.contains("Stats:\n  - misses\n  - hits"), "{}", hot);
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "suggest", path.to_str().unwrap(), "--filter", "Stats"])
+        .args(["--no-color", "--config", config.to_str().unwrap()])
+        .output()
+        .expect("Failed to run suggest");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Access pattern (misses -> hits)"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_inspect_multiple_binaries() {
+    let (simple, modified) =
+        match (find_fixture_path("test_simple"), find_fixture_path("test_modified")) {
+            (Some(s), Some(m)) => (s, m),
+            _ => return,
+        };
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect", "-o", "json", "--filter", "NoPadding"])
+        .arg(&simple)
+        .arg(&modified)
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(output.status.success(), "CLI failed: {:?}", String::from_utf8_lossy(&output.stderr));
+
+    let parsed: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Invalid JSON output");
+    let structs = parsed["structs"].as_array().unwrap();
+    let mut found: Vec<(String, u64)> = structs
+        .iter()
+        .map(|s| (s["binary"].as_str().unwrap().to_string(), s["size"].as_u64().unwrap()))
+        .collect();
+    found.sort();
+    let mut expected =
+        vec![(simple.display().to_string(), 12), (modified.display().to_string(), 16)];
+    expected.sort();
+    assert_eq!(found, expected);
+}
+
 // ============================================================================
 // Check command tests
 // ============================================================================
@@ -782,15 +1472,58 @@ fn test_suggest_sarif_output() {
 }
 
 #[test]
-fn test_diff_filter() {
+fn test_cli_filter_regex_and_member_filter() {
     let path = match get_fixture_path() {
         Some(p) => p,
         None => return,
     };
 
+    let struct_names = |extra: &[&str]| -> Vec<String> {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--", "inspect", path.to_str().unwrap(), "-o", "json"])
+            .args(extra)
+            .output()
+            .expect("Failed to run CLI");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let parsed: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("Invalid JSON");
+        let mut names: Vec<String> = parsed["structs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap().to_string())
+            .collect();
+        names.sort();
+        names
+    };
+
+    assert_eq!(struct_names(&["--filter-regex", "^(Inner|Outer)$"]), ["Inner", "Outer"]);
+    // Only Outer holds a member of type Inner.
+    assert_eq!(struct_names(&["--member-filter", "^Inner$"]), ["Outer"]);
+    // Member names match as well as member types.
+    assert_eq!(
+        struct_names(&["--filter-regex", "Padding$", "--member-filter", "^d$"]),
+        ["InternalPadding"]
+    );
+
     let output = std::process::Command::new("cargo")
-        .args([
-            "run",
+        .args(["run", "--", "inspect", path.to_str().unwrap(), "--member-filter", "("])
+        .output()
+        .expect("Failed to run CLI");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid --member-filter"));
+}
+
+#[test]
+fn test_diff_filter() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
             "--",
             "diff",
             path.to_str().unwrap(),
@@ -891,6 +1624,50 @@ fn test_diff_detects_added_structs() {
     assert!(added.iter().any(|s| s["name"] == "NewStruct"), "Should detect NewStruct as added");
 }
 
+#[test]
+fn test_diff_against_snapshot_baseline() {
+    let old_path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+    let new_path = match get_modified_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let snapshot = dir.path().join("baseline.json");
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "snapshot", old_path.to_str().unwrap(), "-o"])
+        .arg(&snapshot)
+        .output()
+        .expect("Failed to run snapshot command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "diff", "--baseline"])
+        .arg(&snapshot)
+        .args([new_path.to_str().unwrap(), "-o", "json"])
+        .output()
+        .expect("Failed to run diff command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Invalid JSON");
+    let added = parsed["added"].as_array().unwrap();
+    assert!(added.iter().any(|s| s["name"] == "NewStruct"), "Should detect NewStruct as added");
+    let removed = parsed["removed"].as_array().unwrap();
+    assert!(removed.iter().any(|s| s["name"] == "Inner"), "Should detect Inner as removed");
+
+    // A snapshot replaces OLD rather than adding a third side.
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "diff", "--baseline"])
+        .arg(&snapshot)
+        .args([old_path.to_str().unwrap(), new_path.to_str().unwrap()])
+        .output()
+        .expect("Failed to run diff command");
+    assert!(!output.status.success());
+}
+
 #[test]
 fn test_diff_detects_removed_structs() {
     let old_path = match get_fixture_path() {
@@ -1400,6 +2177,67 @@ fn test_suggest_basic() {
     assert!(suggestion["savings_bytes"].as_u64().unwrap() > 0, "Should have savings");
 }
 
+#[test]
+fn test_suggest_emit_code() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "suggest",
+            path.to_str().unwrap(),
+            "--filter",
+            "InternalPadding",
+            "--emit-code",
+            "rust",
+        ])
+        .output()
+        .expect("Failed to run suggest command");
+
+    assert!(
+        output.status.success(),
+        "Suggest should succeed: {:?}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("#[repr(C)]\npub struct InternalPadding {\n"));
+    assert!(stdout.contains("    pub b: i32,\n    pub d: i32,\n"));
+    assert!(stdout.contains("    _pad0: [u8; 2],\n}\n"));
+}
+
+#[test]
+fn test_suggest_locality_orderings() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.yaml");
+    std::fs::write(&config, "field_groups:\n  InternalPadding: [[a, d], [b, c]]\n").unwrap();
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "suggest", path.to_str().unwrap(), "--filter", "InternalPadding"])
+        .args(["--locality", "-c", config.to_str().unwrap(), "-o", "json"])
+        .output()
+        .expect("Failed to run suggest command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Invalid JSON");
+    let front = &parsed["suggestions"][0]["locality"];
+    assert_eq!(front["size_cap"], 16);
+    assert_eq!(front["groups_from_prefixes"], false);
+    // Pairing each int with a char still packs into 12 bytes.
+    let orderings = front["orderings"].as_array().unwrap();
+    assert_eq!(orderings.len(), 1);
+    assert_eq!(orderings[0]["size"], 12);
+    assert_eq!(orderings[0]["grouping_score"], 1.0);
+}
+
 #[test]
 fn test_suggest_no_savings_struct() {
     let path = match get_fixture_path() {
@@ -1612,6 +2450,115 @@ fn test_cpp_simple_template() {
     assert_eq!(value_member["type_name"], "int", "Template member should have resolved type");
 }
 
+#[test]
+fn test_cpp_static_members_excluded_from_layout() {
+    // DWARF 4 describes static data members as DW_TAG_member declarations without an offset.
+    let path = match find_fixture_path("test_cpp_templates_dwarf4") {
+        Some(p) => p,
+        None => return,
+    };
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let mut layouts = DwarfContext::new(&loaded)
+        .find_structs(Some("TypeInfo<int>"), false)
+        .expect("Failed to parse structs");
+    let layout = &mut layouts[0];
+    analyze_layout(layout, 64);
+
+    let members: Vec<&str> = layout.members.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(members, vec!["name", "runtime_size"]);
+    let statics: Vec<&str> = layout.static_members.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(statics, vec!["size", "align"]);
+    assert!(!layout.metrics.partial);
+    assert_eq!(layout.metrics.padding_bytes, 0);
+}
+
+#[test]
+fn test_cpp_flatten_bases() {
+    let path = match get_cpp_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "inspect",
+            path.to_str().unwrap(),
+            "--filter",
+            "TrackedValue<int>",
+            "--flatten-bases",
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("Failed to run inspect");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let parsed: serde_json::Value = serde_json::from_str(&stdout).expect("Invalid JSON");
+    let tracked = &parsed["structs"][0];
+    let members: Vec<(&str, u64)> = tracked["members"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| (m["name"].as_str().unwrap(), m["offset"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            ("BaseMetrics::created_at", 0),
+            ("BaseMetrics::updated_at", 8),
+            ("value", 16),
+            ("version", 20),
+        ]
+    );
+    // Base attribution is still reported for the flattened view.
+    assert_eq!(tracked["metrics"]["bases"][0]["type_name"], "BaseMetrics");
+}
+
+#[test]
+fn test_cpp_object_model_members() {
+    let path = match get_cpp_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let mut layouts =
+        DwarfContext::new(&loaded).find_structs(None, false).expect("Failed to parse");
+    for layout in &mut layouts {
+        analyze_layout(layout, 64);
+    }
+    let find = |name: &str| layouts.iter().find(|l| l.name == name).expect(name);
+    let member = |layout: &StructLayout, prefix: &str| {
+        layout.members.iter().find(|m| m.name.starts_with(prefix)).cloned().expect(prefix)
+    };
+
+    let vptr = member(find("Shape"), "_vptr");
+    assert_eq!((vptr.offset, vptr.role), (Some(0), Some(MemberRole::VtablePointer)));
+
+    let with_empty = find("WithEmptyBase");
+    let empty = member(with_empty, "<base: EmptyPolicy>");
+    assert_eq!((empty.size, empty.role), (Some(0), Some(MemberRole::EmptyBase)));
+    assert_eq!(with_empty.metrics.useful_size, 8);
+
+    // The only virtual base follows the non-virtual part; the offset is inferred.
+    let left = find("LeftPath");
+    let shared = member(left, "<virtual base: SharedState>");
+    assert_eq!((shared.offset, shared.role), (Some(16), Some(MemberRole::VirtualBase)));
+    assert!(!left.metrics.partial);
+    assert!(analyze_bases(left)[0].is_virtual);
+
+    let method = member(find("MethodHolder"), "method");
+    assert_eq!(method.type_name, "Shape::*fn(...)");
+    assert_eq!(method.size, Some(16));
+}
+
 #[test]
 fn test_cpp_nested_templates() {
     let path = match get_cpp_fixture_path() {
@@ -1644,6 +2591,69 @@ fn test_cpp_nested_templates() {
     assert!(complex.is_some(), "Should find MapEntry with nested Vector template");
 }
 
+#[test]
+fn test_cpp_collapse_templates() {
+    let path = match get_cpp_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "inspect",
+            path.to_str().unwrap(),
+            "--filter",
+            "Container",
+            "--collapse-templates",
+            "-o",
+            "json",
+        ])
+        .output()
+        .expect("Failed to run inspect");
+
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let parsed: serde_json::Value = serde_json::from_str(&stdout).expect("Invalid JSON");
+    let structs = parsed["structs"].as_array().unwrap();
+    assert!(structs.iter().all(|s| !s["name"].as_str().unwrap().starts_with("Container<")));
+
+    let templates = parsed["templates"].as_array().unwrap();
+    let container = templates.iter().find(|t| t["template"] == "Container<>").unwrap();
+    assert_eq!(container["instantiations"], 3);
+    assert!(container["min_size"].as_u64() <= container["max_size"].as_u64());
+}
+
+#[test]
+fn test_budget_time_exit_status() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let inspect = |extra: &[&str]| {
+        std::process::Command::new("cargo")
+            .args(["run", "--", "inspect", path.to_str().unwrap(), "-o", "json"])
+            .args(extra)
+            .output()
+            .expect("Failed to run inspect")
+    };
+
+    let output = inspect(&["--budget-time", "0.000001"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("exceeded --budget-time"), "{}", stderr);
+
+    let output = inspect(&["--budget-time", "0.000001", "--emit-partial-results"]);
+    assert_eq!(output.status.code(), Some(3));
+
+    let output = inspect(&["--budget-time", "600"]);
+    assert!(output.status.success());
+}
+
 #[test]
 fn test_cpp_template_padding_detection() {
     let path = match get_cpp_fixture_path() {
@@ -1961,6 +2971,32 @@ fn test_go_suggest_command() {
     );
 }
 
+#[test]
+fn test_go_emit_assertions() {
+    let path = match get_go_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "emit-assertions", path.to_str().unwrap(), "--go-package", "main"])
+        .output()
+        .expect("Failed to run emit-assertions on Go binary");
+
+    assert!(
+        output.status.success(),
+        "emit-assertions should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("package main\n"));
+    assert!(stdout.contains("[unsafe.Sizeof(PoorlyAligned{})-24]"));
+    assert!(stdout.contains("[unsafe.Offsetof(PoorlyAligned{}.bigValue)-8]"));
+    assert!(stdout.contains("[unsafe.Offsetof(PoorlyAligned{}.medium)-20]"));
+    assert!(!stdout.contains("runtime."), "only package main structs are asserted on");
+}
+
 #[test]
 fn test_go_diff_command() {
     let path = match get_go_fixture_path() {
@@ -2084,3 +3120,723 @@ fn test_go_well_aligned_vs_poorly_aligned() {
         poorly[0].metrics.padding_bytes
     );
 }
+
+#[test]
+fn test_exported_structs() {
+    // Shared library fixture, only built on Linux (see tests/fixtures/test_exports.c).
+    let Some(path) = find_fixture_path("libtest_exports.so") else {
+        eprintln!("Shared library fixture not compiled, skipping");
+        return;
+    };
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let exports = binary.exported_symbols().expect("Failed to read exports");
+    assert!(exports.contains("handle_request"));
+    assert!(!exports.contains("hidden_helper"));
+
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let dwarf = DwarfContext::new(&loaded);
+    let reachable = dwarf.exported_structs(&exports).expect("Failed to walk exports");
+    let layouts = dwarf.find_structs(None, false).expect("Failed to find structs");
+    let is_reachable = |name: &str| {
+        let layout = layouts.iter().find(|l| l.name == name).expect("struct present");
+        reachable.contains(layout)
+    };
+
+    // Parameters (through a pointer), nested members, and return types are reachable...
+    for name in ["Request", "Inner", "Response"] {
+        assert!(is_reachable(name), "{} should be reachable", name);
+    }
+    // ...but types used only by hidden functions or inside function bodies are not.
+    assert!(!is_reachable("Hidden"));
+    assert!(!is_reachable("Local"));
+}
+
+#[test]
+fn test_static_access_popularity() {
+    // Optimized fixture, only built on Linux (see tests/fixtures/test_static_access.c).
+    let Some(path) = find_fixture_path("test_static_access") else {
+        eprintln!("Static access fixture not compiled, skipping");
+        return;
+    };
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let dwarf = DwarfContext::new(&loaded);
+    let mut layouts = dwarf.find_structs(Some("Pair"), false).expect("Failed to find structs");
+    dwarf.annotate_static_popularity(&mut layouts).expect("Failed to scan subprograms");
+
+    let pair = layouts.iter().find(|l| l.name == "Pair").expect("Pair not found");
+    assert!(pair.members.iter().all(|m| m.static_popularity.is_some()));
+    let hot = pair.members.iter().find(|m| m.name == "hot").unwrap();
+    assert!(hot.static_popularity.unwrap() >= 1, "hot should be live in some function");
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "suggest"])
+        .arg(&path)
+        .args(["--filter", "Pair", "--static-access", "--no-color"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Static access pattern ("));
+}
+
+#[test]
+fn test_struct_usage() {
+    let (Some(simple), Some(globals)) = (get_fixture_path(), find_fixture_path("test_globals"))
+    else {
+        eprintln!("Test fixtures not compiled, skipping");
+        return;
+    };
+    let usage = |path: &std::path::Path, name: &str| {
+        let binary = BinaryData::load(path).expect("Failed to load binary");
+        let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+        let dwarf = DwarfContext::new(&loaded);
+        let mut layouts = dwarf.find_structs(Some(name), false).expect("Failed to find structs");
+        dwarf.annotate_usage(&mut layouts).expect("Failed to count usage");
+        layouts.iter().find(|l| l.name == name).expect(name).usage.expect("usage")
+    };
+
+    // Inner is only a member of Outer; main declares a local of every other struct.
+    let inner = usage(&simple, "Inner");
+    assert_eq!((inner.functions, inner.types, inner.variables), (0, 1, 0));
+    assert_eq!(usage(&simple, "Outer").functions, 1);
+    let stats = usage(&globals, "Stats");
+    assert_eq!((stats.variables, stats.impact), (1, 1));
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect"])
+        .arg(&simple)
+        .args(["--sort-by", "impact", "-o", "json"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let structs = json["structs"].as_array().unwrap();
+    assert!(structs.iter().all(|s| s["usage"]["impact"].is_u64()));
+}
+
+#[test]
+fn test_binary_summary() {
+    let (Some(simple), Some(globals)) = (get_fixture_path(), find_fixture_path("test_globals"))
+    else {
+        eprintln!("Test fixtures not compiled, skipping");
+        return;
+    };
+    let summary = |args: &[&str]| {
+        let output = std::process::Command::new("cargo")
+            .args(["run", "--"])
+            .args(args)
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let simple = simple.to_str().unwrap();
+    let json = summary(&["summary", simple, "-o", "json"]);
+    let totals = &json["summary"];
+    let histogram = totals["histogram"].as_array().unwrap();
+    let bucketed: u64 = histogram.iter().map(|b| b["structs"].as_u64().unwrap()).sum();
+    assert_eq!(bucketed, totals["structs"].as_u64().unwrap());
+    let padding: u64 = histogram.iter().map(|b| b["padding_bytes"].as_u64().unwrap()).sum();
+    assert_eq!(padding, totals["padding_bytes"].as_u64().unwrap());
+    let offenders: Vec<u64> = totals["top_offenders"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["padding_bytes"].as_u64().unwrap())
+        .collect();
+    assert!(!offenders.is_empty() && offenders.len() <= 10);
+    assert!(offenders.windows(2).all(|w| w[0] >= w[1]));
+
+    // The summary covers every struct, not only the --top ones listed.
+    let inspect = summary(&["inspect", simple, "--summary", "--top", "1", "-o", "json"]);
+    assert_eq!(inspect["structs"].as_array().unwrap().len(), 1);
+    assert_eq!(inspect["summary"], json["summary"]);
+
+    // Stats has one global instance.
+    let json = summary(&["summary", globals.to_str().unwrap(), "-o", "json"]);
+    assert_eq!(json["summary"]["static_instances"], 1);
+}
+
+#[test]
+fn test_static_instance_weighting() {
+    let Some(path) = find_fixture_path("test_static_instances") else {
+        eprintln!("Static instances fixture not compiled, skipping");
+        return;
+    };
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect"])
+        .arg(&path)
+        .args(["--sort-by", "weighted-waste", "-o", "json"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let weighted: Vec<(&str, u64, u64)> = json["structs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| {
+            let metrics = &s["metrics"];
+            (
+                s["name"].as_str().unwrap(),
+                metrics["static_instances"].as_u64().unwrap(),
+                metrics["weighted_waste_bytes"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    // A static array of 1000 and a const one of 4 (in .rodata); the pointer stores none.
+    assert_eq!(weighted, vec![("Entry", 1004, 1004 * 7), ("Limits", 1, 7)]);
+}
+
+#[test]
+fn test_locate_offset() {
+    let Some(path) = get_fixture_path() else {
+        eprintln!("Test fixtures not compiled, skipping");
+        return;
+    };
+    let locate = |type_name: &str, offset: &str| {
+        std::process::Command::new("cargo")
+            .args(["run", "--", "locate"])
+            .arg(&path)
+            .args([type_name, offset, "-o", "json"])
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = locate("Outer", "0x6");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["field_path"], "inner.x");
+    assert_eq!(json["path"][1]["parent"], "Inner");
+    assert_eq!(json["path"][1]["offset"], 4);
+
+    let output = locate("WithPointer", "3");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["field_path"], "<padding>");
+    assert_eq!(json["padding"]["offset"], 1);
+    assert_eq!(json["padding"]["size"], 7);
+    assert_eq!(json["padding"]["after_member"], "tag");
+
+    let output = locate("WithPointer", "24");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("past the end of WithPointer"));
+}
+
+#[test]
+fn test_graph() {
+    let Some(path) = get_fixture_path() else {
+        eprintln!("Test fixtures not compiled, skipping");
+        return;
+    };
+    let graph = |format: &str| {
+        std::process::Command::new("cargo")
+            .args(["run", "--", "graph"])
+            .arg(&path)
+            .args(["--format", format])
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = graph("dot");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let dot = String::from_utf8_lossy(&output.stdout);
+    assert!(dot.starts_with("digraph containment {"));
+    assert!(dot.contains("\"Outer\" -> \"Inner\" [label=\"inner\"];"));
+
+    let output = graph("json");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let inner = json["nodes"].as_array().unwrap().iter().find(|n| n["name"] == "Inner").unwrap();
+    assert_eq!(inner["embedded_copies"], 1);
+    assert_eq!(json["edges"][0]["count"], 1);
+    // Structs embedding nothing and embedded nowhere are left out.
+    assert!(json["nodes"].as_array().unwrap().iter().all(|n| n["name"] != "NoPadding"));
+}
+
+#[test]
+fn test_emit_header() {
+    let Some(path) = get_fixture_path() else {
+        eprintln!("Test fixtures not compiled, skipping");
+        return;
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let header = dir.path().join("layout.h");
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "emit-header"])
+        .arg(&path)
+        .arg("--out")
+        .arg(&header)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let header = std::fs::read_to_string(header).unwrap();
+    assert!(header.contains("#ifndef LAYOUT_H\n#define LAYOUT_H\n"));
+    assert!(header.contains(
+        "struct WithPointer {\n    char tag;\n    char _pad0[7];\n    void *ptr;\n    int value;\n    \
+         char _pad1[4];\n};\n"
+    ));
+    assert!(header.contains(
+        "_Static_assert(offsetof(struct WithPointer, ptr) == 8, \"WithPointer: ptr is at offset 8\");"
+    ));
+    // Inner is defined before Outer, which embeds it.
+    assert!(header.find("struct Inner {").unwrap() < header.find("struct Outer {").unwrap());
+    assert!(header.contains("    Inner inner;\n"));
+}
+
+#[test]
+fn test_anonymous_aggregates_flattened() {
+    let Some(path) = find_fixture_path("test_anonymous") else {
+        eprintln!("Anonymous aggregates fixture not compiled, skipping");
+        return;
+    };
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect"])
+        .arg(&path)
+        .args(["--filter", "Message", "-o", "json"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let message = &json["structs"][0];
+    let members: Vec<(&str, u64)> = message["members"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| (m["name"].as_str().unwrap(), m["offset"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            ("kind", 0),
+            ("code", 8),
+            ("ratio", 8),
+            ("lo", 16),
+            ("hi", 24),
+            ("u.bytes", 32),
+            ("u.word", 32)
+        ]
+    );
+    assert_eq!(message["members"][5]["aggregate"], "u");
+    // Union members overlap rather than count twice: 7 + 6 + 4 bytes of padding.
+    assert_eq!(message["metrics"]["padding_bytes"], 17);
+}
+
+#[test]
+fn test_verify_determinism() {
+    let (Some(simple), Some(modified)) = (get_fixture_path(), find_fixture_path("test_modified"))
+    else {
+        eprintln!("Test fixtures not compiled, skipping");
+        return;
+    };
+    let verify = |binaries: &[&std::path::Path]| {
+        std::process::Command::new("cargo")
+            .args(["run", "--", "verify-determinism", "-o", "json", "--runs", "3"])
+            .args(binaries)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    // Analyzing one binary repeatedly gives the same layouts every time.
+    let output = verify(&[&simple]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["deterministic"], true);
+    assert_eq!(json["runs"].as_array().unwrap().len(), 2);
+
+    let output = verify(&[&simple, &modified]);
+    assert!(!output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["deterministic"], false);
+    let differences = json["runs"][0]["differences"].as_array().unwrap();
+    let internal = differences.iter().find(|d| d["name"] == "InternalPadding").unwrap();
+    assert_eq!(internal["kind"], "conditional_members");
+}
+
+#[test]
+fn test_compare_targets() {
+    // gcc -g -o tests/fixtures/bin/test_targets_x86_64 tests/fixtures/test_targets.c
+    // gcc -m32 -g -c -o tests/fixtures/bin/test_targets_i386 tests/fixtures/test_targets.c
+    let (Some(x86_64), Some(i386)) =
+        (find_fixture_path("test_targets_x86_64"), find_fixture_path("test_targets_i386"))
+    else {
+        eprintln!("Cross-target fixtures not compiled, skipping");
+        return;
+    };
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "compare-targets", "-o", "json"])
+        .args([&x86_64, &i386])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json["new_target"].as_str().unwrap().contains("i686"), "{}", json["new_target"]);
+    assert_eq!(json["unchanged_count"], 1);
+
+    let differences: Vec<(&str, u64, u64, &str)> = json["differences"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| {
+            let size = |key: &str| d[key].as_u64().unwrap();
+            let cause = d["causes"][0].as_str().unwrap();
+            (d["name"].as_str().unwrap(), size("old_size"), size("new_size"), cause)
+        })
+        .collect();
+    assert_eq!(
+        differences,
+        vec![
+            ("Extended", 32, 16, "long_double"),
+            ("ListNode", 16, 8, "pointer_width"),
+            ("Sample", 16, 12, "alignment"),
+        ]
+    );
+    let sample = &json["differences"][2];
+    assert_eq!(
+        (sample["old_alignment"].as_u64(), sample["new_alignment"].as_u64()),
+        (Some(8), Some(4))
+    );
+}
+
+#[test]
+fn test_rust_enum_variants() {
+    let Some(path) = find_fixture_path("test_rust_enums") else {
+        eprintln!("Rust enum fixture not compiled, skipping");
+        return;
+    };
+
+    let binary = BinaryData::load(&path).expect("Failed to load binary");
+    let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+    let dwarf = DwarfContext::new(&loaded);
+    let layouts = dwarf.find_structs(None, false).expect("Failed to find structs");
+    let enum_layout = |name: &str| {
+        let layout = layouts.iter().find(|l| l.name == name).expect(name);
+        layout.enum_layout.clone().expect("enum layout")
+    };
+
+    let shape = enum_layout("Shape");
+    assert!(!shape.niche);
+    assert!(shape.tag_bytes() > 0);
+    let variants: Vec<(&str, Option<i128>)> =
+        shape.variants.iter().map(|v| (v.name.as_str(), v.discriminant_value)).collect();
+    assert_eq!(variants, vec![("Empty", Some(0)), ("Circle", Some(1)), ("Rect", Some(2))]);
+    assert_eq!(shape.largest_variant().map(|v| v.payload_size), Some(16));
+
+    // A null reference and the out-of-range values of a bool encode the dataless variants.
+    for name in ["MaybeRef", "Tri"] {
+        let niche = enum_layout(name);
+        assert!(niche.niche, "{} should use a niche", name);
+        assert_eq!(niche.tag_bytes(), 0);
+        assert!(niche.variants.iter().any(|v| v.discriminant_value.is_none()));
+    }
+
+    let plain = layouts.iter().find(|l| l.name == "Rect").expect("variant payload struct");
+    assert!(plain.enum_layout.is_none());
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect"])
+        .arg(&path)
+        .args(["--filter", "Shape", "--no-color"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Enum: 3 variants"), "{}", stdout);
+    assert!(stdout.contains("(largest)"));
+}
+
+#[test]
+fn test_cli_verify_rustc_type_sizes() {
+    let Some(path) = find_fixture_path("test_rust_enums") else { return };
+
+    // As printed by `rustc -Zprint-type-sizes tests/fixtures/test_rust_enums.rs`.
+    let sizes = "\
+print-type-size type: `Shape`: 24 bytes, alignment: 8 bytes
+print-type-size     discriminant: 4 bytes
+print-type-size     variant `Rect`: 20 bytes
+print-type-size         padding: 4 bytes
+print-type-size         field `.w`: 8 bytes, alignment: 8 bytes
+print-type-size         field `.h`: 8 bytes
+print-type-size     variant `Circle`: 4 bytes
+print-type-size         field `.0`: 4 bytes
+print-type-size     variant `Empty`: 0 bytes
+print-type-size type: `MaybeRef<'_>`: 8 bytes, alignment: 8 bytes
+print-type-size     variant `Just`: 8 bytes
+print-type-size         field `.0`: 8 bytes
+print-type-size     variant `Nothing`: 0 bytes
+";
+    let dir = tempfile::tempdir().expect("tempdir");
+    let run = |text: &str| {
+        let file = dir.path().join("type-sizes.txt");
+        std::fs::write(&file, text).unwrap();
+        std::process::Command::new("cargo")
+            .args(["run", "--", "verify", path.to_str().unwrap(), "--rustc-sizes"])
+            .args([file.to_str().unwrap(), "-o", "json"])
+            .output()
+            .expect("Failed to run verify")
+    };
+
+    let output = run(sizes);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+    assert_eq!(report["checked"], 2);
+
+    let output = run(&sizes.replace("padding: 4 bytes", "padding: 8 bytes"));
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+    let mismatch = &report["mismatches"][0];
+    assert_eq!(mismatch["member"], "Rect.w");
+    assert_eq!(mismatch["kind"], "offset");
+    assert_eq!(mismatch["rustc"], 12);
+    assert_eq!(mismatch["dwarf"], 8);
+}
+
+#[test]
+fn test_cli_forecast_fleet_waste() {
+    let Some(old) = find_fixture_path("test_simple") else { return };
+    let Some(new) = find_fixture_path("test_modified") else { return };
+    let dir = tempfile::tempdir().expect("tempdir");
+    let snapshot = dir.path().join("old.json");
+    let status = std::process::Command::new("cargo")
+        .args(["run", "--", "snapshot", old.to_str().unwrap(), "-o", snapshot.to_str().unwrap()])
+        .status()
+        .expect("Failed to run snapshot");
+    assert!(status.success());
+    let instances = dir.path().join("instances.yaml");
+    std::fs::write(&instances, "InternalPadding: 1000\nNoPadding: 50\nMissing: 3\n").unwrap();
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "forecast", new.to_str().unwrap(), "--instances"])
+        .args([instances.to_str().unwrap(), "--history", snapshot.to_str().unwrap()])
+        .args(["--top", "1", "--horizon", "2", "-o", "json"])
+        .output()
+        .expect("Failed to run forecast");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let forecast: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+    let structs = forecast["structs"].as_array().unwrap();
+    assert_eq!(structs[0]["name"], "InternalPadding");
+    let waste = structs[0]["waste_bytes"].as_u64().unwrap();
+    assert_eq!(waste, structs[0]["padding_bytes"].as_u64().unwrap() * 1000);
+    assert_eq!(forecast["top_recoverable_bytes"], structs[0]["recoverable_bytes"]);
+    assert_eq!(forecast["unmatched"], serde_json::json!(["Missing"]));
+
+    let trend = &forecast["trend"];
+    assert_eq!(trend["points"].as_array().unwrap().len(), 2);
+    assert_eq!(trend["points"][1]["waste_bytes"], forecast["total_waste_bytes"]);
+    let slope = trend["bytes_per_snapshot"].as_i64().unwrap();
+    let current = forecast["total_waste_bytes"].as_i64().unwrap();
+    assert_eq!(trend["projected_waste_bytes"].as_i64().unwrap(), (current + 2 * slope).max(0));
+}
+
+#[test]
+fn test_cli_inspect_by_namespace() {
+    let Some(path) = find_fixture_path("test_rust_enums") else { return };
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect", path.to_str().unwrap(), "--by-namespace", "-o", "json"])
+        .output()
+        .expect("Failed to run CLI");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("JSON");
+    let shape =
+        parsed["structs"].as_array().unwrap().iter().find(|s| s["name"] == "Shape").expect("Shape");
+    assert_eq!(shape["namespace"], "test_rust_enums");
+
+    let namespaces = parsed["namespaces"].as_array().unwrap();
+    let fixture = namespaces
+        .iter()
+        .find(|n| n["namespace"] == "test_rust_enums")
+        .expect("fixture crate namespace");
+    assert!(fixture["structs"].as_u64().unwrap() >= 1);
+    let total: u64 = namespaces.iter().map(|n| n["structs"].as_u64().unwrap()).sum();
+    assert_eq!(total as usize, parsed["structs"].as_array().unwrap().len());
+}
+
+#[test]
+fn test_sort_stable_outputs_are_byte_identical() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let run = |format: &str| {
+        let output = std::process::Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "inspect",
+                path.to_str().unwrap(),
+                "-o",
+                format,
+                "--sort-by",
+                "size",
+                "--sort-stable",
+                "--no-color",
+            ])
+            .output()
+            .expect("Failed to run inspect command");
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        output.stdout
+    };
+
+    for format in ["table", "json", "sarif"] {
+        assert_eq!(run(format), run(format), "{} output differs between runs", format);
+    }
+
+    // Table, JSON, and SARIF list structs in the same order.
+    let json: serde_json::Value = serde_json::from_slice(&run("json")).expect("Invalid JSON");
+    let json_order: Vec<&str> =
+        json["structs"].as_array().unwrap().iter().map(|s| s["name"].as_str().unwrap()).collect();
+    let table = String::from_utf8(run("table")).unwrap();
+    let table_order: Vec<&str> = table
+        .lines()
+        .filter_map(|l| l.strip_prefix("struct "))
+        .map(|l| l.split(" (").next().unwrap())
+        .collect();
+    assert_eq!(json_order, table_order);
+
+    let sarif: serde_json::Value = serde_json::from_slice(&run("sarif")).expect("Invalid SARIF");
+    let sarif_order: Vec<&str> = sarif["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|r| r["ruleId"] == "LAYOUT-PADDING")
+        .map(|r| r["properties"]["struct"].as_str().unwrap())
+        .collect();
+    let padded: Vec<&str> = json["structs"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|s| s["metrics"]["padding_bytes"].as_u64().unwrap() > 0)
+        .map(|s| s["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(sarif_order, padded);
+}
+
+#[test]
+fn test_repro_debug_object_reproduces_layouts() {
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+
+    let layouts_of = |path: &std::path::Path| {
+        let binary = BinaryData::load(path).expect("Failed to load binary");
+        let loaded = binary.load_dwarf().expect("Failed to load DWARF");
+        let dwarf = DwarfContext::new(&loaded);
+        let mut layouts = dwarf.find_structs(None, false).expect("Failed to parse structs");
+        for layout in &mut layouts {
+            analyze_layout(layout, 64);
+        }
+        layout_audit::JsonFormatter::new(false).format(&layouts)
+    };
+
+    let original = BinaryData::load(&path).expect("Failed to load binary");
+    let dir = tempfile::tempdir().unwrap();
+
+    let plain = dir.path().join("plain.elf");
+    std::fs::write(&plain, layout_audit::extract_debug_object(&original.mmap, false).unwrap())
+        .unwrap();
+    assert!(std::fs::metadata(&plain).unwrap().len() < original.mmap.len() as u64);
+    assert_eq!(layouts_of(&plain), layouts_of(&path));
+
+    // Redaction changes out-of-line names but keeps the layouts themselves.
+    let redacted = dir.path().join("redacted.elf");
+    std::fs::write(&redacted, layout_audit::extract_debug_object(&original.mmap, true).unwrap())
+        .unwrap();
+    let redacted_json = layouts_of(&redacted);
+    assert!(!redacted_json.contains("InternalPadding"));
+    let sizes = |json: &str| {
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        let mut sizes: Vec<u64> = value["structs"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["size"].as_u64().unwrap())
+            .collect();
+        sizes.sort();
+        sizes
+    };
+    assert_eq!(sizes(&redacted_json), sizes(&layouts_of(&path)));
+}
+
+#[test]
+fn test_inspect_writes_repro_bundle() {
+    use std::io::Read;
+
+    let path = match get_fixture_path() {
+        Some(p) => p,
+        None => return,
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let bundle = dir.path().join("repro.tar.zst");
+
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "inspect", path.to_str().unwrap(), "--repro-bundle"])
+        .arg(&bundle)
+        .output()
+        .expect("Failed to run inspect command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let compressed = std::fs::File::open(&bundle).unwrap();
+    let mut decoder = ruzstd::decoding::StreamingDecoder::new(compressed).unwrap();
+    let mut tar = Vec::new();
+    decoder.read_to_end(&mut tar).unwrap();
+
+    let mut names = Vec::new();
+    let mut pos = 0;
+    while pos + 512 <= tar.len() && tar[pos] != 0 {
+        let header = &tar[pos..pos + 512];
+        let name_len = header[..100].iter().position(|&b| b == 0).unwrap();
+        names.push(String::from_utf8_lossy(&header[..name_len]).into_owned());
+        let size = usize::from_str_radix(std::str::from_utf8(&header[124..135]).unwrap(), 8);
+        pos += 512 + size.unwrap().next_multiple_of(512);
+    }
+    assert_eq!(names, vec!["manifest.json", "debug.elf", "output.json"]);
+}
+
+#[cfg(feature = "test-support")]
+#[test]
+fn test_golden_render_is_portable() {
+    use layout_audit::golden::{Golden, assert_golden};
+
+    let Some(path) = find_fixture_path("test_simple") else { return };
+    let text = Golden::new()
+        .render_binary(&path, &["InternalPadding", "WithPointer"])
+        .expect("render fixture");
+
+    assert!(text.starts_with(
+        "struct InternalPadding: size 16, padding 6 (test_simple.c:11)\n  @0 a: char (1)\n  @4 b"
+    ));
+    assert!(text.contains("struct WithPointer: size ?, padding ? (test_simple.c:50)\n"));
+    assert!(text.contains("  @? ptr: *void (ptr)\n  @? value: int (4)\n"));
+    assert!(Golden::new().render_binary(&path, &["NoSuchStruct"]).is_err());
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let golden = dir.path().join("simple.txt");
+    std::fs::write(&golden, &text).unwrap();
+    assert_golden(&golden, &text);
+}
+
+#[test]
+fn test_tui_requires_a_terminal() {
+    let Some(path) = get_fixture_path() else {
+        eprintln!("Test fixture not compiled, skipping");
+        return;
+    };
+
+    // Output is piped here, so the explorer must refuse to start rather than take over it.
+    let output = std::process::Command::new("cargo")
+        .args(["run", "--", "tui"])
+        .arg(&path)
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("interactive terminal"));
+}